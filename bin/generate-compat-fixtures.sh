@@ -7,3 +7,5 @@ ORACLE_DIR="$ROOT_DIR/tools/oracle-node"
 cd "$ORACLE_DIR"
 mise x -- npm install
 mise x -- npm run generate
+mise x -- npm run generate:codecs
+mise x -- npm run generate:replay
@@ -0,0 +1,140 @@
+//! Async adapter that feeds a [`StreamingOctetReader`] from a byte source.
+//!
+//! Not an upstream port: upstream streaming readers are fed by `push` from
+//! event callbacks. Sources implement `futures_io::AsyncRead`; tokio
+//! readers go through [`TokioIo`] (`tokio` feature).
+
+use alloc::{vec, vec::Vec};
+use core::future::poll_fn;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use std::io;
+
+pub use futures_io::AsyncRead;
+
+use crate::StreamingOctetReader;
+
+/// Default number of bytes requested from the source per read.
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Adapts a `tokio::io::AsyncRead` to [`AsyncRead`], which is
+/// `futures_io::AsyncRead`: `AsyncStreamingReader::new(TokioIo(stream))`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioIo<R>(pub R);
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncRead for TokioIo<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        ready!(Pin::new(&mut self.0).poll_read(cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+/// A [`StreamingOctetReader`] that pulls its chunks from an [`AsyncRead`].
+///
+/// Dereferences to the buffered [`StreamingOctetReader`], so decoders read
+/// from it with the usual API and call [`fill`](Self::fill) or
+/// [`fill_to`](Self::fill_to) when they run out of bytes. Decoders that own
+/// their reader (`push`-based) take raw chunks from
+/// [`next_chunk`](Self::next_chunk) instead.
+pub struct AsyncStreamingReader<R> {
+    inner: R,
+    reader: StreamingOctetReader,
+    buf: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncStreamingReader<R> {
+    /// Creates a reader requesting up to 8 KiB per read.
+    pub fn new(inner: R) -> Self {
+        Self::with_chunk_size(inner, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a reader requesting up to `chunk_size` bytes per read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size(inner: R, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+        Self {
+            inner,
+            reader: StreamingOctetReader::new(),
+            buf: vec![0; chunk_size],
+        }
+    }
+
+    /// Returns the byte source.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the byte source mutably.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the byte source and the buffered, unread bytes.
+    pub fn into_inner(self) -> (R, StreamingOctetReader) {
+        (self.inner, self.reader)
+    }
+
+    /// Polls the source for its next chunk without buffering it; `None`
+    /// at end of stream.
+    pub fn poll_next_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Vec<u8>>>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_read(cx, &mut self.buf))?;
+        Poll::Ready(Ok((n > 0).then(|| self.buf[..n].to_vec())))
+    }
+
+    /// Reads the next chunk from the source without buffering it; `None`
+    /// at end of stream.
+    pub async fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        poll_fn(|cx| self.poll_next_chunk(cx)).await
+    }
+
+    /// Reads one chunk from the source into the buffer and returns its
+    /// length, or 0 at end of stream.
+    pub async fn fill(&mut self) -> io::Result<usize> {
+        match self.next_chunk().await? {
+            Some(chunk) => {
+                let n = chunk.len();
+                self.reader.push(chunk);
+                Ok(n)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Reads from the source until at least `size` bytes are buffered.
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if the stream ends first;
+    /// the bytes read so far stay buffered.
+    pub async fn fill_to(&mut self, size: usize) -> io::Result<()> {
+        while self.reader.size() < size {
+            if self.fill().await? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> Deref for AsyncStreamingReader<R> {
+    type Target = StreamingOctetReader;
+
+    fn deref(&self) -> &StreamingOctetReader {
+        &self.reader
+    }
+}
+
+impl<R> DerefMut for AsyncStreamingReader<R> {
+    fn deref_mut(&mut self) -> &mut StreamingOctetReader {
+        &mut self.reader
+    }
+}
@@ -0,0 +1,120 @@
+//! A borrowed view into a byte buffer.
+
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use crate::BufferError;
+
+/// Former buffer view type, now [`BufView`].
+#[deprecated(note = "use `BufView`")]
+pub type Slice<'a> = BufView<'a>;
+
+/// Former buffer cut type, now [`BufView`].
+#[deprecated(note = "use `BufView`")]
+pub type Uint8ArrayCut<'a> = BufView<'a>;
+
+/// The bytes `buf[start..end]`, remembering where they sit in `buf`.
+///
+/// Decoders that hand out references into their input return this instead
+/// of a bare slice, so the caller can still tell the offset of what it got
+/// and cut further views from it. Every constructor checks its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufView<'a> {
+    buf: &'a [u8],
+    start: usize,
+    end: usize,
+}
+
+impl<'a> BufView<'a> {
+    /// A view of all of `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            start: 0,
+            end: buf.len(),
+        }
+    }
+
+    /// A view of `buf[start..end]`.
+    pub fn range(buf: &'a [u8], start: usize, end: usize) -> Result<Self, BufferError> {
+        if start > end || end > buf.len() {
+            return Err(BufferError::EndOfBuffer);
+        }
+        Ok(Self { buf, start, end })
+    }
+
+    /// The whole underlying buffer.
+    pub fn buf(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// Offset of the first byte in the underlying buffer.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Offset just past the last byte in the underlying buffer.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The viewed bytes, borrowed for the buffer's lifetime.
+    pub fn as_slice(&self) -> &'a [u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    /// The bytes `start..end` of this view, relative to its start.
+    pub fn subview(&self, start: usize, end: usize) -> Result<Self, BufferError> {
+        if start > end || end > self.len() {
+            return Err(BufferError::EndOfBuffer);
+        }
+        Ok(Self {
+            buf: self.buf,
+            start: self.start + start,
+            end: self.start + end,
+        })
+    }
+
+    /// The viewed bytes, under the method name of the old [`Slice`].
+    #[deprecated(note = "use `as_slice`")]
+    pub fn subarray(&self) -> &'a [u8] {
+        self.as_slice()
+    }
+
+    /// Splits the view in two at `mid`, relative to its start.
+    pub fn split_at(&self, mid: usize) -> Result<(Self, Self), BufferError> {
+        Ok((self.subview(0, mid)?, self.subview(mid, self.len())?))
+    }
+}
+
+impl Deref for BufView<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for BufView<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<'a> From<&'a [u8]> for BufView<'a> {
+    fn from(buf: &'a [u8]) -> Self {
+        Self::new(buf)
+    }
+}
@@ -1,5 +1,7 @@
 //! Byte slice concatenation utilities.
 
+use alloc::vec::Vec;
+
 /// Concatenates two byte slices into a new vector.
 ///
 /// # Example
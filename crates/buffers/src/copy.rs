@@ -1,5 +1,7 @@
 //! Byte slice copy utility.
 
+use alloc::vec::Vec;
+
 /// Creates a copy of a byte slice.
 ///
 /// # Example
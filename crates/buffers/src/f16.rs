@@ -44,7 +44,152 @@ pub fn decode_f16(binary: u16) -> f64 {
         }
     } else {
         // Normalized
-        sign * 2f64.powi(exponent - 15) * (1.0 + fraction / 1024.0)
+        sign * pow2(exponent - 15) * (1.0 + fraction / 1024.0)
+    }
+}
+
+/// Encodes a number as a half-precision (16-bit) floating point value.
+///
+/// Returns the raw binary representation (u16) of the nearest IEEE 754
+/// half-precision float, rounding ties to even. Values too large for the
+/// half-precision range become infinity, values too small become (signed)
+/// zero, and NaN is encoded as the canonical quiet NaN `0x7E00`.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_buffers::encode_f16;
+///
+/// assert_eq!(encode_f16(0.0), 0x0000);
+/// assert_eq!(encode_f16(-0.0), 0x8000);
+/// assert_eq!(encode_f16(1.0), 0x3C00);
+/// assert_eq!(encode_f16(65504.0), 0x7BFF);
+/// assert_eq!(encode_f16(f64::INFINITY), 0x7C00);
+/// assert_eq!(encode_f16(f64::NAN), 0x7E00);
+/// ```
+pub fn encode_f16(value: f64) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let exponent = ((bits >> 52) & 0x7FF) as i32;
+    let mantissa = bits & 0x000F_FFFF_FFFF_FFFF;
+
+    if exponent == 0x7FF {
+        // Infinity or NaN
+        return if mantissa != 0 {
+            sign | 0x7E00
+        } else {
+            sign | 0x7C00
+        };
+    }
+
+    let exponent = exponent - 1023 + 15;
+    if exponent >= 0x1F {
+        // Overflow
+        return sign | 0x7C00;
+    }
+
+    if exponent <= 0 {
+        // Subnormal or zero
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | (1 << 52);
+        let shift = (43 - exponent) as u32;
+        let half = round_ties_to_even(mantissa, shift);
+        // Rounding may carry into the smallest normal exponent, which is
+        // still the correct encoding.
+        return sign | half as u16;
+    }
+
+    // Normalized; a rounding carry may overflow into infinity, which is
+    // the correct result.
+    let half = ((exponent as u64) << 10) + round_ties_to_even(mantissa, 42);
+    sign | half as u16
+}
+
+/// Checks if a number can be exactly represented as a half-precision float.
+///
+/// Returns `true` if the number survives an [`encode_f16`] / [`decode_f16`]
+/// round trip unchanged (including the sign of zero), `false` otherwise.
+/// NaN is never considered exactly representable.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_buffers::is_float16;
+///
+/// assert!(is_float16(1.5));
+/// assert!(is_float16(-65504.0));
+/// assert!(!is_float16(0.1));
+/// assert!(!is_float16(65505.0));
+/// ```
+pub fn is_float16(n: f64) -> bool {
+    decode_f16(encode_f16(n)) == n
+}
+
+/// Like [`decode_f16`], but a NaN keeps its sign and payload: the 10
+/// fraction bits become the top fraction bits of the `f64`.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_buffers::{decode_f16_keep_nan, encode_f16_keep_nan};
+///
+/// let nan = decode_f16_keep_nan(0xFC01);
+/// assert!(nan.is_nan() && nan.is_sign_negative());
+/// assert_eq!(encode_f16_keep_nan(nan), 0xFC01);
+/// ```
+pub fn decode_f16_keep_nan(binary: u16) -> f64 {
+    if binary & 0x7C00 == 0x7C00 && binary & 0x03FF != 0 {
+        let sign = ((binary & 0x8000) as u64) << 48;
+        let payload = ((binary & 0x03FF) as u64) << 42;
+        return f64::from_bits(sign | 0x7FF0_0000_0000_0000 | payload);
+    }
+    decode_f16(binary)
+}
+
+/// Like [`encode_f16`], but a NaN keeps its sign and the top 10 bits of its
+/// payload. A NaN with none of those bits set becomes a quiet NaN.
+pub fn encode_f16_keep_nan(value: f64) -> u16 {
+    if !value.is_nan() {
+        return encode_f16(value);
+    }
+    let bits = value.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let payload = ((bits >> 42) & 0x03FF) as u16;
+    sign | 0x7C00 | if payload == 0 { 0x0200 } else { payload }
+}
+
+/// Checks if a number survives an [`encode_f16_keep_nan`] /
+/// [`decode_f16_keep_nan`] round trip bit for bit, NaN payloads included.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_buffers::is_float16_keep_nan;
+///
+/// assert!(is_float16_keep_nan(1.5));
+/// assert!(is_float16_keep_nan(f64::NAN));
+/// assert!(!is_float16_keep_nan(f64::from_bits(0x7FF8_0000_0000_0001)));
+/// ```
+pub fn is_float16_keep_nan(n: f64) -> bool {
+    decode_f16_keep_nan(encode_f16_keep_nan(n)).to_bits() == n.to_bits()
+}
+
+/// `2^exp` for a normal `f64` exponent, without `f64::powi` (unavailable
+/// under `no_std`).
+fn pow2(exp: i32) -> f64 {
+    f64::from_bits(((exp + 1023) as u64) << 52)
+}
+
+fn round_ties_to_even(mantissa: u64, shift: u32) -> u64 {
+    let truncated = mantissa >> shift;
+    let remainder = mantissa & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
     }
 }
 
@@ -82,4 +227,36 @@ mod tests {
         assert!(decode_f16(0x7C01).is_nan());
         assert!(decode_f16(0xFC01).is_nan());
     }
+
+    #[test]
+    fn test_encode_f16_roundtrip() {
+        for bits in 0u16..=0xFFFF {
+            let value = decode_f16(bits);
+            if value.is_nan() {
+                assert_eq!(encode_f16(value) & 0x7E00, 0x7E00);
+            } else {
+                assert_eq!(encode_f16(value), bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_f16_rounding() {
+        // 1 + 2^-11 is exactly halfway between 1.0 and the next f16; ties to even.
+        assert_eq!(encode_f16(1.0 + 2f64.powi(-11)), 0x3C00);
+        assert_eq!(encode_f16(1.0 + 3.0 * 2f64.powi(-11)), 0x3C02);
+        assert_eq!(encode_f16(65520.0), 0x7C00);
+        assert_eq!(encode_f16(2f64.powi(-25)), 0x0000);
+        assert_eq!(encode_f16(1.5 * 2f64.powi(-25)), 0x0001);
+    }
+
+    #[test]
+    fn test_keep_nan_roundtrip() {
+        for bits in 0u16..=0xFFFF {
+            let value = decode_f16_keep_nan(bits);
+            assert_eq!(encode_f16_keep_nan(value), bits);
+            assert!(is_float16_keep_nan(value));
+        }
+        assert!(!is_float16_keep_nan(f64::from_bits(0x7FF8_0000_0000_0001)));
+    }
 }
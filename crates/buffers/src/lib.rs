@@ -8,17 +8,24 @@
 //! ## Core Types
 //! - [`Reader`] - Reads binary data from a byte slice with cursor tracking
 //! - [`Writer`] - Writes binary data to an auto-growing buffer
-//! - [`Slice`] - A view into a buffer (deprecated, use Reader instead)
+//! - [`WriterPool`] - Reuses writers across short-lived encoders
+//! - [`BufView`] - A bounds-checked view into a buffer, for decoders that
+//!   return references into their input (`Slice` and `Uint8ArrayCut` are
+//!   deprecated aliases)
 //!
 //! ## Streaming Readers
 //! - [`StreamingReader`] - Streaming reader with internal buffer management
 //! - [`StreamingOctetReader`] - Streaming reader for chunked data
+//! - `AsyncStreamingReader` - Fills a `StreamingOctetReader` from an async
+//!   byte source (`async` feature)
 //!
 //! ## Utilities
 //! - [`cmp_uint8_array`], [`cmp_uint8_array2`], [`cmp_uint8_array3`] - Byte slice comparison
 //! - [`concat`], [`concat_list`], [`list_to_uint8`] - Concatenation
 //! - [`copy_slice`] - Copy byte slices
-//! - [`decode_f16`] - Half-precision float decoder
+//! - [`decode_f16`], [`encode_f16`] - Half-precision float codec, with
+//!   `_keep_nan` variants that keep NaN payloads
+//! - [`is_float16`] - Float16 precision check
 //! - [`is_float32`] - Float32 precision check
 //! - [`ascii`], [`utf8`] - String encoding utilities
 //! - [`print_octets`] - Debug hex output
@@ -41,7 +48,22 @@
 //! assert_eq!(reader.u16(), 0x0203);
 //! assert_eq!(reader.utf8(5), "hello");
 //! ```
+//!
+//! # `no_std`
+//!
+//! With default features disabled the crate is `#![no_std]` and depends only
+//! on `alloc`. The `std` feature (on by default) adds the
+//! `std::error::Error` impl for [`BufferError`]. The `async` feature
+//! implies `std` and reads from `futures_io::AsyncRead` sources; `tokio`
+//! adds an adapter for `tokio::io::AsyncRead`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+#[cfg(feature = "async")]
+mod async_streaming_reader;
+mod buf_view;
 mod cmp;
 mod concat;
 mod copy;
@@ -49,27 +71,35 @@ mod f16;
 mod is_float32;
 mod print_octets;
 mod reader;
-mod slice;
 mod streaming_octet_reader;
 mod streaming_reader;
 mod strings;
-mod uint8_array_cut;
 mod writer;
+mod writer_pool;
 
 // Re-export all public items
+#[cfg(feature = "tokio")]
+pub use async_streaming_reader::TokioIo;
+#[cfg(feature = "async")]
+pub use async_streaming_reader::{AsyncRead, AsyncStreamingReader};
+pub use buf_view::BufView;
+#[allow(deprecated)]
+pub use buf_view::{Slice, Uint8ArrayCut};
 pub use cmp::{cmp_uint8_array, cmp_uint8_array2, cmp_uint8_array3};
 pub use concat::{concat, concat_list, list_to_uint8};
 pub use copy::copy_slice;
-pub use f16::decode_f16;
+pub use f16::{
+    decode_f16, decode_f16_keep_nan, encode_f16, encode_f16_keep_nan, is_float16,
+    is_float16_keep_nan,
+};
 pub use is_float32::is_float32;
 pub use print_octets::{print_octets, print_octets_default};
 pub use reader::Reader;
-pub use slice::Slice;
 pub use streaming_octet_reader::StreamingOctetReader;
 pub use streaming_reader::StreamingReader;
 pub use strings::{ascii, utf8};
-pub use uint8_array_cut::Uint8ArrayCut;
 pub use writer::Writer;
+pub use writer_pool::{WriterPool, WriterPoolOptions, WriterPoolStats};
 
 /// Error type for buffer operations.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -82,8 +112,8 @@ pub enum BufferError {
     Overflow,
 }
 
-impl std::fmt::Display for BufferError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             BufferError::EndOfBuffer => write!(f, "end of buffer"),
             BufferError::InvalidUtf8 => write!(f, "invalid UTF-8 sequence"),
@@ -92,4 +122,5 @@ impl std::fmt::Display for BufferError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for BufferError {}
@@ -1,5 +1,7 @@
 //! Debug utility for printing octets as hex strings.
 
+use alloc::{format, string::String};
+
 /// Formats a byte slice as a hex string for debugging.
 ///
 /// # Arguments
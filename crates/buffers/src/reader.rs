@@ -1,6 +1,6 @@
 //! Binary buffer reader with cursor tracking.
 
-use std::str;
+use core::str;
 
 use crate::BufferError;
 
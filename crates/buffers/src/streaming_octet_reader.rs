@@ -1,15 +1,25 @@
 //! Streaming octet reader for reading across chunk boundaries.
 
+use alloc::{string::String, vec, vec::Vec};
+
 /// A streaming reader that manages multiple chunks of byte slices.
 ///
 /// For performance, it does not merge chunks into a single buffer.
 /// Instead, it tracks chunks and reads across boundaries as needed.
+///
+/// Chunks are dropped once read, unless a [`mark`](Self::mark) is set: then
+/// they are kept until [`consume`](Self::consume), so a decoder that finds a
+/// frame incomplete can [`reset_to_mark`](Self::reset_to_mark) and retry.
 pub struct StreamingOctetReader {
     chunks: Vec<Vec<u8>>,
+    /// Index of the current chunk; only non-zero while marked.
+    head: usize,
     /// Current position within the current chunk.
     x: usize,
-    /// Total size of all chunks.
+    /// Total size of the chunks from `head` on.
     chunk_size: usize,
+    /// Marked position within the first chunk.
+    mark: Option<usize>,
 }
 
 impl Default for StreamingOctetReader {
@@ -23,8 +33,10 @@ impl StreamingOctetReader {
     pub fn new() -> Self {
         Self {
             chunks: Vec::new(),
+            head: 0,
             x: 0,
             chunk_size: 0,
+            mark: None,
         }
     }
 
@@ -39,6 +51,42 @@ impl StreamingOctetReader {
         self.chunks.push(chunk);
     }
 
+    /// Remembers the current position for
+    /// [`reset_to_mark`](Self::reset_to_mark), replacing any earlier mark.
+    pub fn mark(&mut self) {
+        self.chunks.drain(..self.head);
+        self.head = 0;
+        self.mark = Some(self.x);
+    }
+
+    /// Rewinds to the last [`mark`](Self::mark), which stays set. Does
+    /// nothing if no mark is set.
+    pub fn reset_to_mark(&mut self) {
+        if let Some(x) = self.mark {
+            self.head = 0;
+            self.x = x;
+            self.chunk_size = self.chunks.iter().map(Vec::len).sum();
+        }
+    }
+
+    /// Clears the mark and drops the chunks read since it was set.
+    pub fn consume(&mut self) {
+        self.mark = None;
+        self.chunks.drain(..self.head);
+        self.head = 0;
+    }
+
+    /// Moves past the current chunk, keeping it while marked.
+    fn next_chunk(&mut self) {
+        self.chunk_size -= self.chunks[self.head].len();
+        if self.mark.is_some() {
+            self.head += 1;
+        } else {
+            self.chunks.remove(0);
+        }
+        self.x = 0;
+    }
+
     fn assert_size(&self, size: usize) {
         if size > self.size() {
             panic!("OUT_OF_BOUNDS");
@@ -48,13 +96,11 @@ impl StreamingOctetReader {
     /// Reads a single unsigned byte.
     pub fn u8(&mut self) -> u8 {
         self.assert_size(1);
-        let chunk = &mut self.chunks[0];
+        let chunk = &self.chunks[self.head];
         let octet = chunk[self.x];
         self.x += 1;
         if self.x >= chunk.len() {
-            self.chunk_size -= chunk.len();
-            self.chunks.remove(0);
-            self.x = 0;
+            self.next_chunk();
         }
         octet
     }
@@ -74,9 +120,22 @@ impl StreamingOctetReader {
             return;
         }
         self.assert_size(size);
+        self.copy_unsafe(size, dst, pos);
+        self.skip_unsafe(size);
+    }
+
+    /// Copies the next `size` bytes into a new vector without advancing.
+    pub fn peek_buf(&self, size: usize) -> Vec<u8> {
+        self.assert_size(size);
+        let mut result = vec![0u8; size];
+        self.copy_unsafe(size, &mut result, 0);
+        result
+    }
+
+    fn copy_unsafe(&self, size: usize, dst: &mut [u8], pos: usize) {
         let mut remaining = size;
         let mut dst_pos = pos;
-        let mut chunk_idx = 0;
+        let mut chunk_idx = self.head;
         let mut local_x = self.x;
 
         while remaining > 0 {
@@ -95,8 +154,6 @@ impl StreamingOctetReader {
                 local_x += to_copy;
             }
         }
-
-        self.skip_unsafe(size);
     }
 
     /// Reads bytes into a new vector.
@@ -117,24 +174,21 @@ impl StreamingOctetReader {
         if n == 0 {
             return;
         }
-        while n > 0 && !self.chunks.is_empty() {
-            let chunk = &self.chunks[0];
-            let remaining = chunk.len() - self.x;
+        while n > 0 && self.head < self.chunks.len() {
+            let remaining = self.chunks[self.head].len() - self.x;
             if remaining > n {
                 self.x += n;
                 return;
             }
             n -= remaining;
-            self.chunk_size -= chunk.len();
-            self.chunks.remove(0);
-            self.x = 0;
+            self.next_chunk();
         }
     }
 
     /// Peeks at the next byte without advancing.
     pub fn peek(&self) -> u8 {
         self.assert_size(1);
-        self.chunks[0][self.x]
+        self.chunks[self.head][self.x]
     }
 
     /// Reads `size` bytes, XOR-masking each byte with `mask[(offset + i) % 4]`.
@@ -221,6 +275,18 @@ mod tests {
         assert_eq!(reader.u8(), 3);
     }
 
+    #[test]
+    fn test_peek_buf() {
+        let mut reader = StreamingOctetReader::new();
+        reader.push(vec![1, 2]);
+        reader.push(vec![3, 4]);
+        reader.skip(1);
+        assert_eq!(reader.peek_buf(3), vec![2, 3, 4]);
+        assert_eq!(reader.peek_buf(0), Vec::<u8>::new());
+        assert_eq!(reader.size(), 3);
+        assert_eq!(reader.u8(), 2);
+    }
+
     #[test]
     fn test_buf() {
         let mut reader = StreamingOctetReader::new();
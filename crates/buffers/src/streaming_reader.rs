@@ -1,14 +1,21 @@
 //! Streaming reader with internal buffer management.
 
 use crate::{Reader, Writer};
+use alloc::vec::Vec;
 
 /// A streaming reader that internally manages a growing buffer.
 ///
 /// Data chunks are pushed into the reader and can be consumed incrementally.
+///
+/// A decoder that finds a frame incomplete can [`mark`](Self::mark) where
+/// the frame starts and [`reset_to_mark`](Self::reset_to_mark) to retry once
+/// more data is pushed.
 pub struct StreamingReader {
     writer: Writer,
     /// Offset from the start of the buffer (x0 in Writer).
     dx: usize,
+    /// Marked offset, relative to x0 like `dx`.
+    mark: usize,
 }
 
 impl Default for StreamingReader {
@@ -28,6 +35,7 @@ impl StreamingReader {
         Self {
             writer: Writer::with_alloc_size(alloc_size),
             dx: 0,
+            mark: 0,
         }
     }
 
@@ -48,9 +56,26 @@ impl StreamingReader {
     }
 
     /// Marks the current position as consumed, freeing memory for reuse.
+    ///
+    /// The mark moves to the new start.
     pub fn consume(&mut self) {
         self.writer.x0 += self.dx;
         self.dx = 0;
+        self.mark = 0;
+    }
+
+    /// Remembers the current position for [`reset_to_mark`](Self::reset_to_mark).
+    ///
+    /// Unlike a saved [`x`](Self::x), the mark stays valid when pushes move
+    /// the buffer.
+    pub fn mark(&mut self) {
+        self.mark = self.dx;
+    }
+
+    /// Rewinds to the last [`mark`](Self::mark), or to the last
+    /// [`consume`](Self::consume) if nothing was marked since.
+    pub fn reset_to_mark(&mut self) {
+        self.dx = self.mark;
     }
 
     /// Returns the current cursor position.
@@ -69,6 +94,13 @@ impl StreamingReader {
         self.writer.uint8[self.x()]
     }
 
+    /// Returns the next `size` bytes without advancing.
+    pub fn peek_buf(&self, size: usize) -> &[u8] {
+        self.assert_size(size);
+        let x = self.x();
+        &self.writer.uint8[x..x + size]
+    }
+
     /// Skips the given number of bytes.
     pub fn skip(&mut self, length: usize) {
         self.assert_size(length);
@@ -242,7 +274,7 @@ impl StreamingReader {
     pub fn utf8(&mut self, size: usize) -> &str {
         self.assert_size(size);
         let x = self.x();
-        let s = std::str::from_utf8(&self.writer.uint8[x..x + size]).unwrap_or("");
+        let s = core::str::from_utf8(&self.writer.uint8[x..x + size]).unwrap_or("");
         self.dx += size;
         s
     }
@@ -255,6 +287,7 @@ impl StreamingReader {
     /// Resets the reader with new data.
     pub fn reset(&mut self, data: &[u8]) {
         self.dx = 0;
+        self.mark = 0;
         self.writer.reset();
         self.push(data);
     }
@@ -1,5 +1,7 @@
 //! String encoding utilities for ASCII and UTF-8.
 
+use alloc::vec::Vec;
+
 /// Converts a string to a vector of ASCII bytes.
 ///
 /// Each character is converted to its ASCII byte value.
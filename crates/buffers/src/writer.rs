@@ -1,5 +1,7 @@
 //! Binary buffer writer with auto-growing capacity.
 
+use alloc::{vec, vec::Vec};
+
 /// A binary buffer writer that grows automatically as needed.
 ///
 /// # Example
@@ -98,14 +100,15 @@ impl Writer {
         result
     }
 
-    /// Returns a slice view of the written data and advances the flush position.
+    /// Returns a view of the written data and advances the flush position.
     ///
     /// Mirrors the upstream `flushSlice()` method. Returns a view into the
     /// internal buffer rather than copying.
-    pub fn flush_slice(&mut self) -> crate::Slice<'_> {
-        let slice = crate::Slice::new(&self.uint8, self.x0, self.x);
+    pub fn flush_slice(&mut self) -> crate::BufView<'_> {
+        let x0 = self.x0;
         self.x0 = self.x;
-        slice
+        crate::BufView::range(&self.uint8[..self.x], x0, self.x)
+            .expect("flush position is within the written data")
     }
 
     /// Writes an unsigned 8-bit integer.
@@ -198,6 +201,70 @@ impl Writer {
         self.x += 4;
     }
 
+    /// Writes a unsigned 16-bit integer (little-endian).
+    #[inline]
+    pub fn u16_le(&mut self, val: u16) {
+        self.ensure_capacity(2);
+        self.uint8[self.x..self.x + 2].copy_from_slice(&val.to_le_bytes());
+        self.x += 2;
+    }
+
+    /// Writes a signed 16-bit integer (little-endian).
+    #[inline]
+    pub fn i16_le(&mut self, val: i16) {
+        self.ensure_capacity(2);
+        self.uint8[self.x..self.x + 2].copy_from_slice(&val.to_le_bytes());
+        self.x += 2;
+    }
+
+    /// Writes a unsigned 32-bit integer (little-endian).
+    #[inline]
+    pub fn u32_le(&mut self, val: u32) {
+        self.ensure_capacity(4);
+        self.uint8[self.x..self.x + 4].copy_from_slice(&val.to_le_bytes());
+        self.x += 4;
+    }
+
+    /// Writes a signed 32-bit integer (little-endian).
+    #[inline]
+    pub fn i32_le(&mut self, val: i32) {
+        self.ensure_capacity(4);
+        self.uint8[self.x..self.x + 4].copy_from_slice(&val.to_le_bytes());
+        self.x += 4;
+    }
+
+    /// Writes a unsigned 64-bit integer (little-endian).
+    #[inline]
+    pub fn u64_le(&mut self, val: u64) {
+        self.ensure_capacity(8);
+        self.uint8[self.x..self.x + 8].copy_from_slice(&val.to_le_bytes());
+        self.x += 8;
+    }
+
+    /// Writes a signed 64-bit integer (little-endian).
+    #[inline]
+    pub fn i64_le(&mut self, val: i64) {
+        self.ensure_capacity(8);
+        self.uint8[self.x..self.x + 8].copy_from_slice(&val.to_le_bytes());
+        self.x += 8;
+    }
+
+    /// Writes a 32-bit floating point number (little-endian).
+    #[inline]
+    pub fn f32_le(&mut self, val: f32) {
+        self.ensure_capacity(4);
+        self.uint8[self.x..self.x + 4].copy_from_slice(&val.to_le_bytes());
+        self.x += 4;
+    }
+
+    /// Writes a 64-bit floating point number (little-endian).
+    #[inline]
+    pub fn f64_le(&mut self, val: f64) {
+        self.ensure_capacity(8);
+        self.uint8[self.x..self.x + 8].copy_from_slice(&val.to_le_bytes());
+        self.x += 8;
+    }
+
     /// Writes a u8 followed by a u16 (big-endian).
     pub fn u8u16(&mut self, u8_val: u8, u16_val: u16) {
         self.ensure_capacity(3);
@@ -371,6 +438,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_little_endian() {
+        let mut writer = Writer::new();
+        writer.u16_le(0x0102);
+        writer.i16_le(-2);
+        writer.u32_le(0x01020304);
+        writer.i32_le(-2);
+        writer.u64_le(0x0102030405060708);
+        writer.i64_le(-2);
+        writer.f32_le(1.5);
+        writer.f64_le(-0.25);
+        let data = writer.flush();
+        let mut expected = vec![0x02, 0x01, 0xfe, 0xff, 0x04, 0x03, 0x02, 0x01];
+        expected.extend_from_slice(&[0xfe, 0xff, 0xff, 0xff]);
+        expected.extend_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+        expected.extend_from_slice(&[0xfe; 1]);
+        expected.extend_from_slice(&[0xff; 7]);
+        expected.extend_from_slice(&1.5f32.to_le_bytes());
+        expected.extend_from_slice(&(-0.25f64).to_le_bytes());
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_mixed_endian() {
+        let mut writer = Writer::new();
+        writer.u32(0x01020304);
+        writer.u32_le(0x01020304);
+        assert_eq!(writer.flush(), [1, 2, 3, 4, 4, 3, 2, 1]);
+    }
+
     #[test]
     fn test_flush_slice() {
         let mut writer = Writer::new();
@@ -378,11 +475,11 @@ mod tests {
         writer.u8(0x0b);
         let slice = writer.flush_slice();
         // The slice view should cover exactly the two written bytes.
-        assert_eq!(slice.subarray(), [0x0a, 0x0b]);
+        assert_eq!(slice.as_slice(), [0x0a, 0x0b]);
         // A subsequent write should be tracked as a new slice.
         writer.u8(0x0c);
         let slice2 = writer.flush_slice();
-        assert_eq!(slice2.subarray(), [0x0c]);
+        assert_eq!(slice2.as_slice(), [0x0c]);
     }
 
     #[test]
@@ -0,0 +1,180 @@
+//! Pool of reusable [`Writer`]s.
+//!
+//! Not an upstream port: upstream shares one writer per encoder instance.
+//! Servers that create an encoder per request can acquire writers from a
+//! pool instead, so each request starts with an already-grown buffer rather
+//! than re-allocating from the default size.
+
+use alloc::vec::Vec;
+
+use crate::Writer;
+
+/// Limits for a [`WriterPool`].
+#[derive(Debug, Clone)]
+pub struct WriterPoolOptions {
+    /// Allocation size of writers created when the pool is empty.
+    pub alloc_size: usize,
+    /// Most idle writers kept; further releases are dropped.
+    pub max_pooled: usize,
+    /// Writers whose buffer grew beyond this many bytes are dropped on
+    /// release, so one huge payload does not pin memory for good.
+    pub max_capacity: usize,
+}
+
+impl Default for WriterPoolOptions {
+    fn default() -> Self {
+        Self {
+            alloc_size: 64 * 1024,
+            max_pooled: 16,
+            max_capacity: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Usage counters for tuning [`WriterPoolOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriterPoolStats {
+    /// Total [`WriterPool::acquire`] calls.
+    pub acquired: u64,
+    /// Acquires served by an idle writer rather than a new allocation.
+    pub reused: u64,
+    /// Total [`WriterPool::release`] calls.
+    pub released: u64,
+    /// Releases dropped because the pool was full or the writer too large.
+    pub discarded: u64,
+    /// Writers currently idle in the pool.
+    pub pooled: usize,
+    /// Sum of the buffer sizes of idle writers, in bytes.
+    pub pooled_capacity: usize,
+    /// Largest buffer size seen on release, in bytes.
+    pub peak_capacity: usize,
+}
+
+/// A free list of [`Writer`]s.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_buffers::WriterPool;
+///
+/// let mut pool = WriterPool::new();
+/// let mut writer = pool.acquire();
+/// writer.utf8("hello");
+/// assert_eq!(writer.flush(), b"hello");
+/// pool.release(writer);
+///
+/// let writer = pool.acquire();
+/// assert_eq!(writer.x, 0);
+/// assert_eq!(pool.stats().reused, 1);
+/// ```
+#[derive(Default)]
+pub struct WriterPool {
+    pub options: WriterPoolOptions,
+    writers: Vec<Writer>,
+    stats: WriterPoolStats,
+}
+
+impl WriterPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: WriterPoolOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Takes an idle writer, or creates one when the pool is empty.
+    pub fn acquire(&mut self) -> Writer {
+        self.stats.acquired += 1;
+        match self.writers.pop() {
+            Some(writer) => {
+                self.stats.reused += 1;
+                self.stats.pooled -= 1;
+                self.stats.pooled_capacity -= writer.uint8.len();
+                writer
+            }
+            None => Writer::with_alloc_size(self.options.alloc_size),
+        }
+    }
+
+    /// Returns a writer to the pool. Its cursor is rewound; unflushed bytes
+    /// are discarded.
+    pub fn release(&mut self, mut writer: Writer) {
+        let capacity = writer.uint8.len();
+        self.stats.released += 1;
+        self.stats.peak_capacity = self.stats.peak_capacity.max(capacity);
+        if self.writers.len() >= self.options.max_pooled || capacity > self.options.max_capacity {
+            self.stats.discarded += 1;
+            return;
+        }
+        writer.x = 0;
+        writer.x0 = 0;
+        self.stats.pooled += 1;
+        self.stats.pooled_capacity += capacity;
+        self.writers.push(writer);
+    }
+
+    pub fn stats(&self) -> WriterPoolStats {
+        self.stats
+    }
+
+    /// Drops all idle writers.
+    pub fn clear(&mut self) {
+        self.writers.clear();
+        self.stats.pooled = 0;
+        self.stats.pooled_capacity = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse_keeps_grown_buffer() {
+        let mut pool = WriterPool::with_options(WriterPoolOptions {
+            alloc_size: 16,
+            ..WriterPoolOptions::default()
+        });
+        let mut writer = pool.acquire();
+        writer.buf(&[7; 100]);
+        writer.flush();
+        let grown = writer.uint8.len();
+        assert!(grown >= 100);
+        pool.release(writer);
+        let stats = pool.stats();
+        assert_eq!((stats.pooled, stats.pooled_capacity), (1, grown));
+        assert_eq!(stats.peak_capacity, grown);
+
+        let writer = pool.acquire();
+        assert_eq!(writer.uint8.len(), grown);
+        assert_eq!((writer.x, writer.x0), (0, 0));
+        let stats = pool.stats();
+        assert_eq!((stats.acquired, stats.reused, stats.pooled), (2, 1, 0));
+        assert_eq!(stats.pooled_capacity, 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut pool = WriterPool::with_options(WriterPoolOptions {
+            alloc_size: 8,
+            max_pooled: 1,
+            max_capacity: 32,
+        });
+        let a = pool.acquire();
+        let b = pool.acquire();
+        let mut big = pool.acquire();
+        big.buf(&[0; 64]);
+        pool.release(a);
+        pool.release(b);
+        pool.release(big);
+        let stats = pool.stats();
+        assert_eq!((stats.released, stats.discarded, stats.pooled), (3, 2, 1));
+        pool.clear();
+        assert_eq!(pool.stats().pooled, 0);
+        assert_eq!(pool.stats().pooled_capacity, 0);
+    }
+}
@@ -0,0 +1,183 @@
+//! `AsyncStreamingReader` over sources that return data in uneven chunks,
+//! stall between reads and fail.
+//!
+//! Not an upstream port. Run with `--features async`, or `--features tokio`
+//! for the tokio adapter.
+
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::io;
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use json_joy_buffers::{AsyncRead, AsyncStreamingReader, StreamingOctetReader};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Polls `future` to completion, counting how often it was pending.
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut pending = 0;
+    loop {
+        if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return (out, pending);
+        }
+        pending += 1;
+    }
+}
+
+/// Hands out scripted chunks, returning `Pending` before each one.
+struct Script {
+    chunks: Vec<io::Result<Vec<u8>>>,
+    stalled: bool,
+}
+
+impl Script {
+    fn new(chunks: Vec<io::Result<Vec<u8>>>) -> Self {
+        Self {
+            chunks,
+            stalled: false,
+        }
+    }
+}
+
+impl AsyncRead for Script {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if !self.stalled {
+            self.stalled = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.stalled = false;
+        if self.chunks.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        match self.chunks.remove(0) {
+            Ok(mut chunk) => {
+                let n = chunk.len().min(buf.len());
+                buf[..n].copy_from_slice(&chunk[..n]);
+                if n < chunk.len() {
+                    self.chunks.insert(0, Ok(chunk.split_off(n)));
+                }
+                Poll::Ready(Ok(n))
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+#[test]
+fn async_streaming_reader_fill_to_matrix() {
+    // Length-prefixed frames split at every possible boundary.
+    let frames: [&[u8]; 3] = [b"hello", b"", b"async world"];
+    let mut wire = Vec::new();
+    for frame in frames {
+        wire.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        wire.extend_from_slice(frame);
+    }
+    for chunk_size in [1, 2, 3, 7, 64] {
+        let mut reader = AsyncStreamingReader::with_chunk_size(&wire[..], chunk_size);
+        let (decoded, pending) = block_on(async {
+            let mut out = Vec::new();
+            loop {
+                match reader.fill_to(4).await {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(e) => panic!("{e}"),
+                }
+                let len = reader.u32() as usize;
+                reader.fill_to(len).await.unwrap();
+                out.push(reader.buf(len));
+            }
+            out
+        });
+        assert_eq!(decoded, frames, "chunk size {chunk_size}");
+        assert_eq!(pending, 0, "slices are always ready");
+        assert_eq!(reader.size(), 0);
+    }
+}
+
+#[test]
+fn async_streaming_reader_pending_and_errors() {
+    let source = Script::new(vec![
+        Ok(vec![1, 2, 3]),
+        Ok(vec![4]),
+        Ok(vec![5, 6, 7, 8, 9]),
+        Err(io::Error::other("reset")),
+    ]);
+    let mut reader = AsyncStreamingReader::with_chunk_size(source, 4);
+
+    let (res, pending) = block_on(reader.fill_to(5));
+    res.unwrap();
+    assert_eq!(pending, 3);
+    assert_eq!(reader.buf(5), [1, 2, 3, 4, 5]);
+
+    // The rest of the split chunk comes first.
+    let (res, _) = block_on(reader.fill());
+    assert_eq!(res.unwrap(), 1);
+    assert_eq!(reader.size(), 4);
+
+    let (res, _) = block_on(reader.fill_to(5));
+    assert_eq!(res.unwrap_err().to_string(), "reset");
+    // Buffered bytes survive the error.
+    let (source, mut rest): (Script, StreamingOctetReader) = reader.into_inner();
+    assert!(source.chunks.is_empty());
+    assert_eq!(rest.buf(4), [6, 7, 8, 9]);
+}
+
+#[test]
+fn async_streaming_reader_next_chunk_and_eof() {
+    let data: &[u8] = b"abcdefg";
+    let mut reader = AsyncStreamingReader::with_chunk_size(data, 3);
+    let mut chunks = Vec::new();
+    while let Some(chunk) = block_on(reader.next_chunk()).0.unwrap() {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks, [b"abc".to_vec(), b"def".to_vec(), b"g".to_vec()]);
+    assert_eq!(reader.size(), 0, "next_chunk does not buffer");
+
+    let (res, _) = block_on(reader.fill());
+    assert_eq!(res.unwrap(), 0);
+    let (res, _) = block_on(reader.fill_to(1));
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+    // Boxed and borrowed sources forward.
+    let mut source: &[u8] = b"xyz";
+    let mut boxed = AsyncStreamingReader::new(Box::new(&mut source));
+    let (res, _) = block_on(boxed.fill_to(3));
+    res.unwrap();
+    assert_eq!(boxed.buf(3), b"xyz");
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn async_streaming_reader_tokio_matrix() {
+    use json_joy_buffers::TokioIo;
+
+    // `&[u8]` is a tokio reader too; read it through the adapter.
+    let data: &[u8] = b"\x00\x00\x00\x03abc";
+    let mut reader = AsyncStreamingReader::with_chunk_size(TokioIo(data), 2);
+    let (res, pending) = block_on(async {
+        reader.fill_to(4).await?;
+        let len = reader.u32() as usize;
+        reader.fill_to(len).await?;
+        Ok::<_, io::Error>(reader.buf(len))
+    });
+    assert_eq!(res.unwrap(), b"abc");
+    assert_eq!(pending, 0);
+    let (res, _) = block_on(reader.fill());
+    assert_eq!(res.unwrap(), 0);
+    assert!(reader.get_ref().0.is_empty());
+}
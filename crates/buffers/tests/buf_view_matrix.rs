@@ -0,0 +1,82 @@
+//! `BufView` bounds checks and subviews.
+//!
+//! Not an upstream port: upstream's `Slice` and `Uint8ArrayCut` do no
+//! bounds checking.
+
+use json_joy_buffers::{BufView, BufferError};
+
+#[test]
+fn buf_view_range_matrix() {
+    let buf = [1u8, 2, 3, 4, 5];
+    let cases: &[(usize, usize, Option<&[u8]>)] = &[
+        (0, 5, Some(&[1, 2, 3, 4, 5])),
+        (1, 4, Some(&[2, 3, 4])),
+        (5, 5, Some(&[])),
+        (0, 6, None),
+        (3, 2, None),
+    ];
+    for &(start, end, expected) in cases {
+        let view = BufView::range(&buf, start, end);
+        match expected {
+            Some(bytes) => {
+                let view = view.unwrap();
+                assert_eq!(view.as_slice(), bytes);
+                assert_eq!(
+                    (view.start(), view.end(), view.len()),
+                    (start, end, end - start)
+                );
+                assert_eq!(view.is_empty(), bytes.is_empty());
+            }
+            None => assert_eq!(view, Err(BufferError::EndOfBuffer), "{start}..{end}"),
+        }
+    }
+    assert_eq!(BufView::new(&buf), BufView::range(&buf, 0, 5).unwrap());
+    assert_eq!(BufView::from(&buf[..]).to_vec(), buf);
+}
+
+#[test]
+fn buf_view_subview_matrix() {
+    let buf = [1u8, 2, 3, 4, 5, 6];
+    let view = BufView::range(&buf, 1, 5).unwrap();
+    assert_eq!(&*view, [2, 3, 4, 5]);
+
+    // Subviews are relative to the view but keep offsets into the buffer.
+    let sub = view.subview(1, 3).unwrap();
+    assert_eq!(sub.as_slice(), [3, 4]);
+    assert_eq!((sub.start(), sub.end()), (2, 4));
+    assert_eq!(sub.buf(), buf);
+    assert_eq!(view.subview(0, 5), Err(BufferError::EndOfBuffer));
+    assert_eq!(view.subview(2, 1), Err(BufferError::EndOfBuffer));
+
+    let (head, tail) = view.split_at(1).unwrap();
+    assert_eq!(
+        (head.as_slice(), tail.as_slice()),
+        (&[2][..], &[3, 4, 5][..])
+    );
+    assert_eq!(tail.start(), 2);
+    assert!(view.split_at(5).is_err());
+
+    // The borrowed bytes outlive the view.
+    let bytes: &[u8] = {
+        let view = BufView::new(&buf);
+        view.subview(4, 6).unwrap().as_slice()
+    };
+    assert_eq!(bytes, [5, 6]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn buf_view_deprecated_aliases() {
+    use json_joy_buffers::{Slice, Uint8ArrayCut, Writer};
+
+    let buf = [1u8, 2, 3];
+    let slice: Slice = BufView::range(&buf, 1, 3).unwrap();
+    assert_eq!(slice.subarray(), [2, 3]);
+    let cut: Uint8ArrayCut = slice.subview(1, 2).unwrap();
+    assert_eq!(cut.as_slice(), [3]);
+
+    let mut writer = Writer::new();
+    writer.u8(9);
+    let flushed: Slice = writer.flush_slice();
+    assert_eq!(flushed.subarray(), [9]);
+}
@@ -0,0 +1,77 @@
+//! Mark, rollback and peeking on the streaming readers.
+//!
+//! Not an upstream port: upstream decoders save and restore `reader.x`.
+
+use json_joy_buffers::{StreamingOctetReader, StreamingReader};
+
+#[test]
+fn streaming_reader_mark_matrix() {
+    let mut reader = StreamingReader::with_alloc_size(4);
+    reader.push(&[1, 2, 3]);
+    assert_eq!(reader.u8(), 1);
+    reader.mark();
+    assert_eq!(reader.u16(), 0x0203);
+    // Pushes move the buffer; the mark follows.
+    reader.push(&[4, 5, 6, 7, 8, 9]);
+    assert_eq!(reader.peek_buf(3), [4, 5, 6]);
+    reader.skip(3);
+    reader.reset_to_mark();
+    assert_eq!(reader.size(), 8);
+    assert_eq!(reader.peek_buf(2), [2, 3]);
+
+    // Consuming moves the mark to the new start.
+    reader.skip(2);
+    reader.consume();
+    reader.skip(4);
+    reader.reset_to_mark();
+    assert_eq!(reader.u8(), 4);
+    // Without a mark since the last consume, that is where it rewinds to.
+    reader.consume();
+    reader.skip(2);
+    reader.reset_to_mark();
+    assert_eq!(reader.peek_buf(5), [5, 6, 7, 8, 9]);
+    assert_eq!(reader.peek_buf(0), [] as [u8; 0]);
+
+    reader.mark();
+    reader.skip(1);
+    reader.reset(&[42]);
+    reader.reset_to_mark();
+    assert_eq!(reader.u8(), 42);
+}
+
+#[test]
+fn streaming_octet_reader_mark_matrix() {
+    let mut reader = StreamingOctetReader::new();
+    reader.push(vec![1, 2]);
+    reader.push(vec![3]);
+    reader.push(vec![4, 5, 6]);
+    assert_eq!(reader.u8(), 1);
+    reader.mark();
+    // Reads across chunk boundaries keep the chunks while marked.
+    assert_eq!(reader.u32(), 0x0203_0405);
+    assert_eq!(reader.size(), 1);
+    reader.reset_to_mark();
+    assert_eq!(reader.size(), 5);
+    reader.skip(4);
+    reader.push(vec![7]);
+    reader.reset_to_mark();
+    assert_eq!(reader.buf(6), [2, 3, 4, 5, 6, 7]);
+    reader.reset_to_mark();
+    assert_eq!(reader.peek_buf(2), [2, 3]);
+
+    // A new mark replaces the old one.
+    reader.skip(3);
+    reader.mark();
+    reader.skip(2);
+    reader.reset_to_mark();
+    assert_eq!(reader.peek(), 5);
+
+    // Consuming clears the mark; rewinding then does nothing.
+    reader.skip(1);
+    reader.consume();
+    reader.reset_to_mark();
+    assert_eq!(reader.buf(2), [6, 7]);
+    assert_eq!(reader.size(), 0);
+    reader.push(vec![8]);
+    assert_eq!(reader.u8(), 8);
+}
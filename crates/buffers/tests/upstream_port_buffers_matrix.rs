@@ -4,7 +4,7 @@
 
 use json_joy_buffers::{
     cmp_uint8_array, cmp_uint8_array2, cmp_uint8_array3, concat, concat_list, decode_f16,
-    is_float32, Reader, Writer,
+    encode_f16, is_float16, is_float32, Reader, Writer,
 };
 
 // ---------------------------------------------------------------------------
@@ -363,6 +363,35 @@ fn f16_half() {
     assert_eq!(decode_f16(0x3800), 0.5);
 }
 
+// ---------------------------------------------------------------------------
+// f16 encode / is_float16
+// ---------------------------------------------------------------------------
+
+#[test]
+fn f16_encode_edge_cases() {
+    assert_eq!(encode_f16(0.5), 0x3800);
+    assert_eq!(encode_f16(-2.0), 0xC000);
+    assert_eq!(encode_f16(decode_f16(0x0001)), 0x0001);
+    assert_eq!(encode_f16(decode_f16(0x03FF)), 0x03FF);
+    assert_eq!(encode_f16(1e6), 0x7C00);
+    assert_eq!(encode_f16(-1e6), 0xFC00);
+    assert_eq!(encode_f16(1e-10), 0x0000);
+    assert_eq!(encode_f16(-1e-10), 0x8000);
+}
+
+#[test]
+fn is_float16_matrix() {
+    assert!(is_float16(0.0));
+    assert!(is_float16(-0.0));
+    assert!(is_float16(0.5));
+    assert!(is_float16(1024.0));
+    assert!(is_float16(f64::INFINITY));
+    assert!(!is_float16(f64::NAN));
+    assert!(!is_float16(0.1));
+    assert!(!is_float16(1e6));
+    assert!(!is_float16(2049.0));
+}
+
 // ---------------------------------------------------------------------------
 // is_float32
 // ---------------------------------------------------------------------------
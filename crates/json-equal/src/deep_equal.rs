@@ -23,6 +23,50 @@ use serde_json::Value;
 /// assert!(!deep_equal(&a, &c));
 /// ```
 pub fn deep_equal(a: &Value, b: &Value) -> bool {
+    deep_equal_with(a, b, &DeepEqualOptions::default())
+}
+
+/// Relaxations for [`deep_equal_with`]. The default is the strict
+/// comparison of [`deep_equal`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeepEqualOptions {
+    /// Numbers, at least one of them a float, are equal if they differ by at
+    /// most this much.
+    pub epsilon: f64,
+    /// Numbers, at least one of them a float, are equal if at most this many
+    /// representable `f64` values lie between them.
+    pub max_ulps: u64,
+    /// An object member whose value is `null` counts as absent, so
+    /// `{"a": null}` equals `{}`. `undefined` values from other encodings
+    /// arrive here as `null`.
+    pub null_as_missing: bool,
+    /// Object members must also appear in the same order. The order is the
+    /// map's iteration order, which is insertion order only with serde_json's
+    /// `preserve_order` feature.
+    pub ordered_objects: bool,
+}
+
+/// [`deep_equal`] with the relaxations in `options`.
+///
+/// Once either float tolerance is set, an integer and a float compare by
+/// value, so `1` equals `1.0`. Two integers still compare exactly.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use json_joy_json_equal::{deep_equal_with, DeepEqualOptions};
+///
+/// let options = DeepEqualOptions {
+///     epsilon: 1e-6,
+///     null_as_missing: true,
+///     ..Default::default()
+/// };
+/// let a = json!({"x": 0.1, "y": null});
+/// let b = json!({"x": 0.1_f32});
+/// assert!(deep_equal_with(&a, &b, &options));
+/// ```
+pub fn deep_equal_with(a: &Value, b: &Value, options: &DeepEqualOptions) -> bool {
     // Fast path: pointer identity.
     if std::ptr::eq(a, b) {
         return true;
@@ -31,31 +75,56 @@ pub fn deep_equal(a: &Value, b: &Value) -> bool {
     match (a, b) {
         (Value::Null, Value::Null) => true,
         (Value::Bool(a), Value::Bool(b)) => a == b,
-        (Value::Number(a), Value::Number(b)) => a == b,
-        (Value::String(a), Value::String(b)) => a == b,
-
-        // Arrays
-        (Value::Array(arr_a), Value::Array(arr_b)) => {
-            if arr_a.len() != arr_b.len() {
+        (Value::Number(a), Value::Number(b)) => {
+            if a == b {
+                return true;
+            }
+            if (options.epsilon <= 0.0 && options.max_ulps == 0) || (!a.is_f64() && !b.is_f64()) {
                 return false;
             }
-            for i in 0..arr_a.len() {
-                if !deep_equal(&arr_a[i], &arr_b[i]) {
-                    return false;
+            match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => {
+                    (a - b).abs() <= options.epsilon || ulps_between(a, b) <= options.max_ulps
                 }
+                _ => false,
             }
-            true
+        }
+        (Value::String(a), Value::String(b)) => a == b,
+
+        // Arrays
+        (Value::Array(arr_a), Value::Array(arr_b)) => {
+            arr_a.len() == arr_b.len()
+                && arr_a
+                    .iter()
+                    .zip(arr_b)
+                    .all(|(a, b)| deep_equal_with(a, b, options))
         }
 
         // Objects
         (Value::Object(obj_a), Value::Object(obj_b)) => {
-            if obj_a.len() != obj_b.len() {
+            let present =
+                |(_, val): &(&String, &Value)| !(options.null_as_missing && val.is_null());
+            if options.ordered_objects {
+                let mut iter_b = obj_b.iter().filter(present);
+                for (key, val_a) in obj_a.iter().filter(present) {
+                    match iter_b.next() {
+                        Some((key_b, val_b)) if key == key_b => {
+                            if !deep_equal_with(val_a, val_b, options) {
+                                return false;
+                            }
+                        }
+                        _ => return false,
+                    }
+                }
+                return iter_b.next().is_none();
+            }
+            if obj_a.iter().filter(present).count() != obj_b.iter().filter(present).count() {
                 return false;
             }
-            for (key, val_a) in obj_a {
+            for (key, val_a) in obj_a.iter().filter(present) {
                 match obj_b.get(key) {
                     Some(val_b) => {
-                        if !deep_equal(val_a, val_b) {
+                        if !deep_equal_with(val_a, val_b, options) {
                             return false;
                         }
                     }
@@ -70,6 +139,21 @@ pub fn deep_equal(a: &Value, b: &Value) -> bool {
     }
 }
 
+/// Number of representable `f64` values from `a` to `b`; `0.0` and `-0.0`
+/// are 0 apart.
+fn ulps_between(a: f64, b: f64) -> u64 {
+    // Map the bit patterns onto a line ordered like the floats themselves.
+    fn ordered(x: f64) -> i64 {
+        let bits = x.to_bits() as i64;
+        if bits < 0 {
+            i64::MIN.wrapping_sub(bits)
+        } else {
+            bits
+        }
+    }
+    (ordered(a) as i128 - ordered(b) as i128).unsigned_abs() as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
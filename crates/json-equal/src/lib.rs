@@ -4,8 +4,10 @@
 //! `@jsonjoy.com/util` in json-joy v18.0.0.
 //!
 //! Provides [`deep_equal`] for recursively comparing two [`serde_json::Value`]
-//! instances with strict type checking.
+//! instances with strict type checking, and [`deep_equal_with`] for
+//! comparisons with float tolerance, `null`-as-missing members or ordered
+//! objects.
 
 mod deep_equal;
 
-pub use deep_equal::deep_equal;
+pub use deep_equal::{deep_equal, deep_equal_with, DeepEqualOptions};
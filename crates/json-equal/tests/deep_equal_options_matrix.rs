@@ -0,0 +1,167 @@
+//! `deep_equal_with` relaxations.
+//!
+//! Not an upstream port: upstream `deepEqual` takes no options. Every case
+//! is checked in both argument orders.
+
+use json_joy_json_equal::{deep_equal, deep_equal_with, DeepEqualOptions};
+use serde_json::{json, Value};
+
+fn check(a: &Value, b: &Value, options: &DeepEqualOptions, expected: bool) {
+    assert_eq!(
+        deep_equal_with(a, b, options),
+        expected,
+        "{a} {b} {options:?}"
+    );
+    assert_eq!(
+        deep_equal_with(b, a, options),
+        expected,
+        "{b} {a} {options:?}"
+    );
+}
+
+#[test]
+fn default_options_match_deep_equal_matrix() {
+    let values = [
+        json!(null),
+        json!(1),
+        json!(1.0),
+        json!(0.1),
+        json!("1"),
+        json!([1, null]),
+        json!({"a": 1}),
+        json!({"a": 1, "b": null}),
+        json!({"b": null, "a": 1}),
+    ];
+    for a in &values {
+        for b in &values {
+            assert_eq!(
+                deep_equal_with(a, b, &DeepEqualOptions::default()),
+                deep_equal(a, b),
+                "{a} {b}"
+            );
+        }
+    }
+}
+
+#[test]
+fn float_tolerance_matrix() {
+    let epsilon = DeepEqualOptions {
+        epsilon: 1e-6,
+        ..Default::default()
+    };
+    let ulps = DeepEqualOptions {
+        max_ulps: 4,
+        ..Default::default()
+    };
+    let next = f64::from_bits(0.3f64.to_bits() + 4);
+    let far = f64::from_bits(0.3f64.to_bits() + 5);
+    let cases = [
+        // (a, b, with epsilon, with max_ulps)
+        (json!(0.1 + 0.2), json!(0.3), true, true),
+        (json!(0.3), json!(next), true, true),
+        (json!(0.3), json!(far), true, false),
+        (json!(0.1), json!(0.1f32), true, false),
+        (json!(1.0), json!(1.001), false, false),
+        (json!(1), json!(1.0), true, true),
+        (json!(0.0), json!(-0.0), true, true),
+        (
+            json!(f64::MIN_POSITIVE),
+            json!(-f64::MIN_POSITIVE),
+            true,
+            false,
+        ),
+        (json!(f64::MAX), json!(f64::MIN), false, false),
+        // Integers stay exact.
+        (json!(u64::MAX), json!(u64::MAX - 1), false, false),
+        (json!(-1), json!(1), false, false),
+        (
+            json!([0.1 + 0.2, {"x": 0.7 * 3.0}]),
+            json!([0.3, {"x": 2.1}]),
+            true,
+            true,
+        ),
+        (json!(0.5), json!("0.5"), false, false),
+    ];
+    for (a, b, with_epsilon, with_ulps) in cases {
+        check(&a, &b, &DeepEqualOptions::default(), a == b);
+        check(&a, &b, &epsilon, with_epsilon);
+        check(&a, &b, &ulps, with_ulps);
+    }
+}
+
+#[test]
+fn null_as_missing_matrix() {
+    let options = DeepEqualOptions {
+        null_as_missing: true,
+        ..Default::default()
+    };
+    let cases = [
+        (json!({"a": null}), json!({}), true),
+        (json!({"a": 1, "b": null}), json!({"a": 1, "c": null}), true),
+        (json!({"a": {"b": null}}), json!({"a": {}}), true),
+        (json!([{"a": null}]), json!([{}]), true),
+        (json!({"a": null}), json!({"a": 0}), false),
+        (
+            json!({"a": null, "b": 1}),
+            json!({"c": null, "d": 1}),
+            false,
+        ),
+        // Array slots are kept.
+        (json!([1, null]), json!([1]), false),
+        (json!(null), json!({}), false),
+    ];
+    for (a, b, expected) in cases {
+        check(&a, &b, &DeepEqualOptions::default(), a == b);
+        check(&a, &b, &options, expected);
+    }
+}
+
+#[test]
+fn ordered_objects_matrix() {
+    let ordered = DeepEqualOptions {
+        ordered_objects: true,
+        ..Default::default()
+    };
+    let both = DeepEqualOptions {
+        ordered_objects: true,
+        null_as_missing: true,
+        ..Default::default()
+    };
+    let obj = |entries: &[(&str, Value)]| {
+        Value::Object(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    };
+    let ab = obj(&[("a", json!(1)), ("b", json!(2))]);
+    let ba = obj(&[("b", json!(2)), ("a", json!(1))]);
+    let a_null_b = obj(&[("a", json!(1)), ("x", json!(null)), ("b", json!(2))]);
+    // Without `preserve_order` the map sorts its keys and both orders agree.
+    let distinct = ab
+        .as_object()
+        .unwrap()
+        .keys()
+        .ne(ba.as_object().unwrap().keys());
+
+    check(&ab, &ab.clone(), &ordered, true);
+    check(&ab, &ba, &DeepEqualOptions::default(), true);
+    check(&ab, &ba, &ordered, !distinct);
+    check(
+        &json!([ab.clone()]),
+        &json!([ba.clone()]),
+        &ordered,
+        !distinct,
+    );
+    check(&ab, &a_null_b, &ordered, false);
+    check(&ab, &a_null_b, &both, true);
+    check(&ba, &a_null_b, &both, !distinct);
+    check(&ab, &obj(&[("a", json!(1))]), &ordered, false);
+    check(
+        &ab,
+        &obj(&[("a", json!(1)), ("c", json!(2))]),
+        &ordered,
+        false,
+    );
+}
@@ -0,0 +1,33 @@
+//! Encode/decode throughput of the json-pack codecs on shared corpora.
+//!
+//! `cargo bench -p json-joy-json-pack --bench codecs [-- <filter>]`. Each
+//! benchmark is named `<codec>/<corpus>/<encode|decode>`, and reports
+//! throughput in bytes of encoded output.
+
+mod corpus;
+
+use std::hint::black_box;
+
+use corpus::{codecs, corpora};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+fn bench_codecs(c: &mut Criterion) {
+    let corpora = corpora();
+    for codec in codecs() {
+        for corpus in &corpora {
+            let encoded = (codec.encode)(&corpus.docs);
+            let bytes: usize = encoded.iter().map(Vec::len).sum();
+
+            let mut group = c.benchmark_group(format!("{}/{}", codec.name, corpus.name));
+            group.throughput(Throughput::Bytes(bytes as u64));
+            group.bench_function("encode", |b| {
+                b.iter(|| (codec.encode)(black_box(&corpus.docs)))
+            });
+            group.bench_function("decode", |b| b.iter(|| (codec.decode)(black_box(&encoded))));
+            group.finish();
+        }
+    }
+}
+
+criterion_group!(benches, bench_codecs);
+criterion_main!(benches);
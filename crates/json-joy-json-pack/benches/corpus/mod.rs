@@ -0,0 +1,170 @@
+//! Shared fixture corpora and codec table for the codec benchmarks.
+//!
+//! Also included by `tests/bench_corpus_matrix.rs`, so every benchmarked
+//! codec is checked for stable round trips on the same data in `cargo test`.
+
+use json_joy_json_pack::bencode::{BencodeDecoder, BencodeEncoder};
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoder, CborEncoderFast};
+use json_joy_json_pack::json::{JsonDecoder, JsonEncoder};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoderFast};
+use json_joy_json_pack::ubjson::{UbjsonDecoder, UbjsonEncoder};
+use json_joy_json_pack::PackValue;
+
+/// A named set of documents encoded one at a time.
+pub struct Corpus {
+    pub name: &'static str,
+    pub docs: Vec<PackValue>,
+}
+
+/// An encoder/decoder pair under benchmark.
+///
+/// Each function reuses one encoder/decoder instance for a whole corpus, as
+/// a long-lived caller would.
+pub struct Codec {
+    pub name: &'static str,
+    pub encode: fn(&[PackValue]) -> Vec<Vec<u8>>,
+    pub decode: fn(&[Vec<u8>]) -> Vec<PackValue>,
+}
+
+macro_rules! codec {
+    ($name:literal, $enc:expr, $dec:expr) => {
+        Codec {
+            name: $name,
+            encode: |docs| {
+                let mut enc = $enc;
+                docs.iter().map(|doc| enc.encode(doc)).collect()
+            },
+            decode: |bufs| {
+                #[allow(unused_mut)]
+                let mut dec = $dec;
+                bufs.iter()
+                    .map(|buf| dec.decode(buf).expect(concat!($name, " decode")))
+                    .collect()
+            },
+        }
+    };
+}
+
+pub fn codecs() -> Vec<Codec> {
+    vec![
+        codec!("cbor", CborEncoder::new(), CborDecoder::new()),
+        codec!("cbor_fast", CborEncoderFast::new(), CborDecoder::new()),
+        codec!(
+            "msgpack_fast",
+            MsgPackEncoderFast::new(),
+            MsgPackDecoderFast::new()
+        ),
+        codec!("json", JsonEncoder::new(), JsonDecoder::new()),
+        codec!("ubjson", UbjsonEncoder::new(), UbjsonDecoder::new()),
+        codec!("bencode", BencodeEncoder::new(), BencodeDecoder::new()),
+    ]
+}
+
+pub fn corpora() -> Vec<Corpus> {
+    let mut rng = Lcg(0x5eed);
+    vec![
+        Corpus {
+            name: "small_objects",
+            docs: (0..1000).map(|i| small_object(&mut rng, i)).collect(),
+        },
+        Corpus {
+            name: "large_arrays",
+            docs: (0..4).map(|_| large_array(&mut rng, 10_000)).collect(),
+        },
+        Corpus {
+            name: "string_heavy",
+            docs: (0..200).map(|_| string_heavy(&mut rng)).collect(),
+        },
+        Corpus {
+            name: "binary_heavy",
+            docs: (0..50).map(|_| binary_heavy(&mut rng)).collect(),
+        },
+    ]
+}
+
+/// Deterministic generator so corpora are identical across runs.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+const WORDS: [&str; 8] = [
+    "alpha",
+    "beta",
+    "gamma",
+    "delta",
+    "naïve",
+    "café",
+    "日本語",
+    "emoji😀",
+];
+
+fn text(rng: &mut Lcg, words: usize) -> String {
+    (0..words)
+        .map(|_| WORDS[rng.below(WORDS.len() as u64) as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn small_object(rng: &mut Lcg, i: usize) -> PackValue {
+    PackValue::Object(vec![
+        ("id".into(), PackValue::Integer(i as i64)),
+        ("name".into(), PackValue::Str(text(rng, 2))),
+        ("active".into(), PackValue::Bool(rng.below(2) == 0)),
+        (
+            "score".into(),
+            PackValue::Float(rng.below(10_000) as f64 / 8.0),
+        ),
+        (
+            "tags".into(),
+            PackValue::Array((0..3).map(|_| PackValue::Str(text(rng, 1))).collect()),
+        ),
+        ("parent".into(), PackValue::Null),
+    ])
+}
+
+fn large_array(rng: &mut Lcg, len: usize) -> PackValue {
+    PackValue::Array(
+        (0..len)
+            .map(|i| match i % 4 {
+                0 => PackValue::Integer(rng.below(256) as i64),
+                1 => PackValue::Integer(-(rng.below(1 << 20) as i64)),
+                2 => PackValue::Integer(rng.next() as i64),
+                _ => PackValue::Float(rng.below(1 << 16) as f64 / 4.0),
+            })
+            .collect(),
+    )
+}
+
+fn string_heavy(rng: &mut Lcg) -> PackValue {
+    PackValue::Object(
+        (0..16)
+            .map(|i| {
+                let words = 4 + rng.below(60) as usize;
+                (format!("field_{i}"), PackValue::Str(text(rng, words)))
+            })
+            .collect(),
+    )
+}
+
+fn binary_heavy(rng: &mut Lcg) -> PackValue {
+    PackValue::Array(
+        (0..8)
+            .map(|_| {
+                let len = 256 + rng.below(4096) as usize;
+                PackValue::Bytes((0..len).map(|_| rng.next() as u8).collect())
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,76 @@
+//! Cost of framing a WebSocket data message on the send path.
+//!
+//! `cargo bench -p json-joy-json-pack --bench ws_frames [-- <filter>]`.
+//! Each benchmark is named `ws_frames/<strategy>/<payload size>`:
+//!
+//! - `copy`: header from `encode_data_msg_hdr_fast`, then header and payload
+//!   concatenated into a fresh buffer.
+//! - `into`: `write_data_msg_into` a reused `Writer`, one payload copy.
+//! - `parts`: `data_msg_parts` plus a vectored write into a reused sink.
+
+use std::hint::black_box;
+use std::io::{IoSlice, Write};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use json_joy_buffers::Writer;
+use json_joy_json_pack::ws::WsFrameEncoder;
+
+const SIZES: [usize; 4] = [64, 4 * 1024, 64 * 1024, 1024 * 1024];
+
+/// Accepts writes without keeping the bytes, like a socket.
+struct Sink;
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        Ok(bufs.iter().map(|b| black_box(b.len())).sum())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn bench_ws_frames(c: &mut Criterion) {
+    let mut encoder = WsFrameEncoder::new();
+    let mut out = Writer::new();
+    let mut group = c.benchmark_group("ws_frames");
+
+    for size in SIZES {
+        let payload = vec![0x5au8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_function(BenchmarkId::new("copy", size), |b| {
+            b.iter(|| {
+                let header = encoder.encode_data_msg_hdr_fast(payload.len());
+                let mut frame = Vec::with_capacity(header.len() + payload.len());
+                frame.extend_from_slice(&header);
+                frame.extend_from_slice(black_box(&payload));
+                frame
+            })
+        });
+        group.bench_function(BenchmarkId::new("into", size), |b| {
+            b.iter(|| {
+                encoder.write_data_msg_into(&mut out, black_box(&payload));
+                black_box(&out.uint8[out.x0..out.x]);
+                // The socket took the bytes: rewind to reuse the buffer.
+                out.x = 0;
+                out.x0 = 0;
+            })
+        });
+        group.bench_function(BenchmarkId::new("parts", size), |b| {
+            b.iter(|| {
+                let (header, body) = WsFrameEncoder::data_msg_parts(black_box(&payload));
+                Sink.write_vectored(&[IoSlice::new(&header), IoSlice::new(body)])
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ws_frames);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! Confluent Schema Registry wire format.
+//!
+//! Each message is a zero magic byte, the registry's schema id as a 4-byte
+//! big-endian integer, then the Avro binary encoding of the value. Schemas
+//! are resolved through [`SchemaLookup`], so callers can plug in a registry
+//! client, a cache, or a fixed map. Not part of upstream json-pack.
+
+use std::collections::HashMap;
+
+use super::decoder::AvroDecodeError;
+use super::schema_decoder::AvroSchemaDecoder;
+use super::schema_encoder::{AvroEncodeError, AvroSchemaEncoder};
+use super::types::{AvroSchema, AvroValue};
+
+/// First byte of every Confluent-framed message.
+pub const MAGIC_BYTE: u8 = 0;
+
+/// Length of the magic byte plus schema id.
+pub const HEADER_LEN: usize = 5;
+
+/// Resolves registry schema ids to writer schemas.
+pub trait SchemaLookup {
+    /// Returns the schema registered under `id`, or `None` if unknown.
+    fn schema(&mut self, id: u32) -> Option<AvroSchema>;
+}
+
+impl SchemaLookup for HashMap<u32, AvroSchema> {
+    fn schema(&mut self, id: u32) -> Option<AvroSchema> {
+        self.get(&id).cloned()
+    }
+}
+
+impl<F: FnMut(u32) -> Option<AvroSchema>> SchemaLookup for F {
+    fn schema(&mut self, id: u32) -> Option<AvroSchema> {
+        self(id)
+    }
+}
+
+/// Encodes `value` with `schema` and frames it under `schema_id`.
+pub fn encode(
+    value: &AvroValue,
+    schema: &AvroSchema,
+    schema_id: u32,
+) -> Result<Vec<u8>, AvroEncodeError> {
+    let body = AvroSchemaEncoder::new().encode(value, schema)?;
+    let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+    out.push(MAGIC_BYTE);
+    out.extend_from_slice(&schema_id.to_be_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Splits a framed message into its schema id and the Avro body.
+pub fn read_header(data: &[u8]) -> Result<(u32, &[u8]), AvroDecodeError> {
+    if data.len() < HEADER_LEN {
+        return Err(AvroDecodeError::EndOfInput(data.len()));
+    }
+    if data[0] != MAGIC_BYTE {
+        return Err(AvroDecodeError::InvalidMagicByte(data[0]));
+    }
+    let id = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+    Ok((id, &data[HEADER_LEN..]))
+}
+
+/// Decodes a framed message, resolving its writer schema through `lookup`.
+/// Returns the schema id alongside the value.
+pub fn decode(
+    data: &[u8],
+    lookup: &mut impl SchemaLookup,
+) -> Result<(u32, AvroValue), AvroDecodeError> {
+    let (id, body) = read_header(data)?;
+    let schema = lookup
+        .schema(id)
+        .ok_or(AvroDecodeError::UnknownSchemaId(id))?;
+    let value = AvroSchemaDecoder::new()
+        .decode(body, &schema)
+        .map_err(|err| err.offset_by(HEADER_LEN))?;
+    Ok((id, value))
+}
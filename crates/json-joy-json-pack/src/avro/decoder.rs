@@ -2,19 +2,21 @@
 //!
 //! Upstream reference: `json-pack/src/avro/AvroDecoder.ts`
 
+use crate::util::varint::{read_varint, zigzag_decode32, zigzag_decode64, VarintError};
+
 /// Avro decoding error.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum AvroDecodeError {
-    #[error("unexpected end of input")]
-    EndOfInput,
+    #[error("unexpected end of input at offset {0}")]
+    EndOfInput(usize),
     #[error("invalid schema")]
     InvalidSchema,
-    #[error("variable-length integer is too long")]
-    VarIntTooLong,
-    #[error("variable-length long is too long")]
-    VarLongTooLong,
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("variable-length integer at offset {0} is too long")]
+    VarIntTooLong(usize),
+    #[error("variable-length long at offset {0} is too long")]
+    VarLongTooLong(usize),
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
     #[error("negative array/map count")]
     NegativeCount,
     #[error("invalid key")]
@@ -23,12 +25,57 @@ pub enum AvroDecodeError {
     InvalidEnumIndex(i32),
     #[error("union index out of range")]
     UnionIndexOutOfRange,
+    #[error("invalid single-object marker")]
+    InvalidSingleObjectMarker,
+    #[error("unknown schema fingerprint: {0:#018x}")]
+    UnknownFingerprint(u64),
+    #[error("invalid magic byte: {0:#04x}")]
+    InvalidMagicByte(u8),
+    #[error("unknown schema id: {0}")]
+    UnknownSchemaId(u32),
+    #[error("invalid {0} logical value")]
+    InvalidLogicalValue(&'static str),
+    #[error("invalid block size: {0}")]
+    InvalidBlockSize(i64),
+}
+
+impl AvroDecodeError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::EndOfInput(x)
+            | Self::VarIntTooLong(x)
+            | Self::VarLongTooLong(x)
+            | Self::InvalidUtf8(x) => Some(*x),
+            _ => None,
+        }
+    }
+    /// Moves the offset `n` bytes on, for a body decoded after a header.
+    pub(crate) fn offset_by(self, n: usize) -> Self {
+        match self {
+            Self::EndOfInput(x) => Self::EndOfInput(x + n),
+            Self::VarIntTooLong(x) => Self::VarIntTooLong(x + n),
+            Self::VarLongTooLong(x) => Self::VarLongTooLong(x + n),
+            Self::InvalidUtf8(x) => Self::InvalidUtf8(x + n),
+            err => err,
+        }
+    }
+}
+
+/// Options for [`AvroDecoder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AvroDecoderOptions {
+    /// Reads array and map block counts as zigzag longs, as the Avro spec
+    /// does, instead of upstream's unsigned varints. A negative count is
+    /// followed by the block's byte size.
+    pub signed_counts: bool,
 }
 
 /// Apache Avro primitive decoder (schema-free).
 pub struct AvroDecoder {
     data: Vec<u8>,
     pos: usize,
+    pub options: AvroDecoderOptions,
 }
 
 impl Default for AvroDecoder {
@@ -39,9 +86,14 @@ impl Default for AvroDecoder {
 
 impl AvroDecoder {
     pub fn new() -> Self {
+        Self::with_options(AvroDecoderOptions::default())
+    }
+
+    pub fn with_options(options: AvroDecoderOptions) -> Self {
         Self {
             data: Vec::new(),
             pos: 0,
+            options,
         }
     }
 
@@ -54,7 +106,7 @@ impl AvroDecoder {
 
     fn read_byte(&mut self) -> Result<u8, AvroDecodeError> {
         if self.pos >= self.data.len() {
-            return Err(AvroDecodeError::EndOfInput);
+            return Err(AvroDecodeError::EndOfInput(self.pos));
         }
         let b = self.data[self.pos];
         self.pos += 1;
@@ -63,55 +115,63 @@ impl AvroDecoder {
 
     fn read_bytes_raw(&mut self, n: usize) -> Result<Vec<u8>, AvroDecodeError> {
         if self.pos + n > self.data.len() {
-            return Err(AvroDecodeError::EndOfInput);
+            return Err(AvroDecodeError::EndOfInput(self.pos));
         }
         let bytes = self.data[self.pos..self.pos + n].to_vec();
         self.pos += n;
         Ok(bytes)
     }
 
+    fn skip(&mut self, n: usize) -> Result<(), AvroDecodeError> {
+        if n > self.data.len() - self.pos {
+            return Err(AvroDecodeError::EndOfInput(self.pos));
+        }
+        self.pos += n;
+        Ok(())
+    }
+
     // ---------------------------------------------------------------- varint
 
     /// Reads a variable-length unsigned integer (max 10 bytes for 64-bit long).
     pub fn read_varint_u64(&mut self) -> Result<u64, AvroDecodeError> {
-        let mut result: u64 = 0;
-        let mut shift = 0u32;
-        for _ in 0..10 {
-            let b = self.read_byte()? as u64;
-            result |= (b & 0x7f) << shift;
-            if b & 0x80 == 0 {
-                return Ok(result);
-            }
-            shift += 7;
-        }
-        Err(AvroDecodeError::VarLongTooLong)
+        self.read_varint(10, AvroDecodeError::VarLongTooLong)
     }
 
     /// Reads a variable-length unsigned integer (max 5 bytes for 32-bit int/length).
     pub fn read_varint_u32(&mut self) -> Result<u32, AvroDecodeError> {
-        let mut result: u32 = 0;
-        let mut shift = 0u32;
-        for _ in 0..5 {
-            let b = self.read_byte()? as u32;
-            result |= (b & 0x7f) << shift;
-            if b & 0x80 == 0 {
-                return Ok(result);
+        Ok(self.read_varint(5, AvroDecodeError::VarIntTooLong)? as u32)
+    }
+
+    fn read_varint(
+        &mut self,
+        max_bytes: usize,
+        too_long: fn(usize) -> AvroDecodeError,
+    ) -> Result<u64, AvroDecodeError> {
+        match read_varint(&self.data, self.pos, max_bytes) {
+            Ok((value, len)) => {
+                self.pos += len;
+                Ok(value)
+            }
+            Err(VarintError::UnexpectedEof) => {
+                self.pos = self.data.len();
+                Err(AvroDecodeError::EndOfInput(self.pos))
+            }
+            Err(VarintError::TooLong) => {
+                let start = self.pos;
+                self.pos += max_bytes;
+                Err(too_long(start))
             }
-            shift += 7;
         }
-        Err(AvroDecodeError::VarIntTooLong)
     }
 
     /// Reads a zigzag-decoded signed integer (Avro int).
     pub fn read_int(&mut self) -> Result<i32, AvroDecodeError> {
-        let encoded = self.read_varint_u32()?;
-        Ok(((encoded >> 1) as i32) ^ -((encoded & 1) as i32))
+        Ok(zigzag_decode32(self.read_varint_u32()?))
     }
 
     /// Reads a zigzag-decoded signed long (Avro long).
     pub fn read_long(&mut self) -> Result<i64, AvroDecodeError> {
-        let encoded = self.read_varint_u64()?;
-        Ok(((encoded >> 1) as i64) ^ -((encoded & 1) as i64))
+        Ok(zigzag_decode64(self.read_varint_u64()?))
     }
 
     // ---------------------------------------------------------------- primitives
@@ -129,11 +189,11 @@ impl AvroDecoder {
 
     pub fn read_double(&mut self) -> Result<f64, AvroDecodeError> {
         if self.pos + 8 > self.data.len() {
-            return Err(AvroDecodeError::EndOfInput);
+            return Err(AvroDecodeError::EndOfInput(self.pos));
         }
         let bytes: [u8; 8] = self.data[self.pos..self.pos + 8]
             .try_into()
-            .map_err(|_| AvroDecodeError::EndOfInput)?;
+            .map_err(|_| AvroDecodeError::EndOfInput(self.pos))?;
         self.pos += 8;
         Ok(f64::from_le_bytes(bytes))
     }
@@ -145,7 +205,8 @@ impl AvroDecoder {
 
     pub fn read_string(&mut self) -> Result<String, AvroDecodeError> {
         let bytes = self.read_bytes()?;
-        String::from_utf8(bytes).map_err(|_| AvroDecodeError::InvalidUtf8)
+        let start = self.pos - bytes.len();
+        String::from_utf8(bytes).map_err(|_| AvroDecodeError::InvalidUtf8(start))
     }
 
     #[inline]
@@ -161,20 +222,55 @@ impl AvroDecoder {
         self.read_bytes_raw(size)
     }
 
+    /// Reads an array/map block header: the item count and, for blocks
+    /// written with a negative count, the byte size. Returns `None` at the
+    /// terminating zero count.
+    pub fn read_block_header(&mut self) -> Result<Option<(usize, Option<usize>)>, AvroDecodeError> {
+        if !self.options.signed_counts {
+            let count = self.read_varint_u32()? as usize;
+            return Ok((count != 0).then_some((count, None)));
+        }
+        let count = self.read_long()?;
+        if count >= 0 {
+            return Ok((count != 0).then_some((count as usize, None)));
+        }
+        let size = self.read_long()?;
+        if size < 0 {
+            return Err(AvroDecodeError::InvalidBlockSize(size));
+        }
+        Ok(Some((count.unsigned_abs() as usize, Some(size as usize))))
+    }
+
+    /// Checks that a block starting at `start` spans its declared size.
+    pub(crate) fn end_block(
+        &self,
+        start: usize,
+        size: Option<usize>,
+    ) -> Result<(), AvroDecodeError> {
+        match size {
+            Some(size) if self.pos - start != size => {
+                Err(AvroDecodeError::InvalidBlockSize(size as i64))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
     /// Reads an array block — returns items in a loop until count = 0.
     pub fn read_array<T, F>(&mut self, mut item_reader: F) -> Result<Vec<T>, AvroDecodeError>
     where
         F: FnMut(&mut Self) -> Result<T, AvroDecodeError>,
     {
         let mut result = Vec::new();
-        loop {
-            let count = self.read_varint_u32()? as usize;
-            if count == 0 {
-                break;
-            }
+        while let Some((count, size)) = self.read_block_header()? {
+            let start = self.pos;
             for _ in 0..count {
                 result.push(item_reader(self)?);
             }
+            self.end_block(start, size)?;
         }
         Ok(result)
     }
@@ -187,22 +283,45 @@ impl AvroDecoder {
     where
         F: FnMut(&mut Self) -> Result<T, AvroDecodeError>,
     {
-        let mut result = Vec::new();
-        loop {
-            let count = self.read_varint_u32()? as usize;
-            if count == 0 {
-                break;
+        self.read_array(|dec| {
+            let key = dec.read_str()?;
+            if key == "__proto__" {
+                return Err(AvroDecodeError::InvalidKey);
             }
-            for _ in 0..count {
-                let key = self.read_str()?;
-                if key == "__proto__" {
-                    return Err(AvroDecodeError::InvalidKey);
+            Ok((key, value_reader(dec)?))
+        })
+    }
+
+    /// Skips an array. Blocks with a byte size are jumped over without
+    /// decoding; items of other blocks are passed to `skip_item`.
+    pub fn skip_array<F>(&mut self, mut skip_item: F) -> Result<(), AvroDecodeError>
+    where
+        F: FnMut(&mut Self) -> Result<(), AvroDecodeError>,
+    {
+        while let Some((count, size)) = self.read_block_header()? {
+            match size {
+                Some(size) => self.skip(size)?,
+                None => {
+                    for _ in 0..count {
+                        skip_item(self)?;
+                    }
                 }
-                let val = value_reader(self)?;
-                result.push((key, val));
             }
         }
-        Ok(result)
+        Ok(())
+    }
+
+    /// Skips a map, as [`skip_array`](Self::skip_array) does; `skip_value`
+    /// is called after each key of a block without a byte size.
+    pub fn skip_map<F>(&mut self, mut skip_value: F) -> Result<(), AvroDecodeError>
+    where
+        F: FnMut(&mut Self) -> Result<(), AvroDecodeError>,
+    {
+        self.skip_array(|dec| {
+            let len = dec.read_varint_u32()? as usize;
+            dec.skip(len)?;
+            skip_value(dec)
+        })
     }
 
     /// Reads a union index.
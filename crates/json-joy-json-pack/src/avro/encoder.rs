@@ -10,14 +10,30 @@
 //! - double: 8 bytes IEEE 754 little-endian
 //! - bytes/string: varint(length) + raw bytes
 //! - array/map: varint(count) + items + varint(0)
+//!
+//! Counts are unsigned varints, as upstream writes them. With
+//! [`AvroEncoderOptions::block_sizes`] counts are zigzag longs as in the Avro
+//! spec, and each block is written with a negative count followed by its
+//! byte size so readers can skip it.
 
 use json_joy_buffers::Writer;
 
+use crate::checked::{self, EncodeLossError};
+use crate::util::varint::{write_varint, zigzag_encode32, zigzag_encode64};
 use crate::PackValue;
 
+/// Options for [`AvroEncoder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AvroEncoderOptions {
+    /// Writes array and map blocks as `long(-count) long(size) items`.
+    /// Decode with [`AvroDecoderOptions::signed_counts`](super::AvroDecoderOptions).
+    pub block_sizes: bool,
+}
+
 /// Apache Avro encoder (schema-free).
 pub struct AvroEncoder {
     pub writer: Writer,
+    pub options: AvroEncoderOptions,
 }
 
 impl Default for AvroEncoder {
@@ -28,8 +44,13 @@ impl Default for AvroEncoder {
 
 impl AvroEncoder {
     pub fn new() -> Self {
+        Self::with_options(AvroEncoderOptions::default())
+    }
+
+    pub fn with_options(options: AvroEncoderOptions) -> Self {
         Self {
             writer: Writer::new(),
+            options,
         }
     }
 
@@ -37,40 +58,22 @@ impl AvroEncoder {
 
     /// Writes a zigzag-encoded signed integer as a varint.
     pub fn write_int(&mut self, n: i32) {
-        let encoded = ((n << 1) ^ (n >> 31)) as u32;
-        self.write_varint_u64(encoded as u64);
+        self.write_varint_u32(zigzag_encode32(n));
     }
 
     /// Writes a zigzag-encoded signed long as a varint.
     pub fn write_long(&mut self, n: i64) {
-        let encoded = ((n << 1) ^ (n >> 63)) as u64;
-        self.write_varint_u64(encoded);
+        self.write_varint_u64(zigzag_encode64(n));
     }
 
     /// Writes a variable-length unsigned integer (no zigzag).
-    pub fn write_varint_u64(&mut self, mut n: u64) {
-        loop {
-            let low7 = (n & 0x7f) as u8;
-            n >>= 7;
-            if n == 0 {
-                self.writer.u8(low7);
-                return;
-            }
-            self.writer.u8(low7 | 0x80);
-        }
+    pub fn write_varint_u64(&mut self, n: u64) {
+        write_varint(&mut self.writer, n);
     }
 
     /// Writes a variable-length unsigned 32-bit integer.
-    pub fn write_varint_u32(&mut self, mut n: u32) {
-        loop {
-            let low7 = (n & 0x7f) as u8;
-            n >>= 7;
-            if n == 0 {
-                self.writer.u8(low7);
-                return;
-            }
-            self.writer.u8(low7 | 0x80);
-        }
+    pub fn write_varint_u32(&mut self, n: u32) {
+        write_varint(&mut self.writer, n as u64);
     }
 
     // ---------------------------------------------------------------- primitives
@@ -84,13 +87,11 @@ impl AvroEncoder {
     }
 
     pub fn write_float(&mut self, f: f32) {
-        let bits = f.to_bits().to_le_bytes();
-        self.writer.buf(&bits);
+        self.writer.f32_le(f);
     }
 
     pub fn write_double(&mut self, f: f64) {
-        let bits = f.to_bits().to_le_bytes();
-        self.writer.buf(&bits);
+        self.writer.f64_le(f);
     }
 
     pub fn write_bytes(&mut self, data: &[u8]) {
@@ -110,8 +111,11 @@ impl AvroEncoder {
     }
 
     /// Writes an array block: varint(count) + items + varint(0).
+    ///
+    /// The block carries no byte size, even with
+    /// [`AvroEncoderOptions::block_sizes`].
     pub fn write_array_start(&mut self, count: usize) {
-        self.write_varint_u32(count as u32);
+        self.write_block_count(count);
     }
 
     pub fn write_array_end(&mut self) {
@@ -119,13 +123,74 @@ impl AvroEncoder {
     }
 
     pub fn write_map_start(&mut self, count: usize) {
-        self.write_varint_u32(count as u32);
+        self.write_block_count(count);
     }
 
     pub fn write_map_end(&mut self) {
         self.write_varint_u32(0);
     }
 
+    fn write_block_count(&mut self, count: usize) {
+        if self.options.block_sizes {
+            self.write_long(count as i64);
+        } else {
+            self.write_varint_u32(count as u32);
+        }
+    }
+
+    /// Starts a block of `count` items, returning the mark to pass to
+    /// [`end_block`](Self::end_block). With block sizes an empty array/map
+    /// is the terminating zero alone, not upstream's two zeros; otherwise
+    /// the count is written here and one byte is reserved for the size.
+    pub(crate) fn begin_block(&mut self, count: usize) -> usize {
+        if !self.options.block_sizes {
+            self.write_varint_u32(count as u32);
+        } else if count > 0 {
+            self.write_long(-(count as i64));
+        }
+        let mark = self.writer.x - self.writer.x0;
+        if self.options.block_sizes && count > 0 {
+            self.writer.u8(0);
+        }
+        mark
+    }
+
+    /// Ends a block, filling in the byte size reserved by
+    /// [`begin_block`](Self::begin_block) when sizes are enabled. The items
+    /// only move when the size needs more than the one reserved byte.
+    pub(crate) fn end_block(&mut self, count: usize, mark: usize) {
+        if self.options.block_sizes && count > 0 {
+            let size = self.writer.x - (self.writer.x0 + mark + 1);
+            let mut n = zigzag_encode64(size as i64);
+            let width = (64 - (n | 1).leading_zeros() as usize).div_ceil(7);
+            if width > 1 {
+                // May move the buffer, so the slot is located afterwards.
+                self.writer.ensure_capacity(width - 1);
+                let items = self.writer.x0 + mark + 1;
+                self.writer
+                    .uint8
+                    .copy_within(items..self.writer.x, items + width - 1);
+                self.writer.x += width - 1;
+            }
+            let slot = self.writer.x0 + mark;
+            for byte in &mut self.writer.uint8[slot..slot + width] {
+                *byte = (n & 0x7f) as u8 | if n >= 0x80 { 0x80 } else { 0 };
+                n >>= 7;
+            }
+        }
+        self.write_varint_u32(0);
+    }
+
+    /// Encodes `value` with [`write_any`](Self::write_any), failing instead
+    /// on a value it would degrade: an integer past the 64-bit long,
+    /// `undefined`, an extension or a blob written as null, or a map key
+    /// with no text form.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::avro_loss, checked::Keys::Text)?;
+        self.write_any(value);
+        Ok(self.writer.flush())
+    }
+
     /// Writes any [`PackValue`] using type inference.
     pub fn write_any(&mut self, value: &PackValue) {
         match value {
@@ -147,22 +212,34 @@ impl AvroEncoder {
             }
             PackValue::Float(f) => self.write_double(*f),
             PackValue::BigInt(n) => self.write_long(*n as i64),
+            PackValue::BigNum(n) => match n.to_i128().and_then(|v| i64::try_from(v).ok()) {
+                Some(v) => self.write_long(v),
+                None => self.write_null(),
+            },
             PackValue::Str(s) => self.write_str(s),
             PackValue::Bytes(b) => self.write_bytes(b),
             PackValue::Array(arr) => {
-                self.write_varint_u32(arr.len() as u32);
+                let mark = self.begin_block(arr.len());
                 for item in arr {
                     self.write_any(item);
                 }
-                self.write_varint_u32(0);
+                self.end_block(arr.len(), mark);
             }
             PackValue::Object(obj) => {
-                self.write_varint_u32(obj.len() as u32);
+                let mark = self.begin_block(obj.len());
                 for (key, val) in obj {
                     self.write_str(key);
                     self.write_any(val);
                 }
-                self.write_varint_u32(0);
+                self.end_block(obj.len(), mark);
+            }
+            PackValue::Map(map) => {
+                let mark = self.begin_block(map.len());
+                for (key, val) in map {
+                    self.write_str(&key.to_key_string());
+                    self.write_any(val);
+                }
+                self.end_block(map.len(), mark);
             }
             PackValue::Extension(_) | PackValue::Blob(_) => self.write_null(),
         }
@@ -0,0 +1,227 @@
+//! Avro schema Parsing Canonical Form and schema fingerprints.
+//!
+//! Reference: Apache Avro 1.12.0 specification, "Parsing Canonical Form for
+//! Schemas" and "Schema Fingerprints".
+//!
+//! Not part of upstream json-pack, which has no single-object encoding.
+
+use std::collections::HashSet;
+
+use super::types::AvroSchema;
+use crate::hash::sha256;
+
+/// CRC-64-AVRO initial value, also the fingerprint of no input.
+const EMPTY64: u64 = 0xc15d_213a_a4d7_a795;
+
+/// Fingerprinting algorithm applied to a schema's Parsing Canonical Form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AvroFingerprintAlgorithm {
+    /// 64-bit Rabin fingerprint (CRC-64-AVRO), used by single-object
+    /// encoding.
+    #[default]
+    Rabin,
+    /// SHA-256 digest, for registries that key schemas by a wider hash.
+    Sha256,
+}
+
+/// Returns the Parsing Canonical Form of `schema`: the JSON text two
+/// schemas share when they read data identically.
+///
+/// Names are fully qualified (a nested name without a namespace inherits
+/// the enclosing one), only attributes that affect parsing are kept, and a
+/// named type is written in full on first use and by name afterwards.
+///
+/// ```
+/// use json_joy_json_pack::avro::{parsing_canonical_form, AvroSchema};
+///
+/// let schema = AvroSchema::Array { items: Box::new(AvroSchema::Int) };
+/// assert_eq!(parsing_canonical_form(&schema), r#"{"type":"array","items":"int"}"#);
+/// ```
+pub fn parsing_canonical_form(schema: &AvroSchema) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, schema, None, &mut HashSet::new());
+    out
+}
+
+/// CRC-64-AVRO fingerprint of the schema's Parsing Canonical Form.
+pub fn fingerprint64(schema: &AvroSchema) -> u64 {
+    crc64_avro(parsing_canonical_form(schema).as_bytes())
+}
+
+/// SHA-256 fingerprint of the schema's Parsing Canonical Form.
+pub fn fingerprint_sha256(schema: &AvroSchema) -> [u8; 32] {
+    sha256(parsing_canonical_form(schema).as_bytes())
+}
+
+/// Fingerprint bytes for `algorithm`. Rabin fingerprints are little-endian,
+/// as in single-object encoding.
+pub fn fingerprint(schema: &AvroSchema, algorithm: AvroFingerprintAlgorithm) -> Vec<u8> {
+    match algorithm {
+        AvroFingerprintAlgorithm::Rabin => fingerprint64(schema).to_le_bytes().to_vec(),
+        AvroFingerprintAlgorithm::Sha256 => fingerprint_sha256(schema).to_vec(),
+    }
+}
+
+/// The 64-bit Rabin fingerprint (CRC-64-AVRO) of `data`.
+pub fn crc64_avro(data: &[u8]) -> u64 {
+    let mut fp = EMPTY64;
+    for &b in data {
+        fp = (fp >> 8) ^ CRC64_TABLE[((fp ^ b as u64) & 0xff) as usize];
+    }
+    fp
+}
+
+static CRC64_TABLE: [u64; 256] = crc64_table();
+
+const fn crc64_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut fp = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            fp = (fp >> 1) ^ (EMPTY64 & (fp & 1).wrapping_neg());
+            j += 1;
+        }
+        table[i] = fp;
+        i += 1;
+    }
+    table
+}
+
+fn write_canonical(
+    out: &mut String,
+    schema: &AvroSchema,
+    namespace: Option<&str>,
+    seen: &mut HashSet<String>,
+) {
+    match schema {
+        AvroSchema::Null => out.push_str("\"null\""),
+        AvroSchema::Boolean => out.push_str("\"boolean\""),
+        AvroSchema::Int => out.push_str("\"int\""),
+        AvroSchema::Long => out.push_str("\"long\""),
+        AvroSchema::Float => out.push_str("\"float\""),
+        AvroSchema::Double => out.push_str("\"double\""),
+        AvroSchema::Bytes => out.push_str("\"bytes\""),
+        AvroSchema::String => out.push_str("\"string\""),
+        AvroSchema::Ref(name) => {
+            let primitive = matches!(
+                name.as_str(),
+                "null" | "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string"
+            );
+            if primitive {
+                write_json_str(out, name);
+            } else {
+                write_json_str(out, &full_name(name, None, namespace));
+            }
+        }
+        // Logical type annotations do not affect parsing.
+        AvroSchema::Logical { schema, .. } => write_canonical(out, schema, namespace, seen),
+        AvroSchema::Array { items } => {
+            out.push_str("{\"type\":\"array\",\"items\":");
+            write_canonical(out, items, namespace, seen);
+            out.push('}');
+        }
+        AvroSchema::Map { values } => {
+            out.push_str("{\"type\":\"map\",\"values\":");
+            write_canonical(out, values, namespace, seen);
+            out.push('}');
+        }
+        AvroSchema::Union(schemas) => {
+            out.push('[');
+            for (i, s) in schemas.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, s, namespace, seen);
+            }
+            out.push(']');
+        }
+        AvroSchema::Record {
+            name,
+            namespace: ns,
+            fields,
+            ..
+        } => {
+            let full = full_name(name, ns.as_deref(), namespace);
+            if !seen.insert(full.clone()) {
+                write_json_str(out, &full);
+                return;
+            }
+            out.push_str("{\"name\":");
+            write_json_str(out, &full);
+            out.push_str(",\"type\":\"record\",\"fields\":[");
+            let inner = enclosing_namespace(&full);
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str("{\"name\":");
+                write_json_str(out, &field.name);
+                out.push_str(",\"type\":");
+                write_canonical(out, &field.type_, inner, seen);
+                out.push('}');
+            }
+            out.push_str("]}");
+        }
+        AvroSchema::Enum {
+            name,
+            namespace: ns,
+            symbols,
+            ..
+        } => {
+            let full = full_name(name, ns.as_deref(), namespace);
+            if !seen.insert(full.clone()) {
+                write_json_str(out, &full);
+                return;
+            }
+            out.push_str("{\"name\":");
+            write_json_str(out, &full);
+            out.push_str(",\"type\":\"enum\",\"symbols\":[");
+            for (i, symbol) in symbols.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_str(out, symbol);
+            }
+            out.push_str("]}");
+        }
+        AvroSchema::Fixed {
+            name,
+            namespace: ns,
+            size,
+            ..
+        } => {
+            let full = full_name(name, ns.as_deref(), namespace);
+            if !seen.insert(full.clone()) {
+                write_json_str(out, &full);
+                return;
+            }
+            out.push_str("{\"name\":");
+            write_json_str(out, &full);
+            out.push_str(",\"type\":\"fixed\",\"size\":");
+            out.push_str(&size.to_string());
+            out.push('}');
+        }
+    }
+}
+
+/// Resolves a name against its own namespace, falling back to the
+/// enclosing one. Names containing a dot are already full names.
+fn full_name(name: &str, namespace: Option<&str>, enclosing: Option<&str>) -> String {
+    if name.contains('.') {
+        return name.to_string();
+    }
+    match namespace.or(enclosing) {
+        Some(ns) if !ns.is_empty() => format!("{ns}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+fn enclosing_namespace(full_name: &str) -> Option<&str> {
+    full_name.rsplit_once('.').map(|(ns, _)| ns)
+}
+
+fn write_json_str(out: &mut String, s: &str) {
+    out.push_str(&serde_json::to_string(s).unwrap_or_default());
+}
@@ -3,16 +3,27 @@
 //! Upstream reference: `json-pack/src/avro/`
 //! Reference: Apache Avro 1.12.0 specification
 
+pub mod confluent;
 pub mod decoder;
 pub mod encoder;
+pub mod fingerprint;
 pub mod schema_decoder;
 pub mod schema_encoder;
 pub mod schema_validator;
+pub mod single_object;
 pub mod types;
 
-pub use decoder::{AvroDecodeError, AvroDecoder};
-pub use encoder::AvroEncoder;
+pub use decoder::{AvroDecodeError, AvroDecoder, AvroDecoderOptions};
+pub use encoder::{AvroEncoder, AvroEncoderOptions};
+pub use fingerprint::{
+    crc64_avro, fingerprint, fingerprint64, fingerprint_sha256, parsing_canonical_form,
+    AvroFingerprintAlgorithm,
+};
 pub use schema_decoder::AvroSchemaDecoder;
 pub use schema_encoder::{AvroEncodeError, AvroSchemaEncoder};
 pub use schema_validator::AvroSchemaValidator;
-pub use types::{AvroField, AvroSchema, AvroValue};
+pub use single_object::{
+    decode_single_object, encode_single_object, read_single_object_header,
+    AvroSingleObjectRegistry, SINGLE_OBJECT_MARKER,
+};
+pub use types::{AvroField, AvroLogicalType, AvroSchema, AvroValue};
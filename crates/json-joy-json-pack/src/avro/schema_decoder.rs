@@ -4,9 +4,10 @@
 
 use std::collections::HashMap;
 
-use super::decoder::{AvroDecodeError, AvroDecoder};
+use super::decoder::{AvroDecodeError, AvroDecoder, AvroDecoderOptions};
 use super::schema_validator::AvroSchemaValidator;
-use super::types::{AvroSchema, AvroValue};
+use super::types::{is_valid_uuid, uuid_from_bytes, AvroLogicalType, AvroSchema, AvroValue};
+use crate::JsonPackBigInt;
 
 /// Apache Avro schema-aware decoder.
 pub struct AvroSchemaDecoder {
@@ -23,8 +24,12 @@ impl Default for AvroSchemaDecoder {
 
 impl AvroSchemaDecoder {
     pub fn new() -> Self {
+        Self::with_options(AvroDecoderOptions::default())
+    }
+
+    pub fn with_options(options: AvroDecoderOptions) -> Self {
         Self {
-            decoder: AvroDecoder::new(),
+            decoder: AvroDecoder::with_options(options),
             validator: AvroSchemaValidator::new(),
             named: HashMap::new(),
         }
@@ -66,6 +71,12 @@ impl AvroSchemaDecoder {
                     self.collect_named(s);
                 }
             }
+            AvroSchema::Logical { schema: base, .. } => {
+                self.collect_named(base);
+                if let Some(name) = base.full_name() {
+                    self.named.insert(name, schema.clone());
+                }
+            }
             _ => {}
         }
     }
@@ -120,21 +131,58 @@ impl AvroSchemaDecoder {
                     value: Box::new(val),
                 })
             }
-            AvroSchema::Ref(_) => Err(AvroDecodeError::EndOfInput), // unresolved ref
+            AvroSchema::Logical {
+                logical_type,
+                schema,
+            } => self.read_logical(logical_type, schema),
+            AvroSchema::Ref(_) => Err(AvroDecodeError::EndOfInput(self.decoder.pos())), // unresolved ref
+        }
+    }
+
+    /// Reads the underlying type and converts it to the logical value. An
+    /// annotation that does not apply yields the underlying value.
+    fn read_logical(
+        &mut self,
+        logical_type: &AvroLogicalType,
+        base: &AvroSchema,
+    ) -> Result<AvroValue, AvroDecodeError> {
+        let applies = logical_type.applies_to(self.resolve(base));
+        let raw = self.read_value(base)?;
+        if !applies {
+            return Ok(raw);
         }
+        Ok(match (logical_type, raw) {
+            (AvroLogicalType::Decimal { scale, .. }, AvroValue::Bytes(b) | AvroValue::Fixed(b)) => {
+                AvroValue::Decimal {
+                    unscaled: JsonPackBigInt::from_twos_complement(&b),
+                    scale: *scale,
+                }
+            }
+            (AvroLogicalType::Uuid, AvroValue::Str(s)) => {
+                if !is_valid_uuid(&s) {
+                    return Err(AvroDecodeError::InvalidLogicalValue("uuid"));
+                }
+                AvroValue::Uuid(s)
+            }
+            (AvroLogicalType::Uuid, AvroValue::Fixed(b)) => AvroValue::Uuid(uuid_from_bytes(&b)),
+            (AvroLogicalType::Date, AvroValue::Int(n)) => AvroValue::Date(n),
+            (AvroLogicalType::TimeMillis, AvroValue::Int(n)) => AvroValue::TimeMillis(n),
+            (AvroLogicalType::TimeMicros, AvroValue::Long(n)) => AvroValue::TimeMicros(n),
+            (AvroLogicalType::TimestampMillis, AvroValue::Long(n)) => AvroValue::TimestampMillis(n),
+            (AvroLogicalType::TimestampMicros, AvroValue::Long(n)) => AvroValue::TimestampMicros(n),
+            (_, raw) => raw,
+        })
     }
 
     fn read_array_value(&mut self, item_schema: &AvroSchema) -> Result<AvroValue, AvroDecodeError> {
         let item_schema = item_schema.clone();
         let mut items = Vec::new();
-        loop {
-            let count = self.decoder.read_varint_u32()? as usize;
-            if count == 0 {
-                break;
-            }
+        while let Some((count, size)) = self.decoder.read_block_header()? {
+            let start = self.decoder.pos();
             for _ in 0..count {
                 items.push(self.read_value(&item_schema)?);
             }
+            self.decoder.end_block(start, size)?;
         }
         Ok(AvroValue::Array(items))
     }
@@ -142,11 +190,8 @@ impl AvroSchemaDecoder {
     fn read_map_value(&mut self, value_schema: &AvroSchema) -> Result<AvroValue, AvroDecodeError> {
         let value_schema = value_schema.clone();
         let mut entries = Vec::new();
-        loop {
-            let count = self.decoder.read_varint_u32()? as usize;
-            if count == 0 {
-                break;
-            }
+        while let Some((count, size)) = self.decoder.read_block_header()? {
+            let start = self.decoder.pos();
             for _ in 0..count {
                 let key = self.decoder.read_str()?;
                 if key == "__proto__" {
@@ -154,6 +199,7 @@ impl AvroSchemaDecoder {
                 }
                 entries.push((key, self.read_value(&value_schema)?));
             }
+            self.decoder.end_block(start, size)?;
         }
         Ok(AvroValue::Map(entries))
     }
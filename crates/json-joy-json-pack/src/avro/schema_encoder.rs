@@ -4,9 +4,9 @@
 
 use std::collections::HashMap;
 
-use super::encoder::AvroEncoder;
+use super::encoder::{AvroEncoder, AvroEncoderOptions};
 use super::schema_validator::AvroSchemaValidator;
-use super::types::{AvroSchema, AvroValue};
+use super::types::{uuid_to_bytes, AvroLogicalType, AvroSchema, AvroValue};
 
 /// Avro encoding error.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -46,8 +46,12 @@ impl Default for AvroSchemaEncoder {
 
 impl AvroSchemaEncoder {
     pub fn new() -> Self {
+        Self::with_options(AvroEncoderOptions::default())
+    }
+
+    pub fn with_options(options: AvroEncoderOptions) -> Self {
         Self {
-            encoder: AvroEncoder::new(),
+            encoder: AvroEncoder::with_options(options),
             validator: AvroSchemaValidator::new(),
             named: HashMap::new(),
         }
@@ -93,6 +97,13 @@ impl AvroSchemaEncoder {
                     self.collect_named(s);
                 }
             }
+            AvroSchema::Logical { schema: base, .. } => {
+                self.collect_named(base);
+                // References to an annotated named type keep the annotation.
+                if let Some(name) = base.full_name() {
+                    self.named.insert(name, schema.clone());
+                }
+            }
             _ => {}
         }
     }
@@ -196,22 +207,22 @@ impl AvroSchemaEncoder {
                 Ok(())
             }
             (AvroSchema::Array { items }, AvroValue::Array(arr)) => {
-                self.encoder.write_varint_u32(arr.len() as u32);
+                let mark = self.encoder.begin_block(arr.len());
                 let items = items.as_ref().clone();
                 for item in arr {
                     self.write_value(item, &items)?;
                 }
-                self.encoder.write_varint_u32(0);
+                self.encoder.end_block(arr.len(), mark);
                 Ok(())
             }
             (AvroSchema::Map { values }, AvroValue::Map(map)) => {
-                self.encoder.write_varint_u32(map.len() as u32);
+                let mark = self.encoder.begin_block(map.len());
                 let values = values.as_ref().clone();
                 for (key, val) in map {
                     self.encoder.write_str(key);
                     self.write_value(val, &values)?;
                 }
-                self.encoder.write_varint_u32(0);
+                self.encoder.end_block(map.len(), mark);
                 Ok(())
             }
             (AvroSchema::Fixed { size, .. }, AvroValue::Fixed(b)) => {
@@ -238,10 +249,64 @@ impl AvroSchemaEncoder {
                 }
                 Err(AvroEncodeError::UnionNoMatchingType)
             }
+            (
+                AvroSchema::Logical {
+                    logical_type,
+                    schema,
+                },
+                value,
+            ) => self.write_logical(value, logical_type, schema),
             _ => Err(AvroEncodeError::TypeMismatch("schema/value mismatch")),
         }
     }
 
+    /// Writes a logical value as its underlying type. Plain values of the
+    /// underlying type, and any value under an annotation that does not
+    /// apply, are written as the underlying type.
+    fn write_logical(
+        &mut self,
+        value: &AvroValue,
+        logical_type: &AvroLogicalType,
+        base: &AvroSchema,
+    ) -> Result<(), AvroEncodeError> {
+        let base = self.resolve(base)?.clone();
+        if !logical_type.applies_to(&base) || !logical_type.matches_value(value) {
+            return self.write_value(value, &base);
+        }
+        match (value, &base) {
+            (AvroValue::Decimal { unscaled, .. }, AvroSchema::Fixed { size, .. }) => {
+                let bytes = unscaled.to_twos_complement();
+                if bytes.len() > *size {
+                    return Err(AvroEncodeError::FixedSizeMismatch);
+                }
+                let pad = if unscaled.is_negative() { 0xff } else { 0 };
+                let mut out = vec![pad; size - bytes.len()];
+                out.extend_from_slice(&bytes);
+                self.encoder.writer.buf(&out);
+            }
+            (AvroValue::Decimal { unscaled, .. }, _) => {
+                let mut bytes = unscaled.to_twos_complement();
+                if bytes.is_empty() {
+                    bytes.push(0);
+                }
+                self.encoder.write_bytes(&bytes);
+            }
+            (AvroValue::Uuid(s), AvroSchema::Fixed { .. }) => {
+                self.encoder.writer.buf(&uuid_to_bytes(s));
+            }
+            (AvroValue::Uuid(s), _) => self.encoder.write_str(s),
+            (AvroValue::Date(n) | AvroValue::TimeMillis(n), _) => self.encoder.write_int(*n),
+            (
+                AvroValue::TimeMicros(n)
+                | AvroValue::TimestampMillis(n)
+                | AvroValue::TimestampMicros(n),
+                _,
+            ) => self.encoder.write_long(*n),
+            _ => return self.write_value(value, &base),
+        }
+        Ok(())
+    }
+
     fn value_matches_schema(&self, value: &AvroValue, schema: &AvroSchema) -> bool {
         let resolved = match self.resolve(schema) {
             Ok(resolved) => resolved,
@@ -289,6 +354,19 @@ impl AvroSchemaEncoder {
             (AvroSchema::Union(schemas), value) => schemas
                 .iter()
                 .any(|schema| self.value_matches_schema(value, schema)),
+            (
+                AvroSchema::Logical {
+                    logical_type,
+                    schema,
+                },
+                value,
+            ) => {
+                let applies = self
+                    .resolve(schema)
+                    .is_ok_and(|base| logical_type.applies_to(base));
+                (applies && logical_type.matches_value(value))
+                    || self.value_matches_schema(value, schema)
+            }
             _ => false,
         }
     }
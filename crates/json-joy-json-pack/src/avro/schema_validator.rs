@@ -65,6 +65,15 @@ impl AvroSchemaValidator {
             } => self.validate_enum_schema(schema, name, namespace.as_deref(), symbols, default),
             AvroSchema::Array { items } => self.validate_schema_internal(items),
             AvroSchema::Map { values } => self.validate_schema_internal(values),
+            AvroSchema::Logical { schema: base, .. } => {
+                if !self.validate_schema_internal(base) {
+                    return false;
+                }
+                if let Some(name) = base.full_name() {
+                    self.named_schemas.insert(name, schema.clone());
+                }
+                true
+            }
             AvroSchema::Fixed {
                 name,
                 namespace,
@@ -214,6 +223,17 @@ impl AvroSchemaValidator {
             AvroSchema::Array { items } => self.validate_value_against_array(value, items),
             AvroSchema::Map { values } => self.validate_value_against_map(value, values),
             AvroSchema::Fixed { size, .. } => self.validate_value_against_fixed(value, *size),
+            AvroSchema::Logical {
+                logical_type,
+                schema,
+            } => {
+                let base = match schema.as_ref() {
+                    AvroSchema::Ref(name) => self.named_schemas.get(name).unwrap_or(schema),
+                    base => base,
+                };
+                (logical_type.applies_to(base) && logical_type.matches_value(value))
+                    || self.validate_value_against_schema(value, schema)
+            }
         }
     }
 
@@ -334,6 +354,7 @@ impl AvroSchemaValidator {
             AvroSchema::Fixed { .. } => "fixed".to_string(),
             AvroSchema::Union(_) => "union".to_string(),
             AvroSchema::Ref(name) => name.clone(),
+            AvroSchema::Logical { schema, .. } => self.schema_type_name(schema),
         }
     }
 }
@@ -0,0 +1,111 @@
+//! Avro single-object encoding.
+//!
+//! Reference: Apache Avro 1.12.0 specification, "Single-object encoding".
+//!
+//! A single-object payload is the two-byte marker `C3 01`, the schema's
+//! CRC-64-AVRO fingerprint as 8 little-endian bytes, then the Avro binary
+//! encoding of the value. Not part of upstream json-pack.
+
+use std::collections::HashMap;
+
+use super::decoder::AvroDecodeError;
+use super::fingerprint::fingerprint64;
+use super::schema_decoder::AvroSchemaDecoder;
+use super::schema_encoder::{AvroEncodeError, AvroSchemaEncoder};
+use super::types::{AvroSchema, AvroValue};
+
+/// Marker that starts every single-object encoded payload.
+pub const SINGLE_OBJECT_MARKER: [u8; 2] = [0xc3, 0x01];
+
+/// Length of the marker plus fingerprint.
+pub const SINGLE_OBJECT_HEADER_LEN: usize = 10;
+
+/// Encodes `value` as a single object written with `schema`.
+pub fn encode_single_object(
+    value: &AvroValue,
+    schema: &AvroSchema,
+) -> Result<Vec<u8>, AvroEncodeError> {
+    let body = AvroSchemaEncoder::new().encode(value, schema)?;
+    let mut out = Vec::with_capacity(SINGLE_OBJECT_HEADER_LEN + body.len());
+    out.extend_from_slice(&SINGLE_OBJECT_MARKER);
+    out.extend_from_slice(&fingerprint64(schema).to_le_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Splits a single-object payload into the writer schema's fingerprint and
+/// the Avro body.
+pub fn read_single_object_header(data: &[u8]) -> Result<(u64, &[u8]), AvroDecodeError> {
+    if data.len() < SINGLE_OBJECT_HEADER_LEN {
+        return Err(AvroDecodeError::EndOfInput(data.len()));
+    }
+    if data[..2] != SINGLE_OBJECT_MARKER {
+        return Err(AvroDecodeError::InvalidSingleObjectMarker);
+    }
+    let mut fp = [0u8; 8];
+    fp.copy_from_slice(&data[2..SINGLE_OBJECT_HEADER_LEN]);
+    Ok((u64::from_le_bytes(fp), &data[SINGLE_OBJECT_HEADER_LEN..]))
+}
+
+/// Decodes a single object that must have been written with `schema`.
+pub fn decode_single_object(
+    data: &[u8],
+    schema: &AvroSchema,
+) -> Result<AvroValue, AvroDecodeError> {
+    let (fp, body) = read_single_object_header(data)?;
+    if fp != fingerprint64(schema) {
+        return Err(AvroDecodeError::UnknownFingerprint(fp));
+    }
+    AvroSchemaDecoder::new()
+        .decode(body, schema)
+        .map_err(|err| err.offset_by(SINGLE_OBJECT_HEADER_LEN))
+}
+
+/// Writer schemas keyed by CRC-64-AVRO fingerprint, used to decode
+/// single-object payloads without knowing the schema up front.
+#[derive(Default)]
+pub struct AvroSingleObjectRegistry {
+    schemas: HashMap<u64, AvroSchema>,
+    decoder: AvroSchemaDecoder,
+}
+
+impl AvroSingleObjectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `schema` and returns its fingerprint. Registering a schema
+    /// with the same canonical form again replaces the earlier one.
+    pub fn register(&mut self, schema: AvroSchema) -> u64 {
+        let fp = fingerprint64(&schema);
+        self.schemas.insert(fp, schema);
+        fp
+    }
+
+    pub fn get(&self, fingerprint: u64) -> Option<&AvroSchema> {
+        self.schemas.get(&fingerprint)
+    }
+
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// Decodes a single-object payload with the registered schema its
+    /// fingerprint names, returning the fingerprint alongside the value.
+    pub fn decode(&mut self, data: &[u8]) -> Result<(u64, AvroValue), AvroDecodeError> {
+        let (fp, body) = read_single_object_header(data)?;
+        let schema = self
+            .schemas
+            .get(&fp)
+            .ok_or(AvroDecodeError::UnknownFingerprint(fp))?;
+        let value = self
+            .decoder
+            .decode(body, schema)
+            .map_err(|err| err.offset_by(SINGLE_OBJECT_HEADER_LEN))?;
+        Ok((fp, value))
+    }
+}
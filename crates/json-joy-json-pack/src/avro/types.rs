@@ -3,6 +3,8 @@
 //! Upstream reference: `json-pack/src/avro/types.ts`
 //! Reference: Apache Avro 1.12.0 specification
 
+use crate::JsonPackBigInt;
+
 /// Avro schema.
 #[derive(Debug, Clone)]
 pub enum AvroSchema {
@@ -43,6 +45,12 @@ pub enum AvroSchema {
     Union(Vec<AvroSchema>),
     /// Reference to a named type (resolved during encoding/decoding).
     Ref(String),
+    /// A schema annotated with a `logicalType`. Annotations that do not
+    /// apply to `schema` are ignored, as the specification requires.
+    Logical {
+        logical_type: AvroLogicalType,
+        schema: Box<AvroSchema>,
+    },
 }
 
 impl AvroSchema {
@@ -58,9 +66,139 @@ impl AvroSchema {
             AvroSchema::Fixed {
                 name, namespace, ..
             } => Some(qualify(name, namespace.as_deref())),
+            AvroSchema::Logical { schema, .. } => schema.full_name(),
             _ => None,
         }
     }
+
+    /// Annotates this schema with a logical type.
+    pub fn logical(self, logical_type: AvroLogicalType) -> Self {
+        AvroSchema::Logical {
+            logical_type,
+            schema: Box::new(self),
+        }
+    }
+}
+
+/// Avro logical type, carried by [`AvroSchema::Logical`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AvroLogicalType {
+    /// Arbitrary-precision decimal over `bytes` or `fixed`.
+    Decimal { precision: u32, scale: u32 },
+    /// RFC 4122 UUID over `string` or `fixed(16)`.
+    Uuid,
+    /// Days since the Unix epoch, over `int`.
+    Date,
+    /// Milliseconds after midnight, over `int`.
+    TimeMillis,
+    /// Microseconds after midnight, over `long`.
+    TimeMicros,
+    /// Milliseconds since the Unix epoch (UTC), over `long`.
+    TimestampMillis,
+    /// Microseconds since the Unix epoch (UTC), over `long`.
+    TimestampMicros,
+}
+
+impl AvroLogicalType {
+    /// The `logicalType` attribute value.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AvroLogicalType::Decimal { .. } => "decimal",
+            AvroLogicalType::Uuid => "uuid",
+            AvroLogicalType::Date => "date",
+            AvroLogicalType::TimeMillis => "time-millis",
+            AvroLogicalType::TimeMicros => "time-micros",
+            AvroLogicalType::TimestampMillis => "timestamp-millis",
+            AvroLogicalType::TimestampMicros => "timestamp-micros",
+        }
+    }
+
+    /// Whether the annotation is valid on `schema`.
+    pub fn applies_to(&self, schema: &AvroSchema) -> bool {
+        match (self, schema) {
+            (AvroLogicalType::Decimal { precision, scale }, AvroSchema::Bytes) => {
+                *precision > 0 && scale <= precision
+            }
+            (AvroLogicalType::Decimal { precision, scale }, AvroSchema::Fixed { size, .. }) => {
+                *precision > 0 && scale <= precision && *precision <= max_decimal_precision(*size)
+            }
+            (AvroLogicalType::Uuid, AvroSchema::String) => true,
+            (AvroLogicalType::Uuid, AvroSchema::Fixed { size, .. }) => *size == 16,
+            (AvroLogicalType::Date | AvroLogicalType::TimeMillis, AvroSchema::Int) => true,
+            (
+                AvroLogicalType::TimeMicros
+                | AvroLogicalType::TimestampMillis
+                | AvroLogicalType::TimestampMicros,
+                AvroSchema::Long,
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether `value` is this logical type's value variant and holds a
+    /// valid value (matching scale, precision and UUID syntax).
+    pub fn matches_value(&self, value: &AvroValue) -> bool {
+        match (self, value) {
+            (
+                AvroLogicalType::Decimal { precision, scale },
+                AvroValue::Decimal {
+                    unscaled,
+                    scale: value_scale,
+                },
+            ) => {
+                let digits = unscaled.to_string().trim_start_matches('-').len();
+                value_scale == scale && digits <= *precision as usize
+            }
+            (AvroLogicalType::Uuid, AvroValue::Uuid(s)) => is_valid_uuid(s),
+            (AvroLogicalType::Date, AvroValue::Date(_))
+            | (AvroLogicalType::TimeMillis, AvroValue::TimeMillis(_))
+            | (AvroLogicalType::TimeMicros, AvroValue::TimeMicros(_))
+            | (AvroLogicalType::TimestampMillis, AvroValue::TimestampMillis(_))
+            | (AvroLogicalType::TimestampMicros, AvroValue::TimestampMicros(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Largest decimal precision a two's complement `fixed` of `size` bytes
+/// can hold: `floor(log10(2^(8 * size - 1)))`.
+fn max_decimal_precision(size: usize) -> u32 {
+    if size == 0 {
+        return 0;
+    }
+    ((8 * size - 1) as f64 * std::f64::consts::LOG10_2).floor() as u32
+}
+
+/// Whether `s` is a hyphenated `8-4-4-4-12` hex UUID.
+pub(crate) fn is_valid_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+/// Packs a hyphenated UUID into 16 bytes. `s` must be valid.
+pub(crate) fn uuid_to_bytes(s: &str) -> Vec<u8> {
+    let hex: Vec<u8> = s.bytes().filter(|&b| b != b'-').collect();
+    hex.chunks_exact(2)
+        .map(|pair| {
+            let digits = std::str::from_utf8(pair).unwrap_or("00");
+            u8::from_str_radix(digits, 16).unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Formats 16 bytes as a lowercase hyphenated UUID.
+pub(crate) fn uuid_from_bytes(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(36);
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        out.push_str(&format!("{b:02x}"));
+    }
+    out
 }
 
 fn qualify(name: &str, namespace: Option<&str>) -> String {
@@ -96,5 +234,25 @@ pub enum AvroValue {
     Array(Vec<AvroValue>),
     Map(Vec<(String, AvroValue)>),
     Fixed(Vec<u8>),
-    Union { index: usize, value: Box<AvroValue> },
+    Union {
+        index: usize,
+        value: Box<AvroValue>,
+    },
+    /// `decimal` logical value: `unscaled * 10^-scale`.
+    Decimal {
+        unscaled: JsonPackBigInt,
+        scale: u32,
+    },
+    /// `uuid` logical value in hyphenated form.
+    Uuid(String),
+    /// `date` logical value: days since the Unix epoch.
+    Date(i32),
+    /// `time-millis` logical value.
+    TimeMillis(i32),
+    /// `time-micros` logical value.
+    TimeMicros(i64),
+    /// `timestamp-millis` logical value.
+    TimestampMillis(i64),
+    /// `timestamp-micros` logical value.
+    TimestampMicros(i64),
 }
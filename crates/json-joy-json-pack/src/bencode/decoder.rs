@@ -15,7 +15,7 @@ impl<'a> Cur<'a> {
     #[inline]
     fn check(&self, n: usize) -> Result<(), BencodeError> {
         if self.pos + n > self.data.len() {
-            Err(BencodeError::UnexpectedEof)
+            Err(BencodeError::UnexpectedEof(self.pos))
         } else {
             Ok(())
         }
@@ -36,29 +36,60 @@ impl<'a> Cur<'a> {
     }
 }
 
+/// Limits on untrusted Bencode input. `None` means unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct BencodeDecoderOptions {
+    /// Longest string accepted, in bytes. Checked against the declared
+    /// length before the bytes are read.
+    pub max_string_len: Option<usize>,
+    /// Deepest nesting of lists and dictionaries accepted: `le` has depth
+    /// 1, a scalar depth 0.
+    pub max_depth: Option<usize>,
+}
+
 /// Stateless Bencode decoder.
 #[derive(Default)]
-pub struct BencodeDecoder;
+pub struct BencodeDecoder {
+    options: BencodeDecoderOptions,
+}
 
 impl BencodeDecoder {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_options(options: BencodeDecoderOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &BencodeDecoderOptions {
+        &self.options
     }
 
     pub fn decode(&self, input: &[u8]) -> Result<PackValue, BencodeError> {
+        self.decode_prefix(input).map(|(value, _)| value)
+    }
+
+    /// Decodes the value at the start of `input`, returning it and the
+    /// number of bytes it took. Bytes after the value are not looked at.
+    pub fn decode_prefix(&self, input: &[u8]) -> Result<(PackValue, usize), BencodeError> {
         let mut c = Cur {
             data: input,
             pos: 0,
         };
-        self.read_any(&mut c)
+        let value = self.read_any(&mut c, 0)?;
+        Ok((value, c.pos))
     }
 
-    fn read_any(&self, c: &mut Cur) -> Result<PackValue, BencodeError> {
+    fn read_any(&self, c: &mut Cur, depth: usize) -> Result<PackValue, BencodeError> {
         let ch = c.peek()?;
+        if matches!(ch, b'd' | b'l') && self.options.max_depth.is_some_and(|max| depth >= max) {
+            return Err(BencodeError::TooDeep(c.pos));
+        }
         match ch {
             b'i' => self.read_num(c),
-            b'd' => self.read_obj(c),
-            b'l' => self.read_arr(c),
+            b'd' => self.read_obj(c, depth + 1),
+            b'l' => self.read_arr(c, depth + 1),
             b't' => {
                 c.pos += 1;
                 Ok(PackValue::Bool(true))
@@ -136,6 +167,9 @@ impl BencodeDecoder {
             }
         }
         let len: usize = len_str.parse().map_err(|_| BencodeError::IntegerOverflow)?;
+        if self.options.max_string_len.is_some_and(|max| len > max) {
+            return Err(BencodeError::StringTooLong(c.pos - 1 - len_str.len()));
+        }
         c.check(len)?;
         let buf = c.data[c.pos..c.pos + len].to_vec();
         c.pos += len;
@@ -145,22 +179,23 @@ impl BencodeDecoder {
     /// Read a bencode string and decode as UTF-8.
     fn read_str(&self, c: &mut Cur) -> Result<String, BencodeError> {
         let bin = self.read_bin(c)?;
-        String::from_utf8(bin).map_err(|_| BencodeError::InvalidUtf8)
+        let start = c.pos - bin.len();
+        String::from_utf8(bin).map_err(|_| BencodeError::InvalidUtf8(start))
     }
 
-    fn read_arr(&self, c: &mut Cur) -> Result<PackValue, BencodeError> {
+    fn read_arr(&self, c: &mut Cur, depth: usize) -> Result<PackValue, BencodeError> {
         if c.u8()? != b'l' {
             return Err(BencodeError::InvalidByte(c.pos - 1));
         }
         let mut arr = Vec::new();
         while c.peek()? != b'e' {
-            arr.push(self.read_any(c)?);
+            arr.push(self.read_any(c, depth)?);
         }
         c.pos += 1; // consume 'e'
         Ok(PackValue::Array(arr))
     }
 
-    fn read_obj(&self, c: &mut Cur) -> Result<PackValue, BencodeError> {
+    fn read_obj(&self, c: &mut Cur, depth: usize) -> Result<PackValue, BencodeError> {
         if c.u8()? != b'd' {
             return Err(BencodeError::InvalidByte(c.pos - 1));
         }
@@ -170,7 +205,7 @@ impl BencodeDecoder {
             if key == "__proto__" {
                 return Err(BencodeError::InvalidKey);
             }
-            let val = self.read_any(c)?;
+            let val = self.read_any(c, depth)?;
             obj.push((key, val));
         }
         c.pos += 1; // consume 'e'
@@ -11,6 +11,8 @@
 //! - Null:    `n`                           [extension]
 //! - Undef:   `u`                           [extension]
 
+use std::borrow::Cow;
+
 use json_joy_buffers::Writer;
 
 use crate::PackValue;
@@ -53,10 +55,12 @@ impl BencodeEncoder {
             PackValue::UInteger(u) => self.write_u_integer(*u),
             PackValue::Float(f) => self.write_float(*f),
             PackValue::BigInt(i) => self.write_big_int(*i),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_map(map),
             PackValue::Extension(_) | PackValue::Blob(_) => self.write_null(),
         }
     }
@@ -131,6 +135,12 @@ impl BencodeEncoder {
         self.writer.u8(b'e');
     }
 
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        self.writer.u8(b'i');
+        self.writer.ascii(&num.to_string());
+        self.writer.u8(b'e');
+    }
+
     pub fn write_bin(&mut self, buf: &[u8]) {
         self.writer.ascii(&buf.len().to_string());
         self.writer.u8(b':');
@@ -167,4 +177,28 @@ impl BencodeEncoder {
         }
         self.writer.u8(b'e');
     }
+
+    /// Writes a [`PackValue::Map`] as a dict. Bencode keys are byte
+    /// strings, so byte keys are written as they are; other non-string keys
+    /// go through [`PackValue::to_key_string`].
+    pub fn write_map(&mut self, map: &[(PackValue, PackValue)]) {
+        self.writer.u8(b'd');
+        let mut sorted: Vec<(Cow<[u8]>, &PackValue)> = map
+            .iter()
+            .map(|(key, val)| {
+                let key = match key {
+                    PackValue::Str(s) => Cow::Borrowed(s.as_bytes()),
+                    PackValue::Bytes(b) => Cow::Borrowed(b.as_slice()),
+                    key => Cow::Owned(key.to_key_string().into_bytes()),
+                };
+                (key, val)
+            })
+            .collect();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, val) in sorted {
+            self.write_bin(&key);
+            self.write_any(val);
+        }
+        self.writer.u8(b'e');
+    }
 }
@@ -6,12 +6,32 @@ use thiserror::Error;
 pub enum BencodeError {
     #[error("invalid bencode: unexpected byte at position {0}")]
     InvalidByte(usize),
-    #[error("invalid bencode: unexpected end of input")]
-    UnexpectedEof,
+    #[error("invalid bencode: unexpected end of input at position {0}")]
+    UnexpectedEof(usize),
     #[error("invalid bencode: integer overflow")]
     IntegerOverflow,
-    #[error("invalid bencode: invalid UTF-8 in string")]
-    InvalidUtf8,
+    #[error("invalid bencode: invalid UTF-8 in string at position {0}")]
+    InvalidUtf8(usize),
     #[error("invalid bencode: invalid key `__proto__`")]
     InvalidKey,
+    #[error("bencode string at position {0} is over the length limit")]
+    StringTooLong(usize),
+    #[error("bencode list or dictionary at position {0} is nested too deep")]
+    TooDeep(usize),
+    #[error("bencode stream would buffer more than {0} bytes")]
+    BufferFull(usize),
+}
+
+impl BencodeError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::InvalidByte(x)
+            | Self::UnexpectedEof(x)
+            | Self::InvalidUtf8(x)
+            | Self::StringTooLong(x)
+            | Self::TooDeep(x) => Some(*x),
+            Self::IntegerOverflow | Self::InvalidKey | Self::BufferFull(_) => None,
+        }
+    }
 }
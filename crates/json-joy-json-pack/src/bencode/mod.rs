@@ -5,9 +5,11 @@
 mod decoder;
 mod encoder;
 mod error;
+mod streaming_decoder;
 mod types;
 
-pub use decoder::BencodeDecoder;
+pub use decoder::{BencodeDecoder, BencodeDecoderOptions};
 pub use encoder::BencodeEncoder;
 pub use error::BencodeError;
+pub use streaming_decoder::BencodeStreamingDecoder;
 pub use types::BencodeUint8Array;
@@ -0,0 +1,175 @@
+//! Streaming Bencode decoder.
+//!
+//! Not an upstream port. Input arrives in chunks through
+//! [`push`](BencodeStreamingDecoder::push); each
+//! [`read_value`](BencodeStreamingDecoder::read_value) returns the next
+//! complete value, or `None` until enough bytes have arrived. Pushed bytes
+//! are scanned once, resuming where the previous call stopped, and a value
+//! is decoded only when its last byte is in. The [`BencodeDecoderOptions`]
+//! limits are checked as soon as a string header or container start is
+//! seen, so an oversized string is rejected without waiting for its bytes.
+
+use super::decoder::{BencodeDecoder, BencodeDecoderOptions};
+use super::error::BencodeError;
+use crate::PackValue;
+
+/// Incremental Bencode decoder that accepts chunked input and emits decoded
+/// values.
+#[derive(Default)]
+pub struct BencodeStreamingDecoder {
+    buffer: Vec<u8>,
+    offset: usize,
+    scan: Scan,
+    decoder: BencodeDecoder,
+    max_buffer: Option<usize>,
+}
+
+/// How far the value at `offset` has been scanned.
+#[derive(Default)]
+struct Scan {
+    /// Next byte to look at, relative to `offset`; past the end of the
+    /// buffer while string bytes are still to come.
+    pos: usize,
+    /// Lists and dictionaries open before `pos`.
+    depth: usize,
+}
+
+/// Longest `i...e` integer the decoder accepts.
+const MAX_INT_LEN: usize = 27;
+/// Most length digits the decoder accepts in a string header.
+const MAX_LEN_DIGITS: usize = 10;
+
+impl BencodeStreamingDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: BencodeDecoderOptions) -> Self {
+        Self {
+            decoder: BencodeDecoder::with_options(options),
+            ..Self::default()
+        }
+    }
+
+    /// Caps the bytes held waiting for a value to complete: a
+    /// [`push`](Self::push) that would hold more fails with
+    /// [`BencodeError::BufferFull`].
+    pub fn with_max_buffer(mut self, max: usize) -> Self {
+        self.max_buffer = Some(max);
+        self
+    }
+
+    /// Appends `data` to the input. Fails, keeping none of `data`, if that
+    /// would buffer more than the [`with_max_buffer`](Self::with_max_buffer)
+    /// limit.
+    pub fn push(&mut self, data: &[u8]) -> Result<(), BencodeError> {
+        if let Some(max) = self.max_buffer {
+            if self.buffered() + data.len() > max {
+                return Err(BencodeError::BufferFull(max));
+            }
+        }
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Number of pushed bytes not yet consumed by a value.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.offset
+    }
+
+    /// Reads the next complete value, or `None` until more input is pushed.
+    ///
+    /// Error positions are relative to the start of the value being read.
+    /// After an error the stream is not resynchronised: the same error is
+    /// returned until the decoder is dropped.
+    pub fn read_value(&mut self) -> Result<Option<PackValue>, BencodeError> {
+        let Some(end) = self.scan()? else {
+            return Ok(None);
+        };
+        let input = &self.buffer[self.offset..self.offset + end];
+        let (value, consumed) = self.decoder.decode_prefix(input)?;
+        self.offset += consumed;
+        self.scan = Scan::default();
+        self.compact();
+        Ok(Some(value))
+    }
+
+    /// Scans on from where the last call stopped, returning the length of
+    /// the value at `offset` once all of it has arrived.
+    fn scan(&mut self) -> Result<Option<usize>, BencodeError> {
+        let data = &self.buffer[self.offset..];
+        let options = self.decoder.options();
+        loop {
+            let Scan { pos, depth } = self.scan;
+            if pos > 0 && depth == 0 {
+                return Ok((pos <= data.len()).then_some(pos));
+            }
+            let Some(&byte) = data.get(pos) else {
+                return Ok(None);
+            };
+            self.scan.pos = match byte {
+                b'e' if depth > 0 => {
+                    self.scan.depth -= 1;
+                    pos + 1
+                }
+                b'd' | b'l' => {
+                    if options.max_depth.is_some_and(|max| depth >= max) {
+                        return Err(BencodeError::TooDeep(pos));
+                    }
+                    self.scan.depth += 1;
+                    pos + 1
+                }
+                b't' | b'f' | b'n' | b'u' => pos + 1,
+                b'i' => {
+                    let int = &data[pos..data.len().min(pos + MAX_INT_LEN)];
+                    match int.iter().position(|&b| b == b'e') {
+                        Some(i) => pos + i + 1,
+                        None if int.len() < MAX_INT_LEN => return Ok(None),
+                        None => return Err(self.error()),
+                    }
+                }
+                b'0'..=b'9' => {
+                    let header = &data[pos..data.len().min(pos + MAX_LEN_DIGITS + 1)];
+                    let digits = header.iter().take_while(|b| b.is_ascii_digit()).count();
+                    if digits == header.len() && digits <= MAX_LEN_DIGITS {
+                        return Ok(None);
+                    }
+                    let len = match (header.get(digits), core::str::from_utf8(&header[..digits])) {
+                        (Some(b':'), Ok(len)) => len.parse::<usize>().ok(),
+                        _ => None,
+                    };
+                    let Some(len) = len else {
+                        return Err(self.error());
+                    };
+                    if options.max_string_len.is_some_and(|max| len > max) {
+                        return Err(BencodeError::StringTooLong(pos));
+                    }
+                    match (pos + digits + 1).checked_add(len) {
+                        Some(end) => end,
+                        None => return Err(BencodeError::IntegerOverflow),
+                    }
+                }
+                _ => return Err(self.error()),
+            };
+        }
+    }
+
+    /// The error the decoder gives for the malformed value at `offset`.
+    fn error(&self) -> BencodeError {
+        let pos = self.scan.pos;
+        match self.decoder.decode_prefix(&self.buffer[self.offset..]) {
+            Err(err) => err,
+            Ok(_) => BencodeError::InvalidByte(pos),
+        }
+    }
+
+    fn compact(&mut self) {
+        if self.offset == self.buffer.len() {
+            self.buffer.clear();
+            self.offset = 0;
+        } else if self.offset >= 8192 || self.offset * 2 >= self.buffer.len() {
+            self.buffer.drain(..self.offset);
+            self.offset = 0;
+        }
+    }
+}
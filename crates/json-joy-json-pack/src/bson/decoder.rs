@@ -37,10 +37,18 @@ impl BsonDecoder {
         self.read_document()
     }
 
+    /// Decodes a lone element value of type `typ`, as found by
+    /// [`BsonScanner`](super::BsonScanner). Error offsets are relative to `data`.
+    pub(super) fn decode_value(&mut self, typ: u8, data: &[u8]) -> Result<BsonValue, BsonError> {
+        self.data = data.to_vec();
+        self.x = 0;
+        self.read_element_value(typ, 0)
+    }
+
     #[inline]
     fn check(&self, n: usize) -> Result<(), BsonError> {
         if self.x + n > self.data.len() {
-            Err(BsonError::UnexpectedEof)
+            Err(BsonError::UnexpectedEof(self.x))
         } else {
             Ok(())
         }
@@ -105,33 +113,34 @@ impl BsonDecoder {
     }
 
     fn utf8(&mut self, n: usize) -> Result<String, BsonError> {
+        let start = self.x;
         let bytes = self.buf(n)?;
-        String::from_utf8(bytes).map_err(|_| BsonError::InvalidUtf8)
+        String::from_utf8(bytes).map_err(|_| BsonError::InvalidUtf8(start))
     }
 
     fn read_document(&mut self) -> Result<Vec<(String, BsonValue)>, BsonError> {
-        let document_size = self.i32_le()? as usize;
-        let start_pos = self.x; // position after the 4-byte size field
-                                // Validate the stated document size fits within the buffer
-        if start_pos + document_size.saturating_sub(4) > self.data.len() {
-            return Err(BsonError::UnexpectedEof);
+        let size_pos = self.x;
+        let document_size = self.length(5)?;
+        if size_pos + document_size > self.data.len() {
+            return Err(BsonError::UnexpectedEof(size_pos));
         }
-        let end_pos = start_pos + document_size - 4 - 1; // before terminating null
+        let end_pos = size_pos + document_size - 1; // before terminating null
         let mut fields: Vec<(String, BsonValue)> = Vec::new();
 
         while self.x < end_pos {
+            let type_pos = self.x;
             let element_type = self.u8()?;
             if element_type == 0 {
                 break;
             }
             let key = self.read_cstring()?;
-            let value = self.read_element_value(element_type)?;
+            let value = self.read_element_value(element_type, type_pos)?;
             fields.push((key, value));
         }
 
         // Skip to end of document (including terminating null)
         if self.x <= end_pos {
-            self.x = start_pos + document_size - 4;
+            self.x = size_pos + document_size;
         }
 
         Ok(fields)
@@ -143,25 +152,39 @@ impl BsonDecoder {
             self.x += 1;
         }
         if self.x >= self.data.len() {
-            return Err(BsonError::UnexpectedEof);
+            return Err(BsonError::UnexpectedEof(start));
         }
         let s = String::from_utf8(self.data[start..self.x].to_vec())
-            .map_err(|_| BsonError::InvalidUtf8)?;
+            .map_err(|_| BsonError::InvalidUtf8(start))?;
         self.x += 1; // skip null terminator
         Ok(s)
     }
 
+    /// Reads an `i32` length prefix that must be at least `min`.
+    fn length(&mut self, min: usize) -> Result<usize, BsonError> {
+        let pos = self.x;
+        let length = self.i32_le()?;
+        match usize::try_from(length) {
+            Ok(n) if n >= min => Ok(n),
+            _ => Err(BsonError::InvalidLength(pos)),
+        }
+    }
+
     fn read_string(&mut self) -> Result<String, BsonError> {
-        let length = self.i32_le()? as usize;
+        let length_pos = self.x;
+        let length = self.length(0)?;
         if length == 0 {
             return Ok(String::new());
         }
         let s = self.utf8(length - 1)?; // -1: length includes null terminator
-        self.x += 1; // skip null terminator
+        if self.u8()? != 0 {
+            return Err(BsonError::InvalidLength(length_pos));
+        }
         Ok(s)
     }
 
-    fn read_element_value(&mut self, typ: u8) -> Result<BsonValue, BsonError> {
+    /// `type_pos` is the offset of the element type byte, for error reporting.
+    fn read_element_value(&mut self, typ: u8, type_pos: usize) -> Result<BsonValue, BsonError> {
         match typ {
             0x01 => Ok(BsonValue::Float(self.f64_le()?)),
             0x02 => Ok(BsonValue::Str(self.read_string()?)),
@@ -190,7 +213,7 @@ impl BsonDecoder {
             })),
             0xff => Ok(BsonValue::MinKey),
             0x7f => Ok(BsonValue::MaxKey),
-            t => Err(BsonError::UnsupportedType(t)),
+            t => Err(BsonError::UnsupportedType(t, type_pos)),
         }
     }
 
@@ -206,8 +229,21 @@ impl BsonDecoder {
     }
 
     fn read_binary(&mut self) -> Result<BsonValue, BsonError> {
-        let length = self.i32_le()? as usize;
+        let length_pos = self.x;
+        let length = self.length(0)?;
         let subtype = self.u8()?;
+        if subtype == 0x02 {
+            // Old binary subtype repeats the payload length inside the data.
+            if length < 4 {
+                return Err(BsonError::InvalidLength(length_pos));
+            }
+            let inner_pos = self.x;
+            if self.length(0)? != length - 4 {
+                return Err(BsonError::InvalidLength(inner_pos));
+            }
+            let data = self.buf(length - 4)?;
+            return Ok(BsonValue::Binary(BsonBinary { subtype, data }));
+        }
         let data = self.buf(length)?;
         Ok(BsonValue::Binary(BsonBinary { subtype, data }))
     }
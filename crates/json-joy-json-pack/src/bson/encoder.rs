@@ -5,6 +5,8 @@
 //! BSON is a little-endian binary format. All multi-byte integers are
 //! written in little-endian byte order.
 
+use json_joy_buffers::Writer;
+
 use super::values::{BsonObjectId, BsonValue};
 
 /// Encodes a BSON document (a slice of key-value pairs) to bytes.
@@ -26,184 +28,179 @@ impl BsonEncoder {
 
     /// Encodes a BSON document to bytes.
     pub fn encode(&self, fields: &[(String, BsonValue)]) -> Vec<u8> {
-        self.write_document(fields)
+        // Documents are usually small; the writer doubles as needed.
+        let mut w = Writer::with_alloc_size(1024);
+        write_document(&mut w, fields);
+        w.flush()
     }
+}
 
-    fn write_document(&self, fields: &[(String, BsonValue)]) -> Vec<u8> {
-        let mut body: Vec<u8> = Vec::new();
-        for (key, value) in fields {
-            self.write_key_value(&mut body, key, value);
-        }
-        body.push(0); // terminating null byte
-        let size = (body.len() as i32) + 4; // +4 for the 4-byte size field
-        let mut result = Vec::with_capacity(4 + body.len());
-        result.extend_from_slice(&size.to_le_bytes());
-        result.extend_from_slice(&body);
-        result
+fn write_document(w: &mut Writer, fields: &[(String, BsonValue)]) {
+    let start = begin_size(w);
+    for (key, value) in fields {
+        write_key_value(w, key, value);
     }
+    w.u8(0); // terminating null byte
+    end_size(w, start);
+}
 
-    fn write_key_value(&self, buf: &mut Vec<u8>, key: &str, value: &BsonValue) {
-        match value {
-            BsonValue::Float(f) => {
-                buf.push(0x01);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&f.to_le_bytes());
-            }
-            BsonValue::Str(s) => {
-                buf.push(0x02);
-                self.write_cstring(buf, key);
-                self.write_string(buf, s);
-            }
-            BsonValue::Document(fields) => {
-                buf.push(0x03);
-                self.write_cstring(buf, key);
-                let doc = self.write_document(fields);
-                buf.extend_from_slice(&doc);
-            }
-            BsonValue::Array(arr) => {
-                buf.push(0x04);
-                self.write_cstring(buf, key);
-                // Encode array as a document with numeric string keys
-                let fields: Vec<(String, BsonValue)> = arr
-                    .iter()
-                    .enumerate()
-                    .map(|(i, v)| (i.to_string(), v.clone()))
-                    .collect();
-                let doc = self.write_document(&fields);
-                buf.extend_from_slice(&doc);
-            }
-            BsonValue::Binary(bin) => {
-                buf.push(0x05);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&(bin.data.len() as i32).to_le_bytes());
-                buf.push(bin.subtype);
-                buf.extend_from_slice(&bin.data);
-            }
-            BsonValue::Undefined => {
-                buf.push(0x06);
-                self.write_cstring(buf, key);
-            }
-            BsonValue::ObjectId(id) => {
-                buf.push(0x07);
-                self.write_cstring(buf, key);
-                self.write_object_id(buf, id);
-            }
-            BsonValue::Boolean(b) => {
-                buf.push(0x08);
-                self.write_cstring(buf, key);
-                buf.push(if *b { 1 } else { 0 });
-            }
-            BsonValue::DateTime(ms) => {
-                buf.push(0x09);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&ms.to_le_bytes());
-            }
-            BsonValue::Null => {
-                buf.push(0x0a);
-                self.write_cstring(buf, key);
-            }
-            BsonValue::Regex(pattern, flags) => {
-                buf.push(0x0b);
-                self.write_cstring(buf, key);
-                self.write_cstring(buf, pattern);
-                self.write_cstring(buf, flags);
-            }
-            BsonValue::DbPointer(ptr) => {
-                buf.push(0x0c);
-                self.write_cstring(buf, key);
-                self.write_string(buf, &ptr.name);
-                self.write_object_id(buf, &ptr.id);
-            }
-            BsonValue::JavaScriptCode(jsc) => {
-                buf.push(0x0d);
-                self.write_cstring(buf, key);
-                self.write_string(buf, &jsc.code);
-            }
-            BsonValue::Symbol(sym) => {
-                buf.push(0x0e);
-                self.write_cstring(buf, key);
-                self.write_string(buf, &sym.symbol);
-            }
-            BsonValue::JavaScriptCodeWithScope(jscws) => {
-                buf.push(0x0f);
-                self.write_cstring(buf, key);
-                // Reserve space for total length
-                let len_start = buf.len();
-                buf.extend_from_slice(&[0u8; 4]); // placeholder
-                self.write_string(buf, &jscws.code);
-                let scope_doc = self.write_document(&jscws.scope);
-                buf.extend_from_slice(&scope_doc);
-                let total_len = (buf.len() - len_start) as i32;
-                buf[len_start..len_start + 4].copy_from_slice(&total_len.to_le_bytes());
-            }
-            BsonValue::Int32(i) => {
-                buf.push(0x10);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&i.to_le_bytes());
-            }
-            BsonValue::Timestamp(ts) => {
-                buf.push(0x11);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&ts.increment.to_le_bytes());
-                buf.extend_from_slice(&ts.timestamp.to_le_bytes());
-            }
-            BsonValue::Int64(i) => {
-                buf.push(0x12);
-                self.write_cstring(buf, key);
-                buf.extend_from_slice(&i.to_le_bytes());
-            }
-            BsonValue::Decimal128(dec) => {
-                buf.push(0x13);
-                self.write_cstring(buf, key);
-                assert_eq!(dec.data.len(), 16, "Decimal128 data must be 16 bytes");
-                buf.extend_from_slice(&dec.data);
-            }
-            BsonValue::MinKey => {
-                buf.push(0xff);
-                self.write_cstring(buf, key);
-            }
-            BsonValue::MaxKey => {
-                buf.push(0x7f);
-                self.write_cstring(buf, key);
-            }
-        }
+fn write_array(w: &mut Writer, arr: &[BsonValue]) {
+    // Arrays are documents with numeric string keys.
+    let start = begin_size(w);
+    for (i, value) in arr.iter().enumerate() {
+        write_key_value(w, &i.to_string(), value);
     }
+    w.u8(0);
+    end_size(w, start);
+}
 
-    /// Writes a null-terminated C-string. Stops at any null byte in the input.
-    fn write_cstring(&self, buf: &mut Vec<u8>, s: &str) {
-        for byte in s.bytes() {
-            if byte == 0 {
-                break;
-            }
-            buf.push(byte);
+/// Reserves an `int32` size field and returns its offset from the
+/// flush position.
+fn begin_size(w: &mut Writer) -> usize {
+    let start = w.x - w.x0;
+    w.move_cursor(4);
+    start
+}
+
+/// Back-fills the size field at `start` with the byte count from it to
+/// the cursor.
+fn end_size(w: &mut Writer, start: usize) {
+    let pos = w.x0 + start;
+    let size = (w.x - pos) as i32;
+    w.uint8[pos..pos + 4].copy_from_slice(&size.to_le_bytes());
+}
+
+fn write_key_value(w: &mut Writer, key: &str, value: &BsonValue) {
+    match value {
+        BsonValue::Float(f) => {
+            write_header(w, 0x01, key);
+            w.f64_le(*f);
+        }
+        BsonValue::Str(s) => {
+            write_header(w, 0x02, key);
+            write_string(w, s);
+        }
+        BsonValue::Document(fields) => {
+            write_header(w, 0x03, key);
+            write_document(w, fields);
+        }
+        BsonValue::Array(arr) => {
+            write_header(w, 0x04, key);
+            write_array(w, arr);
+        }
+        BsonValue::Binary(bin) => {
+            write_header(w, 0x05, key);
+            if bin.subtype == 0x02 {
+                // Old binary subtype repeats the payload length inside the data.
+                w.i32_le(bin.data.len() as i32 + 4);
+                w.u8(bin.subtype);
+                w.i32_le(bin.data.len() as i32);
+            } else {
+                w.i32_le(bin.data.len() as i32);
+                w.u8(bin.subtype);
+            }
+            w.buf(&bin.data);
+        }
+        BsonValue::Undefined => {
+            write_header(w, 0x06, key);
+        }
+        BsonValue::ObjectId(id) => {
+            write_header(w, 0x07, key);
+            write_object_id(w, id);
+        }
+        BsonValue::Boolean(b) => {
+            write_header(w, 0x08, key);
+            w.u8(if *b { 1 } else { 0 });
+        }
+        BsonValue::DateTime(ms) => {
+            write_header(w, 0x09, key);
+            w.i64_le(*ms);
+        }
+        BsonValue::Null => {
+            write_header(w, 0x0a, key);
+        }
+        BsonValue::Regex(pattern, flags) => {
+            write_header(w, 0x0b, key);
+            write_cstring(w, pattern);
+            write_cstring(w, flags);
+        }
+        BsonValue::DbPointer(ptr) => {
+            write_header(w, 0x0c, key);
+            write_string(w, &ptr.name);
+            write_object_id(w, &ptr.id);
+        }
+        BsonValue::JavaScriptCode(jsc) => {
+            write_header(w, 0x0d, key);
+            write_string(w, &jsc.code);
+        }
+        BsonValue::Symbol(sym) => {
+            write_header(w, 0x0e, key);
+            write_string(w, &sym.symbol);
+        }
+        BsonValue::JavaScriptCodeWithScope(jscws) => {
+            write_header(w, 0x0f, key);
+            let start = begin_size(w);
+            write_string(w, &jscws.code);
+            write_document(w, &jscws.scope);
+            end_size(w, start);
+        }
+        BsonValue::Int32(i) => {
+            write_header(w, 0x10, key);
+            w.i32_le(*i);
+        }
+        BsonValue::Timestamp(ts) => {
+            write_header(w, 0x11, key);
+            w.i32_le(ts.increment);
+            w.i32_le(ts.timestamp);
+        }
+        BsonValue::Int64(i) => {
+            write_header(w, 0x12, key);
+            w.i64_le(*i);
+        }
+        BsonValue::Decimal128(dec) => {
+            write_header(w, 0x13, key);
+            assert_eq!(dec.data.len(), 16, "Decimal128 data must be 16 bytes");
+            w.buf(&dec.data);
+        }
+        BsonValue::MinKey => {
+            write_header(w, 0xff, key);
+        }
+        BsonValue::MaxKey => {
+            write_header(w, 0x7f, key);
         }
-        buf.push(0); // null terminator
     }
+}
 
-    /// Writes a BSON string: little-endian i32 (byte_count+1) + UTF-8 bytes + null byte.
-    fn write_string(&self, buf: &mut Vec<u8>, s: &str) {
-        let bytes = s.as_bytes();
-        let len = (bytes.len() as i32) + 1; // +1 for null terminator
-        buf.extend_from_slice(&len.to_le_bytes());
-        buf.extend_from_slice(bytes);
-        buf.push(0); // null terminator
-    }
+/// Writes an element's type byte and key.
+fn write_header(w: &mut Writer, typ: u8, key: &str) {
+    w.u8(typ);
+    write_cstring(w, key);
+}
 
-    /// Writes a 12-byte BSON ObjectId.
-    fn write_object_id(&self, buf: &mut Vec<u8>, id: &BsonObjectId) {
-        // Timestamp: 4 bytes big-endian
-        buf.push((id.timestamp >> 24) as u8);
-        buf.push(((id.timestamp >> 16) & 0xff) as u8);
-        buf.push(((id.timestamp >> 8) & 0xff) as u8);
-        buf.push((id.timestamp & 0xff) as u8);
-        // Process: 5 bytes little-endian (low 4 bytes LE + 1 high byte)
-        let lo32 = id.process as u32;
-        let hi8 = (id.process >> 32) as u8;
-        buf.extend_from_slice(&lo32.to_le_bytes()); // 4 bytes LE
-        buf.push(hi8);
-        // Counter: 3 bytes big-endian
-        buf.push(((id.counter >> 16) & 0xff) as u8);
-        buf.push(((id.counter >> 8) & 0xff) as u8);
-        buf.push((id.counter & 0xff) as u8);
-    }
+/// Writes a null-terminated C-string. Stops at any null byte in the input.
+fn write_cstring(w: &mut Writer, s: &str) {
+    let bytes = s.as_bytes();
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    w.buf(&bytes[..end]);
+    w.u8(0); // null terminator
+}
+
+/// Writes a BSON string: little-endian i32 (byte_count+1) + UTF-8 bytes + null byte.
+fn write_string(w: &mut Writer, s: &str) {
+    let bytes = s.as_bytes();
+    w.i32_le(bytes.len() as i32 + 1); // +1 for null terminator
+    w.buf(bytes);
+    w.u8(0); // null terminator
+}
+
+/// Writes a 12-byte BSON ObjectId.
+fn write_object_id(w: &mut Writer, id: &BsonObjectId) {
+    // Timestamp: 4 bytes big-endian
+    w.u32(id.timestamp);
+    // Process: 5 bytes little-endian (low 4 bytes LE + 1 high byte)
+    w.u32_le(id.process as u32);
+    w.u8((id.process >> 32) as u8);
+    // Counter: 3 bytes big-endian
+    w.u8((id.counter >> 16) as u8);
+    w.u16(id.counter as u16);
 }
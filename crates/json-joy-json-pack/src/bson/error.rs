@@ -5,10 +5,26 @@ use thiserror::Error;
 /// Error type for BSON decoding operations.
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum BsonError {
-    #[error("unexpected end of input")]
-    UnexpectedEof,
-    #[error("unsupported BSON element type: 0x{0:02x}")]
-    UnsupportedType(u8),
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("unsupported BSON element type 0x{0:02x} at offset {1}")]
+    UnsupportedType(u8, usize),
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
+    /// A length prefix is negative, too small for its value, or disagrees
+    /// with the bytes that follow it.
+    #[error("invalid BSON length at offset {0}")]
+    InvalidLength(usize),
+}
+
+impl BsonError {
+    /// Byte offset in the input at which the error was detected.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedEof(x)
+            | Self::UnsupportedType(_, x)
+            | Self::InvalidUtf8(x)
+            | Self::InvalidLength(x) => Some(*x),
+        }
+    }
 }
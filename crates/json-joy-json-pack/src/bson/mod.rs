@@ -5,11 +5,13 @@
 pub mod decoder;
 pub mod encoder;
 pub mod error;
+pub mod scanner;
 pub mod values;
 
 pub use decoder::BsonDecoder;
 pub use encoder::BsonEncoder;
 pub use error::BsonError;
+pub use scanner::{BsonElement, BsonScanner};
 pub use values::{
     BsonBinary, BsonDbPointer, BsonDecimal128, BsonFloat, BsonInt32, BsonInt64, BsonJavascriptCode,
     BsonJavascriptCodeWithScope, BsonMaxKey, BsonMinKey, BsonObjectId, BsonSymbol, BsonTimestamp,
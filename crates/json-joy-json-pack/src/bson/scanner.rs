@@ -0,0 +1,260 @@
+//! Lazy, allocation-free BSON document scanner.
+//!
+//! Not an upstream port: upstream only decodes whole documents. The scanner
+//! walks a document element by element, yielding each field name, type byte
+//! and raw value bytes, so a proxy can inspect a few fields of a Mongo wire
+//! payload (e.g. the command name or `$db`) without materializing the rest.
+
+use super::decoder::BsonDecoder;
+use super::error::BsonError;
+use super::values::BsonValue;
+
+/// One element of a scanned document. `value` borrows the raw value bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BsonElement<'a> {
+    pub name: &'a str,
+    /// BSON element type byte, e.g. `0x02` for a string.
+    pub typ: u8,
+    pub value: &'a [u8],
+    /// Offset of `value` in the buffer the outermost scanner was created on.
+    pub offset: usize,
+}
+
+impl<'a> BsonElement<'a> {
+    /// Decodes the value into an owned [`BsonValue`].
+    pub fn decode(&self) -> Result<BsonValue, BsonError> {
+        BsonDecoder::new()
+            .decode_value(self.typ, self.value)
+            .map_err(|e| shift(e, self.offset))
+    }
+
+    /// Scans an embedded document (0x03) or array (0x04).
+    pub fn scan(&self) -> Option<BsonScanner<'a>> {
+        match self.typ {
+            0x03 | 0x04 => BsonScanner::at(self.value, self.offset).ok(),
+            _ => None,
+        }
+    }
+
+    /// The text of a string (0x02), JavaScript code (0x0d) or symbol (0x0e).
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.typ {
+            0x02 | 0x0d | 0x0e => {
+                let bytes = self.value.get(4..self.value.len().checked_sub(1)?)?;
+                core::str::from_utf8(bytes).ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if self.typ != 0x08 {
+            return None;
+        }
+        self.value.first().map(|&b| b == 1)
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        if self.typ != 0x10 {
+            return None;
+        }
+        self.value.try_into().ok().map(i32::from_le_bytes)
+    }
+
+    /// An int64 (0x12) or UTC datetime (0x09).
+    pub fn as_i64(&self) -> Option<i64> {
+        if !matches!(self.typ, 0x09 | 0x12) {
+            return None;
+        }
+        self.value.try_into().ok().map(i64::from_le_bytes)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        if self.typ != 0x01 {
+            return None;
+        }
+        self.value.try_into().ok().map(f64::from_le_bytes)
+    }
+}
+
+/// Iterates the elements of one BSON document without decoding values.
+///
+/// Only the framing needed to find each value's end is checked; value
+/// contents (UTF-8, nested documents) are validated when decoded.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_json_pack::bson::{BsonEncoder, BsonScanner, BsonValue};
+///
+/// let doc = BsonEncoder::new().encode(&[
+///     ("find".into(), BsonValue::Str("users".into())),
+///     ("$db".into(), BsonValue::Str("app".into())),
+/// ]);
+/// let mut scanner = BsonScanner::new(&doc).unwrap();
+/// let db = scanner.find_field("$db").unwrap().unwrap();
+/// assert_eq!(db.as_str(), Some("app"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BsonScanner<'a> {
+    doc: &'a [u8],
+    base: usize,
+    x: usize,
+}
+
+impl<'a> BsonScanner<'a> {
+    /// Starts scanning the document at the front of `data`. Bytes after the
+    /// declared document size are ignored; see [`size`](Self::size).
+    pub fn new(data: &'a [u8]) -> Result<Self, BsonError> {
+        Self::at(data, 0)
+    }
+
+    fn at(data: &'a [u8], base: usize) -> Result<Self, BsonError> {
+        let header = data
+            .get(..4)
+            .ok_or(BsonError::UnexpectedEof(base + data.len()))?;
+        let size = i32::from_le_bytes(header.try_into().unwrap());
+        let size = match usize::try_from(size) {
+            Ok(n) if n >= 5 => n,
+            _ => return Err(BsonError::InvalidLength(base)),
+        };
+        if size > data.len() {
+            return Err(BsonError::UnexpectedEof(base));
+        }
+        if data[size - 1] != 0 {
+            return Err(BsonError::InvalidLength(base));
+        }
+        Ok(Self {
+            doc: &data[..size],
+            base,
+            x: 4,
+        })
+    }
+
+    /// Declared size of the document in bytes.
+    pub fn size(&self) -> usize {
+        self.doc.len()
+    }
+
+    /// Reads the next element, or `None` at the end of the document.
+    pub fn next_element(&mut self) -> Result<Option<BsonElement<'a>>, BsonError> {
+        let end = self.doc.len() - 1;
+        if self.x >= end {
+            return Ok(None);
+        }
+        let type_pos = self.x;
+        let typ = self.doc[type_pos];
+        if typ == 0 {
+            return Err(BsonError::InvalidLength(self.base + type_pos));
+        }
+        let name_pos = type_pos + 1;
+        let name_len = self.doc[name_pos..end]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(BsonError::UnexpectedEof(self.base + name_pos))?;
+        let name = core::str::from_utf8(&self.doc[name_pos..name_pos + name_len])
+            .map_err(|_| BsonError::InvalidUtf8(self.base + name_pos))?;
+        let value_pos = name_pos + name_len + 1;
+        let size = self.value_size(typ, type_pos, value_pos, end)?;
+        self.x = value_pos + size;
+        Ok(Some(BsonElement {
+            name,
+            typ,
+            value: &self.doc[value_pos..value_pos + size],
+            offset: self.base + value_pos,
+        }))
+    }
+
+    /// Skips the next element, returning `false` at the end of the document.
+    pub fn skip_field(&mut self) -> Result<bool, BsonError> {
+        Ok(self.next_element()?.is_some())
+    }
+
+    /// Advances to the element named `name`, skipping the ones before it.
+    ///
+    /// Scanning resumes after the returned element, so repeated calls find
+    /// fields in document order.
+    pub fn find_field(&mut self, name: &str) -> Result<Option<BsonElement<'a>>, BsonError> {
+        while let Some(element) = self.next_element()? {
+            if element.name == name {
+                return Ok(Some(element));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Byte length of the value of type `typ` at `pos`, which must end by `end`.
+    fn value_size(
+        &self,
+        typ: u8,
+        type_pos: usize,
+        pos: usize,
+        end: usize,
+    ) -> Result<usize, BsonError> {
+        let size = match typ {
+            0x06 | 0x0a | 0x7f | 0xff => 0,
+            0x08 => 1,
+            0x10 => 4,
+            0x01 | 0x09 | 0x11 | 0x12 => 8,
+            0x07 => 12,
+            0x13 => 16,
+            0x02 | 0x0d | 0x0e => 4 + self.length(pos, end, 1)?,
+            0x03 | 0x04 | 0x0f => self.length(pos, end, 5)?,
+            0x05 => 5 + self.length(pos, end, 0)?,
+            0x0c => 4 + self.length(pos, end, 1)? + 12,
+            0x0b => {
+                let mut x = pos;
+                for _ in 0..2 {
+                    let n = self.doc[x..end]
+                        .iter()
+                        .position(|&b| b == 0)
+                        .ok_or(BsonError::UnexpectedEof(self.base + x))?;
+                    x += n + 1;
+                }
+                x - pos
+            }
+            t => return Err(BsonError::UnsupportedType(t, self.base + type_pos)),
+        };
+        if pos + size > end {
+            return Err(BsonError::UnexpectedEof(self.base + pos));
+        }
+        Ok(size)
+    }
+
+    /// Reads an `i32` length prefix at `pos` that must be at least `min`.
+    fn length(&self, pos: usize, end: usize, min: usize) -> Result<usize, BsonError> {
+        if pos + 4 > end {
+            return Err(BsonError::UnexpectedEof(self.base + pos));
+        }
+        let bytes = self.doc[pos..pos + 4].try_into().unwrap();
+        match usize::try_from(i32::from_le_bytes(bytes)) {
+            Ok(n) if n >= min => Ok(n),
+            _ => Err(BsonError::InvalidLength(self.base + pos)),
+        }
+    }
+}
+
+impl<'a> Iterator for BsonScanner<'a> {
+    type Item = Result<BsonElement<'a>, BsonError>;
+
+    /// Yields elements in order; stops after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_element() {
+            Ok(element) => element.map(Ok),
+            Err(e) => {
+                self.x = self.doc.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Rebases an error offset from a value slice onto the scanned buffer.
+fn shift(e: BsonError, base: usize) -> BsonError {
+    match e {
+        BsonError::UnexpectedEof(x) => BsonError::UnexpectedEof(base + x),
+        BsonError::UnsupportedType(t, x) => BsonError::UnsupportedType(t, base + x),
+        BsonError::InvalidUtf8(x) => BsonError::InvalidUtf8(base + x),
+        BsonError::InvalidLength(x) => BsonError::InvalidLength(base + x),
+    }
+}
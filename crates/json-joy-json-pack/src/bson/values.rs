@@ -2,14 +2,152 @@
 //!
 //! Upstream reference: `json-pack/src/bson/values.ts`
 
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
 /// BSON ObjectId (12 bytes: 4-byte timestamp + 5-byte process ID + 3-byte counter).
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Ids order by timestamp, then process, then counter, the same as their hex
+/// strings sort. Like the wire format, equality, ordering and hashing only
+/// see the low 40 bits of `process` and the low 24 bits of `counter`.
+#[derive(Debug, Clone)]
 pub struct BsonObjectId {
     pub timestamp: u32,
     pub process: u64,
     pub counter: u32,
 }
 
+impl BsonObjectId {
+    /// A fresh id created now. See [`with_timestamp`](Self::with_timestamp).
+    ///
+    /// Not available on `wasm32-unknown-unknown`, which has no clock; pass
+    /// the time to [`with_timestamp`](Self::with_timestamp) there.
+    ///
+    /// Not an upstream port.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    // No `Default`: a default that differs on every call would surprise.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as u32);
+        Self::with_timestamp(timestamp)
+    }
+
+    /// A fresh id created at `timestamp` seconds since the Unix epoch: a
+    /// random value fixed for this process, and a counter that starts at a
+    /// random value and wraps at 24 bits.
+    ///
+    /// Not an upstream port.
+    pub fn with_timestamp(timestamp: u32) -> Self {
+        static PROCESS: OnceLock<u64> = OnceLock::new();
+        static COUNTER: OnceLock<AtomicU32> = OnceLock::new();
+        let process = *PROCESS.get_or_init(|| random_u64() & 0xFF_FFFF_FFFF);
+        let counter = COUNTER
+            .get_or_init(|| AtomicU32::new(random_u64() as u32))
+            .fetch_add(1, Ordering::Relaxed);
+        Self {
+            timestamp,
+            process,
+            counter: counter & 0xFF_FFFF,
+        }
+    }
+
+    /// The smallest id created at `timestamp` seconds, for range queries
+    /// over creation time.
+    ///
+    /// Not an upstream port.
+    pub fn from_timestamp(timestamp: u32) -> Self {
+        Self {
+            timestamp,
+            process: 0,
+            counter: 0,
+        }
+    }
+
+    /// Parse the 24-digit hex form used by EJSON `$oid`, in either case.
+    ///
+    /// Not an upstream port.
+    pub fn parse_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 24 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(Self {
+            timestamp: u32::from_str_radix(&hex[0..8], 16).ok()?,
+            process: u64::from_str_radix(&hex[8..18], 16).ok()?,
+            counter: u32::from_str_radix(&hex[18..24], 16).ok()?,
+        })
+    }
+
+    /// The 24-digit lowercase hex form used by EJSON `$oid`.
+    ///
+    /// Not an upstream port.
+    pub fn to_hex(&self) -> String {
+        let (timestamp, process, counter) = self.key();
+        format!("{timestamp:08x}{process:010x}{counter:06x}")
+    }
+
+    /// The creation time in milliseconds since the Unix epoch, as
+    /// [`BsonValue::DateTime`] holds it.
+    ///
+    /// Not an upstream port.
+    pub fn timestamp_ms(&self) -> i64 {
+        i64::from(self.timestamp) * 1000
+    }
+
+    /// The fields as the wire format keeps them.
+    fn key(&self) -> (u32, u64, u32) {
+        (
+            self.timestamp,
+            self.process & 0xFF_FFFF_FFFF,
+            self.counter & 0xFF_FFFF,
+        )
+    }
+}
+
+impl PartialEq for BsonObjectId {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for BsonObjectId {}
+
+impl PartialOrd for BsonObjectId {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BsonObjectId {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for BsonObjectId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl fmt::Display for BsonObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// 64 random bits without a `rand` dependency: std seeds `RandomState`
+/// from the OS and changes the keys of every new one.
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
 /// BSON DBPointer (deprecated BSON type).
 #[derive(Debug, Clone, PartialEq)]
 pub struct BsonDbPointer {
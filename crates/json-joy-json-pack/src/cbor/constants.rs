@@ -34,6 +34,7 @@ pub const CBOR_END: u8 = 0xff;
 
 /// Returns `true` if `f` can be losslessly represented as an `f32`.
 #[inline]
+#[cfg(feature = "std")]
 pub fn is_f32_roundtrip(f: f64) -> bool {
     (f as f32) as f64 == f
 }
@@ -2,17 +2,289 @@
 //!
 //! Provides `json_to_cbor`, `cbor_to_json`, `cbor_to_json_owned` using our
 //! own `PackValue` type instead of `ciborium::Value`.
+//!
+//! [`cbor_to_json_with`] and [`decode_json_from_cbor_bytes_with`] make the
+//! lossy parts of the conversion configurable via [`CborToJsonOptions`];
+//! [`json_to_cbor_with`] encodes selected JSON fields as typed CBOR values
+//! via [`CborJsonHints`].
 
 use serde_json::Value as JsonValue;
 
+use super::decoder::CborDecoder;
+use super::decoder_base::CborDecoderOptions;
 use super::error::CborError;
-use crate::PackValue;
+use crate::{JsonPackBigInt, JsonPackExtension, PackValue};
+
+/// How byte strings are written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CborBytesPolicy {
+    /// `"data:application/octet-stream;base64,..."` (upstream behaviour).
+    #[default]
+    DataUri,
+    /// Bare base64 string.
+    Base64,
+    /// Lowercase hex string.
+    Hex,
+    /// Array of byte values.
+    Array,
+}
+
+/// How tagged values are written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CborTagPolicy {
+    /// Drop the tag and convert the tagged value (upstream behaviour).
+    #[default]
+    Unwrap,
+    /// `{"tag": <number>, "value": <value>}`.
+    Wrap,
+    /// Fail with [`CborError::UnexpectedTag`].
+    Error,
+}
+
+/// How map keys that are not text strings are handled when decoding bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CborKeyPolicy {
+    /// Convert to their text form, e.g. `1` becomes `"1"` (upstream behaviour).
+    #[default]
+    Stringify,
+    /// Fail with [`CborError::UnexpectedObjKey`].
+    Error,
+}
+
+/// How NaN and the infinities are written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CborNonFinitePolicy {
+    /// `null` (upstream behaviour).
+    #[default]
+    Null,
+    /// `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
+    /// Fail with [`CborError::NonFiniteFloat`].
+    Error,
+}
+
+/// Options for converting CBOR values to JSON. The defaults reproduce
+/// [`cbor_to_json`].
+#[derive(Debug, Clone, Default)]
+pub struct CborToJsonOptions {
+    pub bytes: CborBytesPolicy,
+    pub tags: CborTagPolicy,
+    /// Only applies to [`decode_json_from_cbor_bytes_with`]; a [`PackValue`]
+    /// already holds its keys as strings.
+    pub keys: CborKeyPolicy,
+    pub non_finite: CborNonFinitePolicy,
+}
+
+/// CBOR type to produce for a JSON value selected by [`CborJsonHints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CborTypeHint {
+    /// Standard base64 string to a byte string.
+    BytesBase64,
+    /// URL-safe base64 string to a byte string.
+    BytesBase64Url,
+    /// Hex string to a byte string.
+    BytesHex,
+    /// Integer number or decimal string to an integer, or a bignum (tag 2
+    /// or 3) when it does not fit in 64 bits.
+    BigInt,
+    /// RFC 3339 date string wrapped in tag 0.
+    DateTag0,
+    /// Epoch-seconds number wrapped in tag 1.
+    DateTag1,
+}
+
+/// Per-path type hints for [`json_to_cbor_with`].
+///
+/// Paths are JSON Pointers (RFC 6901) in which a `*` segment matches any key
+/// or array index. When several paths match, the first one added wins.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_json_pack::cbor::{json_to_cbor_with, CborJsonHints, CborTypeHint};
+/// use json_joy_json_pack::PackValue;
+///
+/// let mut hints = CborJsonHints::new();
+/// hints.add("/sigs/*", CborTypeHint::BytesHex);
+/// let value = json_to_cbor_with(&serde_json::json!({"sigs": ["0aff"]}), &hints).unwrap();
+/// assert_eq!(
+///     value,
+///     PackValue::Object(vec![(
+///         "sigs".into(),
+///         PackValue::Array(vec![PackValue::Bytes(vec![0x0a, 0xff])]),
+///     )])
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CborJsonHints {
+    hints: Vec<(Vec<String>, CborTypeHint)>,
+}
+
+impl CborJsonHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: &str, hint: CborTypeHint) -> &mut Self {
+        let steps = path
+            .split('/')
+            .skip(1)
+            .map(|s| s.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        self.hints.push((steps, hint));
+        self
+    }
+
+    fn get(&self, path: &[String]) -> Option<CborTypeHint> {
+        self.hints.iter().find_map(|(steps, hint)| {
+            let matches = steps.len() == path.len()
+                && steps.iter().zip(path).all(|(s, p)| s == "*" || s == p);
+            matches.then_some(*hint)
+        })
+    }
+}
 
 /// Convert `serde_json::Value` to `PackValue`.
 pub fn json_to_cbor(v: &JsonValue) -> PackValue {
     PackValue::from(v.clone())
 }
 
+/// Convert `serde_json::Value` to `PackValue`, encoding hinted fields as
+/// typed CBOR values. Fails with [`CborError::HintMismatch`] when a hinted
+/// value cannot be converted.
+pub fn json_to_cbor_with(v: &JsonValue, hints: &CborJsonHints) -> Result<PackValue, CborError> {
+    json_to_cbor_at(v, hints, &mut Vec::new())
+}
+
+fn json_to_cbor_at(
+    v: &JsonValue,
+    hints: &CborJsonHints,
+    path: &mut Vec<String>,
+) -> Result<PackValue, CborError> {
+    if let Some(hint) = hints.get(path) {
+        return apply_hint(v, hint).ok_or_else(|| CborError::HintMismatch(pointer(path)));
+    }
+    Ok(match v {
+        JsonValue::Array(arr) => {
+            let mut items = Vec::with_capacity(arr.len());
+            for (i, item) in arr.iter().enumerate() {
+                path.push(i.to_string());
+                items.push(json_to_cbor_at(item, hints, path)?);
+                path.pop();
+            }
+            PackValue::Array(items)
+        }
+        JsonValue::Object(obj) => {
+            let mut fields = Vec::with_capacity(obj.len());
+            for (key, item) in obj {
+                path.push(key.clone());
+                fields.push((key.clone(), json_to_cbor_at(item, hints, path)?));
+                path.pop();
+            }
+            PackValue::Object(fields)
+        }
+        _ => json_to_cbor(v),
+    })
+}
+
+fn apply_hint(v: &JsonValue, hint: CborTypeHint) -> Option<PackValue> {
+    Some(match (hint, v) {
+        (CborTypeHint::BytesBase64, JsonValue::String(s)) => {
+            PackValue::Bytes(json_joy_base64::from_base64(s).ok()?)
+        }
+        (CborTypeHint::BytesBase64Url, JsonValue::String(s)) => {
+            PackValue::Bytes(json_joy_base64::from_base64_url(s).ok()?)
+        }
+        (CborTypeHint::BytesHex, JsonValue::String(s)) => PackValue::Bytes(from_hex(s)?),
+        (CborTypeHint::BigInt, JsonValue::Number(n)) if !n.is_f64() => {
+            PackValue::BigNum(n.to_string().parse().ok()?)
+        }
+        (CborTypeHint::BigInt, JsonValue::String(s)) => {
+            PackValue::BigNum(s.parse::<JsonPackBigInt>().ok()?)
+        }
+        (CborTypeHint::DateTag0, JsonValue::String(s)) if is_rfc3339(s) => PackValue::Extension(
+            Box::new(JsonPackExtension::new(0, PackValue::Str(s.clone()))),
+        ),
+        (CborTypeHint::DateTag1, JsonValue::Number(_)) => {
+            PackValue::Extension(Box::new(JsonPackExtension::new(1, json_to_cbor(v))))
+        }
+        _ => return None,
+    })
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Whether `s` is an RFC 3339 `date-time`, such as
+/// `1985-04-12T23:20:50.52Z` or `1996-12-19T16:39:57-08:00`.
+fn is_rfc3339(s: &str) -> bool {
+    let b = s.as_bytes();
+    let num = |at: usize, len: usize| -> Option<u32> {
+        let digits = b.get(at..at + len)?;
+        digits.iter().try_fold(0, |n, &d| {
+            d.is_ascii_digit().then(|| n * 10 + u32::from(d - b'0'))
+        })
+    };
+    let sep = |at: usize, c: u8| b.get(at).is_some_and(|&x| x.eq_ignore_ascii_case(&c));
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) = (
+        num(0, 4),
+        num(5, 2),
+        num(8, 2),
+        num(11, 2),
+        num(14, 2),
+        num(17, 2),
+    ) else {
+        return false;
+    };
+    if !(sep(4, b'-') && sep(7, b'-') && sep(10, b'T') && sep(13, b':') && sep(16, b':')) {
+        return false;
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    if day == 0 || day > days || hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+    let mut at = 19;
+    if sep(at, b'.') {
+        let digits = b[at + 1..]
+            .iter()
+            .take_while(|d| d.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return false;
+        }
+        at += 1 + digits;
+    }
+    if sep(at, b'Z') {
+        return at + 1 == b.len();
+    }
+    (sep(at, b'+') || sep(at, b'-'))
+        && sep(at + 3, b':')
+        && at + 6 == b.len()
+        && num(at + 1, 2).is_some_and(|h| h <= 23)
+        && num(at + 4, 2).is_some_and(|m| m <= 59)
+}
+
+/// Formats `path` as a JSON Pointer.
+fn pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|s| format!("/{}", s.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
 /// Convert `PackValue` to `serde_json::Value`.
 pub fn cbor_to_json(v: &PackValue) -> Result<JsonValue, CborError> {
     Ok(cbor_to_json_owned(v.clone()))
@@ -22,3 +294,65 @@ pub fn cbor_to_json(v: &PackValue) -> Result<JsonValue, CborError> {
 pub fn cbor_to_json_owned(v: PackValue) -> JsonValue {
     super::decoder::pack_to_json(v)
 }
+
+/// Convert `PackValue` to `serde_json::Value` under the given policies.
+pub fn cbor_to_json_with(
+    v: &PackValue,
+    options: &CborToJsonOptions,
+) -> Result<JsonValue, CborError> {
+    Ok(match v {
+        PackValue::Float(f) if !f.is_finite() => match options.non_finite {
+            CborNonFinitePolicy::Null => JsonValue::Null,
+            CborNonFinitePolicy::String => JsonValue::String(
+                if f.is_nan() {
+                    "NaN"
+                } else if *f > 0.0 {
+                    "Infinity"
+                } else {
+                    "-Infinity"
+                }
+                .into(),
+            ),
+            CborNonFinitePolicy::Error => return Err(CborError::NonFiniteFloat),
+        },
+        PackValue::Bytes(b) => match options.bytes {
+            CborBytesPolicy::DataUri => cbor_to_json_owned(v.clone()),
+            CborBytesPolicy::Base64 => JsonValue::String(json_joy_base64::to_base64(b)),
+            CborBytesPolicy::Hex => {
+                JsonValue::String(b.iter().map(|byte| format!("{byte:02x}")).collect())
+            }
+            CborBytesPolicy::Array => JsonValue::Array(b.iter().map(|&x| x.into()).collect()),
+        },
+        PackValue::Array(arr) => JsonValue::Array(
+            arr.iter()
+                .map(|v| cbor_to_json_with(v, options))
+                .collect::<Result<_, _>>()?,
+        ),
+        PackValue::Object(obj) => JsonValue::Object(
+            obj.iter()
+                .map(|(k, v)| Ok((k.clone(), cbor_to_json_with(v, options)?)))
+                .collect::<Result<_, CborError>>()?,
+        ),
+        PackValue::Extension(ext) => match options.tags {
+            CborTagPolicy::Unwrap => cbor_to_json_with(&ext.val, options)?,
+            CborTagPolicy::Wrap => serde_json::json!({
+                "tag": ext.tag,
+                "value": cbor_to_json_with(&ext.val, options)?,
+            }),
+            CborTagPolicy::Error => return Err(CborError::UnexpectedTag(ext.tag)),
+        },
+        _ => cbor_to_json_owned(v.clone()),
+    })
+}
+
+/// Decode CBOR bytes into a `serde_json::Value` under the given policies.
+pub fn decode_json_from_cbor_bytes_with(
+    bytes: &[u8],
+    options: &CborToJsonOptions,
+) -> Result<JsonValue, CborError> {
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        strict_keys: options.keys == CborKeyPolicy::Error,
+        ..CborDecoderOptions::default()
+    });
+    cbor_to_json_with(&decoder.decode(bytes)?, options)
+}
@@ -0,0 +1,456 @@
+//! COSE single-signer and single-recipient messages (RFC 9052).
+//!
+//! Not an upstream port. Builds and parses the CBOR structures of
+//! `COSE_Sign1` and `COSE_Mac0`: header maps, the `Sig_structure` /
+//! `MAC_structure` that gets signed or MACed, and the message itself, with
+//! an attached or detached payload. The cryptography is the caller's: it
+//! comes in as a closure over the bytes to sign or check.
+//!
+//! Everything is written with deterministic encoding (RFC 8949 §4.2.1):
+//! shortest-form lengths and header labels sorted by their encoded bytes.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use super::decoder::CborDecoder;
+use super::decoder_base::CborDecoderOptions;
+use super::encoder_stable::CborEncoderStable;
+use super::error::CborError;
+use super::lazy::LazyValue;
+use crate::PackValue;
+
+/// CBOR tag of a `COSE_Sign1` message.
+pub const COSE_SIGN1_TAG: u64 = 18;
+/// CBOR tag of a `COSE_Mac0` message.
+pub const COSE_MAC0_TAG: u64 = 17;
+
+/// Header label `alg`: the algorithm, an integer or text.
+pub const COSE_HEADER_ALG: i64 = 1;
+/// Header label `crit`: labels the recipient must understand.
+pub const COSE_HEADER_CRIT: i64 = 2;
+/// Header label `content type`.
+pub const COSE_HEADER_CONTENT_TYPE: i64 = 3;
+/// Header label `kid`: the key identifier, a byte string.
+pub const COSE_HEADER_KID: i64 = 4;
+/// Header label `IV`.
+pub const COSE_HEADER_IV: i64 = 5;
+/// Header label `Partial IV`.
+pub const COSE_HEADER_PARTIAL_IV: i64 = 6;
+
+/// Algorithm ECDSA w/ SHA-256.
+pub const COSE_ALG_ES256: i64 = -7;
+/// Algorithm EdDSA.
+pub const COSE_ALG_EDDSA: i64 = -8;
+/// Algorithm ECDSA w/ SHA-384.
+pub const COSE_ALG_ES384: i64 = -35;
+/// Algorithm HMAC w/ SHA-256.
+pub const COSE_ALG_HMAC_256_256: i64 = 5;
+
+/// Error reading or checking a COSE message.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CoseError {
+    #[error(transparent)]
+    Cbor(#[from] CborError),
+    /// The CBOR is well formed but not the COSE structure expected.
+    #[error("invalid COSE structure")]
+    InvalidStructure,
+    /// A header map repeats a label.
+    #[error("duplicate COSE header label")]
+    DuplicateLabel,
+    /// The payload is detached and none was supplied.
+    #[error("detached COSE payload not supplied")]
+    MissingPayload,
+    /// A detached payload was supplied for a message that carries one.
+    #[error("COSE payload both attached and supplied detached")]
+    UnexpectedPayload,
+    /// A CWT claim holds a value of the wrong type.
+    #[error("invalid CWT claim {0}")]
+    InvalidClaim(i64),
+}
+
+/// A header label: an integer, or text for private-use labels.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CoseLabel {
+    Int(i64),
+    Text(String),
+}
+
+impl From<i64> for CoseLabel {
+    fn from(label: i64) -> Self {
+        Self::Int(label)
+    }
+}
+
+impl From<&str> for CoseLabel {
+    fn from(label: &str) -> Self {
+        Self::Text(label.into())
+    }
+}
+
+/// A COSE header map.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoseHeaderMap {
+    /// Entries in insertion order; encoding sorts them.
+    pub entries: Vec<(CoseLabel, PackValue)>,
+}
+
+impl CoseHeaderMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, label: &CoseLabel) -> Option<&PackValue> {
+        self.entries
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v)
+    }
+
+    /// Sets `label`, replacing any previous value.
+    pub fn insert(&mut self, label: impl Into<CoseLabel>, value: PackValue) {
+        let label = label.into();
+        match self.entries.iter_mut().find(|(l, _)| *l == label) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((label, value)),
+        }
+    }
+
+    pub fn with(mut self, label: impl Into<CoseLabel>, value: PackValue) -> Self {
+        self.insert(label, value);
+        self
+    }
+
+    pub fn with_alg(self, alg: i64) -> Self {
+        self.with(COSE_HEADER_ALG, PackValue::Integer(alg))
+    }
+
+    pub fn with_kid(self, kid: impl Into<Vec<u8>>) -> Self {
+        self.with(COSE_HEADER_KID, PackValue::Bytes(kid.into()))
+    }
+
+    pub fn with_content_type(self, content_type: PackValue) -> Self {
+        self.with(COSE_HEADER_CONTENT_TYPE, content_type)
+    }
+
+    /// The integer `alg`, if present.
+    pub fn alg(&self) -> Option<i64> {
+        match self.get(&CoseLabel::Int(COSE_HEADER_ALG))? {
+            PackValue::Integer(alg) => Some(*alg),
+            _ => None,
+        }
+    }
+
+    pub fn kid(&self) -> Option<&[u8]> {
+        match self.get(&CoseLabel::Int(COSE_HEADER_KID))? {
+            PackValue::Bytes(kid) => Some(kid),
+            _ => None,
+        }
+    }
+
+    /// The `content type`: an integer CoAP content format or a media type.
+    pub fn content_type(&self) -> Option<&PackValue> {
+        self.get(&CoseLabel::Int(COSE_HEADER_CONTENT_TYPE))
+    }
+
+    /// Encodes the map with its labels in deterministic order.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = CborEncoderStable::new();
+        self.write(&mut encoder);
+        encoder.writer.flush()
+    }
+
+    /// The encoding of a protected header: the encoded map, or nothing for
+    /// an empty map (RFC 9052 §3).
+    pub fn encode_protected(&self) -> Vec<u8> {
+        if self.is_empty() {
+            Vec::new()
+        } else {
+            self.encode()
+        }
+    }
+
+    fn write(&self, encoder: &mut CborEncoderStable) {
+        // Labels are encoded on their own so they can be sorted by bytes.
+        let mut labels = CborEncoderStable::new();
+        let mut keyed: Vec<(Vec<u8>, &PackValue)> = self
+            .entries
+            .iter()
+            .map(|(label, value)| {
+                match label {
+                    CoseLabel::Int(label) => labels.write_integer(*label),
+                    CoseLabel::Text(label) => labels.write_str(label),
+                }
+                (labels.writer.flush(), value)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        encoder.write_obj_hdr(keyed.len());
+        for (key, value) in keyed {
+            encoder.writer.buf(&key);
+            encoder.write_any(value);
+        }
+    }
+
+    /// Decodes an encoded header map.
+    pub fn decode(data: &[u8]) -> Result<Self, CoseError> {
+        let value = LazyValue::new(data);
+        if value.bytes()?.len() != data.len() {
+            return Err(CoseError::InvalidStructure);
+        }
+        Self::read(value)
+    }
+
+    /// Decodes a protected header, where no bytes mean an empty map.
+    pub fn decode_protected(data: &[u8]) -> Result<Self, CoseError> {
+        if data.is_empty() {
+            Ok(Self::new())
+        } else {
+            Self::decode(data)
+        }
+    }
+
+    fn read(value: LazyValue) -> Result<Self, CoseError> {
+        // Values such as a `COSE_Key` are maps with integer labels too.
+        let decoder = CborDecoder::with_options(CborDecoderOptions {
+            any_keys: true,
+            ..CborDecoderOptions::default()
+        });
+        let mut map = Self::new();
+        for entry in value.as_map()?.iter() {
+            let (key, value) = entry?;
+            let label = match key.as_i64() {
+                Ok(label) => CoseLabel::Int(label),
+                Err(_) => CoseLabel::Text(key.as_str()?.into()),
+            };
+            if map.get(&label).is_some() {
+                return Err(CoseError::DuplicateLabel);
+            }
+            map.entries.push((label, decoder.decode(value.bytes()?)?));
+        }
+        Ok(map)
+    }
+}
+
+/// The four fields shared by `COSE_Sign1` and `COSE_Mac0`, as decoded.
+struct Parts {
+    protected: Vec<u8>,
+    unprotected: CoseHeaderMap,
+    payload: Option<Vec<u8>>,
+    last: Vec<u8>,
+}
+
+/// Encodes `[context, protected, external_aad, payload]`.
+fn tbs(context: &str, protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut encoder = CborEncoderStable::new();
+    encoder.write_arr_hdr(4);
+    encoder.write_str(context);
+    encoder.write_bin(protected);
+    encoder.write_bin(external_aad);
+    encoder.write_bin(payload);
+    encoder.writer.flush()
+}
+
+fn encode_parts(
+    tag: Option<u64>,
+    protected: &[u8],
+    unprotected: &CoseHeaderMap,
+    payload: Option<&[u8]>,
+    last: &[u8],
+) -> Vec<u8> {
+    let mut encoder = CborEncoderStable::new();
+    if let Some(tag) = tag {
+        encoder.write_tag_hdr(tag);
+    }
+    encoder.write_arr_hdr(4);
+    encoder.write_bin(protected);
+    unprotected.write(&mut encoder);
+    match payload {
+        Some(payload) => encoder.write_bin(payload),
+        None => encoder.write_null(),
+    }
+    encoder.write_bin(last);
+    encoder.writer.flush()
+}
+
+fn decode_parts(data: &[u8], tag: u64) -> Result<Parts, CoseError> {
+    let value = LazyValue::new(data);
+    if value.bytes()?.len() != data.len() {
+        return Err(CoseError::InvalidStructure);
+    }
+    match value.tags()?[..] {
+        [] => {}
+        [t] if t == tag => {}
+        [t, ..] => return Err(CborError::UnexpectedTag(t).into()),
+    }
+    let items = value.as_array()?;
+    if items.len() != Some(4) {
+        return Err(CoseError::InvalidStructure);
+    }
+    let field = |index| items.get(index)?.ok_or(CborError::IndexOutOfBounds);
+    let payload = field(2)?;
+    Ok(Parts {
+        protected: field(0)?.as_bytes()?.to_vec(),
+        unprotected: CoseHeaderMap::read(field(1)?)?,
+        payload: if payload.is_null() {
+            None
+        } else {
+            Some(payload.as_bytes()?.to_vec())
+        },
+        last: field(3)?.as_bytes()?.to_vec(),
+    })
+}
+
+macro_rules! cose_message {
+    ($(#[$doc:meta])* $name:ident, $last:ident, $tag:expr, $context:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $name {
+            /// The protected header's bytes, exactly as they are covered by
+            /// the cryptography.
+            pub protected: Vec<u8>,
+            pub unprotected: CoseHeaderMap,
+            /// `None` when the payload is detached.
+            pub payload: Option<Vec<u8>>,
+            pub $last: Vec<u8>,
+        }
+
+        impl $name {
+            /// CBOR tag of the message.
+            pub const TAG: u64 = $tag;
+
+            /// Decodes the protected header.
+            pub fn protected_header(&self) -> Result<CoseHeaderMap, CoseError> {
+                CoseHeaderMap::decode_protected(&self.protected)
+            }
+
+            /// The structure the cryptography covers, for this message's
+            /// headers and `external_aad`. A detached payload must be
+            /// passed in, and only then: supplying one for a message that
+            /// carries its payload is an error rather than a silent pick.
+            pub fn to_be_processed(
+                &self,
+                external_aad: &[u8],
+                detached_payload: Option<&[u8]>,
+            ) -> Result<Vec<u8>, CoseError> {
+                let payload = match (detached_payload, self.payload.as_deref()) {
+                    (Some(payload), None) | (None, Some(payload)) => payload,
+                    (None, None) => return Err(CoseError::MissingPayload),
+                    (Some(_), Some(_)) => return Err(CoseError::UnexpectedPayload),
+                };
+                Ok(tbs($context, &self.protected, external_aad, payload))
+            }
+
+            /// Encodes the message, with its CBOR tag if `tagged`.
+            pub fn encode(&self, tagged: bool) -> Vec<u8> {
+                encode_parts(
+                    tagged.then_some(Self::TAG),
+                    &self.protected,
+                    &self.unprotected,
+                    self.payload.as_deref(),
+                    &self.$last,
+                )
+            }
+
+            /// Decodes a tagged or untagged message.
+            pub fn decode(data: &[u8]) -> Result<Self, CoseError> {
+                let parts = decode_parts(data, Self::TAG)?;
+                Ok(Self {
+                    protected: parts.protected,
+                    unprotected: parts.unprotected,
+                    payload: parts.payload,
+                    $last: parts.last,
+                })
+            }
+        }
+    };
+}
+
+cose_message!(
+    /// A `COSE_Sign1` message: one signature over the payload.
+    CoseSign1,
+    signature,
+    COSE_SIGN1_TAG,
+    "Signature1"
+);
+
+cose_message!(
+    /// A `COSE_Mac0` message: one MAC over the payload.
+    CoseMac0,
+    tag,
+    COSE_MAC0_TAG,
+    "MAC0"
+);
+
+impl CoseSign1 {
+    /// Signs `payload`: `signer` gets the `Sig_structure` bytes and returns
+    /// the signature. With `detached`, the message leaves the payload out.
+    pub fn sign<E>(
+        protected: &CoseHeaderMap,
+        unprotected: CoseHeaderMap,
+        payload: &[u8],
+        detached: bool,
+        external_aad: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let protected = protected.encode_protected();
+        let signature = signer(&tbs("Signature1", &protected, external_aad, payload))?;
+        Ok(Self {
+            protected,
+            unprotected,
+            payload: (!detached).then(|| payload.to_vec()),
+            signature,
+        })
+    }
+
+    /// Checks the signature: `verifier` gets the `Sig_structure` bytes and
+    /// the signature.
+    pub fn verify(
+        &self,
+        external_aad: &[u8],
+        detached_payload: Option<&[u8]>,
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<bool, CoseError> {
+        let tbs = self.to_be_processed(external_aad, detached_payload)?;
+        Ok(verifier(&tbs, &self.signature))
+    }
+}
+
+impl CoseMac0 {
+    /// MACs `payload`: `mac` gets the `MAC_structure` bytes and returns the
+    /// tag. With `detached`, the message leaves the payload out.
+    pub fn create<E>(
+        protected: &CoseHeaderMap,
+        unprotected: CoseHeaderMap,
+        payload: &[u8],
+        detached: bool,
+        external_aad: &[u8],
+        mac: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let protected = protected.encode_protected();
+        let tag = mac(&tbs("MAC0", &protected, external_aad, payload))?;
+        Ok(Self {
+            protected,
+            unprotected,
+            payload: (!detached).then(|| payload.to_vec()),
+            tag,
+        })
+    }
+
+    /// Checks the tag: `verifier` gets the `MAC_structure` bytes and the
+    /// tag, and should compare in constant time.
+    pub fn verify(
+        &self,
+        external_aad: &[u8],
+        detached_payload: Option<&[u8]>,
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<bool, CoseError> {
+        let tbs = self.to_be_processed(external_aad, detached_payload)?;
+        Ok(verifier(&tbs, &self.tag))
+    }
+}
@@ -0,0 +1,174 @@
+//! CBOR Web Token claims (RFC 8392).
+//!
+//! Not an upstream port. A typed claims set that converts to and from a
+//! [`PackValue`] map and encodes with the deterministic encoder, ready to
+//! be the payload of a [`CoseSign1`](super::cose::CoseSign1) or
+//! [`CoseMac0`](super::cose::CoseMac0).
+//!
+//! As a [`PackValue`], integer claim keys are decimal strings (`"1"` for
+//! `iss`), the same keys the CBOR decoder produces for them. The dates
+//! `exp`, `nbf` and `iat` are written as tag-1 epoch dates.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::cose::{CoseError, CoseHeaderMap, CoseLabel};
+use super::decoder::decode_cbor_value;
+use crate::{JsonPackExtension, PackValue};
+
+/// Claim key `iss`: the issuer.
+pub const CWT_CLAIM_ISS: i64 = 1;
+/// Claim key `sub`: the subject.
+pub const CWT_CLAIM_SUB: i64 = 2;
+/// Claim key `aud`: the audience.
+pub const CWT_CLAIM_AUD: i64 = 3;
+/// Claim key `exp`: the expiration time.
+pub const CWT_CLAIM_EXP: i64 = 4;
+/// Claim key `nbf`: the time before which the token is not valid.
+pub const CWT_CLAIM_NBF: i64 = 5;
+/// Claim key `iat`: the issue time.
+pub const CWT_CLAIM_IAT: i64 = 6;
+/// Claim key `cti`: the token identifier.
+pub const CWT_CLAIM_CTI: i64 = 7;
+
+/// CBOR tag of an epoch-based date.
+pub const CBOR_TAG_EPOCH_DATE: u64 = 1;
+
+/// A CWT claims set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CwtClaims {
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    pub aud: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub exp: Option<i64>,
+    pub nbf: Option<i64>,
+    pub iat: Option<i64>,
+    pub cti: Option<Vec<u8>>,
+    /// Claims other than the seven registered above.
+    pub other: Vec<(CoseLabel, PackValue)>,
+}
+
+impl CwtClaims {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The claims as a map keyed by label.
+    fn to_map(&self) -> CoseHeaderMap {
+        let date = |secs: i64| {
+            PackValue::Extension(Box::new(JsonPackExtension::new(
+                CBOR_TAG_EPOCH_DATE,
+                PackValue::Integer(secs),
+            )))
+        };
+        let mut map = CoseHeaderMap::new();
+        let text = [
+            (CWT_CLAIM_ISS, &self.iss),
+            (CWT_CLAIM_SUB, &self.sub),
+            (CWT_CLAIM_AUD, &self.aud),
+        ];
+        for (label, value) in text {
+            if let Some(value) = value {
+                map.insert(label, PackValue::Str(value.clone()));
+            }
+        }
+        let dates = [
+            (CWT_CLAIM_EXP, self.exp),
+            (CWT_CLAIM_NBF, self.nbf),
+            (CWT_CLAIM_IAT, self.iat),
+        ];
+        for (label, value) in dates {
+            if let Some(value) = value {
+                map.insert(label, date(value));
+            }
+        }
+        if let Some(cti) = &self.cti {
+            map.insert(CWT_CLAIM_CTI, PackValue::Bytes(cti.clone()));
+        }
+        for (label, value) in &self.other {
+            map.insert(label.clone(), value.clone());
+        }
+        map
+    }
+
+    fn from_map(map: CoseHeaderMap) -> Result<Self, CoseError> {
+        let mut claims = Self::new();
+        for (label, value) in map.entries {
+            let CoseLabel::Int(key @ CWT_CLAIM_ISS..=CWT_CLAIM_CTI) = label else {
+                claims.other.push((label, value));
+                continue;
+            };
+            let invalid = CoseError::InvalidClaim(key);
+            match (key, value) {
+                (CWT_CLAIM_ISS, PackValue::Str(s)) => claims.iss = Some(s),
+                (CWT_CLAIM_SUB, PackValue::Str(s)) => claims.sub = Some(s),
+                (CWT_CLAIM_AUD, PackValue::Str(s)) => claims.aud = Some(s),
+                (CWT_CLAIM_CTI, PackValue::Bytes(b)) => claims.cti = Some(b),
+                (CWT_CLAIM_EXP..=CWT_CLAIM_IAT, value) => {
+                    let secs = read_date(value).ok_or(invalid)?;
+                    match key {
+                        CWT_CLAIM_EXP => claims.exp = Some(secs),
+                        CWT_CLAIM_NBF => claims.nbf = Some(secs),
+                        _ => claims.iat = Some(secs),
+                    }
+                }
+                _ => return Err(invalid),
+            }
+        }
+        Ok(claims)
+    }
+
+    /// The claims as a [`PackValue::Object`], keys in encoded order: what
+    /// the CBOR decoder returns for [`encode`](Self::encode)'s output.
+    /// Fails if an `other` claim holds a [`PackValue::Blob`] that is not
+    /// well-formed CBOR.
+    pub fn to_pack_value(&self) -> Result<PackValue, CoseError> {
+        Ok(decode_cbor_value(&self.encode())?)
+    }
+
+    /// Reads claims from a [`PackValue::Object`] as
+    /// [`to_pack_value`](Self::to_pack_value) writes it. Keys that parse as
+    /// integers are claim labels; dates may be tagged or bare integers.
+    pub fn from_pack_value(value: &PackValue) -> Result<Self, CoseError> {
+        let PackValue::Object(entries) = value else {
+            return Err(CoseError::InvalidStructure);
+        };
+        let mut map = CoseHeaderMap::new();
+        for (key, value) in entries {
+            let label = match key.parse::<i64>() {
+                Ok(label) => CoseLabel::Int(label),
+                Err(_) => CoseLabel::Text(key.clone()),
+            };
+            if map.get(&label).is_some() {
+                return Err(CoseError::DuplicateLabel);
+            }
+            map.entries.push((label, value.clone()));
+        }
+        Self::from_map(map)
+    }
+
+    /// Encodes the claims deterministically, with integer keys.
+    pub fn encode(&self) -> Vec<u8> {
+        self.to_map().encode()
+    }
+
+    /// Decodes an encoded claims set.
+    pub fn decode(data: &[u8]) -> Result<Self, CoseError> {
+        Self::from_map(CoseHeaderMap::decode(data)?)
+    }
+}
+
+/// A NumericDate as seconds, tagged or not. Fractional dates are not
+/// supported.
+fn read_date(value: PackValue) -> Option<i64> {
+    match value {
+        PackValue::Integer(secs) => Some(secs),
+        PackValue::Extension(ext) if ext.tag == CBOR_TAG_EPOCH_DATE => match *ext.val {
+            PackValue::Integer(secs) => Some(secs),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! Direct port of `cbor/CborDecoder.ts` from upstream.
 
-use super::decoder_base::CborDecoderBase;
+use super::decoder_base::{CborDecoderBase, CborDecoderOptions, Cur};
 use super::error::CborError;
+use crate::streaming::StreamingSource;
+use crate::token::TokenSink;
+use crate::util::{KeyCache, KeyCacheStats};
 use crate::PackValue;
+#[cfg(feature = "std")]
 use serde_json::Value as JsonValue;
 
 /// Full CBOR decoder.
@@ -23,6 +27,21 @@ impl CborDecoder {
         }
     }
 
+    pub fn with_options(options: CborDecoderOptions) -> Self {
+        Self {
+            base: CborDecoderBase::with_options(options),
+        }
+    }
+
+    /// A decoder with the same options that interns text map keys through
+    /// `cache`. See [`CborDecoderCached`].
+    pub fn with_key_cache(self, cache: KeyCache) -> CborDecoderCached {
+        CborDecoderCached {
+            base: self.base,
+            key_cache: cache,
+        }
+    }
+
     /// Decode CBOR bytes into a [`PackValue`].
     pub fn decode(&self, input: &[u8]) -> Result<PackValue, CborError> {
         self.base.decode(input)
@@ -33,7 +52,40 @@ impl CborDecoder {
         self.base.decode_with_consumed(input)
     }
 
+    /// Reads the value at the start of `input` as token events, returning
+    /// the number of bytes consumed. Indefinite-length containers are
+    /// reported with their item count.
+    pub fn parse_with<S: TokenSink + ?Sized>(
+        &self,
+        input: &[u8],
+        sink: &mut S,
+    ) -> Result<usize, CborError> {
+        let mut cur = Cur::new(input, 0);
+        self.base.read_tokens(&mut cur, sink)?;
+        Ok(cur.pos)
+    }
+
+    /// Decodes the next value from a chunked source.
+    ///
+    /// Returns `Ok(None)`, leaving the source untouched, while the buffered
+    /// bytes end inside a value; on success the value's bytes are consumed.
+    pub fn read_streaming(
+        &self,
+        source: &mut impl StreamingSource,
+    ) -> Result<Option<PackValue>, CborError> {
+        let result = self.base.decode_with_consumed(&source.unread());
+        match result {
+            Ok((value, consumed)) => {
+                source.advance(consumed);
+                Ok(Some(value))
+            }
+            Err(CborError::UnexpectedEof(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Decode CBOR bytes and convert to `serde_json::Value`.
+    #[cfg(feature = "std")]
     pub fn decode_json(&self, input: &[u8]) -> Result<JsonValue, CborError> {
         let pv = self.decode(input)?;
         Ok(pack_to_json(pv))
@@ -44,8 +96,48 @@ impl CborDecoder {
         self.base.validate(data, offset, size)
     }
 }
+/// CBOR decoder that interns text map keys through a [`KeyCache`] kept
+/// across documents.
+///
+/// The cache changes as keys are read, so decoding takes `&mut self`;
+/// [`CborDecoder`] itself stays stateless and shareable between threads.
+/// Token reads ([`CborDecoder::parse_with`]) borrow keys from the input
+/// and need no cache.
+pub struct CborDecoderCached {
+    base: CborDecoderBase,
+    key_cache: KeyCache,
+}
+
+impl CborDecoderCached {
+    pub fn new(cache: KeyCache) -> Self {
+        CborDecoder::new().with_key_cache(cache)
+    }
+
+    pub fn with_options(options: CborDecoderOptions, cache: KeyCache) -> Self {
+        CborDecoder::with_options(options).with_key_cache(cache)
+    }
+
+    /// Counters of the key cache.
+    pub fn key_cache_stats(&self) -> KeyCacheStats {
+        self.key_cache.stats()
+    }
+
+    /// Decode CBOR bytes into a [`PackValue`].
+    pub fn decode(&mut self, input: &[u8]) -> Result<PackValue, CborError> {
+        self.decode_with_consumed(input).map(|(value, _)| value)
+    }
+
+    /// Decode CBOR bytes, returning value and consumed byte count.
+    pub fn decode_with_consumed(&mut self, input: &[u8]) -> Result<(PackValue, usize), CborError> {
+        let mut cur = Cur::new(input, 0);
+        cur.keys = Some(&mut self.key_cache);
+        let value = self.base.read_any(&mut cur)?;
+        Ok((value, cur.pos))
+    }
+}
 
 /// Convert [`PackValue`] to `serde_json::Value`, losing CBOR-specific types.
+#[cfg(feature = "std")]
 pub fn pack_to_json(v: PackValue) -> JsonValue {
     match v {
         PackValue::Null | PackValue::Undefined | PackValue::Blob(_) => JsonValue::Null,
@@ -63,6 +155,12 @@ pub fn pack_to_json(v: PackValue) -> JsonValue {
                 JsonValue::Null // out of range for JSON numbers
             }
         }
+        PackValue::BigNum(n) => match n.to_i128() {
+            Some(i) if i >= i64::MIN as i128 && i <= i64::MAX as i128 => {
+                JsonValue::Number((i as i64).into())
+            }
+            _ => JsonValue::Null,
+        },
         PackValue::Bytes(b) => {
             use json_joy_base64::to_base64;
             let b64 = to_base64(&b);
@@ -75,6 +173,11 @@ pub fn pack_to_json(v: PackValue) -> JsonValue {
                 obj.into_iter().map(|(k, v)| (k, pack_to_json(v))).collect();
             JsonValue::Object(map)
         }
+        PackValue::Map(map) => JsonValue::Object(
+            map.into_iter()
+                .map(|(k, v)| (k.to_key_string(), pack_to_json(v)))
+                .collect(),
+        ),
         PackValue::Extension(ext) => pack_to_json(*ext.val),
     }
 }
@@ -92,6 +195,7 @@ pub fn decode_cbor_value_with_consumed(bytes: &[u8]) -> Result<(PackValue, usize
 }
 
 /// Decode CBOR bytes into a `serde_json::Value`.
+#[cfg(feature = "std")]
 pub fn decode_json_from_cbor_bytes(bytes: &[u8]) -> Result<JsonValue, CborError> {
     CborDecoder::new().decode_json(bytes)
 }
@@ -102,6 +206,6 @@ pub fn validate_cbor_exact_size(bytes: &[u8], expected_size: usize) -> Result<()
     if consumed == expected_size {
         Ok(())
     } else {
-        Err(CborError::InvalidSize)
+        Err(CborError::InvalidSize(0))
     }
 }
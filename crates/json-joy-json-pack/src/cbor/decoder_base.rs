@@ -2,23 +2,38 @@
 //!
 //! Direct port of `cbor/CborDecoderBase.ts` from upstream.
 
+use alloc::{borrow::ToOwned, boxed::Box, collections::VecDeque, string::String, vec, vec::Vec};
 use json_joy_buffers::decode_f16;
+#[cfg(feature = "f16")]
+use json_joy_buffers::decode_f16_keep_nan;
 
 use super::constants::*;
 use super::error::CborError;
-use crate::{JsonPackExtension, JsonPackValue, PackValue};
+use crate::token::{walk, TokenSink};
+use crate::util::KeyCache;
+use crate::{JsonPackBigInt, JsonPackExtension, JsonPackValue, PackValue};
 
 /// Internal cursor used during decoding.
 pub(crate) struct Cur<'a> {
     pub data: &'a [u8],
     pub pos: usize,
+    /// Cache text map keys are read through, if any.
+    pub keys: Option<&'a mut KeyCache>,
 }
 
 impl<'a> Cur<'a> {
+    pub fn new(data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos,
+            keys: None,
+        }
+    }
+
     #[inline]
     fn check(&self, n: usize) -> Result<(), CborError> {
         if self.pos + n > self.data.len() {
-            Err(CborError::InvalidPayload)
+            Err(CborError::UnexpectedEof(self.pos))
         } else {
             Ok(())
         }
@@ -109,8 +124,8 @@ impl<'a> Cur<'a> {
     #[inline]
     pub fn utf8(&mut self, len: usize) -> Result<&'a str, CborError> {
         self.check(len)?;
-        let s = std::str::from_utf8(&self.data[self.pos..self.pos + len])
-            .map_err(|_| CborError::InvalidPayload)?;
+        let s = crate::utf8::from_utf8(&self.data[self.pos..self.pos + len])
+            .map_err(|_| CborError::InvalidPayload(self.pos))?;
         self.pos += len;
         Ok(s)
     }
@@ -131,37 +146,60 @@ impl<'a> Cur<'a> {
     }
 }
 
-/// Base CBOR decoder. Stateless — instantiate once and reuse.
+/// Options controlling CBOR decoding.
+#[derive(Debug, Clone, Default)]
+pub struct CborDecoderOptions {
+    /// When `true`, bignum tags (2 and 3) wrapping a byte string are decoded
+    /// as [`PackValue::BigNum`] instead of a [`JsonPackExtension`]. Disabled
+    /// by default to match upstream.
+    pub bignum: bool,
+    /// When `true`, map keys that are not text strings fail with
+    /// [`CborError::UnexpectedObjKey`] instead of being converted to their
+    /// text form.
+    pub strict_keys: bool,
+    /// When `true`, maps with a key that is not a text string decode as
+    /// [`PackValue::Map`], keeping the key types; maps with text keys only
+    /// still decode as [`PackValue::Object`]. Takes precedence over
+    /// `strict_keys`.
+    pub any_keys: bool,
+    /// When `true`, f16 and f32 NaNs keep their sign and payload, widened
+    /// into the top fraction bits of the `f64`, instead of decoding as the
+    /// platform's NaN. See [`CborEncoderOptions::nan_payloads`](super::CborEncoderOptions::nan_payloads).
+    /// Needs the `f16` feature.
+    pub nan_payloads: bool,
+}
+
+/// Base CBOR decoder. Stateless apart from options — instantiate once and reuse.
 #[derive(Default)]
-pub struct CborDecoderBase;
+pub struct CborDecoderBase {
+    pub options: CborDecoderOptions,
+}
 
 impl CborDecoderBase {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_options(options: CborDecoderOptions) -> Self {
+        Self { options }
     }
 
     /// Decode CBOR bytes into a [`PackValue`].
     pub fn decode(&self, input: &[u8]) -> Result<PackValue, CborError> {
-        let mut cur = Cur {
-            data: input,
-            pos: 0,
-        };
+        let mut cur = Cur::new(input, 0);
         self.read_any(&mut cur)
     }
 
     /// Decode CBOR bytes, returning value and number of bytes consumed.
     pub fn decode_with_consumed(&self, input: &[u8]) -> Result<(PackValue, usize), CborError> {
-        let mut cur = Cur {
-            data: input,
-            pos: 0,
-        };
+        let mut cur = Cur::new(input, 0);
         let v = self.read_any(&mut cur)?;
         Ok((v, cur.pos))
     }
 
     pub fn read_any(&self, c: &mut Cur) -> Result<PackValue, CborError> {
         if c.pos >= c.data.len() {
-            return Err(CborError::InvalidPayload);
+            return Err(CborError::UnexpectedEof(c.pos));
         }
         let octet = c.u8()?;
         self.read_any_raw(c, octet)
@@ -184,10 +222,11 @@ impl CborDecoderBase {
             MAJOR_BIN => self.read_bin(c, minor).map(PackValue::Bytes),
             MAJOR_STR => self.read_str(c, minor).map(PackValue::Str),
             MAJOR_ARR => self.read_arr(c, minor).map(PackValue::Array),
+            MAJOR_MAP if self.options.any_keys => self.read_map(c, minor),
             MAJOR_MAP => self.read_obj(c, minor).map(PackValue::Object),
             MAJOR_TAG => self.read_tag(c, minor),
             MAJOR_TKN => self.read_tkn(c, minor),
-            _ => Err(CborError::UnexpectedMajor),
+            _ => Err(CborError::UnexpectedMajor(c.pos - 1)),
         }
     }
 
@@ -201,7 +240,7 @@ impl CborDecoderBase {
             26 => Ok(c.u32()? as i64),
             27 => Ok(c.u64()? as i64),
             31 => Ok(-1), // indefinite length
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -216,7 +255,7 @@ impl CborDecoderBase {
             25 => Ok(c.u16()? as u64),
             26 => Ok(c.u32()? as u64),
             27 => c.u64(),
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -262,7 +301,7 @@ impl CborDecoderBase {
                 c.pos += 1;
                 Ok(result)
             }
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -271,10 +310,10 @@ impl CborDecoderBase {
         let major = octet >> 5;
         let minor = octet & MINOR_MASK;
         if major != MAJOR_BIN {
-            return Err(CborError::UnexpectedBinChunkMajor);
+            return Err(CborError::UnexpectedBinChunkMajor(c.pos - 1));
         }
         if minor > 27 {
-            return Err(CborError::UnexpectedBinChunkMinor);
+            return Err(CborError::UnexpectedBinChunkMinor(c.pos - 1));
         }
         self.read_bin(c, minor)
     }
@@ -309,7 +348,7 @@ impl CborDecoderBase {
                 c.pos += 1;
                 Ok(result)
             }
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -320,7 +359,7 @@ impl CborDecoderBase {
             25 => Ok(c.u16()? as usize),
             26 => Ok(c.u32()? as usize),
             27 => Ok(c.u64()? as usize),
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -329,10 +368,10 @@ impl CborDecoderBase {
         let major = octet >> 5;
         let minor = octet & MINOR_MASK;
         if major != MAJOR_STR {
-            return Err(CborError::UnexpectedStrChunkMajor);
+            return Err(CborError::UnexpectedStrChunkMajor(c.pos - 1));
         }
         if minor > 27 {
-            return Err(CborError::UnexpectedStrChunkMinor);
+            return Err(CborError::UnexpectedStrChunkMinor(c.pos - 1));
         }
         self.read_str(c, minor)
     }
@@ -349,7 +388,9 @@ impl CborDecoderBase {
     }
 
     pub fn read_arr_raw(&self, c: &mut Cur, length: usize) -> Result<Vec<PackValue>, CborError> {
-        let mut arr = Vec::with_capacity(length);
+        // Every item takes at least a byte.
+        let room = c.data.len().saturating_sub(c.pos);
+        let mut arr = Vec::with_capacity(length.min(room));
         for _ in 0..length {
             arr.push(self.read_any(c)?);
         }
@@ -381,11 +422,13 @@ impl CborDecoderBase {
         c: &mut Cur,
         length: usize,
     ) -> Result<Vec<(String, PackValue)>, CborError> {
-        let mut obj = Vec::with_capacity(length);
+        let room = c.data.len().saturating_sub(c.pos) / 2;
+        let mut obj = Vec::with_capacity(length.min(room));
         for _ in 0..length {
+            let key_pos = c.pos;
             let key = self.read_key(c)?;
             if key == "__proto__" {
-                return Err(CborError::UnexpectedObjKey);
+                return Err(CborError::UnexpectedObjKey(key_pos));
             }
             let value = self.read_any(c)?;
             obj.push((key, value));
@@ -396,12 +439,13 @@ impl CborDecoderBase {
     pub fn read_obj_indef(&self, c: &mut Cur) -> Result<Vec<(String, PackValue)>, CborError> {
         let mut obj = Vec::new();
         while c.peek()? != CBOR_END {
+            let key_pos = c.pos;
             let key = self.read_key(c)?;
             if key == "__proto__" {
-                return Err(CborError::UnexpectedObjKey);
+                return Err(CborError::UnexpectedObjKey(key_pos));
             }
             if c.peek()? == CBOR_END {
-                return Err(CborError::UnexpectedObjBreak);
+                return Err(CborError::UnexpectedObjBreak(c.pos));
             }
             let value = self.read_any(c)?;
             obj.push((key, value));
@@ -410,18 +454,77 @@ impl CborDecoderBase {
         Ok(obj)
     }
 
+    /// Reads a map keeping its key types: a [`PackValue::Map`] if any key is
+    /// not a text string, otherwise a [`PackValue::Object`].
+    pub fn read_map(&self, c: &mut Cur, minor: u8) -> Result<PackValue, CborError> {
+        let length = self.read_minor_len(c, minor)?;
+        // Every pair takes at least two bytes, which bounds what a forged
+        // length can make us reserve.
+        let room = c.data.len().saturating_sub(c.pos) / 2;
+        let mut map = Vec::with_capacity((length.max(0) as usize).min(room));
+        loop {
+            if length >= 0 {
+                if map.len() == length as usize {
+                    break;
+                }
+            } else if c.peek()? == CBOR_END {
+                c.pos += 1;
+                break;
+            }
+            let key = if c.peek()? >> 5 == MAJOR_STR {
+                let key_pos = c.pos;
+                let key = self.read_key(c)?;
+                if key == "__proto__" {
+                    return Err(CborError::UnexpectedObjKey(key_pos));
+                }
+                PackValue::Str(key)
+            } else {
+                self.read_any(c)?
+            };
+            if length < 0 && c.peek()? == CBOR_END {
+                return Err(CborError::UnexpectedObjBreak(c.pos));
+            }
+            let value = self.read_any(c)?;
+            map.push((key, value));
+        }
+        if !map.iter().all(|(key, _)| matches!(key, PackValue::Str(_))) {
+            return Ok(PackValue::Map(map));
+        }
+        let obj = map
+            .into_iter()
+            .filter_map(|(key, value)| match key {
+                PackValue::Str(key) => Some((key, value)),
+                _ => None,
+            })
+            .collect();
+        Ok(PackValue::Object(obj))
+    }
+
     /// Read object key (always returns a string).
     pub fn read_key(&self, c: &mut Cur) -> Result<String, CborError> {
         let octet = c.u8()?;
         let major = octet >> 5;
         let minor = octet & MINOR_MASK;
         if major != MAJOR_STR {
+            if self.options.strict_keys {
+                return Err(CborError::UnexpectedObjKey(c.pos - 1));
+            }
             // Non-string key: convert to string representation
             let v = self.read_any_raw(c, octet)?;
-            return Ok(pack_value_to_key_string(v));
+            return Ok(v.to_key_string());
         }
         let len = self.read_str_len(c, minor)?;
-        Ok(c.utf8(len)?.to_owned())
+        let pos = c.pos;
+        let raw = c.buf(len)?;
+        let decode = |raw: &[u8]| {
+            crate::utf8::from_utf8(raw)
+                .map(str::to_owned)
+                .map_err(|_| CborError::InvalidPayload(pos))
+        };
+        match c.keys.as_deref_mut() {
+            Some(cache) => cache.get_or_decode(raw, decode).map(String::from),
+            None => decode(raw),
+        }
     }
 
     // ---- Tag ----
@@ -433,6 +536,13 @@ impl CborDecoderBase {
 
     pub fn read_tag_raw(&self, c: &mut Cur, tag: u64) -> Result<PackValue, CborError> {
         let val = self.read_any(c)?;
+        if self.options.bignum {
+            if let PackValue::Bytes(bytes) = &val {
+                if let Some(num) = JsonPackBigInt::from_cbor_bignum(tag, bytes) {
+                    return Ok(PackValue::BigNum(num));
+                }
+            }
+        }
         Ok(PackValue::Extension(Box::new(JsonPackExtension::new(
             tag, val,
         ))))
@@ -453,12 +563,28 @@ impl CborDecoderBase {
             25 => {
                 // f16
                 let raw = c.u16()?;
+                #[cfg(feature = "f16")]
+                if self.options.nan_payloads {
+                    return Ok(PackValue::Float(decode_f16_keep_nan(raw)));
+                }
                 Ok(PackValue::Float(decode_f16(raw)))
             }
-            26 => Ok(PackValue::Float(c.f32()? as f64)),
+            26 => {
+                let float = c.f32()?;
+                #[cfg(feature = "f16")]
+                if float.is_nan() && self.options.nan_payloads {
+                    let bits = float.to_bits() as u64;
+                    let sign = (bits & 0x8000_0000) << 32;
+                    let payload = (bits & 0x007f_ffff) << 29;
+                    return Ok(PackValue::Float(f64::from_bits(
+                        sign | 0x7ff0_0000_0000_0000 | payload,
+                    )));
+                }
+                Ok(PackValue::Float(float as f64))
+            }
             27 => Ok(PackValue::Float(c.f64()?)),
             v if v <= 19 => Ok(PackValue::Blob(JsonPackValue::new(vec![v]))),
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -480,7 +606,7 @@ impl CborDecoderBase {
             MAJOR_MAP => self.skip_obj(c, minor),
             MAJOR_TAG => self.skip_tag(c, minor),
             MAJOR_TKN => self.skip_tkn(c, minor),
-            _ => Err(CborError::UnexpectedMajor),
+            _ => Err(CborError::UnexpectedMajor(c.pos - 1)),
         }
     }
 
@@ -493,7 +619,7 @@ impl CborDecoderBase {
             25 => c.skip(2),
             26 => c.skip(4),
             27 => c.skip(8),
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
@@ -516,7 +642,7 @@ impl CborDecoderBase {
         let major = octet >> 5;
         let minor = octet & MINOR_MASK;
         if major != MAJOR_BIN {
-            return Err(CborError::UnexpectedBinChunkMajor);
+            return Err(CborError::UnexpectedBinChunkMajor(c.pos - 1));
         }
         self.skip_bin(c, minor)
     }
@@ -540,7 +666,7 @@ impl CborDecoderBase {
         let major = octet >> 5;
         let minor = octet & MINOR_MASK;
         if major != MAJOR_STR {
-            return Err(CborError::UnexpectedStrChunkMajor);
+            return Err(CborError::UnexpectedStrChunkMajor(c.pos - 1));
         }
         self.skip_str(c, minor)
     }
@@ -572,7 +698,7 @@ impl CborDecoderBase {
             while c.peek()? != CBOR_END {
                 self.skip_any(c)?;
                 if c.peek()? == CBOR_END {
-                    return Err(CborError::UnexpectedObjBreak);
+                    return Err(CborError::UnexpectedObjBreak(c.pos));
                 }
                 self.skip_any(c)?;
             }
@@ -593,32 +719,247 @@ impl CborDecoderBase {
             26 => c.skip(4),
             27 => c.skip(8),
             v if v <= 23 => Ok(()),
-            _ => Err(CborError::UnexpectedMinor),
+            _ => Err(CborError::UnexpectedMinor(c.pos - 1)),
         }
     }
 
     /// Validate CBOR at offset, checking exact size match.
     pub fn validate(&self, data: &[u8], offset: usize, size: usize) -> Result<(), CborError> {
-        let mut c = Cur { data, pos: offset };
+        let mut c = Cur::new(data, offset);
         let start = offset;
         self.skip_any(&mut c)?;
         let end = c.pos;
         if end - start != size {
-            Err(CborError::InvalidSize)
+            Err(CborError::InvalidSize(start))
         } else {
             Ok(())
         }
     }
 }
 
-fn pack_value_to_key_string(v: PackValue) -> String {
-    match v {
-        PackValue::Str(s) => s,
-        PackValue::Integer(i) => i.to_string(),
-        PackValue::UInteger(u) => u.to_string(),
-        PackValue::Float(f) => f.to_string(),
-        PackValue::Bool(b) => b.to_string(),
-        PackValue::Null => "null".to_string(),
-        _ => String::new(),
+// ---- Tokens ----
+
+impl CborDecoderBase {
+    /// Reads one value as token events. Definite-length strings are handed
+    /// out borrowed; indefinite-length containers are counted up front so
+    /// every container start carries its length.
+    pub(crate) fn read_tokens<S: TokenSink + ?Sized>(
+        &self,
+        c: &mut Cur,
+        sink: &mut S,
+    ) -> Result<(), CborError> {
+        self.read_tokens_counted(c, sink, &mut VecDeque::new())
+    }
+
+    /// [`read_tokens`](Self::read_tokens) with the item counts of the
+    /// indefinite-length containers ahead, as [`count_indef`](Self::count_indef)
+    /// returns them.
+    fn read_tokens_counted<S: TokenSink + ?Sized>(
+        &self,
+        c: &mut Cur,
+        sink: &mut S,
+        counts: &mut IndefCounts,
+    ) -> Result<(), CborError> {
+        let octet = c.u8()?;
+        let major = octet >> 5;
+        let minor = octet & MINOR_MASK;
+        match major {
+            MAJOR_BIN => self.read_bin_with(c, minor, |data| sink.on_bytes(data))?,
+            MAJOR_STR if minor == 31 => sink.on_str(&self.read_str(c, minor)?),
+            MAJOR_STR => {
+                let len = self.read_str_len(c, minor)?;
+                sink.on_str(c.utf8(len)?);
+            }
+            MAJOR_ARR => {
+                let len = self.read_minor_len(c, minor)?;
+                if len >= 0 {
+                    sink.on_array_start(Some(len as usize));
+                    for _ in 0..len {
+                        self.read_tokens_counted(c, sink, counts)?;
+                    }
+                } else {
+                    sink.on_array_start(Some(self.indef_count(c, false, counts)?));
+                    while c.peek()? != CBOR_END {
+                        self.read_tokens_counted(c, sink, counts)?;
+                    }
+                    c.pos += 1;
+                }
+                sink.on_array_end();
+            }
+            MAJOR_MAP => {
+                let len = self.read_minor_len(c, minor)?;
+                if len >= 0 {
+                    sink.on_map_start(Some(len as usize));
+                    for _ in 0..len {
+                        self.read_key_token(c, sink)?;
+                        self.read_tokens_counted(c, sink, counts)?;
+                    }
+                } else {
+                    sink.on_map_start(Some(self.indef_count(c, true, counts)?));
+                    while c.peek()? != CBOR_END {
+                        self.read_key_token(c, sink)?;
+                        if c.peek()? == CBOR_END {
+                            return Err(CborError::UnexpectedObjBreak(c.pos));
+                        }
+                        self.read_tokens_counted(c, sink, counts)?;
+                    }
+                    c.pos += 1;
+                }
+                sink.on_map_end();
+            }
+            MAJOR_TAG => {
+                let tag = self.read_uint(c, minor)?;
+                let next = c.peek()?;
+                if self.options.bignum && next >> 5 == MAJOR_BIN {
+                    c.pos += 1;
+                    self.read_bin_with(c, next & MINOR_MASK, |data| {
+                        match JsonPackBigInt::from_cbor_bignum(tag, data) {
+                            Some(num) => sink.on_big_num(&num),
+                            None => {
+                                sink.on_tag(tag);
+                                sink.on_bytes(data);
+                            }
+                        }
+                    })?;
+                } else {
+                    sink.on_tag(tag);
+                    self.read_tokens_counted(c, sink, counts)?;
+                }
+            }
+            _ => walk(&self.read_any_raw(c, octet)?, sink),
+        }
+        Ok(())
+    }
+
+    /// Reads a byte string, joining indefinite-length chunks, and hands it
+    /// to `f`.
+    fn read_bin_with(
+        &self,
+        c: &mut Cur,
+        minor: u8,
+        f: impl FnOnce(&[u8]),
+    ) -> Result<(), CborError> {
+        if minor == 31 {
+            f(&self.read_bin(c, minor)?);
+        } else {
+            let len = self.read_str_len(c, minor)?;
+            f(c.buf(len)?);
+        }
+        Ok(())
+    }
+
+    /// Map keys as [`CborDecoderBase::read_key`] reads them, borrowing text
+    /// keys from the input.
+    fn read_key_token<S: TokenSink + ?Sized>(
+        &self,
+        c: &mut Cur,
+        sink: &mut S,
+    ) -> Result<(), CborError> {
+        let key_pos = c.pos;
+        let octet = c.peek()?;
+        let key: &str;
+        let owned: String;
+        if octet >> 5 == MAJOR_STR && octet & MINOR_MASK != 31 {
+            c.pos += 1;
+            let len = self.read_str_len(c, octet & MINOR_MASK)?;
+            key = c.utf8(len)?;
+        } else {
+            owned = self.read_key(c)?;
+            key = &owned;
+        }
+        if key == "__proto__" {
+            return Err(CborError::UnexpectedObjKey(key_pos));
+        }
+        sink.on_key(key);
+        Ok(())
+    }
+
+    /// The item (or pair) count of the indefinite-length container whose
+    /// items start at `c.pos`. Taken from `counts` if an enclosing container
+    /// already counted it, else counted now along with every indefinite-length
+    /// container inside it, so each byte is scanned once.
+    fn indef_count(
+        &self,
+        c: &Cur,
+        pairs: bool,
+        counts: &mut IndefCounts,
+    ) -> Result<usize, CborError> {
+        // Containers inside non-text map keys are read without tokens.
+        while counts.front().is_some_and(|&(pos, _)| pos < c.pos) {
+            counts.pop_front();
+        }
+        if counts.front().is_none_or(|&(pos, _)| pos != c.pos) {
+            *counts = self.count_indef(c, pairs)?;
+        }
+        Ok(counts.pop_front().map_or(0, |(_, count)| count))
+    }
+
+    /// Counts the items (or pairs) of the indefinite-length container whose
+    /// items start at `c.pos`, and of each indefinite-length container
+    /// inside it, by skipping ahead. Returns `(items position, count)` in
+    /// input order.
+    fn count_indef(&self, c: &Cur, pairs: bool) -> Result<IndefCounts, CborError> {
+        let mut probe = Cur::new(c.data, c.pos);
+        let mut counts = VecDeque::new();
+        self.count_indef_items(&mut probe, pairs, &mut counts)?;
+        Ok(counts)
+    }
+
+    fn count_indef_items(
+        &self,
+        c: &mut Cur,
+        pairs: bool,
+        counts: &mut IndefCounts,
+    ) -> Result<(), CborError> {
+        let slot = counts.len();
+        counts.push_back((c.pos, 0));
+        let mut count = 0;
+        while c.peek()? != CBOR_END {
+            self.count_indef_any(c, counts)?;
+            if pairs {
+                if c.peek()? == CBOR_END {
+                    return Err(CborError::UnexpectedObjBreak(c.pos));
+                }
+                self.count_indef_any(c, counts)?;
+            }
+            count += 1;
+        }
+        c.pos += 1;
+        counts[slot].1 = count;
+        Ok(())
+    }
+
+    /// Skips one value like [`skip_any`](Self::skip_any), counting the
+    /// indefinite-length containers in it.
+    fn count_indef_any(&self, c: &mut Cur, counts: &mut IndefCounts) -> Result<(), CborError> {
+        let octet = c.u8()?;
+        let major = octet >> 5;
+        let minor = octet & MINOR_MASK;
+        match major {
+            MAJOR_ARR | MAJOR_MAP if minor == 31 => {
+                self.count_indef_items(c, major == MAJOR_MAP, counts)
+            }
+            MAJOR_ARR | MAJOR_MAP => {
+                let len = self.read_minor_len(c, minor)?;
+                let items = if major == MAJOR_MAP {
+                    len.saturating_mul(2)
+                } else {
+                    len
+                };
+                for _ in 0..items {
+                    self.count_indef_any(c, counts)?;
+                }
+                Ok(())
+            }
+            MAJOR_TAG => {
+                self.read_uint(c, minor)?;
+                self.count_indef_any(c, counts)
+            }
+            _ => self.skip_any_raw(c, octet),
+        }
     }
 }
+
+/// `(items position, count)` of indefinite-length containers, in input
+/// order.
+type IndefCounts = VecDeque<(usize, usize)>;
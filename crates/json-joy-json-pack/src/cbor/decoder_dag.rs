@@ -7,6 +7,7 @@
 use super::decoder_base::{CborDecoderBase, Cur};
 use super::error::CborError;
 use crate::{JsonPackExtension, PackValue};
+use alloc::boxed::Box;
 
 /// DAG-JSON CBOR decoder.
 ///
@@ -21,10 +22,7 @@ impl CborDecoderDag {
 
     pub fn decode(&self, input: &[u8]) -> Result<PackValue, CborError> {
         let base = CborDecoderBase::new();
-        let mut c = Cur {
-            data: input,
-            pos: 0,
-        };
+        let mut c = Cur::new(input, 0);
         self.read_any(&base, &mut c)
     }
 
@@ -2,16 +2,39 @@
 //!
 //! Direct port of `cbor/CborEncoder.ts` from upstream.
 
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "f16")]
+use json_joy_buffers::{encode_f16_keep_nan, is_float16_keep_nan};
 use json_joy_buffers::{is_float32, Writer};
 
 use super::constants::*;
+use crate::token::{walk, TokenSink};
+
+/// Options controlling `CborEncoder` output.
+#[derive(Debug, Clone, Default)]
+pub struct CborEncoderOptions {
+    /// When `true`, floats that survive a half-precision round trip bit for
+    /// bit, NaN payloads included, are written as f16 (`0xf9`) ahead of f32
+    /// and f64. Disabled by default to match upstream, which never emits
+    /// f16. Needs the `f16` feature, see the [crate docs](crate#f16).
+    pub float16: bool,
+    /// When `true`, a NaN that `float16` does not cover keeps its sign and
+    /// payload in the smallest of f32 and f64 that holds them, so NaNs read
+    /// with [`CborDecoderOptions::nan_payloads`](super::CborDecoderOptions::nan_payloads)
+    /// re-encode to the same bytes. Needs the `f16` feature.
+    pub nan_payloads: bool,
+}
 
 /// Full CBOR encoder.
 ///
 /// Handles all value types including binary, extensions, Maps, bigint, undefined.
-/// Uses f32 when the value fits losslessly (unlike `CborEncoderFast`).
+/// Uses f32 when the value fits losslessly (unlike `CborEncoderFast`), and
+/// optionally f16 (see [`CborEncoderOptions::float16`]).
 pub struct CborEncoder {
     pub writer: Writer,
+    pub options: CborEncoderOptions,
+    /// Whether each container open via [`TokenSink`] is indefinite-length.
+    token_frames: Vec<bool>,
 }
 
 impl Default for CborEncoder {
@@ -24,11 +47,25 @@ impl CborEncoder {
     pub fn new() -> Self {
         Self {
             writer: Writer::new(),
+            options: CborEncoderOptions::default(),
+            token_frames: Vec::new(),
         }
     }
 
     pub fn with_writer(writer: Writer) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            options: CborEncoderOptions::default(),
+            token_frames: Vec::new(),
+        }
+    }
+
+    pub fn with_options(options: CborEncoderOptions) -> Self {
+        Self {
+            writer: Writer::new(),
+            options,
+            token_frames: Vec::new(),
+        }
     }
 
     pub fn encode(&mut self, value: &crate::PackValue) -> Vec<u8> {
@@ -37,6 +74,7 @@ impl CborEncoder {
         self.writer.flush()
     }
 
+    #[cfg(feature = "std")]
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.writer.reset();
         self.write_json(value);
@@ -53,10 +91,12 @@ impl CborEncoder {
             UInteger(u) => self.write_u_integer(*u),
             Float(f) => self.write_float(*f),
             BigInt(i) => self.write_big_int(*i),
+            BigNum(n) => self.write_big_num(n),
             Bytes(b) => self.write_bin(b),
             Str(s) => self.write_str(s),
             Array(arr) => self.write_arr_values(arr),
             Object(obj) => self.write_obj_pairs(obj),
+            Map(map) => self.write_map_pairs(map),
             Extension(ext) => self.write_tag(ext.tag, &ext.val),
             Blob(blob) => self.writer.buf(&blob.val),
         }
@@ -158,8 +198,35 @@ impl CborEncoder {
         }
     }
 
-    /// Uses f32 if the value fits losslessly, otherwise f64.
+    /// Writes an arbitrary-precision integer: as a plain integer when it fits
+    /// in the 64-bit major type argument, otherwise as a bignum (tag 2 or 3).
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        match num.to_i128() {
+            Some(int) if (-(1i128 << 64)..=u64::MAX as i128).contains(&int) => {
+                self.write_big_int(int)
+            }
+            _ => {
+                let (tag, payload) = num.to_cbor_bignum();
+                self.write_tag_hdr(tag);
+                self.write_bin(&payload);
+            }
+        }
+    }
+
+    /// Uses f32 if the value fits losslessly, otherwise f64. With the
+    /// `float16` option, f16 is preferred when it fits losslessly.
     pub fn write_float(&mut self, float: f64) {
+        #[cfg(feature = "f16")]
+        {
+            if self.options.float16 && is_float16_keep_nan(float) {
+                self.writer.u8u16(0xf9, encode_f16_keep_nan(float));
+                return;
+            }
+            if float.is_nan() && self.options.nan_payloads {
+                self.write_nan(float);
+                return;
+            }
+        }
         if is_float32(float) {
             self.writer.u8f32(0xfa, float as f32);
         } else {
@@ -167,6 +234,19 @@ impl CborEncoder {
         }
     }
 
+    #[cfg(feature = "f16")]
+    fn write_nan(&mut self, nan: f64) {
+        let bits = nan.to_bits();
+        if bits & 0x1fff_ffff == 0 {
+            // The payload fits the 23 fraction bits of an f32.
+            let sign = ((bits >> 32) & 0x8000_0000) as u32;
+            let payload = ((bits >> 29) & 0x007f_ffff) as u32;
+            self.writer.u8u32(0xfa, sign | 0x7f80_0000 | payload);
+        } else {
+            self.writer.u8f64(0xfb, nan);
+        }
+    }
+
     pub fn write_bin(&mut self, buf: &[u8]) {
         let length = buf.len();
         self.write_bin_hdr(length);
@@ -293,6 +373,15 @@ impl CborEncoder {
         }
     }
 
+    /// Writes a map whose keys keep their types.
+    pub fn write_map_pairs(&mut self, pairs: &[(crate::PackValue, crate::PackValue)]) {
+        self.write_obj_hdr(pairs.len());
+        for (key, value) in pairs {
+            self.write_any(key);
+            self.write_any(value);
+        }
+    }
+
     pub fn write_obj_hdr(&mut self, length: usize) {
         let w = &mut self.writer;
         if length <= 23 {
@@ -336,14 +425,102 @@ impl CborEncoder {
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn write_json(&mut self, value: &serde_json::Value) {
         self.write_any(&crate::PackValue::from(value.clone()));
     }
 }
 
+/// Token events map onto the `write_*` methods; containers without a known
+/// length are written as indefinite-length and closed with a break byte.
+impl TokenSink for CborEncoder {
+    fn on_null(&mut self) {
+        self.write_null();
+    }
+
+    fn on_undefined(&mut self) {
+        self.write_undef();
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        self.write_boolean(value);
+    }
+
+    fn on_int(&mut self, value: i64) {
+        self.write_integer(value);
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        self.write_u_integer(value);
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        self.write_big_int(value);
+    }
+
+    fn on_big_num(&mut self, value: &crate::JsonPackBigInt) {
+        self.write_big_num(value);
+    }
+
+    fn on_float(&mut self, value: f64) {
+        self.write_float(value);
+    }
+
+    fn on_str(&mut self, value: &str) {
+        self.write_str(value);
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        self.write_bin(value);
+    }
+
+    fn on_array_start(&mut self, len: Option<usize>) {
+        match len {
+            Some(len) => self.write_arr_hdr(len),
+            None => self.writer.u8(0x9f),
+        }
+        self.token_frames.push(len.is_none());
+    }
+
+    fn on_array_end(&mut self) {
+        if self.token_frames.pop() == Some(true) {
+            self.writer.u8(CBOR_END);
+        }
+    }
+
+    fn on_map_start(&mut self, len: Option<usize>) {
+        match len {
+            Some(len) => self.write_obj_hdr(len),
+            None => self.writer.u8(0xbf),
+        }
+        self.token_frames.push(len.is_none());
+    }
+
+    fn on_key(&mut self, key: &str) {
+        self.write_str(key);
+    }
+
+    fn on_value_key(&mut self, key: &crate::PackValue) {
+        walk(key, self);
+    }
+
+    fn on_map_end(&mut self) {
+        self.on_array_end();
+    }
+
+    fn on_tag(&mut self, tag: u64) {
+        self.write_tag_hdr(tag);
+    }
+
+    fn on_raw(&mut self, bytes: &[u8]) {
+        self.writer.buf(bytes);
+    }
+}
+
 // ---- Legacy stub used by existing tests ----
 /// Encode a `ciborium`-style value. Kept for backward compatibility.
 /// Now delegates through PackValue conversion.
+#[cfg(feature = "std")]
 pub fn encode_cbor_value(value: &serde_json::Value) -> Result<Vec<u8>, super::error::CborError> {
     let mut enc = CborEncoder::new();
     Ok(enc.encode_json(value))
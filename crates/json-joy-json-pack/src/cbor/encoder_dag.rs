@@ -4,10 +4,14 @@
 //! Extends `CborEncoderStable`:
 //! - NaN and Infinity → null
 //! - Only writes tag header for tag 42 (CID); other tags are passed through
+//! - Big numbers outside the 64-bit range → null
 
+use alloc::{string::String, vec::Vec};
 use json_joy_buffers::Writer;
 
 use super::encoder_stable::CborEncoderStable;
+use crate::checked::{self, EncodeLossError};
+use crate::pack_value::text_keyed;
 
 /// DAG-JSON CBOR encoder.
 ///
@@ -41,12 +45,49 @@ impl CborEncoderDag {
         self.stable.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on a value DAG-CBOR cannot
+    /// hold instead of degrading it: `undefined`, a non-finite float, an
+    /// integer beyond 64 bits or a tag other than 42.
+    pub fn try_encode(&mut self, value: &crate::PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::dag_cbor_loss, checked::Keys::Text)?;
+        Ok(self.encode(value))
+    }
+
+    #[cfg(feature = "std")]
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.stable.writer.reset();
-        self.write_any(&crate::PackValue::from(value.clone()));
+        self.write_json(value);
         self.stable.writer.flush()
     }
 
+    /// Writes a `serde_json::Value` without converting it to a
+    /// [`PackValue`](crate::PackValue) first; the bytes are the same.
+    #[cfg(feature = "std")]
+    pub fn write_json(&mut self, value: &serde_json::Value) {
+        use serde_json::Value;
+        match value {
+            Value::Number(n) if !n.is_i64() && !n.is_u64() => {
+                self.write_float(n.as_f64().unwrap_or(0.0))
+            }
+            Value::Array(arr) => {
+                self.stable.write_arr_hdr(arr.len());
+                for item in arr {
+                    self.write_json(item);
+                }
+            }
+            Value::Object(obj) => {
+                let mut entries: Vec<_> = obj.iter().collect();
+                entries.sort_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.0.cmp(b.0)));
+                self.stable.write_obj_hdr(entries.len());
+                for (key, val) in entries {
+                    self.stable.write_str(key);
+                    self.write_json(val);
+                }
+            }
+            scalar => self.stable.write_json(scalar),
+        }
+    }
+
     pub fn write_any(&mut self, value: &crate::PackValue) {
         use crate::PackValue::*;
         match value {
@@ -56,6 +97,7 @@ impl CborEncoderDag {
             UInteger(u) => self.stable.write_u_integer(*u),
             Float(f) => self.write_float(*f),
             BigInt(i) => self.stable.write_big_int(*i),
+            BigNum(n) => self.write_big_num(n),
             Bytes(b) => self.stable.write_bin(b),
             Str(s) => self.stable.write_str(s),
             Array(arr) => {
@@ -73,6 +115,8 @@ impl CborEncoderDag {
                     self.write_any(val);
                 }
             }
+            // DAG-CBOR map keys are text only.
+            Map(map) => self.write_any(&Object(text_keyed(map))),
             Extension(ext) => self.write_tag(ext.tag, &ext.val),
             Blob(blob) => self.stable.writer.buf(&blob.val),
         }
@@ -87,6 +131,17 @@ impl CborEncoderDag {
         }
     }
 
+    /// DAG big number: integers beyond the 64-bit range would need a bignum
+    /// tag, which DAG-CBOR forbids, so they are written as null.
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        match num.to_i128() {
+            Some(int) if (-(1i128 << 64)..=u64::MAX as i128).contains(&int) => {
+                self.stable.write_big_int(int)
+            }
+            _ => self.stable.write_null(),
+        }
+    }
+
     /// DAG tag: only tag 42 gets a tag header; all others unwrap.
     pub fn write_tag(&mut self, tag: u64, value: &crate::PackValue) {
         if tag == 42 {
@@ -95,3 +150,10 @@ impl CborEncoderDag {
         self.write_any(value);
     }
 }
+
+/// Encodes a `serde_json::Value` as DAG-CBOR: keys in length-first order and
+/// every float as 64 bits. Same bytes as [`CborEncoderDag::encode_json`].
+#[cfg(feature = "std")]
+pub fn encode_json_to_cbor_bytes_dag(value: &serde_json::Value) -> Vec<u8> {
+    CborEncoderDag::new().encode_json(value)
+}
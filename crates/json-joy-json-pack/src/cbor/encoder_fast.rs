@@ -2,9 +2,13 @@
 //!
 //! Direct port of `cbor/CborEncoderFast.ts` from upstream.
 
+use alloc::{string::String, vec::Vec};
 use json_joy_buffers::Writer;
+#[cfg(feature = "f16")]
+use json_joy_buffers::{encode_f16_keep_nan, is_float16_keep_nan};
 
 use super::constants::*;
+use crate::checked::{self, EncodeLossError};
 
 /// Fast CBOR encoder supporting only JSON-compatible values.
 ///
@@ -12,6 +16,8 @@ use super::constants::*;
 /// use [`super::encoder::CborEncoder`].
 pub struct CborEncoderFast {
     pub writer: Writer,
+    #[cfg_attr(not(feature = "f16"), allow(dead_code))]
+    float16: bool,
 }
 
 impl Default for CborEncoderFast {
@@ -22,13 +28,23 @@ impl Default for CborEncoderFast {
 
 impl CborEncoderFast {
     pub fn new() -> Self {
+        Self::with_writer(Writer::new())
+    }
+
+    pub fn with_writer(writer: Writer) -> Self {
         Self {
-            writer: Writer::new(),
+            writer,
+            float16: false,
         }
     }
 
-    pub fn with_writer(writer: Writer) -> Self {
-        Self { writer }
+    /// When `float16` is `true`, floats that survive a half-precision round
+    /// trip bit for bit, NaN payloads included, are written as f16 (`0xf9`)
+    /// instead of f64.
+    /// Needs the `f16` feature, see the [crate docs](crate#f16).
+    pub fn with_float16(mut self, float16: bool) -> Self {
+        self.float16 = float16;
+        self
     }
 
     /// Encode a value and return the CBOR bytes.
@@ -38,6 +54,13 @@ impl CborEncoderFast {
         self.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on `undefined`, which the
+    /// fast encoder writes as `null`.
+    pub fn try_encode(&mut self, value: &crate::PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::cbor_fast_loss, checked::Keys::Any)?;
+        Ok(self.encode(value))
+    }
+
     pub fn write_any(&mut self, value: &crate::PackValue) {
         use crate::PackValue::*;
         match value {
@@ -48,10 +71,12 @@ impl CborEncoderFast {
             UInteger(u) => self.write_u_integer(*u),
             Float(f) => self.write_float(*f),
             BigInt(i) => self.write_big_int(*i),
+            BigNum(n) => self.write_big_num(n),
             Bytes(b) => self.write_bin(b),
             Str(s) => self.write_str(s),
             Array(arr) => self.write_arr_values(arr),
             Object(obj) => self.write_obj_pairs(obj),
+            Map(map) => self.write_map_pairs(map),
             Extension(ext) => self.write_tag(ext.tag, &ext.val),
             Blob(blob) => self.writer.buf(&blob.val),
         }
@@ -81,7 +106,8 @@ impl CborEncoderFast {
         //   Number.isInteger(num) && Math.abs(num) <= Number.MAX_SAFE_INTEGER
         // MAX_SAFE_INTEGER = 2^53 - 1 = 9007199254740991
         const MAX_SAFE: f64 = 9_007_199_254_740_991.0; // 2^53 - 1
-        if num.fract() == 0.0 && (-MAX_SAFE..=MAX_SAFE).contains(&num) {
+                                                       // The round-trip cast stands in for `fract()`, which needs `std`.
+        if (-MAX_SAFE..=MAX_SAFE).contains(&num) && (num as i64) as f64 == num {
             if num >= 0.0 {
                 self.write_u_integer(num as u64);
             } else {
@@ -100,6 +126,21 @@ impl CborEncoderFast {
         }
     }
 
+    /// Writes an arbitrary-precision integer: as a plain integer when it fits
+    /// in the 64-bit major type argument, otherwise as a bignum (tag 2 or 3).
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        match num.to_i128() {
+            Some(int) if (-(1i128 << 64)..=u64::MAX as i128).contains(&int) => {
+                self.write_big_int(int)
+            }
+            _ => {
+                let (tag, payload) = num.to_cbor_bignum();
+                self.write_tag_hdr(tag);
+                self.write_bin(&payload);
+            }
+        }
+    }
+
     pub fn write_big_uint(&mut self, uint: u128) {
         if uint <= u64::MAX as u128 {
             self.write_u_integer(uint as u64);
@@ -188,6 +229,11 @@ impl CborEncoderFast {
     }
 
     pub fn write_float(&mut self, float: f64) {
+        #[cfg(feature = "f16")]
+        if self.float16 && is_float16_keep_nan(float) {
+            self.writer.u8u16(0xf9, encode_f16_keep_nan(float));
+            return;
+        }
         self.writer.u8f64(0xfb, float);
     }
 
@@ -323,6 +369,7 @@ impl CborEncoderFast {
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn write_obj(&mut self, obj: &serde_json::Map<String, serde_json::Value>) {
         self.write_obj_hdr(obj.len());
         for (key, value) in obj {
@@ -339,6 +386,15 @@ impl CborEncoderFast {
         }
     }
 
+    /// Writes a map whose keys keep their types.
+    pub fn write_map_pairs(&mut self, pairs: &[(crate::PackValue, crate::PackValue)]) {
+        self.write_obj_hdr(pairs.len());
+        for (key, value) in pairs {
+            self.write_any(key);
+            self.write_any(value);
+        }
+    }
+
     pub fn write_obj_hdr(&mut self, length: usize) {
         let w = &mut self.writer;
         if length <= 23 {
@@ -413,12 +469,14 @@ impl CborEncoderFast {
 
 impl CborEncoderFast {
     /// Encode a `serde_json::Value` to CBOR bytes.
+    #[cfg(feature = "std")]
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.writer.reset();
         self.write_json(value);
         self.writer.flush()
     }
 
+    #[cfg(feature = "std")]
     pub fn write_json(&mut self, value: &serde_json::Value) {
         match value {
             serde_json::Value::Null => self.write_null(),
@@ -508,6 +566,7 @@ pub fn write_cbor_text_like_json_pack(out: &mut Vec<u8>, value: &str) {
 }
 
 /// Write a `serde_json::Value` as CBOR to a `Vec<u8>`.
+#[cfg(feature = "std")]
 pub fn write_json_like_json_pack(
     out: &mut Vec<u8>,
     value: &serde_json::Value,
@@ -555,6 +614,7 @@ pub fn write_json_like_json_pack(
 }
 
 /// Encode a `serde_json::Value` to CBOR bytes.
+#[cfg(feature = "std")]
 pub fn encode_json_to_cbor_bytes(
     value: &serde_json::Value,
 ) -> Result<Vec<u8>, super::error::CborError> {
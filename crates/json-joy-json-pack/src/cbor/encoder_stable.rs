@@ -3,17 +3,25 @@
 //! Direct port of `cbor/CborEncoderStable.ts` from upstream.
 //! Extends `CborEncoder` by sorting object keys before encoding.
 
+use alloc::vec::Vec;
+#[cfg(feature = "f16")]
+use json_joy_buffers::{encode_f16_keep_nan, is_float16_keep_nan};
 use json_joy_buffers::{is_float32, Writer};
 
 use super::constants::*;
+use crate::KeyOrder;
 
 /// Stable CBOR encoder.
 ///
-/// Same as [`super::encoder::CborEncoder`] but sorts object keys
-/// lexicographically (consistent, deterministic output).
+/// Same as [`super::encoder::CborEncoder`] but sorts object keys by
+/// `key_order` (consistent, deterministic output).
 /// Also uses the optimized `write_str` with pre-computed header.
 pub struct CborEncoderStable {
     pub writer: Writer,
+    /// Object key order; defaults to [`KeyOrder::ByteLengthLex`].
+    pub key_order: KeyOrder,
+    #[cfg_attr(not(feature = "f16"), allow(dead_code))]
+    float16: bool,
 }
 
 impl Default for CborEncoderStable {
@@ -24,23 +32,75 @@ impl Default for CborEncoderStable {
 
 impl CborEncoderStable {
     pub fn new() -> Self {
+        Self::with_key_order(KeyOrder::ByteLengthLex)
+    }
+
+    pub fn with_key_order(key_order: KeyOrder) -> Self {
         Self {
             writer: Writer::new(),
+            key_order,
+            float16: false,
         }
     }
 
+    /// When `float16` is `true`, floats that survive a half-precision round
+    /// trip bit for bit, NaN payloads included, are written as f16 (`0xf9`)
+    /// ahead of f32 and f64.
+    /// Needs the `f16` feature, see the [crate docs](crate#f16).
+    pub fn with_float16(mut self, float16: bool) -> Self {
+        self.float16 = float16;
+        self
+    }
+
     pub fn encode(&mut self, value: &crate::PackValue) -> Vec<u8> {
         self.writer.reset();
         self.write_any(value);
         self.writer.flush()
     }
 
+    #[cfg(feature = "std")]
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.writer.reset();
-        self.write_any(&crate::PackValue::from(value.clone()));
+        self.write_json(value);
         self.writer.flush()
     }
 
+    /// Writes a `serde_json::Value` without converting it to a
+    /// [`PackValue`](crate::PackValue) first; the bytes are the same.
+    #[cfg(feature = "std")]
+    pub fn write_json(&mut self, value: &serde_json::Value) {
+        use serde_json::Value;
+        match value {
+            Value::Null => self.write_null(),
+            Value::Bool(b) => self.write_boolean(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    self.write_integer(i)
+                } else if let Some(u) = n.as_u64() {
+                    self.write_u_integer(u)
+                } else {
+                    self.write_float(n.as_f64().unwrap_or(0.0))
+                }
+            }
+            Value::String(s) => self.write_str(s),
+            Value::Array(arr) => {
+                self.write_arr_hdr(arr.len());
+                for item in arr {
+                    self.write_json(item);
+                }
+            }
+            Value::Object(obj) => {
+                let mut entries: Vec<_> = obj.iter().collect();
+                entries.sort_by(|a, b| self.key_order.compare(a.0, b.0));
+                self.write_obj_hdr(entries.len());
+                for (key, val) in entries {
+                    self.write_str(key);
+                    self.write_json(val);
+                }
+            }
+        }
+    }
+
     pub fn write_any(&mut self, value: &crate::PackValue) {
         use crate::PackValue::*;
         match value {
@@ -50,19 +110,38 @@ impl CborEncoderStable {
             UInteger(u) => self.write_u_integer(*u),
             Float(f) => self.write_float(*f),
             BigInt(i) => self.write_big_int(*i),
+            BigNum(n) => self.write_big_num(n),
             Bytes(b) => self.write_bin(b),
             Str(s) => self.write_str(s),
             Array(arr) => self.write_arr_values(arr),
             Object(obj) => {
                 // Sort keys before encoding
-                let mut sorted: Vec<&(String, crate::PackValue)> = obj.iter().collect();
-                sorted.sort_by(|a, b| cmp_obj_key(&a.0, &b.0));
-                self.write_obj_hdr(sorted.len());
-                for (key, val) in sorted {
+                let indices = self.key_order.sorted_indices(obj);
+                self.write_obj_hdr(indices.len());
+                for idx in indices {
+                    let (key, val) = &obj[idx];
                     self.write_str(key);
                     self.write_any(val);
                 }
             }
+            Map(map) => {
+                // Keys are encoded on their own so they can be sorted by
+                // bytes (RFC 8949 §4.2.1), whatever `key_order` says.
+                let mut keys = CborEncoderStable::with_key_order(self.key_order);
+                let mut keyed: Vec<(Vec<u8>, &crate::PackValue)> = map
+                    .iter()
+                    .map(|(key, val)| {
+                        keys.write_any(key);
+                        (keys.writer.flush(), val)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| a.0.cmp(&b.0));
+                self.write_obj_hdr(keyed.len());
+                for (key, val) in keyed {
+                    self.writer.buf(&key);
+                    self.write_any(val);
+                }
+            }
             Extension(ext) => self.write_tag(ext.tag, &ext.val),
             Blob(blob) => self.writer.buf(&blob.val),
         }
@@ -160,7 +239,27 @@ impl CborEncoderStable {
         }
     }
 
+    /// Writes an arbitrary-precision integer: as a plain integer when it fits
+    /// in the 64-bit major type argument, otherwise as a bignum (tag 2 or 3).
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        match num.to_i128() {
+            Some(int) if (-(1i128 << 64)..=u64::MAX as i128).contains(&int) => {
+                self.write_big_int(int)
+            }
+            _ => {
+                let (tag, payload) = num.to_cbor_bignum();
+                self.write_tag_hdr(tag);
+                self.write_bin(&payload);
+            }
+        }
+    }
+
     pub fn write_float(&mut self, float: f64) {
+        #[cfg(feature = "f16")]
+        if self.float16 && is_float16_keep_nan(float) {
+            self.writer.u8u16(0xf9, encode_f16_keep_nan(float));
+            return;
+        }
         if is_float32(float) {
             self.writer.u8f32(0xfa, float as f32);
         } else {
@@ -328,10 +427,12 @@ fn str_header_length(size: usize) -> usize {
     }
 }
 
-/// Compare object keys for stable sort (mirrors `objKeyCmp` from upstream).
-/// Keys are compared by byte length first, then lexicographically.
-fn cmp_obj_key(a: &str, b: &str) -> std::cmp::Ordering {
-    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+/// Encodes a `serde_json::Value` as deterministic CBOR, keys in
+/// [`KeyOrder::ByteLengthLex`] order. Same bytes as
+/// [`CborEncoderStable::encode_json`].
+#[cfg(feature = "std")]
+pub fn encode_json_to_cbor_bytes_stable(value: &serde_json::Value) -> Vec<u8> {
+    CborEncoderStable::new().encode_json(value)
 }
 
 #[cfg(test)]
@@ -369,22 +470,34 @@ mod tests {
         assert_eq!(str_header_length(0x10000), 5);
     }
 
-    // --- cmp_obj_key ---
+    // --- KeyOrder::ByteLengthLex ---
 
     #[test]
-    fn test_cmp_obj_key_shorter_first() {
-        assert_eq!(cmp_obj_key("a", "bb"), std::cmp::Ordering::Less);
+    fn test_byte_length_lex_shorter_first() {
+        assert_eq!(
+            KeyOrder::ByteLengthLex.compare("a", "bb"),
+            std::cmp::Ordering::Less
+        );
     }
 
     #[test]
-    fn test_cmp_obj_key_same_length_lexicographic() {
-        assert_eq!(cmp_obj_key("ab", "ba"), std::cmp::Ordering::Less);
-        assert_eq!(cmp_obj_key("ba", "ab"), std::cmp::Ordering::Greater);
+    fn test_byte_length_lex_same_length_lexicographic() {
+        assert_eq!(
+            KeyOrder::ByteLengthLex.compare("ab", "ba"),
+            std::cmp::Ordering::Less
+        );
+        assert_eq!(
+            KeyOrder::ByteLengthLex.compare("ba", "ab"),
+            std::cmp::Ordering::Greater
+        );
     }
 
     #[test]
-    fn test_cmp_obj_key_equal() {
-        assert_eq!(cmp_obj_key("abc", "abc"), std::cmp::Ordering::Equal);
+    fn test_byte_length_lex_equal() {
+        assert_eq!(
+            KeyOrder::ByteLengthLex.compare("abc", "abc"),
+            std::cmp::Ordering::Equal
+        );
     }
 
     // --- write_null ---
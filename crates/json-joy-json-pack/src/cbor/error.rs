@@ -1,34 +1,74 @@
+use alloc::string::String;
+
 use thiserror::Error;
 
 /// Error type for CBOR encoding/decoding operations.
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum CborError {
-    #[error("invalid cbor payload")]
-    InvalidPayload,
+    #[error("invalid cbor payload at offset {0}")]
+    InvalidPayload(usize),
+    /// The input ended inside a value; more bytes may complete it.
+    #[error("unexpected end of cbor input at offset {0}")]
+    UnexpectedEof(usize),
     #[error("unsupported cbor feature")]
     Unsupported,
-    #[error("unexpected major type")]
-    UnexpectedMajor,
-    #[error("unexpected minor value")]
-    UnexpectedMinor,
-    #[error("unexpected binary chunk major type")]
-    UnexpectedBinChunkMajor,
-    #[error("unexpected binary chunk minor value")]
-    UnexpectedBinChunkMinor,
-    #[error("unexpected string chunk major type")]
-    UnexpectedStrChunkMajor,
-    #[error("unexpected string chunk minor value")]
-    UnexpectedStrChunkMinor,
-    #[error("unexpected object key")]
-    UnexpectedObjKey,
-    #[error("unexpected object break")]
-    UnexpectedObjBreak,
-    #[error("invalid size")]
-    InvalidSize,
+    #[error("unexpected major type at offset {0}")]
+    UnexpectedMajor(usize),
+    #[error("unexpected minor value at offset {0}")]
+    UnexpectedMinor(usize),
+    #[error("unexpected binary chunk major type at offset {0}")]
+    UnexpectedBinChunkMajor(usize),
+    #[error("unexpected binary chunk minor value at offset {0}")]
+    UnexpectedBinChunkMinor(usize),
+    #[error("unexpected string chunk major type at offset {0}")]
+    UnexpectedStrChunkMajor(usize),
+    #[error("unexpected string chunk minor value at offset {0}")]
+    UnexpectedStrChunkMinor(usize),
+    #[error("unexpected object key at offset {0}")]
+    UnexpectedObjKey(usize),
+    #[error("unexpected object break at offset {0}")]
+    UnexpectedObjBreak(usize),
+    #[error("invalid size at offset {0}")]
+    InvalidSize(usize),
     #[error("key not found")]
     KeyNotFound,
     #[error("index out of bounds")]
     IndexOutOfBounds,
     #[error("unexpected string major type")]
     UnexpectedStrMajor,
+    /// A tag was rejected by [`crate::cbor::CborTagPolicy::Error`].
+    #[error("unexpected cbor tag {0}")]
+    UnexpectedTag(u64),
+    /// NaN or an infinity was rejected by
+    /// [`crate::cbor::CborNonFinitePolicy::Error`].
+    #[error("non-finite float")]
+    NonFiniteFloat,
+    /// The JSON value at this JSON Pointer does not fit its
+    /// [`crate::cbor::CborTypeHint`].
+    #[error("value at {0} does not match its type hint")]
+    HintMismatch(String),
+    /// A lazy view's integer getter found a value outside the requested
+    /// type's range.
+    #[error("integer out of range")]
+    IntegerOutOfRange,
+}
+
+impl CborError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::InvalidPayload(x)
+            | Self::UnexpectedEof(x)
+            | Self::UnexpectedMajor(x)
+            | Self::UnexpectedMinor(x)
+            | Self::UnexpectedBinChunkMajor(x)
+            | Self::UnexpectedBinChunkMinor(x)
+            | Self::UnexpectedStrChunkMajor(x)
+            | Self::UnexpectedStrChunkMinor(x)
+            | Self::UnexpectedObjKey(x)
+            | Self::UnexpectedObjBreak(x)
+            | Self::InvalidSize(x) => Some(*x),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,453 @@
+//! Lazy views over encoded CBOR maps and arrays.
+//!
+//! Not an upstream port; the CBOR counterpart of
+//! [`msgpack::LazyMap`](crate::msgpack::LazyMap). A lookup skips over the
+//! entries before it with [`CborDecoderBase::skip_any`] and decodes only the
+//! value asked for. Definite-length strings and byte strings are borrowed
+//! from the buffer; indefinite-length containers are walked up to their
+//! break byte. Typed getters look through tags, so a tagged integer reads
+//! as its integer. The `view` methods return borrows as a [`BufView`],
+//! which also carries their offsets.
+
+use alloc::vec::Vec;
+use json_joy_buffers::{decode_f16, BufView};
+
+use super::constants::*;
+use super::decoder::CborDecoder;
+use super::decoder_base::{CborDecoderBase, Cur};
+use super::error::CborError;
+use crate::PackValue;
+
+fn skip(data: &[u8], pos: &mut usize) -> Result<(), CborError> {
+    let mut c = Cur::new(data, *pos);
+    CborDecoderBase::new().skip_any(&mut c)?;
+    *pos = c.pos;
+    Ok(())
+}
+
+/// The value encoded at `data[pos..]`.
+fn view_value(data: &[u8], pos: usize) -> Result<BufView<'_>, CborError> {
+    let mut end = pos;
+    skip(data, &mut end)?;
+    BufView::range(data, pos, end).map_err(|_| CborError::UnexpectedEof(pos))
+}
+
+/// One encoded value, not yet decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyValue<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> LazyValue<'a> {
+    /// A view of the value encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Offset of the value in the buffer the view was created from.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// The value's encoded bytes, tags included.
+    pub fn bytes(&self) -> Result<&'a [u8], CborError> {
+        self.view().map(|view| view.as_slice())
+    }
+
+    /// The value's encoded bytes, tags included, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, CborError> {
+        view_value(self.data, self.pos)
+    }
+
+    /// Decodes the whole value.
+    pub fn decode(&self) -> Result<PackValue, CborError> {
+        CborDecoder::new().decode(self.bytes()?)
+    }
+
+    /// The tags wrapping the value, outermost first.
+    pub fn tags(&self) -> Result<Vec<u64>, CborError> {
+        let base = CborDecoderBase::new();
+        let mut c = self.cur();
+        let mut tags = Vec::new();
+        loop {
+            let octet = c.peek()?;
+            if octet >> 5 != MAJOR_TAG {
+                return Ok(tags);
+            }
+            c.pos += 1;
+            tags.push(base.read_uint(&mut c, octet & MINOR_MASK)?);
+        }
+    }
+
+    fn cur(&self) -> Cur<'a> {
+        Cur::new(self.data, self.pos)
+    }
+
+    /// Reads past any tags: the cursor after the initial byte, the major
+    /// type and the minor value.
+    fn head(&self) -> Result<(Cur<'a>, u8, u8), CborError> {
+        let base = CborDecoderBase::new();
+        let mut c = self.cur();
+        loop {
+            let octet = c.u8()?;
+            let (major, minor) = (octet >> 5, octet & MINOR_MASK);
+            if major != MAJOR_TAG {
+                return Ok((c, major, minor));
+            }
+            base.read_uint(&mut c, minor)?;
+        }
+    }
+
+    /// The definite length after the initial byte; `None` if indefinite.
+    /// Fails if the length does not fit in `usize`.
+    fn len(c: &mut Cur, minor: u8) -> Result<Option<usize>, CborError> {
+        let pos = c.pos;
+        match CborDecoderBase::new().read_minor_len(c, minor)? {
+            _ if minor == 31 => Ok(None),
+            len => usize::try_from(len)
+                .map(Some)
+                .map_err(|_| CborError::InvalidSize(pos)),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.head(), Ok((_, MAJOR_TKN, 22)))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, CborError> {
+        match self.head()? {
+            (_, MAJOR_TKN, 20) => Ok(false),
+            (_, MAJOR_TKN, 21) => Ok(true),
+            (_, MAJOR_TKN, _) => Err(CborError::UnexpectedMinor(self.pos)),
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    fn int(&self) -> Result<i128, CborError> {
+        let base = CborDecoderBase::new();
+        match self.head()? {
+            (mut c, MAJOR_UIN, minor) => Ok(base.read_uint(&mut c, minor)? as i128),
+            (mut c, MAJOR_NIN, minor) => Ok(-1 - base.read_uint(&mut c, minor)? as i128),
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    /// An integer in `u64` range.
+    pub fn as_u64(&self) -> Result<u64, CborError> {
+        u64::try_from(self.int()?).map_err(|_| CborError::IntegerOutOfRange)
+    }
+
+    /// An integer in `i64` range.
+    pub fn as_i64(&self) -> Result<i64, CborError> {
+        i64::try_from(self.int()?).map_err(|_| CborError::IntegerOutOfRange)
+    }
+
+    /// A float of any width, or an integer converted to one.
+    pub fn as_f64(&self) -> Result<f64, CborError> {
+        match self.head()? {
+            (mut c, MAJOR_TKN, 25) => Ok(decode_f16(c.u16()?)),
+            (mut c, MAJOR_TKN, 26) => Ok(c.f32()? as f64),
+            (mut c, MAJOR_TKN, 27) => c.f64(),
+            (_, MAJOR_TKN, _) => Err(CborError::UnexpectedMinor(self.pos)),
+            _ => Ok(self.int()? as f64),
+        }
+    }
+
+    /// A definite-length string, borrowed from the buffer. An
+    /// indefinite-length string is [`CborError::Unsupported`].
+    pub fn as_str(&self) -> Result<&'a str, CborError> {
+        match self.head()? {
+            (mut c, MAJOR_STR, minor) => match Self::len(&mut c, minor)? {
+                Some(len) => c.utf8(len),
+                None => Err(CborError::Unsupported),
+            },
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    /// A definite-length byte string, borrowed from the buffer. An
+    /// indefinite-length one is [`CborError::Unsupported`].
+    pub fn as_bytes(&self) -> Result<&'a [u8], CborError> {
+        self.bin_view().map(|view| view.as_slice())
+    }
+
+    /// A definite-length byte string's payload, as a view into the buffer.
+    pub fn bin_view(&self) -> Result<BufView<'a>, CborError> {
+        match self.head()? {
+            (mut c, MAJOR_BIN, minor) => match Self::len(&mut c, minor)? {
+                Some(len) => {
+                    let start = c.pos;
+                    c.buf(len)?;
+                    BufView::range(self.data, start, c.pos)
+                        .map_err(|_| CborError::UnexpectedEof(start))
+                }
+                None => Err(CborError::Unsupported),
+            },
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    pub fn as_map(&self) -> Result<LazyMap<'a>, CborError> {
+        match self.head()? {
+            (mut c, MAJOR_MAP, minor) => {
+                let len = Self::len(&mut c, minor)?;
+                // Entries are walked as `2 * len` items.
+                if len.is_some_and(|len| len.checked_mul(2).is_none()) {
+                    return Err(CborError::InvalidSize(self.pos));
+                }
+                Ok(LazyMap {
+                    len,
+                    data: self.data,
+                    start: self.pos,
+                    pos: c.pos,
+                })
+            }
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<LazyArray<'a>, CborError> {
+        match self.head()? {
+            (mut c, MAJOR_ARR, minor) => Ok(LazyArray {
+                len: Self::len(&mut c, minor)?,
+                data: self.data,
+                start: self.pos,
+                pos: c.pos,
+            }),
+            _ => Err(CborError::UnexpectedMajor(self.pos)),
+        }
+    }
+
+    /// The bytes of an untagged, definite-length text string, if it is one.
+    fn text(&self) -> Result<Option<&'a [u8]>, CborError> {
+        let mut c = self.cur();
+        let octet = c.u8()?;
+        if octet >> 5 != MAJOR_STR {
+            return Ok(None);
+        }
+        match Self::len(&mut c, octet & MINOR_MASK)? {
+            Some(len) => c.buf(len).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Typed getters over `get`: `Ok(None)` when the entry is missing, an error
+/// when it is present but of another type.
+macro_rules! typed_getters {
+    ($key:ty) => {
+        pub fn get_str(&self, key: $key) -> Result<Option<&'a str>, CborError> {
+            self.get(key)?.map(|v| v.as_str()).transpose()
+        }
+
+        pub fn get_bytes(&self, key: $key) -> Result<Option<&'a [u8]>, CborError> {
+            self.get(key)?.map(|v| v.as_bytes()).transpose()
+        }
+
+        pub fn get_u64(&self, key: $key) -> Result<Option<u64>, CborError> {
+            self.get(key)?.map(|v| v.as_u64()).transpose()
+        }
+
+        pub fn get_i64(&self, key: $key) -> Result<Option<i64>, CborError> {
+            self.get(key)?.map(|v| v.as_i64()).transpose()
+        }
+
+        pub fn get_f64(&self, key: $key) -> Result<Option<f64>, CborError> {
+            self.get(key)?.map(|v| v.as_f64()).transpose()
+        }
+
+        pub fn get_bool(&self, key: $key) -> Result<Option<bool>, CborError> {
+            self.get(key)?.map(|v| v.as_bool()).transpose()
+        }
+
+        pub fn get_map(&self, key: $key) -> Result<Option<LazyMap<'a>>, CborError> {
+            self.get(key)?.map(|v| v.as_map()).transpose()
+        }
+
+        pub fn get_array(&self, key: $key) -> Result<Option<LazyArray<'a>>, CborError> {
+            self.get(key)?.map(|v| v.as_array()).transpose()
+        }
+    };
+}
+
+/// A view of an encoded map.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyMap<'a> {
+    data: &'a [u8],
+    /// Offset of the value, tags included.
+    start: usize,
+    /// Offset of the first key.
+    pos: usize,
+    /// `None` for an indefinite-length map.
+    len: Option<usize>,
+}
+
+impl<'a> LazyMap<'a> {
+    /// A view of the map encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Result<Self, CborError> {
+        LazyValue::new(data).as_map()
+    }
+
+    /// Number of entries, or `None` for an indefinite-length map.
+    pub fn len(&self) -> Option<usize> {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.len {
+            Some(len) => len == 0,
+            None => self.data.get(self.pos) == Some(&CBOR_END),
+        }
+    }
+
+    /// The whole encoded map, tags included, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, CborError> {
+        view_value(self.data, self.start)
+    }
+
+    /// Entries in encoded order.
+    pub fn iter(&self) -> LazyMapIter<'a> {
+        LazyMapIter {
+            items: LazyArrayIter {
+                data: self.data,
+                pos: self.pos,
+                left: self.len.map(|len| 2 * len),
+            },
+        }
+    }
+
+    /// The value under the first text key equal to `key`. Tagged,
+    /// indefinite-length and non-text keys are skipped.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'a>>, CborError> {
+        for entry in self.iter() {
+            let (k, value) = entry?;
+            if k.text()? == Some(key.as_bytes()) {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    typed_getters!(&str);
+}
+
+/// A view of an encoded array.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyArray<'a> {
+    data: &'a [u8],
+    /// Offset of the value, tags included.
+    start: usize,
+    /// Offset of the first item.
+    pos: usize,
+    /// `None` for an indefinite-length array.
+    len: Option<usize>,
+}
+
+impl<'a> LazyArray<'a> {
+    /// A view of the array encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Result<Self, CborError> {
+        LazyValue::new(data).as_array()
+    }
+
+    /// Number of items, or `None` for an indefinite-length array.
+    pub fn len(&self) -> Option<usize> {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.len {
+            Some(len) => len == 0,
+            None => self.data.get(self.pos) == Some(&CBOR_END),
+        }
+    }
+
+    /// The whole encoded array, tags included, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, CborError> {
+        view_value(self.data, self.start)
+    }
+
+    pub fn iter(&self) -> LazyArrayIter<'a> {
+        LazyArrayIter {
+            data: self.data,
+            pos: self.pos,
+            left: self.len,
+        }
+    }
+
+    /// The item at `index`, skipping the ones before it.
+    pub fn get(&self, index: usize) -> Result<Option<LazyValue<'a>>, CborError> {
+        let mut items = self.iter();
+        for _ in 0..index {
+            match items.next() {
+                Some(item) => item?,
+                None => return Ok(None),
+            };
+        }
+        items.next().transpose()
+    }
+
+    typed_getters!(usize);
+}
+
+/// Iterator over a [`LazyMap`]'s `(key, value)` entries. Stops after the
+/// first error.
+pub struct LazyMapIter<'a> {
+    items: LazyArrayIter<'a>,
+}
+
+impl<'a> Iterator for LazyMapIter<'a> {
+    type Item = Result<(LazyValue<'a>, LazyValue<'a>), CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = match self.items.next()? {
+            Ok(key) => key,
+            Err(err) => return Some(Err(err)),
+        };
+        match self.items.next() {
+            Some(Ok(value)) => Some(Ok((key, value))),
+            Some(Err(err)) => Some(Err(err)),
+            None => Some(Err(CborError::UnexpectedObjBreak(self.items.pos))),
+        }
+    }
+}
+
+/// Iterator over a [`LazyArray`]'s items. Stops after the first error.
+pub struct LazyArrayIter<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Items left; `None` until the break byte of an indefinite-length
+    /// container.
+    left: Option<usize>,
+}
+
+impl<'a> Iterator for LazyArrayIter<'a> {
+    type Item = Result<LazyValue<'a>, CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.left {
+            Some(0) => return None,
+            Some(ref mut left) => *left -= 1,
+            None => match self.data.get(self.pos) {
+                Some(&CBOR_END) => {
+                    self.left = Some(0);
+                    return None;
+                }
+                Some(_) => {}
+                None => {
+                    self.left = Some(0);
+                    return Some(Err(CborError::UnexpectedEof(self.pos)));
+                }
+            },
+        }
+        let value = LazyValue {
+            data: self.data,
+            pos: self.pos,
+        };
+        let result = skip(self.data, &mut self.pos).map(|()| value);
+        if result.is_err() {
+            self.left = Some(0);
+        }
+        Some(result)
+    }
+}
@@ -1,8 +1,12 @@
 //! CBOR module layout aligned to upstream `json-pack/src/cbor/*` family.
 
+#[cfg(feature = "std")]
 mod codec;
 mod constants;
+#[cfg(feature = "std")]
 mod convert;
+pub mod cose;
+pub mod cwt;
 mod decoder;
 mod decoder_base;
 mod decoder_dag;
@@ -11,23 +15,41 @@ mod encoder_dag;
 mod encoder_fast;
 mod encoder_stable;
 mod error;
+mod lazy;
 mod shared;
 mod types;
 
+#[cfg(feature = "std")]
 pub use codec::CborJsonValueCodec;
-pub use convert::{cbor_to_json, cbor_to_json_owned, json_to_cbor};
+#[cfg(feature = "std")]
+pub use convert::{
+    cbor_to_json, cbor_to_json_owned, cbor_to_json_with, decode_json_from_cbor_bytes_with,
+    json_to_cbor, json_to_cbor_with, CborBytesPolicy, CborJsonHints, CborKeyPolicy,
+    CborNonFinitePolicy, CborTagPolicy, CborToJsonOptions, CborTypeHint,
+};
+#[cfg(feature = "std")]
+pub use decoder::decode_json_from_cbor_bytes;
 pub use decoder::{
-    decode_cbor_value, decode_cbor_value_with_consumed, decode_json_from_cbor_bytes,
-    validate_cbor_exact_size, CborDecoder,
+    decode_cbor_value, decode_cbor_value_with_consumed, validate_cbor_exact_size, CborDecoder,
+    CborDecoderCached,
 };
+pub use decoder_base::CborDecoderOptions;
 pub use decoder_dag::CborDecoderDag;
-pub use encoder::{encode_cbor_value, CborEncoder};
+#[cfg(feature = "std")]
+pub use encoder::encode_cbor_value;
+pub use encoder::{CborEncoder, CborEncoderOptions};
+#[cfg(feature = "std")]
+pub use encoder_dag::encode_json_to_cbor_bytes_dag;
 pub use encoder_dag::CborEncoderDag;
+#[cfg(feature = "std")]
+pub use encoder_fast::{encode_json_to_cbor_bytes, write_json_like_json_pack};
 pub use encoder_fast::{
-    encode_json_to_cbor_bytes, write_cbor_signed, write_cbor_text_like_json_pack,
-    write_cbor_uint_major, write_json_like_json_pack, CborEncoderFast,
+    write_cbor_signed, write_cbor_text_like_json_pack, write_cbor_uint_major, CborEncoderFast,
 };
+#[cfg(feature = "std")]
+pub use encoder_stable::encode_json_to_cbor_bytes_stable;
 pub use encoder_stable::CborEncoderStable;
 pub use error::CborError;
+pub use lazy::{LazyArray, LazyArrayIter, LazyMap, LazyMapIter, LazyValue};
 pub use shared::{decode, encode};
 pub use types::CborUint8Array;
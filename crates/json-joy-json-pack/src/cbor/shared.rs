@@ -3,6 +3,7 @@
 //! Upstream reference: `json-pack/src/cbor/shared.ts`
 
 use crate::PackValue;
+use alloc::vec::Vec;
 
 use super::{CborDecoder, CborEncoder, CborError};
 
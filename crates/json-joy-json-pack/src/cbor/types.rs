@@ -2,5 +2,7 @@
 //!
 //! Upstream reference: `json-pack/src/cbor/types.ts`
 
+use alloc::vec::Vec;
+
 /// Branded CBOR byte payload alias.
 pub type CborUint8Array = Vec<u8>;
@@ -0,0 +1,303 @@
+//! Checked encoding: fail instead of silently degrading a value.
+//!
+//! Not an upstream port. Several encoders coerce values their format cannot
+//! represent: the JSON encoders write extensions, blobs and `NaN` as `null`,
+//! DAG-CBOR drops tags other than 42, MessagePack rounds big integers to
+//! `float64`, Avro wraps them to 64 bits, UBJSON clamps them, text-keyed
+//! formats write byte, array and map keys as `""`.
+//! Each of them has a `try_encode` that first looks for such a value and
+//! reports the first one found, as an [`EncodeLossError`] with its JSON
+//! Pointer path, instead of writing anything.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt::{self, Write as _};
+
+use thiserror::Error;
+
+use crate::msgpack::ext::ext_type;
+use crate::msgpack::{MsgPackEncoderOptions, MsgPackExtRegistry, MsgPackExtValuePolicy};
+use crate::{JsonPackExtension, PackValue};
+
+/// What an encoder would have done to a value it cannot represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossKind {
+    /// `NaN` or an infinity, written as `null` or clamped.
+    NonFiniteFloat,
+    /// An integer rounded to a float or replaced by `null`.
+    BigInt,
+    /// An extension written as `null`.
+    Extension,
+    /// A tag that is dropped, keeping only the tagged value.
+    Tag(u64),
+    /// A pre-encoded blob written as `null`.
+    Blob,
+    /// `undefined` written as `null`.
+    Undefined,
+    /// A map key the format's text-only keys cannot hold, written as an
+    /// empty string.
+    NonTextKey,
+}
+
+impl fmt::Display for LossKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteFloat => f.write_str("non-finite float"),
+            Self::BigInt => f.write_str("out-of-range integer"),
+            Self::Extension => f.write_str("extension"),
+            Self::Tag(tag) => write!(f, "tag {tag}"),
+            Self::Blob => f.write_str("blob"),
+            Self::Undefined => f.write_str("undefined"),
+            Self::NonTextKey => f.write_str("non-text map key"),
+        }
+    }
+}
+
+/// A value that `try_encode` refused to degrade.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("cannot encode {kind} at `{path}` without loss")]
+pub struct EncodeLossError {
+    /// JSON Pointer to the value; empty for the root.
+    pub path: String,
+    pub kind: LossKind,
+}
+
+/// The [`PackValue::Map`] keys an encoder writes as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Keys {
+    /// Any value, as in CBOR and MessagePack.
+    Any,
+    /// Scalars, written as text by [`PackValue::to_key_string`].
+    Text,
+}
+
+impl Keys {
+    fn allows(self, key: &PackValue) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Text => key.is_text_key(),
+        }
+    }
+}
+
+/// Walks `value` depth-first and fails on the first node `lossy` flags, or
+/// on the first map key outside `keys`, whose path is that of its entry.
+/// Arrays, objects, maps and extension values that pass are descended into;
+/// an extension's value shares its path.
+pub(crate) fn check(
+    value: &PackValue,
+    lossy: &impl Fn(&PackValue) -> Option<LossKind>,
+    keys: Keys,
+) -> Result<(), EncodeLossError> {
+    let mut path = Vec::new();
+    walk(value, lossy, keys, &mut path).map_err(|kind| EncodeLossError {
+        path: pointer(&path),
+        kind,
+    })
+}
+
+fn walk<'a>(
+    value: &'a PackValue,
+    lossy: &impl Fn(&PackValue) -> Option<LossKind>,
+    keys: Keys,
+    path: &mut Vec<Segment<'a>>,
+) -> Result<(), LossKind> {
+    if let Some(kind) = lossy(value) {
+        return Err(kind);
+    }
+    match value {
+        PackValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(Segment::Index(i));
+                walk(item, lossy, keys, path)?;
+                path.pop();
+            }
+        }
+        PackValue::Object(fields) => {
+            for (key, item) in fields {
+                path.push(Segment::Key(Cow::Borrowed(key)));
+                walk(item, lossy, keys, path)?;
+                path.pop();
+            }
+        }
+        PackValue::Map(entries) => {
+            for (key, item) in entries {
+                path.push(Segment::Key(match key {
+                    PackValue::Str(key) => Cow::Borrowed(key),
+                    key => Cow::Owned(key.to_key_string()),
+                }));
+                if !keys.allows(key) {
+                    return Err(LossKind::NonTextKey);
+                }
+                walk(item, lossy, keys, path)?;
+                path.pop();
+            }
+        }
+        PackValue::Extension(ext) => walk(&ext.val, lossy, keys, path)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+enum Segment<'a> {
+    Key(Cow<'a, str>),
+    Index(usize),
+}
+
+fn pointer(path: &[Segment<'_>]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        out.push('/');
+        match segment {
+            Segment::Key(key) => {
+                for c in key.chars() {
+                    match c {
+                        '~' => out.push_str("~0"),
+                        '/' => out.push_str("~1"),
+                        _ => out.push(c),
+                    }
+                }
+            }
+            Segment::Index(i) => {
+                let _ = write!(out, "{i}");
+            }
+        }
+    }
+    out
+}
+
+/// Losses of [`JsonEncoder`](crate::json::JsonEncoder) and the JSON
+/// encoders built on it.
+#[cfg(feature = "std")]
+pub(crate) fn json_loss(value: &PackValue) -> Option<LossKind> {
+    match value {
+        PackValue::Float(f) if !f.is_finite() => Some(LossKind::NonFiniteFloat),
+        PackValue::Extension(_) => Some(LossKind::Extension),
+        PackValue::Blob(_) => Some(LossKind::Blob),
+        _ => None,
+    }
+}
+
+/// Losses of [`CborEncoderDag`](crate::cbor::CborEncoderDag).
+pub(crate) fn dag_cbor_loss(value: &PackValue) -> Option<LossKind> {
+    let in_range = |int: i128| (-(1i128 << 64)..=u64::MAX as i128).contains(&int);
+    match value {
+        PackValue::Undefined => Some(LossKind::Undefined),
+        PackValue::Float(f) if !f.is_finite() => Some(LossKind::NonFiniteFloat),
+        PackValue::BigInt(int) if !in_range(*int) => Some(LossKind::BigInt),
+        PackValue::BigNum(n) if !n.to_i128().is_some_and(in_range) => Some(LossKind::BigInt),
+        PackValue::Extension(ext) if ext.tag != 42 => Some(LossKind::Tag(ext.tag)),
+        _ => None,
+    }
+}
+
+/// Losses of [`AvroEncoder`](crate::avro::AvroEncoder), whose integers
+/// are 64-bit longs and which writes `undefined`, extensions and blobs as
+/// null.
+#[cfg(feature = "std")]
+pub(crate) fn avro_loss(value: &PackValue) -> Option<LossKind> {
+    let long = |int: i128| i64::try_from(int).is_ok();
+    match value {
+        PackValue::Undefined => Some(LossKind::Undefined),
+        PackValue::UInteger(int) if i64::try_from(*int).is_err() => Some(LossKind::BigInt),
+        PackValue::BigInt(int) if !long(*int) => Some(LossKind::BigInt),
+        PackValue::BigNum(n) if !n.to_i128().is_some_and(long) => Some(LossKind::BigInt),
+        PackValue::Extension(_) => Some(LossKind::Extension),
+        PackValue::Blob(_) => Some(LossKind::Blob),
+        _ => None,
+    }
+}
+
+/// Losses of [`MsgPackEncoderFast`](crate::msgpack::MsgPackEncoderFast),
+/// which writes integers beyond 32 bits as `float64`.
+pub(crate) fn msgpack_loss(value: &PackValue, registry: &MsgPackExtRegistry) -> Option<LossKind> {
+    // `as i128` saturates, so 2^127 would compare equal to `i128::MAX`.
+    let exact = |int: i128| {
+        let float = int as f64;
+        float < i128::MAX as f64 && float as i128 == int
+    };
+    match value {
+        PackValue::Integer(int) if !exact(*int as i128) => Some(LossKind::BigInt),
+        PackValue::UInteger(int) if !exact(*int as i128) => Some(LossKind::BigInt),
+        PackValue::BigInt(int) if !exact(*int) => Some(LossKind::BigInt),
+        PackValue::Extension(ext) => msgpack_ext_loss(ext, registry, false),
+        _ => None,
+    }
+}
+
+/// Whether a MessagePack encoder loses the type of `ext`: it needs a
+/// one-byte type, and a value that is bytes, accepted by a codec in
+/// `registry` or `nested` into the payload.
+fn msgpack_ext_loss(
+    ext: &JsonPackExtension,
+    registry: &MsgPackExtRegistry,
+    nested: bool,
+) -> Option<LossKind> {
+    match ext_type(ext.tag) {
+        Some(_) if nested || matches!(*ext.val, PackValue::Bytes(_)) => None,
+        Some(tag) if registry.encode(tag, &ext.val).is_some() => None,
+        _ => Some(LossKind::Extension),
+    }
+}
+
+/// Losses of [`MsgPackEncoder`](crate::msgpack::MsgPackEncoder) under
+/// `options`: integers as for the fast encoder unless `smallest_int` keeps
+/// them, and extensions the options write without their type.
+pub(crate) fn msgpack_options_loss(
+    value: &PackValue,
+    options: &MsgPackEncoderOptions,
+) -> Option<LossKind> {
+    match value {
+        PackValue::Integer(_) | PackValue::UInteger(_) if options.smallest_int => None,
+        PackValue::BigInt(int)
+            if options.smallest_int
+                && (i64::try_from(*int).is_ok() || u64::try_from(*int).is_ok()) =>
+        {
+            None
+        }
+        PackValue::Extension(ext) => msgpack_ext_loss(
+            ext,
+            &options.ext_registry,
+            options.ext_value == MsgPackExtValuePolicy::Nest,
+        ),
+        _ => msgpack_loss(value, &options.ext_registry),
+    }
+}
+
+/// Losses of [`CborEncoderFast`](crate::cbor::CborEncoderFast), which
+/// writes `undefined` as `null`.
+pub(crate) fn cbor_fast_loss(value: &PackValue) -> Option<LossKind> {
+    match value {
+        PackValue::Undefined => Some(LossKind::Undefined),
+        _ => None,
+    }
+}
+
+/// Losses of [`UbjsonEncoder`](crate::ubjson::UbjsonEncoder): integers
+/// outside `i64` are clamped or wrapped unless `high_precision` writes
+/// them as `H`, and extensions and blobs become `null`.
+#[cfg(feature = "std")]
+pub(crate) fn ubjson_loss(value: &PackValue, high_precision: bool) -> Option<LossKind> {
+    match value {
+        PackValue::UInteger(int) if !high_precision && i64::try_from(*int).is_err() => {
+            Some(LossKind::BigInt)
+        }
+        PackValue::BigInt(int) if !high_precision && i64::try_from(*int).is_err() => {
+            Some(LossKind::BigInt)
+        }
+        PackValue::Extension(_) => Some(LossKind::Extension),
+        PackValue::Blob(_) => Some(LossKind::Blob),
+        _ => None,
+    }
+}
+
+/// Losses of [`IonEncoder`](crate::ion::IonEncoder), which writes
+/// `undefined`, extensions and blobs as `null`.
+#[cfg(feature = "std")]
+pub(crate) fn ion_loss(value: &PackValue) -> Option<LossKind> {
+    match value {
+        PackValue::Undefined => Some(LossKind::Undefined),
+        PackValue::Extension(_) => Some(LossKind::Extension),
+        PackValue::Blob(_) => Some(LossKind::Blob),
+        _ => None,
+    }
+}
@@ -0,0 +1,48 @@
+//! Self-describing payload envelope.
+//!
+//! Not an upstream port. Two header bytes in front of an encoded payload:
+//! the [`EncodingFormat`] id, then a version number the producer chooses
+//! for its payload schema.
+
+use super::types::CodecError;
+use crate::EncodingFormat;
+
+/// An encoded payload tagged with its format and version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    pub format: EncodingFormat,
+    pub version: u8,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Envelope<'a> {
+    /// Length of the header before the payload.
+    pub const HEADER_LEN: usize = 2;
+
+    /// Prefixes `payload` with its format id and `version`.
+    pub fn wrap(format: EncodingFormat, version: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::HEADER_LEN + payload.len());
+        out.push(format as u8);
+        out.push(version);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    /// Splits `bytes` written by [`wrap`](Self::wrap) into header and
+    /// payload, without decoding the payload.
+    pub fn unwrap(bytes: &'a [u8]) -> Result<Self, CodecError> {
+        let [format, version, payload @ ..] = bytes else {
+            return Err(CodecError::TruncatedEnvelope);
+        };
+        Ok(Self {
+            format: EncodingFormat::try_from(*format).map_err(|_| CodecError::UnknownFormat)?,
+            version: *version,
+            payload,
+        })
+    }
+
+    /// The envelope's bytes, as [`wrap`](Self::wrap) writes them.
+    pub fn to_vec(&self) -> Vec<u8> {
+        Self::wrap(self.format, self.version, self.payload)
+    }
+}
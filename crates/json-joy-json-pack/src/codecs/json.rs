@@ -3,6 +3,7 @@
 //! Upstream reference: `json-pack/src/codecs/json.ts`
 
 use crate::{json::JsonDecoder, json::JsonEncoder, EncodingFormat, PackValue};
+use json_joy_buffers::Writer;
 
 use super::types::{CodecError, JsonValueCodec};
 
@@ -25,6 +26,20 @@ impl JsonJsonValueCodec {
         }
     }
 
+    /// Creates a codec whose encoder writes into `writer`, e.g. one taken
+    /// from a [`WriterPool`](json_joy_buffers::WriterPool).
+    pub fn with_writer(writer: Writer) -> Self {
+        Self {
+            encoder: JsonEncoder::with_writer(writer),
+            decoder: JsonDecoder::new(),
+        }
+    }
+
+    /// Returns the encoder's writer, e.g. to release it back to a pool.
+    pub fn into_writer(self) -> Writer {
+        self.encoder.writer
+    }
+
     pub fn id(&self) -> &'static str {
         "json"
     }
@@ -1,12 +1,14 @@
 //! Combined JSON value codecs mirrored from upstream `json-pack/src/codecs/`.
 
 mod cbor;
+mod envelope;
 mod json;
 mod msgpack;
 mod registry;
 mod types;
 
 pub use cbor::CborJsonValueCodec;
+pub use envelope::Envelope;
 pub use json::JsonJsonValueCodec;
 pub use msgpack::MsgPackJsonValueCodec;
 pub use registry::Codecs;
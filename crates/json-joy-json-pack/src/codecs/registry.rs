@@ -2,7 +2,16 @@
 //!
 //! Upstream reference: `json-pack/src/codecs/Codecs.ts`
 
-use super::{CborJsonValueCodec, JsonJsonValueCodec, MsgPackJsonValueCodec};
+use json_joy_buffers::WriterPool;
+
+use super::{CborJsonValueCodec, CodecError, Envelope, JsonJsonValueCodec, MsgPackJsonValueCodec};
+use crate::bson::BsonDecoder;
+use crate::constants::CBOR_MAGIC;
+use crate::ejson::{from_bson, EjsonEncoder};
+use crate::ion::IonDecoder;
+use crate::resp::RespDecoder;
+use crate::ubjson::UbjsonDecoder;
+use crate::{EncodingFormat, PackValue};
 
 pub struct Codecs {
     pub cbor: CborJsonValueCodec,
@@ -24,4 +33,66 @@ impl Codecs {
             json: JsonJsonValueCodec::new(),
         }
     }
+
+    /// Creates codecs whose encoders write into writers taken from `pool`.
+    pub fn from_pool(pool: &mut WriterPool) -> Self {
+        Self {
+            cbor: CborJsonValueCodec::with_writer(pool.acquire()),
+            msgpack: MsgPackJsonValueCodec::with_writer(pool.acquire()),
+            json: JsonJsonValueCodec::with_writer(pool.acquire()),
+        }
+    }
+
+    /// Decodes `bytes` in the format [`EncodingFormat::sniff`] detects.
+    ///
+    /// Not in upstream. See [`decode_as`](Self::decode_as).
+    pub fn decode_any(&mut self, bytes: &[u8]) -> Result<PackValue, CodecError> {
+        let format = EncodingFormat::sniff(bytes).ok_or(CodecError::UnknownFormat)?;
+        self.decode_as(format, bytes)
+    }
+
+    /// Decodes `bytes` as `format`.
+    ///
+    /// Not in upstream. A leading CBOR self-describe tag is dropped. BSON
+    /// documents come back as their relaxed Extended JSON, so BSON-only
+    /// types such as ObjectIds turn into `$`-keyed objects.
+    pub fn decode_as(
+        &mut self,
+        format: EncodingFormat,
+        bytes: &[u8],
+    ) -> Result<PackValue, CodecError> {
+        match format {
+            EncodingFormat::Cbor => self
+                .cbor
+                .decode(bytes.strip_prefix(&CBOR_MAGIC).unwrap_or(bytes)),
+            EncodingFormat::MsgPack => self.msgpack.decode(bytes),
+            EncodingFormat::Json => self.json.decode(bytes),
+            EncodingFormat::Bson => {
+                let fields = BsonDecoder::new().decode(bytes)?;
+                let json = EjsonEncoder::new().encode(&from_bson(&fields))?;
+                self.json.decode(&json)
+            }
+            EncodingFormat::Ion => Ok(IonDecoder::new().decode(bytes)?),
+            EncodingFormat::Ubjson => Ok(UbjsonDecoder::new().decode(bytes)?),
+            EncodingFormat::Resp => Ok(RespDecoder::new().decode(bytes)?),
+        }
+    }
+
+    /// Decodes the payload of an [`Envelope`], returning its version too.
+    ///
+    /// Not in upstream.
+    pub fn decode_envelope(&mut self, bytes: &[u8]) -> Result<(u8, PackValue), CodecError> {
+        let envelope = Envelope::unwrap(bytes)?;
+        Ok((
+            envelope.version,
+            self.decode_as(envelope.format, envelope.payload)?,
+        ))
+    }
+
+    /// Returns the encoders' writers to `pool`.
+    pub fn release(self, pool: &mut WriterPool) {
+        pool.release(self.cbor.into_writer());
+        pool.release(self.msgpack.into_writer());
+        pool.release(self.json.into_writer());
+    }
 }
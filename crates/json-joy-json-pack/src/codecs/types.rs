@@ -2,9 +2,15 @@
 //!
 //! Upstream reference: `json-pack/src/codecs/types.ts`
 
+use crate::bson::BsonError;
+use crate::ejson::EjsonEncodeError;
+use crate::ion::IonDecodeError;
+use crate::resp::RespDecodeError;
+use crate::ubjson::UbjsonError;
 use crate::{cbor::CborError, json::JsonError, msgpack::MsgPackError, EncodingFormat, PackValue};
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum CodecError {
     #[error("CBOR codec error: {0}")]
     Cbor(#[from] CborError),
@@ -12,6 +18,27 @@ pub enum CodecError {
     Json(#[from] JsonError),
     #[error("MessagePack codec error: {0}")]
     MsgPack(#[from] MsgPackError),
+    #[error("BSON codec error: {0}")]
+    Bson(#[from] BsonError),
+    #[error("Ion codec error: {0}")]
+    Ion(#[from] IonDecodeError),
+    #[error("UBJSON codec error: {0}")]
+    Ubjson(#[from] UbjsonError),
+    #[error("RESP codec error: {0}")]
+    Resp(#[from] RespDecodeError),
+    /// BSON decoded but could not be rendered as Extended JSON on the way
+    /// to a [`PackValue`].
+    #[error("EJSON codec error: {0}")]
+    EjsonEncode(#[from] EjsonEncodeError),
+    /// [`EncodingFormat::sniff`] did not recognise the input, or an
+    /// envelope names no known format.
+    #[error("unrecognised encoding format")]
+    UnknownFormat,
+    /// An [`Envelope`](super::Envelope) shorter than its header.
+    #[error("truncated envelope")]
+    TruncatedEnvelope,
+    #[error("unsupported encoding format: {0:?}")]
+    UnsupportedFormat(EncodingFormat),
 }
 
 /// Trait for binary codecs that encode/decode [`PackValue`].
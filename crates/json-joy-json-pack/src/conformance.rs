@@ -0,0 +1,378 @@
+//! Cross-language conformance fixtures for the JSON, CBOR and MessagePack
+//! codecs.
+//!
+//! Not an upstream port. A fixture pairs a JSON value with the bytes an
+//! upstream json-pack encoder writes for it, in the layout of the
+//! `tests/compat` corpus:
+//!
+//! ```json
+//! {
+//!   "fixture_version": 1,
+//!   "name": "codec_cbor_float_v1",
+//!   "scenario": "codec_cbor",
+//!   "input": { "encoder": "CborEncoder", "json": 1.5 },
+//!   "expected": { "hex": "fa3fc00000" }
+//! }
+//! ```
+//!
+//! [`run`] encodes the value with the Rust port of the named encoder and
+//! decodes the bytes with every Rust decoder of the format, collecting each
+//! byte-for-byte or value mismatch in a [`ConformanceReport`].
+
+use std::fmt;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::cbor::{
+    CborDecoder, CborDecoderDag, CborEncoder, CborEncoderDag, CborEncoderFast, CborEncoderStable,
+};
+use crate::json::{JsonDecoder, JsonDecoderDag, JsonEncoder, JsonEncoderDag, JsonEncoderStable};
+use crate::msgpack::{
+    MsgPackDecoder, MsgPackDecoderFast, MsgPackEncoder, MsgPackEncoderFast, MsgPackEncoderStable,
+};
+use crate::PackValue;
+
+/// Fixture format, from the `codec_json`, `codec_cbor` and `codec_msgpack`
+/// scenarios.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecFormat {
+    Json,
+    Cbor,
+    MsgPack,
+}
+
+impl CodecFormat {
+    pub fn from_scenario(scenario: &str) -> Option<Self> {
+        match scenario {
+            "codec_json" => Some(Self::Json),
+            "codec_cbor" => Some(Self::Cbor),
+            "codec_msgpack" => Some(Self::MsgPack),
+            _ => None,
+        }
+    }
+
+    pub fn scenario(self) -> &'static str {
+        match self {
+            Self::Json => "codec_json",
+            Self::Cbor => "codec_cbor",
+            Self::MsgPack => "codec_msgpack",
+        }
+    }
+
+    /// Upstream encoder class names with a Rust port.
+    pub fn encoders(self) -> &'static [&'static str] {
+        match self {
+            Self::Json => &["JsonEncoder", "JsonEncoderStable", "JsonEncoderDag"],
+            Self::Cbor => &[
+                "CborEncoder",
+                "CborEncoderFast",
+                "CborEncoderStable",
+                "CborEncoderDag",
+            ],
+            Self::MsgPack => &[
+                "MsgPackEncoder",
+                "MsgPackEncoderFast",
+                "MsgPackEncoderStable",
+            ],
+        }
+    }
+
+    /// Decoders every fixture of the format is run through.
+    pub fn decoders(self) -> &'static [&'static str] {
+        match self {
+            Self::Json => &["JsonDecoder", "JsonDecoderDag"],
+            Self::Cbor => &["CborDecoder", "CborDecoderDag"],
+            Self::MsgPack => &["MsgPackDecoder", "MsgPackDecoderFast"],
+        }
+    }
+}
+
+/// Fixture loading error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FixtureError {
+    #[error("cannot read {path}: {message}")]
+    Io { path: String, message: String },
+    #[error("invalid JSON in {path}: {message}")]
+    Json { path: String, message: String },
+    #[error("invalid fixture {name}: {reason}")]
+    Invalid { name: String, reason: &'static str },
+}
+
+/// One JSON value and the bytes upstream encodes it to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecFixture {
+    pub name: String,
+    pub format: CodecFormat,
+    /// Upstream encoder class that wrote `bytes`.
+    pub encoder: String,
+    pub json: Value,
+    pub bytes: Vec<u8>,
+}
+
+impl CodecFixture {
+    /// Reads a fixture document. `None` when its scenario is not a codec
+    /// one, so mixed corpora can be loaded.
+    pub fn from_json(doc: &Value) -> Result<Option<Self>, FixtureError> {
+        let name = doc["name"].as_str().unwrap_or_default().to_string();
+        let invalid = |reason| FixtureError::Invalid {
+            name: name.clone(),
+            reason,
+        };
+        let Some(format) = doc["scenario"]
+            .as_str()
+            .and_then(CodecFormat::from_scenario)
+        else {
+            return Ok(None);
+        };
+        if name.is_empty() {
+            return Err(invalid("missing name"));
+        }
+        let encoder = doc["input"]["encoder"]
+            .as_str()
+            .ok_or_else(|| invalid("missing input.encoder"))?;
+        if !format.encoders().contains(&encoder) {
+            return Err(invalid("unknown encoder"));
+        }
+        let json = doc["input"]
+            .get("json")
+            .ok_or_else(|| invalid("missing input.json"))?;
+        let bytes = doc["expected"]["hex"]
+            .as_str()
+            .and_then(from_hex)
+            .ok_or_else(|| invalid("missing or malformed expected.hex"))?;
+        Ok(Some(Self {
+            name: name.clone(),
+            format,
+            encoder: encoder.to_string(),
+            json: json.clone(),
+            bytes,
+        }))
+    }
+
+    /// The fixture document [`CodecFixture::from_json`] reads.
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "fixture_version": 1,
+            "name": self.name,
+            "scenario": self.format.scenario(),
+            "input": { "encoder": self.encoder, "json": self.json },
+            "expected": { "hex": to_hex(&self.bytes) },
+        })
+    }
+}
+
+/// Loads the codec fixtures of every `*.json` file in `dir`, sorted by file
+/// name. Other scenarios and the corpus `manifest.json` are skipped.
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<CodecFixture>, FixtureError> {
+    let dir = dir.as_ref();
+    let io = |path: &Path, e: std::io::Error| FixtureError::Io {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    };
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| io(dir, e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| io(dir, e))?;
+    paths.retain(|p| {
+        p.extension().is_some_and(|ext| ext == "json")
+            && p.file_name().is_some_and(|n| n != "manifest.json")
+    });
+    paths.sort();
+    let mut fixtures = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).map_err(|e| io(&path, e))?;
+        let doc: Value = serde_json::from_str(&text).map_err(|e| FixtureError::Json {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        fixtures.extend(CodecFixture::from_json(&doc)?);
+    }
+    Ok(fixtures)
+}
+
+/// How a Rust codec departed from a fixture.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DivergenceKind {
+    /// The encoder wrote different bytes.
+    Encode { expected: Vec<u8>, actual: Vec<u8> },
+    /// The decoder read a different value.
+    Decode { expected: Value, actual: Value },
+    /// The decoder rejected the bytes.
+    DecodeError { message: String },
+}
+
+/// One codec disagreeing with one fixture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub fixture: String,
+    pub codec: &'static str,
+    pub kind: DivergenceKind,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ", self.fixture, self.codec)?;
+        match &self.kind {
+            DivergenceKind::Encode { expected, actual } => {
+                write!(f, "wrote {}, expected {}", to_hex(actual), to_hex(expected))
+            }
+            DivergenceKind::Decode { expected, actual } => {
+                write!(f, "read {actual}, expected {expected}")
+            }
+            DivergenceKind::DecodeError { message } => write!(f, "failed: {message}"),
+        }
+    }
+}
+
+/// Outcome of [`run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConformanceReport {
+    /// Number of fixtures run.
+    pub fixtures: usize,
+    /// Number of encoder and decoder runs.
+    pub checks: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ConformanceReport {
+    pub fn is_ok(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} fixtures, {} checks, {} divergences",
+            self.fixtures,
+            self.checks,
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n  {divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs each fixture's encoder and every decoder of its format.
+pub fn run(fixtures: &[CodecFixture]) -> ConformanceReport {
+    let mut report = ConformanceReport {
+        fixtures: fixtures.len(),
+        ..ConformanceReport::default()
+    };
+    for fixture in fixtures {
+        let value = PackValue::from(&fixture.json);
+        if let Some((codec, actual)) = encode(&fixture.encoder, &value) {
+            report.checks += 1;
+            if actual != fixture.bytes {
+                report.divergences.push(Divergence {
+                    fixture: fixture.name.clone(),
+                    codec,
+                    kind: DivergenceKind::Encode {
+                        expected: fixture.bytes.clone(),
+                        actual,
+                    },
+                });
+            }
+        }
+        for &codec in fixture.format.decoders() {
+            report.checks += 1;
+            let kind = match decode(codec, &fixture.bytes) {
+                Ok(decoded) => {
+                    let actual = Value::from(decoded);
+                    if same_json(&actual, &fixture.json) {
+                        continue;
+                    }
+                    DivergenceKind::Decode {
+                        expected: fixture.json.clone(),
+                        actual,
+                    }
+                }
+                Err(message) => DivergenceKind::DecodeError { message },
+            };
+            report.divergences.push(Divergence {
+                fixture: fixture.name.clone(),
+                codec,
+                kind,
+            });
+        }
+    }
+    report
+}
+
+fn encode(encoder: &str, value: &PackValue) -> Option<(&'static str, Vec<u8>)> {
+    Some(match encoder {
+        "JsonEncoder" => ("JsonEncoder", JsonEncoder::new().encode(value)),
+        "JsonEncoderStable" => ("JsonEncoderStable", JsonEncoderStable::new().encode(value)),
+        "JsonEncoderDag" => ("JsonEncoderDag", JsonEncoderDag::new().encode(value)),
+        "CborEncoder" => ("CborEncoder", CborEncoder::new().encode(value)),
+        "CborEncoderFast" => ("CborEncoderFast", CborEncoderFast::new().encode(value)),
+        "CborEncoderStable" => ("CborEncoderStable", CborEncoderStable::new().encode(value)),
+        "CborEncoderDag" => ("CborEncoderDag", CborEncoderDag::new().encode(value)),
+        "MsgPackEncoder" => ("MsgPackEncoder", MsgPackEncoder::new().encode(value)),
+        "MsgPackEncoderFast" => (
+            "MsgPackEncoderFast",
+            MsgPackEncoderFast::new().encode(value),
+        ),
+        "MsgPackEncoderStable" => (
+            "MsgPackEncoderStable",
+            MsgPackEncoderStable::new().encode(value),
+        ),
+        _ => return None,
+    })
+}
+
+fn decode(decoder: &str, bytes: &[u8]) -> Result<PackValue, String> {
+    match decoder {
+        "JsonDecoder" => JsonDecoder::new().decode(bytes).map_err(|e| e.to_string()),
+        "JsonDecoderDag" => JsonDecoderDag::new()
+            .decode(bytes)
+            .map_err(|e| e.to_string()),
+        "CborDecoder" => CborDecoder::new().decode(bytes).map_err(|e| e.to_string()),
+        "CborDecoderDag" => CborDecoderDag::new()
+            .decode(bytes)
+            .map_err(|e| e.to_string()),
+        "MsgPackDecoder" => MsgPackDecoder::new()
+            .decode(bytes)
+            .map_err(|e| e.to_string()),
+        "MsgPackDecoderFast" => MsgPackDecoderFast::new()
+            .decode(bytes)
+            .map_err(|e| e.to_string()),
+        _ => Err(format!("unknown decoder {decoder}")),
+    }
+}
+
+/// JSON equality with numbers compared by value, as JavaScript sees them,
+/// and object keys in any order, as stable encoders sort them.
+fn same_json(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same_json(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(k, v)| y.get(k).is_some_and(|w| same_json(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
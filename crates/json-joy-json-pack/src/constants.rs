@@ -1,11 +1,105 @@
 //! Top-level constants for json-pack.
 //!
-//! Mirrors `constants.ts` from upstream.
+//! Mirrors `constants.ts` from upstream. The formats past `Json`, the
+//! `u8` conversion and [`EncodingFormat::sniff`] are Rust-only.
 
 /// Binary encoding format identifier.
+///
+/// More formats may be added, so matches outside this crate need a
+/// wildcard arm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum EncodingFormat {
     Cbor = 0,
     MsgPack = 1,
     Json = 2,
+    Bson = 3,
+    Ion = 4,
+    Ubjson = 5,
+    Resp = 6,
+}
+
+impl TryFrom<u8> for EncodingFormat {
+    type Error = u8;
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Cbor),
+            1 => Ok(Self::MsgPack),
+            2 => Ok(Self::Json),
+            3 => Ok(Self::Bson),
+            4 => Ok(Self::Ion),
+            5 => Ok(Self::Ubjson),
+            6 => Ok(Self::Resp),
+            other => Err(other),
+        }
+    }
+}
+
+/// The CBOR self-describe tag, 55799.
+pub(crate) const CBOR_MAGIC: [u8; 3] = [0xd9, 0xd9, 0xf7];
+/// The Ion 1.0 binary version marker.
+const ION_IVM: [u8; 4] = [0xe0, 0x01, 0x00, 0xea];
+
+impl EncodingFormat {
+    /// Guesses the format of `data` from its leading bytes.
+    ///
+    /// CBOR is only recognised by its self-describe tag and Ion only by its
+    /// version marker; BSON by a length prefix matching `data.len()` and a
+    /// trailing nul; RESP by a type byte whose line ends in CRLF. UBJSON
+    /// and JSON are told apart by their value markers, and anything else
+    /// that can start a MessagePack value is taken as MessagePack, untagged
+    /// CBOR included. Returns `None` for empty or blank input and for the
+    /// unused MessagePack byte `0xc1`.
+    pub fn sniff(data: &[u8]) -> Option<EncodingFormat> {
+        let &first = data.first()?;
+        if data.starts_with(&CBOR_MAGIC) {
+            return Some(Self::Cbor);
+        }
+        if data.starts_with(&ION_IVM) {
+            return Some(Self::Ion);
+        }
+        if data.len() >= 5
+            && data.len() <= i32::MAX as usize
+            && u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize == data.len()
+            && data[data.len() - 1] == 0
+        {
+            return Some(Self::Bson);
+        }
+        if b"+-:$*_,#!=(%~>|".contains(&first) {
+            let line = data.iter().position(|&b| b == b'\n');
+            if line.is_some_and(|n| n > 1 && data[n - 1] == b'\r') {
+                return Some(Self::Resp);
+            }
+        }
+        if let Some(format) = Self::sniff_text(data) {
+            return Some(format);
+        }
+        match first {
+            0xc1 => None,
+            b' ' | b'\t' | b'\n' | b'\r' if data.iter().all(u8::is_ascii_whitespace) => None,
+            _ => Some(Self::MsgPack),
+        }
+    }
+
+    /// UBJSON or JSON, from the first byte that is not a container opener
+    /// or JSON whitespace.
+    fn sniff_text(data: &[u8]) -> Option<EncodingFormat> {
+        let &first = data
+            .iter()
+            .find(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))?;
+        let &next = data
+            .iter()
+            .find(|b| !matches!(b, b'[' | b'{' | b' ' | b'\t' | b'\n' | b'\r'))
+            .unwrap_or(&first);
+        let ubjson = |b: u8| b"ZNTFiUIlLdDCSH$#".contains(&b);
+        let json = |b: u8| b"]}\"tfn-".contains(&b) || b.is_ascii_digit();
+        match first {
+            b'[' | b'{' if ubjson(next) => Some(Self::Ubjson),
+            b'[' | b'{' if json(next) => Some(Self::Json),
+            b'[' | b'{' => None,
+            _ if ubjson(first) && data[0] == first => Some(Self::Ubjson),
+            _ if json(first) && first != b']' && first != b'}' => Some(Self::Json),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,214 @@
+//! `CsvDecoder` — reads CSV/TSV with a header row into an array of objects.
+//!
+//! Accepts `\n` and `\r\n` record terminators and RFC 4180 quoting (`""`
+//! inside a quoted field is a literal quote). A trailing terminator is
+//! optional.
+
+use super::error::CsvError;
+use crate::PackValue;
+
+#[derive(Debug, Clone)]
+pub struct CsvDecoderOptions {
+    /// Field separator; `b','` for CSV, `b'\t'` for TSV.
+    pub delimiter: u8,
+    /// Turn unquoted fields into typed values: empty → `Null`,
+    /// `true`/`false` → `Bool`, JSON-style numbers → `Integer`/`UInteger`/
+    /// `Float`. Quoted fields always stay strings.
+    pub sniff_types: bool,
+}
+
+impl Default for CsvDecoderOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            sniff_types: false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CsvDecoder {
+    pub options: CsvDecoderOptions,
+}
+
+struct Field {
+    text: String,
+    quoted: bool,
+}
+
+impl CsvDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: CsvDecoderOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn decode(&self, input: &[u8]) -> Result<PackValue, CsvError> {
+        let mut x = 0;
+        let Some(header) = self.read_record(input, &mut x)? else {
+            return Ok(PackValue::Array(Vec::new()));
+        };
+        let header: Vec<String> = header.into_iter().map(|f| f.text).collect();
+        let mut rows = Vec::new();
+        loop {
+            let start = x;
+            let Some(fields) = self.read_record(input, &mut x)? else {
+                break;
+            };
+            if fields.len() != header.len() {
+                return Err(CsvError::FieldCount {
+                    offset: start,
+                    expected: header.len(),
+                    found: fields.len(),
+                });
+            }
+            let pairs = header
+                .iter()
+                .zip(fields)
+                .map(|(key, field)| (key.clone(), self.field_value(field)))
+                .collect();
+            rows.push(PackValue::Object(pairs));
+        }
+        Ok(PackValue::Array(rows))
+    }
+
+    fn field_value(&self, field: Field) -> PackValue {
+        if self.options.sniff_types && !field.quoted {
+            sniff(&field.text)
+        } else {
+            PackValue::Str(field.text)
+        }
+    }
+
+    /// Read one record starting at `*x`; `None` at end of input.
+    fn read_record(&self, input: &[u8], x: &mut usize) -> Result<Option<Vec<Field>>, CsvError> {
+        if *x >= input.len() {
+            return Ok(None);
+        }
+        let mut fields = Vec::new();
+        loop {
+            fields.push(self.read_field(input, x)?);
+            match input.get(*x) {
+                Some(&b) if b == self.options.delimiter => *x += 1,
+                Some(b'\r') if input.get(*x + 1) == Some(&b'\n') => {
+                    *x += 2;
+                    break;
+                }
+                Some(b'\n') => {
+                    *x += 1;
+                    break;
+                }
+                None => break,
+                Some(_) => return Err(CsvError::InvalidQuote(*x)),
+            }
+        }
+        Ok(Some(fields))
+    }
+
+    fn read_field(&self, input: &[u8], x: &mut usize) -> Result<Field, CsvError> {
+        let start = *x;
+        if input.get(start) != Some(&b'"') {
+            let delimiter = self.options.delimiter;
+            let mut end = start;
+            while end < input.len() {
+                let b = input[end];
+                if b == delimiter
+                    || b == b'\n'
+                    || (b == b'\r' && input.get(end + 1) == Some(&b'\n'))
+                {
+                    break;
+                }
+                end += 1;
+            }
+            *x = end;
+            let text = crate::utf8::from_utf8(&input[start..end])
+                .map_err(|_| CsvError::InvalidUtf8(start))?;
+            return Ok(Field {
+                text: text.to_string(),
+                quoted: false,
+            });
+        }
+        let mut bytes = Vec::new();
+        let mut i = start + 1;
+        loop {
+            match input.get(i) {
+                None => return Err(CsvError::UnterminatedQuote(start)),
+                Some(b'"') if input.get(i + 1) == Some(&b'"') => {
+                    bytes.push(b'"');
+                    i += 2;
+                }
+                Some(b'"') => {
+                    i += 1;
+                    break;
+                }
+                Some(&b) => {
+                    bytes.push(b);
+                    i += 1;
+                }
+            }
+        }
+        *x = i;
+        let text = String::from_utf8(bytes).map_err(|_| CsvError::InvalidUtf8(start))?;
+        Ok(Field { text, quoted: true })
+    }
+}
+
+/// Typed value of an unquoted field.
+pub(super) fn sniff(text: &str) -> PackValue {
+    match text {
+        "" => return PackValue::Null,
+        "true" => return PackValue::Bool(true),
+        "false" => return PackValue::Bool(false),
+        _ => {}
+    }
+    if !is_json_number(text.as_bytes()) {
+        return PackValue::Str(text.to_string());
+    }
+    if let Ok(i) = text.parse::<i64>() {
+        PackValue::Integer(i)
+    } else if let Ok(u) = text.parse::<u64>() {
+        PackValue::UInteger(u)
+    } else {
+        text.parse::<f64>()
+            .map(PackValue::Float)
+            .unwrap_or_else(|_| PackValue::Str(text.to_string()))
+    }
+}
+
+/// JSON number grammar: `-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?`.
+fn is_json_number(b: &[u8]) -> bool {
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while *i < b.len() && b[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        *i > start
+    };
+    if b.first() == Some(&b'-') {
+        i += 1;
+    }
+    if b.get(i) == Some(&b'0') {
+        i += 1;
+    } else if !digits(&mut i) {
+        return false;
+    }
+    if b.get(i) == Some(&b'.') {
+        i += 1;
+        if !digits(&mut i) {
+            return false;
+        }
+    }
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(b.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if !digits(&mut i) {
+            return false;
+        }
+    }
+    i == b.len()
+}
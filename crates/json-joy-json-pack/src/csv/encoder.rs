@@ -0,0 +1,157 @@
+//! `CsvEncoder` — writes an array of flat objects as CSV/TSV.
+//!
+//! Field mapping:
+//! - `Null`/`Undefined` and non-finite floats → empty field
+//! - `Bool` → `true`/`false`
+//! - integers and floats → decimal text
+//! - `Str` → text, quoted when it contains the delimiter, a quote or a line
+//!   break, is empty, or would otherwise be sniffed as a non-string
+
+use json_joy_buffers::Writer;
+
+use super::decoder::sniff;
+use super::error::CsvError;
+use crate::util::format_f64_js;
+use crate::PackValue;
+
+#[derive(Debug, Clone)]
+pub struct CsvEncoderOptions {
+    /// Field separator; `b','` for CSV, `b'\t'` for TSV.
+    pub delimiter: u8,
+    /// Terminate records with `\r\n` (RFC 4180) instead of `\n`.
+    pub crlf: bool,
+}
+
+impl Default for CsvEncoderOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            crlf: false,
+        }
+    }
+}
+
+pub struct CsvEncoder {
+    pub writer: Writer,
+    pub options: CsvEncoderOptions,
+}
+
+impl Default for CsvEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvEncoder {
+    pub fn new() -> Self {
+        Self::with_options(CsvEncoderOptions::default())
+    }
+
+    pub fn with_options(options: CsvEncoderOptions) -> Self {
+        Self {
+            writer: Writer::new(),
+            options,
+        }
+    }
+
+    pub fn encode(&mut self, value: &PackValue) -> Result<Vec<u8>, CsvError> {
+        self.writer.reset();
+        let rows = match value {
+            PackValue::Array(rows) => rows,
+            _ => return Err(CsvError::NotTabular),
+        };
+        let header = infer_header(rows)?;
+        if header.is_empty() {
+            return Ok(self.writer.flush());
+        }
+        for (i, key) in header.iter().enumerate() {
+            if i > 0 {
+                self.writer.u8(self.options.delimiter);
+            }
+            self.write_str(key);
+        }
+        self.write_eol();
+        for (row, value) in rows.iter().enumerate() {
+            let PackValue::Object(pairs) = value else {
+                unreachable!("checked by infer_header");
+            };
+            for (i, key) in header.iter().enumerate() {
+                if i > 0 {
+                    self.writer.u8(self.options.delimiter);
+                }
+                if let Some((_, field)) = pairs.iter().find(|(k, _)| k == key) {
+                    self.write_field(row, key, field)?;
+                }
+            }
+            self.write_eol();
+        }
+        Ok(self.writer.flush())
+    }
+
+    fn write_field(&mut self, row: usize, column: &str, value: &PackValue) -> Result<(), CsvError> {
+        let unsupported = |kind| CsvError::UnsupportedValue {
+            row,
+            column: column.to_string(),
+            kind,
+        };
+        match value {
+            PackValue::Null | PackValue::Undefined => {}
+            PackValue::Bool(b) => self.writer.ascii(if *b { "true" } else { "false" }),
+            PackValue::Integer(i) => self.writer.ascii(&i.to_string()),
+            PackValue::UInteger(u) => self.writer.ascii(&u.to_string()),
+            PackValue::Float(f) if f.is_finite() => self.writer.ascii(&format_f64_js(*f)),
+            PackValue::Float(_) => {}
+            PackValue::BigInt(i) => self.writer.ascii(&i.to_string()),
+            PackValue::BigNum(n) => self.writer.ascii(&n.to_string()),
+            PackValue::Str(s) => self.write_str(s),
+            PackValue::Bytes(_) => return Err(unsupported("binary")),
+            PackValue::Array(_) => return Err(unsupported("array")),
+            PackValue::Object(_) | PackValue::Map(_) => return Err(unsupported("object")),
+            PackValue::Extension(_) | PackValue::Blob(_) => return Err(unsupported("extension")),
+        }
+        Ok(())
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        let delimiter = self.options.delimiter;
+        let needs_quotes = s.is_empty()
+            || s.bytes()
+                .any(|b| b == delimiter || b == b'"' || b == b'\r' || b == b'\n')
+            || !matches!(sniff(s), PackValue::Str(_));
+        if !needs_quotes {
+            self.writer.utf8(s);
+            return;
+        }
+        self.writer.u8(b'"');
+        for (i, part) in s.split('"').enumerate() {
+            if i > 0 {
+                self.writer.ascii("\"\"");
+            }
+            self.writer.utf8(part);
+        }
+        self.writer.u8(b'"');
+    }
+
+    fn write_eol(&mut self) {
+        if self.options.crlf {
+            self.writer.u8(b'\r');
+        }
+        self.writer.u8(b'\n');
+    }
+}
+
+/// Union of the rows' keys in first-seen order.
+fn infer_header(rows: &[PackValue]) -> Result<Vec<&str>, CsvError> {
+    let mut header: Vec<&str> = Vec::new();
+    for row in rows {
+        let PackValue::Object(pairs) = row else {
+            return Err(CsvError::NotTabular);
+        };
+        for (key, _) in pairs {
+            if !header.contains(&key.as_str()) {
+                header.push(key);
+            }
+        }
+    }
+    Ok(header)
+}
@@ -0,0 +1,38 @@
+//! CSV encoder/decoder error type.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    #[error("CSV input must be an array of objects")]
+    NotTabular,
+    #[error("unsupported {kind} value in row {row}, column `{column}`")]
+    UnsupportedValue {
+        row: usize,
+        column: String,
+        kind: &'static str,
+    },
+    #[error("unterminated quoted field starting at offset {0}")]
+    UnterminatedQuote(usize),
+    #[error("unexpected byte after closing quote at offset {0}")]
+    InvalidQuote(usize),
+    #[error("invalid UTF-8 in field at offset {0}")]
+    InvalidUtf8(usize),
+    #[error("expected {expected} fields, found {found} in record at offset {offset}")]
+    FieldCount {
+        offset: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl CsvError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnterminatedQuote(x) | Self::InvalidQuote(x) | Self::InvalidUtf8(x) => Some(*x),
+            Self::FieldCount { offset, .. } => Some(*offset),
+            Self::NotTabular | Self::UnsupportedValue { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! CSV/TSV encoding/decoding for flat tables.
+//!
+//! Not an upstream port. A table is a [`crate::PackValue::Array`] of objects
+//! whose values are scalars; the header row is the union of all object keys
+//! in first-seen order. Set the delimiter to `b'\t'` for TSV.
+
+mod decoder;
+mod encoder;
+mod error;
+
+pub use decoder::{CsvDecoder, CsvDecoderOptions};
+pub use encoder::{CsvEncoder, CsvEncoderOptions};
+pub use error::CsvError;
@@ -15,40 +15,91 @@ use super::error::EjsonDecodeError;
 use super::value::EjsonValue;
 
 // ----------------------------------------------------------------
-// Decoder state
+// Options
+
+/// Which Extended JSON output format the decoder accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EjsonParseMode {
+    /// Accept canonical and relaxed forms, and legacy mixes of the two
+    /// (upstream behaviour).
+    #[default]
+    Any,
+    /// Accept only canonical EJSON: every number is wrapped and `$date`
+    /// holds `{"$numberLong": ...}`.
+    Canonical,
+    /// Accept only relaxed EJSON: numbers are native JSON unless they are
+    /// non-finite doubles, and `$date` uses an ISO-8601 string for years
+    /// 1970-9999.
+    Relaxed,
+}
 
-/// EJSON decoder — reads Extended JSON bytes and produces `EjsonValue`.
-pub struct EjsonDecoder {
-    data: Vec<u8>,
-    x: usize,
+/// Options controlling EJSON decoding strictness.
+#[derive(Debug, Clone)]
+pub struct EjsonDecoderOptions {
+    pub mode: EjsonParseMode,
+    /// When `true` (default), objects with unrecognised `$`-prefixed keys
+    /// decode as plain objects.  When `false`, they fail with
+    /// [`EjsonDecodeError::UnknownKey`].
+    pub lossy: bool,
 }
 
-impl Default for EjsonDecoder {
+impl Default for EjsonDecoderOptions {
     fn default() -> Self {
-        Self::new()
+        Self {
+            mode: EjsonParseMode::Any,
+            lossy: true,
+        }
     }
 }
 
+// ----------------------------------------------------------------
+// Decoder state
+
+/// EJSON decoder — reads Extended JSON bytes and produces `EjsonValue`.
+#[derive(Debug, Clone, Default)]
+pub struct EjsonDecoder {
+    pub options: EjsonDecoderOptions,
+}
+
 impl EjsonDecoder {
     pub fn new() -> Self {
-        Self {
-            data: Vec::new(),
-            x: 0,
-        }
+        Self::with_options(EjsonDecoderOptions::default())
+    }
+
+    pub fn with_options(options: EjsonDecoderOptions) -> Self {
+        Self { options }
     }
 
-    /// Decode from bytes.
+    /// Decode from bytes. The input is read in place, without copying it or
+    /// validating it as a whole as UTF-8 first.
     pub fn decode(&mut self, input: &[u8]) -> Result<EjsonValue, EjsonDecodeError> {
-        self.data = input.to_vec();
-        self.x = 0;
-        self.read_any()
+        let mut parser = Parser {
+            data: input,
+            x: 0,
+            options: &self.options,
+            wrapper: None,
+        };
+        let value = parser.read_any()?;
+        parser.check_number(&value, None, "")?;
+        Ok(value)
     }
 
     /// Convenience: decode from a UTF-8 string.
     pub fn decode_str(&mut self, s: &str) -> Result<EjsonValue, EjsonDecodeError> {
         self.decode(s.as_bytes())
     }
+}
+
+/// State of a single [`EjsonDecoder::decode`] call.
+struct Parser<'a> {
+    data: &'a [u8],
+    x: usize,
+    options: &'a EjsonDecoderOptions,
+    /// The `$`-prefixed key whose value is being read, if any.
+    wrapper: Option<String>,
+}
 
+impl Parser<'_> {
     // ----------------------------------------------------------------
     // Core read dispatch
 
@@ -185,6 +236,7 @@ impl EjsonDecoder {
             return Err(EjsonDecodeError::InvalidJson(x));
         }
         self.x += 1;
+        self.wrapper = None;
         let mut arr = Vec::new();
         let mut first = true;
         loop {
@@ -203,7 +255,9 @@ impl EjsonDecoder {
                 return Err(EjsonDecodeError::InvalidJson(self.x));
             }
             self.skip_ws();
-            arr.push(self.read_any()?);
+            let item = self.read_any()?;
+            self.check_number(&item, None, "")?;
+            arr.push(item);
             first = false;
         }
     }
@@ -218,6 +272,7 @@ impl EjsonDecoder {
             return Err(EjsonDecodeError::InvalidJson(x));
         }
         self.x += 1;
+        let wrapper = self.wrapper.take();
 
         // Read all key-value pairs as raw EjsonValue
         let mut pairs: Vec<(String, EjsonValue)> = Vec::new();
@@ -251,21 +306,50 @@ impl EjsonDecoder {
             }
             self.x += 1;
             self.skip_ws();
+            self.wrapper = key.starts_with('$').then(|| key.clone());
             let val = self.read_any()?;
+            self.wrapper = None;
+            self.check_number(&val, wrapper.as_deref(), &key)?;
             pairs.push((key, val));
             first = false;
         }
 
-        self.transform_ejson_object(pairs)
+        self.transform_ejson_object(pairs, wrapper.as_deref())
+    }
+
+    /// In canonical mode, rejects a bare JSON number unless it is one of
+    /// the few wrapper operands that stay unwrapped (`$timestamp` fields,
+    /// `$minKey`, `$maxKey`).
+    fn check_number(
+        &self,
+        value: &EjsonValue,
+        wrapper: Option<&str>,
+        key: &str,
+    ) -> Result<(), EjsonDecodeError> {
+        if self.options.mode != EjsonParseMode::Canonical
+            || !matches!(value, EjsonValue::Integer(_) | EjsonValue::Float(_))
+        {
+            return Ok(());
+        }
+        let operand = matches!(key, "$minKey" | "$maxKey")
+            || (wrapper == Some("$timestamp") && matches!(key, "t" | "i"));
+        if operand {
+            Ok(())
+        } else {
+            Err(EjsonDecodeError::NotCanonical("Number"))
+        }
     }
 
     // ----------------------------------------------------------------
     // EJSON type wrapper transformation
 
+    /// `wrapper` is the `$`-prefixed key this object is the value of, if any.
     fn transform_ejson_object(
         &self,
         pairs: Vec<(String, EjsonValue)>,
+        wrapper: Option<&str>,
     ) -> Result<EjsonValue, EjsonDecodeError> {
+        let mode = self.options.mode;
         // Find $ keys
         let dollar_keys: Vec<&str> = pairs
             .iter()
@@ -291,8 +375,8 @@ impl EjsonDecoder {
                     return Err(EjsonDecodeError::ExtraKeys("ObjectId"));
                 }
                 if let Some(EjsonValue::Str(s)) = get("$oid") {
-                    if s.len() == 24 && s.bytes().all(|b: u8| b.is_ascii_hexdigit()) {
-                        return Ok(EjsonValue::ObjectId(parse_object_id(s)));
+                    if let Some(id) = BsonObjectId::parse_hex(s) {
+                        return Ok(EjsonValue::ObjectId(id));
                     }
                 }
                 return Err(EjsonDecodeError::InvalidObjectId);
@@ -303,6 +387,9 @@ impl EjsonDecoder {
                 if !has_exact(&["$numberInt"]) {
                     return Err(EjsonDecodeError::ExtraKeys("Int32"));
                 }
+                if mode == EjsonParseMode::Relaxed {
+                    return Err(EjsonDecodeError::NotRelaxed("Int32"));
+                }
                 if let Some(EjsonValue::Str(s)) = get("$numberInt") {
                     if let Ok(v) = s.parse::<i32>() {
                         return Ok(EjsonValue::Int32(BsonInt32 { value: v }));
@@ -316,6 +403,10 @@ impl EjsonDecoder {
                 if !has_exact(&["$numberLong"]) {
                     return Err(EjsonDecodeError::ExtraKeys("Int64"));
                 }
+                // Relaxed EJSON keeps `$numberLong` only for out-of-range dates.
+                if mode == EjsonParseMode::Relaxed && wrapper != Some("$date") {
+                    return Err(EjsonDecodeError::NotRelaxed("Int64"));
+                }
                 if let Some(EjsonValue::Str(s)) = get("$numberLong") {
                     // Use parse::<f64> to handle large numbers (matching upstream's parseFloat)
                     if let Ok(v) = s.parse::<f64>() {
@@ -346,6 +437,9 @@ impl EjsonDecoder {
                             parsed
                         }
                     };
+                    if mode == EjsonParseMode::Relaxed && v.is_finite() {
+                        return Err(EjsonDecodeError::NotRelaxed("Double"));
+                    }
                     return Ok(EjsonValue::BsonFloat(BsonFloat { value: v }));
                 }
                 return Err(EjsonDecodeError::InvalidDouble);
@@ -431,8 +525,8 @@ impl EjsonDecoder {
                 // Convert scope EjsonValue pairs to BsonValue pairs
                 let bson_scope: Vec<(String, BsonValue)> = scope_pairs
                     .into_iter()
-                    .map(|(k, v)| Ok((k, ejson_to_bson_value(v)?)))
-                    .collect::<Result<_, EjsonDecodeError>>()?;
+                    .map(|(k, v)| (k, v.into()))
+                    .collect();
                 return Ok(EjsonValue::CodeWithScope(BsonJavascriptCodeWithScope {
                     code,
                     scope: bson_scope,
@@ -526,6 +620,9 @@ impl EjsonDecoder {
                     return Err(EjsonDecodeError::ExtraKeys("Date"));
                 }
                 match get("$date") {
+                    Some(EjsonValue::Str(_)) if mode == EjsonParseMode::Canonical => {
+                        return Err(EjsonDecodeError::NotCanonical("Date"));
+                    }
                     Some(EjsonValue::Str(s)) => {
                         // ISO-8601 string (relaxed mode)
                         match parse_iso_date(s) {
@@ -539,12 +636,22 @@ impl EjsonDecoder {
                         }
                     }
                     // Canonical: {"$numberLong":"timestamp"} was already decoded to Int64
+                    Some(EjsonValue::Int64(v))
+                        if mode == EjsonParseMode::Relaxed
+                            && (0..=MAX_RELAXED_DATE_MS).contains(&v.value) =>
+                    {
+                        return Err(EjsonDecodeError::NotRelaxed("Date"));
+                    }
                     Some(EjsonValue::Int64(v)) => {
                         return Ok(EjsonValue::Date {
                             timestamp_ms: v.value,
                             iso: None,
                         });
                     }
+                    // Legacy bare-number form, neither canonical nor relaxed.
+                    Some(EjsonValue::Integer(_)) if mode == EjsonParseMode::Relaxed => {
+                        return Err(EjsonDecodeError::NotRelaxed("Date"));
+                    }
                     Some(EjsonValue::Integer(ms)) => {
                         return Ok(EjsonValue::Date {
                             timestamp_ms: *ms,
@@ -610,7 +717,16 @@ impl EjsonDecoder {
         // DBRef convention: object with $ref + $id (may have additional fields)
         let has_ref = pairs.iter().any(|(k, _)| k == "$ref");
         let has_id = pairs.iter().any(|(k, _)| k == "$id");
-        if has_ref && has_id {
+        let is_db_ref = has_ref && has_id;
+        if !self.options.lossy {
+            let unknown = pairs.iter().map(|(k, _)| k).find(|k| {
+                k.starts_with('$') && !(is_db_ref && matches!(k.as_str(), "$ref" | "$id" | "$db"))
+            });
+            if let Some(key) = unknown {
+                return Err(EjsonDecodeError::UnknownKey(key.clone()));
+            }
+        }
+        if is_db_ref {
             // Pass through as an object, but transform the $id value
             let mut result: Vec<(String, EjsonValue)> = Vec::new();
             for (key, val) in pairs {
@@ -637,7 +753,7 @@ impl EjsonDecoder {
     /// Re-dispatch a value that was read as raw, in case it is a nested EJSON object.
     fn transform_ejson_value(&self, value: EjsonValue) -> Result<EjsonValue, EjsonDecodeError> {
         match value {
-            EjsonValue::Object(pairs) => self.transform_ejson_object(pairs),
+            EjsonValue::Object(pairs) => self.transform_ejson_object(pairs, None),
             EjsonValue::Array(items) => {
                 let mut out = Vec::with_capacity(items.len());
                 for item in items {
@@ -653,17 +769,9 @@ impl EjsonDecoder {
 // ----------------------------------------------------------------
 // Utility functions
 
-fn parse_object_id(hex: &str) -> BsonObjectId {
-    // 24-char hex → 4-byte timestamp + 5-byte process + 3-byte counter
-    let timestamp = u32::from_str_radix(&hex[0..8], 16).unwrap_or(0);
-    let process = u64::from_str_radix(&hex[8..18], 16).unwrap_or(0);
-    let counter = u32::from_str_radix(&hex[18..24], 16).unwrap_or(0);
-    BsonObjectId {
-        timestamp,
-        process,
-        counter,
-    }
-}
+/// `9999-12-31T23:59:59.999Z`, the last date relaxed EJSON writes as an
+/// ISO-8601 string.
+const MAX_RELAXED_DATE_MS: i64 = 253_402_300_799_999;
 
 fn base64_to_bytes(b64: &str) -> Option<Vec<u8>> {
     json_joy_base64::from_base64(b64).ok()
@@ -788,45 +896,3 @@ fn decode_json_string(bytes: &[u8]) -> Result<String, EjsonDecodeError> {
         serde_json::from_slice(&quoted).map_err(|_| EjsonDecodeError::InvalidJson(0))?;
     Ok(s)
 }
-
-/// Convert an `EjsonValue` to a `BsonValue` for use in CodeWithScope scopes.
-fn ejson_to_bson_value(v: EjsonValue) -> Result<BsonValue, EjsonDecodeError> {
-    match v {
-        EjsonValue::Null => Ok(BsonValue::Null),
-        EjsonValue::Bool(b) => Ok(BsonValue::Boolean(b)),
-        EjsonValue::Integer(i) => Ok(BsonValue::Int64(i)),
-        EjsonValue::Float(f) => Ok(BsonValue::Float(f)),
-        EjsonValue::Str(s) => Ok(BsonValue::Str(s)),
-        EjsonValue::Array(arr) => {
-            let bson_arr: Result<Vec<BsonValue>, _> =
-                arr.into_iter().map(ejson_to_bson_value).collect();
-            Ok(BsonValue::Array(bson_arr?))
-        }
-        EjsonValue::Object(pairs) => {
-            let bson_pairs: Result<Vec<(String, BsonValue)>, _> = pairs
-                .into_iter()
-                .map(|(k, v)| ejson_to_bson_value(v).map(|bv| (k, bv)))
-                .collect();
-            Ok(BsonValue::Document(bson_pairs?))
-        }
-        EjsonValue::Number(f) => {
-            if f.fract() == 0.0 && f.is_finite() {
-                Ok(BsonValue::Int64(f as i64))
-            } else {
-                Ok(BsonValue::Float(f))
-            }
-        }
-        EjsonValue::Int32(v) => Ok(BsonValue::Int32(v.value)),
-        EjsonValue::Int64(v) => Ok(BsonValue::Int64(v.value)),
-        EjsonValue::BsonFloat(v) => Ok(BsonValue::Float(v.value)),
-        EjsonValue::ObjectId(v) => Ok(BsonValue::ObjectId(v)),
-        EjsonValue::Binary(v) => Ok(BsonValue::Binary(v)),
-        EjsonValue::Date {
-            timestamp_ms: ms, ..
-        } => Ok(BsonValue::DateTime(ms)),
-        EjsonValue::Symbol(v) => Ok(BsonValue::Symbol(v)),
-        EjsonValue::Timestamp(v) => Ok(BsonValue::Timestamp(v)),
-        // Fall back to null for types that don't map cleanly
-        _ => Ok(BsonValue::Null),
-    }
-}
@@ -11,6 +11,7 @@ use crate::bson::{
     BsonBinary, BsonDbPointer, BsonDecimal128, BsonFloat, BsonInt32, BsonInt64, BsonJavascriptCode,
     BsonObjectId, BsonSymbol, BsonTimestamp, BsonValue,
 };
+use crate::util::format_f64_js;
 
 use super::error::EjsonEncodeError;
 use super::value::EjsonValue;
@@ -58,6 +59,15 @@ impl EjsonEncoder {
         }
     }
 
+    /// Creates an encoder writing into `writer`, e.g. one taken from a
+    /// [`WriterPool`](json_joy_buffers::WriterPool).
+    pub fn with_writer(writer: Writer) -> Self {
+        Self {
+            writer,
+            options: EjsonEncoderOptions::default(),
+        }
+    }
+
     // ----------------------------------------------------------------
     // Public encode entry-points
 
@@ -68,6 +78,23 @@ impl EjsonEncoder {
         Ok(self.writer.flush())
     }
 
+    /// Appends the encoding of `value` to `out`, e.g. after a frame header
+    /// another encoder wrote. On error nothing is appended.
+    pub fn encode_into(
+        &mut self,
+        value: &EjsonValue,
+        out: &mut Writer,
+    ) -> Result<(), EjsonEncodeError> {
+        let mark = out.x - out.x0;
+        core::mem::swap(&mut self.writer, out);
+        let result = self.write_any(value);
+        core::mem::swap(&mut self.writer, out);
+        if result.is_err() {
+            out.x = out.x0 + mark;
+        }
+        result
+    }
+
     /// Convenience: encode and return as a `String`.
     pub fn encode_to_string(&mut self, value: &EjsonValue) -> Result<String, EjsonEncodeError> {
         let bytes = self.encode(value)?;
@@ -182,7 +209,7 @@ impl EjsonEncoder {
 
     /// Write a plain JSON number (integer-like or float).
     pub fn write_number(&mut self, n: f64) {
-        let s = format_number(n);
+        let s = format_f64_js(n);
         self.writer.ascii(&s);
     }
 
@@ -232,7 +259,7 @@ impl EjsonEncoder {
 
     fn write_float_as_ejson(&mut self, value: f64) {
         // A plain float from decoded JSON — emit as-is (not a BSON wrapper)
-        self.writer.ascii(&format_number(value));
+        self.writer.ascii(&format_f64_js(value));
     }
 
     // ----------------------------------------------------------------
@@ -287,7 +314,7 @@ impl EjsonEncoder {
         let s = if !value.is_finite() {
             format_non_finite(value)
         } else {
-            format_number(value)
+            format_f64_js(value)
         };
         self.writer.ascii(&s);
         self.writer.buf(b"\"}");
@@ -348,7 +375,7 @@ impl EjsonEncoder {
     fn write_object_id_as_ejson(&mut self, id: &BsonObjectId) {
         // {"$oid":"hexstring"}
         self.writer.buf(b"{\"$oid\":\"");
-        let hex = object_id_to_hex(id);
+        let hex = id.to_hex();
         self.writer.ascii(&hex);
         self.writer.buf(b"\"}");
     }
@@ -448,7 +475,7 @@ impl EjsonEncoder {
                 }
                 self.write_str(key);
                 self.writer.u8(b':');
-                let ejson_val = bson_to_ejson_value(val);
+                let ejson_val = EjsonValue::from(val.clone());
                 self.write_any(&ejson_val)?;
             }
             self.writer.u8(b'}');
@@ -517,14 +544,6 @@ impl EjsonEncoder {
 // ----------------------------------------------------------------
 // Utility functions
 
-fn format_number(n: f64) -> String {
-    if n.fract() == 0.0 && n.is_finite() && n.abs() < 1e15 {
-        format!("{}", n as i64)
-    } else {
-        format!("{}", n)
-    }
-}
-
 fn format_integer_f64(n: f64) -> String {
     // Large integer stored as f64 — format without decimal point
     format!("{}", n as i64)
@@ -540,11 +559,6 @@ fn format_non_finite(n: f64) -> String {
     }
 }
 
-fn object_id_to_hex(id: &BsonObjectId) -> String {
-    // 4-byte timestamp (8 hex) + 5-byte process (10 hex) + 3-byte counter (6 hex) = 24 hex chars
-    format!("{:08x}{:010x}{:06x}", id.timestamp, id.process, id.counter)
-}
-
 fn iso_string_from_unix_ms(ms: i64) -> Option<String> {
     let (year, month, day, hour, minute, second, millis) = date_parts_from_unix_ms(ms)?;
     if !(1970..=9999).contains(&year) {
@@ -586,31 +600,3 @@ fn civil_from_days(days: i64) -> Option<(i64, i64, i64)> {
 fn decimal128_to_string(_data: &[u8]) -> String {
     "0".to_string()
 }
-
-/// Convert a `BsonValue` to an `EjsonValue` for encoding scope fields.
-fn bson_to_ejson_value(v: &BsonValue) -> EjsonValue {
-    match v {
-        BsonValue::Null => EjsonValue::Null,
-        BsonValue::Boolean(b) => EjsonValue::Bool(*b),
-        BsonValue::Int32(i) => EjsonValue::Int32(BsonInt32 { value: *i }),
-        BsonValue::Int64(i) => EjsonValue::Int64(BsonInt64 { value: *i }),
-        BsonValue::Float(f) => EjsonValue::BsonFloat(BsonFloat { value: *f }),
-        BsonValue::Str(s) => EjsonValue::Str(s.clone()),
-        BsonValue::ObjectId(id) => EjsonValue::ObjectId(id.clone()),
-        BsonValue::Binary(b) => EjsonValue::Binary(b.clone()),
-        BsonValue::DateTime(ms) => EjsonValue::Date {
-            timestamp_ms: *ms,
-            iso: None,
-        },
-        BsonValue::Symbol(s) => EjsonValue::Symbol(s.clone()),
-        BsonValue::Timestamp(t) => EjsonValue::Timestamp(t.clone()),
-        BsonValue::Array(arr) => EjsonValue::Array(arr.iter().map(bson_to_ejson_value).collect()),
-        BsonValue::Document(fields) => EjsonValue::Object(
-            fields
-                .iter()
-                .map(|(k, v)| (k.clone(), bson_to_ejson_value(v)))
-                .collect(),
-        ),
-        _ => EjsonValue::Null,
-    }
-}
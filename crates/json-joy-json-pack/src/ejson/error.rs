@@ -62,6 +62,15 @@ pub enum EjsonDecodeError {
     InvalidUndefined,
     /// Extra keys found where not allowed (strict single-key wrapper).
     ExtraKeys(&'static str),
+    /// A relaxed-only form was found while decoding canonical EJSON.
+    NotCanonical(&'static str),
+    /// A canonical-only form was found while decoding relaxed EJSON.
+    NotRelaxed(&'static str),
+    /// Unrecognised `$`-prefixed key (only when `lossy` is off).
+    UnknownKey(String),
+    /// The top-level value is not an object, so it cannot become a BSON
+    /// document.
+    NotDocument,
 }
 
 impl fmt::Display for EjsonDecodeError {
@@ -91,6 +100,14 @@ impl fmt::Display for EjsonDecodeError {
             EjsonDecodeError::ExtraKeys(kind) => {
                 write!(f, "Invalid {kind} format: extra keys not allowed")
             }
+            EjsonDecodeError::NotCanonical(kind) => {
+                write!(f, "Invalid {kind} format: not canonical EJSON")
+            }
+            EjsonDecodeError::NotRelaxed(kind) => {
+                write!(f, "Invalid {kind} format: not relaxed EJSON")
+            }
+            EjsonDecodeError::UnknownKey(key) => write!(f, "Unknown EJSON key {key:?}"),
+            EjsonDecodeError::NotDocument => write!(f, "Top-level value is not a document"),
         }
     }
 }
@@ -188,6 +205,22 @@ mod tests {
                 EjsonDecodeError::ExtraKeys("$oid"),
                 "Invalid $oid format: extra keys not allowed",
             ),
+            (
+                EjsonDecodeError::NotCanonical("Date"),
+                "Invalid Date format: not canonical EJSON",
+            ),
+            (
+                EjsonDecodeError::NotRelaxed("Int32"),
+                "Invalid Int32 format: not relaxed EJSON",
+            ),
+            (
+                EjsonDecodeError::UnknownKey("$foo".into()),
+                "Unknown EJSON key \"$foo\"",
+            ),
+            (
+                EjsonDecodeError::NotDocument,
+                "Top-level value is not a document",
+            ),
         ];
         for (err, expected) in cases {
             assert_eq!(err.to_string(), expected, "mismatch for {err:?}");
@@ -12,9 +12,11 @@
 pub mod decoder;
 pub mod encoder;
 pub mod error;
+pub mod transcode;
 pub mod value;
 
-pub use decoder::EjsonDecoder;
+pub use decoder::{EjsonDecoder, EjsonDecoderOptions, EjsonParseMode};
 pub use encoder::{EjsonEncoder, EjsonEncoderOptions};
 pub use error::{EjsonDecodeError, EjsonEncodeError};
+pub use transcode::{ejson_str_to_bson_doc, from_bson, to_bson};
 pub use value::EjsonValue;
@@ -0,0 +1,55 @@
+//! Direct EJSON <-> BSON document conversion.
+//!
+//! Rust-only extension: upstream converts through plain JS values. These
+//! helpers map documents field by field using the `EjsonValue` /
+//! `BsonValue` conversions, so callers do not need per-type match arms.
+
+use crate::bson::{BsonEncoder, BsonValue};
+
+use super::decoder::EjsonDecoder;
+use super::error::EjsonDecodeError;
+use super::value::EjsonValue;
+
+/// Converts an EJSON object to BSON document fields.
+///
+/// Returns `None` when `value` is not an object, since BSON has no scalar
+/// top-level encoding. Untyped integers become int32 when they fit and
+/// int64 otherwise, matching upstream.
+pub fn to_bson(value: &EjsonValue) -> Option<Vec<(String, BsonValue)>> {
+    match value {
+        EjsonValue::Object(pairs) => Some(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone().into()))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Converts BSON document fields to an EJSON object.
+pub fn from_bson(fields: &[(String, BsonValue)]) -> EjsonValue {
+    EjsonValue::Object(
+        fields
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone().into()))
+            .collect(),
+    )
+}
+
+/// Decodes Extended JSON text and encodes it as a BSON document.
+///
+/// ```
+/// use json_joy_json_pack::ejson::ejson_str_to_bson_doc;
+///
+/// let bytes = ejson_str_to_bson_doc(r#"{"n":{"$numberInt":"1"}}"#).unwrap();
+/// assert_eq!(bytes, [12, 0, 0, 0, 0x10, b'n', 0, 1, 0, 0, 0, 0]);
+///
+/// // Relaxed integers are written as int32 too.
+/// assert_eq!(ejson_str_to_bson_doc(r#"{"n":1}"#).unwrap(), bytes);
+/// ```
+pub fn ejson_str_to_bson_doc(input: &str) -> Result<Vec<u8>, EjsonDecodeError> {
+    let value = EjsonDecoder::new().decode_str(input)?;
+    let fields = to_bson(&value).ok_or(EjsonDecodeError::NotDocument)?;
+    Ok(BsonEncoder::new().encode(&fields))
+}
@@ -6,6 +6,7 @@
 use crate::bson::{
     BsonBinary, BsonDbPointer, BsonDecimal128, BsonFloat, BsonInt32, BsonInt64, BsonJavascriptCode,
     BsonJavascriptCodeWithScope, BsonMaxKey, BsonMinKey, BsonObjectId, BsonSymbol, BsonTimestamp,
+    BsonValue,
 };
 
 /// A value that the EJSON codec knows how to encode and decode.
@@ -57,3 +58,87 @@ pub enum EjsonValue {
     MinKey(BsonMinKey),
     MaxKey(BsonMaxKey),
 }
+
+/// Maps each BSON element to its typed EJSON variant, so encoding the result
+/// in canonical mode and decoding it back yields the same `BsonValue`.
+impl From<BsonValue> for EjsonValue {
+    fn from(v: BsonValue) -> Self {
+        match v {
+            BsonValue::Float(value) => EjsonValue::BsonFloat(BsonFloat { value }),
+            BsonValue::Str(s) => EjsonValue::Str(s),
+            BsonValue::Document(fields) => {
+                EjsonValue::Object(fields.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+            BsonValue::Array(arr) => EjsonValue::Array(arr.into_iter().map(Into::into).collect()),
+            BsonValue::Binary(b) => EjsonValue::Binary(b),
+            BsonValue::Undefined => EjsonValue::Undefined,
+            BsonValue::ObjectId(id) => EjsonValue::ObjectId(id),
+            BsonValue::Boolean(b) => EjsonValue::Bool(b),
+            BsonValue::DateTime(timestamp_ms) => EjsonValue::Date {
+                timestamp_ms,
+                iso: None,
+            },
+            BsonValue::Null => EjsonValue::Null,
+            BsonValue::Regex(pattern, flags) => EjsonValue::RegExp(pattern, flags),
+            BsonValue::DbPointer(p) => EjsonValue::DbPointer(p),
+            BsonValue::JavaScriptCode(c) => EjsonValue::Code(c),
+            BsonValue::Symbol(s) => EjsonValue::Symbol(s),
+            BsonValue::JavaScriptCodeWithScope(c) => EjsonValue::CodeWithScope(c),
+            BsonValue::Int32(value) => EjsonValue::Int32(BsonInt32 { value }),
+            BsonValue::Timestamp(t) => EjsonValue::Timestamp(t),
+            BsonValue::Int64(value) => EjsonValue::Int64(BsonInt64 { value }),
+            BsonValue::Decimal128(d) => EjsonValue::Decimal128(d),
+            BsonValue::MinKey => EjsonValue::MinKey(BsonMinKey),
+            BsonValue::MaxKey => EjsonValue::MaxKey(BsonMaxKey),
+        }
+    }
+}
+
+/// Untyped JSON numbers become int64 when integral and doubles otherwise.
+/// An untyped integer as upstream's BSON encoder writes it: int32 when it
+/// fits, else int64.
+fn bson_int(i: i64) -> BsonValue {
+    match i32::try_from(i) {
+        Ok(i) => BsonValue::Int32(i),
+        Err(_) => BsonValue::Int64(i),
+    }
+}
+
+impl From<EjsonValue> for BsonValue {
+    fn from(v: EjsonValue) -> Self {
+        match v {
+            EjsonValue::Null => BsonValue::Null,
+            EjsonValue::Undefined => BsonValue::Undefined,
+            EjsonValue::Bool(b) => BsonValue::Boolean(b),
+            EjsonValue::Integer(i) => bson_int(i),
+            EjsonValue::Float(f) => BsonValue::Float(f),
+            EjsonValue::Number(f) => {
+                if f.fract() == 0.0 && f.is_finite() {
+                    bson_int(f as i64)
+                } else {
+                    BsonValue::Float(f)
+                }
+            }
+            EjsonValue::Str(s) => BsonValue::Str(s),
+            EjsonValue::Array(arr) => BsonValue::Array(arr.into_iter().map(Into::into).collect()),
+            EjsonValue::Object(pairs) => {
+                BsonValue::Document(pairs.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+            EjsonValue::Date { timestamp_ms, .. } => BsonValue::DateTime(timestamp_ms),
+            EjsonValue::RegExp(pattern, flags) => BsonValue::Regex(pattern, flags),
+            EjsonValue::ObjectId(id) => BsonValue::ObjectId(id),
+            EjsonValue::Int32(v) => BsonValue::Int32(v.value),
+            EjsonValue::Int64(v) => BsonValue::Int64(v.value),
+            EjsonValue::BsonFloat(v) => BsonValue::Float(v.value),
+            EjsonValue::Decimal128(d) => BsonValue::Decimal128(d),
+            EjsonValue::Binary(b) => BsonValue::Binary(b),
+            EjsonValue::Code(c) => BsonValue::JavaScriptCode(c),
+            EjsonValue::CodeWithScope(c) => BsonValue::JavaScriptCodeWithScope(c),
+            EjsonValue::Symbol(s) => BsonValue::Symbol(s),
+            EjsonValue::Timestamp(t) => BsonValue::Timestamp(t),
+            EjsonValue::DbPointer(p) => BsonValue::DbPointer(p),
+            EjsonValue::MinKey(_) => BsonValue::MinKey,
+            EjsonValue::MaxKey(_) => BsonValue::MaxKey,
+        }
+    }
+}
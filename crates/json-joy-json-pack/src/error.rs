@@ -0,0 +1,148 @@
+//! Crate-level error type wrapping every per-format decoder error.
+//!
+//! Not an upstream port: upstream throws untyped `Error`s. Each format keeps
+//! its own enum; [`JsonPackError`] lets callers that handle several formats
+//! propagate them with `?` and still recover the format and byte offset.
+
+use thiserror::Error;
+
+use crate::avro::AvroDecodeError;
+use crate::bencode::BencodeError;
+use crate::bson::BsonError;
+use crate::cbor::CborError;
+use crate::codecs::CodecError;
+use crate::csv::CsvError;
+use crate::ejson::EjsonDecodeError;
+use crate::http_frames::HttpFrameError;
+use crate::ion::IonDecodeError;
+use crate::json::JsonError;
+use crate::jsonrpc::JsonRpcError;
+use crate::msgpack::MsgPackError;
+use crate::proto::ProtoError;
+use crate::resp::RespDecodeError;
+use crate::rm::RmDecodeError;
+use crate::rpc::RpcDecodeError;
+use crate::rpc_rx::RxError;
+use crate::ssh::SshError;
+use crate::ubjson::UbjsonError;
+use crate::ws::WsFrameDecodingError;
+use crate::xdr::XdrDecodeError;
+
+#[derive(Debug, Error)]
+pub enum JsonPackError {
+    #[error("Avro: {0}")]
+    Avro(#[from] AvroDecodeError),
+    #[error("Bencode: {0}")]
+    Bencode(#[from] BencodeError),
+    #[error("BSON: {0}")]
+    Bson(#[from] BsonError),
+    #[error("CBOR: {0}")]
+    Cbor(#[from] CborError),
+    /// A [`CodecError`] that belongs to no single format, such as input
+    /// whose format was not recognised.
+    #[error("codecs: {0}")]
+    Codec(CodecError),
+    #[error("CSV: {0}")]
+    Csv(#[from] CsvError),
+    #[error("EJSON: {0}")]
+    Ejson(#[from] EjsonDecodeError),
+    #[error("HTTP framing: {0}")]
+    HttpFrames(#[from] HttpFrameError),
+    #[error("Ion: {0}")]
+    Ion(#[from] IonDecodeError),
+    #[error("JSON: {0}")]
+    Json(#[from] JsonError),
+    #[error("JSON-RPC: {0}")]
+    JsonRpc(#[from] JsonRpcError),
+    #[error("MessagePack: {0}")]
+    MsgPack(#[from] MsgPackError),
+    #[error("Protobuf: {0}")]
+    Proto(#[from] ProtoError),
+    #[error("RESP: {0}")]
+    Resp(#[from] RespDecodeError),
+    #[error("RM: {0}")]
+    Rm(#[from] RmDecodeError),
+    #[error("RPC: {0}")]
+    Rpc(#[from] RpcDecodeError),
+    #[error("Reactive-RPC: {0}")]
+    RpcRx(#[from] RxError),
+    #[error("SSH: {0}")]
+    Ssh(#[from] SshError),
+    #[error("UBJSON: {0}")]
+    Ubjson(#[from] UbjsonError),
+    #[error("WebSocket: {0}")]
+    Ws(#[from] WsFrameDecodingError),
+    #[error("XDR: {0}")]
+    Xdr(#[from] XdrDecodeError),
+}
+
+impl JsonPackError {
+    /// Short name of the format that produced the error, e.g. `"msgpack"`.
+    pub fn format(&self) -> &'static str {
+        match self {
+            Self::Avro(_) => "avro",
+            Self::Bencode(_) => "bencode",
+            Self::Bson(_) => "bson",
+            Self::Cbor(_) => "cbor",
+            Self::Codec(_) => "codecs",
+            Self::Csv(_) => "csv",
+            Self::Ejson(_) => "ejson",
+            Self::HttpFrames(_) => "http_frames",
+            Self::Ion(_) => "ion",
+            Self::Json(_) => "json",
+            Self::JsonRpc(_) => "jsonrpc",
+            Self::MsgPack(_) => "msgpack",
+            Self::Proto(_) => "proto",
+            Self::Resp(_) => "resp",
+            Self::Rm(_) => "rm",
+            Self::Rpc(_) => "rpc",
+            Self::RpcRx(_) => "rpc_rx",
+            Self::Ssh(_) => "ssh",
+            Self::Ubjson(_) => "ubjson",
+            Self::Ws(_) => "ws",
+            Self::Xdr(_) => "xdr",
+        }
+    }
+
+    /// Byte offset in the input at which the error was detected, for formats
+    /// that track it.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Avro(e) => e.offset(),
+            Self::Bencode(e) => e.offset(),
+            Self::Bson(e) => e.offset(),
+            Self::Cbor(e) => e.offset(),
+            Self::Csv(e) => e.offset(),
+            Self::Ejson(EjsonDecodeError::InvalidJson(x)) => Some(*x),
+            Self::HttpFrames(e) => e.offset(),
+            Self::Ion(e) => e.offset(),
+            Self::Json(e) => e.offset(),
+            Self::MsgPack(e) => e.offset(),
+            Self::Proto(e) => e.offset(),
+            Self::Resp(e) => e.offset(),
+            Self::RpcRx(e) => e.offset(),
+            Self::Ssh(e) => e.offset(),
+            Self::Ubjson(e) => e.offset(),
+            Self::Xdr(e) => e.offset(),
+            _ => None,
+        }
+    }
+}
+
+impl From<CodecError> for JsonPackError {
+    fn from(err: CodecError) -> Self {
+        match err {
+            CodecError::Cbor(e) => Self::Cbor(e),
+            CodecError::Json(e) => Self::Json(e),
+            CodecError::MsgPack(e) => Self::MsgPack(e),
+            CodecError::Bson(e) => Self::Bson(e),
+            CodecError::Ion(e) => Self::Ion(e),
+            CodecError::Ubjson(e) => Self::Ubjson(e),
+            CodecError::Resp(e) => Self::Resp(e),
+            err @ (CodecError::EjsonEncode(_)
+            | CodecError::UnknownFormat
+            | CodecError::TruncatedEnvelope
+            | CodecError::UnsupportedFormat(_)) => Self::Codec(err),
+        }
+    }
+}
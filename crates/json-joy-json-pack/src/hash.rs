@@ -0,0 +1,215 @@
+//! Structural hashes of values.
+//!
+//! Not an upstream port. A value is hashed through its canonical CBOR
+//! encoding ([`CborEncoderStable`] with its default
+//! [`KeyOrder::ByteLengthLex`](crate::KeyOrder::ByteLengthLex)), so object
+//! key order does not matter and `undefined` hashes like `null`. The result
+//! is stable across runs, platforms and releases that keep the canonical
+//! encoding, which makes it usable for cache keys and deduplication.
+//!
+//! [`hash64`] and [`hash128`] are FNV-1a: fast, but not collision resistant
+//! against chosen inputs. Use [`hash_sha256`] for untrusted data.
+
+use crate::cbor::CborEncoderStable;
+use crate::PackValue;
+
+const FNV64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+const FNV128_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV128_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// Hash function applied to the canonical encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// 64-bit FNV-1a.
+    #[default]
+    Fnv64,
+    /// 128-bit FNV-1a.
+    Fnv128,
+    /// SHA-256 digest.
+    Sha256,
+}
+
+/// A value with a canonical encoding to hash.
+pub trait CanonicalEncode {
+    /// The canonical CBOR encoding of the value.
+    fn canonical_bytes(&self) -> Vec<u8>;
+}
+
+impl CanonicalEncode for PackValue {
+    fn canonical_bytes(&self) -> Vec<u8> {
+        CborEncoderStable::new().encode(self)
+    }
+}
+
+impl CanonicalEncode for serde_json::Value {
+    fn canonical_bytes(&self) -> Vec<u8> {
+        CborEncoderStable::new().encode_json(self)
+    }
+}
+
+/// 64-bit FNV-1a hash of the canonical encoding of `value`.
+///
+/// ```
+/// use json_joy_json_pack::hash::hash64;
+/// use serde_json::json;
+///
+/// assert_eq!(hash64(&json!({"a": 1, "b": [true]})), hash64(&json!({"b": [true], "a": 1})));
+/// assert_ne!(hash64(&json!({"a": 1})), hash64(&json!({"a": 2})));
+/// ```
+pub fn hash64<V: CanonicalEncode + ?Sized>(value: &V) -> u64 {
+    fnv1a64(&value.canonical_bytes())
+}
+
+/// 128-bit FNV-1a hash of the canonical encoding of `value`.
+pub fn hash128<V: CanonicalEncode + ?Sized>(value: &V) -> u128 {
+    fnv1a128(&value.canonical_bytes())
+}
+
+/// SHA-256 digest of the canonical encoding of `value`.
+pub fn hash_sha256<V: CanonicalEncode + ?Sized>(value: &V) -> [u8; 32] {
+    sha256(&value.canonical_bytes())
+}
+
+/// Hash bytes for `algorithm`. FNV hashes are big-endian.
+pub fn hash<V: CanonicalEncode + ?Sized>(value: &V, algorithm: HashAlgorithm) -> Vec<u8> {
+    let bytes = value.canonical_bytes();
+    match algorithm {
+        HashAlgorithm::Fnv64 => fnv1a64(&bytes).to_be_bytes().to_vec(),
+        HashAlgorithm::Fnv128 => fnv1a128(&bytes).to_be_bytes().to_vec(),
+        HashAlgorithm::Sha256 => sha256(&bytes).to_vec(),
+    }
+}
+
+/// 64-bit FNV-1a of `data`.
+pub fn fnv1a64(data: &[u8]) -> u64 {
+    data.iter().fold(FNV64_OFFSET, |h, &b| {
+        (h ^ b as u64).wrapping_mul(FNV64_PRIME)
+    })
+}
+
+/// 128-bit FNV-1a of `data`.
+pub fn fnv1a128(data: &[u8]) -> u128 {
+    data.iter().fold(FNV128_OFFSET, |h, &b| {
+        (h ^ b as u128).wrapping_mul(FNV128_PRIME)
+    })
+}
+
+/// SHA-256 (FIPS 180-4).
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    for block in padded(data).chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for (&k, &word) in K.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+/// SHA-1 (FIPS 180-4), for the WebSocket accept key.
+pub(crate) fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    for block in padded(data).chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+/// `data` padded to whole 64-byte blocks, as SHA-1 and SHA-256 take it: a
+/// `1` bit, zeros, then the bit length as a big-endian `u64`.
+fn padded(data: &[u8]) -> Vec<u8> {
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    message
+}
@@ -0,0 +1,548 @@
+//! Hex dumps annotated with the structure of the encoded data.
+//!
+//! Not an upstream port. [`hexdump_annotated`] extends
+//! [`print_octets`] for interop debugging: it walks CBOR, MessagePack or
+//! RESP bytes and prints each item on its own line, with its offset, its
+//! bytes and what they encode, indented by nesting depth:
+//!
+//! ```text
+//! 0000  a1                          map(1)
+//! 0001  61 61                         text(1) "a"
+//! 0003  82                            array(2)
+//! 0004  01                              uint 1
+//! 0005  f9 41 00                        float16 2.5
+//! ```
+//!
+//! Items longer than eight bytes show their first eight followed by `..`.
+//! When the bytes stop making sense a `!!` line says why, and the rest of
+//! the input is dumped raw, eight bytes per line with their ASCII.
+
+use std::fmt::Write as _;
+
+use json_joy_buffers::{decode_f16, print_octets};
+
+/// Bytes shown per line.
+const LINE_OCTETS: usize = 8;
+/// Width of the hex column: eight bytes plus a `..` marker.
+const HEX_WIDTH: usize = LINE_OCTETS * 3 + 2;
+/// Characters of a string payload quoted in an annotation.
+const PREVIEW_CHARS: usize = 32;
+/// Nesting depth at which the dump gives up.
+const MAX_DEPTH: usize = 256;
+
+/// Encoding understood by [`hexdump_annotated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexdumpFormat {
+    /// CBOR (RFC 8949): major types, arguments and simple values.
+    Cbor,
+    /// MessagePack: type headers and their payloads.
+    MsgPack,
+    /// RESP2/RESP3: one line per CRLF-terminated frame line.
+    Resp,
+}
+
+/// Dumps `bytes` as hex, annotating each item of `format`.
+///
+/// Concatenated top-level values (CBOR sequences, pipelined RESP commands)
+/// are dumped one after another. Malformed or truncated input never
+/// panics: the dump stops annotating at the first problem and shows the
+/// remaining bytes raw.
+///
+/// # Example
+///
+/// ```
+/// use json_joy_json_pack::hexdump::{hexdump_annotated, HexdumpFormat};
+///
+/// let dump = hexdump_annotated(&[0x82, 0x01, 0x20], HexdumpFormat::Cbor);
+/// assert_eq!(
+///     dump,
+///     "0000  82                          array(2)\n\
+///      0001  01                            uint 1\n\
+///      0002  20                            nint -1\n"
+/// );
+/// ```
+pub fn hexdump_annotated(bytes: &[u8], format: HexdumpFormat) -> String {
+    let mut dump = Dump {
+        data: bytes,
+        out: String::new(),
+        width: offset_width(bytes.len()),
+    };
+    let mut x = 0;
+    while x < bytes.len() {
+        let res = match format {
+            HexdumpFormat::Cbor => dump.cbor(&mut x, 0),
+            HexdumpFormat::MsgPack => dump.msgpack(&mut x, 0),
+            HexdumpFormat::Resp => dump.resp(&mut x, 0),
+        };
+        if let Err(fail) = res {
+            dump.fail(fail);
+            break;
+        }
+    }
+    dump.out
+}
+
+/// Number of hex digits needed for the largest offset, at least four.
+fn offset_width(len: usize) -> usize {
+    let digits = (usize::BITS - len.leading_zeros()).div_ceil(4) as usize;
+    digits.max(4)
+}
+
+/// Where and why annotating stopped.
+struct Fail {
+    at: usize,
+    reason: String,
+}
+
+impl Fail {
+    fn new(at: usize, reason: impl Into<String>) -> Self {
+        Self {
+            at,
+            reason: reason.into(),
+        }
+    }
+}
+
+struct Dump<'a> {
+    data: &'a [u8],
+    out: String,
+    width: usize,
+}
+
+impl Dump<'_> {
+    fn line(&mut self, start: usize, end: usize, depth: usize, note: &str) {
+        let bytes = &self.data[start..end];
+        let mut hex = print_octets(&bytes[..bytes.len().min(LINE_OCTETS)], LINE_OCTETS);
+        if bytes.len() > LINE_OCTETS {
+            hex.push_str(" ..");
+        }
+        let _ = writeln!(
+            self.out,
+            "{start:0w$x}  {hex:<hw$}  {:indent$}{note}",
+            "",
+            w = self.width,
+            hw = HEX_WIDTH,
+            indent = depth * 2,
+        );
+    }
+
+    fn fail(&mut self, fail: Fail) {
+        let _ = writeln!(
+            self.out,
+            "{:0w$x}  !! {}",
+            fail.at,
+            fail.reason,
+            w = self.width
+        );
+        for (i, chunk) in self.data[fail.at..].chunks(LINE_OCTETS).enumerate() {
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| match b {
+                    0x20..=0x7e => b as char,
+                    _ => '.',
+                })
+                .collect();
+            let _ = writeln!(
+                self.out,
+                "{:0w$x}  {:<hw$}  |{ascii}|",
+                fail.at + i * LINE_OCTETS,
+                print_octets(chunk, LINE_OCTETS),
+                w = self.width,
+                hw = HEX_WIDTH,
+            );
+        }
+    }
+
+    /// Returns `n` bytes at `x`, or fails the item starting at `start`.
+    fn take(&self, start: usize, x: usize, n: u64) -> Result<&[u8], Fail> {
+        let left = self.data.len() - x;
+        match usize::try_from(n) {
+            Ok(n) if n <= left => Ok(&self.data[x..x + n]),
+            _ => Err(Fail::new(
+                start,
+                format!("truncated: needs {n} more bytes, {left} left"),
+            )),
+        }
+    }
+
+    fn uint_be(&self, start: usize, x: usize, n: usize) -> Result<u64, Fail> {
+        let bytes = self.take(start, x, n as u64)?;
+        Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn check_depth(start: usize, depth: usize) -> Result<(), Fail> {
+        if depth > MAX_DEPTH {
+            return Err(Fail::new(start, format!("nesting deeper than {MAX_DEPTH}")));
+        }
+        Ok(())
+    }
+
+    // ---------------------------------------------------------------- CBOR
+
+    fn cbor(&mut self, x: &mut usize, depth: usize) -> Result<(), Fail> {
+        let start = *x;
+        Self::check_depth(start, depth)?;
+        let b = self.data[start];
+        let major = b >> 5;
+        let info = b & 0x1f;
+        let arg = match info {
+            0..=23 => Some(info as u64),
+            24..=27 => Some(self.uint_be(start, start + 1, 1 << (info - 24))?),
+            31 => None,
+            _ => {
+                return Err(Fail::new(
+                    start,
+                    format!("reserved additional info {info} (initial byte 0x{b:02x})"),
+                ))
+            }
+        };
+        *x = start
+            + 1
+            + if (24..=27).contains(&info) {
+                1 << (info - 24)
+            } else {
+                0
+            };
+        match (major, arg) {
+            (0, Some(n)) => self.line(start, *x, depth, &format!("uint {n}")),
+            (1, Some(n)) => self.line(start, *x, depth, &format!("nint {}", -1 - n as i128)),
+            (2 | 3, Some(n)) => {
+                let payload = self.take(start, *x, n)?;
+                let note = if major == 2 {
+                    format!("bytes({n})")
+                } else {
+                    format!("text({n}) {}", preview(payload))
+                };
+                *x += payload.len();
+                self.line(start, *x, depth, &note);
+            }
+            (4, Some(n)) => {
+                self.line(start, *x, depth, &format!("array({n})"));
+                for _ in 0..n {
+                    self.cbor_item(x, depth + 1)?;
+                }
+            }
+            (5, Some(n)) => {
+                self.line(start, *x, depth, &format!("map({n})"));
+                for _ in 0..n {
+                    self.cbor_item(x, depth + 1)?;
+                    self.cbor_item(x, depth + 1)?;
+                }
+            }
+            (6, Some(n)) => {
+                self.line(start, *x, depth, &format!("tag({n})"));
+                self.cbor_item(x, depth + 1)?;
+            }
+            (2..=5, None) => {
+                let kind = ["bytes", "text", "array", "map"][major as usize - 2];
+                self.line(start, *x, depth, &format!("{kind}(_)"));
+                loop {
+                    if *x >= self.data.len() {
+                        return Err(Fail::new(*x, "truncated: missing break"));
+                    }
+                    if self.data[*x] == 0xff {
+                        *x += 1;
+                        self.line(*x - 1, *x, depth, "break");
+                        break;
+                    }
+                    self.cbor(x, depth + 1)?;
+                }
+            }
+            (7, arg) => self.cbor_simple(start, *x, depth, info, arg)?,
+            _ => {
+                return Err(Fail::new(
+                    start,
+                    format!("indefinite length on major type {major}"),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn cbor_item(&mut self, x: &mut usize, depth: usize) -> Result<(), Fail> {
+        if *x >= self.data.len() {
+            return Err(Fail::new(*x, "truncated: missing item"));
+        }
+        if self.data[*x] == 0xff {
+            return Err(Fail::new(*x, "unexpected break"));
+        }
+        self.cbor(x, depth)
+    }
+
+    fn cbor_simple(
+        &mut self,
+        start: usize,
+        end: usize,
+        depth: usize,
+        info: u8,
+        arg: Option<u64>,
+    ) -> Result<(), Fail> {
+        let note = match (info, arg) {
+            (20, _) => "false".to_string(),
+            (21, _) => "true".to_string(),
+            (22, _) => "null".to_string(),
+            (23, _) => "undefined".to_string(),
+            (24, Some(v)) => format!("simple({v})"),
+            (25, Some(v)) => format!("float16 {}", decode_f16(v as u16)),
+            (26, Some(v)) => format!("float32 {}", f32::from_bits(v as u32)),
+            (27, Some(v)) => format!("float64 {}", f64::from_bits(v)),
+            (31, _) => return Err(Fail::new(start, "unexpected break")),
+            _ => format!("simple({info})"),
+        };
+        self.line(start, end, depth, &note);
+        Ok(())
+    }
+
+    // --------------------------------------------------------- MessagePack
+
+    fn msgpack(&mut self, x: &mut usize, depth: usize) -> Result<(), Fail> {
+        let start = *x;
+        Self::check_depth(start, depth)?;
+        let b = self.data[start];
+        *x += 1;
+        match b {
+            0x00..=0x7f => self.line(start, *x, depth, &format!("positive fixint {b}")),
+            0x80..=0x8f => self.msgpack_map(start, x, depth, "fixmap", (b & 0x0f) as u64)?,
+            0x90..=0x9f => self.msgpack_array(start, x, depth, "fixarray", (b & 0x0f) as u64)?,
+            0xa0..=0xbf => self.msgpack_str(start, x, depth, "fixstr", (b & 0x1f) as u64)?,
+            0xc0 => self.line(start, *x, depth, "nil"),
+            0xc1 => return Err(Fail::new(start, "never-used type byte 0xc1")),
+            0xc2 => self.line(start, *x, depth, "false"),
+            0xc3 => self.line(start, *x, depth, "true"),
+            0xc4..=0xc6 => {
+                let n = self.msgpack_len(start, x, 1 << (b - 0xc4))?;
+                let payload = self.take(start, *x, n)?;
+                *x += payload.len();
+                let bits = 8 << (b - 0xc4);
+                self.line(start, *x, depth, &format!("bin{bits}({n})"));
+            }
+            0xc7..=0xc9 => {
+                let n = self.msgpack_len(start, x, 1 << (b - 0xc7))?;
+                let bits = 8 << (b - 0xc7);
+                self.msgpack_ext(start, x, depth, &format!("ext{bits}"), n)?;
+            }
+            0xca => {
+                let v = self.uint_be(start, *x, 4)?;
+                *x += 4;
+                self.line(
+                    start,
+                    *x,
+                    depth,
+                    &format!("float32 {}", f32::from_bits(v as u32)),
+                );
+            }
+            0xcb => {
+                let v = self.uint_be(start, *x, 8)?;
+                *x += 8;
+                self.line(start, *x, depth, &format!("float64 {}", f64::from_bits(v)));
+            }
+            0xcc..=0xcf => {
+                let n = 1 << (b - 0xcc);
+                let v = self.uint_be(start, *x, n)?;
+                *x += n;
+                self.line(start, *x, depth, &format!("uint{} {v}", n * 8));
+            }
+            0xd0..=0xd3 => {
+                let n = 1 << (b - 0xd0);
+                let v = self.uint_be(start, *x, n)?;
+                *x += n;
+                // Sign-extend from the encoded width.
+                let shift = 64 - n * 8;
+                let v = ((v << shift) as i64) >> shift;
+                self.line(start, *x, depth, &format!("int{} {v}", n * 8));
+            }
+            0xd4..=0xd8 => {
+                let n = 1u64 << (b - 0xd4);
+                self.msgpack_ext(start, x, depth, &format!("fixext{n}"), n)?;
+            }
+            0xd9..=0xdb => {
+                let n = self.msgpack_len(start, x, 1 << (b - 0xd9))?;
+                let bits = 8 << (b - 0xd9);
+                self.msgpack_str(start, x, depth, &format!("str{bits}"), n)?;
+            }
+            0xdc | 0xdd => {
+                let n = self.msgpack_len(start, x, 2 << (b - 0xdc))?;
+                let name = if b == 0xdc { "array16" } else { "array32" };
+                self.msgpack_array(start, x, depth, name, n)?;
+            }
+            0xde | 0xdf => {
+                let n = self.msgpack_len(start, x, 2 << (b - 0xde))?;
+                let name = if b == 0xde { "map16" } else { "map32" };
+                self.msgpack_map(start, x, depth, name, n)?;
+            }
+            0xe0..=0xff => {
+                self.line(start, *x, depth, &format!("negative fixint {}", b as i8));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a big-endian length of `n` bytes after the type byte.
+    fn msgpack_len(&self, start: usize, x: &mut usize, n: usize) -> Result<u64, Fail> {
+        let len = self.uint_be(start, *x, n)?;
+        *x += n;
+        Ok(len)
+    }
+
+    fn msgpack_str(
+        &mut self,
+        start: usize,
+        x: &mut usize,
+        depth: usize,
+        name: &str,
+        n: u64,
+    ) -> Result<(), Fail> {
+        let payload = self.take(start, *x, n)?;
+        let note = format!("{name}({n}) {}", preview(payload));
+        *x += payload.len();
+        self.line(start, *x, depth, &note);
+        Ok(())
+    }
+
+    fn msgpack_ext(
+        &mut self,
+        start: usize,
+        x: &mut usize,
+        depth: usize,
+        name: &str,
+        n: u64,
+    ) -> Result<(), Fail> {
+        let typ = self.take(start, *x, 1)?[0] as i8;
+        let payload = self.take(start, *x + 1, n)?;
+        *x += 1 + payload.len();
+        self.line(start, *x, depth, &format!("{name}({n}) type {typ}"));
+        Ok(())
+    }
+
+    fn msgpack_array(
+        &mut self,
+        start: usize,
+        x: &mut usize,
+        depth: usize,
+        name: &str,
+        n: u64,
+    ) -> Result<(), Fail> {
+        self.line(start, *x, depth, &format!("{name}({n})"));
+        for _ in 0..n {
+            self.msgpack_item(x, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn msgpack_map(
+        &mut self,
+        start: usize,
+        x: &mut usize,
+        depth: usize,
+        name: &str,
+        n: u64,
+    ) -> Result<(), Fail> {
+        self.line(start, *x, depth, &format!("{name}({n})"));
+        for _ in 0..n {
+            self.msgpack_item(x, depth + 1)?;
+            self.msgpack_item(x, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn msgpack_item(&mut self, x: &mut usize, depth: usize) -> Result<(), Fail> {
+        if *x >= self.data.len() {
+            return Err(Fail::new(*x, "truncated: missing item"));
+        }
+        self.msgpack(x, depth)
+    }
+
+    // ---------------------------------------------------------------- RESP
+
+    fn resp(&mut self, x: &mut usize, depth: usize) -> Result<(), Fail> {
+        let start = *x;
+        Self::check_depth(start, depth)?;
+        let typ = self.data[start];
+        if !b"+-:_#,($!=*~>%|".contains(&typ) {
+            return Err(Fail::new(
+                start,
+                format!("unknown RESP type byte 0x{typ:02x}"),
+            ));
+        }
+        let Some(cr) = self.data[start..].windows(2).position(|w| w == b"\r\n") else {
+            return Err(Fail::new(start, "truncated: missing CRLF"));
+        };
+        let line = String::from_utf8_lossy(&self.data[start + 1..start + cr]).into_owned();
+        *x = start + cr + 2;
+        let note = match typ {
+            b'+' => format!("simple string {line:?}"),
+            b'-' => format!("simple error {line:?}"),
+            b':' => format!("integer {line}"),
+            b'_' => "null".to_string(),
+            b'#' => format!("boolean {line}"),
+            b',' => format!("double {line}"),
+            b'(' => format!("big number {line}"),
+            b'$' | b'!' | b'=' => {
+                let kind = match typ {
+                    b'$' => "bulk string",
+                    b'!' => "bulk error",
+                    _ => "verbatim string",
+                };
+                let len = resp_len(start, &line)?;
+                let Ok(n) = u64::try_from(len) else {
+                    self.line(start, *x, depth, &format!("null {kind}"));
+                    return Ok(());
+                };
+                self.line(start, *x, depth, &format!("{kind}({n})"));
+                let data_start = *x;
+                let payload = self.take(data_start, data_start, n)?;
+                let note = preview(payload);
+                *x += payload.len();
+                if self.data.get(*x..*x + 2) != Some(b"\r\n") {
+                    return Err(Fail::new(*x, "missing CRLF after bulk data"));
+                }
+                *x += 2;
+                self.line(data_start, *x, depth + 1, &note);
+                return Ok(());
+            }
+            b'*' | b'~' | b'>' | b'%' | b'|' => {
+                let (kind, per_entry) = match typ {
+                    b'*' => ("array", 1),
+                    b'~' => ("set", 1),
+                    b'>' => ("push", 1),
+                    b'%' => ("map", 2),
+                    _ => ("attribute", 2),
+                };
+                let len = resp_len(start, &line)?;
+                let Ok(n) = u64::try_from(len) else {
+                    self.line(start, *x, depth, &format!("null {kind}"));
+                    return Ok(());
+                };
+                self.line(start, *x, depth, &format!("{kind}({n})"));
+                for _ in 0..n * per_entry {
+                    if *x >= self.data.len() {
+                        return Err(Fail::new(*x, "truncated: missing item"));
+                    }
+                    self.resp(x, depth + 1)?;
+                }
+                return Ok(());
+            }
+            _ => unreachable!(),
+        };
+        self.line(start, *x, depth, &note);
+        Ok(())
+    }
+}
+
+fn resp_len(start: usize, line: &str) -> Result<i64, Fail> {
+    match line.parse::<i64>() {
+        Ok(n) if n >= -1 => Ok(n),
+        _ => Err(Fail::new(start, format!("invalid length {line:?}"))),
+    }
+}
+
+/// Quotes the start of a string payload, or notes that it is not UTF-8.
+fn preview(payload: &[u8]) -> String {
+    match std::str::from_utf8(payload) {
+        Ok(s) => match s.char_indices().nth(PREVIEW_CHARS) {
+            Some((i, _)) => format!("{:?}..", &s[..i]),
+            None => format!("{s:?}"),
+        },
+        Err(_) => "<invalid UTF-8>".to_string(),
+    }
+}
@@ -0,0 +1,27 @@
+//! HTTP framing error type.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HttpFrameError {
+    #[error("invalid multipart boundary")]
+    InvalidBoundary,
+    #[error("SSE {0} field must not contain CR, LF or NUL")]
+    InvalidSseField(&'static str),
+    #[error("header {0:?} contains invalid characters")]
+    InvalidHeaderValue(String),
+    #[error("invalid multipart boundary line at byte {0}")]
+    InvalidBoundaryLine(usize),
+    #[error("invalid multipart part header at byte {0}")]
+    InvalidPartHeader(usize),
+}
+
+impl HttpFrameError {
+    /// Byte offset in the decoded stream, for decoding errors.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::InvalidBoundaryLine(x) | Self::InvalidPartHeader(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! HTTP payload framing: Server-Sent Events and `multipart/*` bodies.
+//!
+//! Not an upstream port. These helpers let streaming payloads (NDJSON lines,
+//! patch streams, byte ranges) be pushed over plain HTTP responses without
+//! pulling in an HTTP stack; only the body framing is handled here.
+
+mod error;
+mod multipart;
+mod sse;
+
+pub use error::HttpFrameError;
+pub use multipart::{ContentRange, MultipartDecoder, MultipartEncoder, MultipartPart};
+pub use sse::{SseDecoder, SseEncoder, SseEvent};
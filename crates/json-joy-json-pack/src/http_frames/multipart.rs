@@ -0,0 +1,329 @@
+//! `multipart/*` body framing (RFC 2046 section 5.1), with helpers for
+//! `multipart/byteranges` (RFC 9110 section 14.6).
+
+use core::fmt;
+
+use json_joy_buffers::Writer;
+
+use super::error::HttpFrameError;
+
+/// Checks a boundary against the RFC 2046 `boundary` grammar: 1 to 70
+/// `bchars`, not ending in a space.
+fn validate_boundary(boundary: &str) -> Result<(), HttpFrameError> {
+    let valid_char = |b: u8| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b);
+    if boundary.is_empty()
+        || boundary.len() > 70
+        || boundary.ends_with(' ')
+        || !boundary.bytes().all(valid_char)
+    {
+        return Err(HttpFrameError::InvalidBoundary);
+    }
+    Ok(())
+}
+
+/// A `Content-Range` value such as `bytes 0-499/1234`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    pub start: u64,
+    /// Inclusive end offset.
+    pub end: u64,
+    /// Complete length, `None` when unknown (`*`).
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parses a satisfied `bytes` range; unsatisfied (`bytes */1234`) and
+    /// inverted ranges yield `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let rest = value.trim().strip_prefix("bytes ")?;
+        let (range, total) = rest.split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+        let number = |s: &str| -> Option<u64> {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            s.parse().ok()
+        };
+        let start = number(start)?;
+        let end = number(end)?;
+        let total = match total {
+            "*" => None,
+            total => Some(number(total)?),
+        };
+        if end < start || total.is_some_and(|t| end >= t) {
+            return None;
+        }
+        Some(Self { start, end, total })
+    }
+}
+
+impl fmt::Display for ContentRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bytes {}-{}/", self.start, self.end)?;
+        match self.total {
+            Some(total) => write!(f, "{total}"),
+            None => f.write_str("*"),
+        }
+    }
+}
+
+/// One body part: its header fields in order and its raw body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultipartPart {
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MultipartPart {
+    /// First header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn content_range(&self) -> Option<ContentRange> {
+        self.header("content-range").and_then(ContentRange::parse)
+    }
+}
+
+/// Writes a multipart body one part at a time, so parts can be flushed to
+/// the response as they are produced.
+pub struct MultipartEncoder {
+    pub writer: Writer,
+    boundary: String,
+    started: bool,
+}
+
+impl MultipartEncoder {
+    pub fn new(boundary: impl Into<String>) -> Result<Self, HttpFrameError> {
+        let boundary = boundary.into();
+        validate_boundary(&boundary)?;
+        Ok(Self {
+            writer: Writer::new(),
+            boundary,
+            started: false,
+        })
+    }
+
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// `Content-Type` header value for the body, e.g.
+    /// `multipart/byteranges; boundary=abc`. The boundary is quoted when it
+    /// contains characters outside the HTTP token set.
+    pub fn content_type(&self, subtype: &str) -> String {
+        let is_token = self
+            .boundary
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"'+_-.".contains(&b));
+        if is_token {
+            format!("multipart/{subtype}; boundary={}", self.boundary)
+        } else {
+            format!("multipart/{subtype}; boundary=\"{}\"", self.boundary)
+        }
+    }
+
+    /// Encodes the delimiter, headers and body of the next part.
+    pub fn encode_part(
+        &mut self,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Vec<u8>, HttpFrameError> {
+        for (name, value) in headers {
+            let invalid_name =
+                name.is_empty() || !name.bytes().all(|b| b.is_ascii_graphic() && b != b':');
+            if invalid_name || value.contains(['\r', '\n']) {
+                return Err(HttpFrameError::InvalidHeaderValue(name.to_string()));
+            }
+        }
+        self.write_delimiter();
+        self.writer.ascii("\r\n");
+        for (name, value) in headers {
+            self.writer.ascii(name);
+            self.writer.ascii(": ");
+            self.writer.utf8(value);
+            self.writer.ascii("\r\n");
+        }
+        self.writer.ascii("\r\n");
+        self.writer.buf(body);
+        Ok(self.writer.flush())
+    }
+
+    /// Encodes one `multipart/byteranges` part.
+    pub fn encode_byterange(
+        &mut self,
+        content_type: &str,
+        range: ContentRange,
+        body: &[u8],
+    ) -> Result<Vec<u8>, HttpFrameError> {
+        let range = range.to_string();
+        self.encode_part(
+            &[("Content-Type", content_type), ("Content-Range", &range)],
+            body,
+        )
+    }
+
+    /// Encodes the close delimiter that ends the body.
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.write_delimiter();
+        self.writer.ascii("--\r\n");
+        self.started = false;
+        self.writer.flush()
+    }
+
+    fn write_delimiter(&mut self) {
+        if self.started {
+            self.writer.ascii("\r\n");
+        }
+        self.started = true;
+        self.writer.ascii("--");
+        self.writer.ascii(&self.boundary);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Boundary,
+    Part,
+    Done,
+}
+
+/// Incremental multipart body parser.
+///
+/// Feed chunks with [`push`](MultipartDecoder::push) and drain parts with
+/// [`read_part`](MultipartDecoder::read_part), which returns `Ok(None)` until
+/// a whole part is buffered. The preamble and epilogue are discarded.
+#[derive(Debug)]
+pub struct MultipartDecoder {
+    /// `--` followed by the boundary.
+    dash_boundary: Vec<u8>,
+    buf: Vec<u8>,
+    /// Stream offset of `buf[0]`, for error positions.
+    offset: usize,
+    state: State,
+}
+
+impl MultipartDecoder {
+    pub fn new(boundary: &str) -> Result<Self, HttpFrameError> {
+        validate_boundary(boundary)?;
+        Ok(Self {
+            dash_boundary: [b"--", boundary.as_bytes()].concat(),
+            buf: Vec::new(),
+            offset: 0,
+            state: State::Preamble,
+        })
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        if self.state != State::Done {
+            self.buf.extend_from_slice(data);
+        }
+    }
+
+    /// Whether the close delimiter has been read.
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    pub fn read_part(&mut self) -> Result<Option<MultipartPart>, HttpFrameError> {
+        loop {
+            match self.state {
+                State::Preamble => {
+                    let at = if self.buf.starts_with(&self.dash_boundary) {
+                        Some(0)
+                    } else {
+                        self.find_delimiter(0).map(|i| i + 2)
+                    };
+                    let Some(at) = at else {
+                        return Ok(None);
+                    };
+                    self.consume(at + self.dash_boundary.len());
+                    self.state = State::Boundary;
+                }
+                State::Boundary => {
+                    if self.buf.starts_with(b"--") {
+                        self.offset += self.buf.len();
+                        self.buf = Vec::new();
+                        self.state = State::Done;
+                        continue;
+                    }
+                    let Some(eol) = find(&self.buf, b"\r\n") else {
+                        if self.buf.len() > 1 && !is_padding(&self.buf) {
+                            return Err(HttpFrameError::InvalidBoundaryLine(self.offset));
+                        }
+                        return Ok(None);
+                    };
+                    if !is_padding(&self.buf[..eol]) {
+                        return Err(HttpFrameError::InvalidBoundaryLine(self.offset));
+                    }
+                    self.consume(eol + 2);
+                    self.state = State::Part;
+                }
+                State::Part => {
+                    let header_end = if self.buf.starts_with(b"\r\n") {
+                        Some(0)
+                    } else {
+                        find(&self.buf, b"\r\n\r\n").map(|i| i + 2)
+                    };
+                    let Some(header_end) = header_end else {
+                        return Ok(None);
+                    };
+                    let Some(body_end) = self.find_delimiter(header_end + 2) else {
+                        return Ok(None);
+                    };
+                    let headers = self.parse_headers(header_end)?;
+                    let body = self.buf[header_end + 2..body_end].to_vec();
+                    self.consume(body_end + 2 + self.dash_boundary.len());
+                    self.state = State::Boundary;
+                    return Ok(Some(MultipartPart { headers, body }));
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+
+    /// Position of the next CRLF + dash-boundary at or after `from`.
+    fn find_delimiter(&self, from: usize) -> Option<usize> {
+        let buf = self.buf.get(from..)?;
+        buf.windows(self.dash_boundary.len() + 2)
+            .position(|w| w.starts_with(b"\r\n") && w[2..] == self.dash_boundary[..])
+            .map(|i| i + from)
+    }
+
+    fn parse_headers(&self, end: usize) -> Result<Vec<(String, String)>, HttpFrameError> {
+        let mut headers = Vec::new();
+        let mut x = 0;
+        while x < end {
+            let len = find(&self.buf[x..end], b"\r\n").unwrap_or(end - x);
+            let line = &self.buf[x..x + len];
+            let invalid = || HttpFrameError::InvalidPartHeader(self.offset + x);
+            let colon = line.iter().position(|&b| b == b':').ok_or_else(invalid)?;
+            let name = core::str::from_utf8(&line[..colon]).map_err(|_| invalid())?;
+            let value = core::str::from_utf8(&line[colon + 1..]).map_err(|_| invalid())?;
+            if name.is_empty() || name.contains(|c: char| c.is_ascii_whitespace()) {
+                return Err(invalid());
+            }
+            headers.push((name.to_string(), value.trim().to_string()));
+            x += len + 2;
+        }
+        Ok(headers)
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.buf.drain(..n);
+        self.offset += n;
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// RFC 2046 transport padding (linear whitespace) after a delimiter.
+fn is_padding(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b == b' ' || b == b'\t')
+}
@@ -0,0 +1,231 @@
+//! Server-Sent Events (`text/event-stream`) encoding and decoding.
+//!
+//! Reference: WHATWG HTML, "Server-sent events", section 9.2.6 (parsing an
+//! event stream).
+
+use json_joy_buffers::Writer;
+
+use super::error::HttpFrameError;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// One dispatched event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `id` field of this event, if it had one.
+    pub id: Option<String>,
+    /// Event type; `None` means the default `message` type.
+    pub event: Option<String>,
+    /// Data lines joined with `\n`.
+    pub data: String,
+    /// Reconnection time in milliseconds, if this event set one.
+    pub retry: Option<u64>,
+}
+
+impl SseEvent {
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+}
+
+pub struct SseEncoder {
+    pub writer: Writer,
+}
+
+impl Default for SseEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SseEncoder {
+    pub fn new() -> Self {
+        Self {
+            writer: Writer::new(),
+        }
+    }
+
+    /// Encodes an event, splitting multi-line data into several `data:`
+    /// fields.
+    pub fn encode(&mut self, event: &SseEvent) -> Result<Vec<u8>, HttpFrameError> {
+        if let Some(id) = &event.id {
+            if id.contains(['\r', '\n', '\0']) {
+                return Err(HttpFrameError::InvalidSseField("id"));
+            }
+            self.write_field("id", id);
+        }
+        if let Some(name) = &event.event {
+            if name.contains(['\r', '\n']) {
+                return Err(HttpFrameError::InvalidSseField("event"));
+            }
+            self.write_field("event", name);
+        }
+        if let Some(retry) = event.retry {
+            self.write_field("retry", &retry.to_string());
+        }
+        for line in lines(&event.data) {
+            self.write_field("data", line);
+        }
+        self.writer.u8(b'\n');
+        Ok(self.writer.flush())
+    }
+
+    /// Encodes a comment, which clients ignore; useful as a keep-alive.
+    pub fn encode_comment(&mut self, text: &str) -> Vec<u8> {
+        for line in lines(text) {
+            self.writer.u8(b':');
+            if !line.is_empty() {
+                self.writer.u8(b' ');
+                self.writer.utf8(line);
+            }
+            self.writer.u8(b'\n');
+        }
+        self.writer.flush()
+    }
+
+    fn write_field(&mut self, name: &str, value: &str) {
+        self.writer.ascii(name);
+        self.writer.u8(b':');
+        if !value.is_empty() {
+            self.writer.u8(b' ');
+            self.writer.utf8(value);
+        }
+        self.writer.u8(b'\n');
+    }
+}
+
+/// Splits on CRLF, CR or LF, like the event-stream parser does.
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    core::iter::from_fn(move || {
+        let s = rest?;
+        match s.find(['\r', '\n']) {
+            Some(i) => {
+                let skip = if s[i..].starts_with("\r\n") { 2 } else { 1 };
+                rest = Some(&s[i + skip..]);
+                Some(&s[..i])
+            }
+            None => {
+                rest = None;
+                Some(s)
+            }
+        }
+    })
+}
+
+/// Incremental event-stream parser.
+///
+/// Feed chunks with [`push`](SseDecoder::push) and drain events with
+/// [`read_event`](SseDecoder::read_event). Invalid UTF-8 is replaced with
+/// U+FFFD, a leading BOM is skipped, and an incomplete trailing event is kept
+/// until more data arrives.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+    started: bool,
+    /// The previous line ended with CR; a leading LF belongs to it.
+    skip_lf: bool,
+    last_event_id: String,
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+    retry: Option<u64>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// The last event ID seen in the stream, sent as `Last-Event-ID` when
+    /// reconnecting. It persists across events until another `id` field.
+    pub fn last_event_id(&self) -> &str {
+        &self.last_event_id
+    }
+
+    /// Returns the next complete event, or `None` when more data is needed.
+    pub fn read_event(&mut self) -> Option<SseEvent> {
+        if !self.started {
+            if self.buf.len() < BOM.len() && BOM.starts_with(&self.buf) {
+                return None;
+            }
+            if self.buf.starts_with(BOM) {
+                self.buf.drain(..BOM.len());
+            }
+            self.started = true;
+        }
+        let mut x = 0;
+        let mut dispatched = None;
+        while dispatched.is_none() {
+            if self.skip_lf && x < self.buf.len() {
+                if self.buf[x] == b'\n' {
+                    x += 1;
+                }
+                self.skip_lf = false;
+            }
+            let Some(len) = self.buf[x..].iter().position(|&b| b == b'\r' || b == b'\n') else {
+                break;
+            };
+            self.skip_lf = self.buf[x + len] == b'\r';
+            let line = String::from_utf8_lossy(&self.buf[x..x + len]).into_owned();
+            x += len + 1;
+            dispatched = self.process_line(&line);
+        }
+        self.buf.drain(..x);
+        dispatched
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        let (field, value) = match line.find(':') {
+            Some(0) => return None,
+            Some(i) => {
+                let value = &line[i + 1..];
+                (&line[..i], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => {
+                self.last_event_id = value.to_string();
+                self.id = Some(value.to_string());
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let id = self.id.take();
+        let event = self.event.take();
+        let retry = self.retry.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut data = core::mem::take(&mut self.data);
+        data.pop();
+        Some(SseEvent {
+            id,
+            event,
+            data,
+            retry,
+        })
+    }
+}
@@ -10,7 +10,11 @@ pub mod Type {
     pub const UINT: u8 = 0b0010;
     pub const NINT: u8 = 0b0011;
     pub const FLOT: u8 = 0b0100;
+    pub const DECI: u8 = 0b0101;
+    pub const TIME: u8 = 0b0110;
+    pub const SYMB: u8 = 0b0111;
     pub const STRI: u8 = 0b1000;
+    pub const CLOB: u8 = 0b1001;
     pub const BINA: u8 = 0b1010;
     pub const LIST: u8 = 0b1011;
     pub const STRU: u8 = 0b1101;
@@ -24,7 +28,11 @@ pub mod TypeOverlay {
     pub const UINT: u8 = super::Type::UINT << 4;
     pub const NINT: u8 = super::Type::NINT << 4;
     pub const FLOT: u8 = super::Type::FLOT << 4;
+    pub const DECI: u8 = super::Type::DECI << 4;
+    pub const TIME: u8 = super::Type::TIME << 4;
+    pub const SYMB: u8 = super::Type::SYMB << 4;
     pub const STRI: u8 = super::Type::STRI << 4;
+    pub const CLOB: u8 = super::Type::CLOB << 4;
     pub const BINA: u8 = super::Type::BINA << 4;
     pub const LIST: u8 = super::Type::LIST << 4;
     pub const STRU: u8 = super::Type::STRU << 4;
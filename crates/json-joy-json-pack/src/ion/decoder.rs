@@ -4,6 +4,7 @@
 
 use super::constants::Type;
 use super::decoder_base::IonDecoderBase;
+use super::value::IonValue;
 use crate::PackValue;
 
 pub use super::decoder_base::IonDecodeError;
@@ -29,7 +30,7 @@ impl IonDecoder {
     pub fn decode(&mut self, data: &[u8]) -> Result<PackValue, IonDecodeError> {
         self.base.reset(data);
         self.base.validate_bvm()?;
-        self.read_symbol_table()?;
+        self.read_symbol_table(false)?;
         self.base.val()
     }
 
@@ -37,7 +38,22 @@ impl IonDecoder {
         self.base.val()
     }
 
-    fn read_symbol_table(&mut self) -> Result<(), IonDecodeError> {
+    /// Like [`decode`](Self::decode), but keeps decimals, timestamps, symbols
+    /// and annotations.
+    pub fn decode_ion(&mut self, data: &[u8]) -> Result<IonValue, IonDecodeError> {
+        self.base.reset(data);
+        self.base.validate_bvm()?;
+        self.read_symbol_table(true)?;
+        self.base.val_ion()
+    }
+
+    pub fn read_ion(&mut self) -> Result<IonValue, IonDecodeError> {
+        self.base.val_ion()
+    }
+
+    /// Reads a leading local symbol table. Upstream treats any annotated
+    /// value as one; with `typed`, other annotated values are left in place.
+    fn read_symbol_table(&mut self, typed: bool) -> Result<(), IonDecodeError> {
         if !self.base.has_remaining() {
             return Ok(());
         }
@@ -46,7 +62,22 @@ impl IonDecoder {
             return Ok(());
         }
 
-        let annotated = self.base.val()?;
+        let annotated = if typed {
+            let start = self.base.position();
+            match self.base.val_ion()? {
+                IonValue::Annotated { annotations, value }
+                    if annotations.first().map(String::as_str) == Some("$ion_symbol_table") =>
+                {
+                    PackValue::from(*value)
+                }
+                _ => {
+                    self.base.seek(start);
+                    return Ok(());
+                }
+            }
+        } else {
+            self.base.val()?
+        };
         let PackValue::Object(fields) = annotated else {
             return Ok(());
         };
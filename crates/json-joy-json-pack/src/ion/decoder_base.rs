@@ -4,33 +4,50 @@
 
 use super::constants::{Type, ION_BVM};
 use super::symbols::IonSymbols;
+use super::value::{IonDecimal, IonTimestamp, IonValue};
 use crate::PackValue;
 
 /// Ion decoding error.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum IonDecodeError {
-    #[error("unexpected end of input")]
-    EndOfInput,
+    #[error("unexpected end of input at offset {0}")]
+    EndOfInput(usize),
     #[error("invalid Ion Binary Version Marker")]
     InvalidBvm,
     #[error("unknown symbol ID: {0}")]
     UnknownSymbol(u32),
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
     #[error("unsupported float length: {0}")]
     UnsupportedFloatLen(u8),
     #[error("negative zero integer is illegal")]
     NegativeZero,
     #[error("invalid bool length: {0}")]
     InvalidBoolLen(u8),
-    #[error("unknown Ion type: 0x{0:01x}")]
-    UnknownType(u8),
+    #[error("unknown Ion type 0x{0:01x} at offset {1}")]
+    UnknownType(u8, usize),
     #[error("annotation wrapper must have at least 3 bytes")]
     AnnotationTooShort(u8),
     #[error("list parsing error: incorrect length")]
     ListLengthMismatch,
     #[error("struct parsing error: incorrect length")]
     StructLengthMismatch,
+    #[error("decimal parsing error: incorrect length")]
+    InvalidDecimal,
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+    #[error("integer field too large")]
+    Overflow,
+}
+
+impl IonDecodeError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::EndOfInput(x) | Self::InvalidUtf8(x) | Self::UnknownType(_, x) => Some(*x),
+            _ => None,
+        }
+    }
 }
 
 /// Base decoder shared by Ion decoder wrappers.
@@ -38,6 +55,8 @@ pub struct IonDecoderBase {
     data: Vec<u8>,
     pos: usize,
     symbols: IonSymbols,
+    /// Set by [`val_ion`](Self::val_ion), cleared by [`val`](Self::val).
+    typed: bool,
 }
 
 impl Default for IonDecoderBase {
@@ -52,6 +71,7 @@ impl IonDecoderBase {
             data: Vec::new(),
             pos: 0,
             symbols: IonSymbols::new(),
+            typed: false,
         }
     }
 
@@ -64,7 +84,7 @@ impl IonDecoderBase {
 
     pub(crate) fn validate_bvm(&mut self) -> Result<(), IonDecodeError> {
         if self.pos + 4 > self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
         }
         let marker = &self.data[self.pos..self.pos + 4];
         if marker != ION_BVM {
@@ -78,6 +98,14 @@ impl IonDecoderBase {
         self.pos < self.data.len()
     }
 
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
     pub(crate) fn peek_type_id(&self) -> Result<u8, IonDecodeError> {
         let descriptor = self.peek_byte()?;
         Ok((descriptor >> 4) & 0x0f)
@@ -88,6 +116,18 @@ impl IonDecoderBase {
     }
 
     pub fn val(&mut self) -> Result<PackValue, IonDecodeError> {
+        self.typed = false;
+        self.read_value().map(PackValue::from)
+    }
+
+    /// Reads the next value, keeping decimals, timestamps, symbols and
+    /// annotations, which [`val`](Self::val) rejects or drops as upstream does.
+    pub fn val_ion(&mut self) -> Result<IonValue, IonDecodeError> {
+        self.typed = true;
+        self.read_value()
+    }
+
+    fn read_value(&mut self) -> Result<IonValue, IonDecodeError> {
         let typedesc = self.read_byte()?;
         let type_id = (typedesc >> 4) & 0x0f;
         let length = typedesc & 0x0f;
@@ -98,18 +138,22 @@ impl IonDecoderBase {
             t if t == Type::UINT => self.read_uint(length),
             t if t == Type::NINT => self.read_nint(length),
             t if t == Type::FLOT => self.read_float(length),
+            t if t == Type::DECI && self.typed => self.read_decimal(length),
+            t if t == Type::TIME && self.typed => self.read_timestamp(length),
+            t if t == Type::SYMB && self.typed => self.read_symbol(length),
             t if t == Type::STRI => self.read_string(length),
+            t if t == Type::CLOB => self.read_clob(length),
             t if t == Type::BINA => self.read_binary(length),
             t if t == Type::LIST => self.read_list(length),
             t if t == Type::STRU => self.read_struct(length),
             t if t == Type::ANNO => self.read_annotation(length),
-            _ => Err(IonDecodeError::UnknownType(type_id)),
+            _ => Err(IonDecodeError::UnknownType(type_id, self.pos - 1)),
         }
     }
 
-    fn read_null(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_null(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
         }
         let pad_len = if length == 14 {
             self.read_vuint()? as usize
@@ -117,67 +161,68 @@ impl IonDecoderBase {
             length as usize
         };
         self.skip(pad_len)?;
-        let _ = self.val()?;
-        Ok(PackValue::Null)
+        let _ = self.read_value()?;
+        Ok(IonValue::Null)
     }
 
-    fn read_bool(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_bool(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         match length {
-            15 => Ok(PackValue::Null),
-            0 => Ok(PackValue::Bool(false)),
-            1 => Ok(PackValue::Bool(true)),
+            15 => Ok(IonValue::Null),
+            0 => Ok(IonValue::Bool(false)),
+            1 => Ok(IonValue::Bool(true)),
             _ => Err(IonDecodeError::InvalidBoolLen(length)),
         }
     }
 
-    fn read_uint(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_uint(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
         }
         if length == 0 {
-            return Ok(PackValue::UInteger(0));
+            return Ok(IonValue::Int(0));
         }
 
-        let bytes = self.read_bytes(length as usize)?;
-        let mut value: u128 = 0;
-        for b in bytes {
-            value = (value << 8) | b as u128;
-        }
-        if value <= u64::MAX as u128 {
-            Ok(PackValue::UInteger(value as u64))
-        } else {
-            Ok(PackValue::BigInt(value as i128))
-        }
+        let value = self.read_magnitude(length)?;
+        let value = i128::try_from(value).map_err(|_| IonDecodeError::Overflow)?;
+        Ok(IonValue::Int(value))
     }
 
-    fn read_nint(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_nint(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
         }
         if length == 0 {
             return Err(IonDecodeError::NegativeZero);
         }
 
-        let bytes = self.read_bytes(length as usize)?;
-        let mut magnitude: u128 = 0;
-        for b in bytes {
-            magnitude = (magnitude << 8) | b as u128;
+        let magnitude = self.read_magnitude(length)?;
+        if magnitude > i128::MIN.unsigned_abs() {
+            return Err(IonDecodeError::Overflow);
         }
-        if magnitude == (i64::MAX as u128) + 1 {
-            Ok(PackValue::Integer(i64::MIN))
-        } else if magnitude <= i64::MAX as u128 {
-            Ok(PackValue::Integer(-(magnitude as i64)))
+        Ok(IonValue::Int((magnitude as i128).wrapping_neg()))
+    }
+
+    /// Reads the big-endian magnitude of a UINT or NINT with length nibble
+    /// `length`; more than 16 bytes cannot fit an `i128`.
+    fn read_magnitude(&mut self, length: u8) -> Result<u128, IonDecodeError> {
+        let actual_length = if length == 14 {
+            self.read_vuint()? as usize
         } else {
-            Ok(PackValue::BigInt(-(magnitude as i128)))
+            length as usize
+        };
+        if actual_length > 16 {
+            return Err(IonDecodeError::Overflow);
         }
+        let bytes = self.read_bytes(actual_length)?;
+        Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u128))
     }
 
-    fn read_float(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_float(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
         }
         if length == 0 {
-            return Ok(PackValue::Float(0.0));
+            return Ok(IonValue::Float(0.0));
         }
 
         match length {
@@ -185,39 +230,112 @@ impl IonDecoderBase {
                 let bytes: [u8; 4] = self
                     .read_bytes(4)?
                     .try_into()
-                    .map_err(|_| IonDecodeError::EndOfInput)?;
-                Ok(PackValue::Float(f32::from_le_bytes(bytes) as f64))
+                    .map_err(|_| IonDecodeError::EndOfInput(self.pos))?;
+                Ok(IonValue::Float(f32::from_le_bytes(bytes) as f64))
             }
             8 => {
                 let bytes: [u8; 8] = self
                     .read_bytes(8)?
                     .try_into()
-                    .map_err(|_| IonDecodeError::EndOfInput)?;
-                Ok(PackValue::Float(f64::from_le_bytes(bytes)))
+                    .map_err(|_| IonDecodeError::EndOfInput(self.pos))?;
+                Ok(IonValue::Float(f64::from_le_bytes(bytes)))
             }
             _ => Err(IonDecodeError::UnsupportedFloatLen(length)),
         }
     }
 
-    fn read_string(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_decimal(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
+        }
+        let end_pos = self.read_end(length)?;
+        if self.pos == end_pos {
+            return Ok(IonValue::Decimal(IonDecimal::default()));
         }
 
-        let actual_length = if length == 14 {
-            self.read_vuint()? as usize
-        } else {
-            length as usize
+        let exponent = self.read_varint()?;
+        let exponent = i32::try_from(exponent).map_err(|_| IonDecodeError::Overflow)?;
+        let rest = end_pos
+            .checked_sub(self.pos)
+            .ok_or(IonDecodeError::InvalidDecimal)?;
+        let coefficient = self.read_int(rest)?;
+        Ok(IonValue::Decimal(IonDecimal::new(coefficient, exponent)))
+    }
+
+    fn read_timestamp(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
+        if length == 15 {
+            return Ok(IonValue::Null);
+        }
+        let end_pos = self.read_end(length)?;
+
+        // Negative zero is the unknown offset.
+        let negative = self.peek_byte()? & 0x40 != 0;
+        let offset = self.read_varint()?;
+        let unknown_offset = negative && offset == 0;
+        let year = self.read_vuint()?;
+        let mut timestamp = IonTimestamp {
+            year: u16::try_from(year).map_err(|_| IonDecodeError::InvalidTimestamp)?,
+            offset: if unknown_offset {
+                None
+            } else {
+                Some(i16::try_from(offset).map_err(|_| IonDecodeError::InvalidTimestamp)?)
+            },
+            ..IonTimestamp::default()
         };
+        if self.pos < end_pos {
+            timestamp.month = Some(self.read_timestamp_field()?);
+        }
+        if self.pos < end_pos {
+            timestamp.day = Some(self.read_timestamp_field()?);
+        }
+        if self.pos < end_pos {
+            timestamp.hour = Some(self.read_timestamp_field()?);
+            if self.pos >= end_pos {
+                return Err(IonDecodeError::InvalidTimestamp);
+            }
+            timestamp.minute = Some(self.read_timestamp_field()?);
+        }
+        if self.pos < end_pos {
+            timestamp.second = Some(self.read_timestamp_field()?);
+        }
+        if self.pos < end_pos {
+            let exponent = self.read_varint()?;
+            let exponent = i32::try_from(exponent).map_err(|_| IonDecodeError::Overflow)?;
+            let rest = end_pos
+                .checked_sub(self.pos)
+                .ok_or(IonDecodeError::InvalidTimestamp)?;
+            let coefficient = self.read_int(rest)?;
+            timestamp.fraction = Some(IonDecimal::new(coefficient, exponent));
+        }
 
-        let bytes = self.read_bytes(actual_length)?;
-        let text = String::from_utf8(bytes).map_err(|_| IonDecodeError::InvalidUtf8)?;
-        Ok(PackValue::Str(text))
+        if self.pos != end_pos {
+            return Err(IonDecodeError::InvalidTimestamp);
+        }
+        Ok(IonValue::Timestamp(timestamp))
+    }
+
+    fn read_timestamp_field(&mut self) -> Result<u8, IonDecodeError> {
+        u8::try_from(self.read_vuint()?).map_err(|_| IonDecodeError::InvalidTimestamp)
+    }
+
+    fn read_symbol(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
+        if length == 15 {
+            return Ok(IonValue::Null);
+        }
+        if length > 4 {
+            return Err(IonDecodeError::Overflow);
+        }
+
+        let mut sid: u32 = 0;
+        for b in self.read_bytes(length as usize)? {
+            sid = (sid << 8) | b as u32;
+        }
+        Ok(IonValue::Symbol(self.symbol_text(sid)?))
     }
 
-    fn read_binary(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_string(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(IonValue::Null);
         }
 
         let actual_length = if length == 14 {
@@ -226,12 +344,28 @@ impl IonDecoderBase {
             length as usize
         };
 
-        Ok(PackValue::Bytes(self.read_bytes(actual_length)?))
+        let start = self.pos;
+        let bytes = self.read_bytes(actual_length)?;
+        let text = String::from_utf8(bytes).map_err(|_| IonDecodeError::InvalidUtf8(start))?;
+        Ok(IonValue::Str(text))
     }
 
-    fn read_list(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_binary(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
+        Ok(self
+            .read_lob(length)?
+            .map_or(IonValue::Null, IonValue::Blob))
+    }
+
+    fn read_clob(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
+        Ok(self
+            .read_lob(length)?
+            .map_or(IonValue::Null, IonValue::Clob))
+    }
+
+    /// Reads the bytes of a blob or clob, `None` for a typed null.
+    fn read_lob(&mut self, length: u8) -> Result<Option<Vec<u8>>, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
+            return Ok(None);
         }
 
         let actual_length = if length == 14 {
@@ -239,55 +373,39 @@ impl IonDecoderBase {
         } else {
             length as usize
         };
-        let end_pos = self
-            .pos
-            .checked_add(actual_length)
-            .ok_or(IonDecodeError::EndOfInput)?;
 
-        if end_pos > self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+        self.read_bytes(actual_length).map(Some)
+    }
+
+    fn read_list(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
+        if length == 15 {
+            return Ok(IonValue::Null);
         }
 
+        let end_pos = self.read_end(length)?;
         let mut list = Vec::new();
         while self.pos < end_pos {
-            list.push(self.val()?);
+            list.push(self.read_value()?);
         }
 
         if self.pos != end_pos {
             return Err(IonDecodeError::ListLengthMismatch);
         }
 
-        Ok(PackValue::Array(list))
+        Ok(IonValue::List(list))
     }
 
-    fn read_struct(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_struct(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length == 15 {
-            return Ok(PackValue::Null);
-        }
-
-        let actual_length = if length == 14 {
-            self.read_vuint()? as usize
-        } else {
-            length as usize
-        };
-        let end_pos = self
-            .pos
-            .checked_add(actual_length)
-            .ok_or(IonDecodeError::EndOfInput)?;
-
-        if end_pos > self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Ok(IonValue::Null);
         }
 
+        let end_pos = self.read_end(length)?;
         let mut fields = Vec::new();
         while self.pos < end_pos {
             let field_sid = self.read_vuint()?;
-            let field_name = self
-                .symbols
-                .get_text(field_sid)
-                .ok_or(IonDecodeError::UnknownSymbol(field_sid))?
-                .to_string();
-            let field_value = self.val()?;
+            let field_name = self.symbol_text(field_sid)?;
+            let field_value = self.read_value()?;
             fields.push((field_name, field_value));
         }
 
@@ -295,10 +413,10 @@ impl IonDecoderBase {
             return Err(IonDecodeError::StructLengthMismatch);
         }
 
-        Ok(PackValue::Object(fields))
+        Ok(IonValue::Struct(fields))
     }
 
-    fn read_annotation(&mut self, length: u8) -> Result<PackValue, IonDecodeError> {
+    fn read_annotation(&mut self, length: u8) -> Result<IonValue, IonDecodeError> {
         if length < 3 {
             return Err(IonDecodeError::AnnotationTooShort(length));
         }
@@ -313,17 +431,90 @@ impl IonDecoderBase {
         let end_annot_pos = self
             .pos
             .checked_add(annot_length)
-            .ok_or(IonDecodeError::EndOfInput)?;
+            .ok_or(IonDecodeError::EndOfInput(self.pos))?;
 
+        let mut annotations = Vec::new();
         while self.pos < end_annot_pos {
-            let _ = self.read_vuint()?;
+            let sid = self.read_vuint()?;
+            if self.typed {
+                annotations.push(self.symbol_text(sid)?);
+            }
         }
 
         if self.pos != end_annot_pos {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
+        }
+
+        let value = self.read_value()?;
+        if annotations.is_empty() {
+            return Ok(value);
         }
+        Ok(IonValue::Annotated {
+            annotations,
+            value: Box::new(value),
+        })
+    }
 
-        self.val()
+    /// Reads the length of a container-like value and returns its end position.
+    fn read_end(&mut self, length: u8) -> Result<usize, IonDecodeError> {
+        let actual_length = if length == 14 {
+            self.read_vuint()? as usize
+        } else {
+            length as usize
+        };
+        let end_pos = self
+            .pos
+            .checked_add(actual_length)
+            .ok_or(IonDecodeError::EndOfInput(self.pos))?;
+
+        if end_pos > self.data.len() {
+            return Err(IonDecodeError::EndOfInput(self.pos));
+        }
+        Ok(end_pos)
+    }
+
+    fn symbol_text(&self, sid: u32) -> Result<String, IonDecodeError> {
+        self.symbols
+            .get_text(sid)
+            .map(str::to_string)
+            .ok_or(IonDecodeError::UnknownSymbol(sid))
+    }
+
+    /// Reads a VarInt: like a VUint, but bit 6 of the first byte is the sign.
+    fn read_varint(&mut self) -> Result<i64, IonDecodeError> {
+        let first = self.read_byte()?;
+        let negative = first & 0x40 != 0;
+        let mut magnitude = (first & 0x3f) as i64;
+        let mut b = first;
+        while b & 0x80 == 0 {
+            if magnitude > i64::MAX >> 7 {
+                return Err(IonDecodeError::Overflow);
+            }
+            b = self.read_byte()?;
+            magnitude = (magnitude << 7) | (b & 0x7f) as i64;
+        }
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    /// Reads an `n`-byte sign-and-magnitude Int; zero bytes read as 0.
+    fn read_int(&mut self, n: usize) -> Result<i128, IonDecodeError> {
+        if n > 16 {
+            return Err(IonDecodeError::Overflow);
+        }
+        let bytes = self.read_bytes(n)?;
+        let Some((&first, rest)) = bytes.split_first() else {
+            return Ok(0);
+        };
+        let mut magnitude = (first & 0x7f) as u128;
+        for &b in rest {
+            magnitude = (magnitude << 8) | b as u128;
+        }
+        let magnitude = i128::try_from(magnitude).map_err(|_| IonDecodeError::Overflow)?;
+        Ok(if first & 0x80 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        })
     }
 
     /// Reads a VUint (max 5 bytes for a 32-bit value).
@@ -338,12 +529,12 @@ impl IonDecoderBase {
                 return Ok(result);
             }
         }
-        Err(IonDecodeError::EndOfInput)
+        Err(IonDecodeError::EndOfInput(self.pos))
     }
 
     fn read_byte(&mut self) -> Result<u8, IonDecodeError> {
         if self.pos >= self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
         }
         let b = self.data[self.pos];
         self.pos += 1;
@@ -352,14 +543,14 @@ impl IonDecoderBase {
 
     fn peek_byte(&self) -> Result<u8, IonDecodeError> {
         if self.pos >= self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
         }
         Ok(self.data[self.pos])
     }
 
     fn read_bytes(&mut self, n: usize) -> Result<Vec<u8>, IonDecodeError> {
         if self.pos + n > self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
         }
         let bytes = self.data[self.pos..self.pos + n].to_vec();
         self.pos += n;
@@ -368,7 +559,7 @@ impl IonDecoderBase {
 
     fn skip(&mut self, n: usize) -> Result<(), IonDecodeError> {
         if self.pos + n > self.data.len() {
-            return Err(IonDecodeError::EndOfInput);
+            return Err(IonDecodeError::EndOfInput(self.pos));
         }
         self.pos += n;
         Ok(())
@@ -6,6 +6,9 @@ use json_joy_buffers::Writer;
 
 use super::constants::{TypeOverlay, ION_BVM, SID_ION_SYMBOL_TABLE, SID_SYMBOLS};
 use super::symbols::IonSymbols;
+use super::value::{IonDecimal, IonTimestamp, IonValue};
+use crate::checked::{self, EncodeLossError};
+use crate::pack_value::text_keyed;
 use crate::PackValue;
 
 /// Calculates the number of bytes needed for a VUint encoding.
@@ -23,11 +26,24 @@ fn vuint_len(n: u32) -> usize {
     }
 }
 
+/// Options controlling Ion encoding of [`PackValue`]s.
+#[derive(Debug, Clone, Default)]
+pub struct IonEncoderOptions {
+    /// When `true`, floats that survive a round trip through `f32` are
+    /// written in 4 bytes, and positive zero in none. Upstream always
+    /// writes 8 bytes.
+    pub compact_floats: bool,
+    /// When `true`, [`PackValue::Bytes`] is written as a clob instead of a
+    /// blob.
+    pub bytes_as_clob: bool,
+}
+
 /// Ion binary encoder.
 ///
 /// Encodes [`PackValue`] to Ion binary format with symbol tables.
 pub struct IonEncoder {
     pub writer: Writer,
+    pub options: IonEncoderOptions,
     symbols: IonSymbols,
 }
 
@@ -39,12 +55,24 @@ impl Default for IonEncoder {
 
 impl IonEncoder {
     pub fn new() -> Self {
+        Self::with_options(IonEncoderOptions::default())
+    }
+
+    pub fn with_options(options: IonEncoderOptions) -> Self {
         Self {
             writer: Writer::new(),
+            options,
             symbols: IonSymbols::new(),
         }
     }
 
+    /// Like [`encode`](Self::encode), but fails on `undefined`, an
+    /// extension, a blob or a non-text key.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::ion_loss, checked::Keys::Text)?;
+        Ok(self.encode(value))
+    }
+
     pub fn encode(&mut self, value: &PackValue) -> Vec<u8> {
         self.writer.reset();
         self.symbols = IonSymbols::new();
@@ -65,6 +93,36 @@ impl IonEncoder {
         self.writer.flush()
     }
 
+    /// Encodes `value` annotated with `annotations`, e.g. `USD::12.5`.
+    pub fn encode_annotated(&mut self, annotations: &[&str], value: &PackValue) -> Vec<u8> {
+        self.writer.reset();
+        self.symbols = IonSymbols::new();
+        for annotation in annotations {
+            self.symbols.add(annotation);
+        }
+        self.collect_symbols(value);
+        self.write_ivm();
+        if self.symbols.has_user_symbols() {
+            self.write_symbol_table();
+        }
+        self.write_annotated(annotations, value);
+        self.writer.flush()
+    }
+
+    /// Encodes a typed [`IonValue`], including decimals, timestamps, symbols
+    /// and annotations.
+    pub fn encode_ion(&mut self, value: &IonValue) -> Vec<u8> {
+        self.writer.reset();
+        self.symbols = IonSymbols::new();
+        self.collect_ion_symbols(value);
+        self.write_ivm();
+        if self.symbols.has_user_symbols() {
+            self.write_symbol_table();
+        }
+        self.write_ion(value);
+        self.writer.flush()
+    }
+
     fn collect_symbols(&mut self, value: &PackValue) {
         match value {
             PackValue::Object(obj) => {
@@ -82,6 +140,32 @@ impl IonEncoder {
         }
     }
 
+    fn collect_ion_symbols(&mut self, value: &IonValue) {
+        match value {
+            IonValue::Symbol(text) => {
+                self.symbols.add(text);
+            }
+            IonValue::List(items) => {
+                for item in items {
+                    self.collect_ion_symbols(item);
+                }
+            }
+            IonValue::Struct(fields) => {
+                for (key, val) in fields {
+                    self.symbols.add(key);
+                    self.collect_ion_symbols(val);
+                }
+            }
+            IonValue::Annotated { annotations, value } => {
+                for annotation in annotations {
+                    self.symbols.add(annotation);
+                }
+                self.collect_ion_symbols(value);
+            }
+            _ => {}
+        }
+    }
+
     fn write_ivm(&mut self) {
         self.writer.buf(&ION_BVM);
     }
@@ -156,37 +240,27 @@ impl IonEncoder {
         match value {
             PackValue::Null | PackValue::Undefined => self.write_null(),
             PackValue::Bool(b) => self.write_bool(*b),
-            PackValue::Integer(n) => {
-                if *n >= 0 {
-                    self.write_uint(*n as u64);
-                } else {
-                    self.write_nint(n.unsigned_abs());
-                }
-            }
+            PackValue::Integer(n) => self.write_int(*n as i128),
             PackValue::UInteger(n) => self.write_uint(*n),
-            PackValue::Float(f) => self.write_float(*f),
-            PackValue::BigInt(n) => {
-                if *n >= 0 {
-                    if *n <= u64::MAX as i128 {
-                        self.write_uint(*n as u64);
-                    } else {
-                        // Upstream only supports JS safe integers; preserve non-lossy behavior
-                        // by avoiding truncation for out-of-range Rust big integers.
-                        self.write_null();
-                    }
+            PackValue::Float(f) => {
+                if !self.options.compact_floats {
+                    self.write_float(*f);
+                } else if *f == 0.0 && f.is_sign_positive() {
+                    self.writer.u8(TypeOverlay::FLOT);
+                } else if (*f as f32) as f64 == *f || f.is_nan() {
+                    self.write_float32(*f as f32);
                 } else {
-                    let magnitude = n.unsigned_abs();
-                    if magnitude <= u64::MAX as u128 {
-                        self.write_nint(magnitude as u64);
-                    } else {
-                        self.write_null();
-                    }
+                    self.write_float(*f);
                 }
             }
+            PackValue::BigInt(n) => self.write_int(*n),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Str(s) => self.write_str(s),
+            PackValue::Bytes(b) if self.options.bytes_as_clob => self.write_clob(b),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(_) | PackValue::Blob(_) => self.write_null(),
         }
     }
@@ -213,6 +287,31 @@ impl IonEncoder {
         }
     }
 
+    /// Writes a positive or negative int of up to 16 magnitude bytes.
+    pub fn write_int(&mut self, n: i128) {
+        let overlay = if n < 0 {
+            TypeOverlay::NINT
+        } else {
+            TypeOverlay::UINT
+        };
+        let bytes = uint_bytes(n.unsigned_abs());
+        self.write_type_len(overlay, bytes.len());
+        self.writer.buf(&bytes);
+    }
+
+    /// Writes an int of any size. Ion ints are unbounded, but
+    /// [`IonDecoder`](super::IonDecoder) reads at most 16 magnitude bytes
+    /// and reports larger ones as an overflow.
+    pub fn write_big_num(&mut self, n: &crate::JsonPackBigInt) {
+        let overlay = if n.is_negative() {
+            TypeOverlay::NINT
+        } else {
+            TypeOverlay::UINT
+        };
+        self.write_type_len(overlay, n.magnitude().len());
+        self.writer.buf(n.magnitude());
+    }
+
     pub fn write_nint(&mut self, n: u64) {
         // n is the magnitude (positive). Encode as negative integer.
         if n == 0 {
@@ -231,8 +330,13 @@ impl IonEncoder {
         // NOTE: The Ion binary spec (§5) requires big-endian IEEE 754, but the upstream
         // TypeScript implementation uses little-endian (matching the @jsonjoy.com/buffers
         // reader). We match the upstream behavior intentionally for wire compatibility.
-        let bits = f.to_bits().to_le_bytes();
-        self.writer.buf(&bits);
+        self.writer.f64_le(f);
+    }
+
+    /// Writes a 4-byte float, little-endian like [`write_float`](Self::write_float).
+    pub fn write_float32(&mut self, f: f32) {
+        self.writer.u8(TypeOverlay::FLOT | 4);
+        self.writer.buf(&f.to_le_bytes());
     }
 
     pub fn write_str(&mut self, s: &str) {
@@ -258,6 +362,30 @@ impl IonEncoder {
         self.writer.buf(data);
     }
 
+    pub fn write_clob(&mut self, data: &[u8]) {
+        self.write_type_len(TypeOverlay::CLOB, data.len());
+        self.writer.buf(data);
+    }
+
+    /// Writes `value` wrapped in `annotations`; with none, just `value`.
+    /// Annotations written outside [`encode_annotated`](Self::encode_annotated)
+    /// must already be in the symbol table.
+    pub fn write_annotated(&mut self, annotations: &[&str], value: &PackValue) {
+        if annotations.is_empty() {
+            return self.write_any(value);
+        }
+        let mut content: Vec<u8> = Vec::new();
+        let mut sids: Vec<u8> = Vec::new();
+        for annotation in annotations {
+            write_vuint_to(&mut sids, self.symbols.add(annotation));
+        }
+        write_vuint_to(&mut content, sids.len() as u32);
+        content.extend_from_slice(&sids);
+        content.extend_from_slice(&self.encode_value_to_bytes(value));
+        self.write_type_len(TypeOverlay::ANNO, content.len());
+        self.writer.buf(&content);
+    }
+
     pub fn write_arr(&mut self, arr: &[PackValue]) {
         // Encode each element using self (shares the symbol table), measuring bytes.
         let mut content: Vec<u8> = Vec::new();
@@ -292,6 +420,119 @@ impl IonEncoder {
         self.writer.buf(&content);
     }
 
+    pub fn write_ion(&mut self, value: &IonValue) {
+        match value {
+            IonValue::Null => self.write_null(),
+            IonValue::Bool(b) => self.write_bool(*b),
+            IonValue::Int(n) => self.write_int(*n),
+            IonValue::Float(f) => self.write_float(*f),
+            IonValue::Decimal(d) => self.write_decimal(d),
+            IonValue::Timestamp(t) => self.write_timestamp(t),
+            IonValue::Symbol(text) => {
+                let bytes = uint_bytes(self.symbols.add(text) as u128);
+                self.write_type_len(TypeOverlay::SYMB, bytes.len());
+                self.writer.buf(&bytes);
+            }
+            IonValue::Str(s) => self.write_str(s),
+            IonValue::Blob(b) => self.write_bin(b),
+            IonValue::Clob(b) => self.write_clob(b),
+            IonValue::List(items) => {
+                let mut content: Vec<u8> = Vec::new();
+                for item in items {
+                    content.extend_from_slice(&self.encode_ion_to_bytes(item));
+                }
+                self.write_type_len(TypeOverlay::LIST, content.len());
+                self.writer.buf(&content);
+            }
+            IonValue::Struct(fields) => {
+                let mut content: Vec<u8> = Vec::new();
+                for (key, val) in fields {
+                    let sid = self.symbols.add(key);
+                    write_vuint_to(&mut content, sid);
+                    content.extend_from_slice(&self.encode_ion_to_bytes(val));
+                }
+                self.write_type_len(TypeOverlay::STRU, content.len());
+                self.writer.buf(&content);
+            }
+            IonValue::Annotated { annotations, value } => {
+                // Ion requires at least one annotation per wrapper.
+                if annotations.is_empty() {
+                    return self.write_ion(value);
+                }
+                let mut sids: Vec<u8> = Vec::new();
+                for annotation in annotations {
+                    let sid = self.symbols.add(annotation);
+                    write_vuint_to(&mut sids, sid);
+                }
+                let mut content: Vec<u8> = Vec::new();
+                write_vuint_to(&mut content, sids.len() as u32);
+                content.extend_from_slice(&sids);
+                content.extend_from_slice(&self.encode_ion_to_bytes(value));
+                self.write_type_len(TypeOverlay::ANNO, content.len());
+                self.writer.buf(&content);
+            }
+        }
+    }
+
+    pub fn write_decimal(&mut self, d: &IonDecimal) {
+        if d.coefficient == 0 && d.exponent == 0 {
+            self.writer.u8(TypeOverlay::DECI);
+            return;
+        }
+        let mut content: Vec<u8> = Vec::new();
+        write_varint_to(&mut content, d.exponent as i64);
+        write_int_to(&mut content, d.coefficient);
+        self.write_type_len(TypeOverlay::DECI, content.len());
+        self.writer.buf(&content);
+    }
+
+    /// Writes the components up to the timestamp's precision; see
+    /// [`IonTimestamp`].
+    pub fn write_timestamp(&mut self, t: &IonTimestamp) {
+        let mut content: Vec<u8> = Vec::new();
+        match t.offset {
+            Some(offset) => write_varint_to(&mut content, offset as i64),
+            None => content.push(0xc0),
+        }
+        write_vuint_to(&mut content, t.year as u32);
+        if let Some(month) = t.month {
+            write_vuint_to(&mut content, month as u32);
+            if let Some(day) = t.day {
+                write_vuint_to(&mut content, day as u32);
+                if let (Some(hour), Some(minute)) = (t.hour, t.minute) {
+                    write_vuint_to(&mut content, hour as u32);
+                    write_vuint_to(&mut content, minute as u32);
+                    if let Some(second) = t.second {
+                        write_vuint_to(&mut content, second as u32);
+                        if let Some(fraction) = t.fraction {
+                            write_varint_to(&mut content, fraction.exponent as i64);
+                            write_int_to(&mut content, fraction.coefficient);
+                        }
+                    }
+                }
+            }
+        }
+        self.write_type_len(TypeOverlay::TIME, content.len());
+        self.writer.buf(&content);
+    }
+
+    fn write_type_len(&mut self, overlay: u8, len: usize) {
+        if len < 14 {
+            self.writer.u8(overlay | len as u8);
+        } else {
+            self.writer.u8(overlay | 14);
+            self.write_vuint(len as u32);
+        }
+    }
+
+    fn encode_ion_to_bytes(&mut self, value: &IonValue) -> Vec<u8> {
+        let mut tmp_writer = Writer::new();
+        std::mem::swap(&mut self.writer, &mut tmp_writer);
+        self.write_ion(value);
+        std::mem::swap(&mut self.writer, &mut tmp_writer);
+        tmp_writer.flush()
+    }
+
     /// Encodes a value to bytes using this encoder's symbol table.
     ///
     /// Temporarily swaps writers so `write_any` targets a fresh buffer; the
@@ -333,6 +574,44 @@ fn uint_byte_len(n: u64) -> usize {
     }
 }
 
+/// Big-endian magnitude bytes without leading zeros.
+fn uint_bytes(n: u128) -> Vec<u8> {
+    let bytes = n.to_be_bytes();
+    let skip = (n.leading_zeros() / 8) as usize;
+    bytes[skip..].to_vec()
+}
+
+/// Write a VarInt: a VUint whose first byte carries the sign in bit 6.
+fn write_varint_to(out: &mut Vec<u8>, n: i64) {
+    let sign = if n < 0 { 0x40 } else { 0 };
+    let mut magnitude = n.unsigned_abs();
+    let mut groups = vec![(magnitude & 0x7f) as u8 | 0x80];
+    magnitude >>= 7;
+    while magnitude > 0 {
+        groups.push((magnitude & 0x7f) as u8);
+        magnitude >>= 7;
+    }
+    // The first byte only has room for 6 magnitude bits.
+    if groups[groups.len() - 1] & 0x40 != 0 {
+        groups.push(0);
+    }
+    let last = groups.len() - 1;
+    groups[last] |= sign;
+    out.extend(groups.iter().rev());
+}
+
+/// Write a sign-and-magnitude Int; zero is written as no bytes.
+fn write_int_to(out: &mut Vec<u8>, n: i128) {
+    let mut bytes = uint_bytes(n.unsigned_abs());
+    if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    if n < 0 {
+        bytes[0] |= 0x80;
+    }
+    out.extend_from_slice(&bytes);
+}
+
 /// Write a VUint to a byte vector.
 /// Ion VUint: each byte has 7 data bits; MSB=1 signals continuation.
 /// The last byte has MSB=0.
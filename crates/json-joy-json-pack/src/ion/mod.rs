@@ -9,11 +9,13 @@ pub mod encoder;
 pub mod import;
 pub mod symbols;
 pub mod types;
+pub mod value;
 
 pub use decoder::{IonDecodeError, IonDecoder};
-pub use encoder::IonEncoder;
+pub use encoder::{IonEncoder, IonEncoderOptions};
 pub use import::{system_symbol_import, system_symbol_table, Import};
 pub use symbols::IonSymbols;
+pub use value::{IonDecimal, IonTimestamp, IonValue};
 
 /// Alias for the fast Ion encoder (matches upstream `IonEncoderFast` class name).
 pub type IonEncoderFast = IonEncoder;
@@ -0,0 +1,207 @@
+//! Typed Ion values.
+//!
+//! Rust-only extension: upstream maps Ion to plain JSON values and has no
+//! representation for decimals, timestamps, symbols or annotations.
+//! [`IonEncoder::encode_ion`](super::IonEncoder::encode_ion) and
+//! [`IonDecoder::decode_ion`](super::IonDecoder::decode_ion) round-trip them.
+
+use std::fmt;
+
+use crate::PackValue;
+
+/// An Ion value, including the types that have no [`PackValue`] counterpart.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IonValue {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Decimal(IonDecimal),
+    Timestamp(IonTimestamp),
+    Symbol(String),
+    Str(String),
+    Blob(Vec<u8>),
+    /// Character data in an unspecified encoding.
+    Clob(Vec<u8>),
+    List(Vec<IonValue>),
+    Struct(Vec<(String, IonValue)>),
+    /// A value with one or more type annotations, e.g. `USD::12.50`.
+    Annotated {
+        annotations: Vec<String>,
+        value: Box<IonValue>,
+    },
+}
+
+/// Arbitrary-precision decimal: `coefficient * 10^exponent`.
+///
+/// A negative-zero coefficient decodes as zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IonDecimal {
+    pub coefficient: i128,
+    pub exponent: i32,
+}
+
+impl IonDecimal {
+    pub fn new(coefficient: i128, exponent: i32) -> Self {
+        Self {
+            coefficient,
+            exponent,
+        }
+    }
+
+    /// Nearest `f64` to this decimal.
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.coefficient, self.exponent)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+}
+
+impl fmt::Display for IonDecimal {
+    /// Ion text notation, e.g. `1250d-2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.coefficient, self.exponent)
+    }
+}
+
+/// Ion timestamp.
+///
+/// The precision is given by the first component that is `None`: a
+/// timestamp with a `day` but no `hour` is a date. `hour` and `minute` are
+/// only written together, and `fraction` only after `second`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IonTimestamp {
+    pub year: u16,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<u8>,
+    /// Fractional seconds in `[0, 1)`, e.g. `IonDecimal::new(123, -3)`.
+    pub fraction: Option<IonDecimal>,
+    /// Offset from UTC in minutes; `None` is the unknown offset `-00:00`.
+    pub offset: Option<i16>,
+}
+
+impl IonTimestamp {
+    /// A day-precision timestamp.
+    pub fn date(year: u16, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month: Some(month),
+            day: Some(day),
+            ..Self::default()
+        }
+    }
+
+    /// Extends a date to second precision at the given UTC offset.
+    pub fn at(self, hour: u8, minute: u8, second: u8, offset: Option<i16>) -> Self {
+        Self {
+            hour: Some(hour),
+            minute: Some(minute),
+            second: Some(second),
+            offset,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for IonTimestamp {
+    /// Ion text notation, e.g. `2024-05-01T12:30:05.250Z`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        let Some(month) = self.month else {
+            return f.write_str("T");
+        };
+        write!(f, "-{month:02}")?;
+        let Some(day) = self.day else {
+            return f.write_str("T");
+        };
+        write!(f, "-{day:02}")?;
+        let (Some(hour), Some(minute)) = (self.hour, self.minute) else {
+            return Ok(());
+        };
+        write!(f, "T{hour:02}:{minute:02}")?;
+        if let Some(second) = self.second {
+            write!(f, ":{second:02}")?;
+            if let Some(fraction) = self.fraction.filter(|d| d.exponent < 0) {
+                let width = fraction.exponent.unsigned_abs() as usize;
+                write!(f, ".{:0width$}", fraction.coefficient)?;
+            }
+        }
+        match self.offset {
+            None => f.write_str("-00:00"),
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let minutes = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// Lossy conversion: annotations are dropped, decimals become floats, and
+/// symbols and timestamps become their text.
+impl From<IonValue> for PackValue {
+    fn from(value: IonValue) -> Self {
+        match value {
+            IonValue::Null => PackValue::Null,
+            IonValue::Bool(b) => PackValue::Bool(b),
+            IonValue::Int(n) => {
+                if n >= 0 && n <= u64::MAX as i128 {
+                    PackValue::UInteger(n as u64)
+                } else if n < 0 && n >= i64::MIN as i128 {
+                    PackValue::Integer(n as i64)
+                } else {
+                    PackValue::BigInt(n)
+                }
+            }
+            IonValue::Float(f) => PackValue::Float(f),
+            IonValue::Decimal(d) => PackValue::Float(d.to_f64()),
+            IonValue::Timestamp(t) => PackValue::Str(t.to_string()),
+            IonValue::Symbol(s) | IonValue::Str(s) => PackValue::Str(s),
+            IonValue::Blob(b) | IonValue::Clob(b) => PackValue::Bytes(b),
+            IonValue::List(items) => PackValue::Array(items.into_iter().map(Into::into).collect()),
+            IonValue::Struct(fields) => PackValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            IonValue::Annotated { value, .. } => (*value).into(),
+        }
+    }
+}
+
+/// Mirrors [`IonEncoder::encode`](super::IonEncoder::encode): values Ion
+/// cannot hold (extensions, blobs, integers beyond 128 bits) become null.
+impl From<PackValue> for IonValue {
+    fn from(value: PackValue) -> Self {
+        match value {
+            PackValue::Null | PackValue::Undefined => IonValue::Null,
+            PackValue::Bool(b) => IonValue::Bool(b),
+            PackValue::Integer(n) => IonValue::Int(n as i128),
+            PackValue::UInteger(n) => IonValue::Int(n as i128),
+            PackValue::BigInt(n) => IonValue::Int(n),
+            PackValue::BigNum(n) => n.to_i128().map_or(IonValue::Null, IonValue::Int),
+            PackValue::Float(f) => IonValue::Float(f),
+            PackValue::Str(s) => IonValue::Str(s),
+            PackValue::Bytes(b) => IonValue::Blob(b),
+            PackValue::Array(items) => IonValue::List(items.into_iter().map(Into::into).collect()),
+            PackValue::Object(fields) => IonValue::Struct(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            PackValue::Map(entries) => IonValue::Struct(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.to_key_string(), value.into()))
+                    .collect(),
+            ),
+            PackValue::Extension(_) | PackValue::Blob(_) => IonValue::Null,
+        }
+    }
+}
@@ -4,21 +4,70 @@
 //!
 //! Handles data URI strings (`data:application/octet-stream;base64,...`) as
 //! `PackValue::Bytes` and the CBOR-undefined sentinel as `PackValue::Undefined`.
+//!
+//! Rust-only addition: [`JsonDecoderOptions`] enables tolerant modes for
+//! JSON with comments and trailing commas (JSONC) and for JSON5.
 
 use json_joy_base64::from_base64_bin;
 
 use super::error::JsonError;
 use super::util::find_ending_quote;
-use crate::PackValue;
+use crate::token::{walk, TokenSink};
+use crate::util::{CachedKey, KeyCache, KeyCacheStats};
+use crate::{JsonPackBigInt, PackValue};
 
 // "data:application/octet-stream;base64," — 37 bytes
 const BIN_PREFIX: &[u8] = b"data:application/octet-stream;base64,";
 // "data:application/cbor,base64;9w==" — 33 bytes (inside the opening quote)
 const UNDEF_INNER: &[u8] = b"ata:application/cbor,base64;9w==\"";
+// Non-ASCII whitespace allowed by JSON5: NBSP, BOM, LS and PS.
+const JSON5_SPACES: [&[u8]; 4] = [
+    "\u{a0}".as_bytes(),
+    "\u{feff}".as_bytes(),
+    "\u{2028}".as_bytes(),
+    "\u{2029}".as_bytes(),
+];
+
+/// Extensions to strict JSON accepted by [`JsonDecoder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonDecoderOptions {
+    /// Skip `// line` and `/* block */` comments wherever whitespace is
+    /// allowed.
+    pub comments: bool,
+    /// Accept a comma after the last array element or object member.
+    pub trailing_commas: bool,
+    /// JSON5 values and keys: unquoted identifier keys, single-quoted
+    /// strings, JSON5 escapes and line continuations, hexadecimal
+    /// integers, a leading `+`, leading or trailing decimal points,
+    /// `Infinity`, `NaN` and the extra JSON5 whitespace characters.
+    pub json5: bool,
+}
+
+impl JsonDecoderOptions {
+    /// JSON with comments (JSONC), as in `tsconfig.json` or VS Code
+    /// settings: comments and trailing commas.
+    pub fn jsonc() -> Self {
+        Self {
+            comments: true,
+            trailing_commas: true,
+            json5: false,
+        }
+    }
+
+    /// JSON5: the JSONC extensions plus JSON5 values and keys.
+    pub fn json5() -> Self {
+        Self {
+            json5: true,
+            ..Self::jsonc()
+        }
+    }
+}
 
 pub struct JsonDecoder {
     pub data: Vec<u8>,
     pub x: usize,
+    options: JsonDecoderOptions,
+    key_cache: Option<KeyCache>,
 }
 
 impl Default for JsonDecoder {
@@ -29,18 +78,54 @@ impl Default for JsonDecoder {
 
 impl JsonDecoder {
     pub fn new() -> Self {
+        Self::with_options(JsonDecoderOptions::default())
+    }
+
+    pub fn with_options(options: JsonDecoderOptions) -> Self {
         Self {
             data: Vec::new(),
             x: 0,
+            options,
+            key_cache: None,
         }
     }
 
+    pub fn options(&self) -> &JsonDecoderOptions {
+        &self.options
+    }
+
+    /// Interns object keys through `cache`, which is kept across documents.
+    /// JSON5 keys are not interned.
+    pub fn with_key_cache(mut self, cache: KeyCache) -> Self {
+        self.key_cache = Some(cache);
+        self
+    }
+
+    /// Counters of the key cache, if one was set.
+    pub fn key_cache_stats(&self) -> Option<KeyCacheStats> {
+        self.key_cache.as_ref().map(KeyCache::stats)
+    }
+
     pub fn decode(&mut self, input: &[u8]) -> Result<PackValue, JsonError> {
         self.data = input.to_vec();
         self.x = 0;
         self.read_any()
     }
 
+    /// Reads the value at the start of `input` as token events, returning
+    /// the number of bytes consumed. Containers are reported without a
+    /// length.
+    pub fn parse_with<S: TokenSink + ?Sized>(
+        &mut self,
+        input: &[u8],
+        sink: &mut S,
+    ) -> Result<usize, JsonError> {
+        self.data = input.to_vec();
+        self.x = 0;
+        self.read_tokens(sink)?;
+        Ok(self.x)
+    }
+
     pub fn read_any(&mut self) -> Result<PackValue, JsonError> {
         self.skip_whitespace();
         let data = &self.data;
@@ -69,6 +154,10 @@ impl JsonDecoder {
             b'n' => self.read_null(),
             b't' => self.read_true(),
             b'{' => self.read_obj(),
+            b'\'' if self.options.json5 => Ok(PackValue::Str(self.read_str5()?)),
+            b'+' | b'.' | b'I' | b'N' | b'-' | b'0'..=b'9' if self.options.json5 => {
+                self.read_num5()
+            }
             c if c.is_ascii_digit() || c == b'-' => self.read_num(),
             _ => Err(JsonError::Invalid(x)),
         }
@@ -78,11 +167,57 @@ impl JsonDecoder {
         while self.x < self.data.len() {
             match self.data[self.x] {
                 b' ' | b'\t' | b'\n' | b'\r' => self.x += 1,
+                b'/' if self.options.comments => {
+                    if !self.skip_comment() {
+                        break;
+                    }
+                }
+                0x0b | 0x0c if self.options.json5 => self.x += 1,
+                0xc2 | 0xe2 | 0xef if self.options.json5 => {
+                    let rest = &self.data[self.x..];
+                    match JSON5_SPACES.iter().find(|ws| rest.starts_with(ws)) {
+                        Some(ws) => self.x += ws.len(),
+                        None => break,
+                    }
+                }
                 _ => break,
             }
         }
     }
 
+    /// Skips the comment starting at `x`. Returns `false`, leaving `x` on
+    /// the `/`, if there is no comment or it is unterminated.
+    fn skip_comment(&mut self) -> bool {
+        let rest = &self.data[self.x..];
+        match rest.get(1) {
+            Some(b'/') => {
+                self.x += rest
+                    .iter()
+                    .position(|&c| c == b'\n')
+                    .map_or(rest.len(), |i| i + 1);
+                true
+            }
+            Some(b'*') => match rest[2..].windows(2).position(|w| w == b"*/") {
+                Some(i) => {
+                    self.x += i + 4;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// With `trailing_commas`, whether the comma just read is followed by
+    /// `close`.
+    fn at_trailing_comma(&mut self, close: u8) -> bool {
+        if !self.options.trailing_commas {
+            return false;
+        }
+        self.skip_whitespace();
+        self.data.get(self.x) == Some(&close)
+    }
+
     pub fn read_null(&mut self) -> Result<PackValue, JsonError> {
         if self.x + 4 > self.data.len() || &self.data[self.x..self.x + 4] != b"null" {
             return Err(JsonError::Invalid(self.x));
@@ -140,7 +275,7 @@ impl JsonDecoder {
         }
         self.x = x;
 
-        let s = std::str::from_utf8(&data[start..x]).map_err(|_| JsonError::InvalidUtf8)?;
+        let s = std::str::from_utf8(&data[start..x]).map_err(|_| JsonError::InvalidUtf8(start))?;
         if is_float {
             let f: f64 = s.parse().map_err(|_| JsonError::Invalid(start))?;
             Ok(PackValue::Float(f))
@@ -150,12 +285,17 @@ impl JsonDecoder {
             Ok(PackValue::UInteger(u))
         } else if let Ok(i) = s.parse::<i128>() {
             Ok(PackValue::BigInt(i))
+        } else if let Ok(n) = s.parse::<JsonPackBigInt>() {
+            Ok(PackValue::BigNum(n))
         } else {
             Err(JsonError::Invalid(start))
         }
     }
 
     pub fn read_str(&mut self) -> Result<String, JsonError> {
+        if self.options.json5 {
+            return self.read_str5();
+        }
         let data = &self.data;
         if self.x >= data.len() || data[self.x] != b'"' {
             return Err(JsonError::Invalid(self.x));
@@ -164,7 +304,7 @@ impl JsonDecoder {
         let x0 = self.x;
         let x1 = find_ending_quote(data, x0)?;
         let slice = &data[x0..x1];
-        let s = decode_json_string(slice)?;
+        let s = decode_json_string(slice, x0)?;
         self.x = x1 + 1; // skip closing quote
         Ok(s)
     }
@@ -211,6 +351,9 @@ impl JsonDecoder {
             }
             if ch == b',' {
                 self.x += 1;
+                if !first && self.at_trailing_comma(b']') {
+                    continue;
+                }
             } else if !first {
                 return Err(JsonError::Invalid(self.x));
             }
@@ -239,12 +382,15 @@ impl JsonDecoder {
             }
             if ch == b',' {
                 self.x += 1;
+                if !first && self.at_trailing_comma(b'}') {
+                    continue;
+                }
             } else if !first {
                 return Err(JsonError::Invalid(self.x));
             }
             self.skip_whitespace();
             // Read key
-            if self.x >= self.data.len() || self.data[self.x] != b'"' {
+            if self.x >= self.data.len() || (self.data[self.x] != b'"' && !self.options.json5) {
                 return Err(JsonError::Invalid(self.x));
             }
             let key = self.read_key()?;
@@ -263,9 +409,94 @@ impl JsonDecoder {
         }
     }
 
-    /// Read a quoted JSON key (without outer quotes in result).
+    fn read_tokens<S: TokenSink + ?Sized>(&mut self, sink: &mut S) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        let map = match self.data.get(self.x) {
+            Some(b'[') => false,
+            Some(b'{') => true,
+            _ => {
+                walk(&self.read_any()?, sink);
+                return Ok(());
+            }
+        };
+        self.x += 1;
+        if map {
+            sink.on_map_start(None);
+        } else {
+            sink.on_array_start(None);
+        }
+        let mut first = true;
+        loop {
+            self.skip_whitespace();
+            if self.x >= self.data.len() {
+                return Err(JsonError::Invalid(self.x));
+            }
+            let ch = self.data[self.x];
+            if ch == if map { b'}' } else { b']' } {
+                self.x += 1;
+                break;
+            }
+            if ch == b',' {
+                self.x += 1;
+                if !first && self.at_trailing_comma(if map { b'}' } else { b']' }) {
+                    continue;
+                }
+            } else if !first {
+                return Err(JsonError::Invalid(self.x));
+            }
+            self.skip_whitespace();
+            if map {
+                if self.x >= self.data.len() || (self.data[self.x] != b'"' && !self.options.json5) {
+                    return Err(JsonError::Invalid(self.x));
+                }
+                let key = self.read_cached_key()?;
+                if &*key == "__proto__" {
+                    return Err(JsonError::InvalidKey);
+                }
+                self.skip_whitespace();
+                if self.x >= self.data.len() || self.data[self.x] != b':' {
+                    return Err(JsonError::Invalid(self.x));
+                }
+                self.x += 1;
+                sink.on_key(&key);
+            }
+            self.read_tokens(sink)?;
+            first = false;
+        }
+        if map {
+            sink.on_map_end();
+        } else {
+            sink.on_array_end();
+        }
+        Ok(())
+    }
+
+    /// Read a quoted JSON key (without outer quotes in result). With
+    /// `json5`, also single-quoted and unquoted keys.
     pub fn read_key(&mut self) -> Result<String, JsonError> {
-        self.read_str()
+        self.read_cached_key().map(String::from)
+    }
+
+    /// [`read_key`](Self::read_key) through the key cache, if set.
+    fn read_cached_key(&mut self) -> Result<CachedKey, JsonError> {
+        if self.options.json5 {
+            if !matches!(self.data.get(self.x), Some(b'"' | b'\'')) {
+                return self.read_ident().map(CachedKey::Decoded);
+            }
+            return self.read_str().map(CachedKey::Decoded);
+        }
+        let Some(cache) = &mut self.key_cache else {
+            return self.read_str().map(CachedKey::Decoded);
+        };
+        let data = &self.data;
+        if self.x >= data.len() || data[self.x] != b'"' {
+            return Err(JsonError::Invalid(self.x));
+        }
+        let x0 = self.x + 1;
+        let x1 = find_ending_quote(data, x0)?;
+        let key = cache.get_or_decode(&data[x0..x1], |raw| decode_json_string(raw, x0))?;
+        self.x = x1 + 1;
+        Ok(key)
     }
 
     fn starts_with_undef_inner(&self, x: usize) -> bool {
@@ -278,13 +509,14 @@ impl JsonDecoder {
 }
 
 /// Decode a JSON string body (between the quotes) handling escape sequences.
-/// Uses serde_json for correctness.
-fn decode_json_string(bytes: &[u8]) -> Result<String, JsonError> {
+/// Uses serde_json for correctness. `offset` is the position of `bytes` in
+/// the input, for error reporting.
+pub(super) fn decode_json_string(bytes: &[u8], offset: usize) -> Result<String, JsonError> {
     // Fast path: no backslash
     if !bytes.contains(&b'\\') {
-        return std::str::from_utf8(bytes)
+        return crate::utf8::from_utf8(bytes)
             .map(|s| s.to_string())
-            .map_err(|_| JsonError::InvalidUtf8);
+            .map_err(|_| JsonError::InvalidUtf8(offset));
     }
     // Wrap in quotes and use serde_json for proper unescaping
     let mut quoted = Vec::with_capacity(bytes.len() + 2);
@@ -241,7 +241,7 @@ impl JsonDecoderDag {
 
         let cid = std::str::from_utf8(&self.inner.data[buf_start..buf_end])
             .map(|s| s.to_string())
-            .map_err(|_| JsonError::InvalidUtf8)?;
+            .map_err(|_| JsonError::InvalidUtf8(buf_start))?;
         Ok(Some(cid))
     }
 
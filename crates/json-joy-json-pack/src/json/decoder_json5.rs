@@ -0,0 +1,250 @@
+//! JSON5 readers for [`JsonDecoder`].
+//!
+//! Not an upstream port. Enabled by [`JsonDecoderOptions::json5`]; the
+//! decoder dispatches here for strings, numbers and unquoted keys. See
+//! <https://spec.json5.org/>.
+//!
+//! [`JsonDecoderOptions::json5`]: super::JsonDecoderOptions::json5
+
+use super::decoder::JsonDecoder;
+use super::error::JsonError;
+use crate::{JsonPackBigInt, PackValue};
+
+impl JsonDecoder {
+    /// Reads a single- or double-quoted JSON5 string.
+    pub(super) fn read_str5(&mut self) -> Result<String, JsonError> {
+        let data = &self.data;
+        let start = self.x;
+        let quote = match data.get(start) {
+            Some(&q @ (b'"' | b'\'')) => q,
+            _ => return Err(JsonError::Invalid(start)),
+        };
+        let mut out = Vec::new();
+        let mut x = start + 1;
+        loop {
+            let Some(&ch) = data.get(x) else {
+                return Err(JsonError::Invalid(x));
+            };
+            x += 1;
+            match ch {
+                _ if ch == quote => break,
+                b'\n' | b'\r' => return Err(JsonError::Invalid(x - 1)),
+                b'\\' => x = read_escape(data, x, &mut out)?,
+                _ => out.push(ch),
+            }
+        }
+        let s = String::from_utf8(out).map_err(|_| JsonError::InvalidUtf8(start))?;
+        self.x = x;
+        Ok(s)
+    }
+
+    /// Reads an unquoted JSON5 object key: an ECMAScript identifier name.
+    /// Non-ASCII bytes are accepted as identifier characters.
+    pub(super) fn read_ident(&mut self) -> Result<String, JsonError> {
+        let data = &self.data;
+        let start = self.x;
+        let is_start = |c: u8| c.is_ascii_alphabetic() || c == b'_' || c == b'$' || c >= 0x80;
+        if !data.get(start).is_some_and(|&c| is_start(c)) {
+            return Err(JsonError::Invalid(start));
+        }
+        let end = data[start..]
+            .iter()
+            .position(|&c| !(is_start(c) || c.is_ascii_digit()))
+            .map_or(data.len(), |i| start + i);
+        let s =
+            std::str::from_utf8(&data[start..end]).map_err(|_| JsonError::InvalidUtf8(start))?;
+        self.x = end;
+        Ok(s.to_string())
+    }
+
+    /// Reads a JSON5 number: JSON numbers plus a leading `+`, hexadecimal
+    /// integers, leading or trailing decimal points, `Infinity` and `NaN`.
+    pub(super) fn read_num5(&mut self) -> Result<PackValue, JsonError> {
+        let data = &self.data;
+        let start = self.x;
+        let mut x = start;
+        let negative = data.get(x) == Some(&b'-');
+        if matches!(data.get(x), Some(b'+' | b'-')) {
+            x += 1;
+        }
+        let rest = &data[x..];
+        if rest.starts_with(b"Infinity") {
+            self.x = x + 8;
+            let inf = if negative {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Ok(PackValue::Float(inf));
+        }
+        if rest.starts_with(b"NaN") {
+            self.x = x + 3;
+            return Ok(PackValue::Float(f64::NAN));
+        }
+        if rest.len() >= 2 && rest[0] == b'0' && matches!(rest[1], b'x' | b'X') {
+            let digits = rest[2..]
+                .iter()
+                .position(|c| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len() - 2);
+            if digits == 0 {
+                return Err(JsonError::Invalid(start));
+            }
+            self.x = x + 2 + digits;
+            return Ok(hex_value(negative, &rest[2..2 + digits]));
+        }
+
+        // Decimal. `f64` parsing accepts `.5`, `5.` and a leading `+`; the
+        // integer parsers get the digits without the `+`.
+        let body = x;
+        let digits = |x: &mut usize| {
+            let from = *x;
+            while data.get(*x).is_some_and(u8::is_ascii_digit) {
+                *x += 1;
+            }
+            *x - from
+        };
+        let mut count = digits(&mut x);
+        let mut is_float = false;
+        if data.get(x) == Some(&b'.') {
+            is_float = true;
+            x += 1;
+            count += digits(&mut x);
+        }
+        if count == 0 {
+            return Err(JsonError::Invalid(start));
+        }
+        if matches!(data.get(x), Some(b'e' | b'E')) {
+            is_float = true;
+            x += 1;
+            if matches!(data.get(x), Some(b'+' | b'-')) {
+                x += 1;
+            }
+            if digits(&mut x) == 0 {
+                return Err(JsonError::Invalid(start));
+            }
+        }
+        self.x = x;
+        let from = if negative { start } else { body };
+        let s = std::str::from_utf8(&data[from..x]).map_err(|_| JsonError::InvalidUtf8(start))?;
+        if is_float {
+            let f: f64 = s.parse().map_err(|_| JsonError::Invalid(start))?;
+            return Ok(PackValue::Float(f));
+        }
+        s.parse::<JsonPackBigInt>()
+            .map(int_value)
+            .map_err(|_| JsonError::Invalid(start))
+    }
+}
+
+/// Decodes the escape after a `\` at `x - 1` into `out`, returning the
+/// offset after it.
+fn read_escape(data: &[u8], x: usize, out: &mut Vec<u8>) -> Result<usize, JsonError> {
+    let Some(&ch) = data.get(x) else {
+        return Err(JsonError::Invalid(x));
+    };
+    let simple = match ch {
+        b'b' => Some(0x08),
+        b'f' => Some(0x0c),
+        b'n' => Some(b'\n'),
+        b'r' => Some(b'\r'),
+        b't' => Some(b'\t'),
+        b'v' => Some(0x0b),
+        b'0' if !data.get(x + 1).is_some_and(u8::is_ascii_digit) => Some(0),
+        b'1'..=b'9' | b'0' => return Err(JsonError::Invalid(x - 1)),
+        _ => None,
+    };
+    if let Some(b) = simple {
+        out.push(b);
+        return Ok(x + 1);
+    }
+    match ch {
+        // Line continuations.
+        b'\n' => Ok(x + 1),
+        b'\r' => Ok(if data.get(x + 1) == Some(&b'\n') {
+            x + 2
+        } else {
+            x + 1
+        }),
+        b'x' => {
+            let c = hex_digits(data, x + 1, 2)?;
+            push_char(out, char::from_u32(c).unwrap());
+            Ok(x + 3)
+        }
+        b'u' => {
+            let hi = hex_digits(data, x + 1, 4)?;
+            if !(0xd800..0xdc00).contains(&hi) {
+                let c = char::from_u32(hi).ok_or(JsonError::Invalid(x - 1))?;
+                push_char(out, c);
+                return Ok(x + 5);
+            }
+            // A high surrogate must be followed by an escaped low surrogate.
+            if data.get(x + 5..x + 7) != Some(b"\\u") {
+                return Err(JsonError::Invalid(x - 1));
+            }
+            let lo = hex_digits(data, x + 7, 4)?;
+            if !(0xdc00..0xe000).contains(&lo) {
+                return Err(JsonError::Invalid(x - 1));
+            }
+            let c = 0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00);
+            push_char(out, char::from_u32(c).unwrap());
+            Ok(x + 11)
+        }
+        // Any other character stands for itself; U+2028 and U+2029 after a
+        // backslash are line continuations.
+        _ => {
+            let len = utf8_len(ch);
+            let bytes = data.get(x..x + len).ok_or(JsonError::Invalid(x))?;
+            if bytes != "\u{2028}".as_bytes() && bytes != "\u{2029}".as_bytes() {
+                out.extend_from_slice(bytes);
+            }
+            Ok(x + len)
+        }
+    }
+}
+
+fn hex_digits(data: &[u8], x: usize, n: usize) -> Result<u32, JsonError> {
+    let digits = data.get(x..x + n).ok_or(JsonError::Invalid(x))?;
+    digits.iter().try_fold(0, |acc, &c| {
+        let d = (c as char).to_digit(16).ok_or(JsonError::Invalid(x))?;
+        Ok(acc * 16 + d)
+    })
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Length of the UTF-8 sequence starting with `b`; 1 for invalid leads,
+/// which the final UTF-8 check rejects.
+fn utf8_len(b: u8) -> usize {
+    match b {
+        0xf0..=0xf7 => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+}
+
+fn hex_value(negative: bool, digits: &[u8]) -> PackValue {
+    // Two hex digits per magnitude byte, padded on the left.
+    let mut magnitude = vec![0u8; digits.len().div_ceil(2)];
+    let pad = magnitude.len() * 2 - digits.len();
+    for (i, c) in digits.iter().enumerate() {
+        let d = (*c as char).to_digit(16).unwrap() as u8;
+        let n = pad + i;
+        magnitude[n / 2] |= if n.is_multiple_of(2) { d << 4 } else { d };
+    }
+    int_value(JsonPackBigInt::new(negative, &magnitude))
+}
+
+/// Narrowest `PackValue` for an integer, as the strict decoder picks.
+fn int_value(n: JsonPackBigInt) -> PackValue {
+    match n.to_i128() {
+        Some(i) => match (i64::try_from(i), u64::try_from(i)) {
+            (Ok(i), _) => PackValue::Integer(i),
+            (_, Ok(u)) => PackValue::UInteger(u),
+            _ => PackValue::BigInt(i),
+        },
+        None => PackValue::BigNum(n),
+    }
+}
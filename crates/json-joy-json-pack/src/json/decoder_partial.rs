@@ -9,9 +9,15 @@
 //! - When a nested structure (array/object) is incomplete, returns the partial structure.
 //! - When a child element is completely corrupt/invalid, drops it and returns the parent.
 //! - When input ends unexpectedly, returns what was collected so far.
+//!
+//! Rust-only additions: [`JsonDecoderPartialOptions`] can keep unterminated
+//! strings and put `null` in place of dropped values, and
+//! [`JsonDecoderPartial::cut`] reports where the last decode stopped and
+//! whether the input was merely incomplete or corrupt.
 
-use super::decoder::JsonDecoder;
+use super::decoder::{decode_json_string, JsonDecoder};
 use super::error::JsonError;
+use super::util::find_ending_quote;
 use crate::PackValue;
 
 /// Carries a partially-decoded value up the call stack.
@@ -19,8 +25,44 @@ use crate::PackValue;
 #[derive(Debug)]
 struct FinishError(Option<PackValue>);
 
+/// Repairs applied by [`JsonDecoderPartial`] to values it cannot finish.
+///
+/// The default applies none, matching upstream: unfinished and corrupt
+/// values are dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonDecoderPartialOptions {
+    /// Keep a string cut off by the end of input as the text read so far.
+    /// A dangling escape or UTF-8 sequence at the cut is left out.
+    pub keep_partial_strings: bool,
+    /// Insert `null` for an array element or object member value that was
+    /// cut off or corrupt, and for a member whose key was read but whose
+    /// value is missing. The top-level value is never replaced.
+    pub null_placeholders: bool,
+}
+
+/// Why [`JsonDecoderPartial`] stopped before the end of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartialCutReason {
+    /// The input ended inside the document; more input can complete it.
+    Incomplete,
+    /// A byte that no continuation can make valid.
+    Corrupt,
+}
+
+/// Where and why [`JsonDecoderPartial`] stopped reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialCut {
+    /// Offset of the first byte not read into the value: the start of the
+    /// unfinished or corrupt token, or the input length when the input
+    /// ended between tokens.
+    pub offset: usize,
+    pub reason: PartialCutReason,
+}
+
 pub struct JsonDecoderPartial {
     pub inner: JsonDecoder,
+    pub options: JsonDecoderPartialOptions,
+    cut: Option<PartialCut>,
 }
 
 impl Default for JsonDecoderPartial {
@@ -31,14 +73,21 @@ impl Default for JsonDecoderPartial {
 
 impl JsonDecoderPartial {
     pub fn new() -> Self {
+        Self::with_options(JsonDecoderPartialOptions::default())
+    }
+
+    pub fn with_options(options: JsonDecoderPartialOptions) -> Self {
         Self {
             inner: JsonDecoder::new(),
+            options,
+            cut: None,
         }
     }
 
     pub fn decode(&mut self, input: &[u8]) -> Result<PackValue, JsonError> {
         self.inner.data = input.to_vec();
         self.inner.x = 0;
+        self.cut = None;
         match self.read_any_partial() {
             Ok(v) => Ok(v),
             Err(FinishError(Some(v))) => Ok(v),
@@ -46,21 +95,96 @@ impl JsonDecoderPartial {
         }
     }
 
+    /// Where the last [`decode`](Self::decode) stopped, or `None` if it read
+    /// a complete value. Also set when `decode` failed.
+    pub fn cut(&self) -> Option<PartialCut> {
+        self.cut
+    }
+
+    /// Records the first place the document stopped making sense.
+    fn set_cut(&mut self, offset: usize, reason: PartialCutReason) {
+        if self.cut.is_none() {
+            self.cut = Some(PartialCut { offset, reason });
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.inner.x >= self.inner.data.len()
+    }
+
     /// Read any value using partial (fault-tolerant) parsing.
     /// Dispatches nested arrays/objects to partial readers instead of the base decoder.
     fn read_any_partial(&mut self) -> Result<PackValue, FinishError> {
         self.inner.skip_whitespace();
-        if self.inner.x >= self.inner.data.len() {
+        if self.at_end() {
+            self.set_cut(self.inner.x, PartialCutReason::Incomplete);
             return Err(FinishError(None));
         }
-        let ch = self.inner.data[self.inner.x];
+        let start = self.inner.x;
+        let ch = self.inner.data[start];
         match ch {
-            b'[' => self.read_arr().map_err(|_| FinishError(None)),
-            b'{' => self.read_obj().map_err(|_| FinishError(None)),
-            _ => self.inner.read_any().map_err(|_| FinishError(None)),
+            b'[' | b'{' => {
+                let res = if ch == b'[' {
+                    self.read_arr()
+                } else {
+                    self.read_obj()
+                };
+                res.map_err(|_| {
+                    // A nested `__proto__` key.
+                    self.set_cut(start, PartialCutReason::Corrupt);
+                    FinishError(None)
+                })
+            }
+            _ => match self.inner.read_any() {
+                Ok(v) => Ok(v),
+                Err(_) => {
+                    if !self.is_cut_off(start) {
+                        self.set_cut(start, PartialCutReason::Corrupt);
+                        return Err(FinishError(None));
+                    }
+                    self.set_cut(start, PartialCutReason::Incomplete);
+                    if ch == b'"' && self.options.keep_partial_strings {
+                        if let Some(s) = self.read_partial_str(start) {
+                            self.inner.x = self.inner.data.len();
+                            return Ok(PackValue::Str(s));
+                        }
+                    }
+                    Err(FinishError(None))
+                }
+            },
+        }
+    }
+
+    /// Whether the scalar at `start` failed only because the input ended,
+    /// i.e. the rest of the input is a prefix of some valid token.
+    fn is_cut_off(&self, start: usize) -> bool {
+        let rest = &self.inner.data[start..];
+        match rest[0] {
+            b'"' => find_ending_quote(&self.inner.data, start + 1).is_err(),
+            b't' => b"true".starts_with(rest),
+            b'f' => b"false".starts_with(rest),
+            b'n' => b"null".starts_with(rest),
+            b'-' | b'0'..=b'9' => rest
+                .iter()
+                .all(|c| matches!(c, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')),
+            _ => false,
         }
     }
 
+    /// Decodes the body of an unterminated string, dropping up to one
+    /// incomplete escape (`\`, `\u12`, a lone high surrogate) or UTF-8
+    /// sequence at the end.
+    fn read_partial_str(&self, start: usize) -> Option<String> {
+        let body = &self.inner.data[start + 1..];
+        (0..=body.len().min(12))
+            .find_map(|trim| decode_json_string(&body[..body.len() - trim], start + 1).ok())
+    }
+
+    /// Placeholder for a dropped array element or member value.
+    fn placeholder(&self) -> Option<PackValue> {
+        self.options.null_placeholders.then_some(PackValue::Null)
+    }
+
     pub fn read_arr(&mut self) -> Result<PackValue, JsonError> {
         if self.inner.x >= self.inner.data.len() || self.inner.data[self.inner.x] != b'[' {
             return Err(JsonError::Invalid(self.inner.x));
@@ -70,8 +194,9 @@ impl JsonDecoderPartial {
         let mut first = true;
         loop {
             self.inner.skip_whitespace();
-            if self.inner.x >= self.inner.data.len() {
+            if self.at_end() {
                 // End of input — return what we have
+                self.set_cut(self.inner.x, PartialCutReason::Incomplete);
                 return Ok(PackValue::Array(arr));
             }
             let ch = self.inner.data[self.inner.x];
@@ -83,9 +208,15 @@ impl JsonDecoderPartial {
                 self.inner.x += 1;
             } else if !first {
                 // Not a comma and not `]` — no valid separator → return what we have
+                self.set_cut(self.inner.x, PartialCutReason::Corrupt);
                 return Ok(PackValue::Array(arr));
             }
             self.inner.skip_whitespace();
+            if self.at_end() {
+                // Nothing after the comma yet — there is no element to replace.
+                self.set_cut(self.inner.x, PartialCutReason::Incomplete);
+                return Ok(PackValue::Array(arr));
+            }
             match self.read_any_partial() {
                 Ok(v) => arr.push(v),
                 Err(FinishError(Some(v))) => {
@@ -95,6 +226,7 @@ impl JsonDecoderPartial {
                 }
                 Err(FinishError(None)) => {
                     // Element completely invalid — drop it and return what we have
+                    arr.extend(self.placeholder());
                     return Ok(PackValue::Array(arr));
                 }
             }
@@ -110,7 +242,8 @@ impl JsonDecoderPartial {
         let mut obj: Vec<(String, PackValue)> = Vec::new();
         loop {
             self.inner.skip_whitespace();
-            if self.inner.x >= self.inner.data.len() {
+            if self.at_end() {
+                self.set_cut(self.inner.x, PartialCutReason::Incomplete);
                 return Ok(PackValue::Object(obj));
             }
             let ch = self.inner.data[self.inner.x];
@@ -124,18 +257,37 @@ impl JsonDecoderPartial {
             }
             // Read key
             if ch != b'"' {
+                self.set_cut(self.inner.x, PartialCutReason::Corrupt);
                 return Ok(PackValue::Object(obj));
             }
+            let key_start = self.inner.x;
             let key = match self.inner.read_key() {
                 Ok(k) => k,
-                Err(_) => return Ok(PackValue::Object(obj)),
+                Err(_) => {
+                    let reason = if self.is_cut_off(key_start) {
+                        PartialCutReason::Incomplete
+                    } else {
+                        PartialCutReason::Corrupt
+                    };
+                    self.set_cut(key_start, reason);
+                    return Ok(PackValue::Object(obj));
+                }
             };
             if key == "__proto__" {
                 return Err(JsonError::InvalidKey);
             }
             self.inner.skip_whitespace();
-            if self.inner.x >= self.inner.data.len() || self.inner.data[self.inner.x] != b':' {
+            if self.at_end() || self.inner.data[self.inner.x] != b':' {
                 // Key with no value — drop this entry and return what we have
+                let reason = if self.at_end() {
+                    PartialCutReason::Incomplete
+                } else {
+                    PartialCutReason::Corrupt
+                };
+                self.set_cut(self.inner.x, reason);
+                if let Some(v) = self.placeholder() {
+                    obj.push((key, v));
+                }
                 return Ok(PackValue::Object(obj));
             }
             self.inner.x += 1;
@@ -149,6 +301,9 @@ impl JsonDecoderPartial {
                 }
                 Err(FinishError(None)) => {
                     // Value completely invalid — drop this key-value and return
+                    if let Some(v) = self.placeholder() {
+                        obj.push((key, v));
+                    }
                     return Ok(PackValue::Object(obj));
                 }
             }
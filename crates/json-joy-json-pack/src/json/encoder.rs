@@ -9,7 +9,11 @@
 
 use json_joy_buffers::Writer;
 
-use crate::PackValue;
+use crate::checked::{self, EncodeLossError};
+use crate::pack_value::text_keyed;
+use crate::token::TokenSink;
+use crate::util::format_f64_js;
+use crate::{JsonPackBigInt, PackValue};
 
 /// CBOR undefined encoded as `"data:application/cbor,base64;9w=="`
 /// (37 bytes total including surrounding quotes).
@@ -18,8 +22,33 @@ const UNDEF_STR: &[u8] = b"\"data:application/cbor,base64;9w==\"";
 /// `data:application/octet-stream;base64,` prefix (38 bytes).
 const BIN_URI_PREFIX: &[u8] = b"\"data:application/octet-stream;base64,";
 
+/// How big integers ([`PackValue::BigInt`] and [`PackValue::BigNum`]) are
+/// written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonBigIntPolicy {
+    /// Bare JSON number literal (upstream behaviour). Lossless in JSON, but
+    /// many parsers round such numbers to `f64`.
+    #[default]
+    Number,
+    /// Decimal digits inside a JSON string, e.g. `"123456789012345678901"`.
+    String,
+}
+
+/// Options controlling `JsonEncoder` output.
+#[derive(Debug, Clone, Default)]
+pub struct JsonEncoderOptions {
+    pub big_int: JsonBigIntPolicy,
+}
+
 pub struct JsonEncoder {
     pub writer: Writer,
+    pub options: JsonEncoderOptions,
+    /// Containers open via [`TokenSink`]: `(is_map, has_items)`.
+    token_frames: Vec<(bool, bool)>,
+    /// Set after a tag: the next value is replaced by `null`.
+    token_tagged: bool,
+    /// Depth of the skipped container being replaced by `null`.
+    token_skip: usize,
 }
 
 impl Default for JsonEncoder {
@@ -32,6 +61,30 @@ impl JsonEncoder {
     pub fn new() -> Self {
         Self {
             writer: Writer::new(),
+            options: JsonEncoderOptions::default(),
+            token_frames: Vec::new(),
+            token_tagged: false,
+            token_skip: 0,
+        }
+    }
+
+    pub fn with_options(options: JsonEncoderOptions) -> Self {
+        Self {
+            writer: Writer::new(),
+            options,
+            token_frames: Vec::new(),
+            token_tagged: false,
+            token_skip: 0,
+        }
+    }
+
+    pub fn with_writer(writer: Writer) -> Self {
+        Self {
+            writer,
+            options: JsonEncoderOptions::default(),
+            token_frames: Vec::new(),
+            token_tagged: false,
+            token_skip: 0,
         }
     }
 
@@ -41,6 +94,14 @@ impl JsonEncoder {
         self.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on a value that would be
+    /// written as `null` or clamped: a non-finite float, an extension or a
+    /// blob.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::json_loss, checked::Keys::Text)?;
+        Ok(self.encode(value))
+    }
+
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.writer.reset();
         self.write_json(value);
@@ -56,10 +117,12 @@ impl JsonEncoder {
             PackValue::UInteger(u) => self.write_u_integer(*u),
             PackValue::Float(f) => self.write_float(*f),
             PackValue::BigInt(i) => self.write_big_int(*i),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(_) | PackValue::Blob(_) => self.write_null(),
         }
     }
@@ -131,7 +194,6 @@ impl JsonEncoder {
     }
 
     pub fn write_number(&mut self, num: f64) {
-        // Use Rust's default float-to-string which produces minimal representation
         let s = format_float(num);
         self.writer.ascii(&s);
     }
@@ -149,7 +211,22 @@ impl JsonEncoder {
     }
 
     pub fn write_big_int(&mut self, int: i128) {
-        self.writer.ascii(&int.to_string());
+        self.write_big_digits(&int.to_string());
+    }
+
+    pub fn write_big_num(&mut self, num: &JsonPackBigInt) {
+        self.write_big_digits(&num.to_string());
+    }
+
+    fn write_big_digits(&mut self, digits: &str) {
+        match self.options.big_int {
+            JsonBigIntPolicy::Number => self.writer.ascii(digits),
+            JsonBigIntPolicy::String => {
+                self.writer.u8(b'"');
+                self.writer.ascii(digits);
+                self.writer.u8(b'"');
+            }
+        }
     }
 
     /// Write binary data as a data URI JSON string:
@@ -260,6 +337,158 @@ impl JsonEncoder {
     }
 }
 
+impl JsonEncoder {
+    /// Whether a value event should be written; if so, writes the array
+    /// separator it needs.
+    fn token_value(&mut self) -> bool {
+        if self.token_skip > 0 {
+            return false;
+        }
+        if self.token_tagged {
+            self.token_tagged = false;
+            return false;
+        }
+        if let Some((map, has_items)) = self.token_frames.last_mut() {
+            if !*map {
+                if *has_items {
+                    self.writer.u8(b',');
+                }
+                *has_items = true;
+            }
+        }
+        true
+    }
+
+    fn token_open(&mut self, map: bool) {
+        if self.token_skip == 0 && self.token_tagged {
+            self.token_tagged = false;
+            self.token_skip = 1;
+        } else if self.token_skip > 0 {
+            self.token_skip += 1;
+        } else {
+            self.token_value();
+            self.writer.u8(if map { b'{' } else { b'[' });
+            self.token_frames.push((map, false));
+        }
+    }
+
+    fn token_close(&mut self) {
+        if self.token_skip > 0 {
+            self.token_skip -= 1;
+        } else if let Some((map, _)) = self.token_frames.pop() {
+            self.writer.u8(if map { b'}' } else { b']' });
+        }
+    }
+}
+
+/// Token events map onto the `write_*` methods. JSON has no tags, so a
+/// tagged value is written as `null`, as are raw blobs, matching
+/// [`JsonEncoder::write_any`].
+impl TokenSink for JsonEncoder {
+    fn on_null(&mut self) {
+        if self.token_value() {
+            self.write_null();
+        }
+    }
+
+    fn on_undefined(&mut self) {
+        if self.token_value() {
+            self.write_undef();
+        }
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        if self.token_value() {
+            self.write_boolean(value);
+        }
+    }
+
+    fn on_int(&mut self, value: i64) {
+        if self.token_value() {
+            self.write_integer(value);
+        }
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        if self.token_value() {
+            self.write_u_integer(value);
+        }
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        if self.token_value() {
+            self.write_big_int(value);
+        }
+    }
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        if self.token_value() {
+            self.write_big_num(value);
+        }
+    }
+
+    fn on_float(&mut self, value: f64) {
+        if self.token_value() {
+            self.write_float(value);
+        }
+    }
+
+    fn on_str(&mut self, value: &str) {
+        if self.token_value() {
+            self.write_str(value);
+        }
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        if self.token_value() {
+            self.write_bin(value);
+        }
+    }
+
+    fn on_array_start(&mut self, _len: Option<usize>) {
+        self.token_open(false);
+    }
+
+    fn on_array_end(&mut self) {
+        self.token_close();
+    }
+
+    fn on_map_start(&mut self, _len: Option<usize>) {
+        self.token_open(true);
+    }
+
+    fn on_key(&mut self, key: &str) {
+        if self.token_skip > 0 {
+            return;
+        }
+        if let Some((_, has_items)) = self.token_frames.last_mut() {
+            if *has_items {
+                self.writer.u8(b',');
+            }
+            *has_items = true;
+        }
+        self.write_str(key);
+        self.writer.u8(b':');
+    }
+
+    fn on_map_end(&mut self) {
+        self.token_close();
+    }
+
+    fn on_tag(&mut self, _tag: u64) {
+        if self.token_skip == 0 && !self.token_tagged && self.token_value() {
+            self.write_null();
+            self.token_tagged = true;
+        }
+    }
+
+    fn on_raw(&mut self, _bytes: &[u8]) {
+        if self.token_value() {
+            self.write_null();
+        }
+    }
+}
+
 fn format_float(f: f64) -> String {
     if f.is_nan() {
         "null".to_string()
@@ -269,10 +498,7 @@ fn format_float(f: f64) -> String {
         } else {
             "-1e308".to_string()
         }
-    } else if f.fract() == 0.0 && f.abs() < 1e15 {
-        format!("{}", f as i64)
     } else {
-        // Use Rust's default float repr (shortest round-trip representation)
-        format!("{}", f)
+        format_f64_js(f)
     }
 }
@@ -8,6 +8,7 @@
 use json_joy_base64::to_base64_bin;
 
 use super::encoder_stable::JsonEncoderStable;
+use crate::checked::{self, EncodeLossError};
 use crate::PackValue;
 
 // "{"/":{"bytes":""}}" = 18 bytes
@@ -38,6 +39,14 @@ impl JsonEncoderDag {
         self.inner.inner.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on a value that would be
+    /// written as `null` or clamped, as
+    /// [`JsonEncoder::try_encode`](super::JsonEncoder::try_encode).
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::json_loss, checked::Keys::Text)?;
+        Ok(self.encode(value))
+    }
+
     pub fn write_any(&mut self, value: &PackValue) {
         match value {
             PackValue::Bytes(b) => self.write_bin(b),
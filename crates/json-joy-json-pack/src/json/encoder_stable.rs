@@ -3,10 +3,14 @@
 //! Direct port of `json/JsonEncoderStable.ts` from upstream.
 
 use super::encoder::JsonEncoder;
-use crate::PackValue;
+use crate::checked::{self, EncodeLossError};
+use crate::pack_value::text_keyed;
+use crate::{KeyOrder, PackValue};
 
 pub struct JsonEncoderStable {
     pub inner: JsonEncoder,
+    /// Object key order; defaults to [`KeyOrder::LengthLex`] like upstream.
+    pub key_order: KeyOrder,
 }
 
 impl Default for JsonEncoderStable {
@@ -17,8 +21,13 @@ impl Default for JsonEncoderStable {
 
 impl JsonEncoderStable {
     pub fn new() -> Self {
+        Self::with_key_order(KeyOrder::LengthLex)
+    }
+
+    pub fn with_key_order(key_order: KeyOrder) -> Self {
         Self {
             inner: JsonEncoder::new(),
+            key_order,
         }
     }
 
@@ -28,6 +37,13 @@ impl JsonEncoderStable {
         self.inner.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on a value that would be
+    /// written as `null` or clamped, as [`JsonEncoder::try_encode`].
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        checked::check(value, &checked::json_loss, checked::Keys::Text)?;
+        Ok(self.encode(value))
+    }
+
     pub fn write_any(&mut self, value: &PackValue) {
         match value {
             PackValue::Null => self.inner.write_null(),
@@ -37,10 +53,12 @@ impl JsonEncoderStable {
             PackValue::UInteger(u) => self.inner.write_u_integer(*u),
             PackValue::Float(f) => self.inner.write_float(*f),
             PackValue::BigInt(i) => self.inner.write_big_int(*i),
+            PackValue::BigNum(n) => self.inner.write_big_num(n),
             PackValue::Bytes(b) => self.inner.write_bin(b),
             PackValue::Str(s) => self.inner.write_str(s),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(_) | PackValue::Blob(_) => self.inner.write_null(),
         }
     }
@@ -57,28 +75,14 @@ impl JsonEncoderStable {
         self.inner.writer.u8(b']');
     }
 
-    /// Write object with keys sorted by length, then lexicographically.
+    /// Write object with keys sorted by `self.key_order`.
     pub fn write_obj(&mut self, obj: &[(String, PackValue)]) {
         if obj.is_empty() {
             self.inner.writer.u8(b'{');
             self.inner.writer.u8(b'}');
             return;
         }
-        // Collect and sort indices by key: length (Unicode scalar count) then lexicographic.
-        // Uses chars().count() to mirror upstream JavaScript's string .length (UTF-16 code units)
-        // for BMP characters.
-        let mut indices: Vec<usize> = (0..obj.len()).collect();
-        indices.sort_by(|&a, &b| {
-            let ka = &obj[a].0;
-            let kb = &obj[b].0;
-            let la = ka.chars().count();
-            let lb = kb.chars().count();
-            if la == lb {
-                ka.cmp(kb)
-            } else {
-                la.cmp(&lb)
-            }
-        });
+        let indices = self.key_order.sorted_indices(obj);
 
         self.inner.writer.u8(b'{');
         let last = indices.len() - 1;
@@ -6,10 +6,20 @@ use thiserror::Error;
 pub enum JsonError {
     #[error("invalid JSON at byte {0}")]
     Invalid(usize),
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("invalid UTF-8 at byte {0}")]
+    InvalidUtf8(usize),
     #[error("invalid key `__proto__`")]
     InvalidKey,
     #[error("parse error: {0}")]
     Parse(#[from] serde_json::Error),
 }
+
+impl JsonError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Invalid(x) | Self::InvalidUtf8(x) => Some(*x),
+            Self::InvalidKey | Self::Parse(_) => None,
+        }
+    }
+}
@@ -4,6 +4,7 @@
 
 pub mod decoder;
 pub mod decoder_dag;
+mod decoder_json5;
 pub mod decoder_partial;
 pub mod encoder;
 pub mod encoder_dag;
@@ -12,10 +13,12 @@ pub mod error;
 pub mod types;
 pub mod util;
 
-pub use decoder::JsonDecoder;
+pub use decoder::{JsonDecoder, JsonDecoderOptions};
 pub use decoder_dag::JsonDecoderDag;
-pub use decoder_partial::JsonDecoderPartial;
-pub use encoder::JsonEncoder;
+pub use decoder_partial::{
+    JsonDecoderPartial, JsonDecoderPartialOptions, PartialCut, PartialCutReason,
+};
+pub use encoder::{JsonBigIntPolicy, JsonEncoder, JsonEncoderOptions};
 pub use encoder_dag::JsonEncoderDag;
 pub use encoder_stable::JsonEncoderStable;
 pub use error::JsonError;
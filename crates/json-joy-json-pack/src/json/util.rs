@@ -10,7 +10,18 @@ use super::error::JsonError;
 /// index is the position of the closing `"` (exclusive of the contents).
 ///
 /// Handles backslash escaping: `\"` inside the string does not terminate it.
-pub fn find_ending_quote(data: &[u8], mut x: usize) -> Result<usize, JsonError> {
+/// An unterminated string reports the end of the input as the error offset
+/// (or `x` itself when it is already past the end), with or without `simd`.
+#[inline]
+pub fn find_ending_quote(data: &[u8], x: usize) -> Result<usize, JsonError> {
+    #[cfg(feature = "simd")]
+    return find_ending_quote_simd(data, x);
+    #[cfg(not(feature = "simd"))]
+    find_ending_quote_scalar(data, x)
+}
+
+#[cfg(any(not(feature = "simd"), test))]
+fn find_ending_quote_scalar(data: &[u8], mut x: usize) -> Result<usize, JsonError> {
     let len = data.len();
     let mut prev: u8 = 0;
     while x < len {
@@ -28,3 +39,65 @@ pub fn find_ending_quote(data: &[u8], mut x: usize) -> Result<usize, JsonError>
     }
     Err(JsonError::Invalid(x))
 }
+
+/// Jumps straight to the next `"` or `\`; every other byte only clears the
+/// pending-escape state.
+#[cfg(feature = "simd")]
+fn find_ending_quote_simd(data: &[u8], start: usize) -> Result<usize, JsonError> {
+    let mut x = start;
+    let mut escaped = false;
+    while x < data.len() {
+        let Some(i) = memchr::memchr2(b'"', b'\\', &data[x..]) else {
+            break;
+        };
+        let p = x + i;
+        if p > x {
+            escaped = false;
+        }
+        if data[p] == b'"' {
+            if !escaped {
+                return Ok(p);
+            }
+            escaped = false;
+        } else {
+            escaped = !escaped;
+        }
+        x = p + 1;
+    }
+    // Same offset the scalar loop stops at.
+    Err(JsonError::Invalid(data.len().max(start)))
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::{find_ending_quote_scalar, find_ending_quote_simd, JsonError};
+
+    #[test]
+    fn simd_matches_scalar() {
+        let long = format!("\"{}\\\"{}", "a".repeat(40), "b".repeat(20));
+        let cases: [&[u8]; 12] = [
+            b"",
+            b"\"",
+            b"\"abc\"",
+            b"\"a\\\"",
+            b"\"a\\\\\"",
+            b"\"a\\\\\\\"b",
+            b"\"unterminated",
+            b"\"trailing\\",
+            b"\"\\x\"",
+            b"\"x\\\\\\\\\"",
+            long.as_bytes(),
+            b"\"0123456789abcdef0123456789abcdef\\\"x\"",
+        ];
+        for data in cases {
+            for x in 0..=data.len() + 1 {
+                let offset = |r: Result<usize, _>| r.map_err(|e: JsonError| e.offset());
+                assert_eq!(
+                    offset(find_ending_quote_simd(data, x)),
+                    offset(find_ending_quote_scalar(data, x)),
+                    "{data:?} at {x}"
+                );
+            }
+        }
+    }
+}
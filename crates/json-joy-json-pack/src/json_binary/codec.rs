@@ -23,6 +23,7 @@ pub fn wrap_binary(value: PackValue) -> JsonValue {
         PackValue::UInteger(u) => serde_json::json!(u),
         PackValue::Float(f) => serde_json::json!(f),
         PackValue::BigInt(i) => serde_json::json!(i),
+        PackValue::BigNum(n) => JsonValue::String(n.to_string()),
         PackValue::Str(s) => JsonValue::String(s),
         PackValue::Bytes(b) => {
             let uri = format!("{}{}", BIN_URI_START, json_joy_base64::to_base64(&b));
@@ -52,6 +53,11 @@ pub fn wrap_binary(value: PackValue) -> JsonValue {
         PackValue::Object(obj) => {
             JsonValue::Object(obj.into_iter().map(|(k, v)| (k, wrap_binary(v))).collect())
         }
+        PackValue::Map(map) => JsonValue::Object(
+            map.into_iter()
+                .map(|(k, v)| (k.to_key_string(), wrap_binary(v)))
+                .collect(),
+        ),
     }
 }
 
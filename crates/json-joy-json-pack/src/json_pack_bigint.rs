@@ -0,0 +1,289 @@
+//! [`JsonPackBigInt`] — arbitrary-precision integer.
+//!
+//! Upstream uses native JS `bigint`, which is unbounded; `PackValue::BigInt`
+//! is an `i128`, so this type carries integers that do not fit in it (e.g.
+//! CBOR bignums, tags 2/3, and cryptographic key material).
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// An arbitrary-precision integer stored as sign + big-endian magnitude.
+///
+/// The magnitude never has leading zero bytes, and zero is always
+/// non-negative with an empty magnitude, so structural equality is numeric
+/// equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPackBigInt {
+    negative: bool,
+    magnitude: Vec<u8>,
+}
+
+/// Decimal digits handled per step when converting to and from text.
+const CHUNK_DIGITS: usize = 9;
+const CHUNK: u64 = 1_000_000_000;
+
+/// Error returned when parsing a [`JsonPackBigInt`] from a decimal string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid big integer literal")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseBigIntError {}
+
+impl JsonPackBigInt {
+    /// Create from a sign and a big-endian magnitude.
+    pub fn new(negative: bool, magnitude: &[u8]) -> Self {
+        let start = magnitude
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(magnitude.len());
+        let magnitude = magnitude[start..].to_vec();
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    /// Big-endian magnitude without leading zero bytes.
+    pub fn magnitude(&self) -> &[u8] {
+        &self.magnitude
+    }
+
+    pub fn from_i128(value: i128) -> Self {
+        Self::new(value < 0, &value.unsigned_abs().to_be_bytes())
+    }
+
+    pub fn from_u128(value: u128) -> Self {
+        Self::new(false, &value.to_be_bytes())
+    }
+
+    /// Convert to an `i128`, or `None` if the value is out of range.
+    pub fn to_i128(&self) -> Option<i128> {
+        let abs = self.to_u128_magnitude()?;
+        if self.negative {
+            if abs <= i128::MAX as u128 + 1 {
+                Some((abs as i128).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i128::try_from(abs).ok()
+        }
+    }
+
+    /// Convert to a `u128`, or `None` if the value is negative or out of range.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.negative {
+            return None;
+        }
+        self.to_u128_magnitude()
+    }
+
+    fn to_u128_magnitude(&self) -> Option<u128> {
+        if self.magnitude.len() > 16 {
+            return None;
+        }
+        Some(
+            self.magnitude
+                .iter()
+                .fold(0u128, |acc, &b| (acc << 8) | b as u128),
+        )
+    }
+
+    /// Decode the payload of a CBOR bignum (tag 2 positive, tag 3 negative).
+    ///
+    /// Returns `None` for any other tag.
+    pub fn from_cbor_bignum(tag: u64, bytes: &[u8]) -> Option<Self> {
+        match tag {
+            2 => Some(Self::new(false, bytes)),
+            // Tag 3 encodes `-1 - n`.
+            3 => {
+                let mut magnitude = bytes.to_vec();
+                add_one(&mut magnitude);
+                Some(Self::new(true, &magnitude))
+            }
+            _ => None,
+        }
+    }
+
+    /// CBOR tag (2 or 3) and byte-string payload for this value.
+    pub fn to_cbor_bignum(&self) -> (u64, Vec<u8>) {
+        if self.negative {
+            let mut payload = self.magnitude.clone();
+            sub_one(&mut payload);
+            let payload = Self::new(false, &payload).magnitude;
+            (3, payload)
+        } else {
+            (2, self.magnitude.clone())
+        }
+    }
+
+    /// Decode a big-endian two's complement byte string.
+    ///
+    /// An empty slice decodes as zero.
+    pub fn from_twos_complement(bytes: &[u8]) -> Self {
+        if bytes.first().is_some_and(|&b| b & 0x80 != 0) {
+            let mut magnitude: Vec<u8> = bytes.iter().map(|b| !b).collect();
+            add_one(&mut magnitude);
+            Self::new(true, &magnitude)
+        } else {
+            Self::new(false, bytes)
+        }
+    }
+
+    /// Encode as the shortest big-endian two's complement byte string.
+    ///
+    /// Zero encodes as an empty byte string.
+    pub fn to_twos_complement(&self) -> Vec<u8> {
+        if self.is_zero() {
+            return Vec::new();
+        }
+        if !self.negative {
+            let mut out = Vec::with_capacity(self.magnitude.len() + 1);
+            if self.magnitude[0] & 0x80 != 0 {
+                out.push(0);
+            }
+            out.extend_from_slice(&self.magnitude);
+            return out;
+        }
+        let mut out = Vec::with_capacity(self.magnitude.len() + 1);
+        out.push(0);
+        out.extend_from_slice(&self.magnitude);
+        for b in out.iter_mut() {
+            *b = !*b;
+        }
+        add_one(&mut out);
+        while out.len() > 1 && out[0] == 0xff && out[1] & 0x80 != 0 {
+            out.remove(0);
+        }
+        out
+    }
+}
+
+impl From<i128> for JsonPackBigInt {
+    fn from(value: i128) -> Self {
+        Self::from_i128(value)
+    }
+}
+
+impl From<u128> for JsonPackBigInt {
+    fn from(value: u128) -> Self {
+        Self::from_u128(value)
+    }
+}
+
+impl fmt::Display for JsonPackBigInt {
+    /// Divides out nine decimal digits at a time over 32-bit limbs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        // Little-endian base-2^32 limbs of the magnitude.
+        let mut limbs: Vec<u32> = self
+            .magnitude
+            .rchunks(4)
+            .map(|chunk| chunk.iter().fold(0, |acc, &b| (acc << 8) | b as u32))
+            .collect();
+        // Little-endian base-10^9 chunks of the decimal form.
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            let mut rem = 0u64;
+            for limb in limbs.iter_mut().rev() {
+                let cur = (rem << 32) | *limb as u64;
+                *limb = (cur / CHUNK) as u32;
+                rem = cur % CHUNK;
+            }
+            chunks.push(rem as u32);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPackBigInt {
+    type Err = ParseBigIntError;
+
+    /// Parse a decimal integer with an optional leading `-` or `+`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() {
+            return Err(ParseBigIntError);
+        }
+        // Little-endian base-2^32 limbs, fed nine digits at a time.
+        let mut limbs: Vec<u32> = Vec::new();
+        for chunk in digits.as_bytes().chunks(CHUNK_DIGITS) {
+            let mut value = 0u64;
+            for &ch in chunk {
+                if !ch.is_ascii_digit() {
+                    return Err(ParseBigIntError);
+                }
+                value = value * 10 + (ch - b'0') as u64;
+            }
+            let scale = 10u64.pow(chunk.len() as u32);
+            let mut carry = value;
+            for limb in limbs.iter_mut() {
+                let cur = *limb as u64 * scale + carry;
+                *limb = cur as u32;
+                carry = cur >> 32;
+            }
+            if carry > 0 {
+                limbs.push(carry as u32);
+            }
+        }
+        let magnitude: Vec<u8> = limbs.iter().rev().flat_map(|l| l.to_be_bytes()).collect();
+        Ok(Self::new(negative, &magnitude))
+    }
+}
+
+/// Add one to a big-endian unsigned byte string, growing it on overflow.
+fn add_one(bytes: &mut Vec<u8>) {
+    for b in bytes.iter_mut().rev() {
+        let (v, overflow) = b.overflowing_add(1);
+        *b = v;
+        if !overflow {
+            return;
+        }
+    }
+    bytes.insert(0, 1);
+}
+
+/// Subtract one from a non-zero big-endian unsigned byte string.
+fn sub_one(bytes: &mut [u8]) {
+    for b in bytes.iter_mut().rev() {
+        let (v, underflow) = b.overflowing_sub(1);
+        *b = v;
+        if !underflow {
+            return;
+        }
+    }
+}
@@ -3,12 +3,16 @@
 //! Mirrors `JsonPackExtension.ts` from upstream.
 
 use crate::PackValue;
+use alloc::boxed::Box;
 
 /// A wrapper for MessagePack extension or CBOR tag value.
 ///
 /// When an encoder encounters a [`JsonPackExtension`] it will encode it as a
 /// MessagePack extension or CBOR tag. Likewise, the decoder will decode
 /// extensions into [`JsonPackExtension`].
+///
+/// MessagePack extension types are signed bytes, held in `tag`
+/// sign-extended: type -1 is `-1i64 as u64`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct JsonPackExtension {
     pub tag: u64,
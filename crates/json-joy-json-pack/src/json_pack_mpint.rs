@@ -1,6 +1,11 @@
 //! [`JsonPackMpint`] — SSH multiprecision integer.
 //!
-//! Mirrors `JsonPackMpint.ts` from upstream.
+//! Mirrors `JsonPackMpint.ts` from upstream. Conversions to and from byte
+//! strings, hex and [`JsonPackBigInt`] are Rust additions so key material can
+//! be handled without an external big-int crate.
+
+use crate::{JsonPackBigInt, ParseBigIntError};
+use alloc::{format, string::String, vec::Vec};
 
 /// Represents an SSH multiprecision integer (mpint).
 ///
@@ -18,81 +23,176 @@ impl JsonPackMpint {
 
     /// Create an mpint from a BigInt value.
     pub fn from_i128(value: i128) -> Self {
-        if value == 0 {
-            return Self::new(vec![]);
+        Self::from_big_int(&JsonPackBigInt::from_i128(value))
+    }
+
+    /// Convert the mpint to an i128.
+    ///
+    /// Values wider than 128 bits wrap (only the low 16 bytes are kept); use
+    /// [`JsonPackMpint::checked_to_i128`] to detect overflow.
+    pub fn to_i128(&self) -> i128 {
+        if self.data.is_empty() {
+            return 0;
         }
+        let fill = if (self.data[0] & 0x80) != 0 { 0xff } else { 0 };
+        let mut bytes = [fill; 16];
+        let tail = &self.data[self.data.len().saturating_sub(16)..];
+        bytes[16 - tail.len()..].copy_from_slice(tail);
+        i128::from_be_bytes(bytes)
+    }
 
-        let negative = value < 0;
-        let mut bytes: Vec<u8> = Vec::new();
+    /// Convert the mpint to an i128, or `None` if it does not fit.
+    pub fn checked_to_i128(&self) -> Option<i128> {
+        self.to_big_int().to_i128()
+    }
 
-        if negative {
-            let abs_value: i128 = -value;
-            let bit_length = (128 - abs_value.leading_zeros()) as usize;
-            let byte_length = (bit_length + 8) / 8; // +1 for sign bit, rounded up
+    /// Create a non-negative mpint from a u128.
+    pub fn from_u128(value: u128) -> Self {
+        Self::from_big_int(&JsonPackBigInt::from_u128(value))
+    }
 
-            let two_complement: u128 = (1u128 << (byte_length * 8)).wrapping_add(value as u128);
+    /// Convert to a u128, or `None` if negative or out of range.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.to_big_int().to_u128()
+    }
 
-            for i in (0..byte_length).rev() {
-                bytes.push(((two_complement >> (i * 8)) & 0xff) as u8);
-            }
+    /// Create from a safe integer.
+    pub fn from_i64(value: i64) -> Self {
+        Self::from_i128(value as i128)
+    }
 
-            // Strip redundant 0xff prefix bytes where MSB of next byte is set
-            while bytes.len() > 1 && bytes[0] == 0xff && (bytes[1] & 0x80) != 0 {
-                bytes.remove(0);
-            }
-        } else {
-            let mut temp = value as u128;
-            while temp > 0 {
-                bytes.insert(0, (temp & 0xff) as u8);
-                temp >>= 8;
-            }
+    /// Convert to i64, failing if out of range.
+    pub fn to_i64(&self) -> Result<i64, &'static str> {
+        self.checked_to_i128()
+            .and_then(|v| i64::try_from(v).ok())
+            .ok_or("Value is outside safe integer range")
+    }
 
-            // Add leading zero if MSB is set (to indicate positive)
-            if !bytes.is_empty() && (bytes[0] & 0x80) != 0 {
-                bytes.insert(0, 0);
-            }
+    /// Create from big-endian two's complement bytes, dropping redundant
+    /// leading `0x00`/`0xff` bytes as RFC 4251 requires.
+    pub fn from_twos_complement(bytes: &[u8]) -> Self {
+        Self::from_big_int(&JsonPackBigInt::from_twos_complement(bytes))
+    }
+
+    /// Create a non-negative mpint from an unsigned big-endian magnitude
+    /// (e.g. an RSA modulus), adding a leading zero byte when the high bit
+    /// is set.
+    pub fn from_unsigned_bytes(bytes: &[u8]) -> Self {
+        Self::from_big_int(&JsonPackBigInt::new(false, bytes))
+    }
+
+    /// Unsigned big-endian magnitude without leading zeros, or `None` if the
+    /// value is negative.
+    pub fn to_unsigned_bytes(&self) -> Option<Vec<u8>> {
+        let n = self.to_big_int();
+        if n.is_negative() {
+            None
+        } else {
+            Some(n.magnitude().to_vec())
         }
+    }
 
-        Self::new(bytes)
+    pub fn from_big_int(value: &JsonPackBigInt) -> Self {
+        Self::new(value.to_twos_complement())
     }
 
-    /// Convert the mpint to an i128.
-    pub fn to_i128(&self) -> i128 {
-        if self.data.is_empty() {
-            return 0;
+    pub fn to_big_int(&self) -> JsonPackBigInt {
+        JsonPackBigInt::from_twos_complement(&self.data)
+    }
+
+    /// Returns `true` if `data` is the minimal encoding (no redundant
+    /// leading bytes), as required on the SSH wire.
+    pub fn is_canonical(&self) -> bool {
+        match self.data.as_slice() {
+            [0x00, next, ..] => next & 0x80 != 0,
+            [0xff, next, ..] => next & 0x80 == 0,
+            [0x00] => false,
+            _ => true,
         }
+    }
 
-        let negative = (self.data[0] & 0x80) != 0;
-        let mut value: i128 = 0;
+    /// Parse a hex string with an optional leading `-` and `0x` prefix
+    /// (e.g. `"-0x1f"`, `"deadbeef"`), like JS `BigInt("0x…")`.
+    pub fn from_hex(s: &str) -> Result<Self, ParseBigIntError> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if digits.is_empty() {
+            return Err(ParseBigIntError);
+        }
+        let nibbles = digits
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(ParseBigIntError)?;
+        let mut magnitude = Vec::with_capacity(nibbles.len() / 2 + 1);
+        if nibbles.len() % 2 == 1 {
+            magnitude.push(nibbles[0]);
+        }
+        for pair in nibbles[nibbles.len() % 2..].chunks(2) {
+            magnitude.push((pair[0] << 4) | pair[1]);
+        }
+        Ok(Self::from_big_int(&JsonPackBigInt::new(
+            negative, &magnitude,
+        )))
+    }
 
-        if negative {
-            for &b in &self.data {
-                value = (value << 8) | (b as i128);
-            }
-            // Two's complement: subtract 2^(8*len)
-            let bit_length = (self.data.len() * 8) as u32;
-            value -= 1i128 << bit_length;
-        } else {
-            for &b in &self.data {
-                value = (value << 8) | (b as i128);
+    /// Lowercase hex string of the value, like JS `bigint.toString(16)`.
+    pub fn to_hex(&self) -> String {
+        let n = self.to_big_int();
+        let mut out = String::new();
+        if n.is_negative() {
+            out.push('-');
+        }
+        let magnitude = n.magnitude();
+        match magnitude.split_first() {
+            None => out.push('0'),
+            Some((first, rest)) => {
+                out.push_str(&format!("{first:x}"));
+                for b in rest {
+                    out.push_str(&format!("{b:02x}"));
+                }
             }
         }
+        out
+    }
 
-        value
+    /// Encode in SSH wire format: uint32 length followed by the minimal
+    /// two's complement bytes (RFC 4251 §5).
+    pub fn to_ssh_bytes(&self) -> Vec<u8> {
+        let data = if self.is_canonical() {
+            self.data.clone()
+        } else {
+            Self::from_twos_complement(&self.data).data
+        };
+        let mut out = Vec::with_capacity(4 + data.len());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&data);
+        out
     }
 
-    /// Create from a safe integer.
-    pub fn from_i64(value: i64) -> Self {
-        Self::from_i128(value as i128)
+    /// Decode from SSH wire format, returning the mpint and the number of
+    /// bytes consumed, or `None` if the input is truncated.
+    pub fn from_ssh_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let len = u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let data = bytes.get(4..4 + len)?;
+        Some((Self::new(data.to_vec()), 4 + len))
     }
+}
 
-    /// Convert to i64 (panics if out of range).
-    pub fn to_i64(&self) -> Result<i64, &'static str> {
-        let v = self.to_i128();
-        if v > i64::MAX as i128 || v < i64::MIN as i128 {
-            Err("Value is outside safe integer range")
-        } else {
-            Ok(v as i64)
-        }
+impl From<&JsonPackBigInt> for JsonPackMpint {
+    fn from(value: &JsonPackBigInt) -> Self {
+        Self::from_big_int(value)
+    }
+}
+
+impl From<&JsonPackMpint> for JsonPackBigInt {
+    fn from(value: &JsonPackMpint) -> Self {
+        value.to_big_int()
     }
 }
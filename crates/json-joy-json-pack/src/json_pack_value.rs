@@ -2,6 +2,8 @@
 //!
 //! Mirrors `JsonPackValue.ts` from upstream.
 
+use alloc::vec::Vec;
+
 /// A wrapper for a pre-encoded MessagePack or CBOR value.
 ///
 /// The contents of `val` will be written as-is to the output document.
@@ -0,0 +1,293 @@
+//! Configurable [`PackValue`] ↔ `serde_json::Value` mapping.
+//!
+//! Not an upstream port. JSON has no bytes, no undefined, no integers past
+//! 64 bits and no NaN or infinities; [`JsonPolicy`] picks what each becomes
+//! in [`PackValue::to_json_with`], and what [`PackValue::from_json_with`]
+//! turns back into them. The default policy is what
+//! `From<PackValue> for serde_json::Value` does.
+//!
+//! Going back is only possible where the JSON form is recognisable: data
+//! URIs, decimal strings of integers outside 64 bits, and the non-finite
+//! strings. Bare base64, hex and byte arrays read back as strings and
+//! arrays, and `null` stays `null`.
+
+use serde_json::{Map, Number, Value as JsonValue};
+use thiserror::Error;
+
+use crate::json_binary::constants::BIN_URI_START;
+use crate::{JsonPackBigInt, PackValue};
+
+/// How [`PackValue::Bytes`] is written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonBytesPolicy {
+    /// `"data:application/octet-stream;base64,..."`.
+    #[default]
+    DataUri,
+    /// Bare base64 string.
+    Base64,
+    /// Lowercase hex string.
+    Hex,
+    /// Array of byte values.
+    Array,
+}
+
+/// How integers that do not fit in `i64` or `u64` are written to JSON.
+/// Big integers that do fit are always plain numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonBigIntPolicy {
+    /// Decimal string.
+    #[default]
+    String,
+    /// Nearest `f64`, as JavaScript's `Number()` would give.
+    Number,
+    /// Fail with [`JsonPolicyError::BigInt`].
+    Error,
+}
+
+/// How [`PackValue::Undefined`] is written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonUndefinedPolicy {
+    /// `null`.
+    #[default]
+    Null,
+    /// Leave object members out, like `JSON.stringify`; array elements and
+    /// a top-level value still become `null`.
+    Skip,
+    /// Fail with [`JsonPolicyError::Undefined`].
+    Error,
+}
+
+/// How NaN and the infinities are written to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonNonFinitePolicy {
+    /// `null`.
+    #[default]
+    Null,
+    /// `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
+    /// Fail with [`JsonPolicyError::NonFiniteFloat`].
+    Error,
+}
+
+/// How [`PackValue::Map`] keys with no text form are written to JSON.
+/// Scalar keys always become their text, and byte keys their
+/// [`JsonBytesPolicy`] string unless that policy is
+/// [`JsonBytesPolicy::Array`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonNonTextKeyPolicy {
+    /// `""`, as [`PackValue::to_key_string`] gives.
+    #[default]
+    Empty,
+    /// Fail with [`JsonPolicyError::NonTextKey`].
+    Error,
+}
+
+/// Policies for the values JSON cannot hold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonPolicy {
+    pub bytes: JsonBytesPolicy,
+    pub big_int: JsonBigIntPolicy,
+    pub undefined: JsonUndefinedPolicy,
+    pub non_finite: JsonNonFinitePolicy,
+    pub non_text_key: JsonNonTextKeyPolicy,
+}
+
+/// A value the [`JsonPolicy`] refuses.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JsonPolicyError {
+    #[error("integer {0} does not fit in 64 bits")]
+    BigInt(String),
+    #[error("undefined value")]
+    Undefined,
+    #[error("non-finite float")]
+    NonFiniteFloat,
+    #[error("non-text map key")]
+    NonTextKey,
+}
+
+impl PackValue {
+    /// Converts to JSON under `policy`. Tags are dropped and blobs become
+    /// `null`.
+    pub fn to_json_with(&self, policy: &JsonPolicy) -> Result<JsonValue, JsonPolicyError> {
+        Ok(match self {
+            PackValue::Null | PackValue::Blob(_) => JsonValue::Null,
+            PackValue::Undefined => match policy.undefined {
+                JsonUndefinedPolicy::Error => return Err(JsonPolicyError::Undefined),
+                _ => JsonValue::Null,
+            },
+            PackValue::Bool(b) => JsonValue::Bool(*b),
+            PackValue::Integer(i) => (*i).into(),
+            PackValue::UInteger(u) => (*u).into(),
+            PackValue::Float(f) => match Number::from_f64(*f) {
+                Some(n) => JsonValue::Number(n),
+                None => match policy.non_finite {
+                    JsonNonFinitePolicy::Null => JsonValue::Null,
+                    JsonNonFinitePolicy::String => JsonValue::String(
+                        if f.is_nan() {
+                            "NaN"
+                        } else if *f > 0.0 {
+                            "Infinity"
+                        } else {
+                            "-Infinity"
+                        }
+                        .into(),
+                    ),
+                    JsonNonFinitePolicy::Error => return Err(JsonPolicyError::NonFiniteFloat),
+                },
+            },
+            PackValue::BigInt(i) => big_int_to_json(*i, policy)?,
+            PackValue::BigNum(n) => match n.to_i128() {
+                Some(i) => big_int_to_json(i, policy)?,
+                None => match policy.big_int {
+                    JsonBigIntPolicy::String => JsonValue::String(n.to_string()),
+                    // Past `i128` the magnitude is far beyond what an `f64`
+                    // keeps exactly; go through the decimal form.
+                    JsonBigIntPolicy::Number => {
+                        float_or_null(n.to_string().parse().unwrap_or(f64::NAN))
+                    }
+                    JsonBigIntPolicy::Error => return Err(JsonPolicyError::BigInt(n.to_string())),
+                },
+            },
+            PackValue::Bytes(b) => match policy.bytes {
+                JsonBytesPolicy::DataUri => {
+                    JsonValue::String(format!("{BIN_URI_START}{}", json_joy_base64::to_base64(b)))
+                }
+                JsonBytesPolicy::Base64 => JsonValue::String(json_joy_base64::to_base64(b)),
+                JsonBytesPolicy::Hex => {
+                    JsonValue::String(b.iter().map(|byte| format!("{byte:02x}")).collect())
+                }
+                JsonBytesPolicy::Array => JsonValue::Array(b.iter().map(|&x| x.into()).collect()),
+            },
+            PackValue::Str(s) => JsonValue::String(s.clone()),
+            PackValue::Array(arr) => JsonValue::Array(
+                arr.iter()
+                    .map(|v| v.to_json_with(policy))
+                    .collect::<Result<_, _>>()?,
+            ),
+            PackValue::Object(obj) => {
+                let mut map = Map::with_capacity(obj.len());
+                for (k, v) in obj {
+                    if policy.undefined == JsonUndefinedPolicy::Skip && is_undefined(v) {
+                        continue;
+                    }
+                    map.insert(k.clone(), v.to_json_with(policy)?);
+                }
+                JsonValue::Object(map)
+            }
+            PackValue::Map(entries) => {
+                let mut map = Map::with_capacity(entries.len());
+                for (k, v) in entries {
+                    if policy.undefined == JsonUndefinedPolicy::Skip && is_undefined(v) {
+                        continue;
+                    }
+                    map.insert(key_to_json(k, policy)?, v.to_json_with(policy)?);
+                }
+                JsonValue::Object(map)
+            }
+            PackValue::Extension(ext) => ext.val.to_json_with(policy)?,
+        })
+    }
+
+    /// Converts from JSON, reading back what [`to_json_with`](Self::to_json_with)
+    /// writes under `policy` where that form can be recognised.
+    pub fn from_json_with(value: &JsonValue, policy: &JsonPolicy) -> PackValue {
+        match value {
+            JsonValue::String(s) => string_from_json(s, policy),
+            JsonValue::Array(arr) => PackValue::Array(
+                arr.iter()
+                    .map(|v| PackValue::from_json_with(v, policy))
+                    .collect(),
+            ),
+            JsonValue::Object(obj) => PackValue::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), PackValue::from_json_with(v, policy)))
+                    .collect(),
+            ),
+            other => PackValue::from(other),
+        }
+    }
+}
+
+/// Whether `v` is undefined once tags are dropped.
+fn is_undefined(v: &PackValue) -> bool {
+    match v {
+        PackValue::Undefined => true,
+        PackValue::Extension(ext) => is_undefined(&ext.val),
+        _ => false,
+    }
+}
+
+fn float_or_null(f: f64) -> JsonValue {
+    Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+/// The object key a [`PackValue::Map`] key becomes.
+fn key_to_json(key: &PackValue, policy: &JsonPolicy) -> Result<String, JsonPolicyError> {
+    if key.is_text_key() {
+        return Ok(key.to_key_string());
+    }
+    if let PackValue::Bytes(_) = key {
+        if let JsonValue::String(s) = key.to_json_with(policy)? {
+            return Ok(s);
+        }
+    }
+    match policy.non_text_key {
+        JsonNonTextKeyPolicy::Empty => Ok(String::new()),
+        JsonNonTextKeyPolicy::Error => Err(JsonPolicyError::NonTextKey),
+    }
+}
+
+fn big_int_to_json(i: i128, policy: &JsonPolicy) -> Result<JsonValue, JsonPolicyError> {
+    if let Ok(i) = i64::try_from(i) {
+        return Ok(i.into());
+    }
+    if let Ok(u) = u64::try_from(i) {
+        return Ok(u.into());
+    }
+    Ok(match policy.big_int {
+        JsonBigIntPolicy::String => JsonValue::String(i.to_string()),
+        JsonBigIntPolicy::Number => float_or_null(i as f64),
+        JsonBigIntPolicy::Error => return Err(JsonPolicyError::BigInt(i.to_string())),
+    })
+}
+
+fn string_from_json(s: &str, policy: &JsonPolicy) -> PackValue {
+    if policy.bytes == JsonBytesPolicy::DataUri {
+        if let Some(b64) = s.strip_prefix(BIN_URI_START) {
+            if let Ok(bytes) = json_joy_base64::from_base64(b64) {
+                return PackValue::Bytes(bytes);
+            }
+        }
+    }
+    if policy.non_finite == JsonNonFinitePolicy::String {
+        match s {
+            "NaN" => return PackValue::Float(f64::NAN),
+            "Infinity" => return PackValue::Float(f64::INFINITY),
+            "-Infinity" => return PackValue::Float(f64::NEG_INFINITY),
+            _ => {}
+        }
+    }
+    // Integers that fit in 64 bits are written as numbers, so a string of
+    // one is just a string.
+    if policy.big_int == JsonBigIntPolicy::String
+        && is_canonical_integer(s)
+        && s.parse::<i64>().is_err()
+        && s.parse::<u64>().is_err()
+    {
+        if let Ok(i) = s.parse::<i128>() {
+            return PackValue::BigInt(i);
+        }
+        if let Ok(n) = s.parse::<JsonPackBigInt>() {
+            return PackValue::BigNum(n);
+        }
+    }
+    PackValue::Str(s.into())
+}
+
+/// An optional minus sign and digits without leading zeros.
+fn is_canonical_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
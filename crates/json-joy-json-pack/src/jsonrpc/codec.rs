@@ -0,0 +1,40 @@
+//! Byte-level encoding of JSON-RPC packets through a [`JsonValueCodec`].
+
+use super::types::{JsonRpcError, JsonRpcPacket};
+use crate::codecs::{CodecError, JsonValueCodec};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonRpcCodec;
+
+impl JsonRpcCodec {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn encode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        packet: &JsonRpcPacket,
+    ) -> Result<Vec<u8>, CodecError> {
+        codec.encode(&packet.to_pack())
+    }
+
+    /// Decodes a packet. Undecodable bytes map to a "Parse error"
+    /// ([`JsonRpcError::PARSE_ERROR`]) carrying the codec message as `data`;
+    /// malformed envelopes map to "Invalid Request".
+    pub fn decode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        bytes: &[u8],
+    ) -> Result<JsonRpcPacket, JsonRpcError> {
+        let value = codec.decode(bytes).map_err(parse_error)?;
+        JsonRpcPacket::from_pack(&value)
+    }
+}
+
+pub(super) fn parse_error(err: CodecError) -> JsonRpcError {
+    JsonRpcError {
+        data: Some(crate::PackValue::Str(err.to_string())),
+        ..JsonRpcError::parse_error()
+    }
+}
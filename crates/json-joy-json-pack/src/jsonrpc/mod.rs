@@ -0,0 +1,16 @@
+//! JSON-RPC 2.0 envelopes and a method router.
+//!
+//! Not an upstream port. Messages convert to and from [`crate::PackValue`],
+//! so they can travel over any [`crate::codecs::JsonValueCodec`] (JSON as the
+//! specification intends, or CBOR/MessagePack).
+
+mod codec;
+mod router;
+mod types;
+
+pub use codec::JsonRpcCodec;
+pub use router::{JsonRpcHandler, JsonRpcRouter};
+pub use types::{
+    JsonRpcError, JsonRpcId, JsonRpcMessage, JsonRpcNotification, JsonRpcPacket, JsonRpcRequest,
+    JsonRpcResponse,
+};
@@ -0,0 +1,131 @@
+//! Dispatches requests and notifications to registered method handlers.
+
+use std::collections::HashMap;
+
+use super::codec::parse_error;
+use super::types::{get, JsonRpcError, JsonRpcId, JsonRpcMessage, JsonRpcResponse};
+use crate::codecs::{CodecError, JsonValueCodec};
+use crate::PackValue;
+
+/// A method implementation. Closures taking the optional `params` implement
+/// this trait.
+pub trait JsonRpcHandler {
+    fn call(&mut self, params: Option<PackValue>) -> Result<PackValue, JsonRpcError>;
+}
+
+impl<F> JsonRpcHandler for F
+where
+    F: FnMut(Option<PackValue>) -> Result<PackValue, JsonRpcError>,
+{
+    fn call(&mut self, params: Option<PackValue>) -> Result<PackValue, JsonRpcError> {
+        self(params)
+    }
+}
+
+/// Method-name router implementing the JSON-RPC 2.0 server rules: requests
+/// get a response, notifications and stray responses get none, batch members
+/// are answered individually, and an empty batch is an invalid request.
+#[derive(Default)]
+pub struct JsonRpcRouter {
+    handlers: HashMap<String, Box<dyn JsonRpcHandler>>,
+}
+
+impl JsonRpcRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `handler` for `method`, replacing any previous handler.
+    pub fn add(&mut self, method: impl Into<String>, handler: impl JsonRpcHandler + 'static) {
+        self.handlers.insert(method.into(), Box::new(handler));
+    }
+
+    pub fn has(&self, method: &str) -> bool {
+        self.handlers.contains_key(method)
+    }
+
+    /// Handles one parsed message; returns the response to send, if any.
+    pub fn handle_message(&mut self, message: JsonRpcMessage) -> Option<JsonRpcResponse> {
+        match message {
+            JsonRpcMessage::Request(request) => Some(JsonRpcResponse {
+                id: request.id,
+                result: self.dispatch(&request.method, request.params),
+            }),
+            JsonRpcMessage::Notification(notification) => {
+                let _ = self.dispatch(&notification.method, notification.params);
+                None
+            }
+            JsonRpcMessage::Response(_) => None,
+        }
+    }
+
+    /// Handles a decoded message or batch; returns the reply value, if any.
+    pub fn handle_value(&mut self, value: &PackValue) -> Option<PackValue> {
+        match value {
+            PackValue::Array(items) if items.is_empty() => {
+                Some(invalid_request(JsonRpcId::Null).to_pack())
+            }
+            PackValue::Array(items) => {
+                let replies: Vec<PackValue> = items
+                    .iter()
+                    .filter_map(|item| self.handle_one(item))
+                    .collect();
+                (!replies.is_empty()).then_some(PackValue::Array(replies))
+            }
+            value => self.handle_one(value),
+        }
+    }
+
+    /// Decodes `bytes` with `codec`, handles the payload and encodes the
+    /// reply. Undecodable input is answered with a "Parse error" response.
+    pub fn handle(
+        &mut self,
+        codec: &mut dyn JsonValueCodec,
+        bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>, CodecError> {
+        let reply = match codec.decode(bytes) {
+            Ok(value) => self.handle_value(&value),
+            Err(err) => Some(
+                JsonRpcMessage::Response(JsonRpcResponse {
+                    id: JsonRpcId::Null,
+                    result: Err(parse_error(err)),
+                })
+                .to_pack(),
+            ),
+        };
+        reply.map(|value| codec.encode(&value)).transpose()
+    }
+
+    fn handle_one(&mut self, value: &PackValue) -> Option<PackValue> {
+        match JsonRpcMessage::from_pack(value) {
+            Ok(message) => self
+                .handle_message(message)
+                .map(|response| JsonRpcMessage::Response(response).to_pack()),
+            Err(_) => {
+                // Echo the id when it is readable so the client can match it.
+                let id = get(value, "id")
+                    .and_then(JsonRpcId::from_pack)
+                    .unwrap_or(JsonRpcId::Null);
+                Some(invalid_request(id).to_pack())
+            }
+        }
+    }
+
+    fn dispatch(
+        &mut self,
+        method: &str,
+        params: Option<PackValue>,
+    ) -> Result<PackValue, JsonRpcError> {
+        match self.handlers.get_mut(method) {
+            Some(handler) => handler.call(params),
+            None => Err(JsonRpcError::method_not_found()),
+        }
+    }
+}
+
+fn invalid_request(id: JsonRpcId) -> JsonRpcMessage {
+    JsonRpcMessage::Response(JsonRpcResponse {
+        id,
+        result: Err(JsonRpcError::invalid_request()),
+    })
+}
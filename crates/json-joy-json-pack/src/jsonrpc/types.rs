@@ -0,0 +1,251 @@
+//! JSON-RPC 2.0 message types and their [`PackValue`] form.
+
+use thiserror::Error;
+
+use crate::PackValue;
+
+/// Request identifier. Fractional numbers are not supported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonRpcId {
+    Num(i64),
+    Str(String),
+    Null,
+}
+
+/// The `error` member of a response; also the error type of decoding.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("JSON-RPC error {code}: {message}")]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<PackValue>,
+}
+
+impl JsonRpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn parse_error() -> Self {
+        Self::new(Self::PARSE_ERROR, "Parse error")
+    }
+
+    pub fn invalid_request() -> Self {
+        Self::new(Self::INVALID_REQUEST, "Invalid Request")
+    }
+
+    pub fn method_not_found() -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, "Method not found")
+    }
+
+    pub fn invalid_params() -> Self {
+        Self::new(Self::INVALID_PARAMS, "Invalid params")
+    }
+
+    pub fn internal_error() -> Self {
+        Self::new(Self::INTERNAL_ERROR, "Internal error")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRpcRequest {
+    pub id: JsonRpcId,
+    pub method: String,
+    /// Array or object, if present.
+    pub params: Option<PackValue>,
+}
+
+/// A request without an `id`; the server sends no response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRpcNotification {
+    pub method: String,
+    pub params: Option<PackValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRpcResponse {
+    pub id: JsonRpcId,
+    pub result: Result<PackValue, JsonRpcError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonRpcMessage {
+    Request(JsonRpcRequest),
+    Notification(JsonRpcNotification),
+    Response(JsonRpcResponse),
+}
+
+/// A single message or a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonRpcPacket {
+    Single(JsonRpcMessage),
+    Batch(Vec<JsonRpcMessage>),
+}
+
+// ---------------------------------------------------------------- PackValue form
+
+fn str(s: &str) -> PackValue {
+    PackValue::Str(s.to_string())
+}
+
+impl JsonRpcId {
+    pub fn to_pack(&self) -> PackValue {
+        match self {
+            Self::Num(n) => PackValue::Integer(*n),
+            Self::Str(s) => str(s),
+            Self::Null => PackValue::Null,
+        }
+    }
+
+    pub fn from_pack(value: &PackValue) -> Option<Self> {
+        match value {
+            PackValue::Integer(n) => Some(Self::Num(*n)),
+            PackValue::UInteger(n) => i64::try_from(*n).ok().map(Self::Num),
+            PackValue::Float(f) if f.fract() == 0.0 && f.abs() < 9.007_199_254_740_992e15 => {
+                Some(Self::Num(*f as i64))
+            }
+            PackValue::Str(s) => Some(Self::Str(s.clone())),
+            PackValue::Null => Some(Self::Null),
+            _ => None,
+        }
+    }
+}
+
+impl JsonRpcError {
+    pub fn to_pack(&self) -> PackValue {
+        let mut obj = vec![
+            ("code".to_string(), PackValue::Integer(self.code)),
+            ("message".to_string(), str(&self.message)),
+        ];
+        if let Some(data) = &self.data {
+            obj.push(("data".to_string(), data.clone()));
+        }
+        PackValue::Object(obj)
+    }
+
+    pub fn from_pack(value: &PackValue) -> Option<Self> {
+        let code = match get(value, "code")? {
+            PackValue::Integer(n) => *n,
+            PackValue::UInteger(n) => i64::try_from(*n).ok()?,
+            PackValue::Float(f) if f.fract() == 0.0 => *f as i64,
+            _ => return None,
+        };
+        let PackValue::Str(message) = get(value, "message")? else {
+            return None;
+        };
+        Some(Self {
+            code,
+            message: message.clone(),
+            data: get(value, "data").cloned(),
+        })
+    }
+}
+
+impl JsonRpcMessage {
+    pub fn to_pack(&self) -> PackValue {
+        let mut obj = vec![("jsonrpc".to_string(), str("2.0"))];
+        let (method, params) = match self {
+            Self::Request(r) => {
+                obj.push(("id".to_string(), r.id.to_pack()));
+                (&r.method, &r.params)
+            }
+            Self::Notification(n) => (&n.method, &n.params),
+            Self::Response(r) => {
+                obj.push(("id".to_string(), r.id.to_pack()));
+                match &r.result {
+                    Ok(result) => obj.push(("result".to_string(), result.clone())),
+                    Err(error) => obj.push(("error".to_string(), error.to_pack())),
+                }
+                return PackValue::Object(obj);
+            }
+        };
+        obj.push(("method".to_string(), str(method)));
+        if let Some(params) = params {
+            obj.push(("params".to_string(), params.clone()));
+        }
+        PackValue::Object(obj)
+    }
+
+    /// Parses one message, failing with an "Invalid Request" error when the
+    /// value is not a well-formed JSON-RPC 2.0 object.
+    pub fn from_pack(value: &PackValue) -> Result<Self, JsonRpcError> {
+        Self::parse(value).ok_or_else(JsonRpcError::invalid_request)
+    }
+
+    fn parse(value: &PackValue) -> Option<Self> {
+        if !matches!(get(value, "jsonrpc")?, PackValue::Str(v) if v == "2.0") {
+            return None;
+        }
+        if let Some(method) = get(value, "method") {
+            let PackValue::Str(method) = method else {
+                return None;
+            };
+            let params = match get(value, "params") {
+                None => None,
+                Some(p @ (PackValue::Array(_) | PackValue::Object(_))) => Some(p.clone()),
+                Some(_) => return None,
+            };
+            return Some(match get(value, "id") {
+                None => Self::Notification(JsonRpcNotification {
+                    method: method.clone(),
+                    params,
+                }),
+                Some(id) => Self::Request(JsonRpcRequest {
+                    id: JsonRpcId::from_pack(id)?,
+                    method: method.clone(),
+                    params,
+                }),
+            });
+        }
+        let id = JsonRpcId::from_pack(get(value, "id")?)?;
+        let result = match (get(value, "result"), get(value, "error")) {
+            (Some(result), None) => Ok(result.clone()),
+            (None, Some(error)) => Err(JsonRpcError::from_pack(error)?),
+            _ => return None,
+        };
+        Some(Self::Response(JsonRpcResponse { id, result }))
+    }
+}
+
+impl JsonRpcPacket {
+    pub fn to_pack(&self) -> PackValue {
+        match self {
+            Self::Single(message) => message.to_pack(),
+            Self::Batch(messages) => {
+                PackValue::Array(messages.iter().map(JsonRpcMessage::to_pack).collect())
+            }
+        }
+    }
+
+    /// Parses a message or a non-empty batch; any invalid member fails the
+    /// whole packet. Use [`crate::jsonrpc::JsonRpcRouter`] to answer invalid
+    /// batch members individually.
+    pub fn from_pack(value: &PackValue) -> Result<Self, JsonRpcError> {
+        match value {
+            PackValue::Array(items) if items.is_empty() => Err(JsonRpcError::invalid_request()),
+            PackValue::Array(items) => items
+                .iter()
+                .map(JsonRpcMessage::from_pack)
+                .collect::<Result<_, _>>()
+                .map(Self::Batch),
+            value => JsonRpcMessage::from_pack(value).map(Self::Single),
+        }
+    }
+}
+
+pub(super) fn get<'a>(value: &'a PackValue, key: &str) -> Option<&'a PackValue> {
+    match value {
+        PackValue::Object(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+        _ => None,
+    }
+}
@@ -0,0 +1,49 @@
+//! Object key ordering for the stable (deterministic) encoders.
+//!
+//! Not an upstream port: upstream hardcodes one order per encoder. Each
+//! stable encoder defaults to its upstream order; [`KeyOrder`] lets callers
+//! pick another, e.g. [`KeyOrder::Utf16`] for RFC 8785 (JCS) canonical JSON.
+
+use alloc::{string::String, vec::Vec};
+use core::cmp::Ordering;
+
+/// Strategy used to sort object keys before encoding.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyOrder {
+    /// Byte-wise order of the UTF-8 keys (same as Unicode code point order).
+    Lexicographic,
+    /// Shorter keys first, measured in UTF-16 code units like JavaScript's
+    /// `String.length`; equal lengths fall back to [`KeyOrder::Lexicographic`].
+    LengthLex,
+    /// Shorter keys first, measured in UTF-8 bytes; equal lengths fall back
+    /// to [`KeyOrder::Lexicographic`]. This is canonical CBOR key order
+    /// (RFC 7049 §3.9).
+    ByteLengthLex,
+    /// Order of the UTF-16 code unit sequences, as required by RFC 8785 (JCS).
+    Utf16,
+    /// Caller-supplied comparator.
+    Custom(fn(&str, &str) -> Ordering),
+}
+
+impl KeyOrder {
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Lexicographic => a.cmp(b),
+            Self::LengthLex => a
+                .encode_utf16()
+                .count()
+                .cmp(&b.encode_utf16().count())
+                .then_with(|| a.cmp(b)),
+            Self::ByteLengthLex => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            Self::Utf16 => a.encode_utf16().cmp(b.encode_utf16()),
+            Self::Custom(cmp) => cmp(a, b),
+        }
+    }
+
+    /// Indices of `pairs` in the order their keys should be written.
+    pub fn sorted_indices<V>(&self, pairs: &[(String, V)]) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..pairs.len()).collect();
+        indices.sort_by(|&a, &b| self.compare(&pairs[a].0, &pairs[b].0));
+        indices
+    }
+}
@@ -2,43 +2,141 @@
 //!
 //! Upstream reference: `@jsonjoy.com/json-pack` v18.0.0
 //! Source: `json-joy/packages/json-pack/src/`
+//!
+//! # `no_std`
+//!
+//! With default features disabled the crate is `#![no_std]` (needing only
+//! `alloc`) and provides [`PackValue`], the [`cbor`] and [`msgpack`] codecs
+//! and [`util::format_f64_js`]. The `std` feature (on by default) enables
+//! every other format and the `serde_json` conversions.
+//!
+//! # `simd`
+//!
+//! The opt-in `simd` feature accelerates the hot string paths of the JSON,
+//! CBOR and MessagePack decoders: UTF-8 validation skips ASCII runs in
+//! 16-byte blocks, and JSON string scanning jumps between quotes and
+//! backslashes with `memchr`.
+//!
+//! # `f16`
+//!
+//! The `f16` feature (on by default) enables half-precision float output:
+//! `CborEncoderOptions::float16`, `CborEncoderFast::with_float16`,
+//! `CborEncoderStable::with_float16` and `UbjsonEncoderOptions::float16`
+//! all write a float as f16 when that keeps every bit, NaN payloads
+//! included. It also enables the CBOR `nan_payloads` options. The options
+//! exist in every build, so turning the feature on never breaks a struct
+//! literal; without it they are ignored and output matches upstream.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 mod constants;
+#[cfg(feature = "std")]
+mod error;
+mod json_pack_bigint;
 mod json_pack_extension;
 mod json_pack_mpint;
 mod json_pack_value;
+#[cfg(feature = "std")]
+mod json_policy;
+mod key_order;
+mod pack_builder;
+mod pack_object;
 mod pack_value;
+mod utf8;
+
+pub mod cbor;
+pub mod checked;
+pub mod msgpack;
+pub mod streaming;
+pub mod token;
+pub mod transcode;
 
+#[cfg(feature = "std")]
 pub mod avro;
+#[cfg(feature = "std")]
 pub mod bencode;
+#[cfg(feature = "std")]
 pub mod bson;
-pub mod cbor;
+#[cfg(feature = "std")]
 pub mod codecs;
+#[cfg(feature = "std")]
+pub mod conformance;
+#[cfg(feature = "std")]
+pub mod csv;
+#[cfg(feature = "std")]
 pub mod ejson;
+#[cfg(feature = "std")]
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod hexdump;
+#[cfg(feature = "std")]
+pub mod http_frames;
+#[cfg(feature = "std")]
 pub mod ion;
+#[cfg(feature = "std")]
 pub mod json;
+#[cfg(feature = "std")]
 pub mod json_binary;
-pub mod msgpack;
+#[cfg(feature = "std")]
+pub mod jsonrpc;
+#[cfg(feature = "std")]
+pub mod proto;
+#[cfg(feature = "std")]
 pub mod resp;
+#[cfg(feature = "std")]
 pub mod rm;
+#[cfg(feature = "std")]
 pub mod rpc;
+#[cfg(feature = "std")]
+pub mod rpc_rx;
+#[cfg(feature = "std")]
 pub mod ssh;
+#[cfg(feature = "std")]
+pub mod transform;
+#[cfg(feature = "std")]
 pub mod ubjson;
 pub mod util;
+#[cfg(feature = "std")]
 pub mod ws;
+#[cfg(feature = "std")]
 pub mod xdr;
 
 pub use constants::EncodingFormat;
+#[cfg(feature = "std")]
+pub use error::JsonPackError;
+pub use json_pack_bigint::{JsonPackBigInt, ParseBigIntError};
 pub use json_pack_extension::JsonPackExtension;
 pub use json_pack_mpint::JsonPackMpint;
 pub use json_pack_value::JsonPackValue;
+#[cfg(feature = "std")]
+pub use json_policy::{
+    JsonBigIntPolicy, JsonBytesPolicy, JsonNonFinitePolicy, JsonNonTextKeyPolicy, JsonPolicy,
+    JsonPolicyError, JsonUndefinedPolicy,
+};
+pub use key_order::KeyOrder;
+pub use pack_builder::{PackArrayBuilder, PackObjectBuilder};
+pub use pack_object::PackObject;
 pub use pack_value::PackValue;
 
+/// Re-exports for the `pack!` macro, which cannot name `alloc` directly.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::String;
+    pub use alloc::vec;
+}
+
+#[cfg(feature = "std")]
 pub use cbor::{
-    cbor_to_json, cbor_to_json_owned, decode_cbor_value, decode_cbor_value_with_consumed,
-    decode_json_from_cbor_bytes, encode_cbor_value, encode_json_to_cbor_bytes, json_to_cbor,
-    validate_cbor_exact_size, write_cbor_signed, write_cbor_text_like_json_pack,
-    write_cbor_uint_major, write_json_like_json_pack, CborEncoder, CborError, CborJsonValueCodec,
+    cbor_to_json, cbor_to_json_owned, decode_json_from_cbor_bytes, encode_cbor_value,
+    encode_json_to_cbor_bytes, encode_json_to_cbor_bytes_dag, encode_json_to_cbor_bytes_stable,
+    json_to_cbor, write_json_like_json_pack, CborJsonValueCodec,
+};
+pub use cbor::{
+    decode_cbor_value, decode_cbor_value_with_consumed, validate_cbor_exact_size,
+    write_cbor_signed, write_cbor_text_like_json_pack, write_cbor_uint_major, CborEncoder,
+    CborError,
 };
 
 #[cfg(test)]
@@ -541,7 +639,7 @@ mod tests {
             ("s".into(), PackValue::Str("hi".into())),
         ]);
         let msgpack = enc.encode(&obj);
-        let json_str = conv.convert(&msgpack);
+        let json_str = conv.convert(&msgpack).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json_str).expect("valid JSON");
         assert_eq!(parsed["n"], serde_json::Value::Null);
         assert_eq!(parsed["b"], serde_json::Value::Bool(true));
@@ -3302,7 +3400,7 @@ mod tests {
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
         let bytes = enc.encode(&PackValue::Integer(-5));
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert_eq!(json, "-5");
     }
 
@@ -3312,7 +3410,7 @@ mod tests {
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
         let bytes = enc.encode(&PackValue::Integer(42));
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert_eq!(json, "42");
     }
 
@@ -3321,9 +3419,15 @@ mod tests {
         use super::msgpack::{MsgPackEncoderFast, MsgPackToJsonConverter};
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
-        assert_eq!(conv.convert(&enc.encode(&PackValue::Null)), "null");
-        assert_eq!(conv.convert(&enc.encode(&PackValue::Bool(true))), "true");
-        assert_eq!(conv.convert(&enc.encode(&PackValue::Bool(false))), "false");
+        assert_eq!(conv.convert(&enc.encode(&PackValue::Null)).unwrap(), "null");
+        assert_eq!(
+            conv.convert(&enc.encode(&PackValue::Bool(true))).unwrap(),
+            "true"
+        );
+        assert_eq!(
+            conv.convert(&enc.encode(&PackValue::Bool(false))).unwrap(),
+            "false"
+        );
     }
 
     #[test]
@@ -3332,7 +3436,7 @@ mod tests {
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
         let bytes = enc.encode(&PackValue::Float(1.5));
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert_eq!(json, "1.5");
     }
 
@@ -3342,7 +3446,7 @@ mod tests {
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
         let bytes = enc.encode(&PackValue::Str("hello \"world\"".into()));
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, serde_json::json!("hello \"world\""));
     }
@@ -3353,7 +3457,7 @@ mod tests {
         let mut enc = MsgPackEncoderFast::new();
         let mut conv = MsgPackToJsonConverter::new();
         let bytes = enc.encode(&PackValue::Bytes(vec![1, 2, 3]));
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert!(json.contains("data:application/octet-stream;base64,"));
     }
 
@@ -3368,7 +3472,7 @@ mod tests {
             PackValue::Null,
         ]);
         let bytes = enc.encode(&arr);
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, serde_json::json!([1, true, null]));
     }
@@ -3386,7 +3490,7 @@ mod tests {
             ),
         ]);
         let bytes = enc.encode(&obj);
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed["arr"], serde_json::json!([1]));
         assert_eq!(parsed["obj"]["k"], serde_json::json!("v"));
@@ -3396,7 +3500,7 @@ mod tests {
     fn msgpack_to_json_empty_input() {
         use super::msgpack::MsgPackToJsonConverter;
         let mut conv = MsgPackToJsonConverter::new();
-        assert_eq!(conv.convert(&[]), "null");
+        assert_eq!(conv.convert(&[]).unwrap(), "null");
     }
 
     #[test]
@@ -3404,7 +3508,7 @@ mod tests {
         use super::msgpack::MsgPackToJsonConverter;
         let mut conv = MsgPackToJsonConverter::new();
         // 0xc1 = undefined/never-used -> null in JSON
-        assert_eq!(conv.convert(&[0xc1]), "null");
+        assert_eq!(conv.convert(&[0xc1]).unwrap(), "null");
     }
 
     #[test]
@@ -3412,7 +3516,7 @@ mod tests {
         use super::msgpack::MsgPackToJsonConverter;
         let mut conv = MsgPackToJsonConverter::new();
         // 0xcc = uint8, followed by 200
-        assert_eq!(conv.convert(&[0xcc, 200]), "200");
+        assert_eq!(conv.convert(&[0xcc, 200]).unwrap(), "200");
     }
 
     #[test]
@@ -3421,7 +3525,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xcd = uint16
         let bytes = [0xcd, 0x01, 0x00]; // 256
-        assert_eq!(conv.convert(&bytes), "256");
+        assert_eq!(conv.convert(&bytes).unwrap(), "256");
     }
 
     #[test]
@@ -3430,7 +3534,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xce = uint32
         let bytes = [0xce, 0x00, 0x01, 0x00, 0x00]; // 65536
-        assert_eq!(conv.convert(&bytes), "65536");
+        assert_eq!(conv.convert(&bytes).unwrap(), "65536");
     }
 
     #[test]
@@ -3439,7 +3543,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xcf = uint64 (hi32:lo32)
         let bytes = [0xcf, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]; // 4294967296
-        assert_eq!(conv.convert(&bytes), "4294967296");
+        assert_eq!(conv.convert(&bytes).unwrap(), "4294967296");
     }
 
     #[test]
@@ -3448,7 +3552,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xd0 = int8, -100
         let bytes = [0xd0, (-100i8) as u8];
-        assert_eq!(conv.convert(&bytes), "-100");
+        assert_eq!(conv.convert(&bytes).unwrap(), "-100");
     }
 
     #[test]
@@ -3459,7 +3563,7 @@ mod tests {
         let val: i16 = -1000;
         let be = val.to_be_bytes();
         let bytes = [0xd1, be[0], be[1]];
-        assert_eq!(conv.convert(&bytes), "-1000");
+        assert_eq!(conv.convert(&bytes).unwrap(), "-1000");
     }
 
     #[test]
@@ -3470,7 +3574,7 @@ mod tests {
         let val: i32 = -100000;
         let be = val.to_be_bytes();
         let bytes = [0xd2, be[0], be[1], be[2], be[3]];
-        assert_eq!(conv.convert(&bytes), "-100000");
+        assert_eq!(conv.convert(&bytes).unwrap(), "-100000");
     }
 
     #[test]
@@ -3479,7 +3583,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xd3 = int64 (hi_i32:lo_u32), value = -1
         let bytes = [0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
-        assert_eq!(conv.convert(&bytes), "-1");
+        assert_eq!(conv.convert(&bytes).unwrap(), "-1");
     }
 
     #[test]
@@ -3490,7 +3594,7 @@ mod tests {
         let val: f32 = 1.5;
         let be = val.to_be_bytes();
         let bytes = [0xca, be[0], be[1], be[2], be[3]];
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert_eq!(json, "1.5");
     }
 
@@ -3500,7 +3604,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xd9 = str8, length=5, "hello"
         let bytes = [0xd9, 5, b'h', b'e', b'l', b'l', b'o'];
-        assert_eq!(conv.convert(&bytes), "\"hello\"");
+        assert_eq!(conv.convert(&bytes).unwrap(), "\"hello\"");
     }
 
     #[test]
@@ -3509,7 +3613,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xc5 = bin16
         let bytes = [0xc5, 0x00, 0x02, 0xAA, 0xBB];
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert!(json.contains("data:application/octet-stream;base64,"));
     }
 
@@ -3519,7 +3623,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xd4 = fixext1 (1 byte of ext data + 1 byte type)
         let bytes = [0xd4, 0x01, 0xFF]; // type=1, data=[0xFF]
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert!(json.contains("data:application/octet-stream;base64,"));
     }
 
@@ -3529,7 +3633,7 @@ mod tests {
         let mut conv = MsgPackToJsonConverter::new();
         // 0xc7 = ext8, length=2, type=5, data=[0x01, 0x02]
         let bytes = [0xc7, 0x02, 0x05, 0x01, 0x02];
-        let json = conv.convert(&bytes);
+        let json = conv.convert(&bytes).unwrap();
         assert!(json.contains("data:application/octet-stream;base64,"));
     }
 
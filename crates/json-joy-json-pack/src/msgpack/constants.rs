@@ -11,3 +11,9 @@ pub enum MsgPackMarker {
     False = 0xc2,
     True = 0xc3,
 }
+
+/// Application extension type used for arbitrary-precision integers
+/// ([`crate::PackValue::BigNum`]). The payload is the big-endian two's
+/// complement representation. Not part of upstream, which has no bigint
+/// extension.
+pub const EXT_BIG_INT: i8 = 0x42;
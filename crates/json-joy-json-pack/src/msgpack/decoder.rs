@@ -2,9 +2,14 @@
 //!
 //! Direct port of `msgpack/MsgPackDecoder.ts` from upstream.
 
-use super::decoder_fast::MsgPackDecoderFast;
+use super::decoder_fast::{MsgPackDecoderFast, MsgPackDecoderOptions};
 use super::error::MsgPackError;
+use crate::streaming::StreamingSource;
 use crate::{JsonPackValue, PackValue};
+use alloc::vec::Vec;
+use json_joy_buffers::BufView;
+#[cfg(feature = "std")]
+use json_joy_json_pointer::PathSegment;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MsgPackPathSegment<'a> {
@@ -29,10 +34,39 @@ impl MsgPackDecoder {
         }
     }
 
+    pub fn with_options(options: MsgPackDecoderOptions) -> Self {
+        Self {
+            inner: MsgPackDecoderFast::with_options(options),
+        }
+    }
+
     pub fn decode(&mut self, input: &[u8]) -> Result<PackValue, MsgPackError> {
         self.inner.decode(input)
     }
 
+    /// Decodes the next value from a chunked source.
+    ///
+    /// Returns `Ok(None)`, leaving the source untouched, while the buffered
+    /// bytes end inside a value; on success the value's bytes are consumed.
+    /// Error offsets are relative to the first unread byte.
+    pub fn read_streaming(
+        &mut self,
+        source: &mut impl StreamingSource,
+    ) -> Result<Option<PackValue>, MsgPackError> {
+        self.reset(&source.unread());
+        if self.inner.data.is_empty() {
+            return Ok(None);
+        }
+        match self.inner.read_any() {
+            Ok(value) => {
+                source.advance(self.inner.x);
+                Ok(Some(value))
+            }
+            Err(MsgPackError::UnexpectedEof(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Reset internal reader state to decode from `input`.
     pub fn reset(&mut self, input: &[u8]) {
         self.inner.data = input.to_vec();
@@ -55,154 +89,21 @@ impl MsgPackDecoder {
 
     /// Skip any MessagePack value and return how many bytes it consumed.
     pub fn skip_any(&mut self) -> Result<usize, MsgPackError> {
-        if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
-        }
-        let start = self.inner.x;
-        let byte = self.inner.data[self.inner.x];
-        self.inner.x += 1;
-
-        // negative fixint: 0xe0-0xff
-        if byte >= 0xe0 {
-            return Ok(1);
-        }
-        // positive fixint: 0x00-0x7f
-        if byte <= 0x7f {
-            return Ok(1);
-        }
-        // fixmap: 0x80-0x8f
-        if (0x80..=0x8f).contains(&byte) {
-            let n = (byte & 0xf) as usize;
-            let s = self.skip_obj(n)?;
-            return Ok(1 + s);
-        }
-        // fixarray: 0x90-0x9f
-        if (0x90..=0x9f).contains(&byte) {
-            let n = (byte & 0xf) as usize;
-            let s = self.skip_arr(n)?;
-            return Ok(1 + s);
-        }
-        // fixstr: 0xa0-0xbf
-        if (0xa0..=0xbf).contains(&byte) {
-            let n = (byte & 0x1f) as usize;
-            return self.skip(n).map(|s| 1 + s);
-        }
-
-        let _after = match byte {
-            0xc0..=0xc3 => 0,
-            0xc4 => {
-                let n = self.read_u8_size()?;
-                self.skip(n)?;
-                n + 1
-            }
-            0xc5 => {
-                let n = self.read_u16_size()?;
-                self.skip(n)?;
-                n + 2
-            }
-            0xc6 => {
-                let n = self.read_u32_size()?;
-                self.skip(n)?;
-                n + 4
-            }
-            0xc7 => {
-                let n = self.read_u8_size()?;
-                self.skip(n + 1)?;
-                n + 2
-            } // ext8
-            0xc8 => {
-                let n = self.read_u16_size()?;
-                self.skip(n + 1)?;
-                n + 3
-            } // ext16
-            0xc9 => {
-                let n = self.read_u32_size()?;
-                self.skip(n + 1)?;
-                n + 5
-            } // ext32
-            0xca => self.skip(4)?,  // float32
-            0xcb => self.skip(8)?,  // float64
-            0xcc => self.skip(1)?,  // uint8
-            0xcd => self.skip(2)?,  // uint16
-            0xce => self.skip(4)?,  // uint32
-            0xcf => self.skip(8)?,  // uint64
-            0xd0 => self.skip(1)?,  // int8
-            0xd1 => self.skip(2)?,  // int16
-            0xd2 => self.skip(4)?,  // int32
-            0xd3 => self.skip(8)?,  // int64
-            0xd4 => self.skip(2)?,  // fixext1
-            0xd5 => self.skip(3)?,  // fixext2
-            0xd6 => self.skip(5)?,  // fixext4
-            0xd7 => self.skip(9)?,  // fixext8
-            0xd8 => self.skip(17)?, // fixext16
-            0xd9 => {
-                let n = self.read_u8_size()?;
-                self.skip(n)?;
-                n + 1
-            }
-            0xda => {
-                let n = self.read_u16_size()?;
-                self.skip(n)?;
-                n + 2
-            }
-            0xdb => {
-                let n = self.read_u32_size()?;
-                self.skip(n)?;
-                n + 4
-            }
-            0xdc => {
-                let n = self.read_u16_size()?;
-                let s = self.skip_arr(n)?;
-                s + 2
-            }
-            0xdd => {
-                let n = self.read_u32_size()?;
-                let s = self.skip_arr(n)?;
-                s + 4
-            }
-            0xde => {
-                let n = self.read_u16_size()?;
-                let s = self.skip_obj(n)?;
-                s + 2
-            }
-            0xdf => {
-                let n = self.read_u32_size()?;
-                let s = self.skip_obj(n)?;
-                s + 4
-            }
-            _ => 0,
-        };
-        Ok(self.inner.x - start)
-    }
-
-    fn skip_arr(&mut self, size: usize) -> Result<usize, MsgPackError> {
-        let mut total = 0;
-        for _ in 0..size {
-            total += self.skip_any()?;
-        }
-        Ok(total)
+        self.skip_view().map(|view| view.len())
     }
 
-    fn skip_obj(&mut self, size: usize) -> Result<usize, MsgPackError> {
-        let mut total = 0;
-        for _ in 0..size {
-            total += self.skip_any()?; // key
-            total += self.skip_any()?; // value
-        }
-        Ok(total)
-    }
-
-    fn skip(&mut self, n: usize) -> Result<usize, MsgPackError> {
-        if self.inner.x + n > self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
-        }
-        self.inner.x += n;
-        Ok(n)
+    /// Skip any MessagePack value and return its encoded bytes, as a view
+    /// into the input.
+    pub fn skip_view(&mut self) -> Result<BufView<'_>, MsgPackError> {
+        let start = self.inner.x;
+        skip_value(&self.inner.data, &mut self.inner.x)?;
+        BufView::range(&self.inner.data, start, self.inner.x)
+            .map_err(|_| MsgPackError::UnexpectedEof(start))
     }
 
     fn read_u8_size(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let v = self.inner.data[self.inner.x] as usize;
         self.inner.x += 1;
@@ -211,7 +112,7 @@ impl MsgPackDecoder {
 
     fn read_u16_size(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x + 2 > self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let v = u16::from_be_bytes([
             self.inner.data[self.inner.x],
@@ -223,7 +124,7 @@ impl MsgPackDecoder {
 
     fn read_u32_size(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x + 4 > self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let v = u32::from_be_bytes([
             self.inner.data[self.inner.x],
@@ -255,7 +156,7 @@ impl MsgPackDecoder {
 
     pub fn read_obj_hdr(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let byte = self.inner.data[self.inner.x];
         self.inner.x += 1;
@@ -271,7 +172,7 @@ impl MsgPackDecoder {
 
     pub fn read_arr_hdr(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let byte = self.inner.data[self.inner.x];
         self.inner.x += 1;
@@ -287,7 +188,7 @@ impl MsgPackDecoder {
 
     pub fn read_str_hdr(&mut self) -> Result<usize, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
         let byte = self.inner.data[self.inner.x];
         self.inner.x += 1;
@@ -342,16 +243,46 @@ impl MsgPackDecoder {
         Ok(self)
     }
 
+    /// Like [`find_path`](Self::find_path), for a parsed JSON Pointer. The
+    /// container at each step decides how a segment applies: a numeric
+    /// segment on a map looks up its decimal form as a key, and `-` never
+    /// resolves in an array.
+    #[cfg(feature = "std")]
+    pub fn find_pointer(&mut self, path: &[PathSegment]) -> Result<&mut Self, MsgPackError> {
+        for segment in path {
+            let byte = *self
+                .inner
+                .data
+                .get(self.inner.x)
+                .ok_or(MsgPackError::UnexpectedEof(self.inner.x))?;
+            let is_map = matches!(byte, 0xde | 0xdf) || (byte >> 4 == 0b1000);
+            match segment {
+                _ if is_map => {
+                    self.find_key(&segment.key())?;
+                }
+                PathSegment::Index(index) => {
+                    self.find_index(*index)?;
+                }
+                PathSegment::ArrayEnd | PathSegment::Key(_) => {
+                    self.read_arr_hdr()?;
+                    return Err(MsgPackError::IndexOutOfBounds);
+                }
+            }
+        }
+        Ok(self)
+    }
+
     fn val_one_level(&mut self) -> Result<PackValue, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
 
         let byte = self.inner.data[self.inner.x];
         let is_map = matches!(byte, 0xde | 0xdf) || (byte >> 4 == 0b1000);
         if is_map {
             let size = self.read_obj_hdr()?;
-            let mut obj = Vec::with_capacity(size);
+            let room = (self.inner.data.len() - self.inner.x) / 2;
+            let mut obj = Vec::with_capacity(size.min(room));
             for _ in 0..size {
                 let key = self.inner.read_key()?;
                 let value = self.primitive()?;
@@ -363,7 +294,8 @@ impl MsgPackDecoder {
         let is_array = matches!(byte, 0xdc | 0xdd) || (byte >> 4 == 0b1001);
         if is_array {
             let size = self.read_arr_hdr()?;
-            let mut arr = Vec::with_capacity(size);
+            let room = self.inner.data.len() - self.inner.x;
+            let mut arr = Vec::with_capacity(size.min(room));
             for _ in 0..size {
                 arr.push(self.primitive()?);
             }
@@ -375,20 +307,76 @@ impl MsgPackDecoder {
 
     fn primitive(&mut self) -> Result<PackValue, MsgPackError> {
         if self.inner.x >= self.inner.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.inner.x));
         }
 
         let byte = self.inner.data[self.inner.x];
         let is_map = matches!(byte, 0xde | 0xdf) || (byte >> 4 == 0b1000);
         let is_array = matches!(byte, 0xdc | 0xdd) || (byte >> 4 == 0b1001);
         if is_map || is_array {
-            let start = self.inner.x;
-            let length = self.skip_any()?;
-            let end = start + length;
-            let value = self.inner.data[start..end].to_vec();
+            let value = self.skip_view()?.to_vec();
             return Ok(PackValue::Blob(JsonPackValue::new(value)));
         }
 
         self.inner.read_any()
     }
 }
+
+/// Advances `x` past the MessagePack value at `data[*x..]` without decoding
+/// it. On error `x` is left inside the value.
+pub(crate) fn skip_value(data: &[u8], x: &mut usize) -> Result<(), MsgPackError> {
+    let size = |x: &mut usize, n: usize| -> Result<usize, MsgPackError> {
+        let bytes = data
+            .get(*x..*x + n)
+            .ok_or(MsgPackError::UnexpectedEof(*x))?;
+        *x += n;
+        Ok(bytes.iter().fold(0, |acc, &b| acc << 8 | b as usize))
+    };
+    let byte = *data.get(*x).ok_or(MsgPackError::UnexpectedEof(*x))?;
+    *x += 1;
+    // Payload bytes to skip, then nested values to skip.
+    let (payload, values) = match byte {
+        0x80..=0x8f => (0, 2 * (byte & 0xf) as usize),
+        0x90..=0x9f => (0, (byte & 0xf) as usize),
+        0xa0..=0xbf => ((byte & 0x1f) as usize, 0),
+        0xc4 | 0xd9 => (size(x, 1)?, 0),
+        0xc5 | 0xda => (size(x, 2)?, 0),
+        0xc6 | 0xdb => (size(x, 4)?, 0),
+        0xc7 => (size(x, 1)? + 1, 0), // ext8
+        0xc8 => (size(x, 2)? + 1, 0), // ext16
+        0xc9 => (
+            size(x, 4)?
+                .checked_add(1)
+                .ok_or(MsgPackError::InvalidSize)?,
+            0,
+        ), // ext32
+        0xcc | 0xd0 => (1, 0),
+        0xcd | 0xd1 | 0xd4 => (2, 0),
+        0xd5 => (3, 0),
+        0xca | 0xce | 0xd2 => (4, 0),
+        0xd6 => (5, 0),
+        0xcb | 0xcf | 0xd3 => (8, 0),
+        0xd7 => (9, 0),
+        0xd8 => (17, 0),
+        0xdc => (0, size(x, 2)?),
+        0xdd => (0, size(x, 4)?),
+        0xde => (0, 2 * size(x, 2)?),
+        // Twice a 32-bit count overflows `usize` on 32-bit targets.
+        0xdf => (
+            0,
+            size(x, 4)?
+                .checked_mul(2)
+                .ok_or(MsgPackError::InvalidSize)?,
+        ),
+        // Fixints, nil, booleans and the unused 0xc1.
+        _ => (0, 0),
+    };
+    if payload > data.len() - *x {
+        return Err(MsgPackError::UnexpectedEof(*x));
+    }
+    *x += payload;
+    for _ in 0..values {
+        skip_value(data, x)?;
+    }
+    Ok(())
+}
@@ -2,12 +2,37 @@
 //!
 //! Direct port of `msgpack/MsgPackDecoderFast.ts` from upstream.
 
+use super::constants::EXT_BIG_INT;
 use super::error::MsgPackError;
-use crate::{JsonPackExtension, PackValue};
+use super::ext::{MsgPackExtPolicy, MsgPackExtRegistry, MsgPackUndefinedPolicy};
+use crate::token::{walk, TokenSink};
+use crate::util::{CachedKey, KeyCache, KeyCacheStats};
+use crate::{JsonPackBigInt, JsonPackExtension, PackValue};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Options controlling MessagePack decoding.
+#[derive(Debug, Clone, Default)]
+pub struct MsgPackDecoderOptions {
+    /// When `true`, [`EXT_BIG_INT`] extensions are decoded as
+    /// [`PackValue::BigNum`] instead of a [`JsonPackExtension`].
+    pub big_int: bool,
+    /// Which wire form reads as [`PackValue::Undefined`].
+    pub undefined: MsgPackUndefinedPolicy,
+    /// Handling of extension types with no codec in `ext_registry`.
+    pub unknown_ext: MsgPackExtPolicy,
+    /// Codecs for application extension types.
+    pub ext_registry: MsgPackExtRegistry,
+}
 
 pub struct MsgPackDecoderFast {
     pub data: Vec<u8>,
     pub x: usize,
+    pub options: MsgPackDecoderOptions,
+    key_cache: Option<KeyCache>,
 }
 
 impl Default for MsgPackDecoderFast {
@@ -18,22 +43,52 @@ impl Default for MsgPackDecoderFast {
 
 impl MsgPackDecoderFast {
     pub fn new() -> Self {
+        Self::with_options(MsgPackDecoderOptions::default())
+    }
+
+    pub fn with_options(options: MsgPackDecoderOptions) -> Self {
         Self {
             data: Vec::new(),
             x: 0,
+            options,
+            key_cache: None,
         }
     }
 
+    /// Interns object keys through `cache`, which is kept across documents.
+    pub fn with_key_cache(mut self, cache: KeyCache) -> Self {
+        self.key_cache = Some(cache);
+        self
+    }
+
+    /// Counters of the key cache, if one was set.
+    pub fn key_cache_stats(&self) -> Option<KeyCacheStats> {
+        self.key_cache.as_ref().map(KeyCache::stats)
+    }
+
     pub fn decode(&mut self, input: &[u8]) -> Result<PackValue, MsgPackError> {
         self.data = input.to_vec();
         self.x = 0;
         self.read_any()
     }
 
+    /// Reads the value at the start of `input` as token events, returning
+    /// the number of bytes consumed.
+    pub fn parse_with<S: TokenSink + ?Sized>(
+        &mut self,
+        input: &[u8],
+        sink: &mut S,
+    ) -> Result<usize, MsgPackError> {
+        self.data = input.to_vec();
+        self.x = 0;
+        self.read_tokens(sink)?;
+        Ok(self.x)
+    }
+
     #[inline]
     fn check(&self, n: usize) -> Result<(), MsgPackError> {
         if self.x + n > self.data.len() {
-            Err(MsgPackError::UnexpectedEof)
+            Err(MsgPackError::UnexpectedEof(self.x))
         } else {
             Ok(())
         }
@@ -130,11 +185,11 @@ impl MsgPackDecoderFast {
     #[inline]
     fn utf8(&mut self, size: usize) -> Result<String, MsgPackError> {
         if self.x + size > self.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.x));
         }
         let slice = &self.data[self.x..self.x + size];
-        let s = std::str::from_utf8(slice)
-            .map_err(|_| MsgPackError::InvalidUtf8)?
+        let s = crate::utf8::from_utf8(slice)
+            .map_err(|_| MsgPackError::InvalidUtf8(self.x))?
             .to_string();
         self.x += size;
         Ok(s)
@@ -143,7 +198,7 @@ impl MsgPackDecoderFast {
     #[inline]
     fn buf(&mut self, size: usize) -> Result<Vec<u8>, MsgPackError> {
         if self.x + size > self.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.x));
         }
         let v = self.data[self.x..self.x + size].to_vec();
         self.x += size;
@@ -152,7 +207,7 @@ impl MsgPackDecoderFast {
 
     pub fn read_any(&mut self) -> Result<PackValue, MsgPackError> {
         if self.x >= self.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.x));
         }
         let byte = self.u8()?;
 
@@ -180,7 +235,10 @@ impl MsgPackDecoderFast {
 
         match byte {
             0xc0 => Ok(PackValue::Null),
-            0xc1 => Ok(PackValue::Undefined),
+            0xc1 => match self.options.undefined {
+                MsgPackUndefinedPolicy::Marker => Ok(PackValue::Undefined),
+                _ => Err(MsgPackError::InvalidByte(self.x - 1)),
+            },
             0xc2 => Ok(PackValue::Bool(false)),
             0xc3 => Ok(PackValue::Bool(true)),
             // bin8, bin16, bin32
@@ -272,7 +330,9 @@ impl MsgPackDecoderFast {
     }
 
     fn read_obj(&mut self, size: usize) -> Result<PackValue, MsgPackError> {
-        let mut obj = Vec::with_capacity(size);
+        // Every entry takes at least two bytes, which bounds what a forged
+        // size can make us reserve.
+        let mut obj = Vec::with_capacity(size.min((self.data.len() - self.x) / 2));
         for _ in 0..size {
             let key = self.read_key()?;
             if key == "__proto__" {
@@ -285,7 +345,7 @@ impl MsgPackDecoderFast {
     }
 
     fn read_arr(&mut self, size: usize) -> Result<PackValue, MsgPackError> {
-        let mut arr = Vec::with_capacity(size);
+        let mut arr = Vec::with_capacity(size.min(self.data.len() - self.x));
         for _ in 0..size {
             arr.push(self.read_any()?);
         }
@@ -293,45 +353,125 @@ impl MsgPackDecoderFast {
     }
 
     fn read_ext(&mut self, size: usize) -> Result<PackValue, MsgPackError> {
+        let offset = self.x;
         let tag = self.i8()?;
         let data = self.buf(size)?;
+        if tag == 0 && data == [0] && self.options.undefined == MsgPackUndefinedPolicy::FixExt {
+            return Ok(PackValue::Undefined);
+        }
+        if self.options.big_int && tag == EXT_BIG_INT {
+            return Ok(PackValue::BigNum(JsonPackBigInt::from_twos_complement(
+                &data,
+            )));
+        }
+        if let Some(value) = self.options.ext_registry.decode(tag, &data) {
+            let value = value.ok_or(MsgPackError::InvalidExt(offset))?;
+            return Ok(PackValue::Extension(Box::new(JsonPackExtension::new(
+                tag as i64 as u64,
+                value,
+            ))));
+        }
+        if self.options.unknown_ext == MsgPackExtPolicy::Error {
+            return Err(MsgPackError::UnknownExt { tag, offset });
+        }
         // Encode MsgPack extension as Extension(tag=ext_type, val=Bytes(data))
         Ok(PackValue::Extension(Box::new(JsonPackExtension::new(
-            tag as u8 as u64,
+            tag as i64 as u64,
             PackValue::Bytes(data),
         ))))
     }
 
+    fn read_tokens<S: TokenSink + ?Sized>(&mut self, sink: &mut S) -> Result<(), MsgPackError> {
+        let byte = *self
+            .data
+            .get(self.x)
+            .ok_or(MsgPackError::UnexpectedEof(self.x))?;
+        if !matches!(byte, 0x80..=0x9f | 0xdc..=0xdf) {
+            walk(&self.read_any()?, sink);
+            return Ok(());
+        }
+        self.x += 1;
+        let (map, size) = match byte {
+            0x80..=0x8f => (true, byte as usize & 0xf),
+            0x90..=0x9f => (false, byte as usize & 0xf),
+            0xdc => (false, self.u16()? as usize),
+            0xdd => (false, self.u32()? as usize),
+            0xde => (true, self.u16()? as usize),
+            _ => (true, self.u32()? as usize),
+        };
+        if map {
+            sink.on_map_start(Some(size));
+            for _ in 0..size {
+                let key = self.read_cached_key()?;
+                if &*key == "__proto__" {
+                    return Err(MsgPackError::InvalidKey);
+                }
+                sink.on_key(&key);
+                self.read_tokens(sink)?;
+            }
+            sink.on_map_end();
+        } else {
+            sink.on_array_start(Some(size));
+            for _ in 0..size {
+                self.read_tokens(sink)?;
+            }
+            sink.on_array_end();
+        }
+        Ok(())
+    }
+
     /// Read a string key (no __proto__ check — caller must check).
     pub fn read_key(&mut self) -> Result<String, MsgPackError> {
+        self.read_cached_key().map(String::from)
+    }
+
+    /// [`read_key`](Self::read_key) through the key cache, if set.
+    fn read_cached_key(&mut self) -> Result<CachedKey, MsgPackError> {
         if self.x >= self.data.len() {
-            return Err(MsgPackError::UnexpectedEof);
+            return Err(MsgPackError::UnexpectedEof(self.x));
         }
         let byte = self.data[self.x];
         // fixstr
         if (0xa0..=0xbf).contains(&byte) {
             let size = (byte & 0x1f) as usize;
             self.x += 1;
-            return self.utf8(size);
+            return self.key_utf8(size);
         }
         // str8
         if byte == 0xd9 {
             self.x += 1;
             let size = self.u8()? as usize;
-            return self.utf8(size);
+            return self.key_utf8(size);
         }
         // str16
         if byte == 0xda {
             self.x += 1;
             let size = self.u16()? as usize;
-            return self.utf8(size);
+            return self.key_utf8(size);
         }
         // str32
         if byte == 0xdb {
             self.x += 1;
             let size = self.u32()? as usize;
-            return self.utf8(size);
+            return self.key_utf8(size);
         }
         Err(MsgPackError::NotStr)
     }
+
+    fn key_utf8(&mut self, size: usize) -> Result<CachedKey, MsgPackError> {
+        let Some(cache) = &mut self.key_cache else {
+            return self.utf8(size).map(CachedKey::Decoded);
+        };
+        let x = self.x;
+        if x + size > self.data.len() {
+            return Err(MsgPackError::UnexpectedEof(x));
+        }
+        let key = cache.get_or_decode(&self.data[x..x + size], |raw| {
+            crate::utf8::from_utf8(raw)
+                .map(|s| s.to_string())
+                .map_err(|_| MsgPackError::InvalidUtf8(x))
+        })?;
+        self.x += size;
+        Ok(key)
+    }
 }
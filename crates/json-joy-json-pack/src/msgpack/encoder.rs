@@ -1,12 +1,56 @@
 //! `MsgPackEncoder` — full MessagePack encoder (handles all PackValue types).
 //!
-//! Direct port of `msgpack/MsgPackEncoder.ts` from upstream.
+//! Direct port of `msgpack/MsgPackEncoder.ts` from upstream, plus
+//! [`MsgPackEncoderOptions`] for output the fast encoder never produces.
 
 use super::encoder_fast::MsgPackEncoderFast;
-use crate::PackValue;
+use super::ext::{ext_type, MsgPackExtRegistry, MsgPackExtValuePolicy};
+use crate::checked::{self, EncodeLossError};
+use crate::token::{walk, TokenSink};
+use crate::{JsonPackBigInt, JsonPackExtension, PackValue};
+use alloc::{string::String, vec::Vec};
+use json_joy_buffers::Writer;
+
+/// Encoding policies for [`MsgPackEncoder`].
+///
+/// The defaults reproduce upstream output byte for byte.
+#[derive(Debug, Clone, Default)]
+pub struct MsgPackEncoderOptions {
+    /// Always use the smallest integer form: `uint8`/`int8` where the fast
+    /// encoder jumps to 16 bits, and `uint64`/`int64` where it falls back to
+    /// `float64` for integers beyond 32 bits.
+    pub smallest_int: bool,
+    /// Write floats as `float32` when the conversion is lossless.
+    pub float32: bool,
+    /// Never emit `str8` (`0xd9`), which predates support in some older
+    /// decoders; strings of 32..=255 bytes use `str16` instead. String
+    /// headers are also sized from the exact UTF-8 length.
+    pub avoid_str8: bool,
+    /// Write `undefined` as `d4 00 00` instead of `0xc1`, for peers that
+    /// read [`MsgPackUndefinedPolicy::FixExt`](super::MsgPackUndefinedPolicy::FixExt).
+    pub undefined_fixext: bool,
+    /// Codecs for extensions whose value is not a byte payload.
+    pub ext_registry: MsgPackExtRegistry,
+    /// Handling of extensions whose value is not bytes and has no codec.
+    pub ext_value: MsgPackExtValuePolicy,
+}
 
 pub struct MsgPackEncoder {
     pub inner: MsgPackEncoderFast,
+    pub options: MsgPackEncoderOptions,
+    /// Containers open via [`TokenSink`].
+    token_frames: Vec<TokenFrame>,
+    /// Extension type waiting for its byte payload.
+    token_tag: Option<u64>,
+}
+
+/// A container written through [`TokenSink`]. Unsized containers get a
+/// 32-bit header at `header` (relative to the writer's `x0`), patched with
+/// `count` when the container closes.
+struct TokenFrame {
+    map: bool,
+    header: Option<usize>,
+    count: u32,
 }
 
 impl Default for MsgPackEncoder {
@@ -17,8 +61,24 @@ impl Default for MsgPackEncoder {
 
 impl MsgPackEncoder {
     pub fn new() -> Self {
+        Self::with_options(MsgPackEncoderOptions::default())
+    }
+
+    pub fn with_options(options: MsgPackEncoderOptions) -> Self {
         Self {
             inner: MsgPackEncoderFast::new(),
+            options,
+            token_frames: Vec::new(),
+            token_tag: None,
+        }
+    }
+
+    pub fn with_writer(writer: Writer) -> Self {
+        Self {
+            inner: MsgPackEncoderFast::with_writer(writer),
+            options: MsgPackEncoderOptions::default(),
+            token_frames: Vec::new(),
+            token_tag: None,
         }
     }
 
@@ -28,8 +88,312 @@ impl MsgPackEncoder {
         self.inner.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on an integer the options
+    /// would round to `float64`, or on an extension they would write
+    /// without its type: one without a one-byte type, or whose value is
+    /// not bytes, not accepted by a registered codec and not nested.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        let options = &self.options;
+        checked::check(
+            value,
+            &|value| checked::msgpack_options_loss(value, options),
+            checked::Keys::Any,
+        )?;
+        Ok(self.encode(value))
+    }
+
     pub fn write_any(&mut self, value: &PackValue) {
-        // MsgPackEncoder handles all PackValue variants explicitly
-        self.inner.write_any(value);
+        match value {
+            PackValue::Integer(i) => self.write_integer(*i),
+            PackValue::UInteger(u) => self.write_u_integer(*u),
+            PackValue::Float(f) => self.write_float(*f),
+            PackValue::BigInt(i) => match i64::try_from(*i) {
+                Ok(i) if self.options.smallest_int => self.write_integer(i),
+                _ => match u64::try_from(*i) {
+                    Ok(u) if self.options.smallest_int => self.write_u_integer(u),
+                    _ => self.write_float(*i as f64),
+                },
+            },
+            PackValue::Str(s) => self.write_str(s),
+            PackValue::Array(arr) => self.write_arr(arr),
+            PackValue::Object(obj) => self.write_obj_pairs(obj),
+            PackValue::Map(entries) => {
+                self.inner.write_obj_hdr(entries.len());
+                for (key, val) in entries {
+                    self.write_any(key);
+                    self.write_any(val);
+                }
+            }
+            PackValue::Extension(ext) => self.encode_ext(ext),
+            PackValue::Undefined => self.write_undefined(),
+            other => self.inner.write_any(other),
+        }
+    }
+
+    pub fn write_integer(&mut self, int: i64) {
+        if !self.options.smallest_int {
+            return self.inner.write_integer(int);
+        }
+        if int >= 0 {
+            return self.write_u_integer(int as u64);
+        }
+        let writer = &mut self.inner.writer;
+        if int >= -0x20 {
+            writer.u8(int as u8);
+        } else if int >= i8::MIN as i64 {
+            writer.u8(0xd0);
+            writer.i8(int as i8);
+        } else if int >= i16::MIN as i64 {
+            writer.u8(0xd1);
+            writer.i16(int as i16);
+        } else if int >= i32::MIN as i64 {
+            writer.u8(0xd2);
+            writer.i32(int as i32);
+        } else {
+            writer.u8(0xd3);
+            writer.i64(int);
+        }
+    }
+
+    pub fn write_u_integer(&mut self, uint: u64) {
+        if !self.options.smallest_int {
+            return self.inner.write_u_integer(uint);
+        }
+        let writer = &mut self.inner.writer;
+        if uint <= 0x7f {
+            writer.u8(uint as u8);
+        } else if uint <= 0xff {
+            writer.u16(0xcc00 | uint as u16);
+        } else if uint <= 0xffff {
+            writer.u8u16(0xcd, uint as u16);
+        } else if uint <= 0xffff_ffff {
+            writer.u8u32(0xce, uint as u32);
+        } else {
+            writer.u8u64(0xcf, uint);
+        }
+    }
+
+    pub fn write_float(&mut self, float: f64) {
+        if self.options.float32 && (float as f32) as f64 == float {
+            self.inner.writer.u8f32(0xca, float as f32);
+        } else {
+            self.inner.write_float(float);
+        }
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        if !self.options.avoid_str8 {
+            return self.inner.write_str(s);
+        }
+        let length = s.len();
+        if length <= 0x1f {
+            self.inner.writer.u8(0xa0 | length as u8);
+        } else if length <= 0xffff {
+            self.inner.writer.u8u16(0xda, length as u16);
+        } else {
+            self.inner.writer.u8u32(0xdb, length as u32);
+        }
+        self.inner.writer.utf8(s);
+    }
+
+    pub fn write_arr(&mut self, arr: &[PackValue]) {
+        self.inner.write_arr_hdr(arr.len());
+        for item in arr {
+            self.write_any(item);
+        }
+    }
+
+    pub fn write_obj_pairs(&mut self, pairs: &[(String, PackValue)]) {
+        self.inner.write_obj_hdr(pairs.len());
+        for (key, val) in pairs {
+            self.write_str(key);
+            self.write_any(val);
+        }
+    }
+
+    pub fn write_undefined(&mut self) {
+        if self.options.undefined_fixext {
+            self.inner.writer.buf(&[0xd4, 0x00, 0x00]);
+        } else {
+            self.inner.writer.u8(0xc1);
+        }
+    }
+
+    pub fn encode_ext(&mut self, ext: &JsonPackExtension) {
+        let other = match ext.val.as_ref() {
+            PackValue::Bytes(_) => return self.inner.encode_ext(ext),
+            other => other,
+        };
+        // A tag with no one-byte type is dropped rather than truncated.
+        let Some(tag) = ext_type(ext.tag) else {
+            return self.write_any(other);
+        };
+        let payload = self.options.ext_registry.encode(tag, other);
+        let payload = payload.or_else(|| match self.options.ext_value {
+            MsgPackExtValuePolicy::Nest => {
+                Some(MsgPackEncoder::with_options(self.options.clone()).encode(other))
+            }
+            MsgPackExtValuePolicy::Unwrap => None,
+        });
+        match payload {
+            Some(data) => {
+                self.inner.encode_ext_header(tag, data.len());
+                self.inner.writer.buf(&data);
+            }
+            // Same fallback as the fast encoder, but honouring the options.
+            None => self.write_any(other),
+        }
+    }
+}
+
+impl MsgPackEncoder {
+    /// Counts a value towards an unsized array and takes the pending
+    /// extension type, if any.
+    fn token_value(&mut self) -> Option<u64> {
+        if let Some(frame) = self.token_frames.last_mut() {
+            if !frame.map {
+                frame.count += 1;
+            }
+        }
+        self.token_tag.take()
+    }
+
+    fn token_open(&mut self, map: bool, len: Option<usize>) {
+        self.token_value();
+        let header = match len {
+            Some(len) if map => {
+                self.inner.write_obj_hdr(len);
+                None
+            }
+            Some(len) => {
+                self.inner.write_arr_hdr(len);
+                None
+            }
+            None => {
+                let writer = &mut self.inner.writer;
+                let header = writer.x - writer.x0;
+                writer.u8u32(if map { 0xdf } else { 0xdd }, 0);
+                Some(header)
+            }
+        };
+        self.token_frames.push(TokenFrame {
+            map,
+            header,
+            count: 0,
+        });
+    }
+
+    fn token_close(&mut self) {
+        if let Some(TokenFrame {
+            header: Some(header),
+            count,
+            ..
+        }) = self.token_frames.pop()
+        {
+            let writer = &mut self.inner.writer;
+            let at = writer.x0 + header + 1;
+            writer.uint8[at..at + 4].copy_from_slice(&count.to_be_bytes());
+        }
+    }
+}
+
+/// Token events map onto the `write_*` methods. A tag followed by a byte
+/// string becomes an extension; any other tag is dropped, as in
+/// [`MsgPackEncoder::encode_ext`]. Containers without a known length get a
+/// 32-bit header that is filled in when they close.
+impl TokenSink for MsgPackEncoder {
+    fn on_null(&mut self) {
+        self.token_value();
+        self.inner.write_null();
+    }
+
+    fn on_undefined(&mut self) {
+        self.token_value();
+        self.write_undefined();
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        self.token_value();
+        self.inner.write_boolean(value);
+    }
+
+    fn on_int(&mut self, value: i64) {
+        self.token_value();
+        self.write_integer(value);
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        self.token_value();
+        self.write_u_integer(value);
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        self.token_value();
+        self.write_any(&PackValue::BigInt(value));
+    }
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        self.token_value();
+        self.inner.write_big_num(value);
+    }
+
+    fn on_float(&mut self, value: f64) {
+        self.token_value();
+        self.write_float(value);
+    }
+
+    fn on_str(&mut self, value: &str) {
+        self.token_value();
+        self.write_str(value);
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        // A tag with no one-byte type is dropped rather than truncated.
+        match self.token_value().and_then(ext_type) {
+            Some(tag) => {
+                self.inner.encode_ext_header(tag, value.len());
+                self.inner.writer.buf(value);
+            }
+            None => self.inner.write_bin(value),
+        }
+    }
+
+    fn on_array_start(&mut self, len: Option<usize>) {
+        self.token_open(false, len);
+    }
+
+    fn on_array_end(&mut self) {
+        self.token_close();
+    }
+
+    fn on_map_start(&mut self, len: Option<usize>) {
+        self.token_open(true, len);
+    }
+
+    fn on_key(&mut self, key: &str) {
+        if let Some(frame) = self.token_frames.last_mut() {
+            frame.count += 1;
+        }
+        self.write_str(key);
+    }
+
+    fn on_value_key(&mut self, key: &PackValue) {
+        if let Some(frame) = self.token_frames.last_mut() {
+            frame.count += 1;
+        }
+        walk(key, self);
+    }
+
+    fn on_map_end(&mut self) {
+        self.token_close();
+    }
+
+    fn on_tag(&mut self, tag: u64) {
+        self.token_tag = Some(tag);
+    }
+
+    fn on_raw(&mut self, bytes: &[u8]) {
+        self.token_value();
+        self.inner.writer.buf(bytes);
     }
 }
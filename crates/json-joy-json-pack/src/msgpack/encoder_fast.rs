@@ -2,12 +2,17 @@
 //!
 //! Direct port of `msgpack/MsgPackEncoderFast.ts` from upstream.
 
+use alloc::{string::String, vec::Vec};
 use json_joy_buffers::Writer;
 
-use crate::{JsonPackExtension, JsonPackValue, PackValue};
+use super::constants::EXT_BIG_INT;
+use super::ext::{ext_type, MsgPackExtRegistry};
+use crate::checked::{self, EncodeLossError};
+use crate::{JsonPackBigInt, JsonPackExtension, JsonPackValue, PackValue};
 
 pub struct MsgPackEncoderFast {
     pub writer: Writer,
+    ext_registry: MsgPackExtRegistry,
 }
 
 impl Default for MsgPackEncoderFast {
@@ -18,17 +23,43 @@ impl Default for MsgPackEncoderFast {
 
 impl MsgPackEncoderFast {
     pub fn new() -> Self {
+        Self::with_writer(Writer::new())
+    }
+
+    pub fn with_writer(writer: Writer) -> Self {
         Self {
-            writer: Writer::new(),
+            writer,
+            ext_registry: MsgPackExtRegistry::default(),
         }
     }
 
+    /// Encodes extensions whose value is not a byte payload through the
+    /// codecs in `registry`.
+    pub fn with_ext_registry(mut self, registry: MsgPackExtRegistry) -> Self {
+        self.ext_registry = registry;
+        self
+    }
+
     pub fn encode(&mut self, value: &PackValue) -> Vec<u8> {
         self.writer.reset();
         self.write_any(value);
         self.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on an integer that does not
+    /// survive the `float64` fallback, or on an extension that is neither a
+    /// byte payload nor accepted by a registered codec under a one-byte
+    /// type.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        let registry = &self.ext_registry;
+        checked::check(
+            value,
+            &|value| checked::msgpack_loss(value, registry),
+            checked::Keys::Any,
+        )?;
+        Ok(self.encode(value))
+    }
+
     pub fn write_any(&mut self, value: &PackValue) {
         match value {
             PackValue::Null => self.write_null(),
@@ -37,10 +68,12 @@ impl MsgPackEncoderFast {
             PackValue::UInteger(u) => self.write_u_integer(*u),
             PackValue::Float(f) => self.write_float(*f),
             PackValue::BigInt(i) => self.write_float(*i as f64),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj_pairs(obj),
+            PackValue::Map(map) => self.write_map_pairs(map),
             PackValue::Undefined => self.writer.u8(0xc1),
             PackValue::Extension(ext) => self.encode_ext(ext),
             PackValue::Blob(blob) => self.write_blob(blob),
@@ -221,6 +254,15 @@ impl MsgPackEncoderFast {
         }
     }
 
+    /// Writes a map whose keys keep their types.
+    pub fn write_map_pairs(&mut self, pairs: &[(PackValue, PackValue)]) {
+        self.write_obj_hdr(pairs.len());
+        for (key, val) in pairs {
+            self.write_any(key);
+            self.write_any(val);
+        }
+    }
+
     pub fn write_bin_hdr(&mut self, length: usize) {
         if length <= 0xff {
             self.writer.u16(0xc400 | length as u16);
@@ -258,12 +300,25 @@ impl MsgPackEncoderFast {
         }
     }
 
+    /// Write an arbitrary-precision integer as an [`EXT_BIG_INT`] extension.
+    pub fn write_big_num(&mut self, num: &JsonPackBigInt) {
+        let data = num.to_twos_complement();
+        self.encode_ext_header(EXT_BIG_INT, data.len());
+        self.writer.buf(&data);
+    }
+
     pub fn encode_ext(&mut self, ext: &JsonPackExtension) {
-        // MsgPack extension: tag is the ext type byte, val is Bytes payload
-        let tag = ext.tag as i8;
+        // MsgPack extension: tag is the ext type byte, val is Bytes payload.
+        // A tag with no one-byte type is dropped rather than truncated.
+        let Some(tag) = ext_type(ext.tag) else {
+            return self.write_any(ext.val.as_ref());
+        };
         if let PackValue::Bytes(data) = ext.val.as_ref() {
             self.encode_ext_header(tag, data.len());
             self.writer.buf(data);
+        } else if let Some(data) = self.ext_registry.encode(tag, &ext.val) {
+            self.encode_ext_header(tag, data.len());
+            self.writer.buf(&data);
         } else {
             // Fallback: encode the value and treat as bin
             self.write_any(ext.val.as_ref());
@@ -3,10 +3,13 @@
 //! Direct port of `msgpack/MsgPackEncoderStable.ts` from upstream.
 
 use super::encoder_fast::MsgPackEncoderFast;
-use crate::PackValue;
+use crate::{KeyOrder, PackValue};
+use alloc::{string::String, vec::Vec};
 
 pub struct MsgPackEncoderStable {
     pub inner: MsgPackEncoderFast,
+    /// Object key order; defaults to [`KeyOrder::Lexicographic`] like upstream.
+    pub key_order: KeyOrder,
 }
 
 impl Default for MsgPackEncoderStable {
@@ -17,8 +20,13 @@ impl Default for MsgPackEncoderStable {
 
 impl MsgPackEncoderStable {
     pub fn new() -> Self {
+        Self::with_key_order(KeyOrder::Lexicographic)
+    }
+
+    pub fn with_key_order(key_order: KeyOrder) -> Self {
         Self {
             inner: MsgPackEncoderFast::new(),
+            key_order,
         }
     }
 
@@ -42,10 +50,9 @@ impl MsgPackEncoderStable {
         }
     }
 
-    /// Write object with keys sorted lexicographically.
+    /// Write object with keys sorted by `self.key_order`.
     pub fn write_obj(&mut self, obj: &[(String, PackValue)]) {
-        let mut indices: Vec<usize> = (0..obj.len()).collect();
-        indices.sort_by(|&a, &b| obj[a].0.cmp(&obj[b].0));
+        let indices = self.key_order.sorted_indices(obj);
 
         self.inner.write_obj_hdr(obj.len());
         for idx in indices {
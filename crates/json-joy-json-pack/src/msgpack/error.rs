@@ -4,12 +4,12 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum MsgPackError {
-    #[error("unexpected end of input")]
-    UnexpectedEof,
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEof(usize),
     #[error("invalid key `__proto__`")]
     InvalidKey,
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
     #[error("invalid size")]
     InvalidSize,
     #[error("not an object")]
@@ -24,4 +24,30 @@ pub enum MsgPackError {
     IndexOutOfBounds,
     #[error("invalid MessagePack byte at offset {0}")]
     InvalidByte(usize),
+    /// A lazy view's typed getter found another type, or an integer out of
+    /// the requested range.
+    #[error("unexpected value type at offset {0}")]
+    UnexpectedType(usize),
+    /// An extension type with no registered codec, under
+    /// [`MsgPackExtPolicy::Error`](super::MsgPackExtPolicy::Error).
+    #[error("unknown extension type {tag} at offset {offset}")]
+    UnknownExt { tag: i8, offset: usize },
+    /// A registered extension codec rejected its payload.
+    #[error("invalid extension payload at offset {0}")]
+    InvalidExt(usize),
+}
+
+impl MsgPackError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedEof(x)
+            | Self::InvalidUtf8(x)
+            | Self::InvalidByte(x)
+            | Self::UnexpectedType(x)
+            | Self::InvalidExt(x)
+            | Self::UnknownExt { offset: x, .. } => Some(*x),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,109 @@
+//! Extension and `undefined` handling shared by the MessagePack encoder,
+//! decoder and JSON converter.
+//!
+//! Not an upstream port: upstream keeps every extension as raw bytes, and
+//! its JSON converter drops the extension type.
+
+use crate::PackValue;
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// The MessagePack extension type a [`JsonPackExtension`](crate::JsonPackExtension)
+/// tag stands for. Types are signed bytes and tags hold them sign-extended,
+/// so type -1 is `u64::MAX`; tags outside `-128..=127` have no type.
+pub(crate) fn ext_type(tag: u64) -> Option<i8> {
+    i8::try_from(tag as i64).ok()
+}
+
+/// What to do with an extension type that has no registered codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MsgPackExtPolicy {
+    /// Keep the payload bytes; the JSON converter writes them as a data URI
+    /// and drops the type, as upstream.
+    #[default]
+    Unwrap,
+    /// Keep the payload bytes; the JSON converter writes
+    /// `{"tag":<type>,"value":"<data URI>"}` so the type survives.
+    Wrap,
+    /// Fail with [`MsgPackError::UnknownExt`](super::MsgPackError::UnknownExt).
+    Error,
+}
+
+/// What the encoders do with an extension whose value is not a byte
+/// payload and that no registered codec accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MsgPackExtValuePolicy {
+    /// Write the value in place of the extension, dropping the type, as
+    /// upstream.
+    #[default]
+    Unwrap,
+    /// Write the value as MessagePack inside the extension payload, so the
+    /// type survives; it decodes back as bytes.
+    Nest,
+}
+
+/// How `undefined` is represented on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MsgPackUndefinedPolicy {
+    /// The never-used byte `0xc1`, as upstream.
+    #[default]
+    Marker,
+    /// Extension type 0 with the single byte `0x00` (`d4 00 00`); `0xc1`
+    /// is rejected.
+    FixExt,
+    /// Reject `0xc1`; extension type 0 is an ordinary extension.
+    Error,
+}
+
+/// Decoder and encoder for one application extension type.
+#[derive(Debug, Clone, Copy)]
+pub struct MsgPackExtCodec {
+    /// Turns a payload into a value, or `None` if the payload is malformed.
+    pub decode: fn(&[u8]) -> Option<PackValue>,
+    /// Turns a value back into a payload, or `None` if it does not fit.
+    pub encode: fn(&PackValue) -> Option<Vec<u8>>,
+}
+
+/// Codecs for application extension types, keyed by type.
+///
+/// Registered payloads decode to a [`PackValue::Extension`] holding the
+/// decoded value instead of its bytes, and [`MsgPackEncoder`](super::MsgPackEncoder)
+/// encodes such values back through the same codec.
+#[derive(Debug, Clone, Default)]
+pub struct MsgPackExtRegistry {
+    codecs: BTreeMap<i8, MsgPackExtCodec>,
+}
+
+impl MsgPackExtRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder form of [`register`](Self::register).
+    pub fn with(mut self, tag: i8, codec: MsgPackExtCodec) -> Self {
+        self.register(tag, codec);
+        self
+    }
+
+    /// Registers `codec` for `tag`, replacing any previous one.
+    pub fn register(&mut self, tag: i8, codec: MsgPackExtCodec) {
+        self.codecs.insert(tag, codec);
+    }
+
+    pub fn contains(&self, tag: i8) -> bool {
+        self.codecs.contains_key(&tag)
+    }
+
+    /// Decodes `data` with the codec for `tag`: `None` if no codec is
+    /// registered, `Some(None)` if the codec rejects the payload.
+    pub fn decode(&self, tag: i8, data: &[u8]) -> Option<Option<PackValue>> {
+        self.codecs.get(&tag).map(|codec| (codec.decode)(data))
+    }
+
+    /// Encodes `value` with the codec for `tag`, if one is registered and
+    /// accepts it.
+    pub fn encode(&self, tag: i8, value: &PackValue) -> Option<Vec<u8>> {
+        self.codecs
+            .get(&tag)
+            .and_then(|codec| (codec.encode)(value))
+    }
+}
@@ -0,0 +1,318 @@
+//! `JsonToMsgPackConverter` — converts JSON text to MessagePack directly.
+//!
+//! Not an upstream port; the inverse of [`MsgPackToJsonConverter`]. The JSON
+//! is tokenized with [`JsonDecoder::parse_with`] and each token is written
+//! straight to MessagePack, without building a `PackValue` tree. Output is
+//! byte-identical to encoding the decoded value with
+//! [`MsgPackEncoderFast`](super::MsgPackEncoderFast).
+//!
+//! [`with_wrapped_ext`](JsonToMsgPackConverter::with_wrapped_ext) reads
+//! extensions back from the `{"tag":<type>,"value":"<data URI>"}` objects
+//! the JSON converter writes under
+//! [`MsgPackExtPolicy::Wrap`](super::MsgPackExtPolicy::Wrap).
+//!
+//! [`MsgPackToJsonConverter`]: super::MsgPackToJsonConverter
+
+use json_joy_buffers::Writer;
+
+use super::encoder::MsgPackEncoder;
+use crate::json::{JsonDecoder, JsonDecoderOptions, JsonError};
+use crate::token::TokenSink;
+use crate::JsonPackBigInt;
+
+pub struct JsonToMsgPackConverter {
+    pub decoder: JsonDecoder,
+    sink: Sink,
+}
+
+impl Default for JsonToMsgPackConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonToMsgPackConverter {
+    pub fn new() -> Self {
+        Self::with_options(JsonDecoderOptions::default())
+    }
+
+    /// A converter reading JSON with the given decoder options, e.g.
+    /// [`JsonDecoderOptions::jsonc`] for input with comments.
+    pub fn with_options(options: JsonDecoderOptions) -> Self {
+        Self {
+            decoder: JsonDecoder::with_options(options),
+            sink: Sink {
+                encoder: MsgPackEncoder::new(),
+                frames: Vec::new(),
+                gaps: Vec::new(),
+                wrapped_ext: false,
+            },
+        }
+    }
+
+    /// Converts objects of exactly the form `{"tag":<type>,"value":"<data
+    /// URI>"}`, with a type in `-128..=127`, to extensions of that type
+    /// instead of maps.
+    pub fn with_wrapped_ext(mut self, enabled: bool) -> Self {
+        self.sink.wrapped_ext = enabled;
+        self
+    }
+
+    /// Converts the JSON value at the start of `input` to a MessagePack
+    /// blob.
+    pub fn convert(&mut self, input: &[u8]) -> Result<Vec<u8>, JsonError> {
+        let mut out = Writer::new();
+        self.convert_into(input, &mut out)?;
+        Ok(out.flush())
+    }
+
+    /// Converts the JSON value at the start of `input`, appending its
+    /// MessagePack to `out`, and returns the number of bytes read.
+    ///
+    /// On error nothing is appended.
+    pub fn convert_into(&mut self, input: &[u8], out: &mut Writer) -> Result<usize, JsonError> {
+        let mark = out.x - out.x0;
+        let writer = &mut self.sink.encoder.inner.writer;
+        core::mem::swap(writer, out);
+        self.sink.frames.clear();
+        self.sink.gaps.clear();
+        let result = self.decoder.parse_with(input, &mut self.sink);
+        core::mem::swap(&mut self.sink.encoder.inner.writer, out);
+        if result.is_err() {
+            out.x = out.x0 + mark;
+        }
+        result
+    }
+}
+
+/// A container being written: its reserved 5-byte header sits at `header`
+/// (relative to the writer's `x0`) and is filled in once `count` is known.
+struct Frame {
+    map: bool,
+    header: usize,
+    count: usize,
+    wrapped: Wrapped,
+}
+
+/// How much of a map so far reads as a wrapped extension,
+/// `{"tag":<type>,"value":"<data URI>"}`.
+enum Wrapped {
+    No,
+    Open,
+    Tag,
+    Type(i8),
+    Value(i8),
+    Done(i8, Vec<u8>),
+}
+
+/// Writes scalars through [`MsgPackEncoder`] and sizes containers itself.
+///
+/// A closed container's header is written at the end of its reserved slot,
+/// and the unused bytes before it are recorded in `gaps` (offset relative to
+/// the writer's `x0`, length). They are squeezed out in one pass when the
+/// outermost container closes, so each byte moves at most once.
+struct Sink {
+    encoder: MsgPackEncoder,
+    frames: Vec<Frame>,
+    gaps: Vec<(usize, usize)>,
+    wrapped_ext: bool,
+}
+
+impl Sink {
+    fn value(&mut self) {
+        self.item(|_| None);
+    }
+
+    /// Counts a value towards the innermost container and moves its
+    /// wrapped-extension match on by `step`; `None` ends the match.
+    fn item(&mut self, step: impl FnOnce(Wrapped) -> Option<Wrapped>) {
+        if let Some(frame) = self.frames.last_mut() {
+            if !frame.map {
+                frame.count += 1;
+            }
+            frame.wrapped = match core::mem::replace(&mut frame.wrapped, Wrapped::No) {
+                Wrapped::No => Wrapped::No,
+                state => step(state).unwrap_or(Wrapped::No),
+            };
+        }
+    }
+
+    fn open(&mut self, map: bool) {
+        self.value();
+        let writer = &mut self.encoder.inner.writer;
+        let header = writer.x - writer.x0;
+        writer.u8u32(0, 0);
+        self.frames.push(Frame {
+            map,
+            header,
+            count: 0,
+            wrapped: if map && self.wrapped_ext {
+                Wrapped::Open
+            } else {
+                Wrapped::No
+            },
+        });
+    }
+
+    fn close(&mut self) {
+        let Some(Frame {
+            map,
+            header,
+            count,
+            wrapped,
+        }) = self.frames.pop()
+        else {
+            return;
+        };
+        if let Wrapped::Done(tag, data) = wrapped {
+            // Only scalars sit inside, so no gaps past `header` remain.
+            let inner = &mut self.encoder.inner;
+            inner.writer.x = inner.writer.x0 + header;
+            inner.encode_ext_header(tag, data.len());
+            inner.writer.buf(&data);
+        } else {
+            let writer = &mut self.encoder.inner.writer;
+            let at = writer.x0 + header;
+            let mut hdr = [0u8; 5];
+            let size = if count <= 0xf {
+                hdr[0] = if map { 0x80 } else { 0x90 } | count as u8;
+                1
+            } else if count <= 0xffff {
+                hdr[0] = if map { 0xde } else { 0xdc };
+                hdr[1..3].copy_from_slice(&(count as u16).to_be_bytes());
+                3
+            } else {
+                hdr[0] = if map { 0xdf } else { 0xdd };
+                hdr[1..5].copy_from_slice(&(count as u32).to_be_bytes());
+                5
+            };
+            writer.uint8[at + 5 - size..at + 5].copy_from_slice(&hdr[..size]);
+            if size < 5 {
+                self.gaps.push((header, 5 - size));
+            }
+        }
+        if self.frames.is_empty() {
+            self.compact();
+        }
+    }
+
+    /// Removes the recorded gaps, moving the bytes between them left.
+    fn compact(&mut self) {
+        if self.gaps.is_empty() {
+            return;
+        }
+        // Inner containers close first, so gaps are recorded out of order.
+        self.gaps.sort_unstable();
+        let writer = &mut self.encoder.inner.writer;
+        let x0 = writer.x0;
+        let mut read = x0 + self.gaps[0].0;
+        let mut write = read;
+        for &(at, len) in &self.gaps {
+            let at = x0 + at;
+            writer.uint8.copy_within(read..at, write);
+            write += at - read;
+            read = at + len;
+        }
+        writer.uint8.copy_within(read..writer.x, write);
+        writer.x = write + (writer.x - read);
+        self.gaps.clear();
+    }
+}
+
+impl TokenSink for Sink {
+    fn on_null(&mut self) {
+        self.value();
+        self.encoder.on_null();
+    }
+
+    fn on_undefined(&mut self) {
+        self.value();
+        self.encoder.on_undefined();
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        self.value();
+        self.encoder.on_bool(value);
+    }
+
+    fn on_int(&mut self, value: i64) {
+        self.item(|state| match state {
+            Wrapped::Tag => i8::try_from(value).ok().map(Wrapped::Type),
+            _ => None,
+        });
+        self.encoder.on_int(value);
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        self.item(|state| match state {
+            Wrapped::Tag => i8::try_from(value).ok().map(Wrapped::Type),
+            _ => None,
+        });
+        self.encoder.on_uint(value);
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        self.value();
+        self.encoder.on_big_int(value);
+    }
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        self.value();
+        self.encoder.on_big_num(value);
+    }
+
+    fn on_float(&mut self, value: f64) {
+        self.value();
+        self.encoder.on_float(value);
+    }
+
+    fn on_str(&mut self, value: &str) {
+        self.value();
+        self.encoder.on_str(value);
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        self.item(|state| match state {
+            Wrapped::Value(tag) => Some(Wrapped::Done(tag, value.to_vec())),
+            _ => None,
+        });
+        self.encoder.on_bytes(value);
+    }
+
+    fn on_array_start(&mut self, _len: Option<usize>) {
+        self.open(false);
+    }
+
+    fn on_array_end(&mut self) {
+        self.close();
+    }
+
+    fn on_map_start(&mut self, _len: Option<usize>) {
+        self.open(true);
+    }
+
+    fn on_key(&mut self, key: &str) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.count += 1;
+            frame.wrapped = match (core::mem::replace(&mut frame.wrapped, Wrapped::No), key) {
+                (Wrapped::Open, "tag") => Wrapped::Tag,
+                (Wrapped::Type(tag), "value") => Wrapped::Value(tag),
+                _ => Wrapped::No,
+            };
+        }
+        self.encoder.on_key(key);
+    }
+
+    fn on_map_end(&mut self) {
+        self.close();
+    }
+
+    fn on_tag(&mut self, tag: u64) {
+        self.encoder.on_tag(tag);
+    }
+
+    fn on_raw(&mut self, bytes: &[u8]) {
+        self.value();
+        self.encoder.on_raw(bytes);
+    }
+}
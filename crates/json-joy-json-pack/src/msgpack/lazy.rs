@@ -0,0 +1,399 @@
+//! Lazy views over encoded MessagePack maps and arrays.
+//!
+//! Not an upstream port. In the spirit of Cap'n Proto and FlatBuffers
+//! readers, a [`LazyMap`] or [`LazyArray`] reads fields straight out of the
+//! encoded buffer: a lookup skips over the entries before it without
+//! decoding them and decodes only the value asked for. Strings and binaries
+//! are borrowed from the buffer; nothing is allocated unless
+//! [`LazyValue::decode`] is called. The `view` methods return the same
+//! borrows as a [`BufView`], which also carries their offsets.
+
+use super::decoder::skip_value;
+use super::decoder_fast::MsgPackDecoderFast;
+use super::error::MsgPackError;
+use crate::PackValue;
+use json_joy_buffers::BufView;
+
+/// Reads `n` big-endian bytes at `at`.
+fn uint(data: &[u8], at: usize, n: usize) -> Result<u64, MsgPackError> {
+    let bytes = data
+        .get(at..at + n)
+        .ok_or(MsgPackError::UnexpectedEof(at))?;
+    Ok(bytes.iter().fold(0, |acc, &b| acc << 8 | b as u64))
+}
+
+/// The value encoded at `data[x..]`.
+fn view_value(data: &[u8], x: usize) -> Result<BufView<'_>, MsgPackError> {
+    let mut end = x;
+    skip_value(data, &mut end)?;
+    BufView::range(data, x, end).map_err(|_| MsgPackError::UnexpectedEof(x))
+}
+
+/// One encoded value, not yet decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyValue<'a> {
+    data: &'a [u8],
+    x: usize,
+}
+
+impl<'a> LazyValue<'a> {
+    /// A view of the value encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, x: 0 }
+    }
+
+    /// Offset of the value in the buffer the view was created from.
+    pub fn offset(&self) -> usize {
+        self.x
+    }
+
+    fn byte(&self) -> Result<u8, MsgPackError> {
+        self.data
+            .get(self.x)
+            .copied()
+            .ok_or(MsgPackError::UnexpectedEof(self.x))
+    }
+
+    /// The value's encoded bytes.
+    pub fn bytes(&self) -> Result<&'a [u8], MsgPackError> {
+        self.view().map(|view| view.as_slice())
+    }
+
+    /// The value's encoded bytes, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, MsgPackError> {
+        view_value(self.data, self.x)
+    }
+
+    /// Decodes the whole value.
+    pub fn decode(&self) -> Result<PackValue, MsgPackError> {
+        MsgPackDecoderFast::new().decode(self.bytes()?)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.byte(), Ok(0xc0))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, MsgPackError> {
+        match self.byte()? {
+            0xc2 => Ok(false),
+            0xc3 => Ok(true),
+            _ => Err(MsgPackError::UnexpectedType(self.x)),
+        }
+    }
+
+    fn int(&self) -> Result<i128, MsgPackError> {
+        let (data, x) = (self.data, self.x + 1);
+        Ok(match self.byte()? {
+            byte @ 0x00..=0x7f => byte as i128,
+            byte @ 0xe0..=0xff => byte as i8 as i128,
+            0xcc => uint(data, x, 1)? as i128,
+            0xcd => uint(data, x, 2)? as i128,
+            0xce => uint(data, x, 4)? as i128,
+            0xcf => uint(data, x, 8)? as i128,
+            0xd0 => uint(data, x, 1)? as u8 as i8 as i128,
+            0xd1 => uint(data, x, 2)? as u16 as i16 as i128,
+            0xd2 => uint(data, x, 4)? as u32 as i32 as i128,
+            0xd3 => uint(data, x, 8)? as i64 as i128,
+            _ => return Err(MsgPackError::UnexpectedType(self.x)),
+        })
+    }
+
+    /// An integer in `u64` range.
+    pub fn as_u64(&self) -> Result<u64, MsgPackError> {
+        u64::try_from(self.int()?).map_err(|_| MsgPackError::UnexpectedType(self.x))
+    }
+
+    /// An integer in `i64` range.
+    pub fn as_i64(&self) -> Result<i64, MsgPackError> {
+        i64::try_from(self.int()?).map_err(|_| MsgPackError::UnexpectedType(self.x))
+    }
+
+    /// A float, or an integer converted to one.
+    pub fn as_f64(&self) -> Result<f64, MsgPackError> {
+        match self.byte()? {
+            0xca => Ok(f32::from_bits(uint(self.data, self.x + 1, 4)? as u32) as f64),
+            0xcb => Ok(f64::from_bits(uint(self.data, self.x + 1, 8)?)),
+            _ => Ok(self.int()? as f64),
+        }
+    }
+
+    /// The payload of a `str` (`is_str`) or `bin` value, if it is one.
+    fn payload(&self, is_str: bool) -> Result<Option<BufView<'a>>, MsgPackError> {
+        let (data, x) = (self.data, self.x + 1);
+        let (start, len) = match (is_str, self.byte()?) {
+            (true, byte @ 0xa0..=0xbf) => (x, (byte & 0x1f) as usize),
+            (true, 0xd9) | (false, 0xc4) => (x + 1, uint(data, x, 1)? as usize),
+            (true, 0xda) | (false, 0xc5) => (x + 2, uint(data, x, 2)? as usize),
+            (true, 0xdb) | (false, 0xc6) => (x + 4, uint(data, x, 4)? as usize),
+            _ => return Ok(None),
+        };
+        BufView::range(data, start, start + len)
+            .map(Some)
+            .map_err(|_| MsgPackError::UnexpectedEof(start))
+    }
+
+    /// A string, borrowed from the buffer.
+    pub fn as_str(&self) -> Result<&'a str, MsgPackError> {
+        let bytes = self.payload(true)?.ok_or(MsgPackError::NotStr)?;
+        crate::utf8::from_utf8(bytes.as_slice()).map_err(|_| MsgPackError::InvalidUtf8(self.x))
+    }
+
+    /// A `bin` value, borrowed from the buffer.
+    pub fn as_bytes(&self) -> Result<&'a [u8], MsgPackError> {
+        self.bin_view().map(|view| view.as_slice())
+    }
+
+    /// A `bin` value's payload, as a view into the buffer.
+    pub fn bin_view(&self) -> Result<BufView<'a>, MsgPackError> {
+        self.payload(false)?
+            .ok_or(MsgPackError::UnexpectedType(self.x))
+    }
+
+    pub fn as_map(&self) -> Result<LazyMap<'a>, MsgPackError> {
+        let (data, x) = (self.data, self.x + 1);
+        let (x, len) = match self.byte()? {
+            byte @ 0x80..=0x8f => (x, (byte & 0xf) as usize),
+            0xde => (x + 2, uint(data, x, 2)? as usize),
+            0xdf => (x + 4, uint(data, x, 4)? as usize),
+            _ => return Err(MsgPackError::NotObj),
+        };
+        Ok(LazyMap {
+            data,
+            start: self.x,
+            x,
+            len,
+        })
+    }
+
+    pub fn as_array(&self) -> Result<LazyArray<'a>, MsgPackError> {
+        let (data, x) = (self.data, self.x + 1);
+        let (x, len) = match self.byte()? {
+            byte @ 0x90..=0x9f => (x, (byte & 0xf) as usize),
+            0xdc => (x + 2, uint(data, x, 2)? as usize),
+            0xdd => (x + 4, uint(data, x, 4)? as usize),
+            _ => return Err(MsgPackError::NotArr),
+        };
+        Ok(LazyArray {
+            data,
+            start: self.x,
+            x,
+            len,
+        })
+    }
+}
+
+/// Typed getters over `get`: `Ok(None)` when the entry is missing, an error
+/// when it is present but of another type.
+macro_rules! typed_getters {
+    ($key:ty) => {
+        pub fn get_str(&self, key: $key) -> Result<Option<&'a str>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_str()).transpose()
+        }
+
+        pub fn get_bytes(&self, key: $key) -> Result<Option<&'a [u8]>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_bytes()).transpose()
+        }
+
+        pub fn get_u64(&self, key: $key) -> Result<Option<u64>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_u64()).transpose()
+        }
+
+        pub fn get_i64(&self, key: $key) -> Result<Option<i64>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_i64()).transpose()
+        }
+
+        pub fn get_f64(&self, key: $key) -> Result<Option<f64>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_f64()).transpose()
+        }
+
+        pub fn get_bool(&self, key: $key) -> Result<Option<bool>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_bool()).transpose()
+        }
+
+        pub fn get_map(&self, key: $key) -> Result<Option<LazyMap<'a>>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_map()).transpose()
+        }
+
+        pub fn get_array(&self, key: $key) -> Result<Option<LazyArray<'a>>, MsgPackError> {
+            self.get(key)?.map(|v| v.as_array()).transpose()
+        }
+    };
+}
+
+/// A view of an encoded map.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyMap<'a> {
+    data: &'a [u8],
+    /// Offset of the header.
+    start: usize,
+    /// Offset of the first key.
+    x: usize,
+    len: usize,
+}
+
+impl<'a> LazyMap<'a> {
+    /// A view of the map encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Result<Self, MsgPackError> {
+        LazyValue::new(data).as_map()
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The whole encoded map, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, MsgPackError> {
+        view_value(self.data, self.start)
+    }
+
+    /// Entries in encoded order.
+    pub fn iter(&self) -> LazyMapIter<'a> {
+        LazyMapIter {
+            data: self.data,
+            x: self.x,
+            left: self.len,
+        }
+    }
+
+    /// The value under the first string key equal to `key`. Keys of other
+    /// types are skipped.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'a>>, MsgPackError> {
+        for entry in self.iter() {
+            let (k, value) = entry?;
+            if k.payload(true)?.as_deref() == Some(key.as_bytes()) {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    typed_getters!(&str);
+}
+
+/// A view of an encoded array.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyArray<'a> {
+    data: &'a [u8],
+    /// Offset of the header.
+    start: usize,
+    /// Offset of the first item.
+    x: usize,
+    len: usize,
+}
+
+impl<'a> LazyArray<'a> {
+    /// A view of the array encoded at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Result<Self, MsgPackError> {
+        LazyValue::new(data).as_array()
+    }
+
+    /// Number of items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The whole encoded array, as a view into the buffer.
+    pub fn view(&self) -> Result<BufView<'a>, MsgPackError> {
+        view_value(self.data, self.start)
+    }
+
+    pub fn iter(&self) -> LazyArrayIter<'a> {
+        LazyArrayIter {
+            data: self.data,
+            x: self.x,
+            left: self.len,
+        }
+    }
+
+    /// The item at `index`, skipping the ones before it.
+    pub fn get(&self, index: usize) -> Result<Option<LazyValue<'a>>, MsgPackError> {
+        if index >= self.len {
+            return Ok(None);
+        }
+        let mut x = self.x;
+        for _ in 0..index {
+            skip_value(self.data, &mut x)?;
+        }
+        Ok(Some(LazyValue { data: self.data, x }))
+    }
+
+    typed_getters!(usize);
+}
+
+/// Iterator over a [`LazyMap`]'s `(key, value)` entries. Stops after the
+/// first error.
+pub struct LazyMapIter<'a> {
+    data: &'a [u8],
+    x: usize,
+    left: usize,
+}
+
+impl<'a> Iterator for LazyMapIter<'a> {
+    type Item = Result<(LazyValue<'a>, LazyValue<'a>), MsgPackError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let key = LazyValue {
+            data: self.data,
+            x: self.x,
+        };
+        let result = skip_value(self.data, &mut self.x).and_then(|()| {
+            let value = LazyValue {
+                data: self.data,
+                x: self.x,
+            };
+            skip_value(self.data, &mut self.x).map(|()| (key, value))
+        });
+        if result.is_err() {
+            self.left = 0;
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left))
+    }
+}
+
+/// Iterator over a [`LazyArray`]'s items. Stops after the first error.
+pub struct LazyArrayIter<'a> {
+    data: &'a [u8],
+    x: usize,
+    left: usize,
+}
+
+impl<'a> Iterator for LazyArrayIter<'a> {
+    type Item = Result<LazyValue<'a>, MsgPackError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let value = LazyValue {
+            data: self.data,
+            x: self.x,
+        };
+        let result = skip_value(self.data, &mut self.x).map(|()| value);
+        if result.is_err() {
+            self.left = 0;
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left))
+    }
+}
@@ -9,19 +9,32 @@ pub mod encoder;
 pub mod encoder_fast;
 pub mod encoder_stable;
 pub mod error;
+pub mod ext;
+#[cfg(feature = "std")]
+pub mod from_json;
+pub mod lazy;
 pub mod shallow_read;
+#[cfg(feature = "std")]
 pub mod to_json;
 pub mod types;
 pub mod util;
 
 pub use constants::MsgPackMarker;
 pub use decoder::{MsgPackDecoder, MsgPackPathSegment};
-pub use decoder_fast::MsgPackDecoderFast;
-pub use encoder::MsgPackEncoder;
+pub use decoder_fast::{MsgPackDecoderFast, MsgPackDecoderOptions};
+pub use encoder::{MsgPackEncoder, MsgPackEncoderOptions};
 pub use encoder_fast::MsgPackEncoderFast;
 pub use encoder_stable::MsgPackEncoderStable;
 pub use error::MsgPackError;
+pub use ext::{
+    MsgPackExtCodec, MsgPackExtPolicy, MsgPackExtRegistry, MsgPackExtValuePolicy,
+    MsgPackUndefinedPolicy,
+};
+#[cfg(feature = "std")]
+pub use from_json::JsonToMsgPackConverter;
+pub use lazy::{LazyArray, LazyArrayIter, LazyMap, LazyMapIter, LazyValue};
 pub use shallow_read::{gen_shallow_reader, ShallowReader};
-pub use to_json::MsgPackToJsonConverter;
+#[cfg(feature = "std")]
+pub use to_json::{MsgPackToJsonConverter, MsgPackToJsonOptions};
 pub use types::{IMessagePackEncoder, MsgPack};
 pub use util::{decode, encode, encode_full};
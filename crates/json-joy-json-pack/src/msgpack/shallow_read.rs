@@ -6,6 +6,7 @@
 //! over the captured path and reuse `MsgPackDecoder::find_path`.
 
 use super::{MsgPackDecoder, MsgPackError, MsgPackPathSegment};
+use alloc::boxed::Box;
 
 /// Path reader closure returned by [`gen_shallow_reader`].
 pub type ShallowReader<'a> = Box<dyn Fn(&mut MsgPackDecoder) -> Result<usize, MsgPackError> + 'a>;
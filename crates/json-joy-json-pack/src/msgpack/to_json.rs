@@ -5,12 +5,39 @@
 //! Converts a binary MessagePack blob to a JSON string without allocating
 //! intermediate `PackValue` objects. Binary and extension data are encoded
 //! as data URI strings.
+//!
+//! Rust-only additions: [`convert_into`](MsgPackToJsonConverter::convert_into)
+//! appends to a caller's [`Writer`], and
+//! [`convert_ndjson`](MsgPackToJsonConverter::convert_ndjson) turns a stream
+//! of concatenated messages into newline-delimited JSON, and
+//! [`MsgPackToJsonOptions`] controls extensions and `undefined`.
+
+use core::fmt::{self, Write as _};
+
+use json_joy_buffers::Writer;
 
+use super::error::MsgPackError;
+use super::ext::{MsgPackExtPolicy, MsgPackExtRegistry, MsgPackUndefinedPolicy};
+use crate::json::JsonEncoder;
 use crate::json_binary::constants::BIN_URI_START;
 
+/// Options for [`MsgPackToJsonConverter`].
+///
+/// The defaults reproduce upstream output.
+#[derive(Debug, Clone, Default)]
+pub struct MsgPackToJsonOptions {
+    /// Which wire form converts as `undefined` (written as `null`).
+    pub undefined: MsgPackUndefinedPolicy,
+    /// Handling of extension types with no codec in `ext_registry`.
+    pub unknown_ext: MsgPackExtPolicy,
+    /// Codecs for application extension types; decoded values are written
+    /// as JSON.
+    pub ext_registry: MsgPackExtRegistry,
+}
+
 pub struct MsgPackToJsonConverter {
-    data: Vec<u8>,
-    x: usize,
+    writer: Writer,
+    pub options: MsgPackToJsonOptions,
 }
 
 impl Default for MsgPackToJsonConverter {
@@ -21,247 +48,344 @@ impl Default for MsgPackToJsonConverter {
 
 impl MsgPackToJsonConverter {
     pub fn new() -> Self {
+        Self::with_options(MsgPackToJsonOptions::default())
+    }
+
+    pub fn with_options(options: MsgPackToJsonOptions) -> Self {
         Self {
-            data: Vec::new(),
-            x: 0,
+            writer: Writer::new(),
+            options,
         }
     }
 
     /// Convert a MessagePack blob to a JSON string.
-    pub fn convert(&mut self, input: &[u8]) -> String {
-        self.data = input.to_vec();
-        self.x = 0;
-        self.val()
+    ///
+    /// As upstream, values missing at the end of the input convert to
+    /// `null`. Fails if the input ends inside a string, binary, extension
+    /// or number, or holds a value the options reject.
+    pub fn convert(&mut self, input: &[u8]) -> Result<String, MsgPackError> {
+        let mut cursor = Cursor::new(input, false, &self.options);
+        let result = cursor.val(&mut self.writer);
+        let json = self.writer.flush();
+        result?;
+        String::from_utf8(json).map_err(|_| MsgPackError::InvalidUtf8(cursor.x))
     }
 
-    fn u8(&mut self) -> u8 {
-        let v = self.data[self.x];
-        self.x += 1;
-        v
+    /// Converts the MessagePack value at the start of `input`, appending its
+    /// JSON to `out`, and returns the number of bytes read.
+    ///
+    /// Fails with [`MsgPackError::UnexpectedEof`] if the value is cut off,
+    /// or with the error of a value the options reject, in which case
+    /// nothing is appended.
+    pub fn convert_into(&mut self, input: &[u8], out: &mut Writer) -> Result<usize, MsgPackError> {
+        let mut cursor = Cursor::new(input, true, &self.options);
+        let mark = out.x - out.x0;
+        match cursor.val(out) {
+            Ok(()) => Ok(cursor.x),
+            Err(err) => {
+                out.x = out.x0 + mark;
+                Err(err)
+            }
+        }
     }
 
-    fn u16(&mut self) -> u16 {
-        let v = u16::from_be_bytes([self.data[self.x], self.data[self.x + 1]]);
-        self.x += 2;
-        v
+    /// Converts concatenated MessagePack values into NDJSON, appending one
+    /// line per value to `out`, and returns the number of bytes converted.
+    ///
+    /// A final value cut off by the end of `input` is not converted, so a
+    /// caller reading a stream in chunks keeps the unconverted tail and
+    /// prepends it to the next chunk.
+    pub fn convert_ndjson(&mut self, input: &[u8], out: &mut Writer) -> usize {
+        let mut x = 0;
+        while x < input.len() {
+            match self.convert_into(&input[x..], out) {
+                Ok(n) => {
+                    out.u8(b'\n');
+                    x += n;
+                }
+                Err(_) => break,
+            }
+        }
+        x
     }
+}
+
+/// Forwards `write!` output to a [`Writer`].
+struct WriterFmt<'a>(&'a mut Writer);
 
-    fn u32(&mut self) -> u32 {
-        let v = u32::from_be_bytes([
-            self.data[self.x],
-            self.data[self.x + 1],
-            self.data[self.x + 2],
-            self.data[self.x + 3],
-        ]);
-        self.x += 4;
-        v
+impl fmt::Write for WriterFmt<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.utf8(s);
+        Ok(())
     }
+}
+
+/// Writes `value` with its `Display` formatting.
+fn write_display(out: &mut Writer, value: impl fmt::Display) {
+    let _ = write!(WriterFmt(out), "{value}");
+}
+
+/// Writes `buf` as a quoted binary data URI.
+fn write_data_uri(out: &mut Writer, buf: &[u8]) {
+    out.u8(b'"');
+    out.ascii(BIN_URI_START);
+    out.ascii(&json_joy_base64::to_base64(buf));
+    out.u8(b'"');
+}
 
-    fn i8(&mut self) -> i8 {
-        let v = self.data[self.x] as i8;
-        self.x += 1;
-        v
+struct Cursor<'a> {
+    data: &'a [u8],
+    x: usize,
+    /// Fail on a value missing at the end of input instead of writing
+    /// `null`.
+    strict: bool,
+    options: &'a MsgPackToJsonOptions,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8], strict: bool, options: &'a MsgPackToJsonOptions) -> Self {
+        Self {
+            data,
+            x: 0,
+            strict,
+            options,
+        }
     }
 
-    fn i16(&mut self) -> i16 {
-        let v = i16::from_be_bytes([self.data[self.x], self.data[self.x + 1]]);
-        self.x += 2;
-        v
+    fn take(&mut self, n: usize) -> Result<&'a [u8], MsgPackError> {
+        let data = self.data;
+        if n > data.len() - self.x {
+            return Err(MsgPackError::UnexpectedEof(self.x));
+        }
+        self.x += n;
+        Ok(&data[self.x - n..self.x])
     }
 
-    fn i32(&mut self) -> i32 {
-        let v = i32::from_be_bytes([
-            self.data[self.x],
-            self.data[self.x + 1],
-            self.data[self.x + 2],
-            self.data[self.x + 3],
-        ]);
-        self.x += 4;
-        v
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], MsgPackError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
     }
 
-    fn f32(&mut self) -> f32 {
-        let v = f32::from_be_bytes([
-            self.data[self.x],
-            self.data[self.x + 1],
-            self.data[self.x + 2],
-            self.data[self.x + 3],
-        ]);
-        self.x += 4;
-        v
+    fn u8(&mut self) -> Result<u8, MsgPackError> {
+        Ok(self.take(1)?[0])
     }
 
-    fn f64(&mut self) -> f64 {
-        let v = f64::from_be_bytes([
-            self.data[self.x],
-            self.data[self.x + 1],
-            self.data[self.x + 2],
-            self.data[self.x + 3],
-            self.data[self.x + 4],
-            self.data[self.x + 5],
-            self.data[self.x + 6],
-            self.data[self.x + 7],
-        ]);
-        self.x += 8;
-        v
+    fn u16(&mut self) -> Result<u16, MsgPackError> {
+        self.array().map(u16::from_be_bytes)
     }
 
-    fn val(&mut self) -> String {
+    fn u32(&mut self) -> Result<u32, MsgPackError> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn val(&mut self, out: &mut Writer) -> Result<(), MsgPackError> {
         if self.x >= self.data.len() {
-            return "null".to_string();
+            if self.strict {
+                return Err(MsgPackError::UnexpectedEof(self.x));
+            }
+            out.ascii("null");
+            return Ok(());
         }
-        let byte = self.u8();
+        let byte = self.u8()?;
 
         if byte >= 0xe0 {
-            return (byte as i8 as i32).to_string();
+            write_display(out, byte as i8);
+            return Ok(());
         }
         if byte <= 0x7f {
-            return byte.to_string();
+            write_display(out, byte);
+            return Ok(());
         }
         if (0x80..=0x8f).contains(&byte) {
-            return self.obj((byte & 0xf) as usize);
+            return self.obj((byte & 0xf) as usize, out);
         }
         if (0x90..=0x9f).contains(&byte) {
-            return self.arr((byte & 0xf) as usize);
+            return self.arr((byte & 0xf) as usize, out);
         }
         if (0xa0..=0xbf).contains(&byte) {
             let n = (byte & 0x1f) as usize;
-            return self.str(n);
+            return self.str(n, out);
         }
 
         match byte {
-            0xc0 => "null".to_string(),
-            0xc1 => "null".to_string(), // undefined → null in JSON
-            0xc2 => "false".to_string(),
-            0xc3 => "true".to_string(),
+            0xc0 => out.ascii("null"),
+            0xc1 => match self.options.undefined {
+                MsgPackUndefinedPolicy::Marker => out.ascii("null"), // undefined → null in JSON
+                _ => return Err(MsgPackError::InvalidByte(self.x - 1)),
+            },
+            0xc2 => out.ascii("false"),
+            0xc3 => out.ascii("true"),
             0xc4 => {
-                let n = self.u8() as usize;
-                self.bin(n)
+                let n = self.u8()? as usize;
+                self.bin(n, out)?;
             }
             0xc5 => {
-                let n = self.u16() as usize;
-                self.bin(n)
+                let n = self.u16()? as usize;
+                self.bin(n, out)?;
             }
             0xc6 => {
-                let n = self.u32() as usize;
-                self.bin(n)
+                let n = self.u32()? as usize;
+                self.bin(n, out)?;
             }
             0xc7 => {
-                let n = self.u8() as usize;
-                self.ext_val(n)
+                let n = self.u8()? as usize;
+                self.ext_val(n, out)?;
             }
             0xc8 => {
-                let n = self.u16() as usize;
-                self.ext_val(n)
+                let n = self.u16()? as usize;
+                self.ext_val(n, out)?;
             }
             0xc9 => {
-                let n = self.u32() as usize;
-                self.ext_val(n)
+                let n = self.u32()? as usize;
+                self.ext_val(n, out)?;
             }
-            0xca => self.f32().to_string(),
-            0xcb => self.f64().to_string(),
-            0xcc => self.u8().to_string(),
-            0xcd => self.u16().to_string(),
-            0xce => self.u32().to_string(),
-            0xcf => {
-                let hi = self.u32() as u64;
-                let lo = self.u32() as u64;
-                (hi * 4294967296 + lo).to_string()
-            }
-            0xd0 => self.i8().to_string(),
-            0xd1 => self.i16().to_string(),
-            0xd2 => self.i32().to_string(),
-            0xd3 => {
-                let hi = self.i32() as i64;
-                let lo = self.u32() as i64;
-                (hi * 4294967296 + lo).to_string()
-            }
-            0xd4 => self.ext_val(1),
-            0xd5 => self.ext_val(2),
-            0xd6 => self.ext_val(4),
-            0xd7 => self.ext_val(8),
-            0xd8 => self.ext_val(16),
+            0xca => write_display(out, f32::from_be_bytes(self.array()?)),
+            0xcb => write_display(out, f64::from_be_bytes(self.array()?)),
+            0xcc => write_display(out, self.u8()?),
+            0xcd => write_display(out, self.u16()?),
+            0xce => write_display(out, self.u32()?),
+            0xcf => write_display(out, u64::from_be_bytes(self.array()?)),
+            0xd0 => write_display(out, i8::from_be_bytes(self.array()?)),
+            0xd1 => write_display(out, i16::from_be_bytes(self.array()?)),
+            0xd2 => write_display(out, i32::from_be_bytes(self.array()?)),
+            0xd3 => write_display(out, i64::from_be_bytes(self.array()?)),
+            0xd4 => self.ext_val(1, out)?,
+            0xd5 => self.ext_val(2, out)?,
+            0xd6 => self.ext_val(4, out)?,
+            0xd7 => self.ext_val(8, out)?,
+            0xd8 => self.ext_val(16, out)?,
             0xd9 => {
-                let n = self.u8() as usize;
-                self.str(n)
+                let n = self.u8()? as usize;
+                self.str(n, out)?;
             }
             0xda => {
-                let n = self.u16() as usize;
-                self.str(n)
+                let n = self.u16()? as usize;
+                self.str(n, out)?;
             }
             0xdb => {
-                let n = self.u32() as usize;
-                self.str(n)
+                let n = self.u32()? as usize;
+                self.str(n, out)?;
             }
             0xdc => {
-                let n = self.u16() as usize;
-                self.arr(n)
+                let n = self.u16()? as usize;
+                self.arr(n, out)?;
             }
             0xdd => {
-                let n = self.u32() as usize;
-                self.arr(n)
+                let n = self.u32()? as usize;
+                self.arr(n, out)?;
             }
             0xde => {
-                let n = self.u16() as usize;
-                self.obj(n)
+                let n = self.u16()? as usize;
+                self.obj(n, out)?;
             }
             0xdf => {
-                let n = self.u32() as usize;
-                self.obj(n)
+                let n = self.u32()? as usize;
+                self.obj(n, out)?;
             }
-            _ => "null".to_string(),
+            _ => out.ascii("null"),
         }
+        Ok(())
     }
 
-    fn str(&mut self, size: usize) -> String {
-        let slice = &self.data[self.x..self.x + size];
-        let s = std::str::from_utf8(slice).unwrap_or("").to_string();
-        self.x += size;
-        // JSON-encode the string
-        serde_json::to_string(&s).unwrap_or_else(|_| "\"\"".to_string())
+    fn str(&mut self, size: usize, out: &mut Writer) -> Result<(), MsgPackError> {
+        let slice = self.take(size)?;
+        // Invalid UTF-8 converts to an empty string.
+        let s = std::str::from_utf8(slice).unwrap_or("");
+        write_json_str(out, s);
+        Ok(())
     }
 
-    fn obj(&mut self, size: usize) -> String {
-        let mut out = "{".to_string();
+    fn obj(&mut self, size: usize, out: &mut Writer) -> Result<(), MsgPackError> {
+        out.u8(b'{');
         for i in 0..size {
             if i > 0 {
-                out.push(',');
+                out.u8(b',');
             }
-            out.push_str(&self.val()); // key (as JSON string)
-            out.push(':');
-            out.push_str(&self.val());
+            self.val(out)?; // key (as JSON string)
+            out.u8(b':');
+            self.val(out)?;
         }
-        out.push('}');
-        out
+        out.u8(b'}');
+        Ok(())
     }
 
-    fn arr(&mut self, size: usize) -> String {
-        let mut out = "[".to_string();
+    fn arr(&mut self, size: usize, out: &mut Writer) -> Result<(), MsgPackError> {
+        out.u8(b'[');
         for i in 0..size {
             if i > 0 {
-                out.push(',');
+                out.u8(b',');
             }
-            out.push_str(&self.val());
+            self.val(out)?;
         }
-        out.push(']');
-        out
+        out.u8(b']');
+        Ok(())
     }
 
-    fn bin(&mut self, size: usize) -> String {
-        let end = self.x + size;
-        let buf = &self.data[self.x..end];
-        let b64 = json_joy_base64::to_base64(buf);
-        self.x = end;
-        format!("\"{}{}\"", BIN_URI_START, b64)
+    fn bin(&mut self, size: usize, out: &mut Writer) -> Result<(), MsgPackError> {
+        let buf = self.take(size)?;
+        write_data_uri(out, buf);
+        Ok(())
     }
 
-    fn ext_val(&mut self, size: usize) -> String {
-        let _ext_type = self.u8();
-        let end = self.x + size;
-        let buf = &self.data[self.x..end];
-        let b64 = json_joy_base64::to_base64(buf);
-        self.x = end;
-        // Extensions are also encoded as data URIs (simplified: use octet-stream)
-        format!("\"{}{}\"", BIN_URI_START, b64)
+    fn ext_val(&mut self, size: usize, out: &mut Writer) -> Result<(), MsgPackError> {
+        let offset = self.x;
+        let tag = self.u8()? as i8;
+        let data = self.take(size)?;
+        if tag == 0 && data == [0] && self.options.undefined == MsgPackUndefinedPolicy::FixExt {
+            out.ascii("null");
+            return Ok(());
+        }
+        if let Some(value) = self.options.ext_registry.decode(tag, data) {
+            let value = value.ok_or(MsgPackError::InvalidExt(offset))?;
+            out.buf(&JsonEncoder::new().encode(&value));
+            return Ok(());
+        }
+        match self.options.unknown_ext {
+            // Extensions are also encoded as data URIs (simplified: use octet-stream)
+            MsgPackExtPolicy::Unwrap => write_data_uri(out, data),
+            MsgPackExtPolicy::Wrap => {
+                out.ascii("{\"tag\":");
+                write_display(out, tag);
+                out.ascii(",\"value\":");
+                write_data_uri(out, data);
+                out.u8(b'}');
+            }
+            MsgPackExtPolicy::Error => return Err(MsgPackError::UnknownExt { tag, offset }),
+        }
+        Ok(())
+    }
+}
+
+/// Writes `s` as a JSON string, escaping like `serde_json`.
+fn write_json_str(out: &mut Writer, s: &str) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    out.u8(b'"');
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let escape: &[u8] = match b {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x00..=0x1f => &[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[(b >> 4) as usize],
+                HEX[(b & 0xf) as usize],
+            ],
+            _ => continue,
+        };
+        out.buf(&bytes[start..i]);
+        out.buf(escape);
+        start = i + 1;
     }
+    out.buf(&bytes[start..]);
+    out.u8(b'"');
 }
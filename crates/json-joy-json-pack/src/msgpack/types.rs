@@ -3,6 +3,7 @@
 //! Upstream reference: `json-pack/src/msgpack/types.ts`
 
 use crate::PackValue;
+use alloc::{string::String, vec::Vec};
 
 /// Binary MessagePack payload alias.
 pub type MsgPack = Vec<u8>;
@@ -0,0 +1,234 @@
+//! Ergonomic [`PackValue`] construction: the [`pack!`](crate::pack) macro and
+//! the [`PackObjectBuilder`] / [`PackArrayBuilder`] fluent builders.
+//!
+//! Not an upstream port: upstream builds values with plain JS literals.
+
+use crate::PackValue;
+use alloc::{string::String, vec::Vec};
+
+/// Builds a [`PackValue`] from a JSON-like literal.
+///
+/// `null` and `undefined` are keywords; `[...]` and `{...}` nest; any other
+/// value is an expression converted with `PackValue::from`, so byte strings
+/// become [`PackValue::Bytes`], `i128`/`u128` become big integers, and
+/// variables can be interpolated. Object keys are string literals or any
+/// single token tree convertible with `String::from`, e.g. a variable or a
+/// parenthesised expression.
+///
+/// ```
+/// use json_joy_json_pack::{pack, PackValue};
+///
+/// let id = 7;
+/// let value = pack!({ "id": id, "tags": ["a", null], "blob": b"\x00\x01" });
+/// assert_eq!(
+///     value,
+///     PackValue::Object(vec![
+///         ("id".into(), PackValue::Integer(7)),
+///         (
+///             "tags".into(),
+///             PackValue::Array(vec![PackValue::Str("a".into()), PackValue::Null]),
+///         ),
+///         ("blob".into(), PackValue::Bytes(vec![0, 1])),
+///     ])
+/// );
+/// ```
+#[macro_export]
+macro_rules! pack {
+    ($($value:tt)+) => {
+        $crate::pack_internal!($($value)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! pack_internal {
+    // Array elements, munched one at a time into `[$($elems,)*]`.
+    (@array [$($elems:expr,)*]) => {
+        $crate::__private::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::PackValue::Null,] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] undefined $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::PackValue::Undefined,] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::pack_internal!([$($array)*]),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::pack_internal!({$($object)*}),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::PackValue::from($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::pack_internal!(@array [$($elems,)* $crate::PackValue::from($last),])
+    };
+
+    // Object entries, munched one at a time into `[$($entries,)*]`.
+    (@object [$($entries:expr,)*]) => {
+        $crate::__private::vec![$($entries,)*]
+    };
+    (@object [$($entries:expr,)*] $key:tt : null $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::PackValue::Null),] $($($rest)*)?)
+    };
+    (@object [$($entries:expr,)*] $key:tt : undefined $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::PackValue::Undefined),] $($($rest)*)?)
+    };
+    (@object [$($entries:expr,)*] $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::pack_internal!([$($array)*])),] $($($rest)*)?)
+    };
+    (@object [$($entries:expr,)*] $key:tt : {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::pack_internal!({$($object)*})),] $($($rest)*)?)
+    };
+    (@object [$($entries:expr,)*] $key:tt : $value:expr, $($rest:tt)*) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::PackValue::from($value)),] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : $value:expr) => {
+        $crate::pack_internal!(@object [$($entries,)* ($crate::__private::String::from($key), $crate::PackValue::from($value)),])
+    };
+
+    (null) => {
+        $crate::PackValue::Null
+    };
+    (undefined) => {
+        $crate::PackValue::Undefined
+    };
+    ([]) => {
+        $crate::PackValue::Array($crate::__private::vec![])
+    };
+    ([$($array:tt)+]) => {
+        $crate::PackValue::Array($crate::pack_internal!(@array [] $($array)+))
+    };
+    ({}) => {
+        $crate::PackValue::Object($crate::__private::vec![])
+    };
+    ({$($object:tt)+}) => {
+        $crate::PackValue::Object($crate::pack_internal!(@object [] $($object)+))
+    };
+    ($other:expr) => {
+        $crate::PackValue::from($other)
+    };
+}
+
+/// Fluent builder for [`PackValue::Object`]. Entries keep insertion order;
+/// repeated keys are kept as-is, like decoded objects.
+///
+/// ```
+/// use json_joy_json_pack::{pack, PackArrayBuilder, PackObjectBuilder};
+///
+/// let value = PackObjectBuilder::new()
+///     .field("id", 1)
+///     .field("tags", PackArrayBuilder::new().item("a").item("b"))
+///     .field_opt("note", None::<&str>)
+///     .build();
+/// assert_eq!(value, pack!({ "id": 1, "tags": ["a", "b"] }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PackObjectBuilder {
+    entries: Vec<(String, PackValue)>,
+}
+
+impl PackObjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends an entry.
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<PackValue>) -> Self {
+        self.entries.push((key.into(), value.into()));
+        self
+    }
+
+    /// Appends an entry only when `value` is `Some`.
+    pub fn field_opt<V: Into<PackValue>>(self, key: impl Into<String>, value: Option<V>) -> Self {
+        match value {
+            Some(value) => self.field(key, value),
+            None => self,
+        }
+    }
+
+    /// Appends an entry in place, for use in loops.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<PackValue>) -> &mut Self {
+        self.entries.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn build(self) -> PackValue {
+        PackValue::Object(self.entries)
+    }
+}
+
+impl From<PackObjectBuilder> for PackValue {
+    fn from(builder: PackObjectBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Fluent builder for [`PackValue::Array`].
+#[derive(Debug, Clone, Default)]
+pub struct PackArrayBuilder {
+    items: Vec<PackValue>,
+}
+
+impl PackArrayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends an element.
+    pub fn item(mut self, value: impl Into<PackValue>) -> Self {
+        self.items.push(value.into());
+        self
+    }
+
+    /// Appends every element of `values`.
+    pub fn items<V: Into<PackValue>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.items.extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appends an element in place, for use in loops.
+    pub fn push(&mut self, value: impl Into<PackValue>) -> &mut Self {
+        self.items.push(value.into());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn build(self) -> PackValue {
+        PackValue::Array(self.items)
+    }
+}
+
+impl From<PackArrayBuilder> for PackValue {
+    fn from(builder: PackArrayBuilder) -> Self {
+        builder.build()
+    }
+}
@@ -0,0 +1,183 @@
+//! [`PackObject`] — an insertion-ordered object with keyed access.
+//!
+//! Not an upstream port: upstream objects are JS objects with hashed keys.
+//! [`PackValue::Object`] stays a plain vector of pairs, which is what the
+//! codecs read and write; convert to a [`PackObject`] for repeated lookups.
+//! With the `indexed` feature a hash index makes lookups O(1); without it
+//! they scan the entries.
+
+use crate::PackValue;
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "indexed")]
+use std::collections::HashMap;
+
+/// Insertion-ordered object with `get` / `insert` / `remove` by key.
+///
+/// Keys are unique in the API: when built from entries with repeated keys
+/// the last one wins for lookups, [`PackObject::insert`] overwrites it in
+/// place, and [`PackObject::remove`] drops every occurrence.
+///
+/// ```
+/// use json_joy_json_pack::{pack, PackObject, PackValue};
+///
+/// let mut object = PackObject::try_from(pack!({ "a": 1, "b": 2 })).unwrap();
+/// assert_eq!(object.get("b"), Some(&PackValue::Integer(2)));
+/// object.insert("a", 3);
+/// object.remove("b");
+/// assert_eq!(PackValue::from(object), pack!({ "a": 3 }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PackObject {
+    entries: Vec<(String, PackValue)>,
+    #[cfg(feature = "indexed")]
+    index: HashMap<String, usize>,
+}
+
+impl PackObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            #[cfg(feature = "indexed")]
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn from_entries(entries: Vec<(String, PackValue)>) -> Self {
+        let mut object = Self {
+            entries,
+            #[cfg(feature = "indexed")]
+            index: HashMap::new(),
+        };
+        object.reindex();
+        object
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&PackValue> {
+        self.position(key).map(|i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut PackValue> {
+        self.position(key).map(|i| &mut self.entries[i].1)
+    }
+
+    /// Sets `key` to `value`, returning the previous value. An existing key
+    /// keeps its position; a new key is appended.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<PackValue>,
+    ) -> Option<PackValue> {
+        let key = key.into();
+        let value = value.into();
+        if let Some(i) = self.position(&key) {
+            return Some(core::mem::replace(&mut self.entries[i].1, value));
+        }
+        #[cfg(feature = "indexed")]
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        None
+    }
+
+    /// Removes `key`, returning its value. Later entries shift down to keep
+    /// their order, so this is O(n) either way.
+    pub fn remove(&mut self, key: &str) -> Option<PackValue> {
+        let i = self.position(key)?;
+        let value = core::mem::replace(&mut self.entries[i].1, PackValue::Null);
+        self.entries.retain(|(k, _)| k != key);
+        self.reindex();
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PackValue)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(k, _)| k.as_str())
+    }
+
+    pub fn entries(&self) -> &[(String, PackValue)] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<(String, PackValue)> {
+        self.entries
+    }
+
+    #[cfg(feature = "indexed")]
+    fn position(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    #[cfg(not(feature = "indexed"))]
+    fn position(&self, key: &str) -> Option<usize> {
+        self.entries.iter().rposition(|(k, _)| k == key)
+    }
+
+    #[cfg(feature = "indexed")]
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (i, (key, _)) in self.entries.iter().enumerate() {
+            self.index.insert(key.clone(), i);
+        }
+    }
+
+    #[cfg(not(feature = "indexed"))]
+    fn reindex(&mut self) {}
+}
+
+impl PartialEq for PackObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl From<Vec<(String, PackValue)>> for PackObject {
+    fn from(entries: Vec<(String, PackValue)>) -> Self {
+        Self::from_entries(entries)
+    }
+}
+
+impl<K: Into<String>, V: Into<PackValue>> FromIterator<(K, V)> for PackObject {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_entries(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<PackValue> for PackObject {
+    type Error = PackValue;
+
+    /// Converts a [`PackValue::Object`]; any other value is handed back.
+    fn try_from(value: PackValue) -> Result<Self, PackValue> {
+        match value {
+            PackValue::Object(entries) => Ok(Self::from_entries(entries)),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<PackObject> for PackValue {
+    fn from(object: PackObject) -> Self {
+        PackValue::Object(object.entries)
+    }
+}
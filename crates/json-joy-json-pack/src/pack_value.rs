@@ -2,7 +2,13 @@
 //!
 //! Mirrors the TypeScript `PackValue` union from `types.ts`.
 
-use crate::{JsonPackExtension, JsonPackValue};
+use crate::util::format_f64_js;
+use crate::{JsonPackBigInt, JsonPackExtension, JsonPackValue};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 /// Universal value type that spans all JSON-pack binary formats.
 ///
@@ -29,6 +35,8 @@ pub enum PackValue {
     Float(f64),
     /// Big integer (two's complement)
     BigInt(i128),
+    /// Arbitrary-precision integer that does not fit in [`PackValue::BigInt`]
+    BigNum(JsonPackBigInt),
     /// Binary data
     Bytes(Vec<u8>),
     /// String
@@ -37,6 +45,9 @@ pub enum PackValue {
     Array(Vec<PackValue>),
     /// Object (ordered key-value pairs)
     Object(Vec<(String, PackValue)>),
+    /// Map with keys of any type (ordered key-value pairs), as CBOR and
+    /// MessagePack allow
+    Map(Vec<(PackValue, PackValue)>),
     /// Extension / CBOR tag
     Extension(Box<JsonPackExtension>),
     /// Pre-encoded blob (written as-is to the output)
@@ -49,6 +60,7 @@ impl PackValue {
     /// Arrays and objects return `PackValue::Null` — callers that need to handle
     /// complex types should convert them separately (e.g. via `build_json` in
     /// CRDT builder code).
+    #[cfg(feature = "std")]
     pub fn from_json_scalar(v: &serde_json::Value) -> Self {
         match v {
             serde_json::Value::Null => PackValue::Null,
@@ -66,9 +78,189 @@ impl PackValue {
             serde_json::Value::Array(_) | serde_json::Value::Object(_) => PackValue::Null,
         }
     }
+
+    /// Looks up `key` in an object by scanning its entries; the last of
+    /// repeated keys wins. `None` for non-objects. Convert to a
+    /// [`PackObject`](crate::PackObject) for repeated lookups.
+    pub fn get(&self, key: &str) -> Option<&PackValue> {
+        match self {
+            PackValue::Object(entries) => {
+                entries.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    /// The text key this value stands for where only text keys are allowed,
+    /// e.g. when a [`PackValue::Map`] is written as a JSON object: strings
+    /// as-is, numbers, booleans, `null` and `undefined` as JavaScript prints
+    /// them, and anything else as an empty string. `try_encode` reports keys
+    /// of the last kind as [`LossKind::NonTextKey`](crate::checked::LossKind::NonTextKey).
+    pub fn to_key_string(&self) -> String {
+        match self {
+            PackValue::Str(s) => s.clone(),
+            PackValue::Integer(i) => i.to_string(),
+            PackValue::UInteger(u) => u.to_string(),
+            PackValue::Float(f) => format_f64_js(*f),
+            PackValue::BigInt(i) => i.to_string(),
+            PackValue::BigNum(n) => n.to_string(),
+            PackValue::Bool(b) => b.to_string(),
+            PackValue::Null => "null".to_string(),
+            PackValue::Undefined => "undefined".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Whether [`PackValue::to_key_string`] gives this value a text of its
+    /// own rather than an empty string.
+    pub(crate) fn is_text_key(&self) -> bool {
+        matches!(
+            self,
+            PackValue::Str(_)
+                | PackValue::Integer(_)
+                | PackValue::UInteger(_)
+                | PackValue::Float(_)
+                | PackValue::BigInt(_)
+                | PackValue::BigNum(_)
+                | PackValue::Bool(_)
+                | PackValue::Null
+                | PackValue::Undefined
+        )
+    }
+
+    /// Mutable variant of [`PackValue::get`].
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut PackValue> {
+        match self {
+            PackValue::Object(entries) => entries
+                .iter_mut()
+                .rev()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Follows `path` through nested containers: object keys (last of
+    /// repeated keys wins), array indices in decimal without leading zeros,
+    /// and map keys compared by [`PackValue::to_key_string`].
+    pub fn get_in(&self, path: &[&str]) -> Option<&PackValue> {
+        path.iter()
+            .try_fold(self, |value, segment| value.child(segment))
+    }
+
+    /// Mutable variant of [`PackValue::get_in`].
+    pub fn get_in_mut(&mut self, path: &[&str]) -> Option<&mut PackValue> {
+        path.iter()
+            .try_fold(self, |value, segment| value.child_mut(segment))
+    }
+
+    /// Sets the value at `path`, returning the previous one. The parent must
+    /// exist: a missing key is appended to an object or map, and an array
+    /// index equal to the length, or `-`, appends to an array. Otherwise
+    /// `value` is handed back as the error. An empty path replaces `self`.
+    pub fn set_in(
+        &mut self,
+        path: &[&str],
+        value: impl Into<PackValue>,
+    ) -> Result<Option<PackValue>, PackValue> {
+        let value = value.into();
+        let Some((last, parent)) = path.split_last() else {
+            return Ok(Some(core::mem::replace(self, value)));
+        };
+        let Some(parent) = self.get_in_mut(parent) else {
+            return Err(value);
+        };
+        if let Some(slot) = parent.child_mut(last) {
+            return Ok(Some(core::mem::replace(slot, value)));
+        }
+        match parent {
+            PackValue::Object(entries) => entries.push((last.to_string(), value)),
+            PackValue::Array(items) if *last == "-" || array_index(last) == Some(items.len()) => {
+                items.push(value)
+            }
+            PackValue::Map(entries) => entries.push((PackValue::Str(last.to_string()), value)),
+            _ => return Err(value),
+        }
+        Ok(None)
+    }
+
+    /// Removes the value at `path` and returns it. Repeated object or map
+    /// keys are all removed; later array items shift down. An empty path
+    /// leaves `Null` in place of `self`.
+    pub fn take_in(&mut self, path: &[&str]) -> Option<PackValue> {
+        let Some((last, parent)) = path.split_last() else {
+            return Some(core::mem::replace(self, PackValue::Null));
+        };
+        match self.get_in_mut(parent)? {
+            PackValue::Object(entries) => {
+                let i = entries.iter().rposition(|(k, _)| k == last)?;
+                let value = entries.remove(i).1;
+                entries.retain(|(k, _)| k != last);
+                Some(value)
+            }
+            PackValue::Array(items) => {
+                let i = array_index(last).filter(|&i| i < items.len())?;
+                Some(items.remove(i))
+            }
+            PackValue::Map(entries) => {
+                let i = entries
+                    .iter()
+                    .rposition(|(k, _)| k.to_key_string() == *last)?;
+                let value = entries.remove(i).1;
+                entries.retain(|(k, _)| k.to_key_string() != *last);
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    fn child(&self, segment: &str) -> Option<&PackValue> {
+        match self {
+            PackValue::Object(_) => self.get(segment),
+            PackValue::Array(items) => items.get(array_index(segment)?),
+            PackValue::Map(entries) => entries
+                .iter()
+                .rev()
+                .find(|(k, _)| k.to_key_string() == segment)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, segment: &str) -> Option<&mut PackValue> {
+        match self {
+            PackValue::Object(_) => self.get_mut(segment),
+            PackValue::Array(items) => items.get_mut(array_index(segment)?),
+            PackValue::Map(entries) => entries
+                .iter_mut()
+                .rev()
+                .find(|(k, _)| k.to_key_string() == segment)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Parses an array index path segment: decimal digits, no leading zeros.
+fn array_index(segment: &str) -> Option<usize> {
+    let canonical = !segment.is_empty()
+        && segment.bytes().all(|b| b.is_ascii_digit())
+        && (segment == "0" || !segment.starts_with('0'));
+    canonical.then(|| segment.parse().ok()).flatten()
 }
 
+/// The entries of a [`PackValue::Map`] with their keys converted by
+/// [`PackValue::to_key_string`], for formats whose keys are text only.
+pub(crate) fn text_keyed(entries: &[(PackValue, PackValue)]) -> Vec<(String, PackValue)> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_key_string(), value.clone()))
+        .collect()
+}
+
+#[cfg(feature = "std")]
 impl From<serde_json::Value> for PackValue {
+    #[cfg(feature = "std")]
     fn from(v: serde_json::Value) -> Self {
         match v {
             serde_json::Value::Null => PackValue::Null,
@@ -95,7 +287,9 @@ impl From<serde_json::Value> for PackValue {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<&serde_json::Value> for PackValue {
+    #[cfg(feature = "std")]
     fn from(v: &serde_json::Value) -> Self {
         match v {
             serde_json::Value::Null => PackValue::Null,
@@ -122,32 +316,173 @@ impl From<&serde_json::Value> for PackValue {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<PackValue> for serde_json::Value {
+    /// Converts under the default [`JsonPolicy`](crate::JsonPolicy), which
+    /// has no failure cases.
     fn from(v: PackValue) -> Self {
-        match v {
-            PackValue::Null => serde_json::Value::Null,
-            PackValue::Undefined => serde_json::Value::Null,
-            PackValue::Bool(b) => serde_json::Value::Bool(b),
-            PackValue::Integer(i) => serde_json::json!(i),
-            PackValue::UInteger(u) => serde_json::json!(u),
-            PackValue::Float(f) => serde_json::json!(f),
-            PackValue::BigInt(i) => serde_json::json!(i),
-            PackValue::Bytes(b) => {
-                use json_joy_base64::to_base64;
-                let b64 = to_base64(&b);
-                serde_json::Value::String(format!("data:application/octet-stream;base64,{}", b64))
+        v.to_json_with(&crate::JsonPolicy::default())
+            .expect("default JSON policy does not fail")
+    }
+}
+
+// Conversions used by the `pack!` macro and the builders. Integers that fit
+// in `i64` become `Integer` and other `u64` values `UInteger`, matching what
+// the decoders produce; wider ones become `BigInt`, or `BigNum` past `i128`.
+// `Vec<u8>` and byte slices become `Bytes` rather than arrays.
+
+impl From<()> for PackValue {
+    fn from(_: ()) -> Self {
+        PackValue::Null
+    }
+}
+
+impl From<bool> for PackValue {
+    fn from(v: bool) -> Self {
+        PackValue::Bool(v)
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for PackValue {
+            fn from(v: $t) -> Self {
+                PackValue::Integer(v as i64)
             }
-            PackValue::Str(s) => serde_json::Value::String(s),
-            PackValue::Array(arr) => {
-                serde_json::Value::Array(arr.into_iter().map(serde_json::Value::from).collect())
+        }
+    )*};
+}
+
+from_int!(i8, i16, i32, i64, isize, u8, u16, u32);
+
+macro_rules! from_uint {
+    ($($t:ty),*) => {$(
+        impl From<$t> for PackValue {
+            fn from(v: $t) -> Self {
+                match i64::try_from(v) {
+                    Ok(i) => PackValue::Integer(i),
+                    Err(_) => PackValue::UInteger(v as u64),
+                }
             }
-            PackValue::Object(obj) => serde_json::Value::Object(
-                obj.into_iter()
-                    .map(|(k, v)| (k, serde_json::Value::from(v)))
-                    .collect(),
-            ),
-            PackValue::Extension(ext) => serde_json::Value::from(*ext.val),
-            PackValue::Blob(_) => serde_json::Value::Null,
         }
+    )*};
+}
+
+from_uint!(u64, usize);
+
+impl From<i128> for PackValue {
+    fn from(v: i128) -> Self {
+        if let Ok(i) = i64::try_from(v) {
+            PackValue::Integer(i)
+        } else if let Ok(u) = u64::try_from(v) {
+            PackValue::UInteger(u)
+        } else {
+            PackValue::BigInt(v)
+        }
+    }
+}
+
+impl From<u128> for PackValue {
+    fn from(v: u128) -> Self {
+        match i128::try_from(v) {
+            Ok(i) => i.into(),
+            Err(_) => PackValue::BigNum(JsonPackBigInt::from_u128(v)),
+        }
+    }
+}
+
+impl From<JsonPackBigInt> for PackValue {
+    fn from(v: JsonPackBigInt) -> Self {
+        PackValue::BigNum(v)
+    }
+}
+
+impl From<f32> for PackValue {
+    fn from(v: f32) -> Self {
+        PackValue::Float(v as f64)
+    }
+}
+
+impl From<f64> for PackValue {
+    fn from(v: f64) -> Self {
+        PackValue::Float(v)
+    }
+}
+
+impl From<&str> for PackValue {
+    fn from(v: &str) -> Self {
+        PackValue::Str(v.into())
+    }
+}
+
+impl From<String> for PackValue {
+    fn from(v: String) -> Self {
+        PackValue::Str(v)
+    }
+}
+
+impl From<&String> for PackValue {
+    fn from(v: &String) -> Self {
+        PackValue::Str(v.clone())
+    }
+}
+
+impl From<char> for PackValue {
+    fn from(v: char) -> Self {
+        PackValue::Str(v.into())
+    }
+}
+
+impl From<&[u8]> for PackValue {
+    fn from(v: &[u8]) -> Self {
+        PackValue::Bytes(v.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for PackValue {
+    fn from(v: &[u8; N]) -> Self {
+        PackValue::Bytes(v.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for PackValue {
+    fn from(v: [u8; N]) -> Self {
+        PackValue::Bytes(v.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for PackValue {
+    fn from(v: Vec<u8>) -> Self {
+        PackValue::Bytes(v)
+    }
+}
+
+impl From<Vec<PackValue>> for PackValue {
+    fn from(v: Vec<PackValue>) -> Self {
+        PackValue::Array(v)
+    }
+}
+
+impl From<Vec<(String, PackValue)>> for PackValue {
+    fn from(v: Vec<(String, PackValue)>) -> Self {
+        PackValue::Object(v)
+    }
+}
+
+impl<T: Into<PackValue>> From<Option<T>> for PackValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(PackValue::Null, Into::into)
+    }
+}
+
+impl From<JsonPackExtension> for PackValue {
+    fn from(v: JsonPackExtension) -> Self {
+        PackValue::Extension(Box::new(v))
+    }
+}
+
+impl From<JsonPackValue> for PackValue {
+    fn from(v: JsonPackValue) -> Self {
+        PackValue::Blob(v)
     }
 }
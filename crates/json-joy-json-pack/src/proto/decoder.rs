@@ -0,0 +1,131 @@
+//! `ProtoDecoder` — reads protobuf wire-format fields from a byte slice.
+//!
+//! Iterating a decoder yields each top-level field with its raw value;
+//! length-delimited payloads borrow from the input, so a nested message is
+//! decoded by iterating a new `ProtoDecoder` over its bytes.
+
+use super::error::ProtoError;
+use super::types::{ProtoField, ProtoValue, WireType};
+use crate::util::varint::{read_varint, zigzag_decode32, zigzag_decode64, VarintError};
+
+pub struct ProtoDecoder<'a> {
+    data: &'a [u8],
+    pub x: usize,
+}
+
+impl<'a> ProtoDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, x: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x >= self.data.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ProtoError> {
+        let end = self
+            .x
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(ProtoError::UnexpectedEof(self.x))?;
+        let bytes = &self.data[self.x..end];
+        self.x = end;
+        Ok(bytes)
+    }
+
+    // ---------------------------------------------------------------- values
+
+    pub fn read_varint(&mut self) -> Result<u64, ProtoError> {
+        match read_varint(self.data, self.x, 10) {
+            Ok((value, len)) => {
+                self.x += len;
+                Ok(value)
+            }
+            Err(VarintError::UnexpectedEof) => Err(ProtoError::UnexpectedEof(self.x)),
+            Err(VarintError::TooLong) => Err(ProtoError::VarintTooLong(self.x)),
+        }
+    }
+
+    /// `int32`/`int64`: negatives are 10-byte two's-complement varints.
+    pub fn read_int64(&mut self) -> Result<i64, ProtoError> {
+        Ok(self.read_varint()? as i64)
+    }
+
+    pub fn read_sint32(&mut self) -> Result<i32, ProtoError> {
+        Ok(zigzag_decode32(self.read_varint()? as u32))
+    }
+
+    pub fn read_sint64(&mut self) -> Result<i64, ProtoError> {
+        Ok(zigzag_decode64(self.read_varint()?))
+    }
+
+    pub fn read_fixed32(&mut self) -> Result<u32, ProtoError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_fixed64(&mut self) -> Result<u64, ProtoError> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Length-prefixed payload, borrowed from the input.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], ProtoError> {
+        let start = self.x;
+        let len = self.read_varint()?;
+        let len = usize::try_from(len).map_err(|_| ProtoError::UnexpectedEof(start))?;
+        self.take(len)
+    }
+
+    pub fn read_str(&mut self) -> Result<&'a str, ProtoError> {
+        let bytes = self.read_bytes()?;
+        let start = self.x - bytes.len();
+        crate::utf8::from_utf8(bytes).map_err(|_| ProtoError::InvalidUtf8(start))
+    }
+
+    // ---------------------------------------------------------------- fields
+
+    pub fn read_tag(&mut self) -> Result<(u32, WireType), ProtoError> {
+        let start = self.x;
+        let tag = self.read_varint()?;
+        let wire_type = WireType::from_u8((tag & 0x7) as u8)
+            .ok_or(ProtoError::InvalidWireType((tag & 0x7) as u8, start))?;
+        let number = tag >> 3;
+        if number == 0 || number > u32::MAX as u64 {
+            return Err(ProtoError::InvalidFieldNumber(start));
+        }
+        Ok((number as u32, wire_type))
+    }
+
+    /// Reads the next field, or `None` at the end of input.
+    pub fn read_field(&mut self) -> Result<Option<ProtoField<'a>>, ProtoError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let (number, wire_type) = self.read_tag()?;
+        let value = match wire_type {
+            WireType::Varint => ProtoValue::Varint(self.read_varint()?),
+            WireType::Fixed64 => ProtoValue::Fixed64(self.read_fixed64()?),
+            WireType::LengthDelimited => ProtoValue::LengthDelimited(self.read_bytes()?),
+            WireType::Fixed32 => ProtoValue::Fixed32(self.read_fixed32()?),
+            WireType::StartGroup | WireType::EndGroup => ProtoValue::Group(wire_type),
+        };
+        Ok(Some(ProtoField { number, value }))
+    }
+}
+
+impl<'a> Iterator for ProtoDecoder<'a> {
+    type Item = Result<ProtoField<'a>, ProtoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_field() {
+            Ok(field) => field.map(Ok),
+            Err(err) => {
+                // Stop iterating after the first error.
+                self.x = self.data.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
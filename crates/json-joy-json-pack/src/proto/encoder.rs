@@ -0,0 +1,123 @@
+//! `ProtoEncoder` — writes protobuf wire-format fields.
+//!
+//! The `write_*_field` methods write a tag followed by the value; the bare
+//! `write_*` methods write only the value, e.g. for packed repeated fields.
+
+use json_joy_buffers::Writer;
+
+use super::types::WireType;
+use crate::util::varint::{write_varint, zigzag_encode32, zigzag_encode64};
+
+pub struct ProtoEncoder {
+    pub writer: Writer,
+}
+
+impl Default for ProtoEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProtoEncoder {
+    pub fn new() -> Self {
+        Self {
+            writer: Writer::new(),
+        }
+    }
+
+    /// Returns the bytes written since the last flush.
+    pub fn flush(&mut self) -> Vec<u8> {
+        self.writer.flush()
+    }
+
+    // ---------------------------------------------------------------- values
+
+    pub fn write_tag(&mut self, field: u32, wire_type: WireType) {
+        self.write_varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    pub fn write_varint(&mut self, n: u64) {
+        write_varint(&mut self.writer, n);
+    }
+
+    /// `int32`/`int64` encode negatives as 10-byte two's-complement varints.
+    pub fn write_int64(&mut self, n: i64) {
+        self.write_varint(n as u64);
+    }
+
+    pub fn write_sint32(&mut self, n: i32) {
+        self.write_varint(zigzag_encode32(n) as u64);
+    }
+
+    pub fn write_sint64(&mut self, n: i64) {
+        self.write_varint(zigzag_encode64(n));
+    }
+
+    pub fn write_fixed32(&mut self, n: u32) {
+        self.writer.u32_le(n);
+    }
+
+    pub fn write_fixed64(&mut self, n: u64) {
+        self.writer.u64_le(n);
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) {
+        self.write_varint(data.len() as u64);
+        self.writer.buf(data);
+    }
+
+    // ---------------------------------------------------------------- fields
+
+    pub fn write_varint_field(&mut self, field: u32, n: u64) {
+        self.write_tag(field, WireType::Varint);
+        self.write_varint(n);
+    }
+
+    pub fn write_int64_field(&mut self, field: u32, n: i64) {
+        self.write_tag(field, WireType::Varint);
+        self.write_int64(n);
+    }
+
+    pub fn write_sint32_field(&mut self, field: u32, n: i32) {
+        self.write_tag(field, WireType::Varint);
+        self.write_sint32(n);
+    }
+
+    pub fn write_sint64_field(&mut self, field: u32, n: i64) {
+        self.write_tag(field, WireType::Varint);
+        self.write_sint64(n);
+    }
+
+    pub fn write_bool_field(&mut self, field: u32, b: bool) {
+        self.write_varint_field(field, b as u64);
+    }
+
+    pub fn write_fixed32_field(&mut self, field: u32, n: u32) {
+        self.write_tag(field, WireType::Fixed32);
+        self.write_fixed32(n);
+    }
+
+    pub fn write_fixed64_field(&mut self, field: u32, n: u64) {
+        self.write_tag(field, WireType::Fixed64);
+        self.write_fixed64(n);
+    }
+
+    pub fn write_float_field(&mut self, field: u32, f: f32) {
+        self.write_fixed32_field(field, f.to_bits());
+    }
+
+    pub fn write_double_field(&mut self, field: u32, f: f64) {
+        self.write_fixed64_field(field, f.to_bits());
+    }
+
+    /// Length-delimited field: `bytes`, an encoded nested message, or a
+    /// packed repeated field.
+    pub fn write_bytes_field(&mut self, field: u32, data: &[u8]) {
+        self.write_tag(field, WireType::LengthDelimited);
+        self.write_bytes(data);
+    }
+
+    pub fn write_str_field(&mut self, field: u32, s: &str) {
+        self.write_bytes_field(field, s.as_bytes());
+    }
+}
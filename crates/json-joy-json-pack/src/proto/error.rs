@@ -0,0 +1,30 @@
+//! Protobuf wire-format error type.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("varint longer than 10 bytes at offset {0}")]
+    VarintTooLong(usize),
+    #[error("invalid wire type {0} at offset {1}")]
+    InvalidWireType(u8, usize),
+    #[error("invalid field number 0 at offset {0}")]
+    InvalidFieldNumber(usize),
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
+}
+
+impl ProtoError {
+    /// Byte offset in the input at which the error was detected.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedEof(x)
+            | Self::VarintTooLong(x)
+            | Self::InvalidWireType(_, x)
+            | Self::InvalidFieldNumber(x)
+            | Self::InvalidUtf8(x) => Some(*x),
+        }
+    }
+}
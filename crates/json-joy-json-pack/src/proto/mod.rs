@@ -0,0 +1,17 @@
+//! Protocol Buffers wire-format primitives.
+//!
+//! Not an upstream port. Low-level reading and writing of tags, varints,
+//! zigzag (`sint32`/`sint64`), fixed-width and length-delimited fields, for
+//! hand-decoding protobuf envelopes without a schema compiler. Message
+//! semantics (defaults, repeated/packed fields, unknown-field policy) are
+//! left to the caller.
+
+mod decoder;
+mod encoder;
+mod error;
+mod types;
+
+pub use decoder::ProtoDecoder;
+pub use encoder::ProtoEncoder;
+pub use error::ProtoError;
+pub use types::{ProtoField, ProtoValue, WireType};
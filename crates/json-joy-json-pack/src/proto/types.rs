@@ -0,0 +1,48 @@
+//! Protobuf wire types and raw field values.
+
+/// The low three bits of a field tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireType {
+    Varint = 0,
+    Fixed64 = 1,
+    LengthDelimited = 2,
+    StartGroup = 3,
+    EndGroup = 4,
+    Fixed32 = 5,
+}
+
+impl WireType {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Varint),
+            1 => Some(Self::Fixed64),
+            2 => Some(Self::LengthDelimited),
+            3 => Some(Self::StartGroup),
+            4 => Some(Self::EndGroup),
+            5 => Some(Self::Fixed32),
+            _ => None,
+        }
+    }
+}
+
+/// Undecoded payload of one field, borrowed from the input.
+///
+/// Interpreting a varint as `int32`, `sint64`, `bool`, … or a
+/// length-delimited payload as a string, nested message or packed array is
+/// up to the caller, who knows the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    LengthDelimited(&'a [u8]),
+    Fixed32(u32),
+    /// Start or end marker of a deprecated group; carries no payload.
+    Group(WireType),
+}
+
+/// A field number with its raw value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtoField<'a> {
+    pub number: u32,
+    pub value: ProtoValue<'a>,
+}
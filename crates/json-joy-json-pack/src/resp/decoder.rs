@@ -3,21 +3,46 @@
 //! Upstream reference: `json-pack/src/resp/RespDecoder.ts`
 
 use super::constants::{Resp, RESP_EXTENSION_ATTRIBUTES, RESP_EXTENSION_PUSH};
+use super::value::RespValue;
 use crate::{JsonPackExtension, PackValue};
 
 /// Decode error for RESP3 parsing.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum RespDecodeError {
-    #[error("unexpected end of input")]
-    EndOfInput,
-    #[error("unknown RESP type byte: 0x{0:02x}")]
-    UnknownType(u8),
+    #[error("unexpected end of input at offset {0}")]
+    EndOfInput(usize),
+    #[error("unknown RESP type byte 0x{0:02x} at offset {1}")]
+    UnknownType(u8, usize),
     #[error("invalid command frame")]
     InvalidCommand,
-    #[error("invalid UTF-8 in RESP payload")]
-    InvalidUtf8,
+    #[error("invalid UTF-8 in RESP payload at offset {0}")]
+    InvalidUtf8(usize),
+    #[error("invalid number at offset {0}")]
+    InvalidNumber(usize),
+    #[error("invalid verbatim string at offset {0}")]
+    InvalidVerbatim(usize),
+    #[error("invalid streamed string chunk at offset {0}")]
+    InvalidChunk(usize),
 }
 
+impl RespDecodeError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::EndOfInput(x)
+            | Self::UnknownType(_, x)
+            | Self::InvalidUtf8(x)
+            | Self::InvalidNumber(x)
+            | Self::InvalidVerbatim(x)
+            | Self::InvalidChunk(x) => Some(*x),
+            Self::InvalidCommand => None,
+        }
+    }
+}
+
+/// Bytes in the shortest RESP value, `_\r\n`.
+const MIN_ITEM: usize = 3;
+
 /// RESP3 protocol decoder.
 ///
 /// Decodes RESP3 wire format into [`PackValue`].
@@ -27,6 +52,13 @@ pub enum RespDecodeError {
 /// - Attributes (`|`) → `PackValue::Extension(tag=2, val=Object)`
 /// - Verbatim string (`=`) with `txt:` encoding → `PackValue::Str`
 /// - Verbatim string with other encoding → `PackValue::Bytes`
+///
+/// [`decode_typed`](Self::decode_typed) keeps the RESP3 types instead; see
+/// [`RespValue`].
+///
+/// Streamed strings (`$?` followed by `;<len>` chunks) are joined, and
+/// streamed aggregates (`*?`, `%?`, `~?`, ... ended by `.`) are read like
+/// their length-prefixed forms.
 pub struct RespDecoder {
     data: Vec<u8>,
     pos: usize,
@@ -67,6 +99,12 @@ impl RespDecoder {
         self.read_any()
     }
 
+    /// Decodes a RESP3 value from `data` without folding it into [`PackValue`].
+    pub fn decode_typed(&mut self, data: &[u8]) -> Result<RespValue, RespDecodeError> {
+        self.reset(data);
+        self.read_typed()
+    }
+
     /// Alias for [`Self::decode`] to match upstream naming.
     pub fn read(&mut self, data: &[u8]) -> Result<PackValue, RespDecodeError> {
         self.decode(data)
@@ -74,14 +112,20 @@ impl RespDecoder {
 
     // ---------------------------------------------------------------- helpers
 
-    #[allow(dead_code)]
     fn remaining(&self) -> usize {
         self.data.len() - self.pos
     }
 
+    /// How many items of an aggregate to reserve room for: the announced
+    /// count comes from the input, so it is capped by how many items of at
+    /// least `min_len` bytes the rest of the input can hold.
+    fn capacity(&self, count: Option<usize>, min_len: usize) -> usize {
+        count.unwrap_or(0).min(self.remaining() / min_len)
+    }
+
     fn u8(&mut self) -> Result<u8, RespDecodeError> {
         if self.pos >= self.data.len() {
-            return Err(RespDecodeError::EndOfInput);
+            return Err(RespDecodeError::EndOfInput(self.pos));
         }
         let b = self.data[self.pos];
         self.pos += 1;
@@ -90,22 +134,22 @@ impl RespDecoder {
 
     fn peek(&self) -> Result<u8, RespDecodeError> {
         if self.pos >= self.data.len() {
-            return Err(RespDecodeError::EndOfInput);
+            return Err(RespDecodeError::EndOfInput(self.pos));
         }
         Ok(self.data[self.pos])
     }
 
     fn skip(&mut self, n: usize) -> Result<(), RespDecodeError> {
-        if self.pos + n > self.data.len() {
-            return Err(RespDecodeError::EndOfInput);
+        if n > self.remaining() {
+            return Err(RespDecodeError::EndOfInput(self.pos));
         }
         self.pos += n;
         Ok(())
     }
 
     fn buf(&mut self, n: usize) -> Result<Vec<u8>, RespDecodeError> {
-        if self.pos + n > self.data.len() {
-            return Err(RespDecodeError::EndOfInput);
+        if n > self.remaining() {
+            return Err(RespDecodeError::EndOfInput(self.pos));
         }
         let bytes = self.data[self.pos..self.pos + n].to_vec();
         self.pos += n;
@@ -118,8 +162,9 @@ impl RespDecoder {
     }
 
     fn utf8(&mut self, n: usize) -> Result<String, RespDecodeError> {
+        let start = self.pos;
         let bytes = self.buf(n)?;
-        String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8)
+        String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8(start))
     }
 
     #[allow(dead_code)]
@@ -130,6 +175,7 @@ impl RespDecoder {
 
     /// Reads ASCII decimal digits up to and including `\r\n`. Returns the number.
     fn read_length(&mut self) -> Result<usize, RespDecodeError> {
+        let start = self.pos;
         let mut n: usize = 0;
         loop {
             let c = self.u8()?;
@@ -137,10 +183,64 @@ impl RespDecoder {
                 self.skip(1)?; // skip \n
                 return Ok(n);
             }
-            n = n * 10 + (c - 48) as usize;
+            n = n
+                .checked_mul(10)
+                .zip(c.checked_sub(b'0').filter(|d| *d <= 9))
+                .and_then(|(n, d)| n.checked_add(d as usize))
+                .ok_or(RespDecodeError::InvalidNumber(start))?;
+        }
+    }
+
+    /// Reads an aggregate length, or `None` for a streamed (`?`) aggregate.
+    fn read_count(&mut self) -> Result<Option<usize>, RespDecodeError> {
+        if self.peek()? == b'?' {
+            self.skip(3)?; // ?\r\n
+            return Ok(None);
+        }
+        self.read_length().map(Some)
+    }
+
+    /// Whether another item of an aggregate follows, counting down `count`.
+    /// For a streamed aggregate, consumes the `.\r\n` terminator at the end.
+    fn next_item(&mut self, count: &mut Option<usize>) -> Result<bool, RespDecodeError> {
+        match count {
+            Some(0) => Ok(false),
+            Some(n) => {
+                *n -= 1;
+                Ok(true)
+            }
+            None if self.peek()? == b'.' => {
+                self.skip(3)?; // .\r\n
+                Ok(false)
+            }
+            None => Ok(true),
         }
     }
 
+    /// Reads the chunks of a streamed string after its `$?\r\n` header.
+    fn read_chunks(&mut self) -> Result<Vec<u8>, RespDecodeError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.read_chunk()? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    /// Reads one `;<len>\r\n<bytes>\r\n` chunk of a streamed string; `None`
+    /// for the final `;0\r\n`.
+    pub(super) fn read_chunk(&mut self) -> Result<Option<Vec<u8>>, RespDecodeError> {
+        if self.u8()? != b';' {
+            return Err(RespDecodeError::InvalidChunk(self.pos - 1));
+        }
+        let length = self.read_length()?;
+        if length == 0 {
+            return Ok(None);
+        }
+        let bytes = self.buf(length)?;
+        self.skip(2)?; // \r\n
+        Ok(Some(bytes))
+    }
+
     // ---------------------------------------------------------------- readers
 
     pub fn read_any(&mut self) -> Result<PackValue, RespDecodeError> {
@@ -176,7 +276,7 @@ impl RespDecoder {
                     PackValue::Object(fields),
                 ))))
             }
-            other => Err(RespDecodeError::UnknownType(other)),
+            other => Err(RespDecodeError::UnknownType(other, self.pos - 1)),
         }
     }
 
@@ -197,7 +297,8 @@ impl RespDecoder {
 
         let prior_try_utf8 = self.try_utf8;
         let cmd = self.read_ascii_bulk_string()?.to_uppercase().into_bytes();
-        let mut args = Vec::with_capacity(len);
+        // `$0\r\n\r\n` is the shortest argument.
+        let mut args = Vec::with_capacity(self.capacity(Some(len - 1), 6) + 1);
         args.push(cmd);
 
         self.try_utf8 = false;
@@ -261,21 +362,14 @@ impl RespDecoder {
             let len = self.pos - start - 1;
             let s = self.ascii_str_at(start, len)?;
             self.skip(1)?; // \n
-            let f = match s.as_str() {
-                "inf" => f64::INFINITY,
-                "-inf" => f64::NEG_INFINITY,
-                "nan" => f64::NAN,
-                other => other
-                    .parse::<f64>()
-                    .map_err(|_| RespDecodeError::InvalidUtf8)?,
-            };
+            let f = parse_float(&s).ok_or(RespDecodeError::InvalidUtf8(start))?;
             return Ok(PackValue::Float(f));
         }
     }
 
     fn ascii_str_at(&self, start: usize, len: usize) -> Result<String, RespDecodeError> {
         if start + len > self.data.len() {
-            return Err(RespDecodeError::EndOfInput);
+            return Err(RespDecodeError::EndOfInput(self.pos));
         }
         Ok(self.data[start..start + len]
             .iter()
@@ -293,7 +387,7 @@ impl RespDecoder {
             let len = self.pos - start - 1;
             let s = self.ascii_str_at(start, len)?;
             self.skip(1)?; // \n
-            let n: i128 = s.parse().map_err(|_| RespDecodeError::InvalidUtf8)?;
+            let n: i128 = s.parse().map_err(|_| RespDecodeError::InvalidUtf8(start))?;
             return Ok(PackValue::BigInt(n));
         }
     }
@@ -307,7 +401,7 @@ impl RespDecoder {
             }
             let size = self.pos - start - 1;
             let s = String::from_utf8(self.data[start..start + size].to_vec())
-                .map_err(|_| RespDecodeError::InvalidUtf8)?;
+                .map_err(|_| RespDecodeError::InvalidUtf8(start))?;
             self.skip(1)?; // \n
             return Ok(PackValue::Str(s));
         }
@@ -318,9 +412,14 @@ impl RespDecoder {
             self.skip(4)?; // -1\r\n
             return Ok(PackValue::Null);
         }
-        let length = self.read_length()?;
-        let bytes = self.buf(length)?;
-        self.skip(2)?; // \r\n
+        let bytes = match self.read_count()? {
+            Some(length) => {
+                let bytes = self.buf(length)?;
+                self.skip(2)?; // \r\n
+                bytes
+            }
+            None => self.read_chunks()?,
+        };
         if self.try_utf8 {
             if let Ok(s) = String::from_utf8(bytes.clone()) {
                 return Ok(PackValue::Str(s));
@@ -334,9 +433,10 @@ impl RespDecoder {
             return Err(RespDecodeError::InvalidCommand);
         }
         let length = self.read_length()?;
+        let start = self.pos;
         let bytes = self.buf(length)?;
         self.skip(2)?; // \r\n
-        String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8)
+        String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8(start))
     }
 
     fn read_str_verbatim(&mut self) -> Result<PackValue, RespDecodeError> {
@@ -372,9 +472,13 @@ impl RespDecoder {
             self.skip(4)?; // -1\r\n
             return Ok(Vec::new());
         }
-        let length = self.read_length()?;
-        let mut arr = Vec::with_capacity(length);
-        for _ in 0..length {
+        self.read_items()
+    }
+
+    fn read_items(&mut self) -> Result<Vec<PackValue>, RespDecodeError> {
+        let mut count = self.read_count()?;
+        let mut arr = Vec::with_capacity(self.capacity(count, MIN_ITEM));
+        while self.next_item(&mut count)? {
             arr.push(self.read_any()?);
         }
         Ok(arr)
@@ -390,19 +494,15 @@ impl RespDecoder {
     }
 
     fn read_set(&mut self) -> Result<PackValue, RespDecodeError> {
-        let length = self.read_length()?;
-        let mut arr = Vec::with_capacity(length);
-        for _ in 0..length {
-            arr.push(self.read_any()?);
-        }
+        let arr = self.read_items()?;
         // RESP sets are encoded as arrays (no native Set in PackValue)
         Ok(PackValue::Array(arr))
     }
 
     fn read_obj_inner(&mut self) -> Result<Vec<(String, PackValue)>, RespDecodeError> {
-        let length = self.read_length()?;
-        let mut fields = Vec::with_capacity(length);
-        for _ in 0..length {
+        let mut count = self.read_count()?;
+        let mut fields = Vec::with_capacity(self.capacity(count, 2 * MIN_ITEM));
+        while self.next_item(&mut count)? {
             let key_val = self.read_any()?;
             let key = match key_val {
                 PackValue::Str(s) => s,
@@ -419,6 +519,133 @@ impl RespDecoder {
         Ok(PackValue::Object(fields))
     }
 
+    // ---------------------------------------------------------- typed readers
+
+    /// Reads the next value as a [`RespValue`].
+    pub fn read_typed(&mut self) -> Result<RespValue, RespDecodeError> {
+        let typ = self.u8()?;
+        Ok(match typ {
+            t if t == Resp::INT => {
+                let (start, line) = self.read_line()?;
+                RespValue::Int(
+                    line.parse()
+                        .map_err(|_| RespDecodeError::InvalidNumber(start))?,
+                )
+            }
+            t if t == Resp::FLOAT => {
+                let (start, line) = self.read_line()?;
+                RespValue::Float(parse_float(&line).ok_or(RespDecodeError::InvalidNumber(start))?)
+            }
+            t if t == Resp::BIG => {
+                let (start, line) = self.read_line()?;
+                RespValue::BigNumber(
+                    line.parse()
+                        .map_err(|_| RespDecodeError::InvalidNumber(start))?,
+                )
+            }
+            t if t == Resp::STR_SIMPLE => RespValue::SimpleStr(self.read_line()?.1),
+            t if t == Resp::ERR_SIMPLE => RespValue::SimpleError(self.read_line()?.1),
+            t if t == Resp::STR_BULK => match self.read_blob()? {
+                Some(bytes) => RespValue::BulkStr(bytes),
+                None => RespValue::Null,
+            },
+            t if t == Resp::ERR_BULK => {
+                let start = self.pos;
+                let bytes = self.read_blob()?.unwrap_or_default();
+                RespValue::BulkError(
+                    String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8(start))?,
+                )
+            }
+            t if t == Resp::STR_VERBATIM => {
+                let start = self.pos;
+                let bytes = self.read_blob()?.unwrap_or_default();
+                if bytes.len() < 4 || bytes[3] != b':' {
+                    return Err(RespDecodeError::InvalidVerbatim(start));
+                }
+                let mut text =
+                    String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8(start))?;
+                let body = text.split_off(4);
+                text.pop(); // ':'
+                RespValue::Verbatim {
+                    format: text,
+                    text: body,
+                }
+            }
+            t if t == Resp::BOOL => {
+                let c = self.u8()?;
+                self.skip(2)?; // \r\n
+                RespValue::Bool(c == b't')
+            }
+            t if t == Resp::NULL => {
+                self.skip(2)?; // \r\n
+                RespValue::Null
+            }
+            t if t == Resp::ARR => {
+                if self.peek()? == Resp::MINUS {
+                    self.skip(4)?; // -1\r\n
+                    return Ok(RespValue::Null);
+                }
+                RespValue::Array(self.read_typed_items()?)
+            }
+            t if t == Resp::SET => RespValue::Set(self.read_typed_items()?),
+            t if t == Resp::PUSH => RespValue::Push(self.read_typed_items()?),
+            t if t == Resp::OBJ => RespValue::Map(self.read_typed_pairs()?),
+            t if t == Resp::ATTR => {
+                let attributes = self.read_typed_pairs()?;
+                RespValue::Attr {
+                    attributes,
+                    value: Box::new(self.read_typed()?),
+                }
+            }
+            other => return Err(RespDecodeError::UnknownType(other, self.pos - 1)),
+        })
+    }
+
+    /// Reads a UTF-8 line up to `\r\n`, returning its start offset and text.
+    fn read_line(&mut self) -> Result<(usize, String), RespDecodeError> {
+        let start = self.pos;
+        while self.u8()? != Resp::R {}
+        let bytes = self.data[start..self.pos - 1].to_vec();
+        self.skip(1)?; // \n
+        let line = String::from_utf8(bytes).map_err(|_| RespDecodeError::InvalidUtf8(start))?;
+        Ok((start, line))
+    }
+
+    /// Reads a length-prefixed payload; `None` for the `-1` null length.
+    fn read_blob(&mut self) -> Result<Option<Vec<u8>>, RespDecodeError> {
+        if self.peek()? == Resp::MINUS {
+            self.skip(4)?; // -1\r\n
+            return Ok(None);
+        }
+        match self.read_count()? {
+            Some(length) => {
+                let bytes = self.buf(length)?;
+                self.skip(2)?; // \r\n
+                Ok(Some(bytes))
+            }
+            None => self.read_chunks().map(Some),
+        }
+    }
+
+    fn read_typed_items(&mut self) -> Result<Vec<RespValue>, RespDecodeError> {
+        let mut count = self.read_count()?;
+        let mut items = Vec::with_capacity(self.capacity(count, MIN_ITEM));
+        while self.next_item(&mut count)? {
+            items.push(self.read_typed()?);
+        }
+        Ok(items)
+    }
+
+    fn read_typed_pairs(&mut self) -> Result<Vec<(RespValue, RespValue)>, RespDecodeError> {
+        let mut count = self.read_count()?;
+        let mut pairs = Vec::with_capacity(self.capacity(count, 2 * MIN_ITEM));
+        while self.next_item(&mut count)? {
+            let key = self.read_typed()?;
+            pairs.push((key, self.read_typed()?));
+        }
+        Ok(pairs)
+    }
+
     // ---------------------------------------------------------------- skip
 
     pub fn skip_any(&mut self) -> Result<(), RespDecodeError> {
@@ -439,7 +666,7 @@ impl RespDecoder {
             t if t == Resp::ERR_SIMPLE => self.skip_line(),
             t if t == Resp::ERR_BULK => self.skip_str_bulk(),
             t if t == Resp::ATTR => self.skip_obj(),
-            other => Err(RespDecodeError::UnknownType(other)),
+            other => Err(RespDecodeError::UnknownType(other, self.pos - 1)),
         }
     }
 
@@ -463,8 +690,13 @@ impl RespDecoder {
         if self.peek()? == Resp::MINUS {
             return self.skip(4); // -1\r\n
         }
-        let length = self.read_length()?;
-        self.skip(length + 2) // content + \r\n
+        match self.read_count()? {
+            Some(length) => self.skip(length + 2), // content + \r\n
+            None => {
+                while self.read_chunk()?.is_some() {}
+                Ok(())
+            }
+        }
     }
 
     fn skip_str_verbatim(&mut self) -> Result<(), RespDecodeError> {
@@ -476,27 +708,32 @@ impl RespDecoder {
         if self.peek()? == Resp::MINUS {
             return self.skip(4);
         }
-        let length = self.read_length()?;
-        for _ in 0..length {
-            self.skip_any()?;
-        }
-        Ok(())
+        self.skip_set()
     }
 
     fn skip_set(&mut self) -> Result<(), RespDecodeError> {
-        let length = self.read_length()?;
-        for _ in 0..length {
+        let mut count = self.read_count()?;
+        while self.next_item(&mut count)? {
             self.skip_any()?;
         }
         Ok(())
     }
 
     fn skip_obj(&mut self) -> Result<(), RespDecodeError> {
-        let length = self.read_length()?;
-        for _ in 0..length {
+        let mut count = self.read_count()?;
+        while self.next_item(&mut count)? {
             self.skip_any()?;
             self.skip_any()?;
         }
         Ok(())
     }
 }
+
+fn parse_float(s: &str) -> Option<f64> {
+    match s {
+        "inf" => Some(f64::INFINITY),
+        "-inf" => Some(f64::NEG_INFINITY),
+        "nan" => Some(f64::NAN),
+        other => other.parse().ok(),
+    }
+}
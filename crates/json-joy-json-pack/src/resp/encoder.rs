@@ -7,6 +7,8 @@ use json_joy_buffers::Writer;
 use super::constants::{
     Resp, RESP_EXTENSION_ATTRIBUTES, RESP_EXTENSION_PUSH, RESP_EXTENSION_VERBATIM_STRING,
 };
+use crate::pack_value::text_keyed;
+use crate::util::format_f64_js;
 use crate::PackValue;
 
 /// RESP3 protocol encoder.
@@ -50,10 +52,12 @@ impl RespEncoder {
             PackValue::UInteger(u) => self.write_integer(*u as i64),
             PackValue::Float(f) => self.write_float(*f),
             PackValue::BigInt(i) => self.write_big_int(*i),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(ext) => {
                 let tag = ext.tag;
                 match tag {
@@ -152,6 +156,12 @@ impl RespEncoder {
         self.write_rn();
     }
 
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        self.writer.u8(Resp::BIG); // (
+        self.writer.ascii(&num.to_string());
+        self.write_rn();
+    }
+
     pub fn write_float(&mut self, f: f64) {
         self.writer.u8(Resp::FLOAT); // ,
         if f == f64::INFINITY {
@@ -164,8 +174,7 @@ impl RespEncoder {
             self.writer.u8(b'n');
             self.writer.u16(u16::from_be_bytes([b'a', b'n']));
         } else {
-            let s = format!("{}", f);
-            self.writer.ascii(&s);
+            self.writer.ascii(&format_f64_js(f));
         }
         self.write_rn();
     }
@@ -387,6 +396,55 @@ impl RespEncoder {
         self.write_rn();
     }
 
+    /// Writes an array from an iterator: length-prefixed when the iterator
+    /// reports an exact size, otherwise as a streamed (`*?`) array.
+    pub fn write_arr_from_iter<'a>(&mut self, items: impl Iterator<Item = &'a PackValue>) {
+        self.write_aggregate_from_iter(Resp::ARR, items, Self::write_any);
+    }
+
+    /// Like [`write_arr_from_iter`](Self::write_arr_from_iter), for maps.
+    pub fn write_obj_from_iter<'a>(
+        &mut self,
+        entries: impl Iterator<Item = (&'a str, &'a PackValue)>,
+    ) {
+        self.write_aggregate_from_iter(Resp::OBJ, entries, |encoder, (key, value)| {
+            encoder.write_obj_chunk(key, value)
+        });
+    }
+
+    /// Like [`write_arr_from_iter`](Self::write_arr_from_iter), for sets.
+    pub fn write_set_from_iter<'a>(&mut self, items: impl Iterator<Item = &'a PackValue>) {
+        self.write_aggregate_from_iter(Resp::SET, items, Self::write_any);
+    }
+
+    fn write_aggregate_from_iter<T>(
+        &mut self,
+        typ: u8,
+        items: impl Iterator<Item = T>,
+        mut write: impl FnMut(&mut Self, T),
+    ) {
+        match items.size_hint() {
+            (lower, Some(upper)) if lower == upper => {
+                self.writer.u8(typ);
+                self.write_length(lower);
+                self.write_rn();
+                for item in items {
+                    write(self, item);
+                }
+            }
+            _ => {
+                self.writer
+                    .u32((typ as u32) << 24 | (b'?' as u32) << 16 | Resp::RN as u32);
+                for item in items {
+                    write(self, item);
+                }
+                // .\r\n
+                self.writer.u8(b'.');
+                self.write_rn();
+            }
+        }
+    }
+
     pub fn write_start_bin(&mut self) {
         // $?\r\n
         self.write_start_str();
@@ -4,6 +4,8 @@
 
 use super::encoder::RespEncoder;
 use super::{RESP_EXTENSION_ATTRIBUTES, RESP_EXTENSION_PUSH, RESP_EXTENSION_VERBATIM_STRING};
+use crate::pack_value::text_keyed;
+use crate::util::format_f64_js;
 use crate::PackValue;
 
 /// Implements RESP2-style encoding semantics used by upstream legacy encoder.
@@ -42,10 +44,12 @@ impl RespEncoderLegacy {
             PackValue::UInteger(n) => self.write_unsigned(*n),
             PackValue::Float(f) => self.write_number(*f),
             PackValue::BigInt(n) => self.encoder.write_simple_str(&n.to_string()),
+            PackValue::BigNum(n) => self.encoder.write_simple_str(&n.to_string()),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Bytes(buf) => self.encoder.write_bin(buf),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(ext) => match ext.tag {
                 RESP_EXTENSION_PUSH => {
                     if let PackValue::Array(arr) = ext.val.as_ref() {
@@ -130,7 +134,7 @@ impl RespEncoderLegacy {
         if is_safe_integer && n >= i64::MIN as f64 && n <= i64::MAX as f64 {
             self.encoder.write_integer(n as i64);
         } else {
-            self.encoder.write_simple_str(&n.to_string());
+            self.encoder.write_simple_str(&format_f64_js(n));
         }
     }
 
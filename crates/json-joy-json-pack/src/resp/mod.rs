@@ -8,6 +8,7 @@ pub mod encoder;
 pub mod encoder_legacy;
 pub mod extensions;
 pub mod streaming_decoder;
+pub mod value;
 
 pub use constants::{
     Resp, RESP_EXTENSION_ATTRIBUTES, RESP_EXTENSION_PUSH, RESP_EXTENSION_VERBATIM_STRING,
@@ -15,4 +16,5 @@ pub use constants::{
 pub use decoder::{RespDecodeError, RespDecoder};
 pub use encoder::RespEncoder;
 pub use encoder_legacy::RespEncoderLegacy;
-pub use streaming_decoder::RespStreamingDecoder;
+pub use streaming_decoder::{RespStreamEvent, RespStreamingDecoder};
+pub use value::RespValue;
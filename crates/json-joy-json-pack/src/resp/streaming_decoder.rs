@@ -2,14 +2,78 @@
 //!
 //! Upstream reference: `json-pack/src/resp/RespStreamingDecoder.ts`
 
-use super::{RespDecodeError, RespDecoder};
+use super::{Resp, RespDecodeError, RespDecoder, RespValue};
 use crate::PackValue;
+use json_joy_buffers::StreamingReader;
+
+/// Type bytes that may start a streamed (`?`) value.
+const STREAMED: [u8; 6] = [
+    Resp::STR_BULK,
+    Resp::ARR,
+    Resp::OBJ,
+    Resp::SET,
+    Resp::PUSH,
+    Resp::ATTR,
+];
+
+/// Type bytes a top-level value can start with, for
+/// [`RespStreamingDecoder::resync`].
+const TYPES: [u8; 15] = [
+    Resp::NULL,
+    Resp::BOOL,
+    Resp::INT,
+    Resp::BIG,
+    Resp::FLOAT,
+    Resp::STR_SIMPLE,
+    Resp::STR_BULK,
+    Resp::STR_VERBATIM,
+    Resp::ERR_SIMPLE,
+    Resp::ERR_BULK,
+    Resp::ARR,
+    Resp::SET,
+    Resp::OBJ,
+    Resp::PUSH,
+    Resp::ATTR,
+];
+
+/// One step of a RESP stream, from [`RespStreamingDecoder::read_event`].
+///
+/// Streamed strings and aggregates are reported piece by piece as their
+/// bytes arrive; anything else is a complete [`Value`](Self::Value).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespStreamEvent {
+    /// A complete value.
+    Value(PackValue),
+    /// `$?`: a streamed string starts.
+    StrStart,
+    /// A chunk of the open streamed string.
+    StrChunk(Vec<u8>),
+    /// The `;0` end of the open streamed string.
+    StrEnd,
+    /// A streamed aggregate starts. The type byte is [`Resp::ARR`],
+    /// [`Resp::OBJ`], [`Resp::SET`], [`Resp::PUSH`] or [`Resp::ATTR`]; its
+    /// items follow as events, map keys and values alternating.
+    AggregateStart(u8),
+    /// The `.` end of the innermost open streamed aggregate.
+    AggregateEnd,
+    /// In recovery mode, input that failed to decode with `error` and was
+    /// discarded up to the next plausible frame boundary. Open streamed
+    /// values are abandoned.
+    Skipped {
+        bytes: Vec<u8>,
+        error: RespDecodeError,
+    },
+}
 
 /// Incremental RESP decoder that accepts chunked input and emits decoded values.
 pub struct RespStreamingDecoder {
-    buffer: Vec<u8>,
-    offset: usize,
+    reader: StreamingReader,
     decoder: RespDecoder,
+    /// Type bytes of the streamed strings and aggregates open in
+    /// [`read_event`](Self::read_event).
+    open: Vec<u8>,
+    recover: bool,
+    skipped: u64,
 }
 
 impl Default for RespStreamingDecoder {
@@ -21,9 +85,11 @@ impl Default for RespStreamingDecoder {
 impl RespStreamingDecoder {
     pub fn new() -> Self {
         Self {
-            buffer: Vec::new(),
-            offset: 0,
+            reader: StreamingReader::new(),
             decoder: RespDecoder::new(),
+            open: Vec::new(),
+            recover: false,
+            skipped: 0,
         }
     }
 
@@ -35,73 +101,186 @@ impl RespStreamingDecoder {
         self.decoder.try_utf8 = value;
     }
 
+    /// Whether [`read_event`](Self::read_event) recovers from malformed
+    /// input; see [`RespStreamEvent::Skipped`].
+    pub fn recover(&self) -> bool {
+        self.recover
+    }
+
+    pub fn set_recover(&mut self, value: bool) {
+        self.recover = value;
+    }
+
+    /// Total bytes discarded by [`resync`](Self::resync).
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
     pub fn push(&mut self, data: &[u8]) {
-        self.buffer.extend_from_slice(data);
+        self.reader.push(data);
     }
 
     pub fn read(&mut self) -> Result<Option<PackValue>, RespDecodeError> {
-        if self.offset >= self.buffer.len() {
+        if self.reader.size() == 0 {
             return Ok(None);
         }
-        let input = &self.buffer[self.offset..];
+        let input = self.reader.peek_buf(self.reader.size());
         self.decoder.reset(input);
         match self.decoder.read_any() {
             Ok(value) => {
-                self.offset += self.decoder.position();
-                self.compact();
+                self.advance(self.decoder.position());
+                Ok(Some(value))
+            }
+            Err(RespDecodeError::EndOfInput(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Like [`read`](Self::read), but returns a [`RespValue`].
+    pub fn read_typed(&mut self) -> Result<Option<RespValue>, RespDecodeError> {
+        if self.reader.size() == 0 {
+            return Ok(None);
+        }
+        let input = self.reader.peek_buf(self.reader.size());
+        self.decoder.reset(input);
+        match self.decoder.read_typed() {
+            Ok(value) => {
+                self.advance(self.decoder.position());
                 Ok(Some(value))
             }
-            Err(RespDecodeError::EndOfInput) => Ok(None),
+            Err(RespDecodeError::EndOfInput(_)) => Ok(None),
             Err(err) => Err(err),
         }
     }
 
     pub fn read_cmd(&mut self) -> Result<Option<Vec<Vec<u8>>>, RespDecodeError> {
-        if self.offset >= self.buffer.len() {
+        if self.reader.size() == 0 {
             return Ok(None);
         }
-        let input = &self.buffer[self.offset..];
+        let input = self.reader.peek_buf(self.reader.size());
         self.decoder.reset(input);
         match self.decoder.read_cmd() {
             Ok(value) => {
-                self.offset += self.decoder.position();
-                self.compact();
+                self.advance(self.decoder.position());
                 Ok(Some(value))
             }
-            Err(RespDecodeError::EndOfInput) => Ok(None),
+            Err(RespDecodeError::EndOfInput(_)) => Ok(None),
             Err(err) => Err(err),
         }
     }
 
     pub fn skip(&mut self) -> Result<Option<()>, RespDecodeError> {
-        if self.offset >= self.buffer.len() {
+        if self.reader.size() == 0 {
             return Ok(None);
         }
-        let input = &self.buffer[self.offset..];
+        let input = self.reader.peek_buf(self.reader.size());
         self.decoder.reset(input);
         match self.decoder.skip_any() {
             Ok(()) => {
-                self.offset += self.decoder.position();
-                self.compact();
+                self.advance(self.decoder.position());
                 Ok(Some(()))
             }
-            Err(RespDecodeError::EndOfInput) => Ok(None),
+            Err(RespDecodeError::EndOfInput(_)) => Ok(None),
             Err(err) => Err(err),
         }
     }
 
-    fn compact(&mut self) {
-        if self.offset == 0 {
-            return;
+    /// Reads the next [`RespStreamEvent`], or `None` until more input is
+    /// pushed.
+    ///
+    /// Unlike [`read`](Self::read), a streamed string or aggregate does not
+    /// have to arrive in full: its header, each chunk or item and its end
+    /// are returned as soon as they are complete.
+    ///
+    /// In [recovery mode](Self::set_recover) a decoding error is not
+    /// returned: the input is [resynced](Self::resync) and reported as
+    /// [`RespStreamEvent::Skipped`].
+    pub fn read_event(&mut self) -> Result<Option<RespStreamEvent>, RespDecodeError> {
+        match self.read_event_raw() {
+            Err(error) if self.recover => {
+                let bytes = self.resync_from(error.offset().unwrap_or(0));
+                if bytes.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(RespStreamEvent::Skipped { bytes, error }))
+            }
+            result => result,
         }
-        if self.offset == self.buffer.len() {
-            self.buffer.clear();
-            self.offset = 0;
-            return;
+    }
+
+    /// Discards buffered input after a decoding error and returns it.
+    ///
+    /// Input is dropped up to the next `\r\n` followed by a type byte a
+    /// value can start with, or by the end of the buffer, at least one byte
+    /// past the current position. Without such a boundary everything
+    /// buffered is dropped except a trailing `\r`. Streamed values being
+    /// read by [`read_event`](Self::read_event) are abandoned.
+    ///
+    /// A frame whose header announces more bytes than will ever arrive
+    /// looks incomplete rather than malformed, and is not resynced.
+    pub fn resync(&mut self) -> Vec<u8> {
+        self.resync_from(0)
+    }
+
+    /// [`resync`](Self::resync) to a boundary at or after `from`, relative
+    /// to the current position.
+    fn resync_from(&mut self, from: usize) -> Vec<u8> {
+        let input = self.reader.peek_buf(self.reader.size());
+        let end = (from + 2..=input.len())
+            .find(|&p| {
+                &input[p - 2..p] == b"\r\n" && input.get(p).is_none_or(|b| TYPES.contains(b))
+            })
+            .unwrap_or(input.len() - usize::from(input.last() == Some(&Resp::R)));
+        let bytes = input[..end].to_vec();
+        self.open.clear();
+        self.advance(end);
+        self.skipped += end as u64;
+        bytes
+    }
+
+    fn read_event_raw(&mut self) -> Result<Option<RespStreamEvent>, RespDecodeError> {
+        let input = self.reader.peek_buf(self.reader.size());
+        let Some(&typ) = input.first() else {
+            return Ok(None);
+        };
+        let top = self.open.last().copied();
+        let (event, consumed) = if top == Some(Resp::STR_BULK) {
+            self.decoder.reset(input);
+            let event = match self.decoder.read_chunk() {
+                Ok(Some(chunk)) => RespStreamEvent::StrChunk(chunk),
+                Ok(None) => RespStreamEvent::StrEnd,
+                Err(RespDecodeError::EndOfInput(_)) => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            (event, self.decoder.position())
+        } else if top.is_some() && typ == b'.' {
+            (RespStreamEvent::AggregateEnd, 3)
+        } else if input.get(1) == Some(&b'?') && STREAMED.contains(&typ) {
+            let event = match typ {
+                Resp::STR_BULK => RespStreamEvent::StrStart,
+                _ => RespStreamEvent::AggregateStart(typ),
+            };
+            (event, 4)
+        } else {
+            return Ok(self.read()?.map(RespStreamEvent::Value));
+        };
+        if input.len() < consumed {
+            return Ok(None);
         }
-        if self.offset >= 8192 || self.offset * 2 >= self.buffer.len() {
-            self.buffer.drain(..self.offset);
-            self.offset = 0;
+        match event {
+            RespStreamEvent::StrStart | RespStreamEvent::AggregateStart(_) => self.open.push(typ),
+            RespStreamEvent::StrEnd | RespStreamEvent::AggregateEnd => {
+                self.open.pop();
+            }
+            _ => {}
         }
+        self.advance(consumed);
+        Ok(Some(event))
+    }
+
+    /// Drops the first `n` unread bytes.
+    fn advance(&mut self, n: usize) {
+        self.reader.skip(n);
+        self.reader.consume();
     }
 }
@@ -0,0 +1,109 @@
+//! Typed RESP3 values.
+//!
+//! Rust-only extension: upstream decodes RESP3 into plain JSON values, which
+//! turns sets into arrays, drops verbatim string formats and error markers,
+//! and stringifies map keys. [`RespDecoder::decode_typed`] keeps them.
+//!
+//! [`RespDecoder::decode_typed`]: super::RespDecoder::decode_typed
+
+use super::constants::RESP_EXTENSION_PUSH;
+use crate::{JsonPackBigInt, JsonPackExtension, PackValue};
+
+/// A RESP3 value with its wire type preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespValue {
+    /// `_`, or a null bulk string or array.
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    BigNumber(JsonPackBigInt),
+    SimpleStr(String),
+    BulkStr(Vec<u8>),
+    /// `=`, e.g. format `"txt"` or `"mkd"`.
+    Verbatim {
+        format: String,
+        text: String,
+    },
+    SimpleError(String),
+    BulkError(String),
+    Array(Vec<RespValue>),
+    Set(Vec<RespValue>),
+    Map(Vec<(RespValue, RespValue)>),
+    Push(Vec<RespValue>),
+    /// Attributes (`|`) and the reply they annotate.
+    Attr {
+        attributes: Vec<(RespValue, RespValue)>,
+        value: Box<RespValue>,
+    },
+}
+
+impl RespValue {
+    /// Text of a string-like value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            RespValue::SimpleStr(s)
+            | RespValue::Verbatim { text: s, .. }
+            | RespValue::SimpleError(s)
+            | RespValue::BulkError(s) => Some(s),
+            RespValue::BulkStr(b) => std::str::from_utf8(b).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, RespValue::SimpleError(_) | RespValue::BulkError(_))
+    }
+}
+
+/// Lossy conversion matching [`RespDecoder::decode`]: sets become arrays,
+/// errors and verbatim strings become strings (or bytes, for a verbatim
+/// format other than `txt`), map keys are stringified, pushes are wrapped in
+/// extension 1, and attributes are dropped in favour of the value they
+/// annotate.
+///
+/// [`RespDecoder::decode`]: super::RespDecoder::decode
+impl From<RespValue> for PackValue {
+    fn from(value: RespValue) -> Self {
+        match value {
+            RespValue::Null => PackValue::Null,
+            RespValue::Bool(b) => PackValue::Bool(b),
+            RespValue::Int(n) => PackValue::Integer(n),
+            RespValue::Float(f) => PackValue::Float(f),
+            RespValue::BigNumber(n) => n.to_i128().map_or(PackValue::BigNum(n), PackValue::BigInt),
+            RespValue::SimpleStr(s) | RespValue::SimpleError(s) | RespValue::BulkError(s) => {
+                PackValue::Str(s)
+            }
+            RespValue::BulkStr(b) => PackValue::Bytes(b),
+            RespValue::Verbatim { format, text } => {
+                if format == "txt" {
+                    PackValue::Str(text)
+                } else {
+                    PackValue::Bytes(text.into_bytes())
+                }
+            }
+            RespValue::Array(items) | RespValue::Set(items) => {
+                PackValue::Array(items.into_iter().map(Into::into).collect())
+            }
+            RespValue::Map(fields) => PackValue::Object(pack_fields(fields)),
+            RespValue::Push(items) => PackValue::Extension(Box::new(JsonPackExtension::new(
+                RESP_EXTENSION_PUSH,
+                PackValue::Array(items.into_iter().map(Into::into).collect()),
+            ))),
+            RespValue::Attr { value, .. } => (*value).into(),
+        }
+    }
+}
+
+fn pack_fields(fields: Vec<(RespValue, RespValue)>) -> Vec<(String, PackValue)> {
+    fields
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match PackValue::from(key) {
+                PackValue::Str(s) => s,
+                other => format!("{:?}", other),
+            };
+            (key, value.into())
+        })
+        .collect()
+}
@@ -4,13 +4,21 @@
 
 use json_joy_buffers::StreamingReader;
 
+use super::error::RmDecodeError;
+use super::message::RmMessage;
+
 /// Record Marshalling frame decoder.
 ///
 /// Accepts pushed byte chunks and assembles complete records from RM frames.
 /// Call [`push`] to feed data and [`read_record`] to receive reassembled records.
 pub struct RmRecordDecoder {
     pub reader: StreamingReader,
+    /// Largest record [`read_message`] will reassemble; `None` is unbounded.
+    ///
+    /// [`read_message`]: RmRecordDecoder::read_message
+    pub max_record_size: Option<usize>,
     fragments: Vec<Vec<u8>>,
+    fragments_len: usize,
 }
 
 impl Default for RmRecordDecoder {
@@ -23,7 +31,20 @@ impl RmRecordDecoder {
     pub fn new() -> Self {
         Self {
             reader: StreamingReader::new(),
+            max_record_size: None,
             fragments: Vec::new(),
+            fragments_len: 0,
+        }
+    }
+
+    /// Creates a decoder whose [`read_message`] rejects records larger than
+    /// `max_record_size` bytes.
+    ///
+    /// [`read_message`]: RmRecordDecoder::read_message
+    pub fn with_max_record_size(max_record_size: usize) -> Self {
+        Self {
+            max_record_size: Some(max_record_size),
+            ..Self::new()
         }
     }
 
@@ -44,30 +65,77 @@ impl RmRecordDecoder {
         if size < 4 {
             return None;
         }
-        let saved_x = self.reader.x();
-        // Use a closure so we can restore position on failure (mirrors the
-        // TypeScript try/catch RangeError pattern).
+        self.reader.mark();
+        // Restore the position on failure (mirrors the TypeScript try/catch
+        // RangeError pattern).
         match self.try_read_fragment() {
             Ok(result) => result,
             Err(()) => {
-                self.reader.set_x(saved_x);
+                self.reader.reset_to_mark();
                 None
             }
         }
     }
 
+    /// Reads every buffered fragment up to and including the next fin=1
+    /// frame and returns the reassembled record.
+    ///
+    /// Returns `Ok(None)` when more data is needed; fragments read so far are
+    /// kept for the next call. Unlike [`read_record`], empty records are
+    /// returned as `Some(vec![])`.
+    ///
+    /// The size limit is checked against frame headers before their payload
+    /// is buffered. On [`RmDecodeError::RecordTooLarge`] the offending header
+    /// is left unread, so the stream cannot continue and the decoder should
+    /// be dropped.
+    ///
+    /// [`read_record`]: RmRecordDecoder::read_record
+    pub fn read_message(&mut self) -> Result<Option<Vec<u8>>, RmDecodeError> {
+        Ok(self.read_fragments()?.map(RmMessage::into_record))
+    }
+
+    /// Like [`read_message`], but returns the record as the fragments it
+    /// was framed in, empty ones included.
+    ///
+    /// [`read_message`]: RmRecordDecoder::read_message
+    pub fn read_fragments(&mut self) -> Result<Option<RmMessage>, RmDecodeError> {
+        loop {
+            if self.reader.size() < 4 {
+                return Ok(None);
+            }
+            self.reader.mark();
+            let header = self.reader.u32();
+            let fin = (header & 0x8000_0000) != 0;
+            let len = (header & 0x7fff_ffff) as usize;
+            let size = self.fragments_len + len;
+            if let Some(limit) = self.max_record_size {
+                if size > limit {
+                    self.reader.reset_to_mark();
+                    return Err(RmDecodeError::RecordTooLarge { size, limit });
+                }
+            }
+            if self.reader.size() < len {
+                self.reader.reset_to_mark();
+                return Ok(None);
+            }
+            self.reader.consume();
+            self.fragments.push(self.reader.buf(len));
+            if fin {
+                self.fragments_len = 0;
+                return Ok(Some(RmMessage {
+                    fragments: core::mem::take(&mut self.fragments),
+                }));
+            }
+            self.fragments_len = size;
+        }
+    }
+
     fn try_read_fragment(&mut self) -> Result<Option<Vec<u8>>, ()> {
         let size = self.reader.size();
         if size < 4 {
             return Ok(None);
         }
-        let header = {
-            // Temporarily snapshot position to detect underflow
-            let saved = self.reader.x();
-            let h = self.reader.u32();
-            let _ = saved;
-            h
-        };
+        let header = self.reader.u32();
         let fin = (header & 0x8000_0000) != 0;
         let len = (header & 0x7fff_ffff) as usize;
         if self.reader.size() < len {
@@ -87,6 +155,7 @@ impl RmRecordDecoder {
             fragments.push(chunk);
             let record: Vec<u8> = fragments.concat();
             self.fragments = Vec::new();
+            self.fragments_len = 0;
             if record.is_empty() {
                 return Ok(None);
             }
@@ -94,6 +163,7 @@ impl RmRecordDecoder {
         } else {
             let chunk = self.reader.buf(len);
             self.fragments.push(chunk);
+            self.fragments_len += len;
             Ok(None)
         }
     }
@@ -4,7 +4,10 @@
 
 use json_joy_buffers::Writer;
 
-const MAX_SINGLE_FRAME_SIZE: u32 = 0x7fff_ffff;
+use super::error::RmEncodeError;
+use super::message::RmMessage;
+
+pub(super) const MAX_SINGLE_FRAME_SIZE: u32 = 0x7fff_ffff;
 
 /// Record Marshalling frame encoder.
 ///
@@ -39,6 +42,12 @@ impl RmRecordEncoder {
         self.writer.flush()
     }
 
+    /// Encodes a message, one frame per fragment, and returns the bytes.
+    pub fn encode_message(&mut self, message: &RmMessage) -> Result<Vec<u8>, RmEncodeError> {
+        self.write_message(message)?;
+        Ok(self.writer.flush())
+    }
+
     /// Writes a frame header into the internal writer.
     pub fn write_hdr(&mut self, fin: bool, length: u32) {
         let header: u32 = if fin { 0x8000_0000 | length } else { length };
@@ -65,6 +74,59 @@ impl RmRecordEncoder {
         }
     }
 
+    /// Writes a record split at `offsets`, which must be increasing and at
+    /// most `record.len()`: one frame per span between consecutive offsets,
+    /// the last one with fin=1. Repeated offsets produce empty fragments.
+    ///
+    /// Nothing is written if the offsets are out of order or out of range,
+    /// or a span exceeds 0x7fff_ffff bytes.
+    pub fn write_record_split(
+        &mut self,
+        record: &[u8],
+        offsets: &[usize],
+    ) -> Result<(), RmEncodeError> {
+        let mut start = 0;
+        for &offset in offsets.iter().chain([&record.len()]) {
+            if offset < start || offset > record.len() {
+                return Err(RmEncodeError::InvalidOffset {
+                    offset,
+                    len: record.len(),
+                });
+            }
+            check_fragment(offset - start)?;
+            start = offset;
+        }
+        let mut start = 0;
+        for &end in offsets {
+            self.write_split_fragment(&record[start..end], false);
+            start = end;
+        }
+        self.write_split_fragment(&record[start..], true);
+        Ok(())
+    }
+
+    /// Writes each fragment of `message` as a frame, the last one with
+    /// fin=1. Nothing is written if a fragment exceeds 0x7fff_ffff bytes.
+    pub fn write_message(&mut self, message: &RmMessage) -> Result<(), RmEncodeError> {
+        for fragment in &message.fragments {
+            check_fragment(fragment.len())?;
+        }
+        let Some((last, init)) = message.fragments.split_last() else {
+            self.write_hdr(true, 0);
+            return Ok(());
+        };
+        for fragment in init {
+            self.write_split_fragment(fragment, false);
+        }
+        self.write_split_fragment(last, true);
+        Ok(())
+    }
+
+    fn write_split_fragment(&mut self, fragment: &[u8], fin: bool) {
+        self.write_hdr(fin, fragment.len() as u32);
+        self.writer.buf(fragment);
+    }
+
     /// Writes a single fragment of a record.
     pub fn write_fragment(&mut self, record: &[u8], offset: usize, length: usize, fin: bool) {
         self.write_hdr(fin, length as u32);
@@ -104,3 +166,10 @@ impl RmRecordEncoder {
         }
     }
 }
+
+fn check_fragment(len: usize) -> Result<(), RmEncodeError> {
+    if len > MAX_SINGLE_FRAME_SIZE as usize {
+        return Err(RmEncodeError::FragmentTooLarge(len));
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! Record Marshalling (RM) error types.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RmDecodeError {
+    #[error("RM record of at least {size} bytes exceeds the {limit}-byte limit")]
+    RecordTooLarge { size: usize, limit: usize },
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RmEncodeError {
+    #[error("RM split offset {offset} is out of order or past the end of a {len}-byte record")]
+    InvalidOffset { offset: usize, len: usize },
+    #[error("RM fragment of {0} bytes exceeds a frame")]
+    FragmentTooLarge(usize),
+}
@@ -0,0 +1,50 @@
+//! Record Marshalling (RM) message as a sequence of fragments.
+//!
+//! Not an upstream port: upstream only exposes reassembled records.
+
+/// A record as the fragments it is sent in.
+///
+/// Like a fragmented WebSocket message, every fragment but the last goes
+/// out with fin=0 and the last with fin=1. Fragments may be empty, and a
+/// message without fragments is sent as a single empty fin=1 frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RmMessage {
+    pub fragments: Vec<Vec<u8>>,
+}
+
+impl RmMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A message of a single fragment.
+    pub fn from_record(record: Vec<u8>) -> Self {
+        Self {
+            fragments: vec![record],
+        }
+    }
+
+    /// Appends a fragment.
+    pub fn push(&mut self, fragment: Vec<u8>) {
+        self.fragments.push(fragment);
+    }
+
+    /// Payload length summed over all fragments.
+    pub fn len(&self) -> usize {
+        self.fragments.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.iter().all(Vec::is_empty)
+    }
+
+    /// The reassembled record. A single fragment is returned without
+    /// copying.
+    pub fn into_record(mut self) -> Vec<u8> {
+        if self.fragments.len() == 1 {
+            self.fragments.pop().unwrap_or_default()
+        } else {
+            self.fragments.concat()
+        }
+    }
+}
@@ -4,6 +4,12 @@
 
 mod decoder;
 mod encoder;
+mod error;
+mod message;
+mod writer;
 
 pub use decoder::RmRecordDecoder;
 pub use encoder::RmRecordEncoder;
+pub use error::{RmDecodeError, RmEncodeError};
+pub use message::RmMessage;
+pub use writer::RmRecordWriter;
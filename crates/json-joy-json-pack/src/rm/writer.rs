@@ -0,0 +1,69 @@
+//! Record Marshalling (RM) streaming record writer.
+//!
+//! Not an upstream port: upstream only encodes records that are fully in
+//! memory.
+
+use super::encoder::{RmRecordEncoder, MAX_SINGLE_FRAME_SIZE};
+
+/// Streams one RM record at a time from incremental payload chunks.
+///
+/// At most `max_fragment_size` payload bytes are buffered. A full fragment
+/// is emitted with fin=0 only once more payload arrives, so [`finish`] can
+/// mark the last fragment fin=1 without sending an empty trailing frame
+/// (unless the record itself is empty).
+///
+/// [`finish`]: RmRecordWriter::finish
+pub struct RmRecordWriter {
+    pub encoder: RmRecordEncoder,
+    max_fragment_size: usize,
+    pending: Vec<u8>,
+}
+
+impl Default for RmRecordWriter {
+    fn default() -> Self {
+        Self::new(MAX_SINGLE_FRAME_SIZE as usize)
+    }
+}
+
+impl RmRecordWriter {
+    /// Creates a writer emitting fragments of at most `max_fragment_size`
+    /// payload bytes, clamped to `1..=0x7fff_ffff`.
+    pub fn new(max_fragment_size: usize) -> Self {
+        Self {
+            encoder: RmRecordEncoder::new(),
+            max_fragment_size: max_fragment_size.clamp(1, MAX_SINGLE_FRAME_SIZE as usize),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn max_fragment_size(&self) -> usize {
+        self.max_fragment_size
+    }
+
+    /// Appends payload bytes to the current record and returns any complete
+    /// fin=0 frames ready to be sent (possibly none).
+    pub fn write(&mut self, mut chunk: &[u8]) -> Vec<u8> {
+        while !chunk.is_empty() {
+            if self.pending.len() == self.max_fragment_size {
+                self.emit(false);
+            }
+            let n = (self.max_fragment_size - self.pending.len()).min(chunk.len());
+            self.pending.extend_from_slice(&chunk[..n]);
+            chunk = &chunk[n..];
+        }
+        self.encoder.writer.flush()
+    }
+
+    /// Ends the current record, returning its final fin=1 frame. The writer
+    /// can then be reused for the next record.
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.emit(true);
+        self.encoder.writer.flush()
+    }
+
+    fn emit(&mut self, fin: bool) {
+        self.encoder.write_hdr(fin, self.pending.len() as u32);
+        self.encoder.writer.buf(&self.pending);
+        self.pending.clear();
+    }
+}
@@ -7,6 +7,9 @@ pub mod constants;
 pub mod decoder;
 pub mod encoder;
 pub mod messages;
+#[cfg(feature = "nfs3")]
+pub mod nfs3;
+pub mod portmap;
 
 pub use constants::{
     RpcAcceptStat, RpcAuthFlavor, RpcAuthStat, RpcMsgType, RpcRejectStat, RpcReplyStat, RPC_VERSION,
@@ -17,3 +20,6 @@ pub use messages::{
     RpcAcceptedReplyMessage, RpcCallMessage, RpcMessage, RpcMismatchInfo, RpcOpaqueAuth,
     RpcRejectedReplyMessage,
 };
+pub use portmap::{
+    PmapCallArgs, PmapCallResult, PmapDecoder, PmapEncoder, PmapError, PmapMapping, PmapProc,
+};
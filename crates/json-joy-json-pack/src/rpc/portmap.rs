@@ -0,0 +1,289 @@
+//! Portmapper (rpcbind version 2) procedures.
+//!
+//! Not an upstream port. Typed XDR bodies for the PMAP program of RFC 1833
+//! §3, plus RPC call builders for the lookups a client makes: `GETPORT` to
+//! find one service, `DUMP` to list them, `CALLIT` to call through the
+//! portmapper.
+
+use super::constants::RpcAcceptStat;
+use super::encoder::{RpcEncodeError, RpcMessageEncoder};
+use super::messages::{RpcMessage, RpcOpaqueAuth};
+use crate::xdr::{XdrDecodeError, XdrDecoder, XdrEncoder};
+
+/// Program number of the portmapper.
+pub const PMAP_PROG: u32 = 100000;
+/// Portmapper protocol version covered here.
+pub const PMAP_VERS: u32 = 2;
+/// Well-known portmapper port, on both TCP and UDP.
+pub const PMAP_PORT: u16 = 111;
+/// `prot` value of a TCP mapping.
+pub const IPPROTO_TCP: u32 = 6;
+/// `prot` value of a UDP mapping.
+pub const IPPROTO_UDP: u32 = 17;
+
+/// Portmapper procedure numbers.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PmapProc {
+    Null = 0,
+    Set = 1,
+    Unset = 2,
+    GetPort = 3,
+    Dump = 4,
+    CallIt = 5,
+}
+
+impl TryFrom<u32> for PmapProc {
+    type Error = u32;
+    fn try_from(v: u32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Null),
+            1 => Ok(Self::Set),
+            2 => Ok(Self::Unset),
+            3 => Ok(Self::GetPort),
+            4 => Ok(Self::Dump),
+            5 => Ok(Self::CallIt),
+            other => Err(other),
+        }
+    }
+}
+
+/// `struct mapping`: argument of `SET`, `UNSET` and `GETPORT`, entry of a
+/// `DUMP` list. `port` is ignored by `UNSET` and `GETPORT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PmapMapping {
+    pub prog: u32,
+    pub vers: u32,
+    pub prot: u32,
+    pub port: u32,
+}
+
+/// `struct call_args`: argument of `CALLIT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PmapCallArgs {
+    pub prog: u32,
+    pub vers: u32,
+    pub proc_: u32,
+    /// The called procedure's XDR-encoded arguments.
+    pub args: Vec<u8>,
+}
+
+/// `struct call_result`: result of `CALLIT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PmapCallResult {
+    /// Port of the called program.
+    pub port: u32,
+    /// The called procedure's XDR-encoded results.
+    pub res: Vec<u8>,
+}
+
+/// Error reading a portmapper reply.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PmapError {
+    #[error("not an accepted RPC reply")]
+    NotAccepted,
+    #[error("portmapper call failed: {0:?}")]
+    Failed(RpcAcceptStat),
+    #[error(transparent)]
+    Xdr(#[from] XdrDecodeError),
+}
+
+/// Encodes portmapper argument and result bodies, and whole calls.
+pub struct PmapEncoder {
+    pub xdr: XdrEncoder,
+    pub rpc: RpcMessageEncoder,
+}
+
+impl Default for PmapEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PmapEncoder {
+    pub fn new() -> Self {
+        Self {
+            xdr: XdrEncoder::new(),
+            rpc: RpcMessageEncoder::new(),
+        }
+    }
+
+    fn write_mapping(&mut self, mapping: &PmapMapping) {
+        self.xdr.write_unsigned_int(mapping.prog);
+        self.xdr.write_unsigned_int(mapping.vers);
+        self.xdr.write_unsigned_int(mapping.prot);
+        self.xdr.write_unsigned_int(mapping.port);
+    }
+
+    pub fn encode_mapping(&mut self, mapping: &PmapMapping) -> Vec<u8> {
+        self.write_mapping(mapping);
+        self.xdr.writer.flush()
+    }
+
+    /// Result of `SET` and `UNSET`.
+    pub fn encode_bool(&mut self, ok: bool) -> Vec<u8> {
+        self.xdr.write_boolean(ok);
+        self.xdr.writer.flush()
+    }
+
+    /// Result of `GETPORT`; 0 when the program is not registered.
+    pub fn encode_port(&mut self, port: u32) -> Vec<u8> {
+        self.xdr.write_unsigned_int(port);
+        self.xdr.writer.flush()
+    }
+
+    /// Result of `DUMP`: the `pmaplist` linked list, each entry behind a
+    /// "more follows" boolean.
+    pub fn encode_pmaplist(&mut self, mappings: &[PmapMapping]) -> Vec<u8> {
+        for mapping in mappings {
+            self.xdr.write_boolean(true);
+            self.write_mapping(mapping);
+        }
+        self.xdr.write_boolean(false);
+        self.xdr.writer.flush()
+    }
+
+    pub fn encode_call_args(&mut self, args: &PmapCallArgs) -> Vec<u8> {
+        self.xdr.write_unsigned_int(args.prog);
+        self.xdr.write_unsigned_int(args.vers);
+        self.xdr.write_unsigned_int(args.proc_);
+        self.xdr.write_varlen_opaque(&args.args);
+        self.xdr.writer.flush()
+    }
+
+    pub fn encode_call_result(&mut self, result: &PmapCallResult) -> Vec<u8> {
+        self.xdr.write_unsigned_int(result.port);
+        self.xdr.write_varlen_opaque(&result.res);
+        self.xdr.writer.flush()
+    }
+
+    /// An `AUTH_NONE` call of `proc_` with the given XDR body.
+    pub fn encode_call(
+        &mut self,
+        xid: u32,
+        proc_: PmapProc,
+        params: &[u8],
+    ) -> Result<Vec<u8>, RpcEncodeError> {
+        let none = RpcOpaqueAuth::none();
+        self.rpc.encode_call(
+            xid,
+            PMAP_PROG,
+            PMAP_VERS,
+            proc_ as u32,
+            &none,
+            &none,
+            params,
+        )
+    }
+
+    /// A `GETPORT` call asking for the port of `prog`/`vers` over `prot`.
+    pub fn encode_getport_call(
+        &mut self,
+        xid: u32,
+        prog: u32,
+        vers: u32,
+        prot: u32,
+    ) -> Result<Vec<u8>, RpcEncodeError> {
+        let params = self.encode_mapping(&PmapMapping {
+            prog,
+            vers,
+            prot,
+            port: 0,
+        });
+        self.encode_call(xid, PmapProc::GetPort, &params)
+    }
+
+    pub fn encode_dump_call(&mut self, xid: u32) -> Result<Vec<u8>, RpcEncodeError> {
+        self.encode_call(xid, PmapProc::Dump, &[])
+    }
+
+    pub fn encode_callit_call(
+        &mut self,
+        xid: u32,
+        args: &PmapCallArgs,
+    ) -> Result<Vec<u8>, RpcEncodeError> {
+        let params = self.encode_call_args(args);
+        self.encode_call(xid, PmapProc::CallIt, &params)
+    }
+}
+
+/// Decodes portmapper argument and result bodies.
+pub struct PmapDecoder {
+    pub xdr: XdrDecoder,
+}
+
+impl Default for PmapDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PmapDecoder {
+    pub fn new() -> Self {
+        Self {
+            xdr: XdrDecoder::new(),
+        }
+    }
+
+    fn read_mapping(&mut self) -> Result<PmapMapping, XdrDecodeError> {
+        Ok(PmapMapping {
+            prog: self.xdr.read_unsigned_int()?,
+            vers: self.xdr.read_unsigned_int()?,
+            prot: self.xdr.read_unsigned_int()?,
+            port: self.xdr.read_unsigned_int()?,
+        })
+    }
+
+    pub fn decode_mapping(&mut self, data: &[u8]) -> Result<PmapMapping, XdrDecodeError> {
+        self.xdr.reset(data);
+        self.read_mapping()
+    }
+
+    pub fn decode_bool(&mut self, data: &[u8]) -> Result<bool, XdrDecodeError> {
+        self.xdr.reset(data);
+        self.xdr.read_boolean()
+    }
+
+    pub fn decode_port(&mut self, data: &[u8]) -> Result<u32, XdrDecodeError> {
+        self.xdr.reset(data);
+        self.xdr.read_unsigned_int()
+    }
+
+    pub fn decode_pmaplist(&mut self, data: &[u8]) -> Result<Vec<PmapMapping>, XdrDecodeError> {
+        self.xdr.reset(data);
+        let mut mappings = Vec::new();
+        while self.xdr.read_boolean()? {
+            mappings.push(self.read_mapping()?);
+        }
+        Ok(mappings)
+    }
+
+    pub fn decode_call_args(&mut self, data: &[u8]) -> Result<PmapCallArgs, XdrDecodeError> {
+        self.xdr.reset(data);
+        Ok(PmapCallArgs {
+            prog: self.xdr.read_unsigned_int()?,
+            vers: self.xdr.read_unsigned_int()?,
+            proc_: self.xdr.read_unsigned_int()?,
+            args: self.xdr.read_varlen_opaque()?,
+        })
+    }
+
+    pub fn decode_call_result(&mut self, data: &[u8]) -> Result<PmapCallResult, XdrDecodeError> {
+        self.xdr.reset(data);
+        Ok(PmapCallResult {
+            port: self.xdr.read_unsigned_int()?,
+            res: self.xdr.read_varlen_opaque()?,
+        })
+    }
+
+    /// The results of a successful reply, ready for a `decode_*` method.
+    pub fn results(msg: &RpcMessage) -> Result<&[u8], PmapError> {
+        match msg {
+            RpcMessage::AcceptedReply(reply) if reply.stat == RpcAcceptStat::Success => {
+                Ok(reply.results.as_deref().unwrap_or(&[]))
+            }
+            RpcMessage::AcceptedReply(reply) => Err(PmapError::Failed(reply.stat)),
+            _ => Err(PmapError::NotAccepted),
+        }
+    }
+}
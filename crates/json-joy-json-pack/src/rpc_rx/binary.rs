@@ -0,0 +1,263 @@
+//! Binary Reactive-RPC codec: framed messages, concatenated.
+//!
+//! Upstream reference: `reactive-rpc/common/codec/binary/`
+//!
+//! Every frame starts with a big-endian `u32` whose top 3 bits are the
+//! frame type. A notification packs the payload size into the next 21 bits
+//! and the name length into the low 8:
+//!
+//! ```text
+//! 000 sssss ssssssss ssssssss nnnnnnnn | name | data
+//! ```
+//!
+//! Requests and responses carry a `u16` id and a payload size of up to 27
+//! bits. Bit 28 (`e`) says whether the size needs more than 12 bits; if so,
+//! the id bytes that no longer fit in the header follow the data:
+//!
+//! ```text
+//! ttt 0 ssss ssssssss iiiiiiii iiiiiiii                 size < 2^12
+//! ttt 1 ssss ssssssss 0sssssss iiiiiiii | ... | iiiiiiii   size < 2^19
+//! ttt 1 ssss ssssssss 1sssssss ssssssss | ... | iiiiiiii iiiiiiii
+//! ```
+//!
+//! Requests put a `u8` name length and the name between the header and the
+//! data. A missing value is a zero-size payload.
+//!
+//! | Type    | Message               |
+//! |---------|-----------------------|
+//! | `0b000` | `Notification`        |
+//! | `0b001` | `RequestData`         |
+//! | `0b010` | `RequestComplete`     |
+//! | `0b011` | `RequestError`        |
+//! | `0b100` | `ResponseData`        |
+//! | `0b101` | `ResponseComplete`    |
+//! | `0b110` | `ResponseError`       |
+//! | `0b111` | control               |
+//!
+//! A control frame is `0xe000_0000 | id` for `RequestUnsubscribe` and
+//! `0xe001_0000 | id` for `ResponseUnsubscribe`.
+
+use json_joy_buffers::Writer;
+
+use super::error::RxError;
+use super::messages::RxMessage;
+use crate::codecs::JsonValueCodec;
+use crate::PackValue;
+
+/// Largest notification payload: 21 bits of size.
+const MAX_NOTIFICATION_SIZE: usize = 0x1f_ffff;
+/// Largest request or response payload: 27 bits of size.
+const MAX_PAYLOAD_SIZE: usize = 0x7ff_ffff;
+/// Bit 28, set when the payload size needs more than 12 bits.
+const EXTENDED: u32 = 1 << 28;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RxBinaryCodec;
+
+impl RxBinaryCodec {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn encode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        messages: &[RxMessage],
+    ) -> Result<Vec<u8>, RxError> {
+        let mut writer = Writer::new();
+        for message in messages {
+            Self::write_message(&mut writer, codec, message)?;
+        }
+        Ok(writer.flush())
+    }
+
+    pub fn decode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        bytes: &[u8],
+    ) -> Result<Vec<RxMessage>, RxError> {
+        let mut c = Cur { data: bytes, x: 0 };
+        let mut messages = Vec::new();
+        while c.x < bytes.len() {
+            messages.push(Self::read_message(&mut c, codec)?);
+        }
+        Ok(messages)
+    }
+
+    fn write_message(
+        writer: &mut Writer,
+        codec: &mut dyn JsonValueCodec,
+        message: &RxMessage,
+    ) -> Result<(), RxError> {
+        let mut payload = |value: Option<&PackValue>, max: usize| -> Result<Vec<u8>, RxError> {
+            let bytes = match value {
+                Some(value) => codec.encode(value)?,
+                None => Vec::new(),
+            };
+            if bytes.len() > max {
+                return Err(RxError::PayloadTooLarge(bytes.len()));
+            }
+            Ok(bytes)
+        };
+        let (kind, id, method, value) = match message {
+            RxMessage::Notification { method, value } => {
+                let data = payload(value.as_ref(), MAX_NOTIFICATION_SIZE)?;
+                let name = method_len(method)?;
+                writer.u32(((data.len() as u32) << 8) | u32::from(name));
+                writer.utf8(method);
+                writer.buf(&data);
+                return Ok(());
+            }
+            RxMessage::RequestUnsubscribe { id } => {
+                writer.u32(0xe000_0000 | u32::from(*id));
+                return Ok(());
+            }
+            RxMessage::ResponseUnsubscribe { id } => {
+                writer.u32(0xe001_0000 | u32::from(*id));
+                return Ok(());
+            }
+            RxMessage::RequestData { id, method, value } => {
+                (0b001, *id, Some(method), value.as_ref())
+            }
+            RxMessage::RequestComplete { id, method, value } => {
+                (0b010, *id, Some(method), value.as_ref())
+            }
+            RxMessage::RequestError { id, method, value } => {
+                (0b011, *id, Some(method), Some(value))
+            }
+            RxMessage::ResponseData { id, value } => (0b100, *id, None, Some(value)),
+            RxMessage::ResponseComplete { id, value } => (0b101, *id, None, value.as_ref()),
+            RxMessage::ResponseError { id, value } => (0b110, *id, None, Some(value)),
+        };
+        let data = payload(value, MAX_PAYLOAD_SIZE)?;
+        let size = data.len() as u32;
+        let id = u32::from(id);
+        let kind = kind << 29;
+        if size < 1 << 12 {
+            writer.u32(kind | (size << 16) | id);
+        } else if size < 1 << 19 {
+            writer.u32(kind | EXTENDED | ((size >> 7) << 16) | ((size & 0x7f) << 8) | (id >> 8));
+        } else {
+            writer.u32(kind | EXTENDED | ((size >> 15) << 16) | 0x8000 | (size & 0x7fff));
+        }
+        if let Some(method) = method {
+            writer.u8(method_len(method)?);
+            writer.utf8(method);
+        }
+        writer.buf(&data);
+        if size >= 1 << 19 {
+            writer.u16(id as u16);
+        } else if size >= 1 << 12 {
+            writer.u8(id as u8);
+        }
+        Ok(())
+    }
+
+    fn read_message(c: &mut Cur, codec: &mut dyn JsonValueCodec) -> Result<RxMessage, RxError> {
+        let at = c.x;
+        let word = c.u32()?;
+        let kind = (word >> 29) as u8;
+        let mut data = |c: &mut Cur, size: usize| -> Result<Option<PackValue>, RxError> {
+            let bytes = c.take(size)?;
+            if bytes.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(codec.decode(bytes)?))
+        };
+        let required = |value: Option<PackValue>| value.unwrap_or(PackValue::Undefined);
+        if kind == 0b000 {
+            let method = c.method((word & 0xff) as usize)?;
+            let value = data(c, (word >> 8) as usize)?;
+            return Ok(RxMessage::Notification { method, value });
+        }
+        if kind == 0b111 {
+            let id = word as u16;
+            return match (word >> 16) & 0x1fff {
+                0 => Ok(RxMessage::RequestUnsubscribe { id }),
+                1 => Ok(RxMessage::ResponseUnsubscribe { id }),
+                control => Err(RxError::InvalidType(control.min(0xff) as u8, at)),
+            };
+        }
+        let high = (word >> 16) & 0xfff;
+        let (size, id_hi, tail) = if word & EXTENDED == 0 {
+            (high, Some(word & 0xffff), 0)
+        } else if word & 0x8000 == 0 {
+            ((high << 7) | ((word >> 8) & 0x7f), Some(word & 0xff), 1)
+        } else {
+            ((high << 15) | (word & 0x7fff), None, 2)
+        };
+        let method = if kind <= 0b011 {
+            let len = c.u8()? as usize;
+            Some(c.method(len)?)
+        } else {
+            None
+        };
+        let value = data(c, size as usize)?;
+        let id = match (id_hi, tail) {
+            (Some(id), 0) => id as u16,
+            (Some(hi), _) => ((hi as u16) << 8) | u16::from(c.u8()?),
+            _ => c.u16()?,
+        };
+        let method = method.unwrap_or_default();
+        Ok(match kind {
+            0b001 => RxMessage::RequestData { id, method, value },
+            0b010 => RxMessage::RequestComplete { id, method, value },
+            0b011 => RxMessage::RequestError {
+                id,
+                method,
+                value: required(value),
+            },
+            0b100 => RxMessage::ResponseData {
+                id,
+                value: required(value),
+            },
+            0b101 => RxMessage::ResponseComplete { id, value },
+            _ => RxMessage::ResponseError {
+                id,
+                value: required(value),
+            },
+        })
+    }
+}
+
+fn method_len(method: &str) -> Result<u8, RxError> {
+    u8::try_from(method.len()).map_err(|_| RxError::MethodTooLong)
+}
+
+struct Cur<'a> {
+    data: &'a [u8],
+    x: usize,
+}
+
+impl<'a> Cur<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], RxError> {
+        if self.data.len() - self.x < n {
+            return Err(RxError::UnexpectedEof(self.x));
+        }
+        let bytes = &self.data[self.x..self.x + n];
+        self.x += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, RxError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, RxError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, RxError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn method(&mut self, len: usize) -> Result<String, RxError> {
+        let at = self.x;
+        let bytes = self.take(len)?;
+        crate::utf8::from_utf8(bytes)
+            .map(str::to_string)
+            .map_err(|_| RxError::InvalidMethod(at))
+    }
+}
@@ -0,0 +1,188 @@
+//! Compact Reactive-RPC codec: each message is a small array.
+//!
+//! Upstream reference: `reactive-rpc/common/codec/compact/`
+//!
+//! | Message               | Array                         |
+//! |-----------------------|-------------------------------|
+//! | `RequestData`         | `[0, id, method, data?]`      |
+//! | `RequestComplete`     | `[1, id, method, data?]`      |
+//! | `RequestError`        | `[2, id, method, error]`      |
+//! | `RequestUnsubscribe`  | `[3, id]`                     |
+//! | `ResponseData`        | `[4, id, data]`               |
+//! | `ResponseComplete`    | `[5, id, data?]`              |
+//! | `ResponseError`       | `[6, id, error]`              |
+//! | `ResponseUnsubscribe` | `[7, id]`                     |
+//! | `Notification`        | `[8, method, data?]`          |
+//!
+//! A batch of one message is that message's array; any other batch is an
+//! array of message arrays.
+
+use super::error::RxError;
+use super::messages::{RxMessage, RxMessageType};
+use crate::codecs::JsonValueCodec;
+use crate::PackValue;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RxCompactCodec;
+
+impl RxCompactCodec {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn encode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        messages: &[RxMessage],
+    ) -> Result<Vec<u8>, RxError> {
+        let value = match messages {
+            [message] => Self::to_pack(message),
+            _ => PackValue::Array(messages.iter().map(Self::to_pack).collect()),
+        };
+        Ok(codec.encode(&value)?)
+    }
+
+    pub fn decode(
+        &self,
+        codec: &mut dyn JsonValueCodec,
+        bytes: &[u8],
+    ) -> Result<Vec<RxMessage>, RxError> {
+        match codec.decode(bytes)? {
+            PackValue::Array(items) if matches!(items.first(), Some(PackValue::Array(_))) => {
+                items.into_iter().map(Self::from_pack).collect()
+            }
+            PackValue::Array(items) if items.is_empty() => Ok(Vec::new()),
+            value => Ok(vec![Self::from_pack(value)?]),
+        }
+    }
+
+    /// Converts a message to its compact array form.
+    pub fn to_pack(message: &RxMessage) -> PackValue {
+        let code = PackValue::Integer(message.message_type() as i64);
+        let id = |id: &u16| PackValue::Integer(*id as i64);
+        let method = |m: &str| PackValue::Str(m.to_string());
+        let arr = match message {
+            RxMessage::Notification { method: m, value } => {
+                let mut arr = vec![code, method(m)];
+                arr.extend(value.clone());
+                arr
+            }
+            RxMessage::RequestData {
+                id: i,
+                method: m,
+                value,
+            }
+            | RxMessage::RequestComplete {
+                id: i,
+                method: m,
+                value,
+            } => {
+                let mut arr = vec![code, id(i), method(m)];
+                arr.extend(value.clone());
+                arr
+            }
+            RxMessage::RequestError {
+                id: i,
+                method: m,
+                value,
+            } => vec![code, id(i), method(m), value.clone()],
+            RxMessage::RequestUnsubscribe { id: i } | RxMessage::ResponseUnsubscribe { id: i } => {
+                vec![code, id(i)]
+            }
+            RxMessage::ResponseData { id: i, value }
+            | RxMessage::ResponseError { id: i, value } => {
+                vec![code, id(i), value.clone()]
+            }
+            RxMessage::ResponseComplete { id: i, value } => {
+                let mut arr = vec![code, id(i)];
+                arr.extend(value.clone());
+                arr
+            }
+        };
+        PackValue::Array(arr)
+    }
+
+    /// Parses one message from its compact array form.
+    pub fn from_pack(value: PackValue) -> Result<RxMessage, RxError> {
+        let PackValue::Array(arr) = value else {
+            return Err(RxError::InvalidCompact("message is not an array"));
+        };
+        let mut it = arr.into_iter();
+        let code = it
+            .next()
+            .and_then(|v| as_u64(&v))
+            .and_then(|c| u8::try_from(c).ok())
+            .and_then(RxMessageType::from_u8)
+            .ok_or(RxError::InvalidCompact("invalid message type"))?;
+        let message = match code {
+            RxMessageType::Notification => RxMessage::Notification {
+                method: take_method(&mut it)?,
+                value: it.next(),
+            },
+            RxMessageType::RequestData => RxMessage::RequestData {
+                id: take_id(&mut it)?,
+                method: take_method(&mut it)?,
+                value: it.next(),
+            },
+            RxMessageType::RequestComplete => RxMessage::RequestComplete {
+                id: take_id(&mut it)?,
+                method: take_method(&mut it)?,
+                value: it.next(),
+            },
+            RxMessageType::RequestError => RxMessage::RequestError {
+                id: take_id(&mut it)?,
+                method: take_method(&mut it)?,
+                value: take_value(&mut it)?,
+            },
+            RxMessageType::RequestUnsubscribe => RxMessage::RequestUnsubscribe {
+                id: take_id(&mut it)?,
+            },
+            RxMessageType::ResponseData => RxMessage::ResponseData {
+                id: take_id(&mut it)?,
+                value: take_value(&mut it)?,
+            },
+            RxMessageType::ResponseComplete => RxMessage::ResponseComplete {
+                id: take_id(&mut it)?,
+                value: it.next(),
+            },
+            RxMessageType::ResponseError => RxMessage::ResponseError {
+                id: take_id(&mut it)?,
+                value: take_value(&mut it)?,
+            },
+            RxMessageType::ResponseUnsubscribe => RxMessage::ResponseUnsubscribe {
+                id: take_id(&mut it)?,
+            },
+        };
+        if it.next().is_some() {
+            return Err(RxError::InvalidCompact("too many elements"));
+        }
+        Ok(message)
+    }
+}
+
+fn as_u64(value: &PackValue) -> Option<u64> {
+    match value {
+        PackValue::Integer(i) => u64::try_from(*i).ok(),
+        PackValue::UInteger(u) => Some(*u),
+        PackValue::Float(f) if f.fract() == 0.0 && *f >= 0.0 => Some(*f as u64),
+        _ => None,
+    }
+}
+
+fn take_id(it: &mut impl Iterator<Item = PackValue>) -> Result<u16, RxError> {
+    it.next()
+        .and_then(|v| as_u64(&v))
+        .and_then(|id| u16::try_from(id).ok())
+        .ok_or(RxError::InvalidCompact("invalid id"))
+}
+
+fn take_method(it: &mut impl Iterator<Item = PackValue>) -> Result<String, RxError> {
+    match it.next() {
+        Some(PackValue::Str(method)) => Ok(method),
+        _ => Err(RxError::InvalidCompact("invalid method")),
+    }
+}
+
+fn take_value(it: &mut impl Iterator<Item = PackValue>) -> Result<PackValue, RxError> {
+    it.next().ok_or(RxError::InvalidCompact("missing value"))
+}
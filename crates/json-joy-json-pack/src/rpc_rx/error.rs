@@ -0,0 +1,33 @@
+//! Reactive-RPC codec error type.
+
+use thiserror::Error;
+
+use crate::codecs::CodecError;
+
+#[derive(Debug, Error)]
+pub enum RxError {
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("invalid message type {0} at offset {1}")]
+    InvalidType(u8, usize),
+    #[error("invalid method name at offset {0}")]
+    InvalidMethod(usize),
+    #[error("invalid compact message: {0}")]
+    InvalidCompact(&'static str),
+    #[error("method name longer than 255 bytes")]
+    MethodTooLong,
+    #[error("payload of {0} bytes is too large for its frame")]
+    PayloadTooLarge(usize),
+    #[error(transparent)]
+    Codec(#[from] CodecError),
+}
+
+impl RxError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedEof(x) | Self::InvalidType(_, x) | Self::InvalidMethod(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
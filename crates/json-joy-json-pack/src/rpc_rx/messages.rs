@@ -0,0 +1,102 @@
+//! Reactive-RPC message structures.
+//!
+//! Upstream reference: `json-joy/src/reactive-rpc/common/messages/messages.ts`
+
+use crate::PackValue;
+
+/// Compact-format message type codes (first element of each message array).
+///
+/// Upstream reference: `CompactMessageType` in
+/// `reactive-rpc/common/codec/compact/constants.ts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxMessageType {
+    RequestData = 0,
+    RequestComplete = 1,
+    RequestError = 2,
+    RequestUnsubscribe = 3,
+    ResponseData = 4,
+    ResponseComplete = 5,
+    ResponseError = 6,
+    ResponseUnsubscribe = 7,
+    Notification = 8,
+}
+
+impl RxMessageType {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Some(match v {
+            0 => Self::RequestData,
+            1 => Self::RequestComplete,
+            2 => Self::RequestError,
+            3 => Self::RequestUnsubscribe,
+            4 => Self::ResponseData,
+            5 => Self::ResponseComplete,
+            6 => Self::ResponseError,
+            7 => Self::ResponseUnsubscribe,
+            8 => Self::Notification,
+            _ => return None,
+        })
+    }
+}
+
+/// A Reactive-RPC message.
+///
+/// Requests and responses are correlated by a 16-bit `id`; a subscription
+/// streams `*Data` messages and ends with `*Complete`, `*Error` or
+/// `*Unsubscribe`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RxMessage {
+    /// Fire-and-forget message with no response.
+    Notification {
+        method: String,
+        value: Option<PackValue>,
+    },
+    RequestData {
+        id: u16,
+        method: String,
+        value: Option<PackValue>,
+    },
+    RequestComplete {
+        id: u16,
+        method: String,
+        value: Option<PackValue>,
+    },
+    RequestError {
+        id: u16,
+        method: String,
+        value: PackValue,
+    },
+    RequestUnsubscribe {
+        id: u16,
+    },
+    ResponseData {
+        id: u16,
+        value: PackValue,
+    },
+    ResponseComplete {
+        id: u16,
+        value: Option<PackValue>,
+    },
+    ResponseError {
+        id: u16,
+        value: PackValue,
+    },
+    ResponseUnsubscribe {
+        id: u16,
+    },
+}
+
+impl RxMessage {
+    pub fn message_type(&self) -> RxMessageType {
+        match self {
+            Self::Notification { .. } => RxMessageType::Notification,
+            Self::RequestData { .. } => RxMessageType::RequestData,
+            Self::RequestComplete { .. } => RxMessageType::RequestComplete,
+            Self::RequestError { .. } => RxMessageType::RequestError,
+            Self::RequestUnsubscribe { .. } => RxMessageType::RequestUnsubscribe,
+            Self::ResponseData { .. } => RxMessageType::ResponseData,
+            Self::ResponseComplete { .. } => RxMessageType::ResponseComplete,
+            Self::ResponseError { .. } => RxMessageType::ResponseError,
+            Self::ResponseUnsubscribe { .. } => RxMessageType::ResponseUnsubscribe,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Reactive-RPC message codecs.
+//!
+//! Upstream reference: `json-joy/src/reactive-rpc/common/` (`messages/` and
+//! `codec/{compact,binary}`)
+//!
+//! Both codecs carry message data as [`crate::PackValue`]s serialized by any
+//! [`crate::codecs::JsonValueCodec`] (CBOR, MessagePack or JSON). The
+//! existing [`crate::rpc`] module is ONC RPC and unrelated.
+
+mod binary;
+mod compact;
+mod error;
+mod messages;
+
+pub use binary::RxBinaryCodec;
+pub use compact::RxCompactCodec;
+pub use error::RxError;
+pub use messages::{RxMessage, RxMessageType};
@@ -0,0 +1,244 @@
+//! SSH payload compression (RFC 4253 §6.2).
+//!
+//! Not an upstream port. `zlib` compresses every packet after the first
+//! `SSH_MSG_NEWKEYS`; OpenSSH's `zlib@openssh.com` waits until user
+//! authentication has succeeded as well. Each direction is one zlib stream
+//! for the life of its keys, with a partial flush after every packet.
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+
+use super::error::SshError;
+
+/// A compression algorithm from a `KEXINIT` name-list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshCompressionAlgorithm {
+    /// `none`.
+    None,
+    /// `zlib`.
+    Zlib,
+    /// `zlib@openssh.com`, delayed until user authentication.
+    ZlibOpenSsh,
+}
+
+impl SshCompressionAlgorithm {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "zlib" => Some(Self::Zlib),
+            "zlib@openssh.com" => Some(Self::ZlibOpenSsh),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Zlib => "zlib",
+            Self::ZlibOpenSsh => "zlib@openssh.com",
+        }
+    }
+
+    /// The first algorithm of the client's list that the server also lists
+    /// (RFC 4253 §7.1), if it is one of ours.
+    pub fn negotiate(client: &[&str], server: &[&str]) -> Option<Self> {
+        let name = client.iter().find(|name| server.contains(name))?;
+        Self::from_name(name)
+    }
+}
+
+/// Which algorithm applies to a direction, and whether it has started.
+#[derive(Debug)]
+struct Negotiation {
+    algorithm: SshCompressionAlgorithm,
+    pending: Option<SshCompressionAlgorithm>,
+    authenticated: bool,
+}
+
+impl Negotiation {
+    fn new() -> Self {
+        Self {
+            algorithm: SshCompressionAlgorithm::None,
+            pending: None,
+            authenticated: false,
+        }
+    }
+
+    /// Switches to the pending algorithm; `true` if the stream must restart.
+    fn on_new_keys(&mut self) -> bool {
+        match self.pending.take() {
+            Some(next) if next != self.algorithm => {
+                self.algorithm = next;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        match self.algorithm {
+            SshCompressionAlgorithm::None => false,
+            SshCompressionAlgorithm::Zlib => true,
+            SshCompressionAlgorithm::ZlibOpenSsh => self.authenticated,
+        }
+    }
+}
+
+/// Compression state of the outgoing direction.
+#[derive(Debug)]
+pub struct SshCompressor {
+    negotiation: Negotiation,
+    stream: Compress,
+}
+
+impl Default for SshCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshCompressor {
+    /// A compressor for a connection before its first key exchange.
+    pub fn new() -> Self {
+        Self {
+            negotiation: Negotiation::new(),
+            stream: Compress::new(Compression::default(), true),
+        }
+    }
+
+    /// Records the algorithm a key exchange settled on. It takes effect at
+    /// the next [`on_new_keys`](Self::on_new_keys); renegotiating the
+    /// current algorithm keeps its stream going.
+    pub fn set_negotiated(&mut self, algorithm: SshCompressionAlgorithm) {
+        self.negotiation.pending = Some(algorithm);
+    }
+
+    /// Call once `SSH_MSG_NEWKEYS` has been sent.
+    pub fn on_new_keys(&mut self) {
+        if self.negotiation.on_new_keys() {
+            self.stream.reset();
+        }
+    }
+
+    /// Call once `SSH_MSG_USERAUTH_SUCCESS` has been received; starts
+    /// delayed compression.
+    pub fn on_auth_success(&mut self) {
+        self.negotiation.authenticated = true;
+    }
+
+    pub fn algorithm(&self) -> SshCompressionAlgorithm {
+        self.negotiation.algorithm
+    }
+
+    /// Whether payloads are compressed now.
+    pub fn is_active(&self) -> bool {
+        self.negotiation.is_active()
+    }
+
+    /// Appends `payload` to `out`, compressed if compression is active.
+    pub fn compress(&mut self, payload: &[u8], out: &mut Vec<u8>) -> Result<(), SshError> {
+        if !self.is_active() {
+            out.extend_from_slice(payload);
+            return Ok(());
+        }
+        let start = self.stream.total_in();
+        loop {
+            out.reserve(payload.len() / 2 + 64);
+            let consumed = (self.stream.total_in() - start) as usize;
+            self.stream
+                .compress_vec(&payload[consumed..], out, FlushCompress::Partial)
+                .map_err(|_| SshError::Compression)?;
+            // The flush is complete once the output stops filling the
+            // spare capacity.
+            if self.stream.total_in() - start == payload.len() as u64 && out.len() < out.capacity()
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Compression state of the incoming direction.
+#[derive(Debug)]
+pub struct SshDecompressor {
+    negotiation: Negotiation,
+    stream: Decompress,
+}
+
+impl Default for SshDecompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshDecompressor {
+    /// A decompressor for a connection before its first key exchange.
+    pub fn new() -> Self {
+        Self {
+            negotiation: Negotiation::new(),
+            stream: Decompress::new(true),
+        }
+    }
+
+    /// See [`SshCompressor::set_negotiated`].
+    pub fn set_negotiated(&mut self, algorithm: SshCompressionAlgorithm) {
+        self.negotiation.pending = Some(algorithm);
+    }
+
+    /// Call once `SSH_MSG_NEWKEYS` has been received.
+    pub fn on_new_keys(&mut self) {
+        if self.negotiation.on_new_keys() {
+            self.stream.reset(true);
+        }
+    }
+
+    /// Call once `SSH_MSG_USERAUTH_SUCCESS` has been sent.
+    pub fn on_auth_success(&mut self) {
+        self.negotiation.authenticated = true;
+    }
+
+    pub fn algorithm(&self) -> SshCompressionAlgorithm {
+        self.negotiation.algorithm
+    }
+
+    /// Whether payloads arrive compressed now.
+    pub fn is_active(&self) -> bool {
+        self.negotiation.is_active()
+    }
+
+    /// Appends `payload` to `out`, decompressed if compression is active.
+    /// Fails with [`SshError::InvalidPacket`] once `out` would grow past
+    /// `limit` bytes.
+    pub fn decompress(
+        &mut self,
+        payload: &[u8],
+        out: &mut Vec<u8>,
+        limit: usize,
+    ) -> Result<(), SshError> {
+        if !self.is_active() {
+            out.extend_from_slice(payload);
+            return Ok(());
+        }
+        let start = self.stream.total_in();
+        loop {
+            out.reserve(payload.len() * 2 + 64);
+            let consumed = (self.stream.total_in() - start) as usize;
+            let written = out.len();
+            let status = self
+                .stream
+                .decompress_vec(&payload[consumed..], out, FlushDecompress::Sync)
+                .map_err(|_| SshError::Compression)?;
+            if out.len() > limit {
+                return Err(SshError::InvalidPacket);
+            }
+            let done = self.stream.total_in() - start == payload.len() as u64;
+            if done && out.len() < out.capacity() {
+                return Ok(());
+            }
+            // A finished stream or a stalled one cannot take the rest.
+            let stalled = consumed as u64 == self.stream.total_in() - start && out.len() == written;
+            if status == Status::StreamEnd && !done || stalled {
+                return Err(SshError::Compression);
+            }
+        }
+    }
+}
@@ -38,7 +38,7 @@ impl SshDecoder {
     #[inline]
     fn check(&self, n: usize) -> Result<(), SshError> {
         if self.x + n > self.reader.len() {
-            Err(SshError::UnexpectedEof)
+            Err(SshError::UnexpectedEof(self.x))
         } else {
             Ok(())
         }
@@ -102,7 +102,8 @@ impl SshDecoder {
     /// Reads an SSH UTF-8 string (uint32 length + UTF-8 bytes).
     pub fn read_str(&mut self) -> Result<String, SshError> {
         let bytes = self.read_bin_str()?;
-        String::from_utf8(bytes).map_err(|_| SshError::InvalidUtf8)
+        let start = self.x - bytes.len();
+        String::from_utf8(bytes).map_err(|_| SshError::InvalidUtf8(start))
     }
 
     /// Reads an SSH ASCII string (uint32 length + ASCII bytes).
@@ -120,7 +121,7 @@ impl SshDecoder {
     /// Reads an SSH mpint (uint32 length + two's-complement MSB-first bytes).
     pub fn read_mpint(&mut self) -> Result<JsonPackMpint, SshError> {
         let bytes = self.read_bin_str()?;
-        Ok(JsonPackMpint { data: bytes })
+        Ok(JsonPackMpint::new(bytes))
     }
 
     /// Reads an SSH name-list (comma-separated ASCII names).
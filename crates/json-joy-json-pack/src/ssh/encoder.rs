@@ -36,6 +36,7 @@ impl SshEncoder {
     /// - `Integer`/`UInteger` → uint32 or uint64 depending on range
     /// - `Str` → SSH string (uint32 length + UTF-8 bytes)
     /// - `Bytes` → SSH string (uint32 length + raw bytes)
+    /// - `BigInt`/`BigNum` → mpint
     /// - `Array` → name-list (comma-separated ASCII strings; all elements must be `Str`)
     pub fn encode(&mut self, value: &PackValue) -> Result<Vec<u8>, SshError> {
         self.writer.reset();
@@ -55,8 +56,11 @@ impl SshEncoder {
             PackValue::Null | PackValue::Undefined => {
                 return Err(SshError::UnsupportedType("null"))
             }
-            PackValue::Object(_) => return Err(SshError::UnsupportedType("object")),
-            PackValue::BigInt(_) => return Err(SshError::UnsupportedType("bigint")),
+            PackValue::Object(_) | PackValue::Map(_) => {
+                return Err(SshError::UnsupportedType("object"))
+            }
+            PackValue::BigInt(i) => self.write_mpint(&JsonPackMpint::from_i128(*i)),
+            PackValue::BigNum(n) => self.write_mpint(&JsonPackMpint::from_big_int(n)),
             PackValue::Extension(_) => return Err(SshError::UnsupportedType("extension")),
             PackValue::Blob(_) => return Err(SshError::UnsupportedType("blob")),
         }
@@ -105,9 +109,10 @@ impl SshEncoder {
     }
 
     /// Writes an SSH mpint (uint32 length + two's-complement MSB-first bytes).
+    ///
+    /// Redundant leading bytes are dropped, as RFC 4251 requires.
     pub fn write_mpint(&mut self, mpint: &JsonPackMpint) {
-        self.write_uint32(mpint.data.len() as u32);
-        self.writer.buf(&mpint.data);
+        self.writer.buf(&mpint.to_ssh_bytes());
     }
 
     /// Writes an SSH name-list (comma-separated names, length-prefixed).
@@ -5,12 +5,32 @@ use thiserror::Error;
 /// Error type for SSH 2.0 binary protocol encoding and decoding operations.
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum SshError {
-    #[error("unexpected end of input")]
-    UnexpectedEof,
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
     #[error("unsupported value type for SSH encoding: {0}")]
     UnsupportedType(&'static str),
     #[error("name-list elements must be strings")]
     InvalidNameList,
+    /// A binary packet's lengths or padding break RFC 4253 §6, or its
+    /// payload exceeds the size limit.
+    #[error("invalid SSH packet")]
+    InvalidPacket,
+    /// A packet block size that is not a multiple of 8 in `8..=248`.
+    #[error("invalid SSH block size {0}")]
+    InvalidBlockSize(usize),
+    /// The zlib stream of a compressed payload is corrupt.
+    #[error("SSH payload compression failed")]
+    Compression,
+}
+
+impl SshError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedEof(x) | Self::InvalidUtf8(x) => Some(*x),
+            _ => None,
+        }
+    }
 }
@@ -2,10 +2,16 @@
 //!
 //! Upstream reference: `json-pack/src/ssh/`
 
+#[cfg(feature = "flate2")]
+mod compression;
 mod decoder;
 mod encoder;
 pub mod error;
+mod packet;
 
+#[cfg(feature = "flate2")]
+pub use compression::{SshCompressionAlgorithm, SshCompressor, SshDecompressor};
 pub use decoder::SshDecoder;
 pub use encoder::SshEncoder;
 pub use error::SshError;
+pub use packet::{SshPacketDecoder, SshPacketEncoder, SSH_MAX_PACKET_LEN};
@@ -0,0 +1,150 @@
+//! SSH binary packet framing (RFC 4253 §6).
+//!
+//! Not an upstream port. Frames payloads as
+//! `uint32 packet_length, byte padding_length, payload, padding` without
+//! encryption or MAC, which a transport layers on top. With the `flate2`
+//! feature, payloads go through the direction's negotiated compression.
+
+use json_joy_buffers::Writer;
+
+#[cfg(feature = "flate2")]
+use super::compression::{SshCompressor, SshDecompressor};
+use super::error::SshError;
+
+/// Largest `packet_length` accepted by default; OpenSSH's limit.
+pub const SSH_MAX_PACKET_LEN: usize = 256 * 1024;
+
+/// Writes binary packets.
+pub struct SshPacketEncoder {
+    pub writer: Writer,
+    block_size: usize,
+    #[cfg(feature = "flate2")]
+    pub compressor: SshCompressor,
+    #[cfg(feature = "flate2")]
+    scratch: Vec<u8>,
+}
+
+impl Default for SshPacketEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshPacketEncoder {
+    pub fn new() -> Self {
+        Self {
+            writer: Writer::new(),
+            block_size: 8,
+            #[cfg(feature = "flate2")]
+            compressor: SshCompressor::new(),
+            #[cfg(feature = "flate2")]
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Cipher block size packets are padded to; 8 by default.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Sets the cipher block size packets are padded to. Fails with
+    /// [`SshError::InvalidBlockSize`] unless it is a multiple of 8 whose
+    /// padding fits the one-byte `padding_length`, i.e. at most 248.
+    pub fn set_block_size(&mut self, block_size: usize) -> Result<(), SshError> {
+        // Padding is up to `block_size + 3` bytes.
+        if block_size == 0 || !block_size.is_multiple_of(8) || block_size > 255 - 3 {
+            return Err(SshError::InvalidBlockSize(block_size));
+        }
+        self.block_size = block_size;
+        Ok(())
+    }
+
+    /// Encodes one packet carrying `payload`.
+    pub fn encode_packet(&mut self, payload: &[u8]) -> Result<Vec<u8>, SshError> {
+        self.write_packet(payload)?;
+        Ok(self.writer.flush())
+    }
+
+    /// Writes one packet carrying `payload` into the writer.
+    ///
+    /// Padding is zero-filled with the minimum length RFC 4253 allows; the
+    /// transport replaces it if its cipher needs random padding.
+    pub fn write_packet(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        #[cfg(feature = "flate2")]
+        let payload = {
+            self.scratch.clear();
+            self.compressor.compress(payload, &mut self.scratch)?;
+            &self.scratch[..]
+        };
+        let block = self.block_size;
+        let mut padding = block - (5 + payload.len()) % block;
+        if padding < 4 {
+            padding += block;
+        }
+        let packet_len = 1 + payload.len() + padding;
+        if packet_len > u32::MAX as usize {
+            return Err(SshError::InvalidPacket);
+        }
+        self.writer.u32(packet_len as u32);
+        self.writer.u8(padding as u8);
+        self.writer.buf(payload);
+        self.writer.buf(&[0; 255][..padding]);
+        Ok(())
+    }
+}
+
+/// Reads binary packets.
+pub struct SshPacketDecoder {
+    /// Largest `packet_length` accepted, also the limit on a decompressed
+    /// payload.
+    pub max_packet_len: usize,
+    #[cfg(feature = "flate2")]
+    pub decompressor: SshDecompressor,
+}
+
+impl Default for SshPacketDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshPacketDecoder {
+    pub fn new() -> Self {
+        Self {
+            max_packet_len: SSH_MAX_PACKET_LEN,
+            #[cfg(feature = "flate2")]
+            decompressor: SshDecompressor::new(),
+        }
+    }
+
+    /// Reads the packet at the start of `input`, returning its payload and
+    /// the number of bytes consumed, or `None` if the packet is incomplete.
+    pub fn read_packet(&mut self, input: &[u8]) -> Result<Option<(Vec<u8>, usize)>, SshError> {
+        let Some(len) = input.get(..4) else {
+            return Ok(None);
+        };
+        let packet_len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        if packet_len < 5 || packet_len > self.max_packet_len || !(4 + packet_len).is_multiple_of(8)
+        {
+            return Err(SshError::InvalidPacket);
+        }
+        let Some(packet) = input.get(4..4 + packet_len) else {
+            return Ok(None);
+        };
+        let padding = packet[0] as usize;
+        if padding < 4 || padding >= packet_len {
+            return Err(SshError::InvalidPacket);
+        }
+        let payload = &packet[1..packet_len - padding];
+        #[cfg(feature = "flate2")]
+        let payload = {
+            let mut out = Vec::with_capacity(payload.len());
+            self.decompressor
+                .decompress(payload, &mut out, self.max_packet_len)?;
+            out
+        };
+        #[cfg(not(feature = "flate2"))]
+        let payload = payload.to_vec();
+        Ok(Some((payload, 4 + packet_len)))
+    }
+}
@@ -0,0 +1,59 @@
+//! Chunked byte sources for incremental decoding.
+//!
+//! Not an upstream port: upstream decoders read from a single `Uint8Array`.
+//! [`StreamingSource`] lets [`crate::msgpack::MsgPackDecoder::read_streaming`]
+//! and [`crate::cbor::CborDecoder::read_streaming`] pull values out of the
+//! `json-joy-buffers` streaming readers as network chunks arrive, returning
+//! `Ok(None)` while a value is still incomplete.
+//!
+//! Each attempt re-decodes from the first unread byte, so a value arriving in
+//! `n` chunks costs `O(n * size)`; bound how much you buffer before a value
+//! completes if peers are untrusted, since a length header can announce an
+//! arbitrarily large value.
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use json_joy_buffers::{StreamingOctetReader, StreamingReader};
+
+/// A byte source that can expose its unread bytes and skip past a decoded
+/// value.
+pub trait StreamingSource {
+    /// All bytes pushed but not yet consumed.
+    fn unread(&self) -> Cow<'_, [u8]>;
+
+    /// Marks the next `n` bytes as consumed.
+    fn advance(&mut self, n: usize);
+}
+
+impl StreamingSource for StreamingReader {
+    fn unread(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.subarray(0, None))
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.skip(n);
+        self.consume();
+    }
+}
+
+/// The chunks are copied into one buffer on every call to
+/// [`unread`](StreamingSource::unread).
+impl StreamingSource for StreamingOctetReader {
+    fn unread(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.peek_buf(self.size()))
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.skip(n);
+    }
+}
+
+impl StreamingSource for Vec<u8> {
+    fn unread(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.drain(..n);
+    }
+}
@@ -0,0 +1,281 @@
+//! Format-agnostic token events.
+//!
+//! Not an upstream port. A [`TokenSink`] receives a value as a flat stream
+//! of events (`on_map_start`, `on_key`, `on_str`, ...) instead of a
+//! [`PackValue`] tree. Only CBOR, MessagePack and JSON speak tokens
+//! directly: their decoders produce events with `parse_with`
+//! ([`CborDecoder`](crate::cbor::CborDecoder),
+//! [`MsgPackDecoderFast`](crate::msgpack::MsgPackDecoderFast) and
+//! [`JsonDecoder`](crate::json::JsonDecoder)), and the matching encoders
+//! implement [`TokenSink`], so a transform written once against the trait
+//! works between any pair of them. The other formats have no token API;
+//! decode them to a [`PackValue`] and replay it with [`walk`].
+//!
+//! Walking a value into an encoder writes the same bytes as
+//! `encode(&value)`, and parsing into a [`PackValueBuilder`] yields the same
+//! value as `decode`.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::{JsonPackBigInt, JsonPackExtension, JsonPackValue, PackValue};
+
+/// Receiver of token events.
+///
+/// Containers open with `on_array_start` / `on_map_start`, carrying the item
+/// (or pair) count when the source knows it up front, and close with the
+/// matching `*_end`. Inside a map each value is preceded by `on_key`.
+pub trait TokenSink {
+    fn on_null(&mut self);
+
+    /// Defaults to [`TokenSink::on_null`] for sinks without `undefined`.
+    fn on_undefined(&mut self) {
+        self.on_null();
+    }
+
+    fn on_bool(&mut self, value: bool);
+
+    fn on_int(&mut self, value: i64);
+
+    /// An unsigned integer above `i64::MAX`.
+    fn on_uint(&mut self, value: u64);
+
+    fn on_big_int(&mut self, value: i128);
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt);
+
+    fn on_float(&mut self, value: f64);
+
+    fn on_str(&mut self, value: &str);
+
+    fn on_bytes(&mut self, value: &[u8]);
+
+    fn on_array_start(&mut self, len: Option<usize>);
+
+    fn on_array_end(&mut self);
+
+    fn on_map_start(&mut self, len: Option<usize>);
+
+    fn on_key(&mut self, key: &str);
+
+    /// A [`PackValue::Map`] key other than a string. Defaults to
+    /// [`TokenSink::on_key`] with [`PackValue::to_key_string`] for sinks
+    /// whose keys are text only.
+    fn on_value_key(&mut self, key: &PackValue) {
+        self.on_key(&key.to_key_string());
+    }
+
+    fn on_map_end(&mut self);
+
+    /// A CBOR tag or MessagePack extension type applying to the next value.
+    fn on_tag(&mut self, tag: u64);
+
+    /// Pre-encoded bytes (a [`JsonPackValue`] blob or CBOR simple value).
+    fn on_raw(&mut self, bytes: &[u8]);
+}
+
+/// Replays `value` as token events.
+pub fn walk<S: TokenSink + ?Sized>(value: &PackValue, sink: &mut S) {
+    match value {
+        PackValue::Null => sink.on_null(),
+        PackValue::Undefined => sink.on_undefined(),
+        PackValue::Bool(b) => sink.on_bool(*b),
+        PackValue::Integer(i) => sink.on_int(*i),
+        PackValue::UInteger(u) => sink.on_uint(*u),
+        PackValue::Float(f) => sink.on_float(*f),
+        PackValue::BigInt(i) => sink.on_big_int(*i),
+        PackValue::BigNum(n) => sink.on_big_num(n),
+        PackValue::Bytes(b) => sink.on_bytes(b),
+        PackValue::Str(s) => sink.on_str(s),
+        PackValue::Array(arr) => {
+            sink.on_array_start(Some(arr.len()));
+            for item in arr {
+                walk(item, sink);
+            }
+            sink.on_array_end();
+        }
+        PackValue::Object(obj) => {
+            sink.on_map_start(Some(obj.len()));
+            for (key, val) in obj {
+                sink.on_key(key);
+                walk(val, sink);
+            }
+            sink.on_map_end();
+        }
+        PackValue::Map(map) => {
+            sink.on_map_start(Some(map.len()));
+            for (key, val) in map {
+                match key {
+                    PackValue::Str(key) => sink.on_key(key),
+                    key => sink.on_value_key(key),
+                }
+                walk(val, sink);
+            }
+            sink.on_map_end();
+        }
+        PackValue::Extension(ext) => {
+            sink.on_tag(ext.tag);
+            walk(&ext.val, sink);
+        }
+        PackValue::Blob(blob) => sink.on_raw(&blob.val),
+    }
+}
+
+/// Most items [`PackValueBuilder`] reserves for a container up front: the
+/// announced length comes from the input and may be a lie.
+const MAX_PREALLOC: usize = 1024;
+
+enum Frame {
+    Array(Vec<PackValue>),
+    Object(Vec<(PackValue, PackValue)>, Option<PackValue>),
+}
+
+/// A [`TokenSink`] that assembles the events back into a [`PackValue`].
+/// Maps come out as [`PackValue::Object`] unless a key arrived through
+/// [`TokenSink::on_value_key`], which makes them a [`PackValue::Map`].
+#[derive(Default)]
+pub struct PackValueBuilder {
+    stack: Vec<(Frame, Vec<u64>)>,
+    tags: Vec<u64>,
+    result: Option<PackValue>,
+}
+
+impl PackValueBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The completed value, or `None` if no value (or only part of one) has
+    /// been received.
+    pub fn finish(self) -> Option<PackValue> {
+        self.result
+    }
+
+    fn open(&mut self, frame: Frame) {
+        let tags = core::mem::take(&mut self.tags);
+        self.stack.push((frame, tags));
+    }
+
+    fn close(&mut self) {
+        let Some((frame, tags)) = self.stack.pop() else {
+            return;
+        };
+        let value = match frame {
+            Frame::Array(arr) => PackValue::Array(arr),
+            Frame::Object(map, _)
+                if !map.iter().all(|(key, _)| matches!(key, PackValue::Str(_))) =>
+            {
+                PackValue::Map(map)
+            }
+            Frame::Object(map, _) => PackValue::Object(
+                map.into_iter()
+                    .filter_map(|(key, value)| match key {
+                        PackValue::Str(key) => Some((key, value)),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+        };
+        self.push(value, tags);
+    }
+
+    fn value(&mut self, value: PackValue) {
+        let tags = core::mem::take(&mut self.tags);
+        self.push(value, tags);
+    }
+
+    fn push(&mut self, mut value: PackValue, tags: Vec<u64>) {
+        for tag in tags.into_iter().rev() {
+            value = PackValue::Extension(Box::new(JsonPackExtension::new(tag, value)));
+        }
+        match self.stack.last_mut() {
+            Some((Frame::Array(arr), _)) => arr.push(value),
+            Some((Frame::Object(map, key), _)) => {
+                let key = key.take().unwrap_or_else(|| PackValue::Str(String::new()));
+                map.push((key, value));
+            }
+            None => self.result = Some(value),
+        }
+    }
+}
+
+fn prealloc(len: Option<usize>) -> usize {
+    len.unwrap_or(0).min(MAX_PREALLOC)
+}
+
+impl TokenSink for PackValueBuilder {
+    fn on_null(&mut self) {
+        self.value(PackValue::Null);
+    }
+
+    fn on_undefined(&mut self) {
+        self.value(PackValue::Undefined);
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        self.value(PackValue::Bool(value));
+    }
+
+    fn on_int(&mut self, value: i64) {
+        self.value(PackValue::Integer(value));
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        self.value(PackValue::UInteger(value));
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        self.value(PackValue::BigInt(value));
+    }
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        self.value(PackValue::BigNum(value.clone()));
+    }
+
+    fn on_float(&mut self, value: f64) {
+        self.value(PackValue::Float(value));
+    }
+
+    fn on_str(&mut self, value: &str) {
+        self.value(PackValue::Str(value.into()));
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        self.value(PackValue::Bytes(value.to_vec()));
+    }
+
+    fn on_array_start(&mut self, len: Option<usize>) {
+        self.open(Frame::Array(Vec::with_capacity(prealloc(len))));
+    }
+
+    fn on_array_end(&mut self) {
+        self.close();
+    }
+
+    fn on_map_start(&mut self, len: Option<usize>) {
+        self.open(Frame::Object(Vec::with_capacity(prealloc(len)), None));
+    }
+
+    fn on_key(&mut self, key: &str) {
+        if let Some((Frame::Object(_, pending), _)) = self.stack.last_mut() {
+            *pending = Some(PackValue::Str(key.into()));
+        }
+    }
+
+    fn on_value_key(&mut self, key: &PackValue) {
+        if let Some((Frame::Object(_, pending), _)) = self.stack.last_mut() {
+            *pending = Some(key.clone());
+        }
+    }
+
+    fn on_map_end(&mut self) {
+        self.close();
+    }
+
+    fn on_tag(&mut self, tag: u64) {
+        self.tags.push(tag);
+    }
+
+    fn on_raw(&mut self, bytes: &[u8]) {
+        self.value(PackValue::Blob(JsonPackValue::new(bytes.to_vec())));
+    }
+}
@@ -0,0 +1,46 @@
+//! Direct MessagePack <-> CBOR transcoding.
+//!
+//! Not an upstream port. Both directions stream the source's token events
+//! (see [`crate::token`]) into the target encoder, writing straight into a
+//! [`Writer`] without building a [`PackValue`](crate::PackValue) tree. The
+//! output is byte-for-byte what decoding with
+//! [`MsgPackDecoderFast`] / [`CborDecoder`] and re-encoding with the default
+//! [`CborEncoder`] / [`MsgPackEncoder`] produces, including their mappings:
+//! MessagePack extensions become CBOR tags over a byte string, and CBOR tags
+//! over a byte string become extensions when the tag fits an extension type
+//! (other tags are dropped).
+//!
+//! On error the writer is rewound to where it started.
+
+use json_joy_buffers::Writer;
+
+use crate::cbor::{CborDecoder, CborEncoder, CborError};
+use crate::msgpack::{MsgPackDecoderFast, MsgPackEncoder, MsgPackError};
+
+/// Transcodes the MessagePack value at the start of `src` to CBOR, appending
+/// it to `writer`. Returns the number of source bytes consumed.
+pub fn msgpack_to_cbor(src: &[u8], writer: &mut Writer) -> Result<usize, MsgPackError> {
+    let start = writer.x - writer.x0;
+    let mut encoder =
+        CborEncoder::with_writer(core::mem::replace(writer, Writer::with_alloc_size(0)));
+    let result = MsgPackDecoderFast::new().parse_with(src, &mut encoder);
+    *writer = encoder.writer;
+    if result.is_err() {
+        writer.x = writer.x0 + start;
+    }
+    result
+}
+
+/// Transcodes the CBOR value at the start of `src` to MessagePack, appending
+/// it to `writer`. Returns the number of source bytes consumed.
+pub fn cbor_to_msgpack(src: &[u8], writer: &mut Writer) -> Result<usize, CborError> {
+    let start = writer.x - writer.x0;
+    let mut encoder =
+        MsgPackEncoder::with_writer(core::mem::replace(writer, Writer::with_alloc_size(0)));
+    let result = CborDecoder::new().parse_with(src, &mut encoder);
+    *writer = encoder.inner.writer;
+    if result.is_err() {
+        writer.x = writer.x0 + start;
+    }
+    result
+}
@@ -0,0 +1,400 @@
+//! Transforms over encoded payloads.
+//!
+//! Not an upstream port. [`redact`] strips or masks JSON Pointer paths from
+//! a CBOR, MessagePack or JSON payload by streaming its token events (see
+//! [`crate::token`]) back into the same format's encoder. Subtrees that no
+//! pointer reaches are copied event by event; only a container that loses
+//! children is assembled in memory, so its new length can be written up
+//! front.
+
+use json_joy_json_pointer::parse_json_pointer_relaxed;
+
+use crate::cbor::{CborDecoder, CborEncoder};
+use crate::codecs::CodecError;
+use crate::json::{JsonDecoder, JsonEncoder};
+use crate::msgpack::{MsgPackDecoderFast, MsgPackEncoder};
+use crate::token::{walk, PackValueBuilder, TokenSink};
+use crate::{EncodingFormat, JsonPackBigInt, PackValue};
+
+/// What [`redact_with`] does to each matched value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Redaction {
+    /// Drop the value, and its key or array slot. Removing the root leaves
+    /// `null`.
+    #[default]
+    Remove,
+    /// Replace the value, keeping its key or array slot.
+    Mask(PackValue),
+}
+
+/// Removes the values at `pointers` from the first value in `bytes`,
+/// re-encoding it in the same `format`: CBOR, MessagePack or JSON, other
+/// formats fail with [`CodecError::UnsupportedFormat`].
+///
+/// Pointers are RFC 6901 strings (a missing leading `/` is tolerated) and
+/// address the input: array indices are not shifted by earlier removals.
+/// Pointers that match nothing are ignored.
+///
+/// ```
+/// use json_joy_json_pack::transform::redact;
+/// use json_joy_json_pack::EncodingFormat;
+///
+/// let json = br#"{"user":"ann","card":"4111","tags":[1,2,3]}"#;
+/// let out = redact(json, EncodingFormat::Json, &["/card", "/tags/1"]).unwrap();
+/// assert_eq!(out, br#"{"user":"ann","tags":[1,3]}"#);
+/// ```
+pub fn redact<P: AsRef<str>>(
+    bytes: &[u8],
+    format: EncodingFormat,
+    pointers: &[P],
+) -> Result<Vec<u8>, CodecError> {
+    redact_with(bytes, format, pointers, &Redaction::Remove)
+}
+
+/// Like [`redact`], applying `redaction` to each matched value.
+pub fn redact_with<P: AsRef<str>>(
+    bytes: &[u8],
+    format: EncodingFormat,
+    pointers: &[P],
+    redaction: &Redaction,
+) -> Result<Vec<u8>, CodecError> {
+    let paths: Vec<Vec<String>> = pointers
+        .iter()
+        .map(|p| parse_json_pointer_relaxed(p.as_ref()))
+        .collect();
+    let mask = match redaction {
+        Redaction::Remove => None,
+        Redaction::Mask(value) => Some(value),
+    };
+    match format {
+        EncodingFormat::Cbor => {
+            let mut encoder = CborEncoder::new();
+            CborDecoder::new().parse_with(bytes, &mut Redactor::new(&mut encoder, &paths, mask))?;
+            Ok(encoder.writer.flush())
+        }
+        EncodingFormat::MsgPack => {
+            let mut encoder = MsgPackEncoder::new();
+            MsgPackDecoderFast::new()
+                .parse_with(bytes, &mut Redactor::new(&mut encoder, &paths, mask))?;
+            Ok(encoder.inner.writer.flush())
+        }
+        EncodingFormat::Json => {
+            let mut encoder = JsonEncoder::new();
+            JsonDecoder::new().parse_with(bytes, &mut Redactor::new(&mut encoder, &paths, mask))?;
+            Ok(encoder.writer.flush())
+        }
+        other => Err(CodecError::UnsupportedFormat(other)),
+    }
+}
+
+/// An open container on a pointer's path.
+struct Frame {
+    map: bool,
+    index: usize,
+    key: Option<String>,
+    /// Children are being assembled in [`Redactor::buffer`] from here.
+    buffered: bool,
+}
+
+/// How a value is matched against the pointers.
+enum Match {
+    /// The value is redacted; the mask, if any, has been written.
+    Redacted,
+    /// The value is kept; `true` if a pointer reaches below it.
+    Kept(bool),
+}
+
+/// A [`TokenSink`] filter forwarding to `sink` everything the pointers do
+/// not redact.
+struct Redactor<'a, S> {
+    sink: &'a mut S,
+    paths: &'a [Vec<String>],
+    mask: Option<&'a PackValue>,
+    /// Path of the value being read, below the root.
+    path: Vec<String>,
+    /// Open containers on a pointer's path.
+    frames: Vec<Frame>,
+    /// Depth inside a container no pointer reaches, copied as-is.
+    copy: usize,
+    /// Depth inside a redacted container.
+    skip: usize,
+    /// The value after a redacted tag is redacted too.
+    skip_next: bool,
+    /// Match of the value after a kept tag.
+    tagged: Option<bool>,
+    buffer: Option<PackValueBuilder>,
+}
+
+impl<'a, S: TokenSink> Redactor<'a, S> {
+    fn new(sink: &'a mut S, paths: &'a [Vec<String>], mask: Option<&'a PackValue>) -> Self {
+        Self {
+            sink,
+            paths,
+            mask,
+            path: Vec::new(),
+            frames: Vec::new(),
+            copy: 0,
+            skip: 0,
+            skip_next: false,
+            tagged: None,
+            buffer: None,
+        }
+    }
+
+    fn out(&mut self) -> &mut dyn TokenSink {
+        match &mut self.buffer {
+            Some(builder) => builder,
+            None => self.sink,
+        }
+    }
+
+    /// Matches the next value, writing its key (or mask) if it has one.
+    fn matches(&mut self) -> Match {
+        let key = match self.frames.last_mut() {
+            Some(frame) if frame.map => Some(frame.key.take().unwrap_or_default()),
+            Some(frame) => {
+                frame.index += 1;
+                Some((frame.index - 1).to_string())
+            }
+            None => None,
+        };
+        let in_map = matches!(self.frames.last(), Some(frame) if frame.map);
+        if let Some(key) = key {
+            self.path.push(key);
+        }
+        let path = &self.path;
+        if self.paths.iter().any(|p| p == path) {
+            if !self.frames.is_empty() {
+                let key = self.path.pop().unwrap_or_default();
+                if let Some(mask) = self.mask {
+                    let out = self.out();
+                    if in_map {
+                        out.on_key(&key);
+                    }
+                    walk(mask, out);
+                }
+            } else {
+                walk(self.mask.unwrap_or(&PackValue::Null), self.out());
+            }
+            return Match::Redacted;
+        }
+        let below = self
+            .paths
+            .iter()
+            .any(|p| p.len() > path.len() && p.starts_with(path));
+        if in_map {
+            let key = self.path.last().cloned().unwrap_or_default();
+            self.out().on_key(&key);
+        }
+        Match::Kept(below)
+    }
+
+    /// Leaves the value just read.
+    fn leave(&mut self) {
+        if !self.frames.is_empty() {
+            self.path.pop();
+        }
+    }
+
+    fn scalar(&mut self, write: impl FnOnce(&mut dyn TokenSink)) {
+        if self.copy > 0 {
+            return write(self.out());
+        }
+        if self.skip > 0 || core::mem::take(&mut self.skip_next) {
+            return;
+        }
+        if self.tagged.take().is_none() {
+            if let Match::Redacted = self.matches() {
+                return;
+            }
+        }
+        write(self.out());
+        self.leave();
+    }
+
+    fn open(&mut self, map: bool, len: Option<usize>) {
+        if self.copy > 0 {
+            self.copy += 1;
+            return start(self.out(), map, len);
+        }
+        if self.skip > 0 {
+            self.skip += 1;
+            return;
+        }
+        if core::mem::take(&mut self.skip_next) {
+            self.skip = 1;
+            return;
+        }
+        let below = match self.tagged.take() {
+            Some(below) => below,
+            None => match self.matches() {
+                Match::Redacted => {
+                    self.skip = 1;
+                    return;
+                }
+                Match::Kept(below) => below,
+            },
+        };
+        if !below {
+            self.leave();
+            self.copy = 1;
+            return start(self.out(), map, len);
+        }
+        // A removed child changes the length, which sized containers
+        // write up front.
+        let path = &self.path;
+        let buffered = self.mask.is_none()
+            && len.is_some()
+            && self.buffer.is_none()
+            && self
+                .paths
+                .iter()
+                .any(|p| p.len() == path.len() + 1 && p.starts_with(path));
+        if buffered {
+            self.buffer = Some(PackValueBuilder::new());
+        }
+        self.frames.push(Frame {
+            map,
+            index: 0,
+            key: None,
+            buffered,
+        });
+        start(self.out(), map, len);
+    }
+
+    fn close(&mut self, map: bool) {
+        if self.copy > 0 {
+            self.copy -= 1;
+            return end(self.out(), map);
+        }
+        if self.skip > 0 {
+            self.skip -= 1;
+            return;
+        }
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        end(self.out(), map);
+        if frame.buffered {
+            if let Some(value) = self.buffer.take().and_then(PackValueBuilder::finish) {
+                walk(&value, self.sink);
+            }
+        }
+        self.leave();
+    }
+}
+
+fn start(out: &mut dyn TokenSink, map: bool, len: Option<usize>) {
+    if map {
+        out.on_map_start(len);
+    } else {
+        out.on_array_start(len);
+    }
+}
+
+fn end(out: &mut dyn TokenSink, map: bool) {
+    if map {
+        out.on_map_end();
+    } else {
+        out.on_array_end();
+    }
+}
+
+impl<S: TokenSink> TokenSink for Redactor<'_, S> {
+    fn on_null(&mut self) {
+        self.scalar(|out| out.on_null());
+    }
+
+    fn on_undefined(&mut self) {
+        self.scalar(|out| out.on_undefined());
+    }
+
+    fn on_bool(&mut self, value: bool) {
+        self.scalar(|out| out.on_bool(value));
+    }
+
+    fn on_int(&mut self, value: i64) {
+        self.scalar(|out| out.on_int(value));
+    }
+
+    fn on_uint(&mut self, value: u64) {
+        self.scalar(|out| out.on_uint(value));
+    }
+
+    fn on_big_int(&mut self, value: i128) {
+        self.scalar(|out| out.on_big_int(value));
+    }
+
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        self.scalar(|out| out.on_big_num(value));
+    }
+
+    fn on_float(&mut self, value: f64) {
+        self.scalar(|out| out.on_float(value));
+    }
+
+    fn on_str(&mut self, value: &str) {
+        self.scalar(|out| out.on_str(value));
+    }
+
+    fn on_bytes(&mut self, value: &[u8]) {
+        self.scalar(|out| out.on_bytes(value));
+    }
+
+    fn on_array_start(&mut self, len: Option<usize>) {
+        self.open(false, len);
+    }
+
+    fn on_array_end(&mut self) {
+        self.close(false);
+    }
+
+    fn on_map_start(&mut self, len: Option<usize>) {
+        self.open(true, len);
+    }
+
+    fn on_key(&mut self, key: &str) {
+        if self.copy > 0 {
+            self.out().on_key(key);
+        } else if self.skip == 0 {
+            if let Some(frame) = self.frames.last_mut() {
+                frame.key = Some(key.into());
+            }
+        }
+    }
+
+    fn on_value_key(&mut self, key: &PackValue) {
+        if self.copy > 0 {
+            self.out().on_value_key(key);
+        } else {
+            self.on_key(&key.to_key_string());
+        }
+    }
+
+    fn on_map_end(&mut self) {
+        self.close(true);
+    }
+
+    fn on_tag(&mut self, tag: u64) {
+        if self.copy > 0 {
+            return self.out().on_tag(tag);
+        }
+        if self.skip > 0 || self.skip_next {
+            return;
+        }
+        if self.tagged.is_none() {
+            match self.matches() {
+                Match::Redacted => {
+                    self.skip_next = true;
+                    return;
+                }
+                Match::Kept(below) => self.tagged = Some(below),
+            }
+        }
+        self.out().on_tag(tag);
+    }
+
+    fn on_raw(&mut self, bytes: &[u8]) {
+        self.scalar(|out| out.on_raw(bytes));
+    }
+}
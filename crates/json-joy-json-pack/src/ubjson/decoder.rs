@@ -2,20 +2,24 @@
 //!
 //! Direct port of `ubjson/UbjsonDecoder.ts` from upstream.
 
+use json_joy_buffers::decode_f16_keep_nan;
+
 use super::error::UbjsonError;
-use crate::{JsonPackExtension, PackValue};
+use crate::{JsonPackBigInt, JsonPackExtension, PackValue};
 
 /// Internal cursor used during decoding.
 struct Cur<'a> {
     data: &'a [u8],
     pos: usize,
+    /// Multi-byte numbers are little-endian (BJData).
+    le: bool,
 }
 
 impl<'a> Cur<'a> {
     #[inline]
     fn check(&self, n: usize) -> Result<(), UbjsonError> {
         if self.pos + n > self.data.len() {
-            Err(UbjsonError::UnexpectedEof)
+            Err(UbjsonError::UnexpectedEof(self.pos))
         } else {
             Ok(())
         }
@@ -44,78 +48,62 @@ impl<'a> Cur<'a> {
     }
 
     #[inline]
-    fn i16_be(&mut self) -> Result<i16, UbjsonError> {
-        self.check(2)?;
-        let v = i16::from_be_bytes([self.data[self.pos], self.data[self.pos + 1]]);
-        self.pos += 2;
-        Ok(v)
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], UbjsonError> {
+        self.check(N)?;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.data[self.pos..self.pos + N]);
+        self.pos += N;
+        if self.le {
+            bytes.reverse();
+        }
+        Ok(bytes)
     }
 
     #[inline]
-    fn i32_be(&mut self) -> Result<i32, UbjsonError> {
-        self.check(4)?;
-        let v = i32::from_be_bytes([
-            self.data[self.pos],
-            self.data[self.pos + 1],
-            self.data[self.pos + 2],
-            self.data[self.pos + 3],
-        ]);
-        self.pos += 4;
-        Ok(v)
+    fn i16(&mut self) -> Result<i16, UbjsonError> {
+        self.bytes().map(i16::from_be_bytes)
     }
 
     #[inline]
-    fn i64_be(&mut self) -> Result<i64, UbjsonError> {
-        self.check(8)?;
-        let v = i64::from_be_bytes([
-            self.data[self.pos],
-            self.data[self.pos + 1],
-            self.data[self.pos + 2],
-            self.data[self.pos + 3],
-            self.data[self.pos + 4],
-            self.data[self.pos + 5],
-            self.data[self.pos + 6],
-            self.data[self.pos + 7],
-        ]);
-        self.pos += 8;
-        Ok(v)
+    fn u16(&mut self) -> Result<u16, UbjsonError> {
+        self.bytes().map(u16::from_be_bytes)
     }
 
     #[inline]
-    fn f32_be(&mut self) -> Result<f32, UbjsonError> {
-        self.check(4)?;
-        let v = f32::from_be_bytes([
-            self.data[self.pos],
-            self.data[self.pos + 1],
-            self.data[self.pos + 2],
-            self.data[self.pos + 3],
-        ]);
-        self.pos += 4;
-        Ok(v)
+    fn i32(&mut self) -> Result<i32, UbjsonError> {
+        self.bytes().map(i32::from_be_bytes)
     }
 
     #[inline]
-    fn f64_be(&mut self) -> Result<f64, UbjsonError> {
-        self.check(8)?;
-        let v = f64::from_be_bytes([
-            self.data[self.pos],
-            self.data[self.pos + 1],
-            self.data[self.pos + 2],
-            self.data[self.pos + 3],
-            self.data[self.pos + 4],
-            self.data[self.pos + 5],
-            self.data[self.pos + 6],
-            self.data[self.pos + 7],
-        ]);
-        self.pos += 8;
-        Ok(v)
+    fn u32(&mut self) -> Result<u32, UbjsonError> {
+        self.bytes().map(u32::from_be_bytes)
+    }
+
+    #[inline]
+    fn i64(&mut self) -> Result<i64, UbjsonError> {
+        self.bytes().map(i64::from_be_bytes)
+    }
+
+    #[inline]
+    fn u64(&mut self) -> Result<u64, UbjsonError> {
+        self.bytes().map(u64::from_be_bytes)
+    }
+
+    #[inline]
+    fn f32(&mut self) -> Result<f32, UbjsonError> {
+        self.bytes().map(f32::from_be_bytes)
+    }
+
+    #[inline]
+    fn f64(&mut self) -> Result<f64, UbjsonError> {
+        self.bytes().map(f64::from_be_bytes)
     }
 
     #[inline]
     fn utf8(&mut self, len: usize) -> Result<&'a str, UbjsonError> {
         self.check(len)?;
         let s = std::str::from_utf8(&self.data[self.pos..self.pos + len])
-            .map_err(|_| UbjsonError::InvalidUtf8)?;
+            .map_err(|_| UbjsonError::InvalidUtf8(self.pos))?;
         self.pos += len;
         Ok(s)
     }
@@ -129,44 +117,145 @@ impl<'a> Cur<'a> {
     }
 }
 
+/// How `H` high-precision numbers are decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UbjsonHighPrecisionPolicy {
+    /// The smallest integer variant that holds the value (up to
+    /// [`PackValue::BigNum`]), or [`PackValue::Float`] for decimals, which may
+    /// round.
+    #[default]
+    Number,
+    /// The digits unchanged, as [`PackValue::Str`].
+    String,
+}
+
+/// How the `N` no-op marker is decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UbjsonNoOpPolicy {
+    /// As [`PackValue::Undefined`], matching upstream.
+    #[default]
+    Undefined,
+    /// Skipped wherever a value, array item or object key may start, as the
+    /// UBJSON spec intends. A stream of only no-ops has no value.
+    Skip,
+}
+
+/// Markers that can start a value.
+const VALUE_MARKERS: &[u8] = b"ZNTFUiIlLdDSHC[{";
+
+/// Options controlling `UbjsonDecoder` input handling.
+#[derive(Debug, Clone, Default)]
+pub struct UbjsonDecoderOptions {
+    pub high_precision: UbjsonHighPrecisionPolicy,
+    pub no_op: UbjsonNoOpPolicy,
+    /// When `true`, bytes that are not a UBJSON marker are skipped, like
+    /// no-ops, where a value, array item or object key may start, instead
+    /// of failing with [`UbjsonError::UnexpectedByte`]. `]` and `}` still
+    /// close containers.
+    pub skip_unknown_markers: bool,
+    /// When `true`, input is BJData (see
+    /// [`UbjsonEncoderOptions::bjdata`](super::UbjsonEncoderOptions::bjdata)):
+    /// multi-byte numbers are little-endian, and the `u`, `m` and `M`
+    /// unsigned integers and `h` half-precision floats (NaN payloads
+    /// included) are read. Typed arrays of those have 2-, 4- and 8-byte items.
+    pub bjdata: bool,
+}
+
 /// Stateless UBJSON decoder.
 #[derive(Default)]
-pub struct UbjsonDecoder;
+pub struct UbjsonDecoder {
+    options: UbjsonDecoderOptions,
+}
 
 impl UbjsonDecoder {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_options(options: UbjsonDecoderOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &UbjsonDecoderOptions {
+        &self.options
     }
 
     pub fn decode(&self, input: &[u8]) -> Result<PackValue, UbjsonError> {
         let mut c = Cur {
             data: input,
             pos: 0,
+            le: self.options.bjdata,
         };
-        self.read_any(&mut c)
+        self.read_value(&mut c)
+    }
+
+    /// Skips the no-ops and unknown markers the options allow before a
+    /// value, array item or object key.
+    fn skip_filler(&self, c: &mut Cur) -> Result<(), UbjsonError> {
+        loop {
+            let octet = c.peek()?;
+            let skip = match octet {
+                0x4e => self.options.no_op == UbjsonNoOpPolicy::Skip, // 'N'
+                0x5d | 0x7d => false,                                 // ']' '}'
+                b'h' | b'u' | b'm' | b'M' if self.options.bjdata => false,
+                _ => self.options.skip_unknown_markers && !VALUE_MARKERS.contains(&octet),
+            };
+            if !skip {
+                return Ok(());
+            }
+            c.pos += 1;
+        }
+    }
+
+    /// Reads a value in a position where fillers may precede it.
+    fn read_value(&self, c: &mut Cur) -> Result<PackValue, UbjsonError> {
+        self.skip_filler(c)?;
+        self.read_any(c)
     }
 
     fn read_any(&self, c: &mut Cur) -> Result<PackValue, UbjsonError> {
         let octet = c.u8()?;
         match octet {
-            0x5a => Ok(PackValue::Null),                        // 'Z'
-            0x54 => Ok(PackValue::Bool(true)),                  // 'T'
-            0x46 => Ok(PackValue::Bool(false)),                 // 'F'
-            0x4e => Ok(PackValue::Undefined),                   // 'N'
-            0x55 => Ok(PackValue::Integer(c.u8()? as i64)),     // 'U' uint8
-            0x69 => Ok(PackValue::Integer(c.i8()? as i64)),     // 'i' int8
-            0x49 => Ok(PackValue::Integer(c.i16_be()? as i64)), // 'I' int16
-            0x6c => Ok(PackValue::Integer(c.i32_be()? as i64)), // 'l' int32
-            0x4c => Ok(PackValue::Integer(c.i64_be()?)),        // 'L' int64
-            0x64 => Ok(PackValue::Float(c.f32_be()? as f64)),   // 'd' float32
-            0x44 => Ok(PackValue::Float(c.f64_be()?)),          // 'D' float64
+            0x5a => Ok(PackValue::Null),                     // 'Z'
+            0x54 => Ok(PackValue::Bool(true)),               // 'T'
+            0x46 => Ok(PackValue::Bool(false)),              // 'F'
+            0x4e => Ok(PackValue::Undefined),                // 'N'
+            0x55 => Ok(PackValue::Integer(c.u8()? as i64)),  // 'U' uint8
+            0x69 => Ok(PackValue::Integer(c.i8()? as i64)),  // 'i' int8
+            0x49 => Ok(PackValue::Integer(c.i16()? as i64)), // 'I' int16
+            0x6c => Ok(PackValue::Integer(c.i32()? as i64)), // 'l' int32
+            0x4c => Ok(PackValue::Integer(c.i64()?)),        // 'L' int64
+            0x64 => Ok(PackValue::Float(c.f32()? as f64)),   // 'd' float32
+            0x44 => Ok(PackValue::Float(c.f64()?)),          // 'D' float64
+            b'u' if self.options.bjdata => Ok(PackValue::Integer(c.u16()? as i64)),
+            b'm' if self.options.bjdata => Ok(PackValue::Integer(c.u32()? as i64)),
+            b'M' if self.options.bjdata => Ok(match c.u64()? {
+                uint if uint > i64::MAX as u64 => PackValue::UInteger(uint),
+                uint => PackValue::Integer(uint as i64),
+            }),
+            b'h' if self.options.bjdata => Ok(PackValue::Float(decode_f16_keep_nan(c.u16()?))),
             0x53 => {
                 // 'S' string: UBJSON-encoded length then UTF-8
+                let len_pos = c.pos;
                 let len_val = self.read_any(c)?;
-                let len = pack_value_to_usize(len_val)?;
+                let len = pack_value_to_usize(len_val, c.data[len_pos], len_pos)?;
                 let s = c.utf8(len)?.to_owned();
                 Ok(PackValue::Str(s))
             }
+            0x48 => {
+                // 'H' high-precision number: UBJSON-encoded length then digits
+                let len_pos = c.pos;
+                let len_val = self.read_any(c)?;
+                let len = pack_value_to_usize(len_val, c.data[len_pos], len_pos)?;
+                let digits_pos = c.pos;
+                let digits = c.utf8(len)?;
+                match self.options.high_precision {
+                    UbjsonHighPrecisionPolicy::Number => {
+                        parse_high_precision(digits).ok_or(UbjsonError::InvalidNumber(digits_pos))
+                    }
+                    UbjsonHighPrecisionPolicy::String => Ok(PackValue::Str(digits.to_owned())),
+                }
+            }
             0x43 => {
                 // 'C' char: single UTF-8 code point encoded as 1 byte
                 let byte = c.u8()?;
@@ -187,8 +276,9 @@ impl UbjsonDecoder {
         // '#'
         {
             c.pos += 3;
+            let count_pos = c.pos;
             let count_val = self.read_any(c)?;
-            let count = pack_value_to_usize(count_val)?;
+            let count = pack_value_to_usize(count_val, c.data[count_pos], count_pos)?;
             let buf = c.buf(count)?.to_vec();
             return Ok(PackValue::Bytes(buf));
         }
@@ -204,8 +294,9 @@ impl UbjsonDecoder {
         if c.data.len() > c.pos && c.data[c.pos] == 0x23 {
             // '#' count
             c.pos += 1;
+            let count_pos = c.pos;
             let count_val = self.read_any(c)?;
-            count = pack_value_to_usize(count_val)? as i32;
+            count = pack_value_to_usize(count_val, c.data[count_pos], count_pos)? as i32;
         }
         // Second chance for type after count
         if c.data.len() > c.pos && c.data[c.pos] == 0x24 {
@@ -219,6 +310,9 @@ impl UbjsonDecoder {
                 0x49 => 2usize,        // 'I' int16
                 0x6c | 0x64 => 4usize, // 'l' int32 or 'd' float32
                 0x44 | 0x4c => 8usize, // 'D' float64 or 'L' int64
+                b'u' | b'h' if self.options.bjdata => 2usize,
+                b'm' if self.options.bjdata => 4usize,
+                b'M' if self.options.bjdata => 8usize,
                 _ => 1usize,
             };
             let total = count as usize * word_size;
@@ -230,7 +324,11 @@ impl UbjsonDecoder {
         } else {
             // Standard array: read items until ']'
             let mut arr = Vec::new();
-            while c.peek()? != 0x5d {
+            loop {
+                self.skip_filler(c)?;
+                if c.peek()? == 0x5d {
+                    break;
+                }
                 arr.push(self.read_any(c)?);
             }
             c.pos += 1; // consume ']'
@@ -240,15 +338,20 @@ impl UbjsonDecoder {
 
     fn read_obj(&self, c: &mut Cur) -> Result<PackValue, UbjsonError> {
         let mut obj = Vec::new();
-        while c.peek()? != 0x7d {
+        loop {
+            self.skip_filler(c)?;
+            if c.peek()? == 0x7d {
+                break;
+            }
             // Key: UBJSON integer (length) + UTF-8 bytes
+            let key_len_pos = c.pos;
             let key_len_val = self.read_any(c)?;
-            let key_len = pack_value_to_usize(key_len_val)?;
+            let key_len = pack_value_to_usize(key_len_val, c.data[key_len_pos], key_len_pos)?;
             let key = c.utf8(key_len)?.to_owned();
             if key == "__proto__" {
                 return Err(UbjsonError::InvalidKey);
             }
-            let val = self.read_any(c)?;
+            let val = self.read_value(c)?;
             obj.push((key, val));
         }
         c.pos += 1; // consume '}'
@@ -256,10 +359,63 @@ impl UbjsonDecoder {
     }
 }
 
-fn pack_value_to_usize(v: PackValue) -> Result<usize, UbjsonError> {
+/// Parses the digits of an `H` value, which follow JSON number syntax.
+fn parse_high_precision(s: &str) -> Option<PackValue> {
+    if !is_json_number(s) {
+        return None;
+    }
+    let body = s.strip_prefix('-').unwrap_or(s);
+    if body.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(if let Ok(i) = s.parse::<i64>() {
+            PackValue::Integer(i)
+        } else if let Ok(u) = s.parse::<u64>() {
+            PackValue::UInteger(u)
+        } else if let Ok(i) = s.parse::<i128>() {
+            PackValue::BigInt(i)
+        } else {
+            PackValue::BigNum(s.parse::<JsonPackBigInt>().ok()?)
+        });
+    }
+    s.parse::<f64>().ok().map(PackValue::Float)
+}
+
+/// Whether `s` is a number in JSON syntax, which the UBJSON spec requires
+/// of `H` digits: `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`.
+fn is_json_number(s: &str) -> bool {
+    fn digits(b: &[u8]) -> usize {
+        b.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let b = s.as_bytes();
+    let mut i = usize::from(b.first() == Some(&b'-'));
+    match digits(&b[i..]) {
+        0 => return false,
+        n if n > 1 && b[i] == b'0' => return false,
+        n => i += n,
+    }
+    if b.get(i) == Some(&b'.') {
+        match digits(&b[i + 1..]) {
+            0 => return false,
+            n => i += 1 + n,
+        }
+    }
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(b.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(&b[i..]) {
+            0 => return false,
+            n => i += n,
+        }
+    }
+    i == b.len()
+}
+
+/// `marker` and `pos` locate the length value, for error reporting.
+fn pack_value_to_usize(v: PackValue, marker: u8, pos: usize) -> Result<usize, UbjsonError> {
     match v {
         PackValue::Integer(i) => Ok(i as usize),
         PackValue::UInteger(u) => Ok(u as usize),
-        _ => Err(UbjsonError::UnexpectedByte(0, 0)),
+        _ => Err(UbjsonError::UnexpectedByte(marker, pos)),
     }
 }
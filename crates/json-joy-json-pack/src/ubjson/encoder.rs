@@ -14,17 +14,55 @@
 //! - `L` (0x4c) = int64 (8 bytes big-endian)
 //! - `d` (0x64) = float32 (4 bytes big-endian)
 //! - `D` (0x44) = float64 (8 bytes big-endian)
+//! - `h` (0x68) = float16 (2 bytes), BJData's half-precision marker (only
+//!   with [`UbjsonEncoderOptions::bjdata`] and
+//!   [`UbjsonEncoderOptions::float16`])
+//! - `H` (0x48) = high-precision number: length integer + decimal digits
+//!   (only with [`UbjsonEncoderOptions::high_precision`])
+//! - `C` (0x43) = ASCII char (only with [`UbjsonEncoderOptions::chars`])
 //! - `S` (0x53) = string: type byte + string-length integer + UTF-8 bytes
 //! - `[` (0x5b) = array start, `]` (0x5d) = array end
 //! - `{` (0x7b) = object start, `}` (0x7d) = object end
 //! - Binary shorthand: `[$U#<count>` then raw bytes
+//!
+//! With [`UbjsonEncoderOptions::bjdata`] the output is BJData: the same
+//! markers, with multi-byte numbers little-endian.
 
 use json_joy_buffers::Writer;
+#[cfg(feature = "f16")]
+use json_joy_buffers::{encode_f16_keep_nan, is_float16_keep_nan};
 
+use crate::checked::{self, EncodeLossError};
+use crate::pack_value::text_keyed;
 use crate::PackValue;
 
+/// Options controlling `UbjsonEncoder` output. All default to off, matching
+/// upstream.
+#[derive(Debug, Clone, Default)]
+pub struct UbjsonEncoderOptions {
+    /// When `true`, integers outside the `i64` range are written as `H`
+    /// high-precision numbers instead of being clamped to `i64`.
+    pub high_precision: bool,
+    /// When `true`, one-character ASCII strings are written as `C` chars.
+    pub chars: bool,
+    /// Writes an `N` no-op after every this many array items and object
+    /// members, as keep-alive padding. Readers must skip no-ops, see
+    /// [`UbjsonNoOpPolicy::Skip`](super::UbjsonNoOpPolicy::Skip).
+    pub no_op_every: Option<usize>,
+    /// When `true`, output is BJData (Binary JData): multi-byte integers,
+    /// floats and string lengths are little-endian. Read it back with
+    /// [`UbjsonDecoderOptions::bjdata`](super::UbjsonDecoderOptions::bjdata).
+    pub bjdata: bool,
+    /// When `true` together with `bjdata`, floats that survive a
+    /// half-precision round trip bit for bit, NaN payloads included, are
+    /// written as BJData `h`. Ignored for plain UBJSON, which has no `h`.
+    /// Needs the `f16` feature, see the [crate docs](crate#f16).
+    pub float16: bool,
+}
+
 pub struct UbjsonEncoder {
     pub writer: Writer,
+    pub options: UbjsonEncoderOptions,
 }
 
 impl Default for UbjsonEncoder {
@@ -37,6 +75,14 @@ impl UbjsonEncoder {
     pub fn new() -> Self {
         Self {
             writer: Writer::new(),
+            options: UbjsonEncoderOptions::default(),
+        }
+    }
+
+    pub fn with_options(options: UbjsonEncoderOptions) -> Self {
+        Self {
+            writer: Writer::new(),
+            options,
         }
     }
 
@@ -46,6 +92,19 @@ impl UbjsonEncoder {
         self.writer.flush()
     }
 
+    /// Like [`encode`](Self::encode), but fails on an integer outside `i64`
+    /// (unless [`high_precision`](UbjsonEncoderOptions::high_precision) is
+    /// on), an extension, a blob or a non-text key.
+    pub fn try_encode(&mut self, value: &PackValue) -> Result<Vec<u8>, EncodeLossError> {
+        let high_precision = self.options.high_precision;
+        checked::check(
+            value,
+            &|value| checked::ubjson_loss(value, high_precision),
+            checked::Keys::Text,
+        )?;
+        Ok(self.encode(value))
+    }
+
     pub fn encode_json(&mut self, value: &serde_json::Value) -> Vec<u8> {
         self.writer.reset();
         self.write_json(value);
@@ -66,16 +125,18 @@ impl UbjsonEncoder {
             serde_json::Value::String(s) => self.write_str(s),
             serde_json::Value::Array(arr) => {
                 self.writer.u8(0x5b); // '['
-                for item in arr {
+                for (i, item) in arr.iter().enumerate() {
                     self.write_json(item);
+                    self.item_written(i);
                 }
                 self.writer.u8(0x5d); // ']'
             }
             serde_json::Value::Object(obj) => {
                 self.writer.u8(0x7b); // '{'
-                for (key, val) in obj {
+                for (i, (key, val)) in obj.iter().enumerate() {
                     self.write_key(key);
                     self.write_json(val);
+                    self.item_written(i);
                 }
                 self.writer.u8(0x7d); // '}'
             }
@@ -91,10 +152,12 @@ impl UbjsonEncoder {
             PackValue::UInteger(u) => self.write_u_integer(*u),
             PackValue::Float(f) => self.write_float(*f),
             PackValue::BigInt(i) => self.write_big_int(*i),
+            PackValue::BigNum(n) => self.write_big_num(n),
             PackValue::Bytes(b) => self.write_bin(b),
             PackValue::Str(s) => self.write_str(s),
             PackValue::Array(arr) => self.write_arr(arr),
             PackValue::Object(obj) => self.write_obj(obj),
+            PackValue::Map(map) => self.write_obj(&text_keyed(map)),
             PackValue::Extension(_) | PackValue::Blob(_) => self.write_null(),
         }
     }
@@ -123,25 +186,28 @@ impl UbjsonEncoder {
             self.writer.u8(int as i8 as u8);
         } else if (-32768..=32767).contains(&int) {
             // int16
-            self.writer.ensure_capacity(3);
-            let x = self.writer.x;
-            self.writer.uint8[x] = 0x49; // 'I'
-            let b = (int as i16).to_be_bytes();
-            self.writer.uint8[x + 1] = b[0];
-            self.writer.uint8[x + 2] = b[1];
-            self.writer.x = x + 3;
+            self.writer.u8(0x49); // 'I'
+            if self.options.bjdata {
+                self.writer.i16_le(int as i16);
+            } else {
+                self.writer.i16(int as i16);
+            }
         } else if (-2147483648..=2147483647).contains(&int) {
             // int32
             self.writer.u8(0x6c); // 'l'
-            self.writer.i32(int as i32);
+            if self.options.bjdata {
+                self.writer.i32_le(int as i32);
+            } else {
+                self.writer.i32(int as i32);
+            }
         } else {
             // int64
             self.writer.u8(0x4c); // 'L'
-            self.writer.ensure_capacity(8);
-            let x = self.writer.x;
-            let b = int.to_be_bytes();
-            self.writer.uint8[x..x + 8].copy_from_slice(&b);
-            self.writer.x = x + 8;
+            if self.options.bjdata {
+                self.writer.i64_le(int);
+            } else {
+                self.writer.i64(int);
+            }
         }
     }
 
@@ -149,25 +215,61 @@ impl UbjsonEncoder {
         if uint <= 0xff {
             self.writer.u8(0x55); // 'U'
             self.writer.u8(uint as u8);
+        } else if uint > i64::MAX as u64 && self.options.high_precision {
+            self.write_high_precision(&uint.to_string());
         } else {
             self.write_integer(uint as i64);
         }
     }
 
     pub fn write_float(&mut self, float: f64) {
+        if self.options.bjdata {
+            self.write_bjdata_float(float);
+            return;
+        }
         self.writer.u8(0x44); // 'D'
         self.writer.f64(float);
     }
 
+    fn write_bjdata_float(&mut self, float: f64) {
+        #[cfg(feature = "f16")]
+        if self.options.float16 && is_float16_keep_nan(float) {
+            self.writer.u8(0x68); // 'h'
+            self.writer.u16_le(encode_f16_keep_nan(float));
+            return;
+        }
+        self.writer.u8(0x44); // 'D'
+        self.writer.f64_le(float);
+    }
+
     pub fn write_big_int(&mut self, int: i128) {
         if int >= i64::MIN as i128 && int <= i64::MAX as i128 {
             self.write_integer(int as i64);
+        } else if self.options.high_precision {
+            self.write_high_precision(&int.to_string());
         } else {
             // Clamp to i64 range for UBJSON (no native i128 support)
             self.write_integer(if int > 0 { i64::MAX } else { i64::MIN });
         }
     }
 
+    /// Big numbers have no upstream counterpart to stay compatible with, so
+    /// past `i64` they always take the high-precision form, whatever
+    /// [`UbjsonEncoderOptions::high_precision`] says.
+    pub fn write_big_num(&mut self, num: &crate::JsonPackBigInt) {
+        match num.to_i128().and_then(|int| i64::try_from(int).ok()) {
+            Some(int) => self.write_integer(int),
+            None => self.write_high_precision(&num.to_string()),
+        }
+    }
+
+    /// Write a decimal number string as `H` + UBJSON-encoded length + digits.
+    pub fn write_high_precision(&mut self, digits: &str) {
+        self.writer.u8(0x48); // 'H'
+        self.write_integer(digits.len() as i64);
+        self.writer.ascii(digits);
+    }
+
     /// Write binary data using the typed array shorthand `[$U#<count>`.
     pub fn write_bin(&mut self, buf: &[u8]) {
         let length = buf.len();
@@ -179,6 +281,11 @@ impl UbjsonEncoder {
     /// Write a UBJSON string: `S` + UBJSON-encoded length + UTF-8 bytes.
     /// Uses max-size-guess strategy to reserve length slot.
     pub fn write_str(&mut self, s: &str) {
+        if self.options.chars && s.len() == 1 && s.is_ascii() {
+            self.writer.u8(0x43); // 'C'
+            self.writer.u8(s.as_bytes()[0]);
+            return;
+        }
         let char_count = s.chars().count();
         let max_len = char_count * 4;
         self.writer.ensure_capacity(max_len + 1 + 5);
@@ -213,28 +320,45 @@ impl UbjsonEncoder {
         if one_byte {
             self.writer.uint8[x + 1] = actual_size as u8;
         } else {
-            let b = (actual_size as u32).to_be_bytes();
+            let size = actual_size as u32;
+            let b = if self.options.bjdata {
+                size.to_le_bytes()
+            } else {
+                size.to_be_bytes()
+            };
             self.writer.uint8[x + 1..x + 5].copy_from_slice(&b);
         }
     }
 
     pub fn write_arr(&mut self, arr: &[PackValue]) {
         self.writer.u8(0x5b); // '['
-        for item in arr {
+        for (i, item) in arr.iter().enumerate() {
             self.write_any(item);
+            self.item_written(i);
         }
         self.writer.u8(0x5d); // ']'
     }
 
     pub fn write_obj(&mut self, obj: &[(String, PackValue)]) {
         self.writer.u8(0x7b); // '{'
-        for (key, val) in obj {
+        for (i, (key, val)) in obj.iter().enumerate() {
             self.write_key(key);
             self.write_any(val);
+            self.item_written(i);
         }
         self.writer.u8(0x7d); // '}'
     }
 
+    /// Writes the no-op [`UbjsonEncoderOptions::no_op_every`] asks for
+    /// after container item `index`.
+    fn item_written(&mut self, index: usize) {
+        if let Some(every) = self.options.no_op_every {
+            if (index + 1).is_multiple_of(every) {
+                self.write_no_op();
+            }
+        }
+    }
+
     // ---- Streaming ----
 
     pub fn write_start_arr(&mut self) {
@@ -252,4 +376,10 @@ impl UbjsonEncoder {
     pub fn write_end_obj(&mut self) {
         self.writer.u8(0x7d);
     }
+
+    /// Writes an `N` no-op: a keep-alive between values of a stream, or
+    /// between items of an open container.
+    pub fn write_no_op(&mut self) {
+        self.writer.u8(0x4e); // 'N'
+    }
 }
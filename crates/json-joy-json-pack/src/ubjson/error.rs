@@ -6,10 +6,25 @@ use thiserror::Error;
 pub enum UbjsonError {
     #[error("unexpected byte 0x{0:02x} at position {1}")]
     UnexpectedByte(u8, usize),
-    #[error("unexpected end of input")]
-    UnexpectedEof,
-    #[error("invalid UTF-8 in string")]
-    InvalidUtf8,
+    #[error("unexpected end of input at position {0}")]
+    UnexpectedEof(usize),
+    #[error("invalid UTF-8 in string at position {0}")]
+    InvalidUtf8(usize),
+    #[error("invalid high-precision number at position {0}")]
+    InvalidNumber(usize),
     #[error("invalid key `__proto__`")]
     InvalidKey,
 }
+
+impl UbjsonError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedByte(_, x)
+            | Self::UnexpectedEof(x)
+            | Self::InvalidUtf8(x)
+            | Self::InvalidNumber(x) => Some(*x),
+            Self::InvalidKey => None,
+        }
+    }
+}
@@ -6,6 +6,8 @@ mod decoder;
 mod encoder;
 mod error;
 
-pub use decoder::UbjsonDecoder;
-pub use encoder::UbjsonEncoder;
+pub use decoder::{
+    UbjsonDecoder, UbjsonDecoderOptions, UbjsonHighPrecisionPolicy, UbjsonNoOpPolicy,
+};
+pub use encoder::{UbjsonEncoder, UbjsonEncoderOptions};
 pub use error::UbjsonError;
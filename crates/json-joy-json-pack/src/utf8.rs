@@ -0,0 +1,89 @@
+//! UTF-8 validation for decoder string paths.
+//!
+//! With the `simd` feature, runs of ASCII are skipped 16 bytes at a time
+//! (SSE2 on x86_64, a word-at-a-time check elsewhere) and only the remainder
+//! goes through `core::str::from_utf8`. Without it this is a thin wrapper.
+
+use core::str::Utf8Error;
+
+/// Validate `bytes` as UTF-8.
+#[inline]
+pub(crate) fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+    #[cfg(feature = "simd")]
+    {
+        let ascii = ascii_prefix_len(bytes);
+        if ascii == bytes.len() {
+            // SAFETY: every byte is ASCII, which is valid UTF-8.
+            return Ok(unsafe { core::str::from_utf8_unchecked(bytes) });
+        }
+        // The ASCII prefix cannot end inside a multi-byte sequence, so the
+        // input is valid iff the remainder is.
+        if core::str::from_utf8(&bytes[ascii..]).is_ok() {
+            // SAFETY: both the prefix and the remainder are valid UTF-8.
+            return Ok(unsafe { core::str::from_utf8_unchecked(bytes) });
+        }
+    }
+    core::str::from_utf8(bytes)
+}
+
+/// Length of the leading run of ASCII bytes.
+#[cfg(feature = "simd")]
+#[inline]
+pub(crate) fn ascii_prefix_len(bytes: &[u8]) -> usize {
+    let mut x = 0;
+    #[cfg(target_arch = "x86_64")]
+    {
+        use core::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_movemask_epi8};
+        while x + 16 <= bytes.len() {
+            // SAFETY: SSE2 is part of the x86_64 baseline and the load is
+            // unaligned and in bounds.
+            let mask = unsafe {
+                _mm_movemask_epi8(_mm_loadu_si128(bytes.as_ptr().add(x) as *const __m128i))
+            };
+            if mask != 0 {
+                return x + mask.trailing_zeros() as usize;
+            }
+            x += 16;
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        const HIGH: u64 = 0x8080_8080_8080_8080;
+        while x + 8 <= bytes.len() {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[x..x + 8]);
+            let high = u64::from_le_bytes(word) & HIGH;
+            if high != 0 {
+                return x + (high.trailing_zeros() / 8) as usize;
+            }
+            x += 8;
+        }
+    }
+    while x < bytes.len() && bytes[x] < 0x80 {
+        x += 1;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_utf8;
+
+    #[test]
+    fn from_utf8_matches_core() {
+        let long = "abcdefghijklmnopqrstuvwxyz".repeat(3);
+        let cases: [&[u8]; 8] = [
+            b"",
+            b"ascii only",
+            long.as_bytes(),
+            "0123456789abcdef\u{e9}t\u{e9} \u{1f600}".as_bytes(),
+            b"0123456789abcdef0123\xff",
+            b"0123456789abcdef\xc3",
+            b"\xe2\x82",
+            b"0123456789abcdef0123456789abcde\xe2\x82\xac",
+        ];
+        for bytes in cases {
+            assert_eq!(from_utf8(bytes), core::str::from_utf8(bytes));
+        }
+    }
+}
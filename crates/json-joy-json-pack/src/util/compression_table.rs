@@ -4,6 +4,7 @@
 
 use std::collections::{BTreeSet, HashMap, HashSet};
 
+use super::format_f64_js;
 use crate::{JsonPackExtension, PackValue};
 
 #[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
@@ -64,19 +65,7 @@ fn literal_sort_key(value: &PackValue) -> Result<String, CompressionError> {
         PackValue::Bool(false) => Ok("false".to_owned()),
         PackValue::Bool(true) => Ok("true".to_owned()),
         PackValue::Str(s) => Ok(s.clone()),
-        PackValue::Float(f) => Ok(if f.is_nan() {
-            "NaN".to_owned()
-        } else if f.is_infinite() {
-            if *f > 0.0 {
-                "Infinity".to_owned()
-            } else {
-                "-Infinity".to_owned()
-            }
-        } else if *f == 0.0 {
-            "0".to_owned()
-        } else {
-            format!("{f}")
-        }),
+        PackValue::Float(f) => Ok(format_f64_js(*f)),
         _ => Err(CompressionError::UnsupportedValueType),
     }
 }
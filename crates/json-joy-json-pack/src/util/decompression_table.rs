@@ -2,6 +2,7 @@
 //!
 //! Upstream reference: `json-pack/src/util/DecompressionTable.ts`
 
+use super::format_f64_js;
 use crate::{JsonPackExtension, PackValue};
 
 #[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
@@ -65,26 +66,13 @@ impl DecompressionTable {
             PackValue::Bool(true) => "true".to_owned(),
             PackValue::Integer(i) => i.to_string(),
             PackValue::UInteger(u) => u.to_string(),
-            PackValue::Float(f) => {
-                if f.is_nan() {
-                    "NaN".to_owned()
-                } else if f.is_infinite() {
-                    if *f > 0.0 {
-                        "Infinity".to_owned()
-                    } else {
-                        "-Infinity".to_owned()
-                    }
-                } else if *f == 0.0 {
-                    "0".to_owned()
-                } else {
-                    format!("{f}")
-                }
-            }
+            PackValue::Float(f) => format_f64_js(*f),
             PackValue::BigInt(i) => i.to_string(),
+            PackValue::BigNum(n) => n.to_string(),
             PackValue::Str(s) => s.clone(),
             PackValue::Bytes(b) => format!("{:?}", b),
             PackValue::Array(_) => "[array]".to_owned(),
-            PackValue::Object(_) => "[object]".to_owned(),
+            PackValue::Object(_) | PackValue::Map(_) => "[object]".to_owned(),
             PackValue::Extension(_) => "[extension]".to_owned(),
             PackValue::Blob(_) => "[blob]".to_owned(),
         }
@@ -0,0 +1,69 @@
+//! JavaScript-compatible float formatting.
+//!
+//! Not an upstream port: upstream gets this from the JavaScript runtime.
+//! Rust's `Display` for `f64` never uses exponent form, so `1e21` would
+//! print as 22 digits and `1e-7` as `0.0000001`, breaking byte equality
+//! with the TypeScript encoders.
+
+use alloc::{borrow::ToOwned, format, string::String, string::ToString};
+
+/// Formats `value` as JavaScript's `Number.prototype.toString` does.
+///
+/// The digits are the shortest that round-trip (from Ryu). Decimal
+/// exponents from -6 to 20 are written in plain notation, others as
+/// `d.ddde+x` / `d.ddde-x`. `-0` is written as `0`, non-finite values as
+/// `NaN`, `Infinity` and `-Infinity`.
+pub fn format_f64_js(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_owned();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned();
+    }
+    if value == 0.0 {
+        return "0".to_owned();
+    }
+    let mut buffer = ryu::Buffer::new();
+    let repr = buffer.format_finite(value.abs());
+    // Ryu writes `123.45`, `1.0` or `1.5e-7`: split it into significant
+    // digits and the position of the decimal point relative to them. Ryu
+    // always writes a valid exponent, so the fallback is never taken.
+    let (mantissa, exp) = match repr.split_once('e') {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i32>().unwrap_or(0)),
+        None => (repr, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all = format!("{int}{frac}");
+    let trimmed = all.trim_start_matches('0');
+    let mut point = int.len() as i32 + exp - (all.len() - trimmed.len()) as i32;
+    let digits = trimmed.trim_end_matches('0');
+    let k = digits.len() as i32;
+
+    let mut out = String::with_capacity(digits.len() + 8);
+    if value < 0.0 {
+        out.push('-');
+    }
+    if k <= point && point <= 21 {
+        out.push_str(digits);
+        out.extend(core::iter::repeat_n('0', (point - k) as usize));
+    } else if 0 < point && point <= 21 {
+        out.push_str(&digits[..point as usize]);
+        out.push('.');
+        out.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.extend(core::iter::repeat_n('0', -point as usize));
+        out.push_str(digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        point -= 1;
+        out.push('e');
+        out.push(if point < 0 { '-' } else { '+' });
+        out.push_str(&point.unsigned_abs().to_string());
+    }
+    out
+}
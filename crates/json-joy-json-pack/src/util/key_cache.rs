@@ -0,0 +1,143 @@
+//! Interning cache for decoded object keys.
+//!
+//! Not an upstream port: upstream relies on the JavaScript engine to
+//! deduplicate short strings.
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec, vec::Vec};
+use core::ops::Deref;
+
+/// Hit and miss counters of a [`KeyCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyCacheStats {
+    /// Keys found in the cache and not decoded again.
+    pub hits: u64,
+    /// Keys decoded and stored.
+    pub misses: u64,
+    /// Stored keys replaced by a miss hashing to the same slot.
+    pub evictions: u64,
+    /// Keys longer than [`KeyCache::max_len`], decoded without caching.
+    pub bypassed: u64,
+}
+
+/// A key read through a [`KeyCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachedKey {
+    /// Found in the cache and shared with it.
+    Interned(Arc<str>),
+    /// Decoded from the input; the cache keeps a copy if it is short
+    /// enough.
+    Decoded(String),
+}
+
+impl Deref for CachedKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            CachedKey::Interned(key) => key,
+            CachedKey::Decoded(key) => key,
+        }
+    }
+}
+
+impl From<CachedKey> for String {
+    fn from(key: CachedKey) -> Self {
+        match key {
+            CachedKey::Interned(key) => String::from(&*key),
+            CachedKey::Decoded(key) => key,
+        }
+    }
+}
+
+/// Encoded bytes of a cached key and the decoded key.
+type Slot = (Box<[u8]>, Arc<str>);
+
+/// Direct-mapped cache from the encoded bytes of an object key to the
+/// decoded key.
+///
+/// Decoders given a cache (`with_key_cache`) look up every key of at most
+/// [`max_len`](Self::max_len) encoded bytes before decoding it, so a
+/// document repeating the same keys validates and unescapes each key once.
+/// A miss decodes the key straight into the output and stores a copy. On a
+/// hit, token sinks borrow the stored key without allocating, while
+/// [`PackValue`] objects, which own their keys, get one copy of it.
+/// A key hashing to an occupied slot replaces the key stored there.
+///
+/// [`PackValue`]: crate::PackValue
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    max_len: usize,
+    slots: Vec<Option<Slot>>,
+    stats: KeyCacheStats,
+}
+
+impl KeyCache {
+    /// Slot count of [`new`](Self::new).
+    pub const DEFAULT_SLOTS: usize = 256;
+
+    /// A cache of [`DEFAULT_SLOTS`](Self::DEFAULT_SLOTS) slots for keys of
+    /// at most `max_len` encoded bytes.
+    pub fn new(max_len: usize) -> Self {
+        Self::with_slots(max_len, Self::DEFAULT_SLOTS)
+    }
+
+    /// A cache of `slots` slots, rounded up to a power of two, for keys of
+    /// at most `max_len` encoded bytes.
+    pub fn with_slots(max_len: usize, slots: usize) -> Self {
+        Self {
+            max_len,
+            slots: vec![None; slots.max(1).next_power_of_two()],
+            stats: KeyCacheStats::default(),
+        }
+    }
+
+    /// Longest key, in encoded bytes, the cache stores.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Counters since creation or the last [`clear`](Self::clear).
+    pub fn stats(&self) -> KeyCacheStats {
+        self.stats
+    }
+
+    /// Drops the stored keys and zeroes the counters.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.stats = KeyCacheStats::default();
+    }
+
+    /// Returns the key encoded as `raw`, calling `decode` only if it is not
+    /// cached.
+    pub fn get_or_decode<E>(
+        &mut self,
+        raw: &[u8],
+        decode: impl FnOnce(&[u8]) -> Result<String, E>,
+    ) -> Result<CachedKey, E> {
+        if raw.len() > self.max_len {
+            self.stats.bypassed += 1;
+            return decode(raw).map(CachedKey::Decoded);
+        }
+        let index = fnv1a(raw) as usize & (self.slots.len() - 1);
+        let slot = &mut self.slots[index];
+        if let Some((bytes, key)) = slot {
+            if **bytes == *raw {
+                self.stats.hits += 1;
+                return Ok(CachedKey::Interned(key.clone()));
+            }
+        }
+        let key = decode(raw)?;
+        if slot.is_some() {
+            self.stats.evictions += 1;
+        }
+        self.stats.misses += 1;
+        *slot = Some((raw.into(), Arc::from(key.as_str())));
+        Ok(CachedKey::Decoded(key))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &b| {
+        (hash ^ b as u32).wrapping_mul(0x0100_0193)
+    })
+}
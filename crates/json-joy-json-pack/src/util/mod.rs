@@ -1,8 +1,19 @@
 //! Utility helpers mirrored from upstream `json-pack/src/util/`.
 
+#[cfg(feature = "std")]
 pub mod buffers;
+#[cfg(feature = "std")]
 mod compression_table;
+#[cfg(feature = "std")]
 mod decompression_table;
+mod float;
+mod key_cache;
+#[cfg(feature = "std")]
+pub mod varint;
 
+#[cfg(feature = "std")]
 pub use compression_table::{CompressionError, CompressionTable};
+#[cfg(feature = "std")]
 pub use decompression_table::{DecompressionError, DecompressionTable};
+pub use float::format_f64_js;
+pub use key_cache::{CachedKey, KeyCache, KeyCacheStats};
@@ -0,0 +1,67 @@
+//! LEB128 varints and zigzag encoding shared by the Avro and Protobuf codecs.
+//!
+//! Not an upstream port: upstream inlines these in each codec.
+
+use json_joy_buffers::Writer;
+
+/// Failure reading a varint with [`read_varint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarintError {
+    /// Input ended before the final byte (high bit clear).
+    UnexpectedEof,
+    /// More than the allowed number of bytes had the continuation bit set.
+    TooLong,
+}
+
+/// Zigzag-encodes a signed 32-bit integer: 0, -1, 1, -2, … → 0, 1, 2, 3, …
+#[inline]
+pub fn zigzag_encode32(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+#[inline]
+pub fn zigzag_decode32(n: u32) -> i32 {
+    ((n >> 1) as i32) ^ -((n & 1) as i32)
+}
+
+/// Zigzag-encodes a signed 64-bit integer: 0, -1, 1, -2, … → 0, 1, 2, 3, …
+#[inline]
+pub fn zigzag_encode64(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+#[inline]
+pub fn zigzag_decode64(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+/// Writes `n` as an unsigned LEB128 varint (1–10 bytes).
+pub fn write_varint(writer: &mut Writer, mut n: u64) {
+    loop {
+        let low7 = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            writer.u8(low7);
+            return;
+        }
+        writer.u8(low7 | 0x80);
+    }
+}
+
+/// Reads an unsigned LEB128 varint of at most `max_bytes` bytes starting at
+/// `data[pos]`. Returns the value and the number of bytes consumed; bits
+/// beyond 64 are discarded.
+pub fn read_varint(data: &[u8], pos: usize, max_bytes: usize) -> Result<(u64, usize), VarintError> {
+    let mut result: u64 = 0;
+    for i in 0..max_bytes {
+        let b = *data.get(pos + i).ok_or(VarintError::UnexpectedEof)? as u64;
+        let shift = 7 * i as u32;
+        if shift < 64 {
+            result |= (b & 0x7f) << shift;
+        }
+        if b & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+    }
+    Err(VarintError::TooLong)
+}
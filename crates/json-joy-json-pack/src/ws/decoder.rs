@@ -8,10 +8,63 @@ use super::constants::WsFrameOpcode;
 use super::frames::{WsCloseFrame, WsFrame, WsFrameHeader, WsPingFrame, WsPongFrame};
 
 /// Error type for WebSocket frame decoding failures.
+///
+/// Each of these fails the connection (RFC 6455 §7.1.7): the decoder is
+/// left mid-frame and cannot be read further. The close code to send is
+/// 1002, or 1007 for [`InvalidCloseReason`](Self::InvalidCloseReason).
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum WsFrameDecodingError {
+    /// Control frame with an unknown opcode (11-15), read without
+    /// [`WsFrameDecoder::with_strict`].
     #[error("invalid WebSocket frame")]
     InvalidFrame,
+    /// RSV bits set that no negotiated extension defines, see
+    /// [`WsFrameDecoder::with_rsv`]. Only checked in strict mode.
+    #[error("reserved bits {0:#05b} set without a negotiated extension")]
+    ReservedBits(u8),
+    /// Opcode 3-7 or 11-15. Only reported in strict mode.
+    #[error("reserved opcode {0}")]
+    ReservedOpcode(u8),
+    /// Ping, Pong or Close frame with the FIN bit clear. Only checked in
+    /// strict mode.
+    #[error("fragmented control frame")]
+    FragmentedControlFrame,
+    /// Control frame payload longer than 125 bytes.
+    #[error("control frame payload of {0} bytes exceeds 125")]
+    ControlFrameTooLong(usize),
+    /// Payload length in a longer form than needed: the 16-bit form for
+    /// less than 126 bytes or the 64-bit form for less than 65536. Only
+    /// checked in strict mode.
+    #[error("payload length {0} is not minimally encoded")]
+    NonMinimalLength(u64),
+    /// 64-bit payload length with the most significant bit set, or too
+    /// large for `usize`.
+    #[error("payload length {0} is too large")]
+    LengthTooLarge(u64),
+    /// Masked frame read from a server.
+    #[error("masked frame from server")]
+    MaskedFrame,
+    /// Unmasked frame read from a client.
+    #[error("unmasked frame from client")]
+    UnmaskedFrame,
+    /// Close frame with a 1-byte payload, too short for a status code.
+    #[error("close frame payload of 1 byte")]
+    TruncatedCloseCode,
+    /// Close code that must not appear on the wire.
+    #[error("invalid close code {0}")]
+    InvalidCloseCode(u16),
+    /// Close reason that is not valid UTF-8.
+    #[error("close reason is not valid UTF-8")]
+    InvalidCloseReason,
+}
+
+/// The endpoint a [`WsFrameDecoder`] reads frames from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsPeer {
+    /// Frames sent by a client, which must be masked.
+    Client,
+    /// Frames sent by a server, which must not be masked.
+    Server,
 }
 
 /// Streaming WebSocket frame decoder.
@@ -19,8 +72,30 @@ pub enum WsFrameDecodingError {
 /// Feed bytes via [`push`] and call [`read_frame_header`] to receive parsed
 /// frame headers. Data frames are returned as [`WsFrame::Data`]; control
 /// frames (Ping/Pong/Close) include their payloads.
+///
+/// By default frames are read as upstream reads them: RSV bits are
+/// ignored, opcodes 3-7 read as data frames, and fragmented control frames
+/// and over-long length encodings are accepted. [`with_strict`](Self::with_strict)
+/// rejects all of these as RFC 6455 requires. Control frame limits are
+/// always checked. Endpoint rules (masking direction, close codes, UTF-8
+/// close reasons) are checked only once the peer is known, see
+/// [`with_peer`](Self::with_peer); without it the decoder reads traffic in
+/// either direction, as a proxy would.
 pub struct WsFrameDecoder {
     pub reader: StreamingOctetReader,
+    /// Whether the RFC 6455 framing rules upstream skips are checked.
+    strict: bool,
+    /// RSV bits (`0b100` is RSV1) negotiated extensions may set.
+    rsv: u8,
+    /// Endpoint the frames come from, if known.
+    peer: Option<WsPeer>,
+    /// Masking key of the last data frame read, for
+    /// [`read_payload_into`](Self::read_payload_into).
+    payload_mask: Option<[u8; 4]>,
+    /// Payload bytes of the last data frame already returned.
+    payload_read: usize,
+    /// Payload bytes of the last data frame not yet returned.
+    payload_remaining: usize,
 }
 
 impl Default for WsFrameDecoder {
@@ -33,9 +108,38 @@ impl WsFrameDecoder {
     pub fn new() -> Self {
         Self {
             reader: StreamingOctetReader::new(),
+            strict: false,
+            rsv: 0,
+            peer: None,
+            payload_mask: None,
+            payload_read: 0,
+            payload_remaining: 0,
         }
     }
 
+    /// Rejects frames with RSV bits no extension negotiated, reserved
+    /// opcodes, fragmented control frames or over-long length encodings.
+    ///
+    /// Not an upstream port: upstream accepts all of these.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Allows the RSV bits in `bits` (`0b100` is RSV1, `0b001` RSV3) in
+    /// strict mode, for extensions such as `permessage-deflate` that use
+    /// them.
+    pub fn with_rsv(mut self, bits: u8) -> Self {
+        self.rsv = bits & 0b111;
+        self
+    }
+
+    /// Checks frames against the endpoint rules for frames sent by `peer`.
+    pub fn with_peer(mut self, peer: WsPeer) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
     /// Pushes a chunk of bytes into the internal buffer.
     pub fn push(&mut self, data: Vec<u8>) {
         self.reader.push(data);
@@ -51,26 +155,53 @@ impl WsFrameDecoder {
     ///
     /// For control frames (`Ping`/`Pong`/`Close`) the payload is read
     /// immediately and included in the returned frame.
+    ///
+    /// A frame cut off anywhere before its payload (or, for control frames,
+    /// inside it) is left unread, so the call can be repeated as data
+    /// arrives.
     pub fn read_frame_header(&mut self) -> Result<Option<WsFrame>, WsFrameDecodingError> {
-        if self.reader.size() < 2 {
-            return Ok(None);
-        }
-        // Attempt to parse; catch under-flow (size check returns Ok(None)).
-        match self.try_read_frame_header() {
-            Ok(v) => Ok(v),
-            Err(WsFrameDecodingError::InvalidFrame) => Err(WsFrameDecodingError::InvalidFrame),
+        self.reader.mark();
+        let result = self.frame_header();
+        if let Ok(None) = result {
+            self.reader.reset_to_mark();
         }
+        self.reader.consume();
+        result
     }
 
-    fn try_read_frame_header(&mut self) -> Result<Option<WsFrame>, WsFrameDecodingError> {
+    fn frame_header(&mut self) -> Result<Option<WsFrame>, WsFrameDecodingError> {
+        if self.reader.size() < 2 {
+            return Ok(None);
+        }
         let b0 = self.reader.u8();
         let b1 = self.reader.u8();
 
         let fin = (b0 >> 7) != 0;
+        let rsv = (b0 >> 4) & 0b111;
         let opcode = b0 & 0x0f;
         let mask_bit = b1 >> 7;
         let mut length = (b1 & 0x7f) as usize;
 
+        let is_control = opcode >= WsFrameOpcode::MIN_CONTROL_OPCODE;
+        if self.strict {
+            if rsv & !self.rsv != 0 {
+                return Err(WsFrameDecodingError::ReservedBits(rsv & !self.rsv));
+            }
+            if WsFrameOpcode::try_from(opcode).is_err() {
+                return Err(WsFrameDecodingError::ReservedOpcode(opcode));
+            }
+            if is_control && !fin {
+                return Err(WsFrameDecodingError::FragmentedControlFrame);
+            }
+        } else if is_control && opcode > WsFrameOpcode::Pong as u8 {
+            return Err(WsFrameDecodingError::InvalidFrame);
+        }
+        match (self.peer, mask_bit != 0) {
+            (Some(WsPeer::Client), false) => return Err(WsFrameDecodingError::UnmaskedFrame),
+            (Some(WsPeer::Server), true) => return Err(WsFrameDecodingError::MaskedFrame),
+            _ => {}
+        }
+
         if length == 126 {
             if self.reader.size() < 2 {
                 return Ok(None);
@@ -78,13 +209,27 @@ impl WsFrameDecoder {
             let hi = self.reader.u8() as usize;
             let lo = self.reader.u8() as usize;
             length = (hi << 8) | lo;
+            if self.strict && length < 126 {
+                return Err(WsFrameDecodingError::NonMinimalLength(length as u64));
+            }
         } else if length == 127 {
             if self.reader.size() < 8 {
                 return Ok(None);
             }
-            // Skip upper 4 bytes (always 0 for reasonable frame sizes)
-            self.reader.skip(4);
-            length = self.reader.u32() as usize;
+            let hi = self.reader.u32() as u64;
+            let lo = self.reader.u32() as u64;
+            let length64 = (hi << 32) | lo;
+            if length64 >> 63 != 0 {
+                return Err(WsFrameDecodingError::LengthTooLarge(length64));
+            }
+            if self.strict && length64 < 0x1_0000 {
+                return Err(WsFrameDecodingError::NonMinimalLength(length64));
+            }
+            length = usize::try_from(length64)
+                .map_err(|_| WsFrameDecodingError::LengthTooLarge(length64))?;
+        }
+        if is_control && length > 125 {
+            return Err(WsFrameDecodingError::ControlFrameTooLong(length));
         }
 
         let mask: Option<[u8; 4]> = if mask_bit != 0 {
@@ -103,7 +248,7 @@ impl WsFrameDecoder {
 
         let header = WsFrameHeader::new(fin, opcode, length, mask);
 
-        if opcode >= WsFrameOpcode::MIN_CONTROL_OPCODE {
+        if is_control {
             match opcode {
                 8 /* CLOSE */ => {
                     return Ok(Some(WsFrame::Close(WsCloseFrame {
@@ -113,9 +258,6 @@ impl WsFrameDecoder {
                     })));
                 }
                 9 /* PING */ => {
-                    if length > 125 {
-                        return Err(WsFrameDecodingError::InvalidFrame);
-                    }
                     if self.reader.size() < length {
                         return Ok(None);
                     }
@@ -125,10 +267,7 @@ impl WsFrameDecoder {
                     };
                     return Ok(Some(WsFrame::Ping(WsPingFrame { header, data })));
                 }
-                10 /* PONG */ => {
-                    if length > 125 {
-                        return Err(WsFrameDecodingError::InvalidFrame);
-                    }
+                _ /* PONG */ => {
                     if self.reader.size() < length {
                         return Ok(None);
                     }
@@ -138,13 +277,44 @@ impl WsFrameDecoder {
                     };
                     return Ok(Some(WsFrame::Pong(WsPongFrame { header, data })));
                 }
-                _ => return Err(WsFrameDecodingError::InvalidFrame),
             }
         }
 
+        self.payload_mask = mask;
+        self.payload_read = 0;
+        self.payload_remaining = length;
         Ok(Some(WsFrame::Data(header)))
     }
 
+    /// Reads the next part of the last data frame's payload into `dst`,
+    /// unmasked, and returns the number of bytes written.
+    ///
+    /// Reads at most `dst.len()` bytes, and only what is buffered, so a
+    /// large frame can be forwarded chunk by chunk as data arrives. Returns 0
+    /// once the payload is exhausted; see
+    /// [`payload_remaining`](Self::payload_remaining).
+    pub fn read_payload_into(&mut self, dst: &mut [u8]) -> usize {
+        let size = dst
+            .len()
+            .min(self.reader.size())
+            .min(self.payload_remaining);
+        self.reader.copy_to(size, dst, 0);
+        if let Some(mask) = self.payload_mask {
+            for (i, b) in dst[..size].iter_mut().enumerate() {
+                *b ^= mask[(self.payload_read + i) % 4];
+            }
+        }
+        self.payload_read += size;
+        self.payload_remaining -= size;
+        size
+    }
+
+    /// Payload bytes of the last data frame not yet read by
+    /// [`read_payload_into`](Self::read_payload_into).
+    pub fn payload_remaining(&self) -> usize {
+        self.payload_remaining
+    }
+
     /// Reads up to `remaining` bytes of data frame payload into `dst[pos..]`.
     ///
     /// Returns the number of bytes still remaining to be read.
@@ -177,36 +347,52 @@ impl WsFrameDecoder {
 
     /// Reads and populates the payload of a Close frame.
     ///
-    /// Updates `frame.code` and `frame.reason` in place.
+    /// Updates `frame.code` and `frame.reason` in place. With a peer set
+    /// (see [`with_peer`](Self::with_peer)) the code must be one an endpoint
+    /// may send and the reason must be valid UTF-8; otherwise an invalid
+    /// reason reads as empty.
     pub fn read_close_frame_data(
         &mut self,
         frame: &mut WsCloseFrame,
     ) -> Result<(), WsFrameDecodingError> {
         let length = frame.header.length;
         if length > 125 {
-            return Err(WsFrameDecodingError::InvalidFrame);
+            return Err(WsFrameDecodingError::ControlFrameTooLong(length));
         }
         if length == 0 {
             return Ok(());
         }
         if length < 2 {
-            return Err(WsFrameDecodingError::InvalidFrame);
+            return Err(WsFrameDecodingError::TruncatedCloseCode);
         }
         let mask = frame.header.mask;
         let b0 = self.reader.u8() ^ mask.map(|m| m[0]).unwrap_or(0);
         let b1 = self.reader.u8() ^ mask.map(|m| m[1]).unwrap_or(0);
         let code = ((b0 as u16) << 8) | b1 as u16;
+        if self.peer.is_some() && !is_valid_close_code(code) {
+            return Err(WsFrameDecodingError::InvalidCloseCode(code));
+        }
         frame.code = code;
         let reason_len = length - 2;
         if reason_len > 0 {
-            frame.reason = match mask {
-                Some(m) => self.reader.utf8_masked(reason_len, m, 2),
-                None => {
-                    let bytes = self.reader.buf(reason_len);
-                    String::from_utf8(bytes).unwrap_or_default()
+            let bytes = match mask {
+                Some(m) => self.reader.buf_xor(reason_len, m, 2),
+                None => self.reader.buf(reason_len),
+            };
+            frame.reason = match String::from_utf8(bytes) {
+                Ok(reason) => reason,
+                Err(_) if self.peer.is_some() => {
+                    return Err(WsFrameDecodingError::InvalidCloseReason)
                 }
+                Err(_) => String::new(),
             };
         }
         Ok(())
     }
 }
+
+/// Whether an endpoint may send close `code` (RFC 6455 §7.4 and the IANA
+/// registry): 1000-1003, 1007-1014 and the 3000-4999 application range.
+fn is_valid_close_code(code: u16) -> bool {
+    matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999)
+}
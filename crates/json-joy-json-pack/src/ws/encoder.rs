@@ -2,10 +2,68 @@
 //!
 //! Upstream reference: `json-pack/src/ws/WsFrameEncoder.ts`
 
+use std::ops::Deref;
+
 use json_joy_buffers::Writer;
 
 use super::constants::WsFrameOpcode;
 
+/// A frame header built on the stack, to go out next to a borrowed payload
+/// in a vectored write (`write_vectored`, `writev`).
+///
+/// Same layout as [`WsFrameEncoder::write_hdr`]: 2 to 14 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WsFrameHeaderBytes {
+    buf: [u8; 14],
+    len: u8,
+}
+
+impl WsFrameHeaderBytes {
+    pub fn new(fin: bool, opcode: WsFrameOpcode, length: usize, mask: u32) -> Self {
+        let mut buf = [0u8; 14];
+        buf[0] = ((fin as u8) << 7) | (opcode as u8);
+        let mask_bit: u8 = if mask != 0 { 0x80 } else { 0x00 };
+        let mut len = if length < 126 {
+            buf[1] = mask_bit | length as u8;
+            2
+        } else if length < 0x1_0000 {
+            buf[1] = mask_bit | 126;
+            buf[2..4].copy_from_slice(&(length as u16).to_be_bytes());
+            4
+        } else {
+            buf[1] = mask_bit | 127;
+            buf[2..10].copy_from_slice(&(length as u64).to_be_bytes());
+            10
+        };
+        if mask != 0 {
+            buf[len..len + 4].copy_from_slice(&mask.to_be_bytes());
+            len += 4;
+        }
+        Self {
+            buf,
+            len: len as u8,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len as usize]
+    }
+}
+
+impl Deref for WsFrameHeaderBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for WsFrameHeaderBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
 /// WebSocket frame encoder.
 ///
 /// Writes RFC 6455 frame headers and payloads into an internal [`Writer`].
@@ -64,6 +122,54 @@ impl WsFrameEncoder {
         self.writer.flush()
     }
 
+    /// Header and payload of an unmasked frame, for sending `payload` with
+    /// a vectored write instead of copying it behind the header.
+    pub fn frame_parts(
+        fin: bool,
+        opcode: WsFrameOpcode,
+        payload: &[u8],
+    ) -> (WsFrameHeaderBytes, &[u8]) {
+        (
+            WsFrameHeaderBytes::new(fin, opcode, payload.len(), 0),
+            payload,
+        )
+    }
+
+    /// [`frame_parts`](Self::frame_parts) for a fast (fin=1, opcode=BINARY)
+    /// data message.
+    pub fn data_msg_parts(payload: &[u8]) -> (WsFrameHeaderBytes, &[u8]) {
+        Self::frame_parts(true, WsFrameOpcode::Binary, payload)
+    }
+
+    /// Appends a whole frame to `out`. The payload is copied once, straight
+    /// into `out`, and masked on the way when `mask != 0`.
+    pub fn write_frame_into(
+        &mut self,
+        out: &mut Writer,
+        fin: bool,
+        opcode: WsFrameOpcode,
+        payload: &[u8],
+        mask: u32,
+    ) {
+        std::mem::swap(&mut self.writer, out);
+        self.write_hdr(fin, opcode, payload.len(), mask);
+        if mask != 0 {
+            self.write_buf_xor(payload, mask);
+        } else {
+            self.writer.buf(payload);
+        }
+        std::mem::swap(&mut self.writer, out);
+    }
+
+    /// [`write_frame_into`](Self::write_frame_into) for a fast (fin=1,
+    /// opcode=BINARY, no mask) data message.
+    pub fn write_data_msg_into(&mut self, out: &mut Writer, payload: &[u8]) {
+        std::mem::swap(&mut self.writer, out);
+        self.write_data_msg_hdr_fast(payload.len());
+        self.writer.buf(payload);
+        std::mem::swap(&mut self.writer, out);
+    }
+
     /// Writes a Ping frame into the internal writer.
     pub fn write_ping(&mut self, data: Option<&[u8]>) {
         match data {
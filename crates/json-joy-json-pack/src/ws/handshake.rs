@@ -0,0 +1,249 @@
+//! WebSocket opening handshake helpers (RFC 6455 §4).
+//!
+//! Rust-only extension: upstream only ships the frame codec. These helpers
+//! cover the header work needed to bring a connection up: the
+//! `Sec-WebSocket-Accept` key, `Sec-WebSocket-Extensions` negotiation and
+//! `Sec-WebSocket-Protocol` selection.
+
+use json_joy_base64::{from_base64, to_base64};
+
+use crate::hash::sha1;
+
+/// GUID appended to the client key when computing `Sec-WebSocket-Accept`.
+pub const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Error type for malformed handshake headers.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WsHandshakeError {
+    #[error("invalid Sec-WebSocket-Extensions element {0:?}")]
+    InvalidExtension(String),
+    #[error("invalid Sec-WebSocket-Protocol token {0:?}")]
+    InvalidProtocol(String),
+}
+
+/// Computes the `Sec-WebSocket-Accept` value for a `Sec-WebSocket-Key`.
+///
+/// ```
+/// use json_joy_json_pack::ws::handshake::accept_key;
+///
+/// assert_eq!(
+///     accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+///     "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+/// );
+/// ```
+pub fn accept_key(client_key: &str) -> String {
+    let mut input = client_key.trim().as_bytes().to_vec();
+    input.extend_from_slice(WS_GUID.as_bytes());
+    to_base64(&sha1(&input))
+}
+
+/// Whether `key` is a valid `Sec-WebSocket-Key`: base64 of 16 bytes.
+pub fn is_valid_client_key(key: &str) -> bool {
+    let key = key.trim();
+    key.len() == 24 && from_base64(key).is_ok_and(|nonce| nonce.len() == 16)
+}
+
+/// Builds the `Sec-WebSocket-Key` for a 16-byte random nonce.
+pub fn client_key(nonce: &[u8; 16]) -> String {
+    to_base64(nonce)
+}
+
+/// One entry of a `Sec-WebSocket-Extensions` header, e.g.
+/// `permessage-deflate; client_max_window_bits=10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsExtension {
+    pub name: String,
+    /// Parameters in header order; `None` for a parameter without a value.
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl WsExtension {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Adds a parameter; `None` writes it without a value.
+    pub fn with_param(mut self, name: impl Into<String>, value: Option<&str>) -> Self {
+        self.params.push((name.into(), value.map(str::to_string)));
+        self
+    }
+
+    /// Looks up a parameter: `None` when absent, `Some(None)` when present
+    /// without a value.
+    pub fn param(&self, name: &str) -> Option<Option<&str>> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_deref())
+    }
+}
+
+/// Parses a `Sec-WebSocket-Extensions` header value. Quoted parameter
+/// values are unquoted; empty list elements are skipped.
+pub fn parse_extensions(header: &str) -> Result<Vec<WsExtension>, WsHandshakeError> {
+    let mut extensions = Vec::new();
+    for element in split_unquoted(header, ',') {
+        let element = element.trim();
+        if element.is_empty() {
+            continue;
+        }
+        let invalid = || WsHandshakeError::InvalidExtension(element.to_string());
+        let mut parts = split_unquoted(element, ';').into_iter();
+        let name = parts.next().unwrap_or_default().trim();
+        if !is_token(name) {
+            return Err(invalid());
+        }
+        let mut extension = WsExtension::new(name);
+        for param in parts {
+            let (key, value) = match param.split_once('=') {
+                Some((key, value)) => {
+                    (key.trim(), Some(unquote(value.trim()).ok_or_else(invalid)?))
+                }
+                None => (param.trim(), None),
+            };
+            if !is_token(key) || value.as_deref().is_some_and(|v| !is_token(v)) {
+                return Err(invalid());
+            }
+            extension.params.push((key.to_string(), value));
+        }
+        extensions.push(extension);
+    }
+    Ok(extensions)
+}
+
+/// Formats extensions as a `Sec-WebSocket-Extensions` header value.
+pub fn format_extensions(extensions: &[WsExtension]) -> String {
+    let mut out = String::new();
+    for (i, extension) in extensions.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&extension.name);
+        for (key, value) in &extension.params {
+            out.push_str("; ");
+            out.push_str(key);
+            if let Some(value) = value {
+                out.push('=');
+                if is_token(value) {
+                    out.push_str(value);
+                } else {
+                    out.push('"');
+                    for c in value.chars() {
+                        if c == '"' || c == '\\' {
+                            out.push('\\');
+                        }
+                        out.push(c);
+                    }
+                    out.push('"');
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Parses a `Sec-WebSocket-Protocol` header value into its subprotocols.
+pub fn parse_protocols(header: &str) -> Result<Vec<String>, WsHandshakeError> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            if is_token(p) {
+                Ok(p.to_string())
+            } else {
+                Err(WsHandshakeError::InvalidProtocol(p.to_string()))
+            }
+        })
+        .collect()
+}
+
+/// Formats subprotocols as a `Sec-WebSocket-Protocol` header value.
+pub fn format_protocols(protocols: &[&str]) -> String {
+    protocols.join(", ")
+}
+
+/// Picks the first subprotocol offered by the client that the server
+/// supports. Subprotocol names are case-sensitive.
+pub fn select_protocol<'a>(offered: &[String], supported: &[&'a str]) -> Option<&'a str> {
+    offered
+        .iter()
+        .find_map(|p| supported.iter().find(|s| **s == p).copied())
+}
+
+/// Builds the server's `101 Switching Protocols` response head, ending in
+/// the blank line.
+pub fn server_response(
+    client_key: &str,
+    protocol: Option<&str>,
+    extensions: &[WsExtension],
+) -> String {
+    let mut out = String::from(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n",
+    );
+    out.push_str("Sec-WebSocket-Accept: ");
+    out.push_str(&accept_key(client_key));
+    out.push_str("\r\n");
+    if let Some(protocol) = protocol {
+        out.push_str("Sec-WebSocket-Protocol: ");
+        out.push_str(protocol);
+        out.push_str("\r\n");
+    }
+    if !extensions.is_empty() {
+        out.push_str("Sec-WebSocket-Extensions: ");
+        out.push_str(&format_extensions(extensions));
+        out.push_str("\r\n");
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// RFC 7230 `token`.
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Splits on `sep` outside of quoted strings.
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Unquotes a `quoted-string`; other values are returned as they are.
+fn unquote(s: &str) -> Option<String> {
+    let Some(inner) = s.strip_prefix('"') else {
+        return Some(s.to_string());
+    };
+    let inner = inner.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
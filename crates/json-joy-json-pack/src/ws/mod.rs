@@ -7,9 +7,11 @@ pub mod decoder;
 pub mod encoder;
 pub mod errors;
 pub mod frames;
+pub mod handshake;
 
 pub use constants::WsFrameOpcode;
-pub use decoder::{WsFrameDecoder, WsFrameDecodingError};
-pub use encoder::WsFrameEncoder;
+pub use decoder::{WsFrameDecoder, WsFrameDecodingError, WsPeer};
+pub use encoder::{WsFrameEncoder, WsFrameHeaderBytes};
 pub use errors::WsFrameEncodingError;
 pub use frames::{WsCloseFrame, WsFrame, WsFrameHeader, WsPingFrame, WsPongFrame};
+pub use handshake::{WsExtension, WsHandshakeError};
@@ -6,10 +6,10 @@
 /// XDR decoding error.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum XdrDecodeError {
-    #[error("unexpected end of input")]
-    EndOfInput,
-    #[error("invalid UTF-8")]
-    InvalidUtf8,
+    #[error("unexpected end of input at offset {0}")]
+    EndOfInput(usize),
+    #[error("invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
     #[error("value exceeds maximum allowed size")]
     MaxSizeExceeded,
     #[error("unknown union discriminant")]
@@ -18,6 +18,16 @@ pub enum XdrDecodeError {
     UnsupportedType(&'static str),
 }
 
+impl XdrDecodeError {
+    /// Byte offset in the input at which the error was detected, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::EndOfInput(x) | Self::InvalidUtf8(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
 /// XDR primitive decoder.
 pub struct XdrDecoder {
     data: Vec<u8>,
@@ -45,9 +55,15 @@ impl XdrDecoder {
 
     // ---------------------------------------------------------------- helpers
 
+    /// How many items of an array to reserve room for: a decoded size is
+    /// capped by the bytes left, as only `void` items take none.
+    pub(crate) fn capacity(&self, size: usize) -> usize {
+        size.min(self.data.len() - self.pos)
+    }
+
     fn read_u32_raw(&mut self) -> Result<u32, XdrDecodeError> {
         if self.pos + 4 > self.data.len() {
-            return Err(XdrDecodeError::EndOfInput);
+            return Err(XdrDecodeError::EndOfInput(self.pos));
         }
         let b = &self.data[self.pos..self.pos + 4];
         let val = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
@@ -57,7 +73,7 @@ impl XdrDecoder {
 
     fn read_bytes(&mut self, n: usize) -> Result<Vec<u8>, XdrDecodeError> {
         if self.pos + n > self.data.len() {
-            return Err(XdrDecodeError::EndOfInput);
+            return Err(XdrDecodeError::EndOfInput(self.pos));
         }
         let bytes = self.data[self.pos..self.pos + n].to_vec();
         self.pos += n;
@@ -69,7 +85,7 @@ impl XdrDecoder {
         if rem != 0 {
             let pad = 4 - rem;
             if self.pos + pad > self.data.len() {
-                return Err(XdrDecodeError::EndOfInput);
+                return Err(XdrDecodeError::EndOfInput(self.pos));
             }
             self.pos += pad;
         }
@@ -95,21 +111,11 @@ impl XdrDecoder {
     }
 
     pub fn read_hyper(&mut self) -> Result<i64, XdrDecodeError> {
-        if self.pos + 8 > self.data.len() {
-            return Err(XdrDecodeError::EndOfInput);
-        }
-        let bytes: [u8; 8] = self.data[self.pos..self.pos + 8].try_into().unwrap();
-        self.pos += 8;
-        Ok(i64::from_be_bytes(bytes))
+        Ok(i64::from_be_bytes(self.read_fixed_opaque()?))
     }
 
     pub fn read_unsigned_hyper(&mut self) -> Result<u64, XdrDecodeError> {
-        if self.pos + 8 > self.data.len() {
-            return Err(XdrDecodeError::EndOfInput);
-        }
-        let bytes: [u8; 8] = self.data[self.pos..self.pos + 8].try_into().unwrap();
-        self.pos += 8;
-        Ok(u64::from_be_bytes(bytes))
+        Ok(u64::from_be_bytes(self.read_fixed_opaque()?))
     }
 
     pub fn read_float(&mut self) -> Result<f32, XdrDecodeError> {
@@ -118,12 +124,12 @@ impl XdrDecoder {
     }
 
     pub fn read_double(&mut self) -> Result<f64, XdrDecodeError> {
-        if self.pos + 8 > self.data.len() {
-            return Err(XdrDecodeError::EndOfInput);
-        }
-        let bytes: [u8; 8] = self.data[self.pos..self.pos + 8].try_into().unwrap();
-        self.pos += 8;
-        Ok(f64::from_be_bytes(bytes))
+        Ok(f64::from_be_bytes(self.read_fixed_opaque()?))
+    }
+
+    /// Reads a quadruple-precision float as its IEEE 754 binary128 bits.
+    pub fn read_quadruple(&mut self) -> Result<u128, XdrDecodeError> {
+        Ok(u128::from_be_bytes(self.read_fixed_opaque()?))
     }
 
     pub fn read_enum(&mut self) -> Result<i32, XdrDecodeError> {
@@ -137,25 +143,44 @@ impl XdrDecoder {
         Ok(data)
     }
 
+    /// Reads `opaque[N]` and skips its padding.
+    pub fn read_fixed_opaque<const N: usize>(&mut self) -> Result<[u8; N], XdrDecodeError> {
+        let end = self.pos + N.next_multiple_of(4);
+        if end > self.data.len() {
+            return Err(XdrDecodeError::EndOfInput(self.pos));
+        }
+        let mut out = [0; N];
+        out.copy_from_slice(&self.data[self.pos..self.pos + N]);
+        self.pos = end;
+        Ok(out)
+    }
+
     /// Reads variable-length opaque: reads length, then opaque(length).
     pub fn read_varlen_opaque(&mut self) -> Result<Vec<u8>, XdrDecodeError> {
         let len = self.read_u32_raw()? as usize;
         self.read_opaque(len)
     }
 
+    /// Reads a multiprecision integer written by
+    /// [`super::XdrEncoder::write_mpint`].
+    pub fn read_mpint(&mut self) -> Result<crate::JsonPackMpint, XdrDecodeError> {
+        Ok(crate::JsonPackMpint::new(self.read_varlen_opaque()?))
+    }
+
     /// Reads a string: [length: u32][utf8 bytes][padding].
     pub fn read_string(&mut self) -> Result<String, XdrDecodeError> {
         let len = self.read_u32_raw()? as usize;
+        let start = self.pos;
         let bytes = self.read_bytes(len)?;
         self.skip_padding(len)?;
-        String::from_utf8(bytes).map_err(|_| XdrDecodeError::InvalidUtf8)
+        String::from_utf8(bytes).map_err(|_| XdrDecodeError::InvalidUtf8(start))
     }
 
     pub fn read_array<T, F>(&mut self, size: usize, mut reader: F) -> Result<Vec<T>, XdrDecodeError>
     where
         F: FnMut(&mut Self) -> Result<T, XdrDecodeError>,
     {
-        let mut arr = Vec::with_capacity(size);
+        let mut arr = Vec::with_capacity(self.capacity(size));
         for _ in 0..size {
             arr.push(reader(self)?);
         }
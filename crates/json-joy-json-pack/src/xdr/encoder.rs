@@ -5,6 +5,8 @@
 
 use json_joy_buffers::Writer;
 
+use crate::JsonPackMpint;
+
 /// XDR primitive encoder.
 ///
 /// Writes XDR-encoded primitives using big-endian byte order and 4-byte alignment.
@@ -66,6 +68,12 @@ impl XdrEncoder {
         self.writer.buf(&bytes);
     }
 
+    /// Writes a quadruple-precision float from its IEEE 754 binary128 bits;
+    /// Rust has no stable `f128` to take instead.
+    pub fn write_quadruple(&mut self, bits: u128) {
+        self.writer.buf(&bits.to_be_bytes());
+    }
+
     pub fn write_float(&mut self, f: f32) {
         self.writer.u32(f.to_bits());
     }
@@ -81,12 +89,25 @@ impl XdrEncoder {
         self.write_padding(data.len());
     }
 
+    /// Writes `opaque[N]`, padded to a 4-byte boundary. The length is part
+    /// of the type, so it cannot disagree with the schema.
+    pub fn write_fixed_opaque<const N: usize>(&mut self, data: &[u8; N]) {
+        self.write_opaque(data);
+    }
+
     /// Writes variable-length opaque: [length: u32][data][padding].
     pub fn write_varlen_opaque(&mut self, data: &[u8]) {
         self.writer.u32(data.len() as u32);
         self.write_opaque(data);
     }
 
+    /// Writes a multiprecision integer as variable-length opaque holding its
+    /// minimal two's complement bytes. XDR has no native bignum type; this
+    /// mirrors the SSH mpint layout with XDR padding.
+    pub fn write_mpint(&mut self, mpint: &JsonPackMpint) {
+        self.write_varlen_opaque(&JsonPackMpint::from_twos_complement(&mpint.data).data);
+    }
+
     /// Writes a string: [length: u32][utf8 bytes][padding].
     pub fn write_str(&mut self, s: &str) {
         let bytes = s.as_bytes();
@@ -87,7 +87,7 @@ impl XdrSchemaDecoder {
                         return Err(XdrDecodeError::MaxSizeExceeded);
                     }
                 }
-                let mut arr = Vec::with_capacity(len);
+                let mut arr = Vec::with_capacity(self.decoder.capacity(len));
                 for _ in 0..len {
                     arr.push(self.read_value(element)?);
                 }
@@ -0,0 +1,197 @@
+//! Avro array/map blocks with byte sizes, and skipping them.
+//!
+//! Not an upstream port: upstream writes unsigned block counts and cannot
+//! skip blocks.
+
+use json_joy_json_pack::avro::{
+    AvroDecodeError, AvroDecoder, AvroDecoderOptions, AvroEncoder, AvroEncoderOptions, AvroSchema,
+    AvroSchemaDecoder, AvroSchemaEncoder, AvroValue,
+};
+use json_joy_json_pack::{pack, PackValue};
+
+fn sized() -> AvroEncoder {
+    AvroEncoder::with_options(AvroEncoderOptions { block_sizes: true })
+}
+
+fn signed(data: &[u8]) -> AvroDecoder {
+    let mut decoder = AvroDecoder::with_options(AvroDecoderOptions {
+        signed_counts: true,
+    });
+    decoder.reset(data);
+    decoder
+}
+
+fn encode(mut encoder: AvroEncoder, value: &PackValue) -> Vec<u8> {
+    encoder.write_any(value);
+    encoder.writer.flush()
+}
+
+#[test]
+fn avro_blocks_encode_matrix() {
+    let cases: Vec<(PackValue, Vec<u8>, Vec<u8>)> = vec![
+        (
+            pack!([1, 2, 3]),
+            vec![0x03, 0x02, 0x04, 0x06, 0x00],
+            vec![0x05, 0x06, 0x02, 0x04, 0x06, 0x00],
+        ),
+        (
+            pack!({"a": 1}),
+            vec![0x01, 0x01, b'a', 0x02, 0x00],
+            vec![0x01, 0x06, 0x01, b'a', 0x02, 0x00],
+        ),
+        (
+            pack!([[1]]),
+            vec![0x01, 0x01, 0x02, 0x00, 0x00],
+            vec![0x01, 0x08, 0x01, 0x02, 0x02, 0x00, 0x00],
+        ),
+        // Upstream writes a zero count before the terminator.
+        (pack!([]), vec![0x00, 0x00], vec![0x00]),
+    ];
+    for (value, plain, blocks) in cases {
+        assert_eq!(encode(AvroEncoder::new(), &value), plain, "{value:?}");
+        assert_eq!(encode(sized(), &value), blocks, "{value:?}");
+    }
+
+    // Sizes past one varint byte.
+    let long = PackValue::Array(vec![PackValue::Integer(1); 100]);
+    let bytes = encode(sized(), &long);
+    assert_eq!(bytes[..3], [0xc7, 0x01, 0xc8]);
+    assert_eq!(bytes.len(), 3 + 1 + 100 + 1);
+
+    // Nested large blocks are patched at every level.
+    let nested = PackValue::Array(vec![long.clone(), long.clone()]);
+    let bytes = encode(sized(), &nested);
+    let mut decoder = signed(&bytes);
+    let rows = decoder
+        .read_array(|d| d.read_array(|d| d.read_long()))
+        .unwrap();
+    assert_eq!(rows, vec![vec![1i64; 100]; 2]);
+    assert_eq!(bytes.len(), 3 + 2 * 105 + 1);
+    assert_eq!(bytes[..3], [0x03, 0xa4, 0x03]);
+
+    // Streamed blocks carry spec counts but no size.
+    let mut encoder = sized();
+    encoder.write_array_start(2);
+    encoder.write_int(1);
+    encoder.write_int(2);
+    encoder.write_array_end();
+    assert_eq!(encoder.writer.flush(), [0x04, 0x02, 0x04, 0x00]);
+}
+
+#[test]
+fn avro_blocks_decode_matrix() {
+    let cases: &[(&[u8], Vec<i32>)] = &[
+        (&[0x05, 0x06, 0x02, 0x04, 0x06, 0x00], vec![1, 2, 3]),
+        // Positive counts are zigzag too.
+        (&[0x04, 0x02, 0x04, 0x00], vec![1, 2]),
+        (&[0x01, 0x02, 0x02, 0x02, 0x04, 0x00], vec![1, 2]),
+        (&[0x00], vec![]),
+    ];
+    for (bytes, expected) in cases {
+        let mut decoder = signed(bytes);
+        assert_eq!(&decoder.read_array(|d| d.read_int()).unwrap(), expected);
+    }
+
+    let mut decoder = signed(&[0x01, 0x06, 0x01, b'a', 0x02, 0x00]);
+    assert_eq!(
+        decoder.read_map(|d| d.read_int()).unwrap(),
+        [("a".to_string(), 1)]
+    );
+
+    let errors: &[(&[u8], AvroDecodeError)] = &[
+        (
+            &[0x01, 0x01, 0x02, 0x00],
+            AvroDecodeError::InvalidBlockSize(-1),
+        ),
+        // The items must span the declared size exactly.
+        (
+            &[0x01, 0x04, 0x02, 0x00],
+            AvroDecodeError::InvalidBlockSize(2),
+        ),
+        (&[0x01], AvroDecodeError::EndOfInput(1)),
+    ];
+    for (bytes, expected) in errors {
+        let mut decoder = signed(bytes);
+        assert_eq!(
+            &decoder.read_array(|d| d.read_int()).unwrap_err(),
+            expected,
+            "{bytes:02x?}"
+        );
+    }
+}
+
+#[test]
+fn avro_blocks_skip_matrix() {
+    // A huge map is jumped over without visiting its values.
+    let map = PackValue::Object(
+        (0..1000)
+            .map(|i| (format!("k{i}"), pack!([i, "value"])))
+            .collect(),
+    );
+    let mut encoder = sized();
+    encoder.write_any(&map);
+    encoder.write_int(-7);
+    let bytes = encoder.writer.flush();
+    let mut decoder = signed(&bytes);
+    decoder
+        .skip_map(|_| panic!("sized blocks are not decoded"))
+        .unwrap();
+    assert_eq!(decoder.read_int(), Ok(-7));
+
+    // Blocks without sizes fall back to the item callback.
+    let cases: &[(&[u8], bool)] = &[
+        (&[0x04, 0x02, 0x04, 0x00, 0x0d], true),
+        (&[0x02, 0x02, 0x04, 0x00, 0x0d], false),
+    ];
+    for (bytes, signed_counts) in cases {
+        let mut decoder = AvroDecoder::with_options(AvroDecoderOptions {
+            signed_counts: *signed_counts,
+        });
+        decoder.reset(bytes);
+        let mut visited = 0;
+        decoder
+            .skip_array(|d| {
+                visited += 1;
+                d.read_int().map(drop)
+            })
+            .unwrap();
+        assert_eq!(decoder.read_int(), Ok(-7));
+        assert!(visited > 0);
+    }
+
+    let mut decoder = signed(&[0x01, 0x0a, 0x02]);
+    assert_eq!(
+        decoder.skip_array(|_| Ok(())),
+        Err(AvroDecodeError::EndOfInput(2))
+    );
+}
+
+#[test]
+fn avro_blocks_schema_matrix() {
+    let schema = AvroSchema::Map {
+        values: Box::new(AvroSchema::Array {
+            items: Box::new(AvroSchema::Long),
+        }),
+    };
+    let value = AvroValue::Map(vec![
+        (
+            "a".into(),
+            AvroValue::Array(vec![AvroValue::Long(1), AvroValue::Long(-300)]),
+        ),
+        ("b".into(), AvroValue::Array(vec![])),
+    ]);
+    let mut encoder = AvroSchemaEncoder::with_options(AvroEncoderOptions { block_sizes: true });
+    let bytes = encoder.encode(&value, &schema).unwrap();
+    let mut decoder = AvroSchemaDecoder::with_options(AvroDecoderOptions {
+        signed_counts: true,
+    });
+    assert_eq!(decoder.decode(&bytes, &schema).unwrap(), value);
+
+    // The default decoder reads upstream's unsigned counts.
+    let plain = AvroSchemaEncoder::new().encode(&value, &schema).unwrap();
+    assert_eq!(
+        AvroSchemaDecoder::new().decode(&plain, &schema).unwrap(),
+        value
+    );
+    assert_ne!(plain, bytes);
+}
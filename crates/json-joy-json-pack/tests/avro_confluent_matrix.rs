@@ -0,0 +1,124 @@
+//! Confluent Schema Registry framing for Avro payloads.
+//!
+//! Not an upstream port: upstream only encodes bare Avro bodies.
+
+use std::collections::HashMap;
+
+use json_joy_json_pack::avro::confluent::{self, SchemaLookup};
+use json_joy_json_pack::avro::{
+    AvroDecodeError, AvroEncodeError, AvroField, AvroSchema, AvroValue,
+};
+
+fn record_schema() -> AvroSchema {
+    AvroSchema::Record {
+        name: "Event".into(),
+        namespace: None,
+        fields: vec![
+            AvroField {
+                name: "id".into(),
+                type_: AvroSchema::Int,
+                default: None,
+                doc: None,
+                aliases: Vec::new(),
+            },
+            AvroField {
+                name: "name".into(),
+                type_: AvroSchema::String,
+                default: None,
+                doc: None,
+                aliases: Vec::new(),
+            },
+        ],
+        aliases: Vec::new(),
+        doc: None,
+    }
+}
+
+fn record_value() -> AvroValue {
+    AvroValue::Record(vec![
+        ("id".into(), AvroValue::Int(-2)),
+        ("name".into(), AvroValue::Str("ab".into())),
+    ])
+}
+
+#[test]
+fn avro_confluent_wire_matrix() {
+    let bytes = confluent::encode(&record_value(), &record_schema(), 0x0102_0304).unwrap();
+    assert_eq!(bytes, [0, 1, 2, 3, 4, 3, 2, b'a', b'b']);
+    assert_eq!(
+        confluent::read_header(&bytes).unwrap(),
+        (0x0102_0304, &bytes[5..])
+    );
+
+    let empty = confluent::encode(&AvroValue::Null, &AvroSchema::Null, 7).unwrap();
+    assert_eq!(empty, [0, 0, 0, 0, 7]);
+    assert_eq!(confluent::read_header(&empty).unwrap(), (7, &[][..]));
+
+    assert_eq!(
+        confluent::encode(&AvroValue::Str("x".into()), &AvroSchema::Int, 1),
+        Err(AvroEncodeError::ValueDoesNotConform)
+    );
+}
+
+#[test]
+fn avro_confluent_lookup_matrix() {
+    let bytes = confluent::encode(&record_value(), &record_schema(), 42).unwrap();
+
+    let mut registry = HashMap::from([(42u32, record_schema()), (1, AvroSchema::Long)]);
+    assert_eq!(
+        confluent::decode(&bytes, &mut registry).unwrap(),
+        (42, record_value())
+    );
+
+    // Closures work as lookups, e.g. to count or cache registry fetches.
+    let mut fetches = Vec::new();
+    let mut lookup = |id: u32| {
+        fetches.push(id);
+        (id == 42).then(record_schema)
+    };
+    assert_eq!(
+        confluent::decode(&bytes, &mut lookup).unwrap(),
+        (42, record_value())
+    );
+    let long = confluent::encode(&AvroValue::Long(5), &AvroSchema::Long, 1).unwrap();
+    assert_eq!(
+        confluent::decode(&long, &mut lookup),
+        Err(AvroDecodeError::UnknownSchemaId(1))
+    );
+    assert_eq!(fetches, [42, 1]);
+    assert_eq!(
+        confluent::decode(&long, &mut registry).unwrap(),
+        (1, AvroValue::Long(5))
+    );
+
+    struct Fixed(AvroSchema);
+    impl SchemaLookup for Fixed {
+        fn schema(&mut self, _id: u32) -> Option<AvroSchema> {
+            Some(self.0.clone())
+        }
+    }
+    assert_eq!(
+        confluent::decode(&long, &mut Fixed(AvroSchema::Long)).unwrap(),
+        (1, AvroValue::Long(5))
+    );
+}
+
+#[test]
+fn avro_confluent_error_matrix() {
+    let mut registry = HashMap::from([(1u32, AvroSchema::String)]);
+    let cases: [(&[u8], AvroDecodeError); 5] = [
+        (&[], AvroDecodeError::EndOfInput(0)),
+        (&[0, 0, 0, 1], AvroDecodeError::EndOfInput(4)),
+        (&[1, 0, 0, 0, 1, 0], AvroDecodeError::InvalidMagicByte(1)),
+        (&[0, 0, 0, 0, 2, 0], AvroDecodeError::UnknownSchemaId(2)),
+        // Body shorter than the string length it declares.
+        (&[0, 0, 0, 0, 1, 4, b'a'], AvroDecodeError::EndOfInput(6)),
+    ];
+    for (bytes, error) in cases {
+        assert_eq!(
+            confluent::decode(bytes, &mut registry),
+            Err(error),
+            "{bytes:?}"
+        );
+    }
+}
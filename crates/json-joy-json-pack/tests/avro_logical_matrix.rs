@@ -0,0 +1,289 @@
+//! Avro logical types: decimal, uuid, date, time and timestamp annotations.
+//!
+//! Not an upstream port: upstream schemas carry no logical types.
+
+use json_joy_json_pack::avro::{
+    parsing_canonical_form, AvroDecodeError, AvroEncodeError, AvroField, AvroLogicalType,
+    AvroSchema, AvroSchemaDecoder, AvroSchemaEncoder, AvroSchemaValidator, AvroValue,
+};
+use json_joy_json_pack::JsonPackBigInt;
+
+const UUID: &str = "123e4567-e89b-12d3-a456-426614174000";
+
+fn fixed(name: &str, size: usize) -> AvroSchema {
+    AvroSchema::Fixed {
+        name: name.into(),
+        namespace: None,
+        size,
+        aliases: vec![],
+    }
+}
+
+fn field(name: &str, type_: AvroSchema) -> AvroField {
+    AvroField {
+        name: name.into(),
+        type_,
+        default: None,
+        doc: None,
+        aliases: Vec::new(),
+    }
+}
+
+fn decimal(unscaled: i128, scale: u32) -> AvroValue {
+    AvroValue::Decimal {
+        unscaled: JsonPackBigInt::from_i128(unscaled),
+        scale,
+    }
+}
+
+fn roundtrip(value: &AvroValue, schema: &AvroSchema) -> Vec<u8> {
+    let bytes = AvroSchemaEncoder::new().encode(value, schema).unwrap();
+    assert_eq!(
+        AvroSchemaDecoder::new().decode(&bytes, schema).unwrap(),
+        *value,
+        "{value:?}"
+    );
+    bytes
+}
+
+#[test]
+fn avro_logical_roundtrip_matrix() {
+    let money = AvroSchema::Bytes.logical(AvroLogicalType::Decimal {
+        precision: 5,
+        scale: 2,
+    });
+    let cases: Vec<(AvroValue, AvroSchema, Vec<u8>)> = vec![
+        (decimal(-1, 2), money.clone(), vec![1, 0xff]),
+        (decimal(0, 2), money.clone(), vec![1, 0]),
+        (decimal(12_345, 2), money.clone(), vec![2, 0x30, 0x39]),
+        (decimal(128, 2), money, vec![2, 0x00, 0x80]),
+        (
+            decimal(-2, 0),
+            fixed("D", 4).logical(AvroLogicalType::Decimal {
+                precision: 9,
+                scale: 0,
+            }),
+            vec![0xff, 0xff, 0xff, 0xfe],
+        ),
+        (
+            decimal(300, 1),
+            fixed("D", 4).logical(AvroLogicalType::Decimal {
+                precision: 9,
+                scale: 1,
+            }),
+            vec![0, 0, 1, 0x2c],
+        ),
+        (
+            AvroValue::Date(-1),
+            AvroSchema::Int.logical(AvroLogicalType::Date),
+            vec![1],
+        ),
+        (
+            AvroValue::TimeMillis(1),
+            AvroSchema::Int.logical(AvroLogicalType::TimeMillis),
+            vec![2],
+        ),
+        (
+            AvroValue::TimeMicros(64),
+            AvroSchema::Long.logical(AvroLogicalType::TimeMicros),
+            vec![0x80, 0x01],
+        ),
+        (
+            AvroValue::TimestampMillis(-64),
+            AvroSchema::Long.logical(AvroLogicalType::TimestampMillis),
+            vec![0x7f],
+        ),
+        (
+            AvroValue::TimestampMicros(0),
+            AvroSchema::Long.logical(AvroLogicalType::TimestampMicros),
+            vec![0],
+        ),
+    ];
+    for (value, schema, expected) in cases {
+        assert_eq!(roundtrip(&value, &schema), expected, "{value:?}");
+    }
+}
+
+#[test]
+fn avro_logical_uuid_matrix() {
+    let string = AvroSchema::String.logical(AvroLogicalType::Uuid);
+    let bytes = roundtrip(&AvroValue::Uuid(UUID.into()), &string);
+    assert_eq!(bytes[0], 36);
+    assert_eq!(&bytes[1..], UUID.as_bytes());
+
+    let fixed16 = fixed("U", 16).logical(AvroLogicalType::Uuid);
+    let bytes = roundtrip(&AvroValue::Uuid(UUID.into()), &fixed16);
+    assert_eq!(bytes[..4], [0x12, 0x3e, 0x45, 0x67]);
+    assert_eq!(bytes[12..], [0x14, 0x17, 0x40, 0x00]);
+
+    // Fixed UUIDs decode as lowercase.
+    let upper = UUID.to_uppercase();
+    let bytes = AvroSchemaEncoder::new()
+        .encode(&AvroValue::Uuid(upper), &fixed16)
+        .unwrap();
+    assert_eq!(
+        AvroSchemaDecoder::new().decode(&bytes, &fixed16).unwrap(),
+        AvroValue::Uuid(UUID.into())
+    );
+
+    // A string that is not a UUID fails to decode under the annotation.
+    let bytes = AvroSchemaEncoder::new()
+        .encode(&AvroValue::Str("nope".into()), &AvroSchema::String)
+        .unwrap();
+    assert_eq!(
+        AvroSchemaDecoder::new().decode(&bytes, &string),
+        Err(AvroDecodeError::InvalidLogicalValue("uuid"))
+    );
+    assert_eq!(
+        AvroSchemaEncoder::new().encode(&AvroValue::Uuid("nope".into()), &string),
+        Err(AvroEncodeError::ValueDoesNotConform)
+    );
+}
+
+#[test]
+fn avro_logical_underlying_values_matrix() {
+    // Plain values of the underlying type are written unchanged and read
+    // back as logical values.
+    let date = AvroSchema::Int.logical(AvroLogicalType::Date);
+    let bytes = AvroSchemaEncoder::new()
+        .encode(&AvroValue::Int(5), &date)
+        .unwrap();
+    assert_eq!(bytes, [10]);
+    assert_eq!(
+        AvroSchemaDecoder::new().decode(&bytes, &date).unwrap(),
+        AvroValue::Date(5)
+    );
+
+    // Annotations that do not apply to their type are ignored.
+    let cases = [
+        (
+            fixed("F", 2).logical(AvroLogicalType::Decimal {
+                precision: 5,
+                scale: 0,
+            }),
+            AvroValue::Fixed(vec![1, 2]),
+        ),
+        (
+            AvroSchema::Bytes.logical(AvroLogicalType::Decimal {
+                precision: 2,
+                scale: 3,
+            }),
+            AvroValue::Bytes(vec![1]),
+        ),
+        (
+            fixed("F", 8).logical(AvroLogicalType::Uuid),
+            AvroValue::Fixed(vec![0; 8]),
+        ),
+        (
+            AvroSchema::Int.logical(AvroLogicalType::TimestampMillis),
+            AvroValue::Int(3),
+        ),
+        (
+            AvroSchema::Long.logical(AvroLogicalType::Date),
+            AvroValue::Long(3),
+        ),
+    ];
+    for (schema, value) in cases {
+        roundtrip(&value, &schema);
+        assert_eq!(
+            AvroSchemaEncoder::new().encode(&AvroValue::Date(3), &schema),
+            Err(AvroEncodeError::ValueDoesNotConform),
+            "{schema:?}"
+        );
+    }
+}
+
+#[test]
+fn avro_logical_nested_matrix() {
+    let schema = AvroSchema::Record {
+        name: "Payment".into(),
+        namespace: None,
+        fields: vec![
+            field("id", fixed("Id", 16).logical(AvroLogicalType::Uuid)),
+            field("parent", AvroSchema::Ref("Id".into())),
+            field(
+                "at",
+                AvroSchema::Union(vec![
+                    AvroSchema::Null,
+                    AvroSchema::Long.logical(AvroLogicalType::TimestampMillis),
+                ]),
+            ),
+            field(
+                "amounts",
+                AvroSchema::Array {
+                    items: Box::new(AvroSchema::Bytes.logical(AvroLogicalType::Decimal {
+                        precision: 10,
+                        scale: 2,
+                    })),
+                },
+            ),
+        ],
+        aliases: vec![],
+        doc: None,
+    };
+    let value = AvroValue::Record(vec![
+        ("id".into(), AvroValue::Uuid(UUID.into())),
+        ("parent".into(), AvroValue::Uuid(UUID.into())),
+        (
+            "at".into(),
+            AvroValue::Union {
+                index: 1,
+                value: Box::new(AvroValue::TimestampMillis(1_700_000_000_000)),
+            },
+        ),
+        (
+            "amounts".into(),
+            AvroValue::Array(vec![decimal(1999, 2), decimal(-5, 2)]),
+        ),
+    ]);
+    assert!(AvroSchemaValidator::new().validate_schema(&schema));
+    assert!(AvroSchemaValidator::new().validate_value(&value, &schema));
+    roundtrip(&value, &schema);
+
+    // Parsing Canonical Form drops logical type annotations.
+    assert_eq!(
+        parsing_canonical_form(&schema),
+        concat!(
+            r#"{"name":"Payment","type":"record","fields":["#,
+            r#"{"name":"id","type":{"name":"Id","type":"fixed","size":16}},"#,
+            r#"{"name":"parent","type":"Id"},"#,
+            r#"{"name":"at","type":["null","long"]},"#,
+            r#"{"name":"amounts","type":{"type":"array","items":"bytes"}}]}"#,
+        )
+    );
+}
+
+#[test]
+fn avro_logical_validation_matrix() {
+    let money = AvroSchema::Bytes.logical(AvroLogicalType::Decimal {
+        precision: 4,
+        scale: 2,
+    });
+    let uuid = AvroSchema::String.logical(AvroLogicalType::Uuid);
+    let date = AvroSchema::Int.logical(AvroLogicalType::Date);
+    let cases = [
+        (decimal(9999, 2), &money, true),
+        (decimal(-9999, 2), &money, true),
+        (decimal(10_000, 2), &money, false),
+        (decimal(1, 3), &money, false),
+        (AvroValue::Bytes(vec![1]), &money, true),
+        (AvroValue::Uuid(UUID.into()), &uuid, true),
+        (
+            AvroValue::Uuid("123e4567e89b12d3a456426614174000".into()),
+            &uuid,
+            false,
+        ),
+        (AvroValue::Str("anything".into()), &uuid, true),
+        (AvroValue::Date(1), &date, true),
+        (AvroValue::TimeMillis(1), &date, false),
+    ];
+    for (value, schema, valid) in cases {
+        assert_eq!(
+            AvroSchemaValidator::new().validate_value(&value, schema),
+            valid,
+            "{value:?}"
+        );
+        let encoded = AvroSchemaEncoder::new().encode(&value, schema);
+        assert_eq!(encoded.is_ok(), valid, "{value:?}");
+    }
+}
@@ -0,0 +1,236 @@
+//! Avro Parsing Canonical Form, schema fingerprints and single-object
+//! encoding.
+//!
+//! Not an upstream port: upstream has no single-object encoding. Expected
+//! fingerprints come from the Avro specification's test vectors and a
+//! reference CRC-64-AVRO / SHA-256 implementation.
+
+use json_joy_json_pack::avro::{
+    crc64_avro, decode_single_object, encode_single_object, fingerprint, fingerprint64,
+    fingerprint_sha256, parsing_canonical_form, read_single_object_header, AvroDecodeError,
+    AvroField, AvroFingerprintAlgorithm, AvroSchema, AvroSingleObjectRegistry, AvroValue,
+    SINGLE_OBJECT_MARKER,
+};
+
+const USER_PCF: &str = concat!(
+    r#"{"name":"ns.User","type":"record","fields":["#,
+    r#"{"name":"id","type":"long"},"#,
+    r#"{"name":"tags","type":{"type":"array","items":"string"}},"#,
+    r#"{"name":"kind","type":{"name":"ns.Kind","type":"enum","symbols":["A","B"]}},"#,
+    r#"{"name":"alt","type":["null","ns.Kind"]}]}"#,
+);
+
+fn field(name: &str, type_: AvroSchema) -> AvroField {
+    AvroField {
+        name: name.to_string(),
+        type_,
+        default: None,
+        doc: None,
+        aliases: Vec::new(),
+    }
+}
+
+fn user_schema() -> AvroSchema {
+    AvroSchema::Record {
+        name: "User".into(),
+        namespace: Some("ns".into()),
+        fields: vec![
+            AvroField {
+                default: Some(AvroValue::Long(0)),
+                doc: Some("Stripped from the canonical form.".into()),
+                ..field("id", AvroSchema::Long)
+            },
+            field(
+                "tags",
+                AvroSchema::Array {
+                    items: Box::new(AvroSchema::String),
+                },
+            ),
+            field(
+                "kind",
+                AvroSchema::Enum {
+                    name: "Kind".into(),
+                    namespace: None,
+                    symbols: vec!["A".into(), "B".into()],
+                    default: Some("A".into()),
+                    aliases: vec!["Sort".into()],
+                },
+            ),
+            field(
+                "alt",
+                AvroSchema::Union(vec![AvroSchema::Null, AvroSchema::Ref("Kind".into())]),
+            ),
+        ],
+        aliases: vec!["Person".into()],
+        doc: Some("A user.".into()),
+    }
+}
+
+fn user_value() -> AvroValue {
+    AvroValue::Record(vec![
+        ("id".into(), AvroValue::Long(7)),
+        (
+            "tags".into(),
+            AvroValue::Array(vec![AvroValue::Str("x".into())]),
+        ),
+        ("kind".into(), AvroValue::Enum("B".into())),
+        (
+            "alt".into(),
+            AvroValue::Union {
+                index: 1,
+                value: Box::new(AvroValue::Enum("A".into())),
+            },
+        ),
+    ])
+}
+
+#[test]
+fn avro_fingerprint_matrix() {
+    assert_eq!(crc64_avro(b""), 0xc15d_213a_a4d7_a795);
+    let primitives = [
+        (AvroSchema::Null, "\"null\"", 7_195_948_357_588_979_594u64),
+        (AvroSchema::Int, "\"int\"", 8_247_732_601_305_521_295),
+        (AvroSchema::String, "\"string\"", 10_304_597_078_529_344_455),
+        (
+            AvroSchema::Ref("string".into()),
+            "\"string\"",
+            10_304_597_078_529_344_455,
+        ),
+    ];
+    for (schema, pcf, fp) in primitives {
+        assert_eq!(parsing_canonical_form(&schema), pcf);
+        assert_eq!(fingerprint64(&schema), fp, "{pcf}");
+    }
+
+    let schema = user_schema();
+    assert_eq!(parsing_canonical_form(&schema), USER_PCF);
+    assert_eq!(fingerprint64(&schema), 0xa4c2_d182_549f_4189);
+    assert_eq!(
+        fingerprint(&schema, AvroFingerprintAlgorithm::Rabin),
+        0xa4c2_d182_549f_4189u64.to_le_bytes()
+    );
+    let sha = fingerprint_sha256(&schema);
+    assert_eq!(sha[..8], [0x67, 0x9d, 0xd2, 0x97, 0xd9, 0x20, 0x55, 0xe8]);
+    assert_eq!(sha[24..], [0xae, 0x71, 0x6e, 0x83, 0xf3, 0x5e, 0x06, 0x2c]);
+    assert_eq!(
+        fingerprint(&schema, AvroFingerprintAlgorithm::Sha256),
+        sha.to_vec()
+    );
+    assert_eq!(
+        fingerprint_sha256(&AvroSchema::Null)[..4],
+        [0xf0, 0x72, 0xcb, 0xec]
+    );
+}
+
+#[test]
+fn avro_canonical_form_names_matrix() {
+    // A named type is written in full once, then by name; explicit
+    // namespaces and dotted names override the enclosing namespace.
+    let fixed = AvroSchema::Fixed {
+        name: "Hash".into(),
+        namespace: Some("other".into()),
+        size: 4,
+        aliases: vec![],
+    };
+    let schema = AvroSchema::Record {
+        name: "a.b.R".into(),
+        namespace: Some("ignored".into()),
+        fields: vec![
+            field("h1", fixed.clone()),
+            field("h2", fixed),
+            field(
+                "m",
+                AvroSchema::Map {
+                    values: Box::new(AvroSchema::Ref("R".into())),
+                },
+            ),
+            field(
+                "top",
+                AvroSchema::Fixed {
+                    name: "T".into(),
+                    namespace: Some(String::new()),
+                    size: 1,
+                    aliases: vec![],
+                },
+            ),
+        ],
+        aliases: vec![],
+        doc: None,
+    };
+    assert_eq!(
+        parsing_canonical_form(&schema),
+        concat!(
+            r#"{"name":"a.b.R","type":"record","fields":["#,
+            r#"{"name":"h1","type":{"name":"other.Hash","type":"fixed","size":4}},"#,
+            r#"{"name":"h2","type":"other.Hash"},"#,
+            r#"{"name":"m","type":{"type":"map","values":"a.b.R"}},"#,
+            r#"{"name":"top","type":{"name":"T","type":"fixed","size":1}}]}"#,
+        )
+    );
+}
+
+#[test]
+fn avro_single_object_roundtrip_matrix() {
+    let schema = user_schema();
+    let value = user_value();
+    let bytes = encode_single_object(&value, &schema).unwrap();
+    assert_eq!(bytes[..2], SINGLE_OBJECT_MARKER);
+    assert_eq!(bytes[2..10], 0xa4c2_d182_549f_4189u64.to_le_bytes());
+    assert_eq!(bytes[10..], [14, 1, 1, b'x', 0, 2, 2, 0]);
+
+    let (fp, body) = read_single_object_header(&bytes).unwrap();
+    assert_eq!(fp, 0xa4c2_d182_549f_4189);
+    assert_eq!(body, &bytes[10..]);
+    assert_eq!(decode_single_object(&bytes, &schema).unwrap(), value);
+    assert_eq!(
+        decode_single_object(&bytes, &AvroSchema::Long),
+        Err(AvroDecodeError::UnknownFingerprint(0xa4c2_d182_549f_4189))
+    );
+
+    let long = encode_single_object(&AvroValue::Long(-1), &AvroSchema::Long).unwrap();
+    assert_eq!(long.len(), 11);
+    assert_eq!(long[10], 1);
+}
+
+#[test]
+fn avro_single_object_registry_matrix() {
+    let mut registry = AvroSingleObjectRegistry::new();
+    assert!(registry.is_empty());
+    let user_fp = registry.register(user_schema());
+    let long_fp = registry.register(AvroSchema::Long);
+    assert_eq!(registry.register(AvroSchema::Long), long_fp);
+    assert_eq!(fingerprint64(&AvroSchema::Ref("long".into())), long_fp);
+    assert_eq!(registry.len(), 2);
+    assert!(matches!(
+        registry.get(user_fp),
+        Some(AvroSchema::Record { .. })
+    ));
+
+    let user = encode_single_object(&user_value(), &user_schema()).unwrap();
+    let long = encode_single_object(&AvroValue::Long(300), &AvroSchema::Long).unwrap();
+    assert_eq!(registry.decode(&user).unwrap(), (user_fp, user_value()));
+    assert_eq!(
+        registry.decode(&long).unwrap(),
+        (long_fp, AvroValue::Long(300))
+    );
+
+    let unknown = encode_single_object(&AvroValue::Int(1), &AvroSchema::Int).unwrap();
+    assert_eq!(
+        registry.decode(&unknown),
+        Err(AvroDecodeError::UnknownFingerprint(
+            8_247_732_601_305_521_295
+        ))
+    );
+    assert_eq!(
+        registry.decode(&[0xc3, 0x02, 0, 0, 0, 0, 0, 0, 0, 0]),
+        Err(AvroDecodeError::InvalidSingleObjectMarker)
+    );
+    assert_eq!(
+        registry.decode(&user[..9]),
+        Err(AvroDecodeError::EndOfInput(9))
+    );
+    assert_eq!(
+        registry.decode(&user[..11]),
+        Err(AvroDecodeError::EndOfInput(11))
+    );
+}
@@ -0,0 +1,27 @@
+//! Round-trip guard for the benchmark corpora in `benches/corpus`.
+//!
+//! Not an upstream port. Keeps the benchmarked codecs honest: a fast path
+//! that stops round-tripping fails here before its numbers are trusted.
+
+#[path = "../benches/corpus/mod.rs"]
+mod corpus;
+
+use corpus::{codecs, corpora};
+
+#[test]
+fn bench_corpus_roundtrip_matrix() {
+    let corpora = corpora();
+    for codec in codecs() {
+        for corpus in &corpora {
+            let encoded = (codec.encode)(&corpus.docs);
+            let reencoded = (codec.encode)(&(codec.decode)(&encoded));
+            for (i, (a, b)) in encoded.iter().zip(&reencoded).enumerate() {
+                assert_eq!(
+                    a, b,
+                    "{}/{} doc {i} is not byte-stable",
+                    codec.name, corpus.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! `BencodeStreamingDecoder` and the `BencodeDecoderOptions` limits.
+//!
+//! Not an upstream port: upstream decodes complete buffers without limits.
+
+use json_joy_json_pack::bencode::{
+    BencodeDecoder, BencodeDecoderOptions, BencodeEncoder, BencodeError, BencodeStreamingDecoder,
+};
+use json_joy_json_pack::{pack, PackValue};
+
+fn torrent() -> PackValue {
+    PackValue::Object(vec![
+        (
+            "announce".into(),
+            PackValue::Bytes(b"http://t.example/a".to_vec()),
+        ),
+        (
+            "info".into(),
+            PackValue::Object(vec![
+                ("length".into(), PackValue::Integer(1 << 40)),
+                ("name".into(), PackValue::Bytes(b"file.bin".to_vec())),
+                ("pieces".into(), PackValue::Bytes(vec![0xab; 40])),
+            ]),
+        ),
+    ])
+}
+
+#[test]
+fn bencode_streaming_byte_by_byte() {
+    let mut stream = Vec::new();
+    for value in [torrent(), pack!([1, [2, []]]), PackValue::Integer(-7)] {
+        stream.extend(BencodeEncoder::new().encode(&value));
+    }
+    let mut decoder = BencodeStreamingDecoder::new();
+    let mut values = Vec::new();
+    for &byte in &stream {
+        decoder.push(&[byte]).unwrap();
+        while let Some(value) = decoder.read_value().unwrap() {
+            values.push(value);
+        }
+    }
+    assert_eq!(
+        values,
+        [torrent(), pack!([1, [2, []]]), PackValue::Integer(-7)]
+    );
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.read_value().unwrap(), None);
+}
+
+#[test]
+fn bencode_streaming_chunks() {
+    let mut decoder = BencodeStreamingDecoder::new();
+    decoder.push(b"d1:ai1ee4:sp").unwrap();
+    assert_eq!(
+        decoder.read_value().unwrap(),
+        Some(PackValue::Object(vec![("a".into(), PackValue::Integer(1))]))
+    );
+    assert_eq!(decoder.read_value().unwrap(), None);
+    assert_eq!(decoder.buffered(), 4);
+    decoder.push(b"amli").unwrap();
+    assert_eq!(
+        decoder.read_value().unwrap(),
+        Some(PackValue::Bytes(b"spam".to_vec()))
+    );
+    assert_eq!(decoder.read_value().unwrap(), None);
+    decoder.push(b"2ee").unwrap();
+    assert_eq!(decoder.read_value().unwrap(), Some(pack!([2])));
+
+    decoder.push(b"x").unwrap();
+    assert!(matches!(
+        decoder.read_value(),
+        Err(BencodeError::InvalidByte(0))
+    ));
+}
+
+#[test]
+fn bencode_streaming_resumes_scan() {
+    // Byte-by-byte input of a long list is scanned once, not re-parsed on
+    // every call.
+    let list = PackValue::Array((0..50_000).map(PackValue::Integer).collect());
+    let bytes = BencodeEncoder::new().encode(&list);
+    let mut decoder = BencodeStreamingDecoder::new();
+    let (last, head) = bytes.split_last().unwrap();
+    for &byte in head {
+        decoder.push(&[byte]).unwrap();
+        assert_eq!(decoder.read_value().unwrap(), None);
+    }
+    decoder.push(&[*last]).unwrap();
+    assert_eq!(decoder.read_value().unwrap(), Some(list));
+
+    // Malformed input reports what the decoder reports.
+    for input in [
+        &b"li1exe"[..],
+        b"i12345678901234567890123456e",
+        b"12345678901:",
+    ] {
+        let mut decoder = BencodeStreamingDecoder::new();
+        decoder.push(input).unwrap();
+        let err = decoder.read_value().unwrap_err();
+        let expected = BencodeDecoder::new().decode(input).unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string(), "{input:?}");
+    }
+    let mut decoder = BencodeStreamingDecoder::new();
+    decoder.push(b"d1:\xffi1e").unwrap();
+    assert_eq!(decoder.read_value().unwrap(), None);
+    decoder.push(b"e").unwrap();
+    assert!(matches!(
+        decoder.read_value(),
+        Err(BencodeError::InvalidUtf8(3))
+    ));
+}
+
+#[test]
+fn bencode_streaming_max_buffer() {
+    let mut decoder = BencodeStreamingDecoder::new().with_max_buffer(8);
+    decoder.push(b"4:spam").unwrap();
+    assert!(matches!(
+        decoder.push(b"i10e"),
+        Err(BencodeError::BufferFull(8))
+    ));
+    assert_eq!(decoder.buffered(), 6);
+    decoder.push(b"i1").unwrap();
+    assert_eq!(
+        decoder.read_value().unwrap(),
+        Some(PackValue::Bytes(b"spam".to_vec()))
+    );
+    // Reading a value frees its bytes.
+    decoder.push(b"0e").unwrap();
+    assert_eq!(decoder.read_value().unwrap(), Some(PackValue::Integer(10)));
+    assert_eq!(BencodeError::BufferFull(8).offset(), None);
+
+    // A value longer than the limit never fits.
+    let mut decoder = BencodeStreamingDecoder::new().with_max_buffer(8);
+    decoder.push(b"9:abcdef").unwrap();
+    assert_eq!(decoder.read_value().unwrap(), None);
+    assert!(decoder.push(b"ghi").is_err());
+}
+
+#[test]
+fn bencode_limits_matrix() {
+    let options = BencodeDecoderOptions {
+        max_string_len: Some(4),
+        max_depth: Some(2),
+    };
+    let decoder = BencodeDecoder::with_options(options.clone());
+    for (input, ok) in [
+        (&b"4:spam"[..], true),
+        (b"5:spams", false),
+        (b"ll4:spamee", true),
+        (b"llleee", false),
+        (b"d1:ad1:bi1eee", true),
+        (b"d1:ad1:bleee", false),
+        (b"i99999999999e", true),
+    ] {
+        assert_eq!(decoder.decode(input).is_ok(), ok, "{input:?}");
+        // No limits: everything decodes.
+        assert!(BencodeDecoder::new().decode(input).is_ok(), "{input:?}");
+    }
+    assert!(matches!(
+        decoder.decode(b"l1:a12:abcdefghijkle"),
+        Err(BencodeError::StringTooLong(4))
+    ));
+    assert!(matches!(
+        decoder.decode(b"llleee"),
+        Err(BencodeError::TooDeep(2))
+    ));
+    assert_eq!(BencodeError::TooDeep(2).offset(), Some(2));
+
+    // A declared length over the limit fails before its bytes arrive.
+    let mut stream = BencodeStreamingDecoder::with_options(options);
+    stream.push(b"4294967295:").unwrap();
+    assert!(matches!(
+        stream.read_value(),
+        Err(BencodeError::StringTooLong(0))
+    ));
+    let mut stream = BencodeStreamingDecoder::with_options(BencodeDecoderOptions {
+        max_depth: Some(100),
+        ..Default::default()
+    });
+    stream.push(&[b'l'; 101]).unwrap();
+    assert!(matches!(
+        stream.read_value(),
+        Err(BencodeError::TooDeep(100))
+    ));
+}
+
+#[test]
+fn bencode_decode_prefix_reports_length() {
+    let decoder = BencodeDecoder::new();
+    let (value, used) = decoder.decode_prefix(b"i42eXYZ").unwrap();
+    assert_eq!((value, used), (PackValue::Integer(42), 4));
+    assert!(matches!(
+        decoder.decode_prefix(b"l"),
+        Err(BencodeError::UnexpectedEof(1))
+    ));
+}
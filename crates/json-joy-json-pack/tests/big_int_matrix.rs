@@ -0,0 +1,166 @@
+//! Arbitrary-precision integers (`PackValue::BigNum`) across codecs.
+//!
+//! Not an upstream port: upstream relies on native JS `bigint`.
+
+use json_joy_json_pack::cbor::{
+    CborDecoder, CborDecoderOptions, CborEncoder, CborEncoderDag, CborEncoderFast,
+    CborEncoderStable,
+};
+use json_joy_json_pack::json::{JsonBigIntPolicy, JsonDecoder, JsonEncoder, JsonEncoderOptions};
+use json_joy_json_pack::msgpack::constants::EXT_BIG_INT;
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackDecoderOptions, MsgPackEncoderFast};
+use json_joy_json_pack::{JsonPackBigInt, JsonPackExtension, PackValue};
+
+const BIG: &str = "340282366920938463463374607431768211456"; // 2^128
+const NEG_BIG: &str = "-340282366920938463463374607431768211457"; // -2^128 - 1
+
+fn big(s: &str) -> JsonPackBigInt {
+    s.parse().expect("parse big int")
+}
+
+#[test]
+fn big_int_decimal_roundtrip_matrix() {
+    for s in [
+        "0",
+        "1",
+        "-1",
+        "255",
+        "256",
+        "-9223372036854775808",
+        "18446744073709551616",
+        BIG,
+        NEG_BIG,
+        "123456789012345678901234567890123456789012345678901234567890",
+    ] {
+        assert_eq!(big(s).to_string(), s);
+    }
+    assert_eq!(big("-0"), JsonPackBigInt::default());
+    assert_eq!(big("+0042").to_string(), "42");
+    // Long literals take whole nine-digit chunks plus a short one.
+    let long = "9876543210".repeat(2_000);
+    assert_eq!(big(&long).to_string(), long);
+    assert_eq!(big(&format!("-{long}1")).to_string(), format!("-{long}1"));
+    assert!("".parse::<JsonPackBigInt>().is_err());
+    assert!("-".parse::<JsonPackBigInt>().is_err());
+    assert!("12a".parse::<JsonPackBigInt>().is_err());
+}
+
+#[test]
+fn big_int_i128_and_bytes_matrix() {
+    for v in [0i128, 1, -1, 127, -128, 128, -129, i128::MAX, i128::MIN] {
+        let n = JsonPackBigInt::from_i128(v);
+        assert_eq!(n.to_i128(), Some(v));
+        assert_eq!(n.to_string(), v.to_string());
+        assert_eq!(
+            JsonPackBigInt::from_twos_complement(&n.to_twos_complement()),
+            n
+        );
+    }
+    assert_eq!(big(BIG).to_i128(), None);
+    assert_eq!(
+        big(BIG).magnitude(),
+        &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        JsonPackBigInt::from_i128(-1).to_twos_complement(),
+        vec![0xff]
+    );
+    assert_eq!(
+        JsonPackBigInt::from_i128(128).to_twos_complement(),
+        vec![0, 0x80]
+    );
+    assert_eq!(
+        JsonPackBigInt::from_i128(-128).to_twos_complement(),
+        vec![0x80]
+    );
+    assert!(JsonPackBigInt::from_i128(0).to_twos_complement().is_empty());
+}
+
+#[test]
+fn big_int_cbor_bignum_matrix() {
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        bignum: true,
+        ..CborDecoderOptions::default()
+    });
+    let value = PackValue::BigNum(big(BIG));
+    let mut expected = vec![0xc2, 0x51, 0x01];
+    expected.extend_from_slice(&[0; 16]);
+
+    assert_eq!(CborEncoder::new().encode(&value), expected);
+    assert_eq!(CborEncoderFast::new().encode(&value), expected);
+    assert_eq!(CborEncoderStable::new().encode(&value), expected);
+    assert_eq!(decoder.decode(&expected).unwrap(), value);
+
+    // Tag 3 carries `-1 - n`, so -2^128 - 1 has payload 2^128.
+    let neg = PackValue::BigNum(big(NEG_BIG));
+    let mut neg_expected = expected.clone();
+    neg_expected[0] = 0xc3;
+    assert_eq!(CborEncoder::new().encode(&neg), neg_expected);
+    assert_eq!(decoder.decode(&neg_expected).unwrap(), neg);
+
+    // Default decoding keeps upstream's extension representation.
+    assert_eq!(
+        CborDecoder::new().decode(&expected).unwrap(),
+        PackValue::Extension(Box::new(JsonPackExtension::new(
+            2,
+            PackValue::Bytes(expected[2..].to_vec())
+        )))
+    );
+
+    // Values within the 64-bit argument range use plain integers.
+    let small = PackValue::BigNum(big("-18446744073709551616"));
+    let encoded = CborEncoder::new().encode(&small);
+    assert_eq!(
+        encoded,
+        [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+
+    // DAG-CBOR has no bignum tags.
+    assert_eq!(CborEncoderDag::new().encode(&value), [0xf6]);
+}
+
+#[test]
+fn big_int_json_policy_matrix() {
+    let value = PackValue::Array(vec![
+        PackValue::BigNum(big(BIG)),
+        PackValue::BigInt(-170141183460469231731687303715884105728),
+    ]);
+    let mut encoder = JsonEncoder::new();
+    let number = encoder.encode(&value);
+    assert_eq!(
+        String::from_utf8(number.clone()).unwrap(),
+        format!("[{BIG},-170141183460469231731687303715884105728]")
+    );
+    let decoded = JsonDecoder::new().decode(&number).unwrap();
+    assert_eq!(decoded, value);
+
+    let mut encoder = JsonEncoder::with_options(JsonEncoderOptions {
+        big_int: JsonBigIntPolicy::String,
+    });
+    let string = encoder.encode(&value);
+    assert_eq!(
+        String::from_utf8(string).unwrap(),
+        format!("[\"{BIG}\",\"-170141183460469231731687303715884105728\"]")
+    );
+}
+
+#[test]
+fn big_int_msgpack_ext_matrix() {
+    let value = PackValue::BigNum(big(NEG_BIG));
+    let encoded = MsgPackEncoderFast::new().encode(&value);
+    assert_eq!(encoded[0], 0xc7);
+    assert_eq!(encoded[1], 17);
+    assert_eq!(encoded[2] as i8, EXT_BIG_INT);
+
+    let mut decoder = MsgPackDecoderFast::with_options(MsgPackDecoderOptions {
+        big_int: true,
+        ..Default::default()
+    });
+    assert_eq!(decoder.decode(&encoded).unwrap(), value);
+
+    let mut plain = MsgPackDecoderFast::new();
+    assert!(matches!(
+        plain.decode(&encoded).unwrap(),
+        PackValue::Extension(ext) if ext.tag == EXT_BIG_INT as u64
+    ));
+}
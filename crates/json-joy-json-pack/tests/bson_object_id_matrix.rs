@@ -0,0 +1,117 @@
+//! `BsonObjectId` generation, hex form, ordering and timestamps.
+//!
+//! Not an upstream port: upstream's `BsonObjectId` only holds the three
+//! fields.
+
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use json_joy_json_pack::bson::{BsonDecoder, BsonEncoder, BsonObjectId, BsonValue};
+use json_joy_json_pack::ejson::{EjsonDecoder, EjsonEncoder, EjsonValue};
+
+#[test]
+fn bson_object_id_hex_matrix() {
+    let id = BsonObjectId {
+        timestamp: 0x5f1d_7a2b,
+        process: 0x00_1122_3344,
+        counter: 0x00aa_bbcc,
+    };
+    assert_eq!(id.to_hex(), "5f1d7a2b0011223344aabbcc");
+    assert_eq!(id.to_string(), id.to_hex());
+    assert_eq!(BsonObjectId::parse_hex(&id.to_hex()), Some(id.clone()));
+    assert_eq!(
+        BsonObjectId::parse_hex("5F1D7A2B0011223344AABBCC"),
+        Some(id.clone())
+    );
+
+    let invalid = [
+        "",
+        "5f1d7a2b0011223344aabbc",
+        "5f1d7a2b0011223344aabbccd",
+        "5f1d7a2b0011223344aabbcg",
+        "+f1d7a2b0011223344aabbcc",
+        "5f1d7a2b00112233é4aabbc",
+    ];
+    for hex in invalid {
+        assert_eq!(BsonObjectId::parse_hex(hex), None, "{hex:?}");
+    }
+
+    // EJSON `$oid` goes through the same hex form.
+    let json = EjsonEncoder::new()
+        .encode_to_string(&EjsonValue::ObjectId(id.clone()))
+        .unwrap();
+    assert_eq!(json, r#"{"$oid":"5f1d7a2b0011223344aabbcc"}"#);
+    assert_eq!(
+        EjsonDecoder::new().decode_str(&json).unwrap(),
+        EjsonValue::ObjectId(id)
+    );
+}
+
+#[test]
+fn bson_object_id_new_matrix() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    let ids: Vec<BsonObjectId> = (0..1000).map(|_| BsonObjectId::new()).collect();
+    let first = &ids[0];
+    for (i, id) in ids.iter().enumerate() {
+        assert!(id.timestamp.abs_diff(now) <= 5, "{id}");
+        assert_eq!(id.process, first.process);
+        assert!(id.process < 1 << 40);
+        assert_eq!(id.counter, (first.counter + i as u32) & 0xFF_FFFF);
+        assert_eq!(BsonObjectId::parse_hex(&id.to_hex()).as_ref(), Some(id));
+    }
+    let unique: HashSet<_> = ids.iter().collect();
+    assert_eq!(unique.len(), ids.len());
+
+    // A caller-supplied time, for targets without a clock.
+    let id = BsonObjectId::with_timestamp(7);
+    assert_eq!(id.timestamp, 7);
+    assert_eq!(id.process, first.process);
+
+    // Generated ids survive the BSON wire format.
+    let doc = vec![("_id".to_string(), BsonValue::ObjectId(first.clone()))];
+    let bytes = BsonEncoder::new().encode(&doc);
+    assert_eq!(BsonDecoder::new().decode(&bytes).unwrap(), doc);
+}
+
+#[test]
+fn bson_object_id_order_matrix() {
+    let id = |timestamp, process, counter| BsonObjectId {
+        timestamp,
+        process,
+        counter,
+    };
+    let mut ids = [
+        id(2, 0, 0),
+        id(1, 0xFF_FFFF_FFFF, 0xFF_FFFF),
+        id(1, 1, 0),
+        id(1, 0, 5),
+        BsonObjectId::from_timestamp(1),
+    ];
+    ids.sort();
+    let hex: Vec<String> = ids.iter().map(BsonObjectId::to_hex).collect();
+    let mut sorted = hex.clone();
+    sorted.sort();
+    assert_eq!(hex, sorted);
+    assert_eq!(ids[0], id(1, 0, 0));
+    assert_eq!(ids[4], id(2, 0, 0));
+
+    // `from_timestamp` bounds every id created in that second.
+    let generated = BsonObjectId::new();
+    assert!(BsonObjectId::from_timestamp(generated.timestamp) <= generated);
+    assert!(generated < BsonObjectId::from_timestamp(generated.timestamp + 1));
+
+    // Bits the wire format drops take no part in comparisons.
+    let wide = id(1, 0x0100_0000_0002, 0x0100_0003);
+    assert_eq!(wide, id(1, 2, 3));
+    assert_eq!(wide.cmp(&id(1, 2, 3)), std::cmp::Ordering::Equal);
+    assert!(wide < id(1, 2, 4));
+    let set: HashSet<_> = [wide.clone(), id(1, 2, 3)].into_iter().collect();
+    assert_eq!(set.len(), 1);
+    assert_eq!(wide.to_hex(), id(1, 2, 3).to_hex());
+
+    assert_eq!(id(1_700_000_000, 0, 0).timestamp_ms(), 1_700_000_000_000);
+    assert_eq!(id(u32::MAX, 0, 0).timestamp_ms(), 4_294_967_295_000);
+}
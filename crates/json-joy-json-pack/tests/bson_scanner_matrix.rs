@@ -0,0 +1,206 @@
+//! Field-by-field BSON document scanning.
+//!
+//! Not an upstream port: upstream only decodes whole documents.
+
+use json_joy_json_pack::bson::{
+    BsonBinary, BsonDbPointer, BsonDecoder, BsonElement, BsonEncoder, BsonError,
+    BsonJavascriptCodeWithScope, BsonObjectId, BsonScanner, BsonValue,
+};
+
+fn command() -> Vec<(String, BsonValue)> {
+    vec![
+        ("insert".into(), BsonValue::Str("users".into())),
+        ("ordered".into(), BsonValue::Boolean(true)),
+        (
+            "documents".into(),
+            BsonValue::Array(vec![
+                BsonValue::Document(vec![
+                    ("_id".into(), BsonValue::Int32(1)),
+                    ("re".into(), BsonValue::Regex("^a".into(), "i".into())),
+                ]),
+                BsonValue::Document(vec![("_id".into(), BsonValue::Int64(2))]),
+            ]),
+        ),
+        (
+            "bin".into(),
+            BsonValue::Binary(BsonBinary {
+                subtype: 0x02,
+                data: vec![9, 9],
+            }),
+        ),
+        (
+            "ptr".into(),
+            BsonValue::DbPointer(BsonDbPointer {
+                name: "c".into(),
+                id: BsonObjectId {
+                    timestamp: 1,
+                    process: 2,
+                    counter: 3,
+                },
+            }),
+        ),
+        (
+            "fn".into(),
+            BsonValue::JavaScriptCodeWithScope(BsonJavascriptCodeWithScope {
+                code: "x".into(),
+                scope: vec![("x".into(), BsonValue::Null)],
+            }),
+        ),
+        ("maxTimeMS".into(), BsonValue::Float(1.5)),
+        ("at".into(), BsonValue::DateTime(-5)),
+        ("$db".into(), BsonValue::Str("app".into())),
+    ]
+}
+
+#[test]
+fn bson_scanner_iteration_matrix() {
+    let doc = command();
+    let bytes = BsonEncoder::new().encode(&doc);
+    let scanner = BsonScanner::new(&bytes).unwrap();
+    assert_eq!(scanner.size(), bytes.len());
+
+    let elements: Vec<_> = scanner.map(Result::unwrap).collect();
+    assert_eq!(elements.len(), doc.len());
+    for (element, (name, value)) in elements.iter().zip(&doc) {
+        assert_eq!(element.name, name);
+        assert_eq!(bytes[element.offset - name.len() - 2], element.typ);
+        assert_eq!(
+            &bytes[element.offset..element.offset + element.value.len()],
+            element.value
+        );
+        assert_eq!(&element.decode().unwrap(), value, "{name}");
+    }
+
+    assert_eq!(elements[0].as_str(), Some("users"));
+    assert_eq!(elements[1].as_bool(), Some(true));
+    assert_eq!(elements[6].as_f64(), Some(1.5));
+    assert_eq!(elements[7].as_i64(), Some(-5));
+    assert_eq!(elements[0].as_i32(), None);
+    assert!(elements[0].scan().is_none());
+}
+
+#[test]
+fn bson_scanner_find_skip_matrix() {
+    let bytes = BsonEncoder::new().encode(&command());
+    let mut scanner = BsonScanner::new(&bytes).unwrap();
+    assert!(scanner.skip_field().unwrap());
+    let docs = scanner.find_field("documents").unwrap().unwrap();
+    assert_eq!(
+        scanner.find_field("$db").unwrap().unwrap().as_str(),
+        Some("app")
+    );
+    assert!(!scanner.skip_field().unwrap());
+    // Fields are found in document order only.
+    assert_eq!(scanner.find_field("insert").unwrap(), None);
+
+    // Nested arrays scan with absolute offsets into the outer buffer.
+    let mut items = docs.scan().unwrap();
+    let second = items.find_field("1").unwrap().unwrap();
+    let id = second.scan().unwrap().find_field("_id").unwrap().unwrap();
+    assert_eq!(id.as_i64(), Some(2));
+    assert_eq!(
+        i64::from_le_bytes(bytes[id.offset..id.offset + 8].try_into().unwrap()),
+        2
+    );
+
+    // Bytes after the declared document are not scanned.
+    let mut trailing = bytes.clone();
+    trailing.extend_from_slice(&[0xde, 0xad]);
+    let scanner = BsonScanner::new(&trailing).unwrap();
+    assert_eq!(scanner.size(), bytes.len());
+    assert_eq!(scanner.count(), command().len());
+}
+
+#[test]
+fn bson_scanner_error_matrix() {
+    assert_eq!(
+        BsonScanner::new(&[5, 0]).unwrap_err(),
+        BsonError::UnexpectedEof(2)
+    );
+    assert_eq!(
+        BsonScanner::new(&[4, 0, 0, 0, 0]).unwrap_err(),
+        BsonError::InvalidLength(0)
+    );
+    assert_eq!(
+        BsonScanner::new(&[6, 0, 0, 0, 0]).unwrap_err(),
+        BsonError::UnexpectedEof(0)
+    );
+    assert_eq!(
+        BsonScanner::new(&[5, 0, 0, 0, 1]).unwrap_err(),
+        BsonError::InvalidLength(0)
+    );
+
+    // Unsupported type, then iteration stops.
+    let unsupported = [8, 0, 0, 0, 0x14, b'a', 0, 0];
+    let mut scanner = BsonScanner::new(&unsupported).unwrap();
+    assert_eq!(
+        scanner.next(),
+        Some(Err(BsonError::UnsupportedType(0x14, 4)))
+    );
+    assert_eq!(scanner.next(), None);
+
+    // String length running past the document end.
+    let overrun = [13, 0, 0, 0, 0x02, b'a', 0, 9, 0, 0, 0, 0, 0];
+    assert_eq!(
+        BsonScanner::new(&overrun).unwrap().next_element(),
+        Err(BsonError::UnexpectedEof(7))
+    );
+
+    // Negative binary length.
+    let negative = [13, 0, 0, 0, 0x05, b'a', 0, 0xff, 0xff, 0xff, 0xff, 0, 0];
+    assert_eq!(
+        BsonScanner::new(&negative).unwrap().next_element(),
+        Err(BsonError::InvalidLength(7))
+    );
+
+    // Unterminated field name.
+    let name = [7, 0, 0, 0, 0x0a, b'a', 0];
+    assert_eq!(
+        BsonScanner::new(&name).unwrap().next_element(),
+        Err(BsonError::UnexpectedEof(5))
+    );
+
+    // Framing is valid but the value is not: decode reports the absolute offset.
+    let bad_utf8 = [14, 0, 0, 0, 0x02, b'a', 0, 2, 0, 0, 0, 0xff, 0, 0];
+    let element = BsonScanner::new(&bad_utf8)
+        .unwrap()
+        .next_element()
+        .unwrap()
+        .unwrap();
+    assert_eq!(element.as_str(), None);
+    assert_eq!(element.decode(), Err(BsonError::InvalidUtf8(11)));
+    assert_eq!(
+        BsonDecoder::new().decode(&bad_utf8),
+        Err(BsonError::InvalidUtf8(11))
+    );
+
+    // Hand-built elements with the wrong value length give `None`.
+    let cases: &[(u8, &[u8])] = &[
+        (0x10, &[1]),
+        (0x12, &[1, 2, 3, 4]),
+        (0x09, &[]),
+        (0x01, &[0; 9]),
+        (0x02, &[]),
+        (0x02, &[1, 0]),
+        (0x08, &[]),
+    ];
+    for &(typ, value) in cases {
+        let element = BsonElement {
+            name: "a",
+            typ,
+            value,
+            offset: 0,
+        };
+        assert_eq!(
+            (
+                element.as_i32(),
+                element.as_i64(),
+                element.as_f64(),
+                element.as_str(),
+                element.as_bool(),
+            ),
+            (None, None, None, None, None),
+            "{typ:#04x} {value:?}"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! BSON element type table: wire bytes per type, round-trips through
+//! `EjsonValue`, old-binary framing and malformed length prefixes.
+//!
+//! Not an upstream port: upstream has no `EjsonValue` <-> `BsonValue`
+//! conversion and stores old-binary (subtype 0x02) payloads unframed.
+
+use json_joy_json_pack::bson::{
+    BsonBinary, BsonDbPointer, BsonDecimal128, BsonDecoder, BsonEncoder, BsonError,
+    BsonJavascriptCode, BsonJavascriptCodeWithScope, BsonObjectId, BsonSymbol, BsonTimestamp,
+    BsonValue,
+};
+use json_joy_json_pack::ejson::{EjsonDecoder, EjsonEncoder, EjsonValue};
+
+fn object_id() -> BsonObjectId {
+    BsonObjectId {
+        timestamp: 0x5f1d_7a2b,
+        process: 0x00_1122_3344,
+        counter: 0x00aa_bbcc,
+    }
+}
+
+/// One value per element type, with the type byte BSON tags it with.
+fn type_table() -> Vec<(u8, BsonValue)> {
+    vec![
+        (0x01, BsonValue::Float(-2.5)),
+        (0x02, BsonValue::Str("héllo".into())),
+        (
+            0x03,
+            BsonValue::Document(vec![("a".into(), BsonValue::Int32(1))]),
+        ),
+        (
+            0x04,
+            BsonValue::Array(vec![
+                BsonValue::Int32(1),
+                BsonValue::Array(vec![BsonValue::Str("nested".into())]),
+                BsonValue::Null,
+            ]),
+        ),
+        (
+            0x05,
+            BsonValue::Binary(BsonBinary {
+                subtype: 0x00,
+                data: vec![1, 2, 3],
+            }),
+        ),
+        (
+            0x05,
+            BsonValue::Binary(BsonBinary {
+                subtype: 0x04,
+                data: (0..16).collect(),
+            }),
+        ),
+        (
+            0x05,
+            BsonValue::Binary(BsonBinary {
+                subtype: 0x80,
+                data: vec![],
+            }),
+        ),
+        (0x06, BsonValue::Undefined),
+        (0x07, BsonValue::ObjectId(object_id())),
+        (0x08, BsonValue::Boolean(false)),
+        (0x09, BsonValue::DateTime(1_700_000_000_123)),
+        (0x09, BsonValue::DateTime(-1)),
+        (0x0a, BsonValue::Null),
+        (0x0b, BsonValue::Regex("^a.c$".into(), "im".into())),
+        (
+            0x0c,
+            BsonValue::DbPointer(BsonDbPointer {
+                name: "db.users".into(),
+                id: object_id(),
+            }),
+        ),
+        (
+            0x0d,
+            BsonValue::JavaScriptCode(BsonJavascriptCode {
+                code: "return 1".into(),
+            }),
+        ),
+        (
+            0x0e,
+            BsonValue::Symbol(BsonSymbol {
+                symbol: "sym".into(),
+            }),
+        ),
+        (
+            0x0f,
+            BsonValue::JavaScriptCodeWithScope(BsonJavascriptCodeWithScope {
+                code: "return x".into(),
+                scope: vec![
+                    ("x".into(), BsonValue::Int32(7)),
+                    ("re".into(), BsonValue::Regex("b".into(), "".into())),
+                ],
+            }),
+        ),
+        (0x10, BsonValue::Int32(i32::MIN)),
+        (
+            0x11,
+            BsonValue::Timestamp(BsonTimestamp {
+                increment: 3,
+                timestamp: 1_700_000_000,
+            }),
+        ),
+        (0x12, BsonValue::Int64(i64::MAX)),
+        (
+            0x13,
+            BsonValue::Decimal128(BsonDecimal128 {
+                data: (1..=16).collect(),
+            }),
+        ),
+        (0xff, BsonValue::MinKey),
+        (0x7f, BsonValue::MaxKey),
+    ]
+}
+
+#[test]
+fn bson_type_byte_matrix() {
+    let encoder = BsonEncoder::new();
+    let mut decoder = BsonDecoder::new();
+    for (typ, value) in type_table() {
+        let doc = vec![("v".to_owned(), value)];
+        let encoded = encoder.encode(&doc);
+        assert_eq!(encoded[4], typ, "{doc:?}");
+        assert_eq!(&encoded[5..7], b"v\0");
+        assert_eq!(decoder.decode(&encoded).unwrap(), doc);
+    }
+
+    // Arrays are documents keyed by decimal indices.
+    let doc = vec![(
+        "a".to_owned(),
+        BsonValue::Array(vec![BsonValue::Boolean(true), BsonValue::Null]),
+    )];
+    let encoded = encoder.encode(&doc);
+    assert_eq!(
+        &encoded[7..],
+        &[12, 0, 0, 0, 0x08, b'0', 0, 1, 0x0a, b'1', 0, 0, 0]
+    );
+}
+
+#[test]
+fn bson_ejson_round_trip_matrix() {
+    let mut decoder = EjsonDecoder::new();
+    for (_, value) in type_table() {
+        let ejson = EjsonValue::from(value.clone());
+        assert_eq!(BsonValue::from(ejson.clone()), value);
+
+        // Decimal128 text conversion is a stub in the EJSON codec.
+        if matches!(value, BsonValue::Decimal128(_)) {
+            continue;
+        }
+        let mut encoder = EjsonEncoder::canonical();
+        let text = encoder.encode_to_string(&ejson).unwrap();
+        let decoded = decoder.decode_str(&text).unwrap();
+        assert_eq!(BsonValue::from(decoded), value, "{text}");
+    }
+
+    // Untyped JSON numbers become int32 when they fit, as upstream, else
+    // int64, unless they carry a fraction.
+    let cases = [
+        (5, BsonValue::Int32(5)),
+        (i32::MAX as i64, BsonValue::Int32(i32::MAX)),
+        (i32::MIN as i64, BsonValue::Int32(i32::MIN)),
+        (i32::MAX as i64 + 1, BsonValue::Int64(i32::MAX as i64 + 1)),
+        (i32::MIN as i64 - 1, BsonValue::Int64(i32::MIN as i64 - 1)),
+    ];
+    for (int, bson) in cases {
+        assert_eq!(BsonValue::from(EjsonValue::Integer(int)), bson);
+        assert_eq!(BsonValue::from(EjsonValue::Number(int as f64)), bson);
+    }
+    assert_eq!(
+        BsonValue::from(EjsonValue::Number(0.5)),
+        BsonValue::Float(0.5)
+    );
+}
+
+#[test]
+fn bson_old_binary_matrix() {
+    let doc = vec![(
+        "b".to_owned(),
+        BsonValue::Binary(BsonBinary {
+            subtype: 0x02,
+            data: vec![0xaa, 0xbb],
+        }),
+    )];
+    let encoded = BsonEncoder::new().encode(&doc);
+    assert_eq!(&encoded[7..18], &[6, 0, 0, 0, 0x02, 2, 0, 0, 0, 0xaa, 0xbb]);
+    assert_eq!(BsonDecoder::new().decode(&encoded).unwrap(), doc);
+
+    // Inner length must match the outer length minus its own four bytes.
+    let mut bad = encoded.clone();
+    bad[12] = 3;
+    assert_eq!(
+        BsonDecoder::new().decode(&bad),
+        Err(BsonError::InvalidLength(12))
+    );
+    let mut short = encoded;
+    short[7] = 3;
+    assert_eq!(
+        BsonDecoder::new().decode(&short),
+        Err(BsonError::InvalidLength(7))
+    );
+}
+
+#[test]
+fn bson_length_error_matrix() {
+    let mut decoder = BsonDecoder::new();
+
+    // Document sizes below the five-byte minimum or negative.
+    for size in [0i32, 4, -1, i32::MIN] {
+        let mut bytes = size.to_le_bytes().to_vec();
+        bytes.push(0);
+        assert_eq!(decoder.decode(&bytes), Err(BsonError::InvalidLength(0)));
+    }
+    assert_eq!(
+        decoder.decode(&[6, 0, 0, 0, 0]),
+        Err(BsonError::UnexpectedEof(0))
+    );
+
+    // Negative string length.
+    let negative = [13, 0, 0, 0, 0x02, b'a', 0, 0xff, 0xff, 0xff, 0xff, 0, 0];
+    assert_eq!(decoder.decode(&negative), Err(BsonError::InvalidLength(7)));
+
+    // String whose declared length does not end on its null terminator.
+    let unterminated = [14, 0, 0, 0, 0x02, b'a', 0, 2, 0, 0, 0, b'x', b'y', 0];
+    assert_eq!(
+        decoder.decode(&unterminated),
+        Err(BsonError::InvalidLength(7))
+    );
+
+    // Negative binary length.
+    let negative_bin = [13, 0, 0, 0, 0x05, b'a', 0, 0xfe, 0xff, 0xff, 0xff, 0, 0];
+    assert_eq!(
+        decoder.decode(&negative_bin),
+        Err(BsonError::InvalidLength(7))
+    );
+
+    // Truncated nested array.
+    let truncated = [12, 0, 0, 0, 0x04, b'a', 0, 9, 0, 0, 0, 0];
+    assert_eq!(decoder.decode(&truncated), Err(BsonError::UnexpectedEof(7)));
+}
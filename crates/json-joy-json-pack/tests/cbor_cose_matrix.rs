@@ -0,0 +1,230 @@
+//! COSE_Sign1 and COSE_Mac0 structures, checked against RFC 9052
+//! Appendix C and hand-assembled bytes. A toy digest stands in for the
+//! cryptography.
+//!
+//! Not an upstream port: upstream has no COSE support.
+
+use json_joy_json_pack::cbor::cose::*;
+use json_joy_json_pack::cbor::CborError;
+use json_joy_json_pack::PackValue;
+
+const CONTENT: &[u8] = b"This is the content.";
+
+/// Not a signature: enough to tell inputs apart.
+fn toy(tbs: &[u8]) -> Vec<u8> {
+    let mut h = 0xcbf2_9ce4_8422_2325_u64;
+    for &b in tbs {
+        h = (h ^ b as u64).wrapping_mul(0x100_0000_01b3);
+    }
+    h.to_be_bytes().to_vec()
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn cbor_cose_header_matrix() {
+    let map = CoseHeaderMap::new()
+        .with("z", PackValue::Bool(true))
+        .with(-1, PackValue::Integer(7))
+        .with_kid(*b"11")
+        .with_alg(COSE_ALG_ES256)
+        .with(24, PackValue::Str("x".into()));
+    assert_eq!(map.alg(), Some(-7));
+    assert_eq!(map.kid(), Some(&b"11"[..]));
+    assert_eq!(map.content_type(), None);
+
+    // Labels sorted by their encoded bytes: 1, 4, 24, -1, "z".
+    let bytes = map.encode();
+    assert_eq!(bytes, hex("a5012604423131181861782007617af5"));
+    let decoded = CoseHeaderMap::decode(&bytes).unwrap();
+    assert_eq!(decoded.encode(), bytes);
+    assert_eq!(
+        decoded.get(&CoseLabel::from("z")),
+        Some(&PackValue::Bool(true))
+    );
+
+    // Replacing keeps one entry.
+    let map = map.with_alg(COSE_ALG_EDDSA);
+    assert_eq!((map.entries.len(), map.alg()), (5, Some(-8)));
+
+    // An empty protected header is an empty byte string.
+    assert_eq!(CoseHeaderMap::new().encode(), [0xa0]);
+    assert!(CoseHeaderMap::new().encode_protected().is_empty());
+    assert!(CoseHeaderMap::decode_protected(&[]).unwrap().is_empty());
+
+    for (bytes, err) in [
+        (hex("a201260127"), CoseError::DuplicateLabel),
+        (hex("a10126ff"), CoseError::InvalidStructure),
+        (
+            hex("a1f426"),
+            CoseError::Cbor(CborError::UnexpectedMajor(1)),
+        ),
+        (hex("a101"), CoseError::Cbor(CborError::UnexpectedEof(2))),
+        (hex("8101"), CoseError::Cbor(CborError::UnexpectedMajor(0))),
+    ] {
+        assert_eq!(CoseHeaderMap::decode(&bytes), Err(err), "{bytes:02x?}");
+    }
+}
+
+#[test]
+fn cbor_cose_sign1_matrix() {
+    let protected = CoseHeaderMap::new().with_alg(COSE_ALG_ES256);
+    let unprotected = CoseHeaderMap::new().with_kid(*b"11");
+    let sign = |detached| {
+        CoseSign1::sign(
+            &protected,
+            unprotected.clone(),
+            CONTENT,
+            detached,
+            b"",
+            |tbs| Ok::<_, ()>(toy(tbs)),
+        )
+        .unwrap()
+    };
+
+    // The ToBeSigned bytes of RFC 9052 Appendix C.2.1.
+    let tbs = hex("846a5369676e61747572653143a101264054546869732069732074686520636f6e74656e742e");
+    let msg = sign(false);
+    assert_eq!(msg.protected, hex("a10126"));
+    assert_eq!(msg.to_be_processed(b"", None).unwrap(), tbs);
+    assert_eq!(msg.signature, toy(&tbs));
+    assert_eq!(msg.protected_header().unwrap(), protected);
+
+    let bytes = msg.encode(true);
+    let mut expected = hex("d28443a10126a104423131");
+    expected.push(0x54);
+    expected.extend(CONTENT);
+    expected.extend(hex("48"));
+    expected.extend(toy(&tbs));
+    assert_eq!(bytes, expected);
+    assert_eq!(CoseSign1::decode(&bytes).unwrap(), msg);
+    assert_eq!(CoseSign1::decode(&msg.encode(false)).unwrap(), msg);
+
+    let check = |tbs: &[u8], sig: &[u8]| toy(tbs) == sig;
+    assert_eq!(msg.verify(b"", None, check), Ok(true));
+    assert_eq!(msg.verify(b"aad", None, check), Ok(false));
+    // An attached payload is never overridden by a detached one.
+    assert_eq!(
+        msg.verify(b"", Some(b"other"), check),
+        Err(CoseError::UnexpectedPayload)
+    );
+    let mut tampered = msg.clone();
+    tampered.payload.as_mut().unwrap()[0] ^= 1;
+    assert_eq!(tampered.verify(b"", None, check), Ok(false));
+
+    // Detached: nil in the message, supplied when checking.
+    let detached = sign(true);
+    assert_eq!(detached.signature, msg.signature);
+    let bytes = detached.encode(true);
+    assert_eq!(bytes[11], 0xf6);
+    let decoded = CoseSign1::decode(&bytes).unwrap();
+    assert_eq!(decoded.payload, None);
+    assert_eq!(
+        decoded.verify(b"", None, check),
+        Err(CoseError::MissingPayload)
+    );
+    assert_eq!(decoded.verify(b"", Some(CONTENT), check), Ok(true));
+    assert_eq!(decoded.verify(b"", Some(b"other"), check), Ok(false));
+
+    // Signer errors come back as they are.
+    assert_eq!(
+        CoseSign1::sign(&protected, unprotected.clone(), CONTENT, false, b"", |_| {
+            Err("hsm")
+        }),
+        Err("hsm")
+    );
+}
+
+#[test]
+fn cbor_cose_mac0_matrix() {
+    let protected = CoseHeaderMap::new().with_alg(COSE_ALG_HMAC_256_256);
+    let msg = CoseMac0::create(
+        &protected,
+        CoseHeaderMap::new(),
+        CONTENT,
+        false,
+        b"",
+        |tbs| Ok::<_, ()>(toy(tbs)),
+    )
+    .unwrap();
+    let mut tbs = hex("84644d41433043a101054054");
+    tbs.extend(CONTENT);
+    assert_eq!(msg.to_be_processed(b"", None).unwrap(), tbs);
+    assert_eq!(msg.tag, toy(&tbs));
+
+    let bytes = msg.encode(true);
+    assert_eq!(bytes[..7], hex("d18443a10105a0"));
+    assert_eq!(CoseMac0::decode(&bytes).unwrap(), msg);
+    let check = |tbs: &[u8], tag: &[u8]| toy(tbs) == tag;
+    assert_eq!(msg.verify(b"", None, check), Ok(true));
+
+    // An empty protected header MACs over an empty byte string.
+    let bare = CoseMac0::create(
+        &CoseHeaderMap::new(),
+        CoseHeaderMap::new(),
+        b"",
+        true,
+        b"",
+        |_| Ok::<_, ()>(vec![0; 4]),
+    )
+    .unwrap();
+    assert_eq!(
+        bare.to_be_processed(b"", Some(b"")).unwrap(),
+        hex("84644d414330404040")
+    );
+    assert_eq!(bare.encode(false), hex("8440a0f64400000000"));
+}
+
+#[test]
+fn cbor_cose_invalid_matrix() {
+    let msg = CoseSign1::sign(
+        &CoseHeaderMap::new().with_alg(COSE_ALG_ES256),
+        CoseHeaderMap::new(),
+        CONTENT,
+        false,
+        b"",
+        |tbs| Ok::<_, ()>(toy(tbs)),
+    )
+    .unwrap();
+    let tagged = msg.encode(true);
+
+    // The other message's tag, or a tag on top of the right one.
+    let mut as_mac0 = tagged.clone();
+    as_mac0[0] = 0xd1;
+    assert_eq!(
+        CoseSign1::decode(&as_mac0),
+        Err(CoseError::Cbor(CborError::UnexpectedTag(17)))
+    );
+    assert_eq!(
+        CoseMac0::decode(&tagged),
+        Err(CoseError::Cbor(CborError::UnexpectedTag(18)))
+    );
+    let mut nested = vec![0xd8, 0x40];
+    nested.extend(&tagged);
+    assert_eq!(
+        CoseSign1::decode(&nested),
+        Err(CoseError::Cbor(CborError::UnexpectedTag(64)))
+    );
+
+    let mut trailing = tagged.clone();
+    trailing.push(0);
+    for bytes in [trailing, hex("8340a0f6"), hex("d28540a0f64000")] {
+        assert_eq!(
+            CoseSign1::decode(&bytes),
+            Err(CoseError::InvalidStructure),
+            "{bytes:02x?}"
+        );
+    }
+    for bytes in [hex("84a0a0f640"), hex("8440a0f6f6"), hex("8440a0614040")] {
+        assert!(CoseSign1::decode(&bytes).is_err(), "{bytes:02x?}");
+    }
+    assert_eq!(
+        CoseSign1::decode(&tagged[..tagged.len() - 1]),
+        Err(CoseError::Cbor(CborError::UnexpectedEof(29)))
+    );
+}
@@ -0,0 +1,171 @@
+//! CWT claims sets, checked against the RFC 8392 Appendix A.1 example.
+//!
+//! Not an upstream port: upstream has no CWT support.
+
+use json_joy_json_pack::cbor::cose::{CoseError, CoseHeaderMap, CoseLabel, CoseSign1};
+use json_joy_json_pack::cbor::cwt::*;
+use json_joy_json_pack::cbor::{decode_cbor_value, CborError};
+use json_joy_json_pack::{JsonPackExtension, JsonPackValue, PackValue};
+
+/// RFC 8392 Appendix A.1, with untagged dates.
+const RFC_CLAIMS: &str = "a70175636f61703a2f2f61732e6578616d706c652e636f6d02656572696b77037818636f61703a2f2f6c696768742e6578616d706c652e636f6d041a5612aeb0051a5610d9f0061a5610d9f007420b71";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn date(secs: i64) -> PackValue {
+    PackValue::Extension(Box::new(JsonPackExtension::new(
+        1,
+        PackValue::Integer(secs),
+    )))
+}
+
+fn rfc_claims() -> CwtClaims {
+    CwtClaims {
+        iss: Some("coap://as.example.com".into()),
+        sub: Some("erikw".into()),
+        aud: Some("coap://light.example.com".into()),
+        exp: Some(1444064944),
+        nbf: Some(1443944944),
+        iat: Some(1443944944),
+        cti: Some(vec![0x0b, 0x71]),
+        other: vec![],
+    }
+}
+
+#[test]
+fn cbor_cwt_encode_matrix() {
+    let claims = rfc_claims();
+    assert_eq!(CwtClaims::decode(&hex(RFC_CLAIMS)).unwrap(), claims);
+
+    // The same map with each date under tag 1.
+    let tagged = RFC_CLAIMS
+        .replace("041a", "04c11a")
+        .replace("051a", "05c11a")
+        .replace("061a", "06c11a");
+    let bytes = claims.encode();
+    assert_eq!(bytes, hex(&tagged));
+    assert_eq!(CwtClaims::decode(&bytes).unwrap(), claims);
+
+    // The generic decoder sees the same map as `to_pack_value`.
+    let value = claims.to_pack_value().unwrap();
+    assert_eq!(decode_cbor_value(&bytes).unwrap(), value);
+    let PackValue::Object(entries) = &value else {
+        panic!("{value:?}");
+    };
+    assert_eq!(
+        entries[0],
+        ("1".into(), PackValue::Str("coap://as.example.com".into()))
+    );
+    assert_eq!(entries[3], ("4".into(), date(1444064944)));
+    assert_eq!(entries[6], ("7".into(), PackValue::Bytes(vec![0x0b, 0x71])));
+    assert_eq!(CwtClaims::from_pack_value(&value).unwrap(), claims);
+
+    assert_eq!(CwtClaims::new().encode(), [0xa0]);
+    assert_eq!(
+        CwtClaims::new().to_pack_value().unwrap(),
+        PackValue::Object(vec![])
+    );
+}
+
+#[test]
+fn cbor_cwt_other_claims_matrix() {
+    let claims = CwtClaims {
+        sub: Some("s".into()),
+        exp: Some(-1),
+        other: vec![
+            ("scope".into(), PackValue::Str("read".into())),
+            (CoseLabel::Int(-70000), PackValue::Bool(true)),
+            (CoseLabel::Int(8), PackValue::Object(vec![])),
+        ],
+        ..CwtClaims::new()
+    };
+    // Deterministic order: 2, 4, 8, -70000, "scope".
+    let bytes = claims.encode();
+    assert_eq!(
+        bytes,
+        hex("a5026173 04c120 08a0 3a0001116ff5 6573636f7065 6472656164"
+            .replace(' ', "")
+            .as_str())
+    );
+    let decoded = CwtClaims::decode(&bytes).unwrap();
+    assert_eq!(decoded.encode(), bytes);
+    assert_eq!(decoded.other.len(), 3);
+
+    let value = claims.to_pack_value().unwrap();
+    let PackValue::Object(entries) = &value else {
+        panic!("{value:?}");
+    };
+    let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["2", "4", "8", "-70000", "scope"]);
+    let back = CwtClaims::from_pack_value(&value).unwrap();
+    assert_eq!(back.encode(), bytes);
+
+    // Bare integer dates are read too.
+    let value = PackValue::Object(vec![("6".into(), PackValue::Integer(5))]);
+    assert_eq!(CwtClaims::from_pack_value(&value).unwrap().iat, Some(5));
+
+    // A blob is written as it is; one that is not CBOR cannot be read back.
+    let blob = |bytes: &[u8]| CwtClaims {
+        other: vec![(
+            CoseLabel::Int(9),
+            PackValue::Blob(JsonPackValue::new(bytes.to_vec())),
+        )],
+        ..CwtClaims::new()
+    };
+    assert_eq!(
+        blob(&[0xf5]).to_pack_value().unwrap(),
+        PackValue::Object(vec![("9".into(), PackValue::Bool(true))])
+    );
+    assert!(matches!(
+        blob(&[0xff]).to_pack_value(),
+        Err(CoseError::Cbor(_))
+    ));
+}
+
+#[test]
+fn cbor_cwt_invalid_matrix() {
+    for (bytes, err) in [
+        ("a10101", CoseError::InvalidClaim(1)),
+        ("a102f5", CoseError::InvalidClaim(2)),
+        ("a1f501", CoseError::Cbor(CborError::UnexpectedMajor(1))),
+        ("a10441aa", CoseError::InvalidClaim(4)),
+        ("a104c2411a", CoseError::InvalidClaim(4)),
+        ("a104fb3ff8000000000000", CoseError::InvalidClaim(4)),
+        ("a1076161", CoseError::InvalidClaim(7)),
+        ("a2040104c101", CoseError::DuplicateLabel),
+    ] {
+        assert_eq!(CwtClaims::decode(&hex(bytes)), Err(err), "{bytes}");
+    }
+    for value in [
+        PackValue::Array(vec![]),
+        PackValue::Object(vec![("5".into(), PackValue::Str("soon".into()))]),
+        PackValue::Object(vec![
+            ("1".into(), PackValue::Str("a".into())),
+            ("1".into(), PackValue::Str("b".into())),
+        ]),
+    ] {
+        assert!(CwtClaims::from_pack_value(&value).is_err(), "{value:?}");
+    }
+}
+
+#[test]
+fn cbor_cwt_sign1_matrix() {
+    let claims = rfc_claims();
+    let msg = CoseSign1::sign(
+        &CoseHeaderMap::new().with_alg(-7),
+        CoseHeaderMap::new(),
+        &claims.encode(),
+        false,
+        b"",
+        |tbs| Ok::<_, ()>(tbs[tbs.len() - 4..].to_vec()),
+    )
+    .unwrap();
+    let decoded = CoseSign1::decode(&msg.encode(true)).unwrap();
+    let payload = decoded.payload.as_deref().unwrap();
+    assert_eq!(CwtClaims::decode(payload).unwrap(), claims);
+}
@@ -0,0 +1,98 @@
+//! Stable and DAG CBOR encoding straight from `serde_json::Value`.
+//!
+//! Not an upstream port: upstream encodes JavaScript values, so there is no
+//! separate JSON input path. Output must match encoding the same value as a
+//! `PackValue`.
+
+use json_joy_json_pack::cbor::{CborEncoderDag, CborEncoderStable};
+use json_joy_json_pack::{
+    encode_json_to_cbor_bytes, encode_json_to_cbor_bytes_dag, encode_json_to_cbor_bytes_stable,
+    KeyOrder, PackValue,
+};
+use serde_json::{json, Value};
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn cases() -> Vec<Value> {
+    vec![
+        json!(null),
+        json!(true),
+        json!(0),
+        json!(-1),
+        json!(i64::MIN),
+        json!(u64::MAX),
+        json!(1.5),
+        json!(0.1),
+        json!(-1e300),
+        json!("héllo"),
+        json!([]),
+        json!({}),
+        json!([1, [2, [3, {"a": 0.25}]]]),
+        json!({"bb": 1, "a": 2, "ä": 3, "c": {"zz": [], "y": null}, "": false}),
+    ]
+}
+
+#[test]
+fn cbor_json_encode_matches_pack_value() {
+    for value in cases() {
+        let pack = PackValue::from(&value);
+        assert_eq!(
+            encode_json_to_cbor_bytes_stable(&value),
+            CborEncoderStable::new().encode(&pack),
+            "{value}"
+        );
+        assert_eq!(
+            encode_json_to_cbor_bytes_dag(&value),
+            CborEncoderDag::new().encode(&pack),
+            "{value}"
+        );
+        for order in [KeyOrder::Lexicographic, KeyOrder::Utf16] {
+            let mut encoder = CborEncoderStable::with_key_order(order);
+            assert_eq!(
+                encoder.encode_json(&value),
+                encoder.encode(&pack),
+                "{value}"
+            );
+        }
+    }
+}
+
+#[test]
+fn cbor_json_encode_modes_matrix() {
+    let value = json!({"bb": 1.5, "a": [0.1], "c": "x"});
+    // Fast: insertion order. Stable: sorted keys. DAG: sorted keys and
+    // 64-bit floats only.
+    assert_eq!(
+        encode_json_to_cbor_bytes(&value).unwrap(),
+        hex("a3626262fa3fc00000616181fb3fb999999999999a61636178")
+    );
+    assert_eq!(
+        encode_json_to_cbor_bytes_stable(&value),
+        hex("a3616181fb3fb999999999999a61636178626262fa3fc00000")
+    );
+    assert_eq!(
+        encode_json_to_cbor_bytes_dag(&value),
+        hex("a3616181fb3fb999999999999a61636178626262fb3ff8000000000000")
+    );
+
+    // The same object in any insertion order encodes the same.
+    let reordered = json!({"c": "x", "a": [0.1], "bb": 1.5});
+    assert_eq!(
+        encode_json_to_cbor_bytes_stable(&reordered),
+        encode_json_to_cbor_bytes_stable(&value)
+    );
+    assert_eq!(
+        encode_json_to_cbor_bytes_dag(&reordered),
+        encode_json_to_cbor_bytes_dag(&value)
+    );
+
+    // Encoders are reusable.
+    let mut dag = CborEncoderDag::new();
+    assert_eq!(dag.encode_json(&json!(1)), [0x01]);
+    assert_eq!(dag.encode_json(&json!([])), [0x80]);
+}
@@ -0,0 +1,199 @@
+//! Typed JSON to CBOR conversion driven by per-path hints.
+//!
+//! Not an upstream port: upstream converts JSON strings to CBOR text only.
+
+use json_joy_json_pack::cbor::{
+    json_to_cbor, json_to_cbor_with, CborDecoder, CborDecoderOptions, CborEncoder, CborError,
+    CborJsonHints, CborTypeHint,
+};
+use json_joy_json_pack::{JsonPackBigInt, JsonPackExtension, PackValue};
+use serde_json::json;
+
+fn tag(tag: u64, value: PackValue) -> PackValue {
+    PackValue::Extension(Box::new(JsonPackExtension::new(tag, value)))
+}
+
+#[test]
+fn cbor_json_hint_type_matrix() {
+    let cases = [
+        (
+            CborTypeHint::BytesBase64,
+            json!("AP8/"),
+            PackValue::Bytes(vec![0, 255, 63]),
+        ),
+        (
+            CborTypeHint::BytesBase64Url,
+            json!("AP8_"),
+            PackValue::Bytes(vec![0, 255, 63]),
+        ),
+        (
+            CborTypeHint::BytesHex,
+            json!("00fF3f"),
+            PackValue::Bytes(vec![0, 255, 63]),
+        ),
+        (CborTypeHint::BytesHex, json!(""), PackValue::Bytes(vec![])),
+        (
+            CborTypeHint::BigInt,
+            json!(42),
+            PackValue::BigNum(JsonPackBigInt::from_i128(42)),
+        ),
+        (
+            CborTypeHint::BigInt,
+            json!("-123456789012345678901234567890"),
+            PackValue::BigNum("-123456789012345678901234567890".parse().unwrap()),
+        ),
+        (
+            CborTypeHint::DateTag0,
+            json!("2013-03-21T20:04:00Z"),
+            tag(0, PackValue::Str("2013-03-21T20:04:00Z".into())),
+        ),
+        (
+            CborTypeHint::DateTag0,
+            json!("1996-12-19T16:39:57.123-08:00"),
+            tag(0, PackValue::Str("1996-12-19T16:39:57.123-08:00".into())),
+        ),
+        (
+            CborTypeHint::DateTag0,
+            json!("2016-12-31t23:59:60z"),
+            tag(0, PackValue::Str("2016-12-31t23:59:60z".into())),
+        ),
+        (
+            CborTypeHint::DateTag0,
+            json!("2000-02-29T00:00:00+14:00"),
+            tag(0, PackValue::Str("2000-02-29T00:00:00+14:00".into())),
+        ),
+        (
+            CborTypeHint::DateTag1,
+            json!(1_363_896_240),
+            tag(1, PackValue::Integer(1_363_896_240)),
+        ),
+        (
+            CborTypeHint::DateTag1,
+            json!(1.5),
+            tag(1, PackValue::Float(1.5)),
+        ),
+    ];
+    for (hint, input, expected) in cases {
+        let mut hints = CborJsonHints::new();
+        hints.add("/v", hint);
+        let got = json_to_cbor_with(&json!({ "v": input }), &hints).unwrap();
+        assert_eq!(
+            got,
+            PackValue::Object(vec![("v".into(), expected)]),
+            "{hint:?} {input}"
+        );
+    }
+}
+
+#[test]
+fn cbor_json_hint_path_matrix() {
+    let input = json!({
+        "protected": "oQEm",
+        "signatures": [{"sig": "0a0b"}, {"sig": "0c"}],
+        "a/b": {"~": "ff"},
+        "plain": "0a0b",
+    });
+    let mut hints = CborJsonHints::new();
+    hints
+        .add("/protected", CborTypeHint::BytesBase64)
+        .add("/signatures/*/sig", CborTypeHint::BytesHex)
+        .add("/a~1b/~0", CborTypeHint::BytesHex)
+        .add("/signatures/*/sig", CborTypeHint::BytesBase64);
+    let got = json_to_cbor_with(&input, &hints).unwrap();
+    assert_eq!(
+        got,
+        PackValue::Object(vec![
+            ("protected".into(), PackValue::Bytes(vec![0xa1, 0x01, 0x26])),
+            (
+                "signatures".into(),
+                PackValue::Array(vec![
+                    PackValue::Object(vec![("sig".into(), PackValue::Bytes(vec![10, 11]))]),
+                    PackValue::Object(vec![("sig".into(), PackValue::Bytes(vec![12]))]),
+                ]),
+            ),
+            (
+                "a/b".into(),
+                PackValue::Object(vec![("~".into(), PackValue::Bytes(vec![0xff]))]),
+            ),
+            ("plain".into(), PackValue::Str("0a0b".into())),
+        ])
+    );
+
+    // No hints: same as the plain conversion.
+    assert_eq!(
+        json_to_cbor_with(&input, &CborJsonHints::new()).unwrap(),
+        json_to_cbor(&input)
+    );
+}
+
+#[test]
+fn cbor_json_hint_wire_matrix() {
+    let mut hints = CborJsonHints::new();
+    hints
+        .add("/id", CborTypeHint::BytesHex)
+        .add("/n", CborTypeHint::BigInt)
+        .add("/at", CborTypeHint::DateTag1);
+    let value = json_to_cbor_with(
+        &json!({"id": "beef", "n": "18446744073709551616", "at": 0}),
+        &hints,
+    )
+    .unwrap();
+    let bytes = CborEncoder::new().encode(&value);
+    let expected: Vec<u8> = [
+        &[0xa3][..],                                          // map(3)
+        &[0x62, b'i', b'd', 0x42, 0xbe, 0xef],                // "id": h'beef'
+        &[0x61, b'n', 0xc2, 0x49, 1, 0, 0, 0, 0, 0, 0, 0, 0], // "n": 2(h'010000000000000000')
+        &[0x62, b'a', b't', 0xc1, 0x00],                      // "at": 1(0)
+    ]
+    .concat();
+    assert_eq!(bytes, expected);
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        bignum: true,
+        ..CborDecoderOptions::default()
+    });
+    assert_eq!(
+        decoder.decode(&bytes).unwrap(),
+        PackValue::Object(vec![
+            ("id".into(), PackValue::Bytes(vec![0xbe, 0xef])),
+            (
+                "n".into(),
+                PackValue::BigNum("18446744073709551616".parse().unwrap())
+            ),
+            ("at".into(), tag(1, PackValue::Integer(0))),
+        ])
+    );
+}
+
+#[test]
+fn cbor_json_hint_error_matrix() {
+    let cases = [
+        (CborTypeHint::BytesBase64, json!("***")),
+        (CborTypeHint::BytesBase64, json!(1)),
+        (CborTypeHint::BytesHex, json!("abc")),
+        (CborTypeHint::BytesHex, json!("+f")),
+        (CborTypeHint::BigInt, json!(1.5)),
+        (CborTypeHint::BigInt, json!("12x")),
+        (CborTypeHint::DateTag0, json!(0)),
+        (CborTypeHint::DateTag0, json!("not a date")),
+        (CborTypeHint::DateTag0, json!("2013-03-21")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T20:04:00")),
+        (CborTypeHint::DateTag0, json!("2013-03-21 20:04:00Z")),
+        (CborTypeHint::DateTag0, json!("2013-13-21T20:04:00Z")),
+        (CborTypeHint::DateTag0, json!("2013-02-29T20:04:00Z")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T24:04:00Z")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T20:04:00.Z")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T20:04:00+0100")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T20:04:00Z ")),
+        (CborTypeHint::DateTag0, json!("2013-03-21T20:04:00é")),
+        (CborTypeHint::DateTag1, json!("2013-03-21T20:04:00Z")),
+    ];
+    for (hint, input) in cases {
+        let mut hints = CborJsonHints::new();
+        hints.add("/a~1b/0", hint);
+        assert_eq!(
+            json_to_cbor_with(&json!({"a/b": [input]}), &hints),
+            Err(CborError::HintMismatch("/a~1b/0".into())),
+            "{hint:?} {input}"
+        );
+    }
+}
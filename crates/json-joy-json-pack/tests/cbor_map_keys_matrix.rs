@@ -0,0 +1,173 @@
+//! CBOR maps with non-text keys decoded as `PackValue::Map`.
+//!
+//! Not an upstream port: upstream always converts map keys to strings.
+
+use json_joy_json_pack::bencode::BencodeEncoder;
+use json_joy_json_pack::cbor::cose::{CoseHeaderMap, CoseLabel};
+use json_joy_json_pack::cbor::{
+    CborDecoder, CborDecoderOptions, CborEncoder, CborEncoderFast, CborEncoderStable, CborError,
+};
+use json_joy_json_pack::json::JsonEncoder;
+use json_joy_json_pack::msgpack::{MsgPackEncoder, MsgPackEncoderFast, MsgPackEncoderOptions};
+use json_joy_json_pack::PackValue;
+
+fn any_keys() -> CborDecoder {
+    CborDecoder::with_options(CborDecoderOptions {
+        any_keys: true,
+        ..CborDecoderOptions::default()
+    })
+}
+
+fn int(i: i64) -> PackValue {
+    PackValue::Integer(i)
+}
+
+fn str(s: &str) -> PackValue {
+    PackValue::Str(s.into())
+}
+
+/// A COSE_Key (RFC 9052 §7): `{1: 2, -1: 1, -2: h'01', "x": true}`.
+fn cose_key() -> PackValue {
+    PackValue::Map(vec![
+        (int(1), int(2)),
+        (int(-1), int(1)),
+        (int(-2), PackValue::Bytes(vec![1])),
+        (str("x"), PackValue::Bool(true)),
+    ])
+}
+
+#[test]
+fn cbor_map_keys_decode_matrix() {
+    let cases: &[(&[u8], PackValue)] = &[
+        (
+            &[0xa2, 0x01, 0x61, 0x61, 0x20, 0x61, 0x62],
+            PackValue::Map(vec![(int(1), str("a")), (int(-1), str("b"))]),
+        ),
+        (
+            &[0xa1, 0x42, 0x01, 0x02, 0xf5],
+            PackValue::Map(vec![(PackValue::Bytes(vec![1, 2]), PackValue::Bool(true))]),
+        ),
+        (
+            &[0xbf, 0x61, 0x61, 0x01, 0xf4, 0x02, 0xff],
+            PackValue::Map(vec![(str("a"), int(1)), (PackValue::Bool(false), int(2))]),
+        ),
+        // Text keys only: still an object.
+        (
+            &[0xa1, 0x61, 0x61, 0xa1, 0x01, 0x02],
+            PackValue::Object(vec![("a".into(), PackValue::Map(vec![(int(1), int(2))]))]),
+        ),
+        (&[0xbf, 0xff], PackValue::Object(vec![])),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(&any_keys().decode(bytes).unwrap(), expected, "{bytes:02x?}");
+    }
+
+    // Without the option keys are converted to text.
+    assert_eq!(
+        CborDecoder::new()
+            .decode(&[0xa2, 0x01, 0x61, 0x61, 0x20, 0x61, 0x62])
+            .unwrap(),
+        PackValue::Object(vec![("1".into(), str("a")), ("-1".into(), str("b"))])
+    );
+    // `any_keys` wins over `strict_keys`.
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        strict_keys: true,
+        any_keys: true,
+        ..CborDecoderOptions::default()
+    });
+    assert_eq!(
+        decoder.decode(&[0xa1, 0x01, 0x02]).unwrap(),
+        PackValue::Map(vec![(int(1), int(2))])
+    );
+
+    let errors: &[(&[u8], CborError)] = &[
+        (
+            &[
+                0xa1, 0x69, b'_', b'_', b'p', b'r', b'o', b't', b'o', b'_', b'_', 0x01,
+            ],
+            CborError::UnexpectedObjKey(1),
+        ),
+        (&[0xbf, 0x01, 0xff], CborError::UnexpectedObjBreak(2)),
+        (&[0xa2, 0x01, 0x02], CborError::UnexpectedEof(3)),
+        // A forged pair count is not reserved up front.
+        (
+            &[0xba, 0xff, 0xff, 0xff, 0xff, 0x01],
+            CborError::UnexpectedEof(6),
+        ),
+    ];
+    for (bytes, expected) in errors {
+        assert_eq!(
+            &any_keys().decode(bytes).unwrap_err(),
+            expected,
+            "{bytes:02x?}"
+        );
+    }
+}
+
+#[test]
+fn cbor_map_keys_encode_matrix() {
+    let value = cose_key();
+    let bytes = CborEncoder::new().encode(&value);
+    assert_eq!(
+        bytes,
+        [0xa4, 0x01, 0x02, 0x20, 0x01, 0x21, 0x41, 0x01, 0x61, b'x', 0xf5]
+    );
+    assert_eq!(CborEncoderFast::new().encode(&value), bytes);
+    assert_eq!(any_keys().decode(&bytes).unwrap(), value);
+
+    // Stable encoding sorts keys by their encoded bytes.
+    assert_eq!(
+        CborEncoderStable::new().encode(&value),
+        [0xa4, 0x01, 0x02, 0x20, 0x01, 0x21, 0x41, 0x01, 0x61, b'x', 0xf5]
+    );
+    let unsorted = PackValue::Map(vec![(str("a"), int(1)), (int(10), int(2))]);
+    assert_eq!(
+        CborEncoderStable::new().encode(&unsorted),
+        [0xa2, 0x0a, 0x02, 0x61, b'a', 0x01]
+    );
+
+    assert_eq!(
+        MsgPackEncoderFast::new().encode(&value),
+        [0x84, 0x01, 0x02, 0xff, 0x01, 0xfe, 0xc4, 0x01, 0x01, 0xa1, b'x', 0xc3]
+    );
+    // The options of `MsgPackEncoder` reach map keys and values.
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        smallest_int: true,
+        float32: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        encoder.encode(&PackValue::Map(vec![
+            (int(200), int(200)),
+            (PackValue::Float(1.5), PackValue::Float(0.5)),
+        ])),
+        [0x82, 0xcc, 0xc8, 0xcc, 0xc8, 0xca, 0x3f, 0xc0, 0, 0, 0xca, 0x3f, 0, 0, 0]
+    );
+
+    // Formats with text keys only convert keys to text.
+    assert_eq!(
+        JsonEncoder::new().encode(&value),
+        br#"{"1":2,"-1":1,"-2":"data:application/octet-stream;base64,AQ==","x":true}"#
+    );
+    // Bencode keys are byte strings: byte keys stay bytes, sorted with the
+    // rest.
+    let value = PackValue::Map(vec![
+        (int(1), int(2)),
+        (int(-1), int(1)),
+        (PackValue::Bytes(vec![1]), PackValue::Bytes(vec![1])),
+        (str("x"), PackValue::Bool(true)),
+    ]);
+    assert_eq!(
+        BencodeEncoder::new().encode(&value),
+        b"d1:\x011:\x012:-1i1e1:1i2e1:xte"
+    );
+}
+
+#[test]
+fn cbor_map_keys_cose_header_matrix() {
+    // A `cnf`-style header carrying a COSE_Key keeps its integer labels.
+    let header = CoseHeaderMap::new().with_alg(-7).with(8, cose_key());
+    let decoded = CoseHeaderMap::decode(&header.encode()).unwrap();
+    assert_eq!(decoded.get(&CoseLabel::Int(8)), Some(&cose_key()));
+    assert_eq!(decoded.alg(), Some(-7));
+}
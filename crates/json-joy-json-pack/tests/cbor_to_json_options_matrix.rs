@@ -0,0 +1,130 @@
+//! Configurable CBOR to JSON conversion policies.
+//!
+//! Not an upstream port: upstream conversion choices are fixed.
+
+use json_joy_json_pack::cbor::{
+    cbor_to_json, cbor_to_json_with, decode_json_from_cbor_bytes_with, CborBytesPolicy, CborError,
+    CborKeyPolicy, CborNonFinitePolicy, CborTagPolicy, CborToJsonOptions,
+};
+use json_joy_json_pack::{JsonPackExtension, PackValue};
+use serde_json::json;
+
+fn sample() -> PackValue {
+    PackValue::Object(vec![
+        ("bin".into(), PackValue::Bytes(vec![0x00, 0xff, 0x10])),
+        (
+            "date".into(),
+            PackValue::Extension(Box::new(JsonPackExtension::new(
+                1,
+                PackValue::Integer(1_700_000_000),
+            ))),
+        ),
+        (
+            "floats".into(),
+            PackValue::Array(vec![
+                PackValue::Float(f64::NAN),
+                PackValue::Float(f64::INFINITY),
+                PackValue::Float(f64::NEG_INFINITY),
+                PackValue::Float(0.5),
+            ]),
+        ),
+    ])
+}
+
+#[test]
+fn cbor_to_json_defaults_match_legacy() {
+    let value = sample();
+    assert_eq!(
+        cbor_to_json_with(&value, &CborToJsonOptions::default()).unwrap(),
+        cbor_to_json(&value).unwrap()
+    );
+}
+
+#[test]
+fn cbor_to_json_bytes_policy_matrix() {
+    let bytes = PackValue::Bytes(vec![0x00, 0xff, 0x10]);
+    let cases = [
+        (
+            CborBytesPolicy::DataUri,
+            json!("data:application/octet-stream;base64,AP8Q"),
+        ),
+        (CborBytesPolicy::Base64, json!("AP8Q")),
+        (CborBytesPolicy::Hex, json!("00ff10")),
+        (CborBytesPolicy::Array, json!([0, 255, 16])),
+    ];
+    for (policy, expected) in cases {
+        let options = CborToJsonOptions {
+            bytes: policy,
+            ..CborToJsonOptions::default()
+        };
+        assert_eq!(cbor_to_json_with(&bytes, &options).unwrap(), expected);
+    }
+}
+
+#[test]
+fn cbor_to_json_tag_policy_matrix() {
+    let tagged = PackValue::Array(vec![PackValue::Extension(Box::new(
+        JsonPackExtension::new(24, PackValue::Bytes(vec![1])),
+    ))]);
+    let options = |tags| CborToJsonOptions {
+        tags,
+        bytes: CborBytesPolicy::Hex,
+        ..CborToJsonOptions::default()
+    };
+    assert_eq!(
+        cbor_to_json_with(&tagged, &options(CborTagPolicy::Unwrap)).unwrap(),
+        json!(["01"])
+    );
+    assert_eq!(
+        cbor_to_json_with(&tagged, &options(CborTagPolicy::Wrap)).unwrap(),
+        json!([{"tag": 24, "value": "01"}])
+    );
+    assert_eq!(
+        cbor_to_json_with(&tagged, &options(CborTagPolicy::Error)),
+        Err(CborError::UnexpectedTag(24))
+    );
+}
+
+#[test]
+fn cbor_to_json_non_finite_policy_matrix() {
+    let value = sample();
+    let options = |non_finite| CborToJsonOptions {
+        non_finite,
+        ..CborToJsonOptions::default()
+    };
+    let json = cbor_to_json_with(&value, &options(CborNonFinitePolicy::Null)).unwrap();
+    assert_eq!(json["floats"], json!([null, null, null, 0.5]));
+    let json = cbor_to_json_with(&value, &options(CborNonFinitePolicy::String)).unwrap();
+    assert_eq!(json["floats"], json!(["NaN", "Infinity", "-Infinity", 0.5]));
+    assert_eq!(
+        cbor_to_json_with(&value, &options(CborNonFinitePolicy::Error)),
+        Err(CborError::NonFiniteFloat)
+    );
+}
+
+#[test]
+fn cbor_bytes_to_json_key_policy_matrix() {
+    // {1: "a", "b": h'01'}
+    let bytes = [0xa2, 0x01, 0x61, b'a', 0x61, b'b', 0x41, 0x01];
+    let stringify = CborToJsonOptions {
+        bytes: CborBytesPolicy::Array,
+        ..CborToJsonOptions::default()
+    };
+    assert_eq!(
+        decode_json_from_cbor_bytes_with(&bytes, &stringify).unwrap(),
+        json!({"1": "a", "b": [1]})
+    );
+    let strict = CborToJsonOptions {
+        keys: CborKeyPolicy::Error,
+        ..stringify
+    };
+    assert_eq!(
+        decode_json_from_cbor_bytes_with(&bytes, &strict),
+        Err(CborError::UnexpectedObjKey(1))
+    );
+    // Text keys pass under the strict policy.
+    assert_eq!(
+        decode_json_from_cbor_bytes_with(&[0xa1, 0x61, b'k', 0xf6], &strict).unwrap(),
+        json!({"k": null})
+    );
+}
@@ -0,0 +1,130 @@
+//! The conformance runner over the `tests/compat/codec_fixtures` corpus.
+//!
+//! Not an upstream port: the corpus pairs JSON values with the bytes
+//! upstream json-pack encoders write for them.
+
+use json_joy_json_pack::conformance::{
+    self, CodecFixture, CodecFormat, ConformanceReport, DivergenceKind, FixtureError,
+};
+use serde_json::json;
+
+const CORPUS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/compat/codec_fixtures"
+);
+
+#[test]
+fn codec_conformance_corpus_matrix() {
+    let fixtures = conformance::load_dir(CORPUS).unwrap();
+    for format in [CodecFormat::Json, CodecFormat::Cbor, CodecFormat::MsgPack] {
+        assert!(
+            fixtures.iter().any(|f| f.format == format),
+            "no {format:?} fixtures"
+        );
+    }
+    let report = conformance::run(&fixtures);
+    assert!(report.is_ok(), "{report}");
+    assert_eq!(report.fixtures, fixtures.len());
+    // One encoder and two decoders per fixture.
+    assert_eq!(report.checks, 3 * fixtures.len());
+
+    // The CRDT corpus holds no codec fixtures.
+    let mixed = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/compat/fixtures");
+    assert_eq!(conformance::load_dir(mixed).unwrap(), []);
+}
+
+#[test]
+fn codec_conformance_divergence_matrix() {
+    let doc = json!({
+        "fixture_version": 1,
+        "name": "cbor_array",
+        "scenario": "codec_cbor",
+        "input": { "encoder": "CborEncoder", "json": [1, 2] },
+        "expected": { "hex": "820103" },
+    });
+    let fixture = CodecFixture::from_json(&doc).unwrap().unwrap();
+    assert_eq!(fixture.bytes, [0x82, 0x01, 0x03]);
+    assert_eq!(fixture.to_json(), doc);
+
+    let report = conformance::run(&[fixture]);
+    assert_eq!(
+        report.divergences[0].kind,
+        DivergenceKind::Encode {
+            expected: vec![0x82, 0x01, 0x03],
+            actual: vec![0x82, 0x01, 0x02],
+        }
+    );
+    assert_eq!(
+        report.divergences[1].kind,
+        DivergenceKind::Decode {
+            expected: json!([1, 2]),
+            actual: json!([1, 3]),
+        }
+    );
+    assert_eq!(report.divergences.len(), 3);
+    assert_eq!(
+        report.to_string().lines().take(2).collect::<Vec<_>>(),
+        [
+            "1 fixtures, 3 checks, 3 divergences",
+            "  cbor_array: CborEncoder wrote 820102, expected 820103",
+        ]
+    );
+
+    let truncated = CodecFixture {
+        bytes: vec![0x82, 0x01],
+        ..CodecFixture::from_json(&doc).unwrap().unwrap()
+    };
+    let report = conformance::run(&[truncated]);
+    assert!(matches!(
+        report.divergences[1].kind,
+        DivergenceKind::DecodeError { .. }
+    ));
+    assert_eq!(
+        ConformanceReport::default().to_string(),
+        "0 fixtures, 0 checks, 0 divergences"
+    );
+}
+
+#[test]
+fn codec_conformance_fixture_error_matrix() {
+    let base = json!({
+        "name": "f",
+        "scenario": "codec_msgpack",
+        "input": { "encoder": "MsgPackEncoder", "json": null },
+        "expected": { "hex": "c0" },
+    });
+    assert!(CodecFixture::from_json(&base).unwrap().is_some());
+
+    let cases: &[(&str, serde_json::Value, &str)] = &[
+        ("/name", json!(""), "missing name"),
+        ("/input/encoder", json!("CborEncoder"), "unknown encoder"),
+        (
+            "/expected/hex",
+            json!("c"),
+            "missing or malformed expected.hex",
+        ),
+        (
+            "/expected/hex",
+            json!("zz"),
+            "missing or malformed expected.hex",
+        ),
+    ];
+    for (pointer, replacement, reason) in cases {
+        let mut doc = base.clone();
+        *doc.pointer_mut(pointer).unwrap() = replacement.clone();
+        let name = doc["name"].as_str().unwrap().to_string();
+        assert_eq!(
+            CodecFixture::from_json(&doc),
+            Err(FixtureError::Invalid { name, reason }),
+            "{pointer}"
+        );
+    }
+
+    let mut doc = base.clone();
+    doc["scenario"] = json!("patch_alt_codecs");
+    assert_eq!(CodecFixture::from_json(&doc), Ok(None));
+    assert!(matches!(
+        conformance::load_dir("/nonexistent"),
+        Err(FixtureError::Io { .. })
+    ));
+}
@@ -0,0 +1,84 @@
+//! Format-and-version envelopes around encoded payloads.
+//!
+//! Not an upstream port: upstream passes the format out of band.
+
+use json_joy_json_pack::codecs::{CodecError, Codecs, Envelope};
+use json_joy_json_pack::{pack, EncodingFormat, JsonPackError};
+
+const FORMATS: [EncodingFormat; 7] = [
+    EncodingFormat::Cbor,
+    EncodingFormat::MsgPack,
+    EncodingFormat::Json,
+    EncodingFormat::Bson,
+    EncodingFormat::Ion,
+    EncodingFormat::Ubjson,
+    EncodingFormat::Resp,
+];
+
+#[test]
+fn codecs_envelope_wrap_matrix() {
+    for (id, format) in FORMATS.into_iter().enumerate() {
+        assert_eq!(EncodingFormat::try_from(id as u8), Ok(format));
+        for payload in [&b""[..], b"\x01", b"{\"a\":1}"] {
+            let bytes = Envelope::wrap(format, 3, payload);
+            assert_eq!(bytes[..2], [id as u8, 3]);
+            assert_eq!(&bytes[2..], payload);
+            let envelope = Envelope::unwrap(&bytes).unwrap();
+            assert_eq!(
+                envelope,
+                Envelope {
+                    format,
+                    version: 3,
+                    payload
+                }
+            );
+            assert_eq!(envelope.to_vec(), bytes);
+        }
+    }
+    assert_eq!(EncodingFormat::try_from(7), Err(7));
+
+    for bytes in [&b""[..], b"\x00"] {
+        assert!(matches!(
+            Envelope::unwrap(bytes),
+            Err(CodecError::TruncatedEnvelope)
+        ));
+    }
+    assert!(matches!(
+        Envelope::unwrap(b"\x07\x01{}"),
+        Err(CodecError::UnknownFormat)
+    ));
+    let err: JsonPackError = Envelope::unwrap(b"\xff\x01").unwrap_err().into();
+    assert_eq!(err.format(), "codecs");
+}
+
+#[test]
+fn codecs_envelope_decode_matrix() {
+    let mut codecs = Codecs::new();
+    let value = pack!({ "id": 7, "tags": ["a", "b"], "ok": true });
+    for (format, payload) in [
+        (EncodingFormat::Cbor, codecs.cbor.encode(&value).unwrap()),
+        (
+            EncodingFormat::MsgPack,
+            codecs.msgpack.encode(&value).unwrap(),
+        ),
+        (EncodingFormat::Json, codecs.json.encode(&value).unwrap()),
+    ] {
+        for version in [0, 1, 255] {
+            let bytes = Envelope::wrap(format, version, &payload);
+            assert_eq!(
+                codecs.decode_envelope(&bytes).unwrap(),
+                (version, value.clone())
+            );
+        }
+    }
+
+    // The envelope, not sniffing, decides the format: this payload would
+    // sniff as JSON.
+    let bytes = Envelope::wrap(EncodingFormat::MsgPack, 1, b"5");
+    assert_eq!(codecs.decode_envelope(&bytes).unwrap(), (1, pack!(53)));
+    let bytes = Envelope::wrap(EncodingFormat::Json, 1, b"{");
+    assert!(matches!(
+        codecs.decode_envelope(&bytes),
+        Err(CodecError::Json(_))
+    ));
+}
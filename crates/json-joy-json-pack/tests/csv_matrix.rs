@@ -0,0 +1,204 @@
+//! CSV/TSV tabular codec.
+//!
+//! Not an upstream port: upstream json-pack has no CSV support.
+
+use json_joy_json_pack::csv::{
+    CsvDecoder, CsvDecoderOptions, CsvEncoder, CsvEncoderOptions, CsvError,
+};
+use json_joy_json_pack::PackValue;
+
+fn row(fields: &[(&str, PackValue)]) -> PackValue {
+    PackValue::Object(
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+    )
+}
+
+fn str(s: &str) -> PackValue {
+    PackValue::Str(s.to_string())
+}
+
+#[test]
+fn csv_encoder_matrix() {
+    let table = PackValue::Array(vec![
+        row(&[("id", PackValue::Integer(1)), ("name", str("Ann"))]),
+        row(&[
+            ("name", str("Smith, \"Bo\"")),
+            ("id", PackValue::Integer(2)),
+            ("ok", PackValue::Bool(true)),
+        ]),
+        row(&[
+            ("id", PackValue::Float(2.5)),
+            ("name", str("")),
+            ("ok", PackValue::Null),
+        ]),
+        row(&[("name", str("42")), ("ok", str("line\nbreak"))]),
+    ]);
+    let csv = CsvEncoder::new().encode(&table).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "id,name,ok\n\
+         1,Ann,\n\
+         2,\"Smith, \"\"Bo\"\"\",true\n\
+         2.5,\"\",\n\
+         ,\"42\",\"line\nbreak\"\n"
+    );
+
+    let mut tsv = CsvEncoder::with_options(CsvEncoderOptions {
+        delimiter: b'\t',
+        crlf: true,
+    });
+    let out = tsv
+        .encode(&PackValue::Array(vec![row(&[
+            ("a", str("x,y")),
+            ("b", str("tab\there")),
+        ])]))
+        .unwrap();
+    assert_eq!(out, b"a\tb\r\nx,y\t\"tab\there\"\r\n");
+
+    assert_eq!(
+        CsvEncoder::new().encode(&PackValue::Array(vec![])).unwrap(),
+        b""
+    );
+    assert_eq!(
+        CsvEncoder::new().encode(&PackValue::Integer(1)),
+        Err(CsvError::NotTabular)
+    );
+    assert_eq!(
+        CsvEncoder::new().encode(&PackValue::Array(vec![str("a")])),
+        Err(CsvError::NotTabular)
+    );
+    assert_eq!(
+        CsvEncoder::new().encode(&PackValue::Array(vec![
+            row(&[("a", PackValue::Integer(1))]),
+            row(&[("a", PackValue::Array(vec![]))]),
+        ])),
+        Err(CsvError::UnsupportedValue {
+            row: 1,
+            column: "a".to_string(),
+            kind: "array",
+        })
+    );
+}
+
+#[test]
+fn csv_decoder_matrix() {
+    let input = b"id,name,note\r\n1,Ann,\"say \"\"hi\"\"\"\r\n-2.5e3,\"42\",\n";
+    let plain = CsvDecoder::new().decode(input).unwrap();
+    assert_eq!(
+        plain,
+        PackValue::Array(vec![
+            row(&[
+                ("id", str("1")),
+                ("name", str("Ann")),
+                ("note", str("say \"hi\""))
+            ]),
+            row(&[
+                ("id", str("-2.5e3")),
+                ("name", str("42")),
+                ("note", str(""))
+            ]),
+        ])
+    );
+
+    let sniffing = CsvDecoder::with_options(CsvDecoderOptions {
+        sniff_types: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        sniffing.decode(input).unwrap(),
+        PackValue::Array(vec![
+            row(&[
+                ("id", PackValue::Integer(1)),
+                ("name", str("Ann")),
+                ("note", str("say \"hi\"")),
+            ]),
+            row(&[
+                ("id", PackValue::Float(-2500.0)),
+                ("name", str("42")),
+                ("note", PackValue::Null),
+            ]),
+        ])
+    );
+    let sniffed = sniffing
+        .decode(b"v\ntrue\n18446744073709551615\n01\n1.\nNaN\n")
+        .unwrap();
+    assert_eq!(
+        sniffed,
+        PackValue::Array(vec![
+            row(&[("v", PackValue::Bool(true))]),
+            row(&[("v", PackValue::UInteger(u64::MAX))]),
+            row(&[("v", str("01"))]),
+            row(&[("v", str("1."))]),
+            row(&[("v", str("NaN"))]),
+        ])
+    );
+
+    let tsv = CsvDecoder::with_options(CsvDecoderOptions {
+        delimiter: b'\t',
+        ..Default::default()
+    });
+    assert_eq!(
+        tsv.decode(b"a\tb\nx,y\tz").unwrap(),
+        PackValue::Array(vec![row(&[("a", str("x,y")), ("b", str("z"))])])
+    );
+    assert_eq!(
+        CsvDecoder::new().decode(b"").unwrap(),
+        PackValue::Array(vec![])
+    );
+}
+
+#[test]
+fn csv_decoder_error_matrix() {
+    let decoder = CsvDecoder::new();
+    let err = decoder.decode(b"a,b\n1,2\n3\n").unwrap_err();
+    assert_eq!(
+        err,
+        CsvError::FieldCount {
+            offset: 8,
+            expected: 2,
+            found: 1,
+        }
+    );
+    assert_eq!(err.offset(), Some(8));
+    assert_eq!(
+        decoder.decode(b"a\n\"open").unwrap_err(),
+        CsvError::UnterminatedQuote(2)
+    );
+    assert_eq!(
+        decoder.decode(b"a\n\"x\"y").unwrap_err(),
+        CsvError::InvalidQuote(5)
+    );
+    assert_eq!(
+        decoder.decode(b"a\n\xff").unwrap_err(),
+        CsvError::InvalidUtf8(2)
+    );
+}
+
+#[test]
+fn csv_roundtrip_with_sniffing() {
+    let table = PackValue::Array(vec![
+        row(&[
+            ("n", PackValue::Integer(-7)),
+            ("s", str("true")),
+            ("t", str("plain text")),
+            ("x", PackValue::Null),
+        ]),
+        row(&[
+            ("n", PackValue::Float(0.125)),
+            ("s", str("")),
+            ("t", str("a,\"b\"\r\nc")),
+            ("x", PackValue::Bool(false)),
+        ]),
+    ]);
+    let csv = CsvEncoder::new().encode(&table).unwrap();
+    let decoded = CsvDecoder::with_options(CsvDecoderOptions {
+        sniff_types: true,
+        ..Default::default()
+    })
+    .decode(&csv)
+    .unwrap();
+    assert_eq!(decoded, table);
+}
@@ -0,0 +1,121 @@
+//! Direct EJSON <-> BSON document conversion.
+//!
+//! Not an upstream port: upstream converts through plain JS values.
+
+use json_joy_json_pack::bson::{BsonDecoder, BsonEncoder, BsonTimestamp, BsonValue};
+use json_joy_json_pack::ejson::{
+    ejson_str_to_bson_doc, from_bson, to_bson, EjsonDecodeError, EjsonDecoder, EjsonEncoder,
+    EjsonValue,
+};
+
+const CANONICAL: &str = concat!(
+    r#"{"_id":{"$oid":"57e193d7a9cc81b4027498b5"},"#,
+    r#""i":{"$numberInt":"-7"},"l":{"$numberLong":"1234567890123"},"#,
+    r#""d":{"$numberDouble":"-Infinity"},"s":"text","b":true,"n":null,"#,
+    r#""at":{"$date":{"$numberLong":"1356351330501"}},"#,
+    r#""bin":{"$binary":{"base64":"AQID","subType":"80"}},"#,
+    r#""re":{"$regularExpression":{"pattern":"^a","options":"i"}},"#,
+    r#""ts":{"$timestamp":{"t":42,"i":1}},"#,
+    r#""code":{"$code":"x()"},"#,
+    r#""scoped":{"$code":"y","$scope":{"v":{"$numberInt":"1"}}},"#,
+    r#""sym":{"$symbol":"s"},"#,
+    r#""ptr":{"$dbPointer":{"$ref":"c","$id":{"$oid":"57e193d7a9cc81b4027498b5"}}},"#,
+    r#""min":{"$minKey":1},"max":{"$maxKey":1},"u":{"$undefined":true},"#,
+    r#""arr":[{"$numberInt":"1"},{"sub":{"$numberLong":"2"}}]}"#,
+);
+
+fn canonical(value: &EjsonValue) -> String {
+    EjsonEncoder::canonical().encode_to_string(value).unwrap()
+}
+
+#[test]
+fn ejson_bson_transcode_roundtrip_matrix() {
+    let value = EjsonDecoder::new().decode_str(CANONICAL).unwrap();
+    let fields = to_bson(&value).unwrap();
+
+    assert_eq!(fields.len(), 19);
+    assert_eq!(fields[1], ("i".into(), BsonValue::Int32(-7)));
+    assert_eq!(fields[2], ("l".into(), BsonValue::Int64(1_234_567_890_123)));
+    assert_eq!(
+        fields[7],
+        ("at".into(), BsonValue::DateTime(1_356_351_330_501))
+    );
+    assert_eq!(
+        fields[10],
+        (
+            "ts".into(),
+            BsonValue::Timestamp(BsonTimestamp {
+                timestamp: 42,
+                increment: 1,
+            })
+        )
+    );
+    assert_eq!(fields[15], ("min".into(), BsonValue::MinKey));
+    assert_eq!(fields[17], ("u".into(), BsonValue::Undefined));
+
+    // EJSON -> BSON bytes -> EJSON keeps every typed value.
+    let bytes = BsonEncoder::new().encode(&fields);
+    let decoded = BsonDecoder::new().decode(&bytes).unwrap();
+    assert_eq!(decoded, fields);
+    let back = from_bson(&decoded);
+    assert_eq!(canonical(&back), canonical(&value));
+
+    assert_eq!(ejson_str_to_bson_doc(CANONICAL).unwrap(), bytes);
+}
+
+#[test]
+fn ejson_bson_transcode_untyped_numbers_matrix() {
+    // Relaxed input: integral JSON numbers become int32, the rest doubles.
+    let value = EjsonDecoder::new()
+        .decode_str(r#"{"a":1,"b":1.5,"c":{"d":[-2]}}"#)
+        .unwrap();
+    assert_eq!(
+        to_bson(&value).unwrap(),
+        vec![
+            ("a".to_string(), BsonValue::Int32(1)),
+            ("b".to_string(), BsonValue::Float(1.5)),
+            (
+                "c".to_string(),
+                BsonValue::Document(vec![(
+                    "d".to_string(),
+                    BsonValue::Array(vec![BsonValue::Int32(-2)])
+                )])
+            ),
+        ]
+    );
+    assert_eq!(from_bson(&[]), EjsonValue::Object(vec![]));
+
+    // Element type bytes: 0x10 int32 while the value fits, 0x12 int64 past it.
+    let bytes =
+        ejson_str_to_bson_doc(r#"{"a":2147483647,"b":2147483648,"c":-2147483649}"#).unwrap();
+    assert_eq!(bytes[4], 0x10);
+    assert_eq!(bytes[4 + 3 + 4], 0x12);
+    assert_eq!(bytes[4 + 3 + 4 + 3 + 8], 0x12);
+}
+
+#[test]
+fn ejson_bson_transcode_error_matrix() {
+    for value in [
+        EjsonValue::Null,
+        EjsonValue::Integer(1),
+        EjsonValue::Array(vec![]),
+    ] {
+        assert_eq!(to_bson(&value), None, "{value:?}");
+    }
+    assert_eq!(
+        ejson_str_to_bson_doc("[1]"),
+        Err(EjsonDecodeError::NotDocument)
+    );
+    assert_eq!(
+        ejson_str_to_bson_doc(r#"{"$oid":"57e193d7a9cc81b4027498b5"}"#),
+        Err(EjsonDecodeError::NotDocument)
+    );
+    assert_eq!(
+        ejson_str_to_bson_doc(r#"{"a":{"$oid":"nope"}}"#),
+        Err(EjsonDecodeError::InvalidObjectId)
+    );
+    assert!(matches!(
+        ejson_str_to_bson_doc("{"),
+        Err(EjsonDecodeError::InvalidJson(_))
+    ));
+}
@@ -0,0 +1,78 @@
+//! EJSON encoding into writers and decoding from byte slices.
+//!
+//! Not an upstream port: upstream encodes to and decodes from JavaScript
+//! strings.
+
+use json_joy_buffers::{Writer, WriterPool};
+use json_joy_json_pack::bson::BsonObjectId;
+use json_joy_json_pack::cbor::CborEncoder;
+use json_joy_json_pack::ejson::{EjsonDecodeError, EjsonDecoder, EjsonEncoder, EjsonValue};
+use json_joy_json_pack::PackValue;
+
+fn doc() -> EjsonValue {
+    EjsonValue::Object(vec![
+        (
+            "_id".into(),
+            EjsonValue::ObjectId(BsonObjectId {
+                timestamp: 0x5f0a_1b2c,
+                process: 0x01_0203_0405,
+                counter: 0x0a0b0c,
+            }),
+        ),
+        ("name".into(), EjsonValue::Str("Zoë ✓".into())),
+        (
+            "tags".into(),
+            EjsonValue::Array(vec![EjsonValue::Integer(1), EjsonValue::Float(0.5)]),
+        ),
+    ])
+}
+
+const DOC: &str = r#"{"_id":{"$oid":"5f0a1b2c01020304050a0b0c"},"name":"Zoë ✓","tags":[1,0.5]}"#;
+
+#[test]
+fn ejson_bytes_encode_matrix() {
+    let mut encoder = EjsonEncoder::new();
+    assert_eq!(encoder.encode(&doc()).unwrap(), DOC.as_bytes());
+    assert_eq!(encoder.encode_to_string(&doc()).unwrap(), DOC);
+
+    // Appends after bytes another encoder wrote, growing the writer.
+    let mut out = Writer::with_alloc_size(8);
+    let mut cbor = CborEncoder::new();
+    cbor.write_any(&PackValue::Integer(1));
+    out.buf(&cbor.writer.flush());
+    encoder.encode_into(&doc(), &mut out).unwrap();
+    encoder.encode_into(&EjsonValue::Null, &mut out).unwrap();
+    assert_eq!(out.flush(), [b"\x01", DOC.as_bytes(), b"null"].concat());
+
+    // The encoder's own writer is untouched.
+    assert_eq!(encoder.encode(&EjsonValue::Bool(true)).unwrap(), b"true");
+
+    let mut pool = WriterPool::new();
+    let mut encoder = EjsonEncoder::with_writer(pool.acquire());
+    assert_eq!(encoder.encode(&doc()).unwrap(), DOC.as_bytes());
+    pool.release(encoder.writer);
+}
+
+#[test]
+fn ejson_bytes_decode_matrix() {
+    let mut decoder = EjsonDecoder::new();
+    assert_eq!(decoder.decode(DOC.as_bytes()).unwrap(), doc());
+    assert_eq!(decoder.decode_str(DOC).unwrap(), doc());
+
+    // A document inside a larger buffer.
+    let buf = [b"\x00\x01", DOC.as_bytes(), b"\xff"].concat();
+    assert_eq!(decoder.decode(&buf[2..buf.len() - 1]).unwrap(), doc());
+
+    // Bytes outside strings need not be UTF-8; inside they must be.
+    let cases: &[(&[u8], EjsonDecodeError)] = &[
+        (b"[\"a\xff\"]", EjsonDecodeError::InvalidUtf8),
+        (b"\xff", EjsonDecodeError::InvalidJson(0)),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(&decoder.decode(bytes).unwrap_err(), expected);
+    }
+
+    // Round trip through the byte APIs only.
+    let bytes = EjsonEncoder::canonical().encode(&doc()).unwrap();
+    assert_eq!(decoder.decode(&bytes).unwrap(), doc());
+}
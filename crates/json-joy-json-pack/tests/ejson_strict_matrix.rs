@@ -0,0 +1,233 @@
+//! EJSON decoder strictness: canonical-only and relaxed-only parse modes,
+//! and `lossy: false` rejection of unknown `$` keys.
+//!
+//! Not an upstream port: upstream accepts any mix of canonical and relaxed
+//! forms and treats unknown `$` keys as plain object keys.
+
+use json_joy_json_pack::bson::{BsonFloat, BsonInt32, BsonInt64, BsonMinKey, BsonTimestamp};
+use json_joy_json_pack::ejson::{
+    EjsonDecodeError, EjsonDecoder, EjsonDecoderOptions, EjsonEncoder, EjsonEncoderOptions,
+    EjsonParseMode, EjsonValue,
+};
+
+fn decoder(mode: EjsonParseMode, lossy: bool) -> EjsonDecoder {
+    EjsonDecoder::with_options(EjsonDecoderOptions { mode, lossy })
+}
+
+fn decode(mode: EjsonParseMode, input: &str) -> Result<EjsonValue, EjsonDecodeError> {
+    decoder(mode, true).decode_str(input)
+}
+
+#[test]
+fn ejson_strict_canonical_matrix() {
+    use EjsonParseMode::Canonical;
+
+    let ok = [
+        (
+            r#"{"$numberInt":"1"}"#,
+            EjsonValue::Int32(BsonInt32 { value: 1 }),
+        ),
+        (
+            r#"{"$numberDouble":"1.5"}"#,
+            EjsonValue::BsonFloat(BsonFloat { value: 1.5 }),
+        ),
+        (
+            r#"{"$date":{"$numberLong":"1000"}}"#,
+            EjsonValue::Date {
+                timestamp_ms: 1000,
+                iso: None,
+            },
+        ),
+        (
+            r#"{"$timestamp":{"t":1,"i":2}}"#,
+            EjsonValue::Timestamp(BsonTimestamp {
+                timestamp: 1,
+                increment: 2,
+            }),
+        ),
+        (r#"{"$minKey":1}"#, EjsonValue::MinKey(BsonMinKey)),
+        (
+            r#"["a",true,null]"#,
+            EjsonValue::Array(vec![
+                EjsonValue::Str("a".into()),
+                EjsonValue::Bool(true),
+                EjsonValue::Null,
+            ]),
+        ),
+    ];
+    for (input, expected) in ok {
+        assert_eq!(decode(Canonical, input), Ok(expected), "{input}");
+    }
+
+    let bad = [
+        ("1", EjsonDecodeError::NotCanonical("Number")),
+        (r#"[1.5]"#, EjsonDecodeError::NotCanonical("Number")),
+        (r#"{"a":{"b":2}}"#, EjsonDecodeError::NotCanonical("Number")),
+        (
+            r#"{"$code":"x","$scope":{"n":1}}"#,
+            EjsonDecodeError::NotCanonical("Number"),
+        ),
+        (r#"{"$date":0}"#, EjsonDecodeError::NotCanonical("Number")),
+        (
+            r#"{"$date":"1970-01-01T00:00:00.000Z"}"#,
+            EjsonDecodeError::NotCanonical("Date"),
+        ),
+    ];
+    for (input, error) in bad {
+        assert_eq!(decode(Canonical, input), Err(error), "{input}");
+    }
+}
+
+#[test]
+fn ejson_strict_relaxed_matrix() {
+    use EjsonParseMode::Relaxed;
+
+    let ok = [
+        (
+            r#"{"a":1}"#,
+            EjsonValue::Object(vec![("a".into(), EjsonValue::Integer(1))]),
+        ),
+        (
+            r#"{"$numberDouble":"-Infinity"}"#,
+            EjsonValue::BsonFloat(BsonFloat {
+                value: f64::NEG_INFINITY,
+            }),
+        ),
+        (
+            r#"{"$date":"1970-01-01T00:00:01.000Z"}"#,
+            EjsonValue::Date {
+                timestamp_ms: 1000,
+                iso: None,
+            },
+        ),
+        // Out-of-range dates keep the canonical form in relaxed output.
+        (
+            r#"{"$date":{"$numberLong":"-1"}}"#,
+            EjsonValue::Date {
+                timestamp_ms: -1,
+                iso: None,
+            },
+        ),
+        (
+            r#"{"$date":{"$numberLong":"253402300800000"}}"#,
+            EjsonValue::Date {
+                timestamp_ms: 253_402_300_800_000,
+                iso: None,
+            },
+        ),
+    ];
+    for (input, expected) in ok {
+        assert_eq!(decode(Relaxed, input), Ok(expected), "{input}");
+    }
+
+    let bad = [
+        (
+            r#"{"$numberInt":"1"}"#,
+            EjsonDecodeError::NotRelaxed("Int32"),
+        ),
+        (
+            r#"[{"$numberLong":"1"}]"#,
+            EjsonDecodeError::NotRelaxed("Int64"),
+        ),
+        (
+            r#"{"$numberDouble":"1.5"}"#,
+            EjsonDecodeError::NotRelaxed("Double"),
+        ),
+        (
+            r#"{"$date":{"$numberLong":"1000"}}"#,
+            EjsonDecodeError::NotRelaxed("Date"),
+        ),
+        (r#"{"$date":1000}"#, EjsonDecodeError::NotRelaxed("Date")),
+    ];
+    for (input, error) in bad {
+        assert_eq!(decode(Relaxed, input), Err(error), "{input}");
+    }
+
+    // The default mode still accepts every form.
+    for input in [
+        r#"{"$numberInt":"1"}"#,
+        r#"{"$date":{"$numberLong":"1000"}}"#,
+        r#"{"$date":1000}"#,
+        r#"{"$date":"1970-01-01T00:00:01.000Z"}"#,
+    ] {
+        assert!(decode(EjsonParseMode::Any, input).is_ok(), "{input}");
+    }
+}
+
+#[test]
+fn ejson_strict_encoder_roundtrip_matrix() {
+    let value = EjsonValue::Object(vec![
+        ("i".into(), EjsonValue::Int32(BsonInt32 { value: 7 })),
+        ("l".into(), EjsonValue::Int64(BsonInt64 { value: -3 })),
+        ("d".into(), EjsonValue::BsonFloat(BsonFloat { value: 0.25 })),
+        (
+            "t".into(),
+            EjsonValue::Timestamp(BsonTimestamp {
+                timestamp: 5,
+                increment: 6,
+            }),
+        ),
+        (
+            "at".into(),
+            EjsonValue::Date {
+                timestamp_ms: 1000,
+                iso: Some("1970-01-01T00:00:01.000Z".into()),
+            },
+        ),
+        ("n".into(), EjsonValue::Array(vec![EjsonValue::Null])),
+    ]);
+
+    let canonical = EjsonEncoder::with_options(EjsonEncoderOptions { canonical: true })
+        .encode_to_string(&value)
+        .unwrap();
+    assert!(decode(EjsonParseMode::Canonical, &canonical).is_ok());
+    assert!(decode(EjsonParseMode::Relaxed, &canonical).is_err());
+
+    let relaxed = EjsonEncoder::new().encode_to_string(&value).unwrap();
+    assert!(decode(EjsonParseMode::Relaxed, &relaxed).is_ok());
+    assert!(decode(EjsonParseMode::Canonical, &relaxed).is_err());
+}
+
+#[test]
+fn ejson_strict_unknown_key_matrix() {
+    let mut lossy = EjsonDecoder::new();
+    let mut strict = decoder(EjsonParseMode::Any, false);
+
+    let input = r#"{"a":{"$foo":1}}"#;
+    assert_eq!(
+        lossy.decode_str(input).unwrap(),
+        EjsonValue::Object(vec![(
+            "a".into(),
+            EjsonValue::Object(vec![("$foo".into(), EjsonValue::Integer(1))])
+        )])
+    );
+    assert_eq!(
+        strict.decode_str(input),
+        Err(EjsonDecodeError::UnknownKey("$foo".into()))
+    );
+    assert_eq!(
+        strict.decode_str(r#"{"$scope":{}}"#),
+        Err(EjsonDecodeError::UnknownKey("$scope".into()))
+    );
+    assert_eq!(
+        strict.decode_str(r#"{"$ref":"c"}"#),
+        Err(EjsonDecodeError::UnknownKey("$ref".into()))
+    );
+
+    // DBRef keys are known once the object is a DBRef.
+    let db_ref = r#"{"$ref":"c","$id":{"$oid":"57e193d7a9cc81b4027498b5"},"$db":"d","x":1}"#;
+    assert_eq!(strict.decode_str(db_ref), lossy.decode_str(db_ref));
+    assert_eq!(
+        strict.decode_str(r#"{"$ref":"c","$id":1,"$foo":2}"#),
+        Err(EjsonDecodeError::UnknownKey("$foo".into()))
+    );
+
+    // Unknown keys next to a wrapper are still reported as extra keys.
+    assert_eq!(
+        strict.decode_str(r#"{"$numberInt":"1","$foo":2}"#),
+        Err(EjsonDecodeError::ExtraKeys("Int32"))
+    );
+    assert!(strict
+        .decode_str(r#"{"$dbPointer":{"$ref":"c","$id":{"$oid":"57e193d7a9cc81b4027498b5"}}}"#)
+        .is_ok());
+}
@@ -0,0 +1,152 @@
+//! Format detection from leading bytes, and `Codecs::decode_any` on top
+//! of it.
+//!
+//! Not an upstream port: upstream always knows the format it decodes.
+
+use json_joy_json_pack::bson::{BsonEncoder, BsonObjectId, BsonValue};
+use json_joy_json_pack::cbor::CborEncoder;
+use json_joy_json_pack::codecs::{CodecError, Codecs};
+use json_joy_json_pack::ion::IonEncoder;
+use json_joy_json_pack::json::JsonEncoder;
+use json_joy_json_pack::msgpack::MsgPackEncoder;
+use json_joy_json_pack::resp::RespEncoder;
+use json_joy_json_pack::ubjson::UbjsonEncoder;
+use json_joy_json_pack::{EncodingFormat, PackValue};
+
+fn doc() -> PackValue {
+    PackValue::Object(vec![
+        ("id".into(), PackValue::Integer(7)),
+        ("name".into(), PackValue::Str("ann".into())),
+        (
+            "tags".into(),
+            PackValue::Array(vec![PackValue::Str("a".into()), PackValue::Integer(-2)]),
+        ),
+        ("ok".into(), PackValue::Bool(true)),
+    ])
+}
+
+fn bson_doc() -> Vec<(String, BsonValue)> {
+    vec![
+        ("id".into(), BsonValue::Int32(7)),
+        ("name".into(), BsonValue::Str("ann".into())),
+        (
+            "tags".into(),
+            BsonValue::Array(vec![BsonValue::Str("a".into()), BsonValue::Int64(-2)]),
+        ),
+        ("ok".into(), BsonValue::Boolean(true)),
+    ]
+}
+
+#[test]
+fn encoding_sniff_encoders_matrix() {
+    let value = doc();
+    let mut cbor = vec![0xd9, 0xd9, 0xf7];
+    cbor.extend(CborEncoder::new().encode(&value));
+    let mut pretty = b"\n  ".to_vec();
+    pretty.extend(JsonEncoder::new().encode(&value));
+    let cases = [
+        (EncodingFormat::Cbor, cbor),
+        (
+            EncodingFormat::MsgPack,
+            MsgPackEncoder::new().encode(&value),
+        ),
+        (EncodingFormat::Json, JsonEncoder::new().encode(&value)),
+        (EncodingFormat::Json, pretty),
+        (EncodingFormat::Bson, BsonEncoder::new().encode(&bson_doc())),
+        (EncodingFormat::Ion, IonEncoder::new().encode(&value)),
+        (EncodingFormat::Ubjson, UbjsonEncoder::new().encode(&value)),
+        (EncodingFormat::Resp, RespEncoder::new().encode(&value)),
+    ];
+    // Compared as JSON: Ion reads non-negative integers back as unsigned.
+    let json = JsonEncoder::new().encode(&value);
+    let mut codecs = Codecs::new();
+    for (format, bytes) in cases {
+        assert_eq!(EncodingFormat::sniff(&bytes), Some(format), "{bytes:02x?}");
+        let decoded = codecs.decode_any(&bytes).unwrap();
+        assert_eq!(JsonEncoder::new().encode(&decoded), json, "{format:?}");
+    }
+}
+
+#[test]
+fn encoding_sniff_bytes_matrix() {
+    use EncodingFormat::*;
+    for (bytes, expected) in [
+        // Empty, blank and never-valid input.
+        (&b""[..], None),
+        (b" \r\n\t", None),
+        (b"\xc1", None),
+        // JSON scalars and containers.
+        (b"null", Some(Json)),
+        (b"-12.5", Some(Json)),
+        (b"\"s\"", Some(Json)),
+        (b"[]", Some(Json)),
+        (b"[[{\"a\":[]}]]", Some(Json)),
+        (b" { }", Some(Json)),
+        // UBJSON markers are upper case or type-tagged.
+        (b"T", Some(Ubjson)),
+        (b"Z", Some(Ubjson)),
+        (b"i\x05", Some(Ubjson)),
+        (b"[$i#i\x02\x01\x02", Some(Ubjson)),
+        (b"{i\x01aZ}", Some(Ubjson)),
+        (b"[[SU\x01x]]", Some(Ubjson)),
+        // RESP needs its line ending; a bare negative number is JSON.
+        (b"-ERR no\r\n", Some(Resp)),
+        (b"+OK\r\n", Some(Resp)),
+        (b"_\r\n", Some(Resp)),
+        (b"*1\r\n:1\r\n", Some(Resp)),
+        (b"-1\n", Some(Json)),
+        // Self-describe tag and version marker.
+        (b"\xd9\xd9\xf7\xf6", Some(Cbor)),
+        (b"\xe0\x01\x00\xea\x0f", Some(Ion)),
+        // BSON length must match and end in nul.
+        (b"\x05\x00\x00\x00\x00", Some(Bson)),
+        (b"\x06\x00\x00\x00\x00", Some(MsgPack)),
+        (b"\x05\x00\x00\x00\x01", Some(MsgPack)),
+        // Everything else that starts a MessagePack value.
+        (b"\x81\xa1a\x01", Some(MsgPack)),
+        (b"\xc0", Some(MsgPack)),
+        (b"\xcb\x3f\xf0\0\0\0\0\0\0", Some(MsgPack)),
+        (b"\x00", Some(MsgPack)),
+    ] {
+        assert_eq!(EncodingFormat::sniff(bytes), expected, "{bytes:02x?}");
+    }
+}
+
+#[test]
+fn encoding_sniff_decode_any_matrix() {
+    let mut codecs = Codecs::new();
+    assert!(matches!(
+        codecs.decode_any(b""),
+        Err(CodecError::UnknownFormat)
+    ));
+    assert!(matches!(
+        codecs.decode_any(b"{\"a\":"),
+        Err(CodecError::Json(_))
+    ));
+    assert!(matches!(
+        codecs.decode_any(b"\x92\x01"),
+        Err(CodecError::MsgPack(_))
+    ));
+    assert!(matches!(
+        codecs.decode_any(b"\x08\x00\x00\x00\x20a\x00\x00"),
+        Err(CodecError::Bson(_))
+    ));
+
+    // BSON-only types surface as relaxed Extended JSON.
+    let oid = BsonObjectId {
+        timestamp: 0x6500_0000,
+        process: 0x01_0203_0405,
+        counter: 0x06_0708,
+    };
+    let bytes = BsonEncoder::new().encode(&[("_id".into(), BsonValue::ObjectId(oid))]);
+    assert_eq!(
+        codecs.decode_any(&bytes).unwrap(),
+        PackValue::Object(vec![(
+            "_id".into(),
+            PackValue::Object(vec![(
+                "$oid".into(),
+                PackValue::Str("650000000102030405060708".into())
+            )])
+        )])
+    );
+}
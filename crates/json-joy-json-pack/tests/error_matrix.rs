@@ -0,0 +1,137 @@
+//! Per-format decoder errors and the crate-level `JsonPackError`.
+//!
+//! Not an upstream port: upstream throws untyped JS errors.
+
+use std::error::Error as _;
+
+use json_joy_json_pack::avro::AvroDecoder;
+use json_joy_json_pack::bson::BsonDecoder;
+use json_joy_json_pack::cbor::{CborDecoder, CborError};
+use json_joy_json_pack::codecs::CborJsonValueCodec;
+use json_joy_json_pack::ion::IonDecoder;
+use json_joy_json_pack::json::{JsonDecoder, JsonError};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackError};
+use json_joy_json_pack::resp::{RespDecodeError, RespDecoder};
+use json_joy_json_pack::ssh::SshDecoder;
+use json_joy_json_pack::ubjson::UbjsonDecoder;
+use json_joy_json_pack::xdr::XdrDecoder;
+use json_joy_json_pack::{JsonPackError, PackValue};
+
+fn decode_msgpack(input: &[u8]) -> Result<PackValue, JsonPackError> {
+    Ok(MsgPackDecoderFast::new().decode(input)?)
+}
+
+fn decode_resp(input: &[u8]) -> Result<PackValue, JsonPackError> {
+    Ok(RespDecoder::new().decode(input)?)
+}
+
+#[test]
+fn decoder_error_offset_matrix() {
+    // fixstr of length 3 with only 2 bytes of payload.
+    let err = MsgPackDecoderFast::new()
+        .decode(&[0xa3, b'a', b'b'])
+        .unwrap_err();
+    assert!(matches!(err, MsgPackError::UnexpectedEof(1)));
+    assert_eq!(err.offset(), Some(1));
+    let err = MsgPackDecoderFast::new()
+        .decode(&[0xa2, 0xc3, 0x28])
+        .unwrap_err();
+    assert!(matches!(err, MsgPackError::InvalidUtf8(1)));
+
+    let err = JsonDecoder::new().decode(b"[1, ?]").unwrap_err();
+    assert!(matches!(err, JsonError::Invalid(4)));
+    assert_eq!(err.to_string(), "invalid JSON at byte 4");
+    let err = JsonDecoder::new().decode(b"[\"\xff\"]").unwrap_err();
+    assert!(matches!(err, JsonError::InvalidUtf8(2)));
+
+    let err = UbjsonDecoder::new()
+        .decode(&[0x5b, 0x53, 0x55])
+        .unwrap_err();
+    assert_eq!(err.offset(), Some(3));
+
+    let err = BsonDecoder::new().decode(&[5, 0, 0]).unwrap_err();
+    assert_eq!(err.offset(), Some(0));
+
+    let err = RespDecoder::new().decode(b"$5\r\nab").unwrap_err();
+    assert_eq!(err, RespDecodeError::EndOfInput(4));
+    let err = RespDecoder::new().decode(b"\x01").unwrap_err();
+    assert_eq!(err, RespDecodeError::UnknownType(0x01, 0));
+    assert_eq!(err.to_string(), "unknown RESP type byte 0x01 at offset 0");
+}
+
+#[test]
+fn decoder_forged_count_matrix() {
+    // Huge announced counts fail on the missing items instead of reserving
+    // room for all of them.
+    for input in [
+        &[0xdd, 0xff, 0xff, 0xff, 0xff][..],
+        &[0xdf, 0xff, 0xff, 0xff, 0xff],
+    ] {
+        assert!(matches!(
+            MsgPackDecoderFast::new().decode(input),
+            Err(MsgPackError::UnexpectedEof(5))
+        ));
+    }
+    for head in [0x9b, 0xbb] {
+        let input = [head, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            CborDecoder::new().decode(&input),
+            Err(CborError::UnexpectedEof(9))
+        );
+    }
+    let mut decoder = XdrDecoder::new();
+    decoder.reset(&[0xff, 0xff, 0xff, 0xff]);
+    assert!(decoder.read_varlen_array(|d| d.read_int()).is_err());
+}
+
+#[test]
+fn json_pack_error_conversion_matrix() {
+    let err = decode_msgpack(&[0xa3, b'a']).unwrap_err();
+    assert_eq!(err.format(), "msgpack");
+    assert_eq!(err.offset(), Some(1));
+    assert_eq!(
+        err.to_string(),
+        "MessagePack: unexpected end of input at offset 1"
+    );
+    assert!(err.source().is_some());
+
+    let err = decode_resp(b"*2\r\n:1\r\n").unwrap_err();
+    assert_eq!(err.format(), "resp");
+    assert_eq!(err.offset(), Some(8));
+
+    let err = JsonPackError::from(CborError::Unsupported);
+    assert_eq!(err.format(), "cbor");
+    assert_eq!(err.offset(), None);
+
+    // Minor 28 is reserved.
+    let err = JsonPackError::from(CborDecoder::new().decode(&[0x82, 0x01, 0x1c]).unwrap_err());
+    assert_eq!(err.offset(), Some(2));
+    assert_eq!(err.to_string(), "CBOR: unexpected minor value at offset 2");
+
+    // Strings one byte short of their length.
+    let mut avro = AvroDecoder::new();
+    avro.reset(&[0x04, b'a']);
+    let err = JsonPackError::from(avro.read_string().unwrap_err());
+    assert_eq!((err.format(), err.offset()), ("avro", Some(1)));
+
+    let err = IonDecoder::new()
+        .decode(&[0xe0, 0x01, 0x00, 0xea, 0x83, b'a', b'b'])
+        .unwrap_err();
+    let err = JsonPackError::from(err);
+    assert_eq!((err.format(), err.offset()), ("ion", Some(5)));
+
+    let mut xdr = XdrDecoder::new();
+    xdr.reset(&[0, 0, 0, 5, b'a']);
+    let err = JsonPackError::from(xdr.read_string().unwrap_err());
+    assert_eq!((err.format(), err.offset()), ("xdr", Some(4)));
+
+    let mut ssh = SshDecoder::new();
+    ssh.reset(&[0, 0, 0, 1, 0xff]);
+    let err = JsonPackError::from(ssh.read_str().unwrap_err());
+    assert_eq!((err.format(), err.offset()), ("ssh", Some(4)));
+
+    // Codec errors flatten into the matching format variant.
+    let mut codec = CborJsonValueCodec::default();
+    let err = JsonPackError::from(codec.decode(&[0x1c]).unwrap_err());
+    assert!(matches!(err, JsonPackError::Cbor(_)));
+}
@@ -0,0 +1,220 @@
+//! Half-precision floats and NaN payloads through CBOR and BJData.
+//!
+//! Not an upstream port: upstream never writes f16, reads every NaN as the
+//! canonical one and has no BJData mode. Needs the default `f16` feature.
+
+#![cfg(feature = "f16")]
+
+use json_joy_json_pack::cbor::{
+    CborDecoder, CborDecoderOptions, CborEncoder, CborEncoderFast, CborEncoderOptions,
+    CborEncoderStable,
+};
+use json_joy_json_pack::ubjson::{
+    UbjsonDecoder, UbjsonDecoderOptions, UbjsonEncoder, UbjsonEncoderOptions, UbjsonError,
+};
+use json_joy_json_pack::{JsonPackExtension, PackValue};
+
+/// Readings that fit f16, and one (0.1) that does not.
+fn readings() -> Vec<f64> {
+    vec![
+        1.5,
+        -0.0,
+        65504.0,
+        -2.0f64.powi(-24),
+        0.0999755859375,
+        f64::INFINITY,
+        0.1,
+    ]
+}
+
+fn floats(value: &PackValue) -> Vec<u64> {
+    match value {
+        PackValue::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                PackValue::Float(f) => f.to_bits(),
+                other => panic!("not a float: {other:?}"),
+            })
+            .collect(),
+        other => panic!("not an array: {other:?}"),
+    }
+}
+
+fn array(values: &[f64]) -> PackValue {
+    PackValue::Array(values.iter().copied().map(PackValue::Float).collect())
+}
+
+/// Input, decoded bits, output with f16, output without.
+type NanCase = (&'static [u8], u64, &'static [u8], &'static [u8]);
+
+#[test]
+fn float16_cbor_matrix() {
+    let options = CborEncoderOptions {
+        float16: true,
+        nan_payloads: true,
+    };
+    let value = array(&readings());
+    let bytes = CborEncoder::with_options(options.clone()).encode(&value);
+    assert_eq!(bytes.iter().filter(|&&b| b == 0xf9).count(), 6);
+    assert_eq!(bytes[1..4], [0xf9, 0x3e, 0x00]);
+    let decoded = CborDecoder::new().decode(&bytes).unwrap();
+    assert_eq!(floats(&decoded), floats(&value));
+    assert_eq!(CborEncoder::with_options(options).encode(&decoded), bytes);
+
+    // Upstream output is unchanged.
+    assert!(!CborEncoder::new().encode(&value).contains(&0xf9));
+}
+
+#[test]
+fn float16_cbor_nan_payload_matrix() {
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        nan_payloads: true,
+        ..Default::default()
+    });
+    let mut f16 = CborEncoder::with_options(CborEncoderOptions {
+        float16: true,
+        nan_payloads: true,
+    });
+    let mut wide = CborEncoder::with_options(CborEncoderOptions {
+        nan_payloads: true,
+        ..Default::default()
+    });
+    // NaNs are written in the smallest width that keeps their payload.
+    let cases: &[NanCase] = &[
+        (
+            &[0xf9, 0x7e, 0x00],
+            0x7ff8_0000_0000_0000,
+            &[0xf9, 0x7e, 0x00],
+            &[0xfa, 0x7f, 0xc0, 0, 0],
+        ),
+        (
+            &[0xf9, 0xfc, 0x01],
+            0xfff0_0400_0000_0000,
+            &[0xf9, 0xfc, 0x01],
+            &[0xfa, 0xff, 0x80, 0x20, 0],
+        ),
+        (
+            &[0xfa, 0x7f, 0x80, 0x00, 0x01],
+            0x7ff0_0000_2000_0000,
+            &[0xfa, 0x7f, 0x80, 0x00, 0x01],
+            &[0xfa, 0x7f, 0x80, 0x00, 0x01],
+        ),
+        (
+            &[0xfa, 0xff, 0xc0, 0x00, 0x00],
+            0xfff8_0000_0000_0000,
+            &[0xf9, 0xfe, 0x00],
+            &[0xfa, 0xff, 0xc0, 0x00, 0x00],
+        ),
+        (
+            &[0xfb, 0x7f, 0xf0, 0, 0, 0, 0, 0, 0x01],
+            0x7ff0_0000_0000_0001,
+            &[0xfb, 0x7f, 0xf0, 0, 0, 0, 0, 0, 0x01],
+            &[0xfb, 0x7f, 0xf0, 0, 0, 0, 0, 0, 0x01],
+        ),
+    ];
+    for &(bytes, bits, f16_bytes, wide_bytes) in cases {
+        let PackValue::Float(nan) = decoder.decode(bytes).unwrap() else {
+            panic!("not a float");
+        };
+        assert_eq!(nan.to_bits(), bits, "{bytes:02x?}");
+        assert_eq!(
+            f16.encode(&PackValue::Float(nan)),
+            f16_bytes,
+            "{bytes:02x?}"
+        );
+        assert_eq!(
+            wide.encode(&PackValue::Float(nan)),
+            wide_bytes,
+            "{bytes:02x?}"
+        );
+    }
+
+    // Without the option every NaN is the canonical one.
+    let PackValue::Float(nan) = CborDecoder::new().decode(&[0xf9, 0xfc, 0x01]).unwrap() else {
+        panic!("not a float");
+    };
+    assert!(nan.is_nan());
+    assert_eq!(
+        CborEncoder::with_options(CborEncoderOptions {
+            float16: true,
+            ..Default::default()
+        })
+        .encode(&PackValue::Float(nan)),
+        [0xf9, 0x7e, 0x00]
+    );
+}
+
+#[test]
+fn float16_cbor_fast_stable_matrix() {
+    let nan = f64::from_bits(0xfff0_0400_0000_0000);
+    let mut values = readings();
+    values.push(nan);
+    let value = array(&values);
+    let decoder = CborDecoder::with_options(CborDecoderOptions {
+        nan_payloads: true,
+        ..Default::default()
+    });
+
+    let fast = CborEncoderFast::new().with_float16(true).encode(&value);
+    assert_eq!(fast.iter().filter(|&&b| b == 0xf9).count(), 7);
+    assert_eq!(fast[fast.len() - 3..], [0xf9, 0xfc, 0x01]);
+    assert_eq!(floats(&decoder.decode(&fast).unwrap()), floats(&value));
+
+    let stable = CborEncoderStable::new().with_float16(true).encode(&value);
+    assert_eq!(stable, fast);
+
+    // Without the builder both keep their upstream widths.
+    let fast = CborEncoderFast::new().encode(&value);
+    assert!(!fast.contains(&0xf9));
+    assert_eq!(fast.len(), 1 + 8 * 9);
+    let stable = CborEncoderStable::new().encode(&value);
+    assert!(!stable.contains(&0xf9));
+    assert!(stable.contains(&0xfa));
+}
+
+#[test]
+fn float16_bjdata_matrix() {
+    let encoder_options = UbjsonEncoderOptions {
+        bjdata: true,
+        float16: true,
+        ..Default::default()
+    };
+    let decoder = UbjsonDecoder::with_options(UbjsonDecoderOptions {
+        bjdata: true,
+        ..Default::default()
+    });
+    let nan = f64::from_bits(0xfff0_0400_0000_0000);
+    let mut values = readings();
+    values.push(nan);
+    let value = array(&values);
+    let bytes = UbjsonEncoder::with_options(encoder_options).encode(&value);
+    // `h` is little-endian like every BJData number.
+    assert_eq!(bytes[1..4], [b'h', 0x00, 0x3e]);
+    assert_eq!(bytes.iter().filter(|&&b| b == b'h').count(), 7);
+    assert_eq!(bytes[bytes.len() - 4..], [b'h', 0x01, 0xfc, b']']);
+    assert_eq!(floats(&decoder.decode(&bytes).unwrap()), floats(&value));
+
+    // UBJSON readers do not know the marker.
+    assert!(matches!(
+        UbjsonDecoder::new().decode(&bytes),
+        Err(UbjsonError::UnexpectedByte(b'h', 1))
+    ));
+    assert!(!UbjsonEncoder::new().encode(&value).contains(&b'h'));
+    // Plain UBJSON has no `h`, so `float16` alone changes nothing.
+    let ubjson = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        float16: true,
+        ..Default::default()
+    })
+    .encode(&value);
+    assert_eq!(ubjson, UbjsonEncoder::new().encode(&value));
+
+    // Typed arrays of `h` have 2-byte items.
+    let typed = b"[$h#U\x02\x00\x3e\x00\x7c";
+    assert_eq!(
+        decoder.decode(typed).unwrap(),
+        PackValue::Extension(Box::new(JsonPackExtension::new(
+            b'h' as u64,
+            PackValue::Bytes(vec![0x00, 0x3e, 0x00, 0x7c]),
+        )))
+    );
+}
@@ -0,0 +1,80 @@
+//! `util::format_f64_js` and the encoders that stringify floats with it.
+//!
+//! Not an upstream port: upstream gets float formatting from the
+//! JavaScript runtime. Expected strings are `String(x)` in JavaScript.
+
+use json_joy_json_pack::ejson::{EjsonEncoder, EjsonEncoderOptions, EjsonValue};
+use json_joy_json_pack::json::JsonEncoder;
+use json_joy_json_pack::resp::RespEncoder;
+use json_joy_json_pack::util::format_f64_js;
+use json_joy_json_pack::PackValue;
+
+const CASES: &[(f64, &str)] = &[
+    (0.0, "0"),
+    (-0.0, "0"),
+    (1.0, "1"),
+    (-1.0, "-1"),
+    (0.1, "0.1"),
+    (0.1 + 0.2, "0.30000000000000004"),
+    (1.0 / 3.0, "0.3333333333333333"),
+    (123.456, "123.456"),
+    (-1.5, "-1.5"),
+    (100.0, "100"),
+    (9007199254740992.0, "9007199254740992"),
+    (1e20, "100000000000000000000"),
+    (123456789012345680000.0, "123456789012345680000"),
+    (1e21, "1e+21"),
+    (-1.5e21, "-1.5e+21"),
+    (1.7976931348623157e308, "1.7976931348623157e+308"),
+    (0.000001, "0.000001"),
+    (0.0000012345, "0.0000012345"),
+    (1e-7, "1e-7"),
+    (-1.5e-7, "-1.5e-7"),
+    (1.2345e-10, "1.2345e-10"),
+    (5e-324, "5e-324"),
+    (2.2250738585072014e-308, "2.2250738585072014e-308"),
+    (f64::INFINITY, "Infinity"),
+    (f64::NEG_INFINITY, "-Infinity"),
+    (f64::NAN, "NaN"),
+];
+
+#[test]
+fn format_f64_js_matrix() {
+    for &(value, expected) in CASES {
+        let formatted = format_f64_js(value);
+        assert_eq!(formatted, expected, "{value:e}");
+        if value.is_finite() {
+            assert_eq!(formatted.parse::<f64>().unwrap(), value, "{value:e}");
+        }
+    }
+}
+
+#[test]
+fn format_f64_js_encoders_matrix() {
+    let mut json = JsonEncoder::new();
+    let mut resp = RespEncoder::new();
+    for &(value, expected) in CASES.iter().filter(|(value, _)| value.is_finite()) {
+        assert_eq!(
+            json.encode(&PackValue::Float(value)),
+            expected.as_bytes(),
+            "{value:e}"
+        );
+        assert_eq!(
+            resp.encode(&PackValue::Float(value)),
+            format!(",{expected}\r\n").as_bytes(),
+            "{value:e}"
+        );
+        assert_eq!(
+            EjsonEncoder::new()
+                .encode_to_string(&EjsonValue::Float(value))
+                .unwrap(),
+            expected,
+            "{value:e}"
+        );
+    }
+
+    let canonical = EjsonEncoder::with_options(EjsonEncoderOptions { canonical: true })
+        .encode_to_string(&EjsonValue::Number(1e-7))
+        .unwrap();
+    assert_eq!(canonical, r#"{"$numberDouble":"1e-7"}"#);
+}
@@ -0,0 +1,120 @@
+//! Structural hashing of values.
+//!
+//! Not an upstream port. Covers the FNV-1a and SHA-256 reference vectors,
+//! invariance under object key order, agreement between `PackValue` and
+//! `serde_json::Value`, and pinned hashes that must not change between
+//! releases.
+
+use json_joy_json_pack::cbor::CborEncoderStable;
+use json_joy_json_pack::hash::{
+    fnv1a128, fnv1a64, hash, hash128, hash64, hash_sha256, CanonicalEncode, HashAlgorithm,
+};
+use json_joy_json_pack::{pack, JsonPackExtension, PackValue};
+use serde_json::json;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[test]
+fn hash_fnv_reference_vectors() {
+    let cases: [(&[u8], u64, u128); 3] = [
+        (b"", 0xcbf29ce484222325, 0x6c62272e07bb014262b821756295c58d),
+        (b"a", 0xaf63dc4c8601ec8c, 0xd228cb696f1a8caf78912b704e4a8964),
+        (
+            b"foobar",
+            0x85944171f73967e8,
+            0x343e1662793c64bf6f0d3597ba446f18,
+        ),
+    ];
+    for (data, h64, h128) in cases {
+        assert_eq!(fnv1a64(data), h64, "{data:?}");
+        assert_eq!(fnv1a128(data), h128, "{data:?}");
+    }
+}
+
+#[test]
+fn hash_matches_canonical_encoding_matrix() {
+    let values = [
+        json!(null),
+        json!(1),
+        json!(-1.5),
+        json!("abc"),
+        json!([1, [2], {}]),
+        json!({"b": 1, "a": {"d": [], "c": null}}),
+    ];
+    for value in values {
+        let bytes = CborEncoderStable::new().encode_json(&value);
+        assert_eq!(value.canonical_bytes(), bytes);
+        assert_eq!(hash64(&value), fnv1a64(&bytes));
+        assert_eq!(hash128(&value), fnv1a128(&bytes));
+        assert_eq!(
+            hash(&value, HashAlgorithm::Fnv64),
+            fnv1a64(&bytes).to_be_bytes()
+        );
+        assert_eq!(
+            hash(&value, HashAlgorithm::Fnv128),
+            fnv1a128(&bytes).to_be_bytes()
+        );
+        assert_eq!(hash(&value, HashAlgorithm::Sha256), hash_sha256(&value));
+
+        let packed = PackValue::from(value.clone());
+        assert_eq!(hash64(&packed), hash64(&value));
+        assert_eq!(hash128(&packed), hash128(&value));
+        assert_eq!(hash_sha256(&packed), hash_sha256(&value));
+    }
+}
+
+#[test]
+fn hash_structural_matrix() {
+    // Equal up to key order.
+    let a = pack!({ "x": 1, "y": { "p": [1, 2], "q": "s" }, "": null });
+    let b = pack!({ "": null, "y": { "q": "s", "p": [1, 2] }, "x": 1 });
+    assert_eq!(hash64(&a), hash64(&b));
+    assert_eq!(hash128(&a), hash128(&b));
+    assert_eq!(hash_sha256(&a), hash_sha256(&b));
+    assert_eq!(hash64(&pack!(undefined)), hash64(&pack!(null)));
+
+    // Distinct values.
+    let values = [
+        pack!(null),
+        pack!(false),
+        pack!(0),
+        pack!(0.5),
+        pack!(""),
+        pack!(b""),
+        pack!([]),
+        pack!({}),
+        pack!([null]),
+        pack!([[]]),
+        pack!({ "": null }),
+        pack!({ "a": 1 }),
+        pack!({ "a": "1" }),
+        pack!([1, 2]),
+        pack!([2, 1]),
+        pack!(JsonPackExtension::new(1, pack!(0))),
+    ];
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            assert_ne!(hash64(a), hash64(b), "{a:?} {b:?}");
+            assert_ne!(hash128(a), hash128(b), "{a:?} {b:?}");
+            assert_ne!(hash_sha256(a), hash_sha256(b), "{a:?} {b:?}");
+        }
+    }
+}
+
+#[test]
+fn hash_pinned_matrix() {
+    // Changing these breaks every stored cache key.
+    let value = json!({"id": 7, "tags": ["a", "b"], "ok": true});
+    assert_eq!(
+        hex(&value.canonical_bytes()),
+        "a362696407626f6bf564746167738261616162"
+    );
+    assert_eq!(hash64(&value), 0xd978e3b82d65c1b5);
+    assert_eq!(
+        hex(&hash_sha256(&json!(""))),
+        // SHA-256 of the single byte 0x60.
+        "8d33f520a3c4cef80d2453aef81b612bfe1cb44c8b2025630ad38662763f13d3"
+    );
+}
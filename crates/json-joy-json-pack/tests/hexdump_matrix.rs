@@ -0,0 +1,205 @@
+//! Annotated hex dumps of CBOR, MessagePack and RESP, including truncated
+//! and corrupt input.
+//!
+//! Not an upstream port.
+
+use json_joy_json_pack::cbor::CborEncoder;
+use json_joy_json_pack::hexdump::{hexdump_annotated, HexdumpFormat};
+use json_joy_json_pack::msgpack::MsgPackEncoderFast;
+use json_joy_json_pack::resp::RespEncoder;
+use json_joy_json_pack::PackValue;
+
+fn sample() -> PackValue {
+    PackValue::Object(vec![
+        ("id".into(), PackValue::Integer(-300)),
+        (
+            "tags".into(),
+            PackValue::Array(vec![
+                PackValue::Str("a long string that needs more than eight bytes".into()),
+                PackValue::Bool(true),
+                PackValue::Null,
+            ]),
+        ),
+        ("raw".into(), PackValue::Bytes(vec![1, 2, 3])),
+        ("pi".into(), PackValue::Float(3.25)),
+        ("big".into(), PackValue::UInteger(u64::MAX)),
+    ])
+}
+
+#[test]
+fn hexdump_cbor_matrix() {
+    let bytes = CborEncoder::new().encode(&sample());
+    let dump = hexdump_annotated(&bytes, HexdumpFormat::Cbor);
+    let expected = "\
+0000  a5                          map(5)
+0001  62 69 64                      text(2) \"id\"
+0004  39 01 2b                      nint -300
+0007  64 74 61 67 73                text(4) \"tags\"
+000c  83                            array(3)
+000d  78 2e 61 20 6c 6f 6e 67 ..      text(46) \"a long string that needs more th\"..
+003d  f5                              true
+003e  f6                              null
+003f  63 72 61 77                   text(3) \"raw\"
+0043  43 01 02 03                   bytes(3)
+0047  62 70 69                      text(2) \"pi\"
+004a  fa 40 50 00 00                float32 3.25
+004f  63 62 69 67                   text(3) \"big\"
+0053  1b ff ff ff ff ff ff ff ..    uint 18446744073709551615
+";
+    assert_eq!(dump, expected);
+
+    // Indefinite lengths, tags, simple values and a CBOR sequence.
+    let bytes = [
+        0x9f, 0x01, 0xc1, 0x1a, 0x00, 0x01, 0x00, 0x00, 0xff, 0xf7, 0xf9, 0x3e, 0x00, 0xfb, 0x3f,
+        0xf8, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(
+        hexdump_annotated(&bytes, HexdumpFormat::Cbor),
+        "\
+0000  9f                          array(_)
+0001  01                            uint 1
+0002  c1                            tag(1)
+0003  1a 00 01 00 00                  uint 65536
+0008  ff                          break
+0009  f7                          undefined
+000a  f9 3e 00                    float16 1.5
+000d  fb 3f f8 00 00 00 00 00 ..  float64 1.5
+"
+    );
+}
+
+#[test]
+fn hexdump_msgpack_matrix() {
+    let bytes = MsgPackEncoderFast::new().encode(&sample());
+    let dump = hexdump_annotated(&bytes, HexdumpFormat::MsgPack);
+    let expected = "\
+0000  85                          fixmap(5)
+0001  a2 69 64                      fixstr(2) \"id\"
+0004  d1 fe d4                      int16 -300
+0007  a4 74 61 67 73                fixstr(4) \"tags\"
+000c  93                            fixarray(3)
+000d  d9 2e 61 20 6c 6f 6e 67 ..      str8(46) \"a long string that needs more th\"..
+003d  c3                              true
+003e  c0                              nil
+003f  a3 72 61 77                   fixstr(3) \"raw\"
+0043  c4 03 01 02 03                bin8(3)
+0048  a2 70 69                      fixstr(2) \"pi\"
+004b  cb 40 0a 00 00 00 00 00 ..    float64 3.25
+0054  a3 62 69 67                   fixstr(3) \"big\"
+0058  cb 43 f0 00 00 00 00 00 ..    float64 18446744073709552000
+";
+    assert_eq!(dump, expected);
+
+    let bytes = [
+        0xe0, 0x7f, 0xcc, 0xff, 0xd4, 0x05, 0x01, 0xcb, 0xbf, 0xf8, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(
+        hexdump_annotated(&bytes, HexdumpFormat::MsgPack),
+        "\
+0000  e0                          negative fixint -32
+0001  7f                          positive fixint 127
+0002  cc ff                       uint8 255
+0004  d4 05 01                    fixext1(1) type 5
+0007  cb bf f8 00 00 00 00 00 ..  float64 -1.5
+"
+    );
+}
+
+#[test]
+fn hexdump_resp_matrix() {
+    let value = PackValue::Array(vec![
+        PackValue::Str("SET".into()),
+        PackValue::Bytes(b"key".to_vec()),
+        PackValue::Integer(42),
+        PackValue::Null,
+    ]);
+    let bytes = RespEncoder::new().encode(&value);
+    assert_eq!(
+        hexdump_annotated(&bytes, HexdumpFormat::Resp),
+        "\
+0000  2a 34 0d 0a                 array(4)
+0004  2b 53 45 54 0d 0a             simple string \"SET\"
+000a  24 33 0d 0a                   bulk string(3)
+000e  6b 65 79 0d 0a                  \"key\"
+0013  3a 34 32 0d 0a                integer 42
+0018  5f 0d 0a                      null
+"
+    );
+
+    // RESP3 maps, null bulk strings and pipelined frames.
+    let bytes = b"%1\r\n+k\r\n#t\r\n$-1\r\n,1.5\r\n";
+    assert_eq!(
+        hexdump_annotated(bytes, HexdumpFormat::Resp),
+        "\
+0000  25 31 0d 0a                 map(1)
+0004  2b 6b 0d 0a                   simple string \"k\"
+0008  23 74 0d 0a                   boolean t
+000c  24 2d 31 0d 0a              null bulk string
+0011  2c 31 2e 35 0d 0a           double 1.5
+"
+    );
+}
+
+#[test]
+fn hexdump_malformed_matrix() {
+    // A truncated CBOR string keeps the lines before it and dumps the rest.
+    assert_eq!(
+        hexdump_annotated(&[0x82, 0x01, 0x65, b'a', b'b'], HexdumpFormat::Cbor),
+        "\
+0000  82                          array(2)
+0001  01                            uint 1
+0002  !! truncated: needs 5 more bytes, 2 left
+0002  65 61 62                    |eab|
+"
+    );
+    assert_eq!(
+        hexdump_annotated(&[0x1c, 0x00], HexdumpFormat::Cbor),
+        "\
+0000  !! reserved additional info 28 (initial byte 0x1c)
+0000  1c 00                       |..|
+"
+    );
+    assert_eq!(
+        hexdump_annotated(&[0x81], HexdumpFormat::Cbor),
+        "\
+0000  81                          array(1)
+0001  !! truncated: missing item
+"
+    );
+    assert!(hexdump_annotated(&[0xff], HexdumpFormat::Cbor).contains("!! unexpected break"));
+    assert!(hexdump_annotated(&[0x1f], HexdumpFormat::Cbor)
+        .contains("!! indefinite length on major type 0"));
+
+    assert_eq!(
+        hexdump_annotated(&[0x91, 0xc1, 0x00], HexdumpFormat::MsgPack),
+        "\
+0000  91                          fixarray(1)
+0001  !! never-used type byte 0xc1
+0001  c1 00                       |..|
+"
+    );
+    assert!(
+        hexdump_annotated(&[0xdd, 0xff, 0xff, 0xff, 0xff], HexdumpFormat::MsgPack)
+            .contains("!! truncated: missing item")
+    );
+
+    // RESP: a raw tail longer than one line, and a missing CRLF.
+    assert_eq!(
+        hexdump_annotated(b":1\r\n?not resp at all", HexdumpFormat::Resp),
+        "\
+0000  3a 31 0d 0a                 integer 1
+0004  !! unknown RESP type byte 0x3f
+0004  3f 6e 6f 74 20 72 65 73     |?not res|
+000c  70 20 61 74 20 61 6c 6c     |p at all|
+"
+    );
+    assert!(hexdump_annotated(b"$5\r\nhel", HexdumpFormat::Resp)
+        .contains("!! truncated: needs 5 more bytes, 3 left"));
+    assert!(hexdump_annotated(b"+OK", HexdumpFormat::Resp).contains("!! truncated: missing CRLF"));
+    assert!(hexdump_annotated(b"*x\r\n", HexdumpFormat::Resp).contains("!! invalid length \"x\""));
+
+    // Deep nesting stops instead of overflowing the stack.
+    let deep = vec![0x81; 10_000];
+    assert!(hexdump_annotated(&deep, HexdumpFormat::Cbor).contains("!! nesting deeper than 256"));
+    assert_eq!(hexdump_annotated(&[], HexdumpFormat::Cbor), "");
+}
@@ -0,0 +1,361 @@
+//! Server-Sent Events and multipart body framing.
+//!
+//! Not an upstream port: SSE cases follow the WHATWG event-stream examples,
+//! multipart cases follow RFC 2046 section 5.1.1 and RFC 9110 section 14.6.
+
+use json_joy_json_pack::http_frames::{
+    ContentRange, HttpFrameError, MultipartDecoder, MultipartEncoder, MultipartPart, SseDecoder,
+    SseEncoder, SseEvent,
+};
+use json_joy_json_pack::JsonPackError;
+
+type Headers<'a> = &'a [(&'a str, &'a str)];
+
+fn sse_all(chunks: &[&[u8]]) -> Vec<SseEvent> {
+    let mut decoder = SseDecoder::new();
+    let mut events = Vec::new();
+    for chunk in chunks {
+        decoder.push(chunk);
+        while let Some(event) = decoder.read_event() {
+            events.push(event);
+        }
+    }
+    events
+}
+
+#[test]
+fn sse_encode_matrix() {
+    let mut encoder = SseEncoder::new();
+    let cases = [
+        (SseEvent::new("hello"), "data: hello\n\n"),
+        (SseEvent::new(""), "data:\n\n"),
+        (
+            SseEvent::new("a\nb\r\nc\rd"),
+            "data: a\ndata: b\ndata: c\ndata: d\n\n",
+        ),
+        (
+            SseEvent {
+                id: Some("7".into()),
+                event: Some("patch".into()),
+                data: r#"{"op":"add"}"#.into(),
+                retry: Some(1500),
+            },
+            "id: 7\nevent: patch\nretry: 1500\ndata: {\"op\":\"add\"}\n\n",
+        ),
+    ];
+    for (event, expected) in cases {
+        let bytes = encoder.encode(&event).unwrap();
+        assert_eq!(String::from_utf8(bytes.clone()).unwrap(), expected);
+        let decoded = sse_all(&[&bytes]);
+        let expected_data = event.data.replace("\r\n", "\n").replace('\r', "\n");
+        assert_eq!(
+            decoded,
+            [SseEvent {
+                data: expected_data,
+                ..event
+            }]
+        );
+    }
+    assert_eq!(encoder.encode_comment("ping\nok"), b": ping\n: ok\n");
+    assert_eq!(encoder.encode_comment(""), b":\n");
+
+    let err = encoder
+        .encode(&SseEvent {
+            id: Some("a\nb".into()),
+            ..SseEvent::new("x")
+        })
+        .unwrap_err();
+    assert_eq!(err, HttpFrameError::InvalidSseField("id"));
+    let err = encoder
+        .encode(&SseEvent {
+            event: Some("a\rb".into()),
+            ..SseEvent::new("x")
+        })
+        .unwrap_err();
+    assert_eq!(err, HttpFrameError::InvalidSseField("event"));
+}
+
+#[test]
+fn sse_decode_spec_matrix() {
+    // WHATWG example: comment, multi-line data, id persistence.
+    let stream = b": test stream\n\ndata: first event\nid: 1\n\ndata:second event\nid\n\ndata:  third event\n\n";
+    let events = sse_all(&[stream]);
+    assert_eq!(
+        events,
+        [
+            SseEvent {
+                id: Some("1".into()),
+                ..SseEvent::new("first event")
+            },
+            SseEvent {
+                id: Some("".into()),
+                ..SseEvent::new("second event")
+            },
+            SseEvent::new(" third event"),
+        ]
+    );
+
+    // Empty data fields and a trailing unterminated event.
+    let events = sse_all(&[b"data\n\ndata\ndata\n\ndata:"]);
+    assert_eq!(events, [SseEvent::new(""), SseEvent::new("\n")]);
+
+    // Events without data are not dispatched; unknown fields are ignored.
+    let events = sse_all(&[b"event: x\nfoo: bar\n\nretry: 1x\nretry: 20\ndata: y\n\n"]);
+    assert_eq!(
+        events,
+        [SseEvent {
+            retry: Some(20),
+            ..SseEvent::new("y")
+        }]
+    );
+}
+
+#[test]
+fn sse_decode_chunking_matrix() {
+    let stream = "\u{FEFF}id: 42\r\nevent: tick\r\ndata: a\rdata: b\r\n\r\ndata: ünï\n\n";
+    let expected = [
+        SseEvent {
+            id: Some("42".into()),
+            event: Some("tick".into()),
+            data: "a\nb".into(),
+            retry: None,
+        },
+        SseEvent::new("ünï"),
+    ];
+    let bytes = stream.as_bytes();
+    assert_eq!(sse_all(&[bytes]), expected);
+    for split in 0..bytes.len() {
+        let (a, b) = bytes.split_at(split);
+        assert_eq!(sse_all(&[a, b]), expected, "split at {split}");
+    }
+    let single: Vec<&[u8]> = bytes.chunks(1).collect();
+    assert_eq!(sse_all(&single), expected);
+
+    let mut decoder = SseDecoder::new();
+    decoder.push(b"id: 5\ndata: x\n\ndata: y\n\n");
+    assert_eq!(decoder.read_event().unwrap().id.as_deref(), Some("5"));
+    assert_eq!(decoder.read_event().unwrap().id, None);
+    assert_eq!(decoder.last_event_id(), "5");
+    assert_eq!(decoder.read_event(), None);
+
+    // Invalid UTF-8 is replaced rather than rejected.
+    assert_eq!(sse_all(&[b"data: \xff\n\n"]), [SseEvent::new("\u{FFFD}")]);
+}
+
+#[test]
+fn multipart_encode_matrix() {
+    let mut encoder = MultipartEncoder::new("THIS_STRING_SEPARATES").unwrap();
+    assert_eq!(
+        encoder.content_type("byteranges"),
+        "multipart/byteranges; boundary=THIS_STRING_SEPARATES"
+    );
+    let mut body = Vec::new();
+    body.extend(
+        encoder
+            .encode_byterange(
+                "application/pdf",
+                ContentRange {
+                    start: 500,
+                    end: 999,
+                    total: Some(8000),
+                },
+                b"...the first range...",
+            )
+            .unwrap(),
+    );
+    body.extend(
+        encoder
+            .encode_byterange(
+                "application/pdf",
+                ContentRange {
+                    start: 7000,
+                    end: 7999,
+                    total: None,
+                },
+                b"...the second range",
+            )
+            .unwrap(),
+    );
+    body.extend(encoder.finish());
+    let expected = concat!(
+        "--THIS_STRING_SEPARATES\r\n",
+        "Content-Type: application/pdf\r\n",
+        "Content-Range: bytes 500-999/8000\r\n",
+        "\r\n",
+        "...the first range...\r\n",
+        "--THIS_STRING_SEPARATES\r\n",
+        "Content-Type: application/pdf\r\n",
+        "Content-Range: bytes 7000-7999/*\r\n",
+        "\r\n",
+        "...the second range\r\n",
+        "--THIS_STRING_SEPARATES--\r\n",
+    );
+    assert_eq!(String::from_utf8(body).unwrap(), expected);
+    let mut decoder = MultipartDecoder::new("THIS_STRING_SEPARATES").unwrap();
+    decoder.push(expected.as_bytes());
+    let part = decoder.read_part().unwrap().unwrap();
+    assert_eq!(part.content_range().map(|r| r.start), Some(500));
+    assert_eq!(part.body, b"...the first range...");
+
+    let encoder = MultipartEncoder::new("a b").unwrap();
+    assert_eq!(
+        encoder.content_type("mixed"),
+        "multipart/mixed; boundary=\"a b\""
+    );
+    for boundary in ["", "trailing ", "bad\"quote", &"x".repeat(71)] {
+        assert!(matches!(
+            MultipartEncoder::new(boundary),
+            Err(HttpFrameError::InvalidBoundary)
+        ));
+        assert!(MultipartDecoder::new(boundary).is_err());
+    }
+    let mut encoder = MultipartEncoder::new("b").unwrap();
+    let err = encoder.encode_part(&[("X", "a\r\nb")], b"").unwrap_err();
+    assert_eq!(err, HttpFrameError::InvalidHeaderValue("X".into()));
+    assert!(encoder.encode_part(&[("Bad Name", "v")], b"").is_err());
+}
+
+#[test]
+fn multipart_decode_matrix() {
+    // RFC 2046 section 5.1.1 example, with preamble and epilogue.
+    let body = concat!(
+        "This is the preamble.  It is to be ignored, though it\r\n",
+        "is a handy place for composition agents to include an\r\n",
+        "explanatory note to non-MIME conformant readers.\r\n",
+        "\r\n",
+        "--simple boundary\r\n",
+        "\r\n",
+        "This is implicitly typed plain US-ASCII text.\r\n",
+        "It does NOT end with a linebreak.\r\n",
+        "--simple boundary\r\n",
+        "Content-type: text/plain; charset=us-ascii\r\n",
+        "\r\n",
+        "This is explicitly typed plain US-ASCII text.\r\n",
+        "It DOES end with a linebreak.\r\n",
+        "\r\n",
+        "--simple boundary--\r\n",
+        "\r\n",
+        "This is the epilogue.  It is also to be ignored.\r\n",
+    )
+    .as_bytes();
+    let expected = [
+        MultipartPart {
+            headers: vec![],
+            body: b"This is implicitly typed plain US-ASCII text.\r\nIt does NOT end with a linebreak.".to_vec(),
+        },
+        MultipartPart {
+            headers: vec![(
+                "Content-type".into(),
+                "text/plain; charset=us-ascii".into(),
+            )],
+            body: b"This is explicitly typed plain US-ASCII text.\r\nIt DOES end with a linebreak.\r\n"
+                .to_vec(),
+        },
+    ];
+    let decode = |chunks: &[&[u8]]| {
+        let mut decoder = MultipartDecoder::new("simple boundary").unwrap();
+        let mut parts = Vec::new();
+        for chunk in chunks {
+            decoder.push(chunk);
+            while let Some(part) = decoder.read_part().unwrap() {
+                parts.push(part);
+            }
+        }
+        assert!(decoder.is_done());
+        parts
+    };
+    assert_eq!(decode(&[body]), expected);
+    for split in (0..body.len()).step_by(7) {
+        let (a, b) = body.split_at(split);
+        assert_eq!(decode(&[a, b]), expected, "split at {split}");
+    }
+    let single: Vec<&[u8]> = body.chunks(1).collect();
+    assert_eq!(decode(&single), expected);
+    assert_eq!(
+        expected[1].header("CONTENT-TYPE"),
+        Some("text/plain; charset=us-ascii")
+    );
+}
+
+#[test]
+fn multipart_roundtrip_matrix() {
+    let parts: [(Headers, &[u8]); 3] = [
+        (
+            &[("Content-Type", "application/x-ndjson")],
+            b"{\"a\":1}\n{\"a\":2}\n",
+        ),
+        (&[], b""),
+        (&[("X-Tricky", "v")], b"--bound\r\n--boundar\r\n-"),
+    ];
+    let mut encoder = MultipartEncoder::new("boundary").unwrap();
+    let mut body = Vec::new();
+    for (headers, data) in parts {
+        body.extend(encoder.encode_part(headers, data).unwrap());
+    }
+    body.extend(encoder.finish());
+
+    let mut decoder = MultipartDecoder::new("boundary").unwrap();
+    decoder.push(&body);
+    for (headers, data) in parts {
+        let part = decoder.read_part().unwrap().unwrap();
+        let expected: Vec<(String, String)> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(part.headers, expected);
+        assert_eq!(part.body, data);
+    }
+    assert_eq!(decoder.read_part().unwrap(), None);
+    assert!(decoder.is_done());
+}
+
+#[test]
+fn multipart_error_matrix() {
+    let mut decoder = MultipartDecoder::new("b").unwrap();
+    decoder.push(b"--bx\r\n\r\nbody\r\n--b--");
+    let err = decoder.read_part().unwrap_err();
+    assert_eq!(err, HttpFrameError::InvalidBoundaryLine(3));
+    assert_eq!(err.offset(), Some(3));
+
+    let mut decoder = MultipartDecoder::new("b").unwrap();
+    decoder.push(b"--b \t\r\nNoColon\r\n\r\nbody\r\n--b--");
+    let err = decoder.read_part().unwrap_err();
+    assert_eq!(err, HttpFrameError::InvalidPartHeader(7));
+
+    let err: JsonPackError = err.into();
+    assert_eq!(err.format(), "http_frames");
+    assert_eq!(err.offset(), Some(7));
+}
+
+#[test]
+fn content_range_matrix() {
+    let cases = [
+        (
+            "bytes 0-499/1234",
+            Some(ContentRange {
+                start: 0,
+                end: 499,
+                total: Some(1234),
+            }),
+        ),
+        (
+            "bytes 42-42/*",
+            Some(ContentRange {
+                start: 42,
+                end: 42,
+                total: None,
+            }),
+        ),
+        ("bytes */1234", None),
+        ("bytes 5-4/10", None),
+        ("bytes 0-10/10", None),
+        ("items 0-1/2", None),
+        ("bytes +1-2/3", None),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(ContentRange::parse(input), expected, "{input}");
+        if let Some(range) = expected {
+            assert_eq!(range.to_string(), input);
+        }
+    }
+}
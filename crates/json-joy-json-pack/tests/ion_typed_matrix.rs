@@ -0,0 +1,263 @@
+//! Typed Ion values: decimals, timestamps, symbols and annotations.
+//!
+//! Not an upstream port: upstream decodes Ion into plain JSON values only.
+
+use json_joy_json_pack::ion::{
+    IonDecimal, IonDecodeError, IonDecoder, IonEncoder, IonTimestamp, IonValue,
+};
+use json_joy_json_pack::PackValue;
+
+const IVM: [u8; 4] = [0xe0, 0x01, 0x00, 0xea];
+
+fn ion(body: &[u8]) -> Vec<u8> {
+    [&IVM[..], body].concat()
+}
+
+fn annotated(annotations: &[&str], value: IonValue) -> IonValue {
+    IonValue::Annotated {
+        annotations: annotations.iter().map(|a| a.to_string()).collect(),
+        value: Box::new(value),
+    }
+}
+
+#[test]
+fn ion_typed_scalar_wire_matrix() {
+    let cases = [
+        (IonValue::Decimal(IonDecimal::new(0, 0)), vec![0x50]),
+        // 12.50 = 1250d-2
+        (
+            IonValue::Decimal(IonDecimal::new(1250, -2)),
+            vec![0x53, 0xc2, 0x04, 0xe2],
+        ),
+        // -1d100: exponent needs two VarInt bytes, coefficient has its sign bit set.
+        (
+            IonValue::Decimal(IonDecimal::new(-1, 100)),
+            vec![0x53, 0x00, 0xe4, 0x81],
+        ),
+        // 128d0: the magnitude's high bit forces a leading zero byte.
+        (
+            IonValue::Decimal(IonDecimal::new(128, 0)),
+            vec![0x53, 0x80, 0x00, 0x80],
+        ),
+        (
+            IonValue::Timestamp(IonTimestamp::date(2024, 5, 1).at(12, 30, 5, Some(0))),
+            vec![0x68, 0x80, 0x0f, 0xe8, 0x85, 0x81, 0x8c, 0x9e, 0x85],
+        ),
+        (
+            IonValue::Timestamp(IonTimestamp {
+                year: 2024,
+                ..IonTimestamp::default()
+            }),
+            vec![0x63, 0xc0, 0x0f, 0xe8],
+        ),
+        (
+            IonValue::Timestamp(IonTimestamp {
+                fraction: Some(IonDecimal::new(79, -3)),
+                ..IonTimestamp::date(2007, 2, 23).at(12, 14, 33, Some(-480))
+            }),
+            vec![
+                0x6b, 0x43, 0xe0, 0x0f, 0xd7, 0x82, 0x97, 0x8c, 0x8e, 0xa1, 0xc3, 0x4f,
+            ],
+        ),
+        (IonValue::Int(-(1i128 << 70)), {
+            let mut bytes = vec![0x39, 0x40];
+            bytes.extend([0; 8]);
+            bytes
+        }),
+        // The system symbol `name`, so no local symbol table is needed.
+        (IonValue::Symbol("name".into()), vec![0x71, 0x04]),
+    ];
+    let mut encoder = IonEncoder::new();
+    let mut decoder = IonDecoder::new();
+    for (value, body) in cases {
+        let bytes = encoder.encode_ion(&value);
+        assert_eq!(bytes, ion(&body), "{value:?}");
+        assert_eq!(decoder.decode_ion(&bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn ion_typed_roundtrip_matrix() {
+    let order = IonValue::Struct(vec![
+        ("id".into(), IonValue::Symbol("ord_1".into())),
+        (
+            "total".into(),
+            annotated(&["USD"], IonValue::Decimal(IonDecimal::new(1999, -2))),
+        ),
+        (
+            "placed".into(),
+            IonValue::Timestamp(IonTimestamp {
+                fraction: Some(IonDecimal::new(250, -3)),
+                ..IonTimestamp::date(2024, 12, 31).at(23, 59, 59, Some(330))
+            }),
+        ),
+        (
+            "items".into(),
+            IonValue::List(vec![
+                annotated(&["sku", "v2"], IonValue::Str("a".into())),
+                IonValue::Int(i128::MAX),
+                IonValue::Float(-0.5),
+                IonValue::Blob(vec![1, 2, 3]),
+                IonValue::Null,
+                IonValue::Bool(true),
+            ]),
+        ),
+        (
+            "ship".into(),
+            IonValue::Timestamp(IonTimestamp::date(2025, 1, 2)),
+        ),
+    ]);
+    let bytes = IonEncoder::new().encode_ion(&order);
+    let mut decoder = IonDecoder::new();
+    assert_eq!(decoder.decode_ion(&bytes).unwrap(), order);
+
+    // The plain decoder drops annotations and rejects the typed scalars.
+    assert!(matches!(
+        decoder.decode(&bytes),
+        Err(IonDecodeError::UnknownType(7, _))
+    ));
+    let annotated_str = IonEncoder::new().encode_ion(&annotated(&["x"], IonValue::Str("s".into())));
+    assert_eq!(
+        decoder.decode(&annotated_str).unwrap(),
+        PackValue::Str("s".into())
+    );
+
+    // Plain values encode identically through either entry point.
+    let plain = PackValue::Object(vec![
+        ("a".into(), PackValue::Array(vec![PackValue::Integer(-3)])),
+        ("b".into(), PackValue::Str("x".into())),
+    ]);
+    assert_eq!(
+        IonEncoder::new().encode_ion(&plain.clone().into()),
+        IonEncoder::new().encode(&plain)
+    );
+    assert_eq!(
+        PackValue::from(
+            decoder
+                .decode_ion(&IonEncoder::new().encode(&plain))
+                .unwrap()
+        ),
+        plain
+    );
+}
+
+#[test]
+fn ion_typed_to_pack_value_matrix() {
+    let value = IonValue::List(vec![
+        IonValue::Decimal(IonDecimal::new(1250, -2)),
+        IonValue::Timestamp(IonTimestamp::date(2024, 5, 1).at(12, 30, 5, Some(0))),
+        IonValue::Symbol("sym".into()),
+        annotated(&["a"], IonValue::Int(-1)),
+        IonValue::Int(1 << 64),
+    ]);
+    assert_eq!(
+        PackValue::from(value),
+        PackValue::Array(vec![
+            PackValue::Float(12.5),
+            PackValue::Str("2024-05-01T12:30:05Z".into()),
+            PackValue::Str("sym".into()),
+            PackValue::Integer(-1),
+            PackValue::BigInt(1 << 64),
+        ])
+    );
+}
+
+#[test]
+fn ion_timestamp_display_matrix() {
+    let cases = [
+        (
+            IonTimestamp {
+                year: 7,
+                ..IonTimestamp::default()
+            },
+            "0007T",
+        ),
+        (
+            IonTimestamp {
+                year: 2007,
+                month: Some(2),
+                ..IonTimestamp::default()
+            },
+            "2007-02T",
+        ),
+        (IonTimestamp::date(2007, 2, 23), "2007-02-23"),
+        (
+            IonTimestamp {
+                second: None,
+                ..IonTimestamp::date(2007, 2, 23).at(12, 14, 0, None)
+            },
+            "2007-02-23T12:14-00:00",
+        ),
+        (
+            IonTimestamp {
+                fraction: Some(IonDecimal::new(79, -3)),
+                ..IonTimestamp::date(2007, 2, 23).at(12, 14, 33, Some(-480))
+            },
+            "2007-02-23T12:14:33.079-08:00",
+        ),
+        (
+            IonTimestamp::date(2007, 2, 23).at(1, 2, 3, Some(345)),
+            "2007-02-23T01:02:03+05:45",
+        ),
+    ];
+    for (timestamp, text) in cases {
+        assert_eq!(timestamp.to_string(), text);
+    }
+    assert_eq!(IonDecimal::new(-1250, -2).to_string(), "-1250d-2");
+    assert_eq!(IonDecimal::new(-1250, -2).to_f64(), -12.5);
+}
+
+#[test]
+fn ion_typed_decode_error_matrix() {
+    let mut decoder = IonDecoder::new();
+    let cases = [
+        // Symbol ID with no text.
+        (vec![0x71, 0x0a], IonDecodeError::UnknownSymbol(10)),
+        // Annotation with an unknown symbol.
+        (
+            vec![0xe3, 0x81, 0x8a, 0x20],
+            IonDecodeError::UnknownSymbol(10),
+        ),
+        // Hour without minute.
+        (
+            vec![0x66, 0x80, 0x0f, 0xe8, 0x85, 0x81, 0x8c],
+            IonDecodeError::InvalidTimestamp,
+        ),
+        // Month does not fit in a byte.
+        (
+            vec![0x65, 0x80, 0x0f, 0xe8, 0x02, 0x80],
+            IonDecodeError::InvalidTimestamp,
+        ),
+        // Decimal exponent running past the declared length.
+        (vec![0x51, 0x01, 0x81], IonDecodeError::InvalidDecimal),
+        // Coefficient wider than 128 bits.
+        (
+            [&[0x5e, 0x92, 0x80][..], &[0x01; 17]].concat(),
+            IonDecodeError::Overflow,
+        ),
+        // Exponent wider than 32 bits.
+        (vec![0x56, 0x01, 0, 0, 0, 0, 0x80], IonDecodeError::Overflow),
+        // Integers longer than 16 bytes, or past the `i128` range.
+        (
+            [&[0x2e, 0x91][..], &[0x01; 17]].concat(),
+            IonDecodeError::Overflow,
+        ),
+        (
+            [&[0x3e, 0x91][..], &[0x01; 17]].concat(),
+            IonDecodeError::Overflow,
+        ),
+        (
+            [&[0x2e, 0x90][..], &[0x80], &[0; 15]].concat(),
+            IonDecodeError::Overflow,
+        ),
+        (
+            [&[0x3e, 0x90][..], &[0x80], &[0; 14], &[1]].concat(),
+            IonDecodeError::Overflow,
+        ),
+    ];
+    for (body, error) in cases {
+        assert_eq!(decoder.decode_ion(&ion(&body)), Err(error), "{body:02x?}");
+    }
+    let min = [&[0x3e, 0x90][..], &[0x80], &[0; 15]].concat();
+    assert_eq!(decoder.decode_ion(&ion(&min)), Ok(IonValue::Int(i128::MIN)));
+}
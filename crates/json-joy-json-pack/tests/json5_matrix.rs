@@ -0,0 +1,146 @@
+//! `JsonDecoder` in JSON5 mode.
+//!
+//! Not an upstream port: upstream decodes strict JSON only. Cases follow
+//! the examples of <https://spec.json5.org/>.
+
+use json_joy_json_pack::json::{JsonDecoder, JsonDecoderOptions};
+use json_joy_json_pack::token::PackValueBuilder;
+use json_joy_json_pack::{pack, JsonPackBigInt, PackValue};
+
+const SPEC_EXAMPLE: &str = r#"// This file is written in JSON5 syntax.
+{
+  // comments
+  unquoted: 'and you can quote me on that',
+  singleQuotes: 'I can use "double quotes" here',
+  lineBreaks: "Look, Mom! \
+No \\n's!",
+  hexadecimal: 0xdecaf,
+  leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+  positiveSign: +1,
+  trailingComma: 'in objects', andIn: ['arrays',],
+  "backwardsCompatible": "with JSON",
+  $id_1: -Infinity,
+}
+"#;
+
+fn spec_example() -> PackValue {
+    pack!({
+        "unquoted": "and you can quote me on that",
+        "singleQuotes": "I can use \"double quotes\" here",
+        "lineBreaks": "Look, Mom! No \\n's!",
+        "hexadecimal": 0xdecaf,
+        "leadingDecimalPoint": 0.8675309,
+        "andTrailing": 8675309.0,
+        "positiveSign": 1,
+        "trailingComma": "in objects",
+        "andIn": ["arrays"],
+        "backwardsCompatible": "with JSON",
+        "$id_1": (f64::NEG_INFINITY)
+    })
+}
+
+#[test]
+fn json5_spec_example() {
+    let mut decoder = JsonDecoder::with_options(JsonDecoderOptions::json5());
+    assert_eq!(
+        decoder.decode(SPEC_EXAMPLE.as_bytes()).unwrap(),
+        spec_example()
+    );
+
+    let mut builder = PackValueBuilder::new();
+    decoder
+        .parse_with(SPEC_EXAMPLE.as_bytes(), &mut builder)
+        .unwrap();
+    assert_eq!(builder.finish(), Some(spec_example()));
+}
+
+#[test]
+fn json5_values_matrix() {
+    let mut decoder = JsonDecoder::with_options(JsonDecoderOptions::json5());
+    let cases: &[(&str, PackValue)] = &[
+        ("Infinity", PackValue::Float(f64::INFINITY)),
+        ("+Infinity", PackValue::Float(f64::INFINITY)),
+        ("0x1F", PackValue::Integer(31)),
+        ("-0xff", PackValue::Integer(-255)),
+        ("0xFFFFFFFFFFFFFFFF", PackValue::UInteger(u64::MAX)),
+        (
+            "0x7fffffffffffffffffffffffffffffff",
+            PackValue::BigInt(i128::MAX),
+        ),
+        (
+            "-0x1000000000000000000000000000000000",
+            PackValue::BigNum(JsonPackBigInt::new(
+                true,
+                &[0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            )),
+        ),
+        ("+.5e1", PackValue::Float(5.0)),
+        ("-5.", PackValue::Float(-5.0)),
+        ("12", PackValue::Integer(12)),
+        (
+            "'\\x41\\u00e9\\v\\0\\'\\q'",
+            PackValue::Str("A\u{e9}\u{b}\0'q".into()),
+        ),
+        ("'\\ud83d\\ude00'", PackValue::Str("\u{1f600}".into())),
+        ("'a\\\r\nb\\\u{2028}c'", PackValue::Str("abc".into())),
+        (
+            "\"caf\u{e9} \u{2028}\"",
+            PackValue::Str("caf\u{e9} \u{2028}".into()),
+        ),
+        (
+            "{'a': 1, b: 2, \u{e9}t\u{e9}: 3}",
+            pack!({"a": 1, "b": 2, "\u{e9}t\u{e9}": 3}),
+        ),
+        ("\u{feff}\u{a0}[1,\u{b}\u{c}\u{2029}2]", pack!([1, 2])),
+        (
+            "{null: null, true: true}",
+            pack!({"null": null, "true": true}),
+        ),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            decoder.decode(input.as_bytes()).unwrap(),
+            *expected,
+            "{input}"
+        );
+    }
+    match decoder.decode(b"[NaN, -NaN]").unwrap() {
+        PackValue::Array(items) => assert!(items
+            .iter()
+            .all(|v| matches!(v, PackValue::Float(f) if f.is_nan()))),
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn json5_rejects_matrix() {
+    let mut decoder = JsonDecoder::with_options(JsonDecoderOptions::json5());
+    let cases: &[&str] = &[
+        "0x",
+        ".",
+        "+",
+        "1e",
+        "'unterminated",
+        "'line\nbreak'",
+        "'\\1'",
+        "'\\01'",
+        "'\\x4'",
+        "'\\ud83d'",
+        "'\\udc00'",
+        "{1a: 1}",
+        "{a-b: 1}",
+        "Inf",
+    ];
+    for input in cases {
+        assert!(decoder.decode(input.as_bytes()).is_err(), "{input}");
+    }
+
+    // Without the option, JSON5 syntax stays an error.
+    let mut strict = JsonDecoder::new();
+    for input in ["{a: 1}", "'a'", "+1", "Infinity", ".5"] {
+        assert!(strict.decode(input.as_bytes()).is_err(), "{input}");
+    }
+    // JSONC alone does not enable JSON5 values.
+    let mut jsonc = JsonDecoder::with_options(JsonDecoderOptions::jsonc());
+    assert!(jsonc.decode(b"{a: 1}").is_err());
+}
@@ -0,0 +1,162 @@
+//! `JsonDecoderPartial` repair options and cut reports on incomplete and
+//! corrupt documents.
+//!
+//! Not an upstream port: upstream always drops unfinished values and does
+//! not say where or why it stopped.
+
+use json_joy_json_pack::json::{
+    JsonDecoderPartial, JsonDecoderPartialOptions, PartialCut, PartialCutReason,
+};
+use json_joy_json_pack::PackValue;
+
+fn str(s: &str) -> PackValue {
+    PackValue::Str(s.into())
+}
+
+fn obj(fields: &[(&str, PackValue)]) -> PackValue {
+    PackValue::Object(
+        fields
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), v.clone()))
+            .collect(),
+    )
+}
+
+fn incomplete(offset: usize) -> Option<PartialCut> {
+    Some(PartialCut {
+        offset,
+        reason: PartialCutReason::Incomplete,
+    })
+}
+
+fn corrupt(offset: usize) -> Option<PartialCut> {
+    Some(PartialCut {
+        offset,
+        reason: PartialCutReason::Corrupt,
+    })
+}
+
+#[test]
+fn json_partial_cut_matrix() {
+    let mut decoder = JsonDecoderPartial::new();
+    let cases: &[(&[u8], Option<PartialCut>)] = &[
+        (b"[1, 2]", None),
+        (b"{\"a\": [true, null]} ", None),
+        (b"[1, 2", incomplete(5)),
+        (b"[1, 2, ", incomplete(7)),
+        (b"[1, \"ab", incomplete(4)),
+        (b"[1, tr", incomplete(4)),
+        (b"[1, -", incomplete(4)),
+        (b"[1, 2.5e", incomplete(4)),
+        (b"{\"a\": 1, \"b", incomplete(9)),
+        (b"{\"a\"", incomplete(4)),
+        (b"{\"a\": ", incomplete(6)),
+        (b"{\"a\": {\"b\": [", incomplete(13)),
+        (b"[1, 2 3]", corrupt(6)),
+        (b"[1, x]", corrupt(4)),
+        (b"[1, trux]", corrupt(4)),
+        (b"[true, \"asdf\",,", corrupt(14)),
+        (b"[\"a\\q\", 1]", corrupt(1)),
+        (b"{\"a\" 1}", corrupt(5)),
+        (b"{1: 2}", corrupt(1)),
+        (b"[{\"__proto__\": 1}]", corrupt(1)),
+        // The first problem wins: corruption inside a document cut short.
+        (b"[[1 x], [2", corrupt(4)),
+    ];
+    for (input, cut) in cases {
+        let text = String::from_utf8_lossy(input);
+        decoder.decode(input).unwrap();
+        assert_eq!(decoder.cut(), *cut, "{text}");
+    }
+
+    // Failing decodes still report why.
+    assert!(decoder.decode(b"  ").is_err());
+    assert_eq!(decoder.cut(), incomplete(2));
+    assert!(decoder.decode(b"\"ab").is_err());
+    assert_eq!(decoder.cut(), incomplete(0));
+    assert!(decoder.decode(b"?").is_err());
+    assert_eq!(decoder.cut(), corrupt(0));
+}
+
+#[test]
+fn json_partial_keep_strings_matrix() {
+    let mut decoder = JsonDecoderPartial::with_options(JsonDecoderPartialOptions {
+        keep_partial_strings: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        decoder
+            .decode(b"{\"name\": {\"first\": \"ind\", \"last\": \"go")
+            .unwrap(),
+        obj(&[("name", obj(&[("first", str("ind")), ("last", str("go"))]))])
+    );
+    assert_eq!(decoder.cut(), incomplete(34));
+
+    // Dangling escapes and UTF-8 sequences are left out.
+    let cases: &[(&[u8], &str)] = &[
+        (b"\"", ""),
+        (b"\"line\\", "line"),
+        (b"\"tab\\t", "tab\t"),
+        (b"\"e\\u00", "e"),
+        (b"\"e\\u00e9", "e\u{e9}"),
+        (b"\"smile \\ud83d", "smile "),
+        (b"\"smile \\ud83d\\ude", "smile "),
+        (b"\"caf\xc3", "caf"),
+        (b"\"caf\xc3\xa9", "caf\u{e9}"),
+    ];
+    for (input, expected) in cases {
+        let text = String::from_utf8_lossy(input);
+        assert_eq!(decoder.decode(input).unwrap(), str(expected), "{text}");
+        assert_eq!(decoder.cut(), incomplete(0), "{text}");
+    }
+
+    // Keys and corrupt strings are still dropped.
+    assert_eq!(
+        decoder.decode(b"{\"a\": 1, \"ke").unwrap(),
+        obj(&[("a", PackValue::Integer(1))])
+    );
+    assert_eq!(
+        decoder.decode(b"[\"ok\", \"bad\\x\"]").unwrap(),
+        PackValue::Array(vec![str("ok")])
+    );
+    assert_eq!(decoder.cut(), corrupt(7));
+}
+
+#[test]
+fn json_partial_null_placeholders_matrix() {
+    let mut decoder = JsonDecoderPartial::with_options(JsonDecoderPartialOptions {
+        null_placeholders: true,
+        ..Default::default()
+    });
+    let one = || PackValue::Integer(1);
+    let cases: &[(&[u8], PackValue)] = &[
+        (b"[1, tr", PackValue::Array(vec![one(), PackValue::Null])),
+        (b"[1, x]", PackValue::Array(vec![one(), PackValue::Null])),
+        (b"[1, \"ab", PackValue::Array(vec![one(), PackValue::Null])),
+        // Nothing started after the comma: no element to replace.
+        (b"[1, ", PackValue::Array(vec![one()])),
+        (b"{\"a\"", obj(&[("a", PackValue::Null)])),
+        (b"{\"a\": ", obj(&[("a", PackValue::Null)])),
+        (b"{\"a\": nu", obj(&[("a", PackValue::Null)])),
+        (b"{\"a\" 1}", obj(&[("a", PackValue::Null)])),
+        (b"{\"a\": 1, \"b", obj(&[("a", one())])),
+    ];
+    for (input, expected) in cases {
+        let text = String::from_utf8_lossy(input);
+        assert_eq!(decoder.decode(input).unwrap(), *expected, "{text}");
+    }
+    // The top-level value is never replaced.
+    assert!(decoder.decode(b"tru").is_err());
+
+    // Both options: strings are kept, everything else becomes null.
+    decoder.options.keep_partial_strings = true;
+    assert_eq!(
+        decoder.decode(b"[1, \"ab").unwrap(),
+        PackValue::Array(vec![one(), str("ab")])
+    );
+    assert_eq!(
+        decoder.decode(b"{\"s\": \"x\", \"n\": 12e").unwrap(),
+        obj(&[("s", str("x")), ("n", PackValue::Null)])
+    );
+    assert_eq!(decoder.cut(), incomplete(16));
+}
@@ -0,0 +1,303 @@
+//! `PackValue` ↔ `serde_json::Value` under each `JsonPolicy` setting.
+//!
+//! Not an upstream port: upstream's JSON conversions are fixed.
+
+use json_joy_json_pack::{
+    pack, JsonBigIntPolicy, JsonBytesPolicy, JsonNonFinitePolicy, JsonNonTextKeyPolicy,
+    JsonPackBigInt, JsonPackExtension, JsonPolicy, JsonPolicyError, JsonUndefinedPolicy, PackValue,
+};
+use serde_json::json;
+
+fn policy() -> JsonPolicy {
+    JsonPolicy::default()
+}
+
+fn big_num() -> JsonPackBigInt {
+    "-1606938044258990275541962092341162602522202993782792835301376"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn json_policy_default_matrix() {
+    let value = PackValue::Object(vec![
+        ("b".into(), PackValue::Bytes(vec![1, 2, 3])),
+        ("i".into(), PackValue::BigInt(i128::MAX)),
+        ("u".into(), PackValue::Undefined),
+        ("f".into(), PackValue::Float(f64::NAN)),
+        (
+            "t".into(),
+            PackValue::Extension(Box::new(JsonPackExtension::new(1, PackValue::Integer(5)))),
+        ),
+        ("n".into(), pack!([1, -2.5, "x", null, true])),
+    ]);
+    let expected = json!({
+        "b": "data:application/octet-stream;base64,AQID",
+        "i": i128::MAX.to_string(),
+        "u": null,
+        "f": null,
+        "t": 5,
+        "n": [1, -2.5, "x", null, true],
+    });
+    assert_eq!(value.to_json_with(&policy()).unwrap(), expected);
+    assert_eq!(serde_json::Value::from(value), expected);
+}
+
+#[test]
+fn json_policy_bytes_matrix() {
+    let bytes = PackValue::Bytes(vec![0x0a, 0xff]);
+    for (bytes_policy, expected) in [
+        (
+            JsonBytesPolicy::DataUri,
+            json!("data:application/octet-stream;base64,Cv8="),
+        ),
+        (JsonBytesPolicy::Base64, json!("Cv8=")),
+        (JsonBytesPolicy::Hex, json!("0aff")),
+        (JsonBytesPolicy::Array, json!([10, 255])),
+    ] {
+        let policy = JsonPolicy {
+            bytes: bytes_policy,
+            ..policy()
+        };
+        let out = bytes.to_json_with(&policy).unwrap();
+        assert_eq!(out, expected, "{bytes_policy:?}");
+        // Only the data URI is recognised on the way back.
+        let back = PackValue::from_json_with(&out, &policy);
+        assert_eq!(
+            back == bytes,
+            bytes_policy == JsonBytesPolicy::DataUri,
+            "{bytes_policy:?}"
+        );
+    }
+    // Not valid base64 after the prefix: left as a string.
+    let uri = json!("data:application/octet-stream;base64,!!");
+    assert_eq!(
+        PackValue::from_json_with(&uri, &policy()),
+        PackValue::Str("data:application/octet-stream;base64,!!".into())
+    );
+}
+
+#[test]
+fn json_policy_big_int_matrix() {
+    let big = 1i128 << 70;
+    for (big_int, expected) in [
+        (
+            JsonBigIntPolicy::String,
+            Ok(json!("1180591620717411303424")),
+        ),
+        (JsonBigIntPolicy::Number, Ok(json!(1.1805916207174113e21))),
+        (
+            JsonBigIntPolicy::Error,
+            Err(JsonPolicyError::BigInt("1180591620717411303424".into())),
+        ),
+    ] {
+        let policy = JsonPolicy {
+            big_int,
+            ..policy()
+        };
+        assert_eq!(PackValue::BigInt(big).to_json_with(&policy), expected);
+        // Anything that fits in 64 bits stays a number.
+        for (value, json) in [
+            (PackValue::BigInt(-5), json!(-5)),
+            (PackValue::BigInt(u64::MAX as i128), json!(u64::MAX)),
+            (PackValue::BigNum(JsonPackBigInt::from_i128(7)), json!(7)),
+        ] {
+            assert_eq!(value.to_json_with(&policy), Ok(json), "{value:?}");
+        }
+    }
+
+    let n = PackValue::BigNum(big_num());
+    let string = JsonPolicy {
+        big_int: JsonBigIntPolicy::String,
+        ..policy()
+    };
+    let out = n.to_json_with(&string).unwrap();
+    assert_eq!(out, json!(big_num().to_string()));
+    assert_eq!(PackValue::from_json_with(&out, &string), n);
+    let number = JsonPolicy {
+        big_int: JsonBigIntPolicy::Number,
+        ..policy()
+    };
+    assert_eq!(n.to_json_with(&number), Ok(json!(-1.6069380442589903e60)));
+    let error = JsonPolicy {
+        big_int: JsonBigIntPolicy::Error,
+        ..policy()
+    };
+    assert!(matches!(
+        n.to_json_with(&error),
+        Err(JsonPolicyError::BigInt(_))
+    ));
+
+    // Reading back: only integer strings that need more than 64 bits.
+    for (json, value) in [
+        (json!("1180591620717411303424"), PackValue::BigInt(big)),
+        (
+            json!("-9223372036854775809"),
+            PackValue::BigInt(i64::MIN as i128 - 1),
+        ),
+        (
+            json!("18446744073709551615"),
+            PackValue::Str("18446744073709551615".into()),
+        ),
+        (json!("123"), PackValue::Str("123".into())),
+        (
+            json!("01180591620717411303424"),
+            PackValue::Str("01180591620717411303424".into()),
+        ),
+        (json!("1e30"), PackValue::Str("1e30".into())),
+        (json!(1e30), PackValue::Float(1e30)),
+    ] {
+        assert_eq!(PackValue::from_json_with(&json, &string), value, "{json}");
+    }
+    assert_eq!(
+        PackValue::from_json_with(&json!("1180591620717411303424"), &number),
+        PackValue::Str("1180591620717411303424".into())
+    );
+}
+
+#[test]
+fn json_policy_undefined_matrix() {
+    let tagged_undefined =
+        PackValue::Extension(Box::new(JsonPackExtension::new(9, PackValue::Undefined)));
+    let value = PackValue::Object(vec![
+        ("a".into(), PackValue::Undefined),
+        (
+            "b".into(),
+            PackValue::Array(vec![PackValue::Undefined, 1.into()]),
+        ),
+        ("c".into(), tagged_undefined),
+        ("d".into(), PackValue::Null),
+    ]);
+    for (undefined, expected) in [
+        (
+            JsonUndefinedPolicy::Null,
+            Ok(json!({"a": null, "b": [null, 1], "c": null, "d": null})),
+        ),
+        (
+            JsonUndefinedPolicy::Skip,
+            Ok(json!({"b": [null, 1], "d": null})),
+        ),
+        (JsonUndefinedPolicy::Error, Err(JsonPolicyError::Undefined)),
+    ] {
+        let policy = JsonPolicy {
+            undefined,
+            ..policy()
+        };
+        assert_eq!(value.to_json_with(&policy), expected, "{undefined:?}");
+    }
+    let skip = JsonPolicy {
+        undefined: JsonUndefinedPolicy::Skip,
+        ..policy()
+    };
+    assert_eq!(PackValue::Undefined.to_json_with(&skip), Ok(json!(null)));
+}
+
+#[test]
+fn json_policy_non_finite_matrix() {
+    for (f, name) in [
+        (f64::NAN, "NaN"),
+        (f64::INFINITY, "Infinity"),
+        (f64::NEG_INFINITY, "-Infinity"),
+    ] {
+        for (non_finite, expected) in [
+            (JsonNonFinitePolicy::Null, Ok(json!(null))),
+            (JsonNonFinitePolicy::String, Ok(json!(name))),
+            (
+                JsonNonFinitePolicy::Error,
+                Err(JsonPolicyError::NonFiniteFloat),
+            ),
+        ] {
+            let policy = JsonPolicy {
+                non_finite,
+                ..policy()
+            };
+            assert_eq!(PackValue::Float(f).to_json_with(&policy), expected);
+        }
+        let string = JsonPolicy {
+            non_finite: JsonNonFinitePolicy::String,
+            ..policy()
+        };
+        let PackValue::Float(back) = PackValue::from_json_with(&json!(name), &string) else {
+            panic!("{name}");
+        };
+        assert_eq!(back.to_bits(), f.to_bits());
+        assert_eq!(
+            PackValue::from_json_with(&json!(name), &policy()),
+            PackValue::Str(name.into())
+        );
+    }
+    assert_eq!(
+        PackValue::Float(-0.5).to_json_with(&JsonPolicy {
+            non_finite: JsonNonFinitePolicy::Error,
+            ..policy()
+        }),
+        Ok(json!(-0.5))
+    );
+}
+
+#[test]
+fn json_policy_non_text_key_matrix() {
+    let map = |key: PackValue| PackValue::Map(vec![(key, PackValue::Integer(1))]);
+    let error = JsonPolicy {
+        non_text_key: JsonNonTextKeyPolicy::Error,
+        ..policy()
+    };
+    let hex = JsonPolicy {
+        bytes: JsonBytesPolicy::Hex,
+        ..error
+    };
+
+    // Scalar keys have a text form under every policy.
+    for (key, text) in [
+        (PackValue::Integer(-3), "-3"),
+        (
+            PackValue::BigInt(i128::MAX),
+            "170141183460469231731687303715884105727",
+        ),
+        (PackValue::Bool(true), "true"),
+        (PackValue::Undefined, "undefined"),
+    ] {
+        assert_eq!(map(key).to_json_with(&error).unwrap(), json!({ text: 1 }));
+    }
+
+    // Byte keys follow the bytes policy while it gives a string.
+    let bytes = || map(PackValue::Bytes(vec![0x0a, 0xff]));
+    assert_eq!(bytes().to_json_with(&hex).unwrap(), json!({"0aff": 1}));
+    let array = JsonPolicy {
+        bytes: JsonBytesPolicy::Array,
+        ..error
+    };
+    assert_eq!(
+        bytes().to_json_with(&array),
+        Err(JsonPolicyError::NonTextKey)
+    );
+
+    let nested = || map(pack!([1]));
+    assert_eq!(nested().to_json_with(&policy()).unwrap(), json!({"": 1}));
+    assert_eq!(
+        nested().to_json_with(&error),
+        Err(JsonPolicyError::NonTextKey)
+    );
+}
+
+#[test]
+fn json_policy_round_trip_matrix() {
+    let lossless = JsonPolicy {
+        non_finite: JsonNonFinitePolicy::String,
+        ..policy()
+    };
+    let value = PackValue::Object(vec![
+        ("bytes".into(), PackValue::Bytes(vec![0, 1, 254, 255])),
+        ("big".into(), PackValue::BigInt(-(1i128 << 100))),
+        ("huge".into(), PackValue::BigNum(big_num())),
+        ("inf".into(), PackValue::Float(f64::INFINITY)),
+        ("max".into(), PackValue::UInteger(u64::MAX)),
+        ("list".into(), pack!([{ "s": "NaNa" }, 0.25, false])),
+    ]);
+    let json = value.to_json_with(&lossless).unwrap();
+    assert_eq!(PackValue::from_json_with(&json, &lossless), value);
+    assert_eq!(
+        PackValue::from_json_with(&json, &lossless).to_json_with(&lossless),
+        Ok(json)
+    );
+}
@@ -0,0 +1,104 @@
+//! `JsonToMsgPackConverter`: JSON text to MessagePack in one pass.
+//!
+//! Not an upstream port: upstream decodes to a value and encodes it.
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::json::{JsonDecoder, JsonDecoderOptions, JsonError};
+use json_joy_json_pack::msgpack::{
+    JsonToMsgPackConverter, MsgPackDecoderFast, MsgPackEncoderFast, MsgPackToJsonConverter,
+};
+
+fn cases() -> Vec<String> {
+    let mut cases: Vec<String> = [
+        "null",
+        "true",
+        "-1",
+        "70000",
+        "18446744073709551615",
+        "-9223372036854775808",
+        "3.5",
+        "\"caf\\u00e9 \\ud83d\\ude00\"",
+        "\"data:application/octet-stream;base64,AAEC/w==\"",
+        "[]",
+        "{}",
+        "[1,[2,[3,{}]],{\"a\":[]}]",
+        "{\"level\":\"info\",\"tags\":[\"a\",\"b\"],\"nested\":{\"x\":null}}",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    // Header sizes change at 16 and 65536 entries.
+    for n in [15, 16, 17, 0xffff, 0x10000] {
+        let items: Vec<String> = (0..n).map(|i| (i % 200).to_string()).collect();
+        cases.push(format!("[{}]", items.join(",")));
+        let fields: Vec<String> = (0..n).map(|i| format!("\"k{i}\":[{i}]")).collect();
+        cases.push(format!("{{{}}}", fields.join(",")));
+    }
+    // Deep nesting with a wide container at every level.
+    let wide: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+    let wide = wide.join(",");
+    cases.push(format!(
+        "{}{}{}",
+        format!("[{wide},").repeat(100),
+        wide,
+        "]".repeat(100)
+    ));
+    cases
+}
+
+#[test]
+fn json_to_msgpack_matches_encoder() {
+    let mut converter = JsonToMsgPackConverter::new();
+    let mut decoder = JsonDecoder::new();
+    let mut encoder = MsgPackEncoderFast::new();
+    for json in cases() {
+        let expected = encoder.encode(&decoder.decode(json.as_bytes()).unwrap());
+        let msgpack = converter.convert(json.as_bytes()).unwrap();
+        assert!(msgpack == expected, "{}", &json[..json.len().min(40)]);
+        // Large integers become floats, so compare after re-encoding.
+        let decoded = MsgPackDecoderFast::new().decode(&msgpack).unwrap();
+        assert!(encoder.encode(&decoded) == msgpack);
+    }
+
+    // Round trip through the msgpack to JSON direction.
+    let json = "{\"a\":[1,-2,\"x\"],\"b\":{\"c\":true}}";
+    let msgpack = converter.convert(json.as_bytes()).unwrap();
+    assert_eq!(
+        MsgPackToJsonConverter::new().convert(&msgpack).unwrap(),
+        json
+    );
+}
+
+#[test]
+fn json_to_msgpack_convert_into() {
+    let mut converter = JsonToMsgPackConverter::new();
+    let json = b"  [1, {\"a\": [2, 3]}] trailing";
+    let expected = converter.convert(b"[1,{\"a\":[2,3]}]").unwrap();
+
+    let mut out = Writer::with_alloc_size(8);
+    out.buf(b"pre");
+    assert_eq!(converter.convert_into(json, &mut out).unwrap(), 20);
+    assert_eq!(out.flush(), [b"pre".as_slice(), &expected].concat());
+
+    // Invalid input appends nothing.
+    for bad in ["[1,", "{\"a\":", "[1 2]", "{\"__proto__\":1}", ""] {
+        let mut out = Writer::with_alloc_size(8);
+        out.buf(b"kept");
+        assert!(
+            converter.convert_into(bad.as_bytes(), &mut out).is_err(),
+            "{bad}"
+        );
+        assert_eq!(out.flush(), b"kept", "{bad}");
+    }
+    assert!(matches!(
+        converter.convert(b"{\"__proto__\":1}"),
+        Err(JsonError::InvalidKey)
+    ));
+
+    // Decoder options carry over.
+    let mut jsonc = JsonToMsgPackConverter::with_options(JsonDecoderOptions::jsonc());
+    assert_eq!(
+        jsonc.convert(b"// c\n[1, 2, /* x */]").unwrap(),
+        converter.convert(b"[1,2]").unwrap()
+    );
+}
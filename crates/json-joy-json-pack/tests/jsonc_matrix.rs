@@ -0,0 +1,106 @@
+//! `JsonDecoder` with comments and trailing commas (JSONC).
+//!
+//! Not an upstream port: upstream decodes strict JSON only.
+
+use json_joy_json_pack::json::{JsonDecoder, JsonDecoderOptions, JsonError};
+use json_joy_json_pack::token::PackValueBuilder;
+use json_joy_json_pack::{pack, PackValue};
+
+const TSCONFIG: &str = r#"// tsconfig.json
+{
+    /* Compiler options.
+       Block comments span lines. */
+    "compilerOptions": {
+        "target": "es2022", // trailing line comment
+        "strict": true,
+        "paths": {"@/*": ["src/*",],},
+    },
+    "include": [
+        "src", /* inline */ "test",
+    ],
+    "url": "http://example.com/*not a comment*/",
+} // done"#;
+
+fn tsconfig() -> PackValue {
+    pack!({
+        "compilerOptions": {
+            "target": "es2022",
+            "strict": true,
+            "paths": {"@/*": ["src/*"]}
+        },
+        "include": ["src", "test"],
+        "url": "http://example.com/*not a comment*/"
+    })
+}
+
+#[test]
+fn jsonc_decode_matrix() {
+    let mut decoder = JsonDecoder::with_options(JsonDecoderOptions::jsonc());
+    assert_eq!(decoder.options(), &JsonDecoderOptions::jsonc());
+    assert_eq!(decoder.decode(TSCONFIG.as_bytes()).unwrap(), tsconfig());
+
+    let cases: &[(&str, PackValue)] = &[
+        ("/**/1", PackValue::Integer(1)),
+        ("//\n[]", PackValue::Array(vec![])),
+        ("[1,]", pack!([1])),
+        ("[1 , /* x */ ]", pack!([1])),
+        ("{\"a\":1,}", pack!({"a": 1})),
+        ("{\"a\"/*k*/:/*v*/1}", pack!({"a": 1})),
+        ("[\"// not a comment\"]", pack!(["// not a comment"])),
+        ("[1, // two\n2]", pack!([1, 2])),
+        ("[/***/]", PackValue::Array(vec![])),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            decoder.decode(input.as_bytes()).unwrap(),
+            *expected,
+            "{input}"
+        );
+    }
+
+    // The token path accepts the same input.
+    let mut builder = PackValueBuilder::new();
+    decoder
+        .parse_with(TSCONFIG.as_bytes(), &mut builder)
+        .unwrap();
+    assert_eq!(builder.finish(), Some(tsconfig()));
+}
+
+#[test]
+fn jsonc_rejects_matrix() {
+    let mut jsonc = JsonDecoder::with_options(JsonDecoderOptions::jsonc());
+    let cases: &[(&str, usize)] = &[
+        ("[1,,]", 3),
+        ("[1, /* open", 4),
+        ("/ 1", 0),
+        ("[1 /", 3),
+        ("{\"a\":}", 5),
+        ("{\"a\" // no colon\n}", 17),
+    ];
+    for (input, offset) in cases {
+        let err = jsonc.decode(input.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, JsonError::Invalid(x) if x == *offset),
+            "{input}: {err:?}"
+        );
+    }
+
+    // Strict mode is the default; each extension can be enabled alone.
+    let mut strict = JsonDecoder::new();
+    assert!(strict.decode(b"[1,]").is_err());
+    assert!(strict.decode(b"/**/1").is_err());
+
+    let mut comments = JsonDecoder::with_options(JsonDecoderOptions {
+        comments: true,
+        ..Default::default()
+    });
+    assert_eq!(comments.decode(b"[1 /**/]").unwrap(), pack!([1]));
+    assert!(comments.decode(b"[1,]").is_err());
+
+    let mut commas = JsonDecoder::with_options(JsonDecoderOptions {
+        trailing_commas: true,
+        ..Default::default()
+    });
+    assert_eq!(commas.decode(b"{\"a\": [1,],}").unwrap(), pack!({"a": [1]}));
+    assert!(commas.decode(b"[1 /**/]").is_err());
+}
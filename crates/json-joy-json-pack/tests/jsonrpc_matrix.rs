@@ -0,0 +1,248 @@
+//! JSON-RPC 2.0 envelopes and router.
+//!
+//! Not an upstream port: cases follow the examples in the JSON-RPC 2.0
+//! specification, section 7.
+
+use json_joy_json_pack::codecs::{
+    CborJsonValueCodec, JsonJsonValueCodec, JsonValueCodec, MsgPackJsonValueCodec,
+};
+use json_joy_json_pack::jsonrpc::{
+    JsonRpcCodec, JsonRpcError, JsonRpcId, JsonRpcMessage, JsonRpcNotification, JsonRpcPacket,
+    JsonRpcRequest, JsonRpcResponse, JsonRpcRouter,
+};
+use json_joy_json_pack::{JsonPackError, PackValue};
+
+fn ints(values: &[i64]) -> PackValue {
+    PackValue::Array(values.iter().map(|v| PackValue::Integer(*v)).collect())
+}
+
+fn router() -> JsonRpcRouter {
+    let mut router = JsonRpcRouter::new();
+    router.add("subtract", |params: Option<PackValue>| match params {
+        Some(PackValue::Array(args)) => match args.as_slice() {
+            [PackValue::Integer(a), PackValue::Integer(b)] => Ok(PackValue::Integer(a - b)),
+            _ => Err(JsonRpcError::invalid_params()),
+        },
+        _ => Err(JsonRpcError::invalid_params()),
+    });
+    router.add("sum", |params: Option<PackValue>| match params {
+        Some(PackValue::Array(args)) => Ok(PackValue::Integer(
+            args.iter()
+                .map(|v| match v {
+                    PackValue::Integer(n) => *n,
+                    _ => 0,
+                })
+                .sum(),
+        )),
+        _ => Err(JsonRpcError::invalid_params()),
+    });
+    router.add("notify_hello", |_| Ok(PackValue::Null));
+    router
+}
+
+fn respond(router: &mut JsonRpcRouter, input: &str) -> Option<String> {
+    let mut json = JsonJsonValueCodec::new();
+    router
+        .handle(&mut json, input.as_bytes())
+        .unwrap()
+        .map(|bytes| String::from_utf8(bytes).unwrap())
+}
+
+#[test]
+fn jsonrpc_encode_shape_matrix() {
+    let mut json = JsonJsonValueCodec::new();
+    let codec = JsonRpcCodec::new();
+    let cases = [
+        (
+            JsonRpcMessage::Request(JsonRpcRequest {
+                id: JsonRpcId::Num(1),
+                method: "subtract".into(),
+                params: Some(ints(&[42, 23])),
+            }),
+            r#"{"jsonrpc":"2.0","id":1,"method":"subtract","params":[42,23]}"#,
+        ),
+        (
+            JsonRpcMessage::Notification(JsonRpcNotification {
+                method: "update".into(),
+                params: None,
+            }),
+            r#"{"jsonrpc":"2.0","method":"update"}"#,
+        ),
+        (
+            JsonRpcMessage::Response(JsonRpcResponse {
+                id: JsonRpcId::Str("a".into()),
+                result: Ok(PackValue::Integer(19)),
+            }),
+            r#"{"jsonrpc":"2.0","id":"a","result":19}"#,
+        ),
+        (
+            JsonRpcMessage::Response(JsonRpcResponse {
+                id: JsonRpcId::Null,
+                result: Err(JsonRpcError::parse_error()),
+            }),
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error"}}"#,
+        ),
+    ];
+    for (message, expected) in cases {
+        let packet = JsonRpcPacket::Single(message);
+        let bytes = codec.encode(&mut json, &packet).unwrap();
+        assert_eq!(String::from_utf8(bytes.clone()).unwrap(), expected);
+        assert_eq!(codec.decode(&mut json, &bytes).unwrap(), packet);
+    }
+}
+
+#[test]
+fn jsonrpc_codec_roundtrip_matrix() {
+    let mut error = JsonRpcError::new(-32000, "Server error");
+    error.data = Some(PackValue::Str("details".into()));
+    let packet = JsonRpcPacket::Batch(vec![
+        JsonRpcMessage::Request(JsonRpcRequest {
+            id: JsonRpcId::Num(-5),
+            method: "get".into(),
+            params: Some(PackValue::Object(vec![("k".into(), PackValue::Bool(true))])),
+        }),
+        JsonRpcMessage::Notification(JsonRpcNotification {
+            method: "ping".into(),
+            params: Some(ints(&[])),
+        }),
+        JsonRpcMessage::Response(JsonRpcResponse {
+            id: JsonRpcId::Str("x".into()),
+            result: Err(error),
+        }),
+    ]);
+    let mut codecs: Vec<Box<dyn JsonValueCodec>> = vec![
+        Box::new(CborJsonValueCodec::new()),
+        Box::new(MsgPackJsonValueCodec::new()),
+        Box::new(JsonJsonValueCodec::new()),
+    ];
+    let codec = JsonRpcCodec::new();
+    for value_codec in codecs.iter_mut() {
+        let bytes = codec.encode(value_codec.as_mut(), &packet).unwrap();
+        assert_eq!(codec.decode(value_codec.as_mut(), &bytes).unwrap(), packet);
+    }
+}
+
+#[test]
+fn jsonrpc_decode_error_matrix() {
+    let mut json = JsonJsonValueCodec::new();
+    let codec = JsonRpcCodec::new();
+    let err = codec.decode(&mut json, b"{\"jsonrpc\"").unwrap_err();
+    assert_eq!(err.code, JsonRpcError::PARSE_ERROR);
+    assert!(err.data.is_some());
+    for input in [
+        "[]",
+        "1",
+        r#"{"jsonrpc":"1.0","method":"a"}"#,
+        r#"{"jsonrpc":"2.0","method":1}"#,
+        r#"{"jsonrpc":"2.0","method":"a","params":"bar"}"#,
+        r#"{"jsonrpc":"2.0","method":"a","id":1.5}"#,
+        r#"{"jsonrpc":"2.0","id":1}"#,
+        r#"{"jsonrpc":"2.0","id":1,"result":1,"error":{"code":1,"message":""}}"#,
+        r#"[{"jsonrpc":"2.0","method":"a"},1]"#,
+    ] {
+        let err = codec.decode(&mut json, input.as_bytes()).unwrap_err();
+        assert_eq!(err.code, JsonRpcError::INVALID_REQUEST, "{input}");
+    }
+    let err: JsonPackError = JsonRpcError::invalid_request().into();
+    assert_eq!(err.format(), "jsonrpc");
+    assert_eq!(err.offset(), None);
+}
+
+#[test]
+fn jsonrpc_router_spec_matrix() {
+    let mut router = router();
+    let cases: [(&str, Option<&str>); 9] = [
+        (
+            r#"{"jsonrpc":"2.0","method":"subtract","params":[42,23],"id":1}"#,
+            Some(r#"{"jsonrpc":"2.0","id":1,"result":19}"#),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","method":"update","params":[1,2]}"#,
+            None,
+        ),
+        (
+            r#"{"jsonrpc":"2.0","method":"foobar","id":"1"}"#,
+            Some(
+                r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32601,"message":"Method not found"}}"#,
+            ),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","method":"subtract","params":{},"id":2}"#,
+            Some(r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"Invalid params"}}"#),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","method":1,"params":"bar","id":3}"#,
+            Some(r#"{"jsonrpc":"2.0","id":3,"error":{"code":-32600,"message":"Invalid Request"}}"#),
+        ),
+        (
+            "[]",
+            Some(
+                r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"Invalid Request"}}"#,
+            ),
+        ),
+        (
+            "[1,2]",
+            Some(concat!(
+                r#"[{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"Invalid Request"}},"#,
+                r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"Invalid Request"}}]"#,
+            )),
+        ),
+        (
+            r#"[{"jsonrpc":"2.0","method":"notify_hello","params":[7]},{"jsonrpc":"2.0","method":"sum","params":[1,2,4],"id":"1"}]"#,
+            Some(r#"[{"jsonrpc":"2.0","id":"1","result":7}]"#),
+        ),
+        (
+            r#"[{"jsonrpc":"2.0","method":"notify_hello","params":[7]}]"#,
+            None,
+        ),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(respond(&mut router, input).as_deref(), expected, "{input}");
+    }
+
+    let reply = respond(&mut router, r#"{"jsonrpc":"2.0","method""#).unwrap();
+    assert!(reply.starts_with(r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"#));
+    // Responses sent to a server are ignored.
+    assert_eq!(
+        respond(&mut router, r#"{"jsonrpc":"2.0","id":1,"result":1}"#),
+        None
+    );
+}
+
+#[test]
+fn jsonrpc_router_state_matrix() {
+    let mut router = JsonRpcRouter::new();
+    let mut count = 0;
+    router.add("inc", move |_| {
+        count += 1;
+        Ok(PackValue::Integer(count))
+    });
+    assert!(router.has("inc"));
+    assert!(!router.has("dec"));
+    for expected in 1..=3 {
+        let response = router
+            .handle_message(JsonRpcMessage::Request(JsonRpcRequest {
+                id: JsonRpcId::Num(expected),
+                method: "inc".into(),
+                params: None,
+            }))
+            .unwrap();
+        assert_eq!(response.result, Ok(PackValue::Integer(expected)));
+    }
+    router.add("inc", |_| Ok(PackValue::Null));
+    let reply = router.handle_value(&PackValue::Object(vec![
+        ("jsonrpc".into(), PackValue::Str("2.0".into())),
+        ("id".into(), PackValue::Integer(9)),
+        ("method".into(), PackValue::Str("inc".into())),
+    ]));
+    assert_eq!(
+        reply,
+        Some(
+            JsonRpcMessage::Response(JsonRpcResponse {
+                id: JsonRpcId::Num(9),
+                result: Ok(PackValue::Null),
+            })
+            .to_pack()
+        )
+    );
+}
@@ -0,0 +1,143 @@
+//! Object key interning in the JSON, MessagePack and CBOR decoders.
+//!
+//! Not an upstream port: upstream has no key cache.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborDecoderCached, CborEncoder};
+use json_joy_json_pack::json::{JsonDecoder, JsonEncoder};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoderFast};
+use json_joy_json_pack::token::PackValueBuilder;
+use json_joy_json_pack::util::{CachedKey, KeyCache, KeyCacheStats};
+use json_joy_json_pack::PackValue;
+
+/// 100 rows of `{"id", "name", "a_key_longer_than_sixteen_bytes"}`.
+fn rows() -> PackValue {
+    PackValue::Array(
+        (0..100)
+            .map(|i| {
+                PackValue::Object(vec![
+                    ("id".into(), PackValue::Integer(i)),
+                    ("name".into(), PackValue::Str(format!("row {i}"))),
+                    (
+                        "a_key_longer_than_sixteen_bytes".into(),
+                        PackValue::Bool(i % 2 == 0),
+                    ),
+                ])
+            })
+            .collect(),
+    )
+}
+
+const ROWS_STATS: KeyCacheStats = KeyCacheStats {
+    hits: 198,
+    misses: 2,
+    evictions: 0,
+    bypassed: 100,
+};
+
+#[test]
+fn key_cache_decode_matrix() {
+    let value = rows();
+
+    let json = JsonEncoder::new().encode(&value);
+    let mut decoder = JsonDecoder::new().with_key_cache(KeyCache::new(16));
+    assert_eq!(decoder.decode(&json).unwrap(), value);
+    assert_eq!(decoder.key_cache_stats(), Some(ROWS_STATS));
+    assert_eq!(JsonDecoder::new().key_cache_stats(), None);
+
+    let msgpack = MsgPackEncoderFast::new().encode(&value);
+    let mut decoder = MsgPackDecoderFast::new().with_key_cache(KeyCache::new(16));
+    assert_eq!(decoder.decode(&msgpack).unwrap(), value);
+    assert_eq!(decoder.key_cache_stats(), Some(ROWS_STATS));
+
+    let cbor = CborEncoder::new().encode(&value);
+    let mut decoder = CborDecoder::new().with_key_cache(KeyCache::new(16));
+    assert_eq!(decoder.decode(&cbor).unwrap(), value);
+    assert_eq!(decoder.key_cache_stats(), ROWS_STATS);
+
+    // The cache is kept across documents.
+    decoder.decode(&cbor).unwrap();
+    assert_eq!(decoder.key_cache_stats().hits, 398);
+
+    // The plain decoder stays shareable; the cached one can be sent.
+    fn send_sync<T: Send + Sync>() {}
+    send_sync::<CborDecoder>();
+    send_sync::<CborDecoderCached>();
+}
+
+#[test]
+fn key_cache_token_matrix() {
+    let value = rows();
+
+    let json = JsonEncoder::new().encode(&value);
+    let mut decoder = JsonDecoder::new().with_key_cache(KeyCache::new(16));
+    let mut builder = PackValueBuilder::new();
+    decoder.parse_with(&json, &mut builder).unwrap();
+    assert_eq!(builder.finish(), Some(value.clone()));
+    assert_eq!(decoder.key_cache_stats(), Some(ROWS_STATS));
+
+    let msgpack = MsgPackEncoderFast::new().encode(&value);
+    let mut decoder = MsgPackDecoderFast::new().with_key_cache(KeyCache::new(16));
+    let mut builder = PackValueBuilder::new();
+    decoder.parse_with(&msgpack, &mut builder).unwrap();
+    assert_eq!(builder.finish(), Some(value));
+    assert_eq!(decoder.key_cache_stats(), Some(ROWS_STATS));
+}
+
+#[test]
+fn key_cache_escape_and_error_matrix() {
+    // Escaped and unescaped spellings of a key are cached separately.
+    let mut decoder = JsonDecoder::new().with_key_cache(KeyCache::new(16));
+    let value = decoder
+        .decode(br#"[{"ab":1},{"a\u0062":2},{"ab":3}]"#)
+        .unwrap();
+    let keys: Vec<_> = match value {
+        PackValue::Array(rows) => rows
+            .into_iter()
+            .map(|row| match row {
+                PackValue::Object(mut entries) => entries.remove(0).0,
+                other => panic!("expected object, got {other:?}"),
+            })
+            .collect(),
+        other => panic!("expected array, got {other:?}"),
+    };
+    assert_eq!(keys, ["ab", "ab", "ab"]);
+    let stats = decoder.key_cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+
+    // A key failing to decode is reported and not cached.
+    let mut decoder = MsgPackDecoderFast::new().with_key_cache(KeyCache::new(16));
+    assert!(decoder.decode(&[0x81, 0xa1, 0xff, 0x01]).is_err());
+    assert!(decoder.decode(&[0x81, 0xa1, 0xff, 0x01]).is_err());
+    assert_eq!(decoder.key_cache_stats(), Some(KeyCacheStats::default()));
+}
+
+#[test]
+fn key_cache_slots_matrix() {
+    let mut cache = KeyCache::with_slots(8, 1);
+    let decode = |raw: &[u8]| Ok::<_, ()>(String::from_utf8(raw.to_vec()).unwrap());
+    // Only the repeated "b" is handed out from the cache.
+    for (key, interned) in [
+        ("a", false),
+        ("b", false),
+        ("b", true),
+        ("a", false),
+        ("too long a key", false),
+    ] {
+        let cached = cache.get_or_decode(key.as_bytes(), decode).unwrap();
+        assert_eq!(&*cached, key);
+        assert_eq!(matches!(cached, CachedKey::Interned(_)), interned, "{key}");
+    }
+    assert_eq!(
+        cache.stats(),
+        KeyCacheStats {
+            hits: 1,
+            misses: 3,
+            evictions: 2,
+            bypassed: 1,
+        }
+    );
+    cache.clear();
+    assert_eq!(cache.stats(), KeyCacheStats::default());
+    cache.get_or_decode(b"b", decode).unwrap();
+    assert_eq!(cache.stats().misses, 1);
+}
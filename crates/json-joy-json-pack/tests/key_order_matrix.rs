@@ -0,0 +1,120 @@
+//! Pluggable object key order for the stable encoders.
+//!
+//! Not an upstream port: upstream hardcodes one order per encoder.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoderStable};
+use json_joy_json_pack::json::JsonEncoderStable;
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoderStable};
+use json_joy_json_pack::{KeyOrder, PackValue};
+
+fn obj(keys: &[&str]) -> PackValue {
+    PackValue::Object(
+        keys.iter()
+            .enumerate()
+            .map(|(i, k)| (k.to_string(), PackValue::Integer(i as i64)))
+            .collect(),
+    )
+}
+
+fn keys(value: &PackValue) -> Vec<String> {
+    match value {
+        PackValue::Object(pairs) => pairs.iter().map(|(k, _)| k.clone()).collect(),
+        other => panic!("expected object, got {other:?}"),
+    }
+}
+
+#[test]
+fn key_order_compare_matrix() {
+    let cases: [(KeyOrder, [&str; 4]); 4] = [
+        (KeyOrder::Lexicographic, ["a", "aa", "b", "\u{e9}"]),
+        (KeyOrder::LengthLex, ["a", "b", "\u{e9}", "aa"]),
+        (KeyOrder::ByteLengthLex, ["a", "b", "aa", "\u{e9}"]),
+        (
+            KeyOrder::Custom(|a, b| b.cmp(a)),
+            ["\u{e9}", "b", "aa", "a"],
+        ),
+    ];
+    for (order, expected) in cases {
+        let mut input = vec!["\u{e9}", "aa", "b", "a"];
+        input.sort_by(|a, b| order.compare(a, b));
+        assert_eq!(input, expected, "{order:?}");
+    }
+
+    // JavaScript `.length` counts an astral character as two code units.
+    let mut input = vec!["\u{1f600}", "\u{e9}"];
+    input.sort_by(|a, b| KeyOrder::LengthLex.compare(a, b));
+    assert_eq!(input, ["\u{e9}", "\u{1f600}"]);
+}
+
+#[test]
+fn json_stable_jcs_key_order() {
+    // RFC 8785 §3.2.3 sorting example.
+    let value = obj(&[
+        "\u{20ac}",
+        "\r",
+        "\u{fb33}",
+        "1",
+        "\u{1f600}",
+        "\u{80}",
+        "\u{f6}",
+    ]);
+    let mut encoder = JsonEncoderStable::with_key_order(KeyOrder::Utf16);
+    let json: serde_json::Value = serde_json::from_slice(&encoder.encode(&value)).unwrap();
+    let order: Vec<&String> = json.as_object().unwrap().keys().collect();
+    assert_eq!(
+        order,
+        [
+            "\r",
+            "1",
+            "\u{80}",
+            "\u{f6}",
+            "\u{20ac}",
+            "\u{1f600}",
+            "\u{fb33}"
+        ]
+    );
+
+    // Plain code point order puts the astral character last.
+    let mut encoder = JsonEncoderStable::with_key_order(KeyOrder::Lexicographic);
+    let text = String::from_utf8(encoder.encode(&obj(&["\u{1f600}", "\u{fb33}"]))).unwrap();
+    assert_eq!(text, "{\"\u{fb33}\":1,\"\u{1f600}\":0}");
+}
+
+#[test]
+fn stable_encoder_key_order_matrix() {
+    let value = obj(&["bb", "a", "ccc", "ab"]);
+
+    let mut json = JsonEncoderStable::new();
+    assert_eq!(json.encode(&value), br#"{"a":1,"ab":3,"bb":0,"ccc":2}"#);
+    json.key_order = KeyOrder::Lexicographic;
+    assert_eq!(json.encode(&value), br#"{"a":1,"ab":3,"bb":0,"ccc":2}"#);
+    json.key_order = KeyOrder::Custom(|a, b| b.cmp(a));
+    assert_eq!(json.encode(&value), br#"{"ccc":2,"bb":0,"ab":3,"a":1}"#);
+
+    let mut msgpack = MsgPackEncoderStable::new();
+    let decoded = MsgPackDecoderFast::new()
+        .decode(&msgpack.encode(&value))
+        .unwrap();
+    assert_eq!(keys(&decoded), ["a", "ab", "bb", "ccc"]);
+    let mut msgpack = MsgPackEncoderStable::with_key_order(KeyOrder::LengthLex);
+    let decoded = MsgPackDecoderFast::new()
+        .decode(&msgpack.encode(&value))
+        .unwrap();
+    assert_eq!(keys(&decoded), ["a", "ab", "bb", "ccc"]);
+    let value = obj(&["b", "aa"]);
+    let decoded = MsgPackDecoderFast::new()
+        .decode(&msgpack.encode(&value))
+        .unwrap();
+    assert_eq!(keys(&decoded), ["b", "aa"]);
+
+    let mut cbor = CborEncoderStable::new();
+    let decoded = CborDecoder::new().decode(&cbor.encode(&value)).unwrap();
+    assert_eq!(keys(&decoded), ["b", "aa"]);
+    let mut cbor = CborEncoderStable::with_key_order(KeyOrder::Lexicographic);
+    let nested = PackValue::Array(vec![value]);
+    let decoded = CborDecoder::new().decode(&cbor.encode(&nested)).unwrap();
+    match decoded {
+        PackValue::Array(items) => assert_eq!(keys(&items[0]), ["aa", "b"]),
+        other => panic!("expected array, got {other:?}"),
+    }
+}
@@ -0,0 +1,336 @@
+//! Lazy map and array views over MessagePack and CBOR, checked against a
+//! full decode of the same buffer.
+//!
+//! Not an upstream port: upstream decodes whole values or reads one path
+//! with `find`.
+
+use json_joy_json_pack::cbor::{self, CborDecoder, CborEncoder, CborError};
+use json_joy_json_pack::msgpack::{self, MsgPackDecoderFast, MsgPackEncoder, MsgPackError};
+use json_joy_json_pack::{pack, PackValue};
+
+fn record() -> PackValue {
+    PackValue::Object(vec![
+        ("name".into(), pack!("ada")),
+        ("id".into(), pack!(42)),
+        ("neg".into(), pack!(-70000)),
+        ("max".into(), PackValue::UInteger(u32::MAX as u64)),
+        ("min".into(), PackValue::Integer(i32::MIN as i64)),
+        ("big".into(), PackValue::UInteger(u64::MAX)),
+        ("ratio".into(), pack!(0.5)),
+        ("ok".into(), pack!(true)),
+        ("nil".into(), PackValue::Null),
+        ("blob".into(), PackValue::Bytes(vec![0, 1, 255])),
+        ("tags".into(), pack!(["a", "é", "😀"])),
+        ("nested".into(), pack!({"deep": {"x": 1, "y": [1, 2, 3]}})),
+        (
+            "items".into(),
+            PackValue::Array((0..40).map(PackValue::Integer).collect()),
+        ),
+        ("long".into(), pack!("x".repeat(300))),
+    ])
+}
+
+fn fields(value: &PackValue) -> &[(String, PackValue)] {
+    match value {
+        PackValue::Object(fields) => fields,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn msgpack_lazy_view_matrix() {
+    let record = record();
+    let buf = MsgPackEncoder::new().encode(&record);
+    let decoded = MsgPackDecoderFast::new().decode(&buf).unwrap();
+    let map = msgpack::LazyMap::new(&buf).unwrap();
+    assert_eq!(map.len(), fields(&record).len());
+
+    // Every entry, by lookup and by iteration, decodes like the full value.
+    let mut iter = map.iter();
+    for (key, expected) in fields(&decoded) {
+        let value = map.get(key).unwrap().unwrap();
+        assert_eq!(&value.decode().unwrap(), expected, "{key}");
+        let (k, v) = iter.next().unwrap().unwrap();
+        assert_eq!(k.as_str().unwrap(), key);
+        assert_eq!(v.offset(), value.offset());
+    }
+    assert!(iter.next().is_none());
+
+    assert_eq!(map.get_str("name").unwrap(), Some("ada"));
+    assert_eq!(map.get_u64("id").unwrap(), Some(42));
+    assert_eq!(map.get_i64("neg").unwrap(), Some(-70000));
+    assert_eq!(map.get_u64("max").unwrap(), Some(u32::MAX as u64));
+    assert_eq!(map.get_i64("min").unwrap(), Some(i32::MIN as i64));
+    assert_eq!(map.get_f64("ratio").unwrap(), Some(0.5));
+    assert_eq!(map.get_f64("id").unwrap(), Some(42.0));
+    assert_eq!(map.get_f64("big").unwrap(), Some(u64::MAX as f64));
+    assert_eq!(map.get_bool("ok").unwrap(), Some(true));
+    assert!(map.get("nil").unwrap().unwrap().is_null());
+    assert_eq!(map.get_bytes("blob").unwrap(), Some(&[0u8, 1, 255][..]));
+    assert_eq!(map.get_str("long").unwrap().map(str::len), Some(300));
+
+    let tags = map.get_array("tags").unwrap().unwrap();
+    assert_eq!(tags.len(), 3);
+    assert_eq!(tags.get_str(2).unwrap(), Some("😀"));
+    assert_eq!(tags.get_str(3).unwrap(), None);
+    let items = map.get_array("items").unwrap().unwrap();
+    assert_eq!(items.len(), 40);
+    assert_eq!(items.get_u64(39).unwrap(), Some(39));
+    let values: Vec<_> = items.iter().map(|v| v.unwrap().as_u64().unwrap()).collect();
+    assert_eq!(values, (0..40).collect::<Vec<_>>());
+    let deep = map
+        .get_map("nested")
+        .unwrap()
+        .unwrap()
+        .get_map("deep")
+        .unwrap()
+        .unwrap();
+    assert_eq!(deep.get_u64("x").unwrap(), Some(1));
+    assert_eq!(
+        deep.get_array("y").unwrap().unwrap().get_u64(1).unwrap(),
+        Some(2)
+    );
+
+    // Missing keys are `None`; present values of another type are errors.
+    assert_eq!(map.get_str("missing").unwrap(), None);
+    assert!(matches!(map.get_str("id"), Err(MsgPackError::NotStr)));
+    assert!(matches!(map.get_map("tags"), Err(MsgPackError::NotObj)));
+    assert!(matches!(map.get_array("nested"), Err(MsgPackError::NotArr)));
+    assert!(matches!(
+        map.get_bool("nil"),
+        Err(MsgPackError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        map.get_u64("neg"),
+        Err(MsgPackError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        map.get_u64("big"),
+        Err(MsgPackError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        map.get_bytes("name"),
+        Err(MsgPackError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        msgpack::LazyMap::new(&[0x90]),
+        Err(MsgPackError::NotObj)
+    ));
+
+    // 64-bit integers, which the encoders write as floats.
+    let mut wide = vec![0x82, 0xa3, b'm', b'a', b'x', 0xcf];
+    wide.extend_from_slice(&u64::MAX.to_be_bytes());
+    wide.extend_from_slice(&[0xa3, b'm', b'i', b'n', 0xd3]);
+    wide.extend_from_slice(&i64::MIN.to_be_bytes());
+    let wide = msgpack::LazyMap::new(&wide).unwrap();
+    assert_eq!(wide.get_u64("max").unwrap(), Some(u64::MAX));
+    assert_eq!(wide.get_i64("min").unwrap(), Some(i64::MIN));
+    assert!(matches!(
+        wide.get_i64("max"),
+        Err(MsgPackError::UnexpectedType(5))
+    ));
+    assert!(matches!(
+        wide.get_u64("min"),
+        Err(MsgPackError::UnexpectedType(18))
+    ));
+
+    // A truncated buffer fails when the cut-off part is read.
+    for cut in 0..buf.len() {
+        let result = msgpack::LazyMap::new(&buf[..cut]).and_then(|map| map.get_str("long"));
+        assert!(result.is_err(), "cut {cut}");
+    }
+}
+
+#[test]
+fn cbor_lazy_view_matrix() {
+    let record = record();
+    let buf = CborEncoder::new().encode(&record);
+    let decoder = CborDecoder::new();
+    let decoded = decoder.decode(&buf).unwrap();
+    let map = cbor::LazyMap::new(&buf).unwrap();
+    assert_eq!(map.len(), Some(fields(&record).len()));
+
+    let mut iter = map.iter();
+    for (key, expected) in fields(&decoded) {
+        let value = map.get(key).unwrap().unwrap();
+        assert_eq!(&value.decode().unwrap(), expected, "{key}");
+        let (k, v) = iter.next().unwrap().unwrap();
+        assert_eq!(k.as_str().unwrap(), key);
+        assert_eq!(v.offset(), value.offset());
+    }
+    assert!(iter.next().is_none());
+
+    assert_eq!(map.get_str("name").unwrap(), Some("ada"));
+    assert_eq!(map.get_u64("id").unwrap(), Some(42));
+    assert_eq!(map.get_i64("neg").unwrap(), Some(-70000));
+    assert_eq!(map.get_u64("big").unwrap(), Some(u64::MAX));
+    assert_eq!(map.get_i64("min").unwrap(), Some(i32::MIN as i64));
+    assert_eq!(map.get_f64("ratio").unwrap(), Some(0.5));
+    assert_eq!(map.get_bool("ok").unwrap(), Some(true));
+    assert!(map.get("nil").unwrap().unwrap().is_null());
+    assert_eq!(map.get_bytes("blob").unwrap(), Some(&[0u8, 1, 255][..]));
+    let items = map.get_array("items").unwrap().unwrap();
+    assert_eq!(items.len(), Some(40));
+    assert_eq!(items.get_u64(39).unwrap(), Some(39));
+    let deep = map
+        .get_map("nested")
+        .unwrap()
+        .unwrap()
+        .get_map("deep")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        deep.get_array("y").unwrap().unwrap().get_u64(2).unwrap(),
+        Some(3)
+    );
+
+    assert_eq!(map.get_str("missing").unwrap(), None);
+    assert_eq!(
+        map.get_str("nested").unwrap_err(),
+        CborError::UnexpectedMajor(108)
+    );
+    assert_eq!(
+        map.get_u64("neg").unwrap_err(),
+        CborError::IntegerOutOfRange
+    );
+    assert_eq!(
+        map.get_i64("big").unwrap_err(),
+        CborError::IntegerOutOfRange
+    );
+    assert_eq!(
+        map.get_bool("nil").unwrap_err(),
+        CborError::UnexpectedMinor(74)
+    );
+
+    for cut in 0..buf.len() {
+        let result = cbor::LazyMap::new(&buf[..cut]).and_then(|map| map.get_str("long"));
+        assert!(result.is_err(), "cut {cut}");
+    }
+
+    // A length past `usize` is not read as indefinite.
+    let huge = [0xbb, 0x80, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(
+        cbor::LazyMap::new(&huge).unwrap_err(),
+        CborError::InvalidSize(1)
+    );
+}
+
+#[test]
+fn cbor_lazy_view_indefinite_and_tagged_matrix() {
+    // {_ "a": [_ 1, 2], "t": 1(1700000000), "s": (_ "ab", "c"), "h": 1.5 as f16}
+    let buf = [
+        0xbf, 0x61, b'a', 0x9f, 0x01, 0x02, 0xff, 0x61, b't', 0xc1, 0x1a, 0x65, 0x53, 0xf1, 0x00,
+        0x61, b's', 0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff, 0x61, b'h', 0xf9, 0x3e, 0x00, 0xff,
+    ];
+    let map = cbor::LazyMap::new(&buf).unwrap();
+    assert_eq!(map.len(), None);
+    assert!(!map.is_empty());
+    assert_eq!(map.iter().count(), 4);
+
+    let arr = map.get_array("a").unwrap().unwrap();
+    assert_eq!(arr.len(), None);
+    assert_eq!(arr.get_u64(1).unwrap(), Some(2));
+    assert_eq!(arr.get(2).unwrap().map(|v| v.offset()), None);
+
+    let t = map.get("t").unwrap().unwrap();
+    assert_eq!(t.tags().unwrap(), vec![1]);
+    assert_eq!(t.as_u64().unwrap(), 1_700_000_000);
+    assert_eq!(t.decode().unwrap(), decode_slice(t.bytes().unwrap()));
+
+    assert_eq!(map.get_str("s").unwrap_err(), CborError::Unsupported);
+    assert_eq!(
+        map.get("s").unwrap().unwrap().decode().unwrap(),
+        pack!("abc")
+    );
+    assert_eq!(map.get_f64("h").unwrap(), Some(1.5));
+
+    assert!(cbor::LazyMap::new(&[0xbf, 0xff]).unwrap().is_empty());
+    // An indefinite map cut before its break byte.
+    assert_eq!(
+        cbor::LazyMap::new(&buf[..buf.len() - 1])
+            .unwrap()
+            .get("missing")
+            .unwrap_err(),
+        CborError::UnexpectedEof(29)
+    );
+    // A key without a value before the break.
+    assert_eq!(
+        cbor::LazyMap::new(&[0xbf, 0x61, b'a', 0xff])
+            .unwrap()
+            .get("b")
+            .unwrap_err(),
+        CborError::UnexpectedObjBreak(3)
+    );
+}
+
+fn decode_slice(bytes: &[u8]) -> PackValue {
+    CborDecoder::new().decode(bytes).unwrap()
+}
+
+#[test]
+fn msgpack_lazy_buf_view_matrix() {
+    let buf = MsgPackEncoder::new().encode(&record());
+    let map = msgpack::LazyMap::new(&buf).unwrap();
+    let view = map.view().unwrap();
+    assert_eq!((view.start(), view.end()), (0, buf.len()));
+
+    // Views know where they sit in the buffer.
+    let nested = map.get_map("nested").unwrap().unwrap().view().unwrap();
+    assert_eq!(
+        nested.as_slice(),
+        MsgPackEncoder::new().encode(&pack!({"deep": {"x": 1, "y": [1, 2, 3]}}))
+    );
+    assert_eq!(&buf[nested.start()..nested.end()], &*nested);
+    let items = map.get_array("items").unwrap().unwrap().view().unwrap();
+    assert_eq!(items[..3], [0xdc, 0x00, 40]);
+    let blob = map.get("blob").unwrap().unwrap().bin_view().unwrap();
+    assert_eq!(blob.as_slice(), [0, 1, 255]);
+    assert_eq!(buf[blob.start() - 2..blob.start()], [0xc4, 3]);
+    let id = map.get("id").unwrap().unwrap();
+    assert_eq!(id.view().unwrap().as_slice(), id.bytes().unwrap());
+    assert_eq!(id.view().unwrap().start(), id.offset());
+    assert!(matches!(
+        msgpack::LazyValue::new(&buf[..buf.len() - 1]).view(),
+        Err(MsgPackError::UnexpectedEof(_))
+    ));
+
+    let mut decoder = msgpack::MsgPackDecoder::new();
+    decoder.reset(&buf);
+    decoder.read_obj_hdr().unwrap();
+    let key = decoder.skip_view().unwrap();
+    assert_eq!((key.as_slice(), key.start()), (&b"\xa4name"[..], 1));
+    assert_eq!(decoder.skip_any().unwrap(), 4);
+}
+
+#[test]
+fn cbor_lazy_buf_view_matrix() {
+    let buf = CborEncoder::new().encode(&record());
+    let map = cbor::LazyMap::new(&buf).unwrap();
+    let view = map.view().unwrap();
+    assert_eq!((view.start(), view.end()), (0, buf.len()));
+
+    let nested = map.get_map("nested").unwrap().unwrap().view().unwrap();
+    assert_eq!(
+        nested.as_slice(),
+        CborEncoder::new().encode(&pack!({"deep": {"x": 1, "y": [1, 2, 3]}}))
+    );
+    assert_eq!(&buf[nested.start()..nested.end()], &*nested);
+    let items = map.get_array("items").unwrap().unwrap().view().unwrap();
+    assert_eq!(items[..3], [0x98, 40, 0x00]);
+    let blob = map.get("blob").unwrap().unwrap().bin_view().unwrap();
+    assert_eq!(blob.as_slice(), [0, 1, 255]);
+    assert_eq!(buf[blob.start() - 1], 0x43);
+    let id = map.get("id").unwrap().unwrap();
+    assert_eq!(id.view().unwrap().as_slice(), id.bytes().unwrap());
+    assert_eq!(id.view().unwrap().start(), id.offset());
+    assert!(matches!(
+        cbor::LazyValue::new(&buf[..buf.len() - 1]).view(),
+        Err(CborError::UnexpectedEof(_))
+    ));
+
+    // Tags are part of the value's view.
+    let tagged = [0xc1, 0x1a, 0x65, 0x53, 0xf1, 0x00];
+    let value = cbor::LazyValue::new(&tagged);
+    assert_eq!(value.view().unwrap().as_slice(), tagged);
+    assert_eq!(value.bin_view().unwrap_err(), CborError::UnexpectedMajor(0));
+}
@@ -0,0 +1,367 @@
+//! MessagePack extension and `undefined` policies, and extension registries.
+//!
+//! Not an upstream port: upstream keeps every extension as raw bytes and
+//! always reads `0xc1` as `undefined`.
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::msgpack::{
+    JsonToMsgPackConverter, MsgPackDecoder, MsgPackDecoderOptions, MsgPackEncoder,
+    MsgPackEncoderFast, MsgPackEncoderOptions, MsgPackError, MsgPackExtCodec, MsgPackExtPolicy,
+    MsgPackExtRegistry, MsgPackExtValuePolicy, MsgPackToJsonConverter, MsgPackToJsonOptions,
+    MsgPackUndefinedPolicy,
+};
+use json_joy_json_pack::{JsonPackExtension, PackValue};
+
+/// Application type 1: a big-endian `u32`.
+fn registry() -> MsgPackExtRegistry {
+    MsgPackExtRegistry::new().with(
+        1,
+        MsgPackExtCodec {
+            decode: |data| {
+                Some(PackValue::UInteger(
+                    u32::from_be_bytes(data.try_into().ok()?) as u64,
+                ))
+            },
+            encode: |value| match value {
+                PackValue::UInteger(u) => Some(u32::try_from(*u).ok()?.to_be_bytes().to_vec()),
+                _ => None,
+            },
+        },
+    )
+}
+
+fn ext(tag: u64, val: PackValue) -> PackValue {
+    PackValue::Extension(Box::new(JsonPackExtension::new(tag, val)))
+}
+
+fn decode(options: MsgPackDecoderOptions, data: &[u8]) -> Result<PackValue, MsgPackError> {
+    MsgPackDecoder::with_options(options).decode(data)
+}
+
+fn convert(options: MsgPackToJsonOptions, data: &[u8]) -> Result<String, MsgPackError> {
+    let mut out = Writer::new();
+    MsgPackToJsonConverter::with_options(options).convert_into(data, &mut out)?;
+    Ok(String::from_utf8(out.flush()).unwrap())
+}
+
+#[test]
+fn msgpack_ext_undefined_matrix() {
+    let fixext = [0xd4, 0x00, 0x00];
+    let cases: &[(MsgPackUndefinedPolicy, &[u8], Option<PackValue>)] = &[
+        (
+            MsgPackUndefinedPolicy::Marker,
+            &[0xc1],
+            Some(PackValue::Undefined),
+        ),
+        (
+            MsgPackUndefinedPolicy::Marker,
+            &fixext,
+            Some(ext(0, PackValue::Bytes(vec![0]))),
+        ),
+        (MsgPackUndefinedPolicy::FixExt, &[0xc1], None),
+        (
+            MsgPackUndefinedPolicy::FixExt,
+            &fixext,
+            Some(PackValue::Undefined),
+        ),
+        (
+            MsgPackUndefinedPolicy::FixExt,
+            &[0xd4, 0x00, 0x01],
+            Some(ext(0, PackValue::Bytes(vec![1]))),
+        ),
+        (MsgPackUndefinedPolicy::Error, &[0xc1], None),
+        (
+            MsgPackUndefinedPolicy::Error,
+            &fixext,
+            Some(ext(0, PackValue::Bytes(vec![0]))),
+        ),
+    ];
+    for (undefined, data, expected) in cases {
+        let options = MsgPackDecoderOptions {
+            undefined: *undefined,
+            ..Default::default()
+        };
+        let decoded = decode(options, data);
+        match expected {
+            Some(value) => assert_eq!(&decoded.unwrap(), value, "{undefined:?} {data:02x?}"),
+            None => assert!(
+                matches!(decoded, Err(MsgPackError::InvalidByte(0))),
+                "{undefined:?} {data:02x?}"
+            ),
+        }
+        let options = MsgPackToJsonOptions {
+            undefined: *undefined,
+            ..Default::default()
+        };
+        let converted = convert(options, data);
+        match expected {
+            Some(PackValue::Undefined) => assert_eq!(converted.unwrap(), "null"),
+            Some(_) => assert!(converted.unwrap().starts_with("\"data:")),
+            None => assert!(matches!(converted, Err(MsgPackError::InvalidByte(0)))),
+        }
+    }
+
+    let value = PackValue::Array(vec![PackValue::Undefined]);
+    assert_eq!(MsgPackEncoder::new().encode(&value), [0x91, 0xc1]);
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        undefined_fixext: true,
+        ..Default::default()
+    });
+    let bytes = encoder.encode(&value);
+    assert_eq!(bytes, [0x91, 0xd4, 0x00, 0x00]);
+    let options = MsgPackDecoderOptions {
+        undefined: MsgPackUndefinedPolicy::FixExt,
+        ..Default::default()
+    };
+    assert_eq!(decode(options, &bytes).unwrap(), value);
+}
+
+#[test]
+fn msgpack_ext_unknown_matrix() {
+    // fixext1 of type 5, then fixext2 of type -1, held sign-extended.
+    let data = [0x92, 0xd4, 0x05, 0xaa, 0xd5, 0xff, 0x01, 0x02];
+    let preserved = PackValue::Array(vec![
+        ext(5, PackValue::Bytes(vec![0xaa])),
+        ext(-1i64 as u64, PackValue::Bytes(vec![1, 2])),
+    ]);
+    for unknown_ext in [MsgPackExtPolicy::Unwrap, MsgPackExtPolicy::Wrap] {
+        let options = MsgPackDecoderOptions {
+            unknown_ext,
+            ..Default::default()
+        };
+        let decoded = decode(options, &data).unwrap();
+        assert_eq!(decoded, preserved);
+        // Preserved extensions re-encode byte for byte.
+        assert_eq!(MsgPackEncoder::new().encode(&decoded), data);
+    }
+    let options = MsgPackDecoderOptions {
+        unknown_ext: MsgPackExtPolicy::Error,
+        ..Default::default()
+    };
+    assert!(matches!(
+        decode(options, &data),
+        Err(MsgPackError::UnknownExt { tag: 5, offset: 2 })
+    ));
+
+    let uri = "data:application/octet-stream;base64,";
+    let cases = [
+        (
+            MsgPackExtPolicy::Unwrap,
+            format!("[\"{uri}qg==\",\"{uri}AQI=\"]"),
+        ),
+        (
+            MsgPackExtPolicy::Wrap,
+            format!(
+                "[{{\"tag\":5,\"value\":\"{uri}qg==\"}},{{\"tag\":-1,\"value\":\"{uri}AQI=\"}}]"
+            ),
+        ),
+    ];
+    for (unknown_ext, expected) in cases {
+        let options = MsgPackToJsonOptions {
+            unknown_ext,
+            ..Default::default()
+        };
+        assert_eq!(convert(options, &data).unwrap(), expected);
+    }
+    assert_eq!(
+        MsgPackToJsonConverter::new().convert(&data).unwrap(),
+        format!("[\"{uri}qg==\",\"{uri}AQI=\"]")
+    );
+    let options = MsgPackToJsonOptions {
+        unknown_ext: MsgPackExtPolicy::Error,
+        ..Default::default()
+    };
+    let mut out = Writer::new();
+    let mut converter = MsgPackToJsonConverter::with_options(options);
+    assert!(matches!(
+        converter.convert_into(&data, &mut out),
+        Err(MsgPackError::UnknownExt { tag: 5, offset: 2 })
+    ));
+    assert!(out.flush().is_empty());
+    assert!(matches!(
+        converter.convert(&data),
+        Err(MsgPackError::UnknownExt { tag: 5, offset: 2 })
+    ));
+    // A failed conversion leaves nothing behind for the next one.
+    assert_eq!(converter.convert(&[0x92, 0x01, 0xc0]).unwrap(), "[1,null]");
+    assert!(matches!(
+        converter.convert(&[0xa3, b'a']),
+        Err(MsgPackError::UnexpectedEof(1))
+    ));
+}
+
+#[test]
+fn msgpack_ext_wrapped_json_matrix() {
+    // The wrapped JSON form converts back to the same extensions.
+    let data = [0x92, 0xd4, 0x05, 0xaa, 0xc7, 0x03, 0x80, 0x01, 0x02, 0x03];
+    let options = MsgPackToJsonOptions {
+        unknown_ext: MsgPackExtPolicy::Wrap,
+        ..Default::default()
+    };
+    let json = convert(options, &data).unwrap();
+    let mut converter = JsonToMsgPackConverter::new().with_wrapped_ext(true);
+    assert_eq!(converter.convert(json.as_bytes()).unwrap(), data);
+    assert_ne!(
+        JsonToMsgPackConverter::new()
+            .convert(json.as_bytes())
+            .unwrap(),
+        data
+    );
+
+    // Anything else in the shape of an object stays a map.
+    let uri = "data:application/octet-stream;base64,AQ==";
+    let maps = [
+        format!("{{\"tag\":128,\"value\":\"{uri}\"}}"),
+        format!("{{\"tag\":1.5,\"value\":\"{uri}\"}}"),
+        "{\"tag\":1,\"value\":\"AQ==\"}".to_string(),
+        format!("{{\"value\":\"{uri}\",\"tag\":1}}"),
+        format!("{{\"tag\":1,\"value\":\"{uri}\",\"x\":1}}"),
+        format!("{{\"tag\":1,\"value\":[\"{uri}\"]}}"),
+        "{\"tag\":1}".to_string(),
+    ];
+    for json in maps {
+        assert_eq!(
+            converter.convert(json.as_bytes()).unwrap(),
+            JsonToMsgPackConverter::new()
+                .convert(json.as_bytes())
+                .unwrap(),
+            "{json}"
+        );
+    }
+    let json = format!("{{\"a\":[{{\"tag\":-1,\"value\":\"{uri}\"}},{{}}]}}");
+    assert_eq!(
+        converter.convert(json.as_bytes()).unwrap(),
+        [0x81, 0xa1, b'a', 0x92, 0xd4, 0xff, 0x01, 0x80]
+    );
+}
+
+#[test]
+fn msgpack_ext_registry_matrix() {
+    // Type 1 is registered; type 2 is not.
+    let data = [0x92, 0xd6, 0x01, 0x00, 0x00, 0x01, 0x00, 0xd4, 0x02, 0x07];
+    let options = MsgPackDecoderOptions {
+        ext_registry: registry(),
+        unknown_ext: MsgPackExtPolicy::Error,
+        ..Default::default()
+    };
+    assert!(matches!(
+        decode(options.clone(), &data),
+        Err(MsgPackError::UnknownExt { tag: 2, offset: 8 })
+    ));
+    let options = MsgPackDecoderOptions {
+        unknown_ext: MsgPackExtPolicy::Unwrap,
+        ..options
+    };
+    let decoded = decode(options.clone(), &data).unwrap();
+    assert_eq!(
+        decoded,
+        PackValue::Array(vec![
+            ext(1, PackValue::UInteger(256)),
+            ext(2, PackValue::Bytes(vec![7])),
+        ])
+    );
+
+    // The registry encodes decoded values back to the same payload.
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        ext_registry: registry(),
+        ..Default::default()
+    });
+    assert_eq!(encoder.encode(&decoded), data);
+    // Without a codec the value is written in place of the extension.
+    assert_eq!(
+        MsgPackEncoder::new().encode(&ext(1, PackValue::UInteger(256))),
+        [0xcd, 0x01, 0x00]
+    );
+    assert_eq!(
+        encoder.encode(&ext(1, PackValue::Str("x".into()))),
+        [0xa1, b'x']
+    );
+    // The fast encoder takes the same registry.
+    let mut fast = MsgPackEncoderFast::new().with_ext_registry(registry());
+    assert_eq!(fast.encode(&decoded), data);
+    assert_eq!(fast.try_encode(&decoded).unwrap(), data);
+    assert!(MsgPackEncoderFast::new().try_encode(&decoded).is_err());
+
+    // Nesting keeps the type of a value no codec takes.
+    let mut nesting = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        ext_registry: registry(),
+        ext_value: MsgPackExtValuePolicy::Nest,
+        ..Default::default()
+    });
+    let value = PackValue::Array(vec![
+        ext(1, PackValue::UInteger(256)),
+        ext(1, PackValue::Str("x".into())),
+        ext(3, PackValue::UInteger(256)),
+    ]);
+    let bytes = nesting.try_encode(&value).unwrap();
+    assert_eq!(
+        bytes,
+        [
+            0x93, 0xd6, 0x01, 0x00, 0x00, 0x01, 0x00, 0xd5, 0x01, 0xa1, b'x', 0xc7, 0x03, 0x03,
+            0xcd, 0x01, 0x00
+        ]
+    );
+    assert_eq!(
+        decode(MsgPackDecoderOptions::default(), &bytes).unwrap(),
+        PackValue::Array(vec![
+            ext(1, PackValue::Bytes(vec![0, 0, 1, 0])),
+            ext(1, PackValue::Bytes(vec![0xa1, b'x'])),
+            ext(3, PackValue::Bytes(vec![0xcd, 0x01, 0x00])),
+        ])
+    );
+
+    // A registered codec rejecting its payload is an error.
+    assert!(matches!(
+        decode(options, &[0xd4, 0x01, 0x00]),
+        Err(MsgPackError::InvalidExt(1))
+    ));
+    let options = MsgPackToJsonOptions {
+        ext_registry: registry(),
+        unknown_ext: MsgPackExtPolicy::Wrap,
+        ..Default::default()
+    };
+    assert_eq!(
+        convert(options.clone(), &data).unwrap(),
+        "[256,{\"tag\":2,\"value\":\"data:application/octet-stream;base64,Bw==\"}]"
+    );
+    let err = convert(options, &[0xd4, 0x01, 0x00]).unwrap_err();
+    assert!(matches!(err, MsgPackError::InvalidExt(1)));
+    assert_eq!(err.offset(), Some(1));
+}
+
+#[test]
+fn msgpack_ext_type_range_matrix() {
+    // 127 is the largest type; 128 and up would wrap to negative types, so
+    // both encoders drop the type instead.
+    let nested = MsgPackEncoderOptions {
+        ext_value: MsgPackExtValuePolicy::Nest,
+        ..MsgPackEncoderOptions::default()
+    };
+    let cases = [
+        (
+            ext(127, PackValue::Bytes(vec![1, 2])),
+            vec![0xd5, 0x7f, 1, 2],
+        ),
+        (
+            ext(128, PackValue::Bytes(vec![1, 2])),
+            vec![0xc4, 0x02, 1, 2],
+        ),
+        (
+            ext(300, PackValue::Bytes(vec![1, 2])),
+            vec![0xc4, 0x02, 1, 2],
+        ),
+        (ext(128, PackValue::Integer(7)), vec![0x07]),
+    ];
+    for (value, bytes) in cases {
+        assert_eq!(MsgPackEncoderFast::new().encode(&value), bytes, "{value:?}");
+        assert_eq!(
+            MsgPackEncoder::with_options(nested.clone()).encode(&value),
+            bytes,
+            "{value:?}"
+        );
+    }
+    // `try_encode` reports the dropped type.
+    assert!(MsgPackEncoder::new()
+        .try_encode(&ext(128, PackValue::Bytes(vec![1])))
+        .is_err());
+}
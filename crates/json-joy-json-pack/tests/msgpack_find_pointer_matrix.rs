@@ -0,0 +1,49 @@
+//! `MsgPackDecoder::find_pointer` with typed JSON Pointer segments.
+//!
+//! Not an upstream port: upstream `findPath` takes untyped steps. Results
+//! are checked against `get_typed` on the same document.
+
+use json_joy_json_pack::msgpack::{MsgPackDecoder, MsgPackEncoderFast};
+use json_joy_json_pack::PackValue;
+use json_joy_json_pointer::{get_typed, parse_typed_pointer};
+use serde_json::json;
+
+#[test]
+fn msgpack_find_pointer_matrix() {
+    let doc = json!({
+        "users": [
+            {"name": "ann", "tags": ["a", "b"]},
+            {"name": "bob", "tags": []}
+        ],
+        "a/b": {"~c": 1},
+        "10": "key",
+        "-": "dash",
+        "n": null
+    });
+    let bytes = MsgPackEncoderFast::new().encode(&PackValue::from(doc.clone()));
+    let mut decoder = MsgPackDecoder::new();
+    for pointer in [
+        "",
+        "/users/0/name",
+        "/users/0/tags/1",
+        "/users/1/tags/0",
+        "/users/2",
+        "/users/-",
+        "/users/x",
+        "/users/0/name/x",
+        "/a~1b/~0c",
+        "/10",
+        "/-",
+        "/n",
+        "/missing",
+    ] {
+        let path = parse_typed_pointer(pointer);
+        decoder.reset(&bytes);
+        let found = decoder
+            .find_pointer(&path)
+            .ok()
+            .map(|d| d.read_any().unwrap());
+        let expected = get_typed(&doc, &path).cloned().map(PackValue::from);
+        assert_eq!(found, expected, "{pointer}");
+    }
+}
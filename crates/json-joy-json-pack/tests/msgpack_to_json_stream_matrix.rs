@@ -0,0 +1,104 @@
+//! `MsgPackToJsonConverter` writing into a caller's `Writer`, and NDJSON
+//! conversion of concatenated messages fed in arbitrary chunks.
+//!
+//! Not an upstream port: upstream converts one message to a string.
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::msgpack::{MsgPackEncoderFast, MsgPackError, MsgPackToJsonConverter};
+use json_joy_json_pack::{pack, PackValue};
+
+fn records() -> Vec<PackValue> {
+    vec![
+        pack!({"level": "info", "msg": "started", "pid": 4242}),
+        pack!({"level": "warn", "msg": "quote \" and \\ and \n\t\u{1}\u{1f}", "tags": ["a", "é", "😀"]}),
+        PackValue::Bytes(vec![0, 1, 2, 255]),
+        pack!([-1, -200, 70000, 3.5, null, true, false]),
+        PackValue::UInteger(u64::MAX),
+        PackValue::Integer(i64::MIN),
+        pack!("x".repeat(300)),
+    ]
+}
+
+#[test]
+fn msgpack_convert_into_matrix() {
+    let mut encoder = MsgPackEncoderFast::new();
+    let mut converter = MsgPackToJsonConverter::new();
+    for record in records() {
+        let msgpack = encoder.encode(&record);
+        let expected = converter.convert(&msgpack).unwrap();
+
+        // Appends after existing output and reports the bytes read.
+        let mut out = Writer::with_alloc_size(16);
+        out.ascii("prefix ");
+        assert_eq!(
+            converter.convert_into(&msgpack, &mut out).unwrap(),
+            msgpack.len()
+        );
+        assert_eq!(
+            String::from_utf8(out.flush()).unwrap(),
+            format!("prefix {expected}")
+        );
+
+        // String escaping matches serde_json.
+        if let PackValue::Object(fields) = &record {
+            for (_, value) in fields {
+                if let PackValue::Str(s) = value {
+                    assert!(expected.contains(&serde_json::to_string(s).unwrap()));
+                }
+            }
+        }
+
+        // A cut-off value fails without writing anything.
+        for cut in 0..msgpack.len() {
+            let mut out = Writer::with_alloc_size(16);
+            out.ascii("kept");
+            let err = converter
+                .convert_into(&msgpack[..cut], &mut out)
+                .unwrap_err();
+            assert!(matches!(err, MsgPackError::UnexpectedEof(_)), "cut {cut}");
+            assert_eq!(out.flush(), b"kept");
+        }
+    }
+
+    // The string API keeps upstream's lenient handling of missing values.
+    assert_eq!(converter.convert(&[]).unwrap(), "null");
+    assert_eq!(converter.convert(&[0x92, 0x01]).unwrap(), "[1,null]");
+}
+
+#[test]
+fn msgpack_convert_ndjson_matrix() {
+    let mut encoder = MsgPackEncoderFast::new();
+    let mut converter = MsgPackToJsonConverter::new();
+    let mut stream = Vec::new();
+    let mut expected = String::new();
+    for record in records() {
+        let msgpack = encoder.encode(&record);
+        expected.push_str(&converter.convert(&msgpack).unwrap());
+        expected.push('\n');
+        stream.extend_from_slice(&msgpack);
+    }
+
+    let mut out = Writer::new();
+    assert_eq!(converter.convert_ndjson(&stream, &mut out), stream.len());
+    assert_eq!(String::from_utf8(out.flush()).unwrap(), expected);
+
+    // Fed in chunks, the unconverted tail is carried into the next chunk.
+    for chunk_size in [1, 2, 3, 7, 64, 1000] {
+        let mut out = Writer::with_alloc_size(64);
+        let mut pending = Vec::new();
+        for chunk in stream.chunks(chunk_size) {
+            pending.extend_from_slice(chunk);
+            let n = converter.convert_ndjson(&pending, &mut out);
+            pending.drain(..n);
+        }
+        assert!(pending.is_empty(), "chunk size {chunk_size}");
+        assert_eq!(
+            String::from_utf8(out.flush()).unwrap(),
+            expected,
+            "chunk size {chunk_size}"
+        );
+    }
+
+    assert_eq!(converter.convert_ndjson(&[], &mut out), 0);
+    assert!(out.flush().is_empty());
+}
@@ -0,0 +1,154 @@
+//! `pack!` macro, `PackValue` conversions and the fluent builders.
+//!
+//! Not an upstream port: upstream builds values with plain JS literals.
+
+use json_joy_json_pack::cbor::{decode_cbor_value, CborEncoder};
+use json_joy_json_pack::{
+    pack, JsonPackBigInt, JsonPackExtension, PackArrayBuilder, PackObjectBuilder, PackValue,
+};
+
+fn obj(entries: Vec<(&str, PackValue)>) -> PackValue {
+    PackValue::Object(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+}
+
+#[test]
+fn pack_macro_scalar_matrix() {
+    let big = JsonPackBigInt::from_u128(u128::MAX);
+    let cases = [
+        (pack!(null), PackValue::Null),
+        (pack!(undefined), PackValue::Undefined),
+        (pack!(true), PackValue::Bool(true)),
+        (pack!(-5), PackValue::Integer(-5)),
+        (pack!(200u8), PackValue::Integer(200)),
+        (pack!(u64::MAX), PackValue::UInteger(u64::MAX)),
+        (pack!(1u64 << 62), PackValue::Integer(1 << 62)),
+        (pack!(1.5), PackValue::Float(1.5)),
+        (pack!(0.5f32), PackValue::Float(0.5)),
+        (pack!(-1i128 << 100), PackValue::BigInt(-1 << 100)),
+        (pack!(-7i128), PackValue::Integer(-7)),
+        (pack!(u64::MAX as i128), PackValue::UInteger(u64::MAX)),
+        (pack!(1u128 << 64), PackValue::BigInt(1 << 64)),
+        (pack!(3u128), PackValue::Integer(3)),
+        (pack!(u128::MAX), PackValue::BigNum(big.clone())),
+        (pack!(big.clone()), PackValue::BigNum(big)),
+        (pack!("hi"), PackValue::Str("hi".into())),
+        (pack!(String::from("hi")), PackValue::Str("hi".into())),
+        (pack!('x'), PackValue::Str("x".into())),
+        (pack!(b"\x01\x02"), PackValue::Bytes(vec![1, 2])),
+        (pack!(vec![1u8, 2]), PackValue::Bytes(vec![1, 2])),
+        (pack!(&[3u8][..]), PackValue::Bytes(vec![3])),
+        (pack!(None::<i32>), PackValue::Null),
+        (pack!(Some("x")), PackValue::Str("x".into())),
+        (pack!(()), PackValue::Null),
+        (pack!([]), PackValue::Array(vec![])),
+        (pack!({}), PackValue::Object(vec![])),
+        (
+            pack!(JsonPackExtension::new(1, pack!(null))),
+            PackValue::Extension(Box::new(JsonPackExtension::new(1, PackValue::Null))),
+        ),
+    ];
+    for (i, (actual, expected)) in cases.into_iter().enumerate() {
+        assert_eq!(actual, expected, "case {i}");
+    }
+}
+
+#[test]
+fn pack_macro_nested_matrix() {
+    let key = "dynamic";
+    let name = String::from("Ann");
+    let value = pack!({
+        "name": name,
+        "age": 30 + 1,
+        "tags": ["a", null, undefined, -2, [1, []], {}],
+        "nested": { "deep": { "x": b"\xff" }, "empty": [] },
+        key: true,
+        ("computed".to_string() + "!"): null,
+    });
+    let expected = obj(vec![
+        ("name", PackValue::Str("Ann".into())),
+        ("age", PackValue::Integer(31)),
+        (
+            "tags",
+            PackValue::Array(vec![
+                PackValue::Str("a".into()),
+                PackValue::Null,
+                PackValue::Undefined,
+                PackValue::Integer(-2),
+                PackValue::Array(vec![PackValue::Integer(1), PackValue::Array(vec![])]),
+                PackValue::Object(vec![]),
+            ]),
+        ),
+        (
+            "nested",
+            obj(vec![
+                ("deep", obj(vec![("x", PackValue::Bytes(vec![0xff]))])),
+                ("empty", PackValue::Array(vec![])),
+            ]),
+        ),
+        ("dynamic", PackValue::Bool(true)),
+        ("computed!", PackValue::Null),
+    ]);
+    assert_eq!(value, expected);
+
+    // Interpolated values and trailing commas.
+    let inner = pack!([1, 2,]);
+    assert_eq!(
+        pack!([inner.clone(), { "v": inner }]),
+        PackValue::Array(vec![
+            PackValue::Array(vec![PackValue::Integer(1), PackValue::Integer(2)]),
+            obj(vec![(
+                "v",
+                PackValue::Array(vec![PackValue::Integer(1), PackValue::Integer(2)])
+            )]),
+        ])
+    );
+
+    // Macro-built values round-trip through a codec unchanged.
+    let value = pack!({ "a": [1, "b", null], "c": { "d": false } });
+    assert_eq!(
+        decode_cbor_value(&CborEncoder::new().encode(&value)).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn pack_builder_matrix() {
+    let value = PackObjectBuilder::new()
+        .field("id", 1u32)
+        .field(String::from("name"), "x")
+        .field_opt("skipped", None::<i64>)
+        .field_opt("kept", Some(2.5))
+        .field(
+            "list",
+            PackArrayBuilder::new()
+                .item(true)
+                .items(["a", "b"])
+                .item(PackObjectBuilder::new()),
+        )
+        .build();
+    assert_eq!(
+        value,
+        pack!({
+            "id": 1,
+            "name": "x",
+            "kept": 2.5,
+            "list": [true, "a", "b", {}],
+        })
+    );
+
+    let mut object = PackObjectBuilder::with_capacity(3);
+    let mut array = PackArrayBuilder::new();
+    assert!(object.is_empty() && array.is_empty());
+    for i in 0..3 {
+        object.insert(format!("k{i}"), i);
+        array.push(i);
+    }
+    // Repeated keys are kept, as in decoded objects.
+    object.insert("k0", "again");
+    assert_eq!((object.len(), array.len()), (4, 3));
+    assert_eq!(
+        PackValue::from(object),
+        pack!({ "k0": 0, "k1": 1, "k2": 2, "k0": "again" })
+    );
+    assert_eq!(array.build(), pack!([0, 1, 2]));
+}
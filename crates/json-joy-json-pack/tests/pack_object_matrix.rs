@@ -0,0 +1,91 @@
+//! Keyed access on objects: `PackValue::get` and `PackObject`.
+//!
+//! Not an upstream port. Run with `--features indexed` to cover the hashed
+//! lookups as well as the scanning ones.
+
+use json_joy_json_pack::msgpack::{MsgPackDecoder, MsgPackEncoder};
+use json_joy_json_pack::{pack, PackObject, PackValue};
+
+#[test]
+fn pack_value_get_matrix() {
+    let mut value = pack!({ "a": 1, "b": { "c": [true] }, "a": 2 });
+    assert_eq!(value.get("a"), Some(&PackValue::Integer(2)));
+    assert_eq!(
+        value.get("b").and_then(|b| b.get("c")),
+        Some(&pack!([true]))
+    );
+    assert_eq!(value.get("z"), None);
+    assert_eq!(pack!([1]).get("a"), None);
+    assert_eq!(pack!(null).get("a"), None);
+
+    *value.get_mut("b").unwrap() = pack!("x");
+    assert_eq!(value, pack!({ "a": 1, "b": "x", "a": 2 }));
+    assert_eq!(pack!("s").get_mut("a"), None);
+}
+
+#[test]
+fn pack_object_crud_matrix() {
+    let mut object = PackObject::new();
+    assert!(object.is_empty());
+    assert_eq!(object.insert("a", 1), None);
+    assert_eq!(object.insert("b", "two"), None);
+    assert_eq!(object.insert("c", pack!([])), None);
+    assert_eq!(object.insert("a", 10), Some(PackValue::Integer(1)));
+    assert_eq!(object.len(), 3);
+    assert_eq!(object.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(object.get("a"), Some(&PackValue::Integer(10)));
+    assert!(object.contains_key("b") && !object.contains_key("z"));
+
+    if let Some(PackValue::Array(items)) = object.get_mut("c") {
+        items.push(pack!(null));
+    }
+    assert_eq!(object.remove("b"), Some(pack!("two")));
+    assert_eq!(object.remove("b"), None);
+    // Lookups still resolve after entries shift down.
+    assert_eq!(object.get("c"), Some(&pack!([null])));
+    assert_eq!(object.insert("d", 4), None);
+    assert_eq!(
+        object.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        ["a", "c", "d"]
+    );
+    assert_eq!(
+        PackValue::from(object.clone()),
+        pack!({ "a": 10, "c": [null], "d": 4 })
+    );
+    assert_eq!(object.into_entries().len(), 3);
+}
+
+#[test]
+fn pack_object_duplicate_keys_matrix() {
+    let mut object = PackObject::try_from(pack!({ "k": 1, "x": 0, "k": 2 })).unwrap();
+    assert_eq!(object.get("k"), Some(&PackValue::Integer(2)));
+    // Overwrites the last occurrence in place.
+    assert_eq!(object.insert("k", 3), Some(PackValue::Integer(2)));
+    assert_eq!(
+        object.entries()[2],
+        ("k".to_string(), PackValue::Integer(3))
+    );
+    // Removing drops every occurrence.
+    assert_eq!(object.remove("k"), Some(PackValue::Integer(3)));
+    assert_eq!(PackValue::from(object), pack!({ "x": 0 }));
+
+    assert_eq!(PackObject::try_from(pack!([1])), Err(pack!([1])));
+    let collected: PackObject = [("a", 1), ("b", 2)].into_iter().collect();
+    assert_eq!(
+        collected,
+        PackObject::try_from(pack!({ "a": 1, "b": 2 })).unwrap()
+    );
+}
+
+#[test]
+fn pack_object_large_decoded_matrix() {
+    let object: PackObject = (0..1000).map(|i| (format!("key{i}"), i)).collect();
+    let bytes = MsgPackEncoder::new().encode(&PackValue::from(object));
+    let decoded = MsgPackDecoder::new().decode(&bytes).unwrap();
+    let object = PackObject::try_from(decoded).unwrap();
+    assert_eq!(object.len(), 1000);
+    for i in (0..1000).step_by(37) {
+        assert_eq!(object.get(&format!("key{i}")), Some(&PackValue::Integer(i)));
+    }
+    assert_eq!(object.keys().last(), Some("key999"));
+}
@@ -0,0 +1,121 @@
+//! Path access on nested values: `get_in`, `set_in` and `take_in`.
+//!
+//! Not an upstream port: upstream values are plain JavaScript objects.
+
+use json_joy_json_pack::{pack, PackValue};
+
+fn doc() -> PackValue {
+    pack!({
+        "user": { "name": "ada", "tags": ["a", "b"] },
+        "items": [{ "id": 1 }, { "id": 2 }],
+        "dup": 1,
+        "dup": 2
+    })
+}
+
+#[test]
+fn pack_value_get_in_matrix() {
+    let value = doc();
+    let cases: &[(&[&str], Option<PackValue>)] = &[
+        (&[], Some(doc())),
+        (&["user", "name"], Some(pack!("ada"))),
+        (&["user", "tags", "1"], Some(pack!("b"))),
+        (&["items", "0", "id"], Some(pack!(1))),
+        (&["dup"], Some(pack!(2))),
+        (&["user", "tags", "2"], None),
+        (&["user", "tags", "01"], None),
+        (&["user", "tags", "+1"], None),
+        (&["user", "tags", "-"], None),
+        (&["user", "name", "x"], None),
+        (&["items", "id"], None),
+        (&["missing", "x"], None),
+    ];
+    for (path, expected) in cases {
+        assert_eq!(value.get_in(path), expected.as_ref(), "{path:?}");
+    }
+
+    // Map keys match by their text form.
+    let map = PackValue::Map(vec![
+        (pack!(1), pack!("one")),
+        (pack!(true), pack!({ "x": [0] })),
+    ]);
+    assert_eq!(map.get_in(&["1"]), Some(&pack!("one")));
+    assert_eq!(map.get_in(&["true", "x", "0"]), Some(&pack!(0)));
+    assert_eq!(map.get_in(&["2"]), None);
+
+    let mut value = doc();
+    *value.get_in_mut(&["items", "1", "id"]).unwrap() = pack!(20);
+    assert_eq!(value.get_in(&["items", "1", "id"]), Some(&pack!(20)));
+}
+
+#[test]
+fn pack_value_set_in_matrix() {
+    let mut value = doc();
+    assert_eq!(
+        value.set_in(&["user", "name"], "grace"),
+        Ok(Some(pack!("ada")))
+    );
+    assert_eq!(value.set_in(&["user", "age"], 36), Ok(None));
+    assert_eq!(
+        value.set_in(&["user", "tags", "0"], "z"),
+        Ok(Some(pack!("a")))
+    );
+    assert_eq!(value.set_in(&["user", "tags", "2"], "c"), Ok(None));
+    assert_eq!(value.set_in(&["user", "tags", "-"], "d"), Ok(None));
+    assert_eq!(value.set_in(&["dup"], 3), Ok(Some(pack!(2))));
+    assert_eq!(
+        value.get("user"),
+        Some(&pack!({ "name": "grace", "tags": ["z", "b", "c", "d"], "age": 36 }))
+    );
+    assert_eq!(value.get_in(&["dup"]), Some(&pack!(3)));
+
+    // The parent must exist and hold the segment.
+    let errors: &[&[&str]] = &[
+        &["missing", "x"],
+        &["user", "tags", "9"],
+        &["user", "tags", "x"],
+        &["user", "name", "x"],
+    ];
+    for path in errors {
+        assert_eq!(value.set_in(path, 1), Err(pack!(1)), "{path:?}");
+    }
+
+    let mut map = PackValue::Map(vec![(pack!(1), pack!("one"))]);
+    assert_eq!(map.set_in(&["1"], "uno"), Ok(Some(pack!("one"))));
+    assert_eq!(map.set_in(&["2"], "dos"), Ok(None));
+    assert_eq!(
+        map,
+        PackValue::Map(vec![(pack!(1), pack!("uno")), (pack!("2"), pack!("dos"))])
+    );
+
+    let mut scalar = pack!(1);
+    assert_eq!(scalar.set_in(&[], "x"), Ok(Some(pack!(1))));
+    assert_eq!(scalar, pack!("x"));
+}
+
+#[test]
+fn pack_value_take_in_matrix() {
+    let mut value = doc();
+    assert_eq!(value.take_in(&["user", "tags", "0"]), Some(pack!("a")));
+    assert_eq!(value.get_in(&["user", "tags"]), Some(&pack!(["b"])));
+    assert_eq!(value.take_in(&["items", "0"]), Some(pack!({ "id": 1 })));
+    assert_eq!(value.get_in(&["items", "0", "id"]), Some(&pack!(2)));
+    // Every repeated key goes; the value `get` saw is returned.
+    assert_eq!(value.take_in(&["dup"]), Some(pack!(2)));
+    assert_eq!(value.get("dup"), None);
+
+    let misses: &[&[&str]] = &[&["dup"], &["items", "5"], &["user", "name", "x"]];
+    for path in misses {
+        assert_eq!(value.take_in(path), None, "{path:?}");
+    }
+
+    let mut map = PackValue::Map(vec![(pack!(1), pack!("a")), (pack!("1"), pack!("b"))]);
+    assert_eq!(map.take_in(&["1"]), Some(pack!("b")));
+    assert_eq!(map, PackValue::Map(vec![]));
+
+    assert_eq!(
+        value.take_in(&[]),
+        Some(pack!({ "user": { "name": "ada", "tags": ["b"] }, "items": [{ "id": 2 }] }))
+    );
+    assert_eq!(value, PackValue::Null);
+}
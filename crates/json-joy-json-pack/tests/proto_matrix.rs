@@ -0,0 +1,150 @@
+//! Protobuf wire-format primitives and the shared varint/zigzag helpers.
+//!
+//! Not an upstream port: upstream json-pack has no protobuf support.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoder};
+use json_joy_json_pack::proto::{ProtoDecoder, ProtoEncoder, ProtoError, ProtoValue, WireType};
+use json_joy_json_pack::util::varint::{
+    read_varint, zigzag_decode32, zigzag_decode64, zigzag_encode32, zigzag_encode64, VarintError,
+};
+use json_joy_json_pack::PackValue;
+
+#[test]
+fn varint_and_zigzag_matrix() {
+    for (n, z) in [
+        (0, 0),
+        (-1, 1),
+        (1, 2),
+        (-2, 3),
+        (i32::MAX, u32::MAX - 1),
+        (i32::MIN, u32::MAX),
+    ] {
+        assert_eq!(zigzag_encode32(n), z);
+        assert_eq!(zigzag_decode32(z), n);
+    }
+    for n in [0, -1, 1, i64::MAX, i64::MIN] {
+        assert_eq!(zigzag_decode64(zigzag_encode64(n)), n);
+    }
+    assert_eq!(zigzag_encode64(i64::MIN), u64::MAX);
+
+    assert_eq!(read_varint(&[0x96, 0x01], 0, 10), Ok((150, 2)));
+    assert_eq!(read_varint(&[0x00, 0xac, 0x02], 1, 10), Ok((300, 2)));
+    assert_eq!(
+        read_varint(&[0x80, 0x80], 0, 10),
+        Err(VarintError::UnexpectedEof)
+    );
+    assert_eq!(read_varint(&[0xff; 6], 0, 5), Err(VarintError::TooLong));
+}
+
+#[test]
+fn proto_encoder_wire_matrix() {
+    let mut enc = ProtoEncoder::new();
+    enc.write_varint_field(1, 150);
+    assert_eq!(enc.flush(), [0x08, 0x96, 0x01]);
+    enc.write_str_field(2, "testing");
+    assert_eq!(
+        enc.flush(),
+        [0x12, 0x07, b't', b'e', b's', b't', b'i', b'n', b'g']
+    );
+    enc.write_sint32_field(3, -1);
+    enc.write_sint64_field(3, -2);
+    assert_eq!(enc.flush(), [0x18, 0x01, 0x18, 0x03]);
+    enc.write_int64_field(1, -1);
+    assert_eq!(
+        enc.flush(),
+        [0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+    );
+    enc.write_fixed32_field(4, 1);
+    enc.write_double_field(5, 1.0);
+    assert_eq!(
+        enc.flush(),
+        [0x25, 1, 0, 0, 0, 0x29, 0, 0, 0, 0, 0, 0, 0xf0, 0x3f]
+    );
+    enc.write_bool_field(16, true);
+    assert_eq!(enc.flush(), [0x80, 0x01, 0x01]);
+}
+
+#[test]
+fn proto_decoder_envelope_roundtrip() {
+    // An envelope wrapping a CBOR payload in field 3, with a nested header
+    // message in field 1 and a repeated packed field 4.
+    let payload = CborEncoder::new().encode(&PackValue::Str("hello".into()));
+    let mut header = ProtoEncoder::new();
+    header.write_str_field(1, "application/cbor");
+    header.write_sint64_field(2, -42);
+    let header = header.flush();
+    let mut packed = ProtoEncoder::new();
+    for n in [1, 300, 70000] {
+        packed.write_varint(n);
+    }
+    let packed = packed.flush();
+
+    let mut enc = ProtoEncoder::new();
+    enc.write_bytes_field(1, &header);
+    enc.write_fixed64_field(2, 0x0102030405060708);
+    enc.write_bytes_field(3, &payload);
+    enc.write_bytes_field(4, &packed);
+    enc.write_float_field(5, 0.5);
+    let bytes = enc.flush();
+
+    let fields: Vec<_> = ProtoDecoder::new(&bytes).collect::<Result<_, _>>().unwrap();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(fields[0].number, 1);
+    let ProtoValue::LengthDelimited(header) = fields[0].value else {
+        panic!("expected header bytes");
+    };
+    let mut dec = ProtoDecoder::new(header);
+    assert_eq!(dec.read_tag(), Ok((1, WireType::LengthDelimited)));
+    assert_eq!(dec.read_str(), Ok("application/cbor"));
+    assert_eq!(dec.read_tag(), Ok((2, WireType::Varint)));
+    assert_eq!(dec.read_sint64(), Ok(-42));
+    assert!(dec.is_empty());
+
+    assert_eq!(fields[1].value, ProtoValue::Fixed64(0x0102030405060708));
+    let ProtoValue::LengthDelimited(cbor) = fields[2].value else {
+        panic!("expected payload bytes");
+    };
+    assert_eq!(
+        CborDecoder::new().decode(cbor).unwrap(),
+        PackValue::Str("hello".into())
+    );
+    let ProtoValue::LengthDelimited(packed) = fields[3].value else {
+        panic!("expected packed bytes");
+    };
+    let mut dec = ProtoDecoder::new(packed);
+    let mut values = Vec::new();
+    while !dec.is_empty() {
+        values.push(dec.read_varint().unwrap());
+    }
+    assert_eq!(values, [1, 300, 70000]);
+    assert_eq!(fields[4].value, ProtoValue::Fixed32(0.5f32.to_bits()));
+}
+
+#[test]
+fn proto_decoder_error_matrix() {
+    let cases: [(&[u8], ProtoError); 6] = [
+        (&[0x08], ProtoError::UnexpectedEof(1)),
+        (&[0x12, 0x05, b'a'], ProtoError::UnexpectedEof(2)),
+        (&[0x0d, 1, 2], ProtoError::UnexpectedEof(1)),
+        (&[0x0e], ProtoError::InvalidWireType(6, 0)),
+        (&[0x08, 0x01, 0x00], ProtoError::InvalidFieldNumber(2)),
+        (
+            &[
+                0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+            ],
+            ProtoError::VarintTooLong(1),
+        ),
+    ];
+    for (input, expected) in cases {
+        let result: Result<Vec<_>, _> = ProtoDecoder::new(input).collect();
+        assert_eq!(result.unwrap_err(), expected, "{input:?}");
+    }
+
+    let mut dec = ProtoDecoder::new(&[0x02, 0xc3, 0x28]);
+    assert_eq!(dec.read_str(), Err(ProtoError::InvalidUtf8(1)));
+
+    // Iteration stops after the first error.
+    let mut iter = ProtoDecoder::new(&[0x0e, 0x08, 0x01]);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+}
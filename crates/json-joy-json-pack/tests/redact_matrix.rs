@@ -0,0 +1,189 @@
+//! Redaction of JSON Pointer paths from encoded payloads.
+//!
+//! Not an upstream port. Each case is checked against decoding, editing the
+//! `PackValue` tree and re-encoding.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoder};
+use json_joy_json_pack::codecs::CodecError;
+use json_joy_json_pack::json::{JsonDecoder, JsonEncoder};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoder};
+use json_joy_json_pack::transform::{redact, redact_with, Redaction};
+use json_joy_json_pack::{pack, EncodingFormat, JsonPackExtension, PackValue};
+
+const FORMATS: [EncodingFormat; 3] = [
+    EncodingFormat::Cbor,
+    EncodingFormat::MsgPack,
+    EncodingFormat::Json,
+];
+
+fn encode(format: EncodingFormat, value: &PackValue) -> Vec<u8> {
+    match format {
+        EncodingFormat::Cbor => CborEncoder::new().encode(value),
+        EncodingFormat::MsgPack => MsgPackEncoder::new().encode(value),
+        EncodingFormat::Json => JsonEncoder::new().encode(value),
+        other => unreachable!("{other:?}"),
+    }
+}
+
+fn decode(format: EncodingFormat, bytes: &[u8]) -> PackValue {
+    match format {
+        EncodingFormat::Cbor => CborDecoder::new().decode(bytes).unwrap(),
+        EncodingFormat::MsgPack => MsgPackDecoderFast::new().decode(bytes).unwrap(),
+        EncodingFormat::Json => JsonDecoder::new().decode(bytes).unwrap(),
+        other => unreachable!("{other:?}"),
+    }
+}
+
+fn document() -> PackValue {
+    pack!({
+        "id": 7,
+        "user": { "name": "ann", "email": "a@example.com", "cards": ["4111", "5500", "3400"] },
+        "events": [
+            { "ip": "10.0.0.1", "ok": true },
+            { "ip": "10.0.0.2", "ok": false, "tags": [1, 2] }
+        ],
+        "blob": b"\x01\x02",
+        "tagged": JsonPackExtension::new(9, pack!({ "ssn": "123", "k": "v" })),
+        "a/b": { "~": 1, "keep": 2 },
+        "": null
+    })
+}
+
+#[test]
+fn redact_remove_matrix() {
+    let cases: [(&[&str], PackValue); 8] = [
+        (&[], document()),
+        (&["/nope", "/user/zip", "/events/5"], document()),
+        (&["/id"], {
+            let mut v = document();
+            remove(&mut v, "id");
+            v
+        }),
+        (&["/user/email", "/user/cards/1"], {
+            let mut v = document();
+            let user = v.get_mut("user").unwrap();
+            remove(user, "email");
+            let PackValue::Array(cards) = user.get_mut("cards").unwrap() else {
+                unreachable!()
+            };
+            cards.remove(1);
+            v
+        }),
+        // Indices address the input, whatever else is removed.
+        (&["/events/0", "/events/1/ip", "events/1/tags/0"], {
+            let mut v = document();
+            let PackValue::Array(events) = v.get_mut("events").unwrap() else {
+                unreachable!()
+            };
+            events.remove(0);
+            remove(&mut events[0], "ip");
+            let PackValue::Array(tags) = events[0].get_mut("tags").unwrap() else {
+                unreachable!()
+            };
+            tags.remove(0);
+            v
+        }),
+        (&["/a~1b/~0"], {
+            let mut v = document();
+            remove(v.get_mut("a/b").unwrap(), "~");
+            v
+        }),
+        (&["/user", "/user/name", "/blob", "/"], {
+            let mut v = document();
+            remove(&mut v, "user");
+            remove(&mut v, "blob");
+            remove(&mut v, "");
+            v
+        }),
+        (&[""], pack!(null)),
+    ];
+    for format in FORMATS {
+        let bytes = encode(format, &document());
+        for (pointers, expected) in &cases {
+            let out = redact(&bytes, format, pointers).unwrap();
+            assert_eq!(out, encode(format, expected), "{format:?} {pointers:?}");
+            decode(format, &out);
+        }
+    }
+}
+
+#[test]
+fn redact_tagged_matrix() {
+    // A tag stays with its value; pointers see through it.
+    let expected = |ext: PackValue| {
+        let mut v = document();
+        *v.get_mut("tagged").unwrap() = ext;
+        v
+    };
+    for format in [EncodingFormat::Cbor, EncodingFormat::MsgPack] {
+        let bytes = encode(format, &document());
+        assert_eq!(
+            redact(&bytes, format, &["/tagged/ssn"]).unwrap(),
+            encode(
+                format,
+                &expected(pack!(JsonPackExtension::new(9, pack!({ "k": "v" }))))
+            ),
+        );
+        let mut v = document();
+        remove(&mut v, "tagged");
+        assert_eq!(
+            redact(&bytes, format, &["/tagged"]).unwrap(),
+            encode(format, &v)
+        );
+    }
+}
+
+#[test]
+fn redact_mask_matrix() {
+    let mask = Redaction::Mask(pack!("***"));
+    let mut expected = document();
+    *expected.get_mut("id").unwrap() = pack!("***");
+    *expected.get_mut("user").unwrap().get_mut("name").unwrap() = pack!("***");
+    let PackValue::Array(events) = expected.get_mut("events").unwrap() else {
+        unreachable!()
+    };
+    events[1] = pack!("***");
+    *expected.get_mut("tagged").unwrap() = pack!("***");
+    for format in FORMATS {
+        let bytes = encode(format, &document());
+        let out = redact_with(
+            &bytes,
+            format,
+            &["/id", "/user/name", "/events/1", "/tagged", "/events/1/ip"],
+            &mask,
+        )
+        .unwrap();
+        assert_eq!(out, encode(format, &expected), "{format:?}");
+        assert_eq!(
+            redact_with(&bytes, format, &[""], &mask).unwrap(),
+            encode(format, &pack!("***"))
+        );
+    }
+}
+
+#[test]
+fn redact_error_matrix() {
+    assert!(matches!(
+        redact(&[0x82, 0x01], EncodingFormat::Cbor, &["/0"]),
+        Err(CodecError::Cbor(_))
+    ));
+    assert!(matches!(
+        redact(&[0x92, 0x01], EncodingFormat::MsgPack, &["/0"]),
+        Err(CodecError::MsgPack(_))
+    ));
+    assert!(matches!(
+        redact(b"[1,", EncodingFormat::Json, &["/0"]),
+        Err(CodecError::Json(_))
+    ));
+    assert!(matches!(
+        redact(b"+OK\r\n", EncodingFormat::Resp, &["/0"]),
+        Err(CodecError::UnsupportedFormat(EncodingFormat::Resp))
+    ));
+}
+
+fn remove(value: &mut PackValue, key: &str) {
+    let PackValue::Object(entries) = value else {
+        unreachable!()
+    };
+    entries.retain(|(k, _)| k != key);
+}
@@ -0,0 +1,82 @@
+//! RESP aggregates written from iterators, with framing chosen from the
+//! iterator's size hint.
+//!
+//! Not an upstream port: upstream only writes aggregates from slices or via
+//! explicit start/end calls.
+
+use json_joy_json_pack::resp::{RespDecoder, RespEncoder, RespValue};
+use json_joy_json_pack::PackValue;
+
+fn items() -> Vec<PackValue> {
+    vec![
+        PackValue::Integer(1),
+        PackValue::Str("a".into()),
+        PackValue::Null,
+    ]
+}
+
+#[test]
+fn resp_iter_exact_size_matrix() {
+    let items = items();
+    let mut encoder = RespEncoder::new();
+
+    encoder.write_arr_from_iter(items.iter());
+    let bytes = encoder.writer.flush();
+    assert_eq!(bytes, b"*3\r\n:1\r\n+a\r\n_\r\n");
+    assert_eq!(
+        bytes,
+        RespEncoder::new().encode(&PackValue::Array(items.clone()))
+    );
+
+    encoder.write_set_from_iter(items.iter().skip(1));
+    assert_eq!(encoder.writer.flush(), b"~2\r\n+a\r\n_\r\n");
+
+    let fields = [("k".to_string(), PackValue::Bool(true))];
+    encoder.write_obj_from_iter(fields.iter().map(|(k, v)| (k.as_str(), v)));
+    let bytes = encoder.writer.flush();
+    assert_eq!(bytes, b"%1\r\n+k\r\n#t\r\n");
+    assert_eq!(
+        RespDecoder::new().decode_typed(&bytes).unwrap(),
+        RespValue::Map(vec![(
+            RespValue::SimpleStr("k".into()),
+            RespValue::Bool(true)
+        )])
+    );
+
+    encoder.write_arr_from_iter(std::iter::empty());
+    assert_eq!(encoder.writer.flush(), b"*0\r\n");
+}
+
+#[test]
+fn resp_iter_unknown_size_matrix() {
+    let items = items();
+    let mut encoder = RespEncoder::new();
+
+    encoder.write_arr_from_iter(items.iter().filter(|v| !matches!(v, PackValue::Null)));
+    assert_eq!(encoder.writer.flush(), b"*?\r\n:1\r\n+a\r\n.\r\n");
+
+    // Same bytes as the manual start/chunk/end sequence.
+    encoder.write_start_arr();
+    encoder.write_arr_chunk(&items[0]);
+    encoder.write_arr_chunk(&items[1]);
+    encoder.write_end_arr();
+    assert_eq!(encoder.writer.flush(), b"*?\r\n:1\r\n+a\r\n.\r\n");
+
+    encoder.write_set_from_iter(items.iter().take_while(|_| true));
+    assert_eq!(encoder.writer.flush(), b"~?\r\n:1\r\n+a\r\n_\r\n.\r\n");
+
+    let fields = [
+        ("a".to_string(), PackValue::Integer(1)),
+        ("b".to_string(), PackValue::Integer(2)),
+    ];
+    encoder.write_obj_from_iter(
+        fields
+            .iter()
+            .filter(|(k, _)| k != "a")
+            .map(|(k, v)| (k.as_str(), v)),
+    );
+    assert_eq!(encoder.writer.flush(), b"%?\r\n+b\r\n:2\r\n.\r\n");
+
+    encoder.write_arr_from_iter(items.iter().filter(|_| false));
+    assert_eq!(encoder.writer.flush(), b"*?\r\n.\r\n");
+}
@@ -0,0 +1,126 @@
+//! Resynchronising a RESP stream after malformed input.
+//!
+//! Not an upstream port: upstream fails on malformed input.
+
+use json_joy_json_pack::resp::{RespDecodeError, RespStreamEvent, RespStreamingDecoder};
+use json_joy_json_pack::{pack, PackValue};
+
+use RespStreamEvent::{Skipped, Value};
+
+fn recovering() -> RespStreamingDecoder {
+    let mut decoder = RespStreamingDecoder::new();
+    decoder.set_recover(true);
+    decoder
+}
+
+fn events(decoder: &mut RespStreamingDecoder) -> Vec<RespStreamEvent> {
+    let mut out = Vec::new();
+    while let Some(event) = decoder.read_event().unwrap() {
+        out.push(event);
+    }
+    out
+}
+
+fn skipped(bytes: &[u8], error: RespDecodeError) -> RespStreamEvent {
+    Skipped {
+        bytes: bytes.to_vec(),
+        error,
+    }
+}
+
+#[test]
+fn resp_recovery_event_matrix() {
+    let cases: &[(&[u8], Vec<RespStreamEvent>)] = &[
+        (
+            b"+OK\r\n?garbage\r\n:1\r\n",
+            vec![
+                Value(pack!("OK")),
+                skipped(b"?garbage\r\n", RespDecodeError::UnknownType(b'?', 0)),
+                Value(pack!(1)),
+            ],
+        ),
+        (
+            b"(12x\r\n+OK\r\n",
+            vec![
+                skipped(b"(12x\r\n", RespDecodeError::InvalidUtf8(1)),
+                Value(pack!("OK")),
+            ],
+        ),
+        // The boundary is searched past the error, not inside the broken
+        // aggregate.
+        (
+            b"*2\r\n:1\r\n(x\r\n+OK\r\n",
+            vec![
+                skipped(b"*2\r\n:1\r\n(x\r\n", RespDecodeError::InvalidUtf8(9)),
+                Value(pack!("OK")),
+            ],
+        ),
+        // `\r\n` followed by a byte no value starts with is not a boundary.
+        (
+            b"@a\r\nb\r\n_\r\n",
+            vec![
+                skipped(b"@a\r\nb\r\n", RespDecodeError::UnknownType(b'@', 0)),
+                Value(PackValue::Null),
+            ],
+        ),
+    ];
+    for (bytes, expected) in cases {
+        let mut decoder = recovering();
+        decoder.push(bytes);
+        assert_eq!(
+            &events(&mut decoder),
+            expected,
+            "{:?}",
+            bytes.escape_ascii()
+        );
+    }
+}
+
+#[test]
+fn resp_recovery_chunked_matrix() {
+    // Garbage split across chunks is reported as it arrives; a trailing
+    // `\r` is kept in case `\n` follows.
+    let mut decoder = recovering();
+    decoder.push(b"?x\r");
+    assert_eq!(
+        events(&mut decoder),
+        [skipped(b"?x", RespDecodeError::UnknownType(b'?', 0))]
+    );
+    decoder.push(b"\n:7");
+    assert_eq!(
+        events(&mut decoder),
+        [skipped(b"\r\n", RespDecodeError::UnknownType(b'\r', 0))]
+    );
+    decoder.push(b"\r\n");
+    assert_eq!(events(&mut decoder), [Value(pack!(7))]);
+    assert_eq!(decoder.skipped(), 4);
+
+    // An open streamed aggregate is abandoned.
+    let mut decoder = recovering();
+    decoder.push(b"*?\r\n:1\r\n?x\r\n+OK\r\n");
+    let out = events(&mut decoder);
+    assert_eq!(
+        out[..2],
+        [RespStreamEvent::AggregateStart(b'*'), Value(pack!(1))]
+    );
+    assert!(matches!(&out[2], Skipped { bytes, .. } if bytes == b"?x\r\n"));
+    assert_eq!(out[3..], [Value(pack!("OK"))]);
+    decoder.push(b".\r\n");
+    assert!(matches!(
+        decoder.read_event().unwrap(),
+        Some(Skipped { .. })
+    ));
+}
+
+#[test]
+fn resp_recovery_manual_matrix() {
+    // Without recovery mode errors are returned; `resync` skips past them.
+    let mut decoder = RespStreamingDecoder::new();
+    assert!(!decoder.recover());
+    decoder.push(b"?bad\r\n+OK\r\n");
+    assert_eq!(decoder.read(), Err(RespDecodeError::UnknownType(b'?', 0)));
+    assert_eq!(decoder.resync(), b"?bad\r\n");
+    assert_eq!(decoder.read(), Ok(Some(pack!("OK"))));
+    assert_eq!(decoder.resync(), b"");
+    assert_eq!(decoder.skipped(), 6);
+}
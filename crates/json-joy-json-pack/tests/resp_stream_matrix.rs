@@ -0,0 +1,170 @@
+//! Decoding streamed RESP3 strings (`$?` with `;len` chunks) and streamed
+//! aggregates (`*?` ... `.`), whole and as incremental events.
+//!
+//! Not an upstream port: upstream encodes streamed values but cannot decode
+//! them.
+
+use json_joy_json_pack::resp::{
+    Resp, RespDecodeError, RespDecoder, RespEncoder, RespStreamEvent, RespStreamingDecoder,
+    RespValue,
+};
+use json_joy_json_pack::{pack, PackValue};
+
+/// `[ "hello world", {"k": [1, 2]}, 3 ]` with every string and aggregate
+/// streamed.
+fn streamed() -> Vec<u8> {
+    let mut encoder = RespEncoder::new();
+    encoder.write_start_arr();
+    encoder.write_start_str();
+    encoder.write_str_chunk("hello ");
+    encoder.write_str_chunk("world");
+    encoder.write_end_str();
+    encoder.write_start_obj();
+    encoder.write_obj_chunk("k", &pack!([1, 2]));
+    encoder.write_end_obj();
+    encoder.write_arr_chunk(&PackValue::Integer(3));
+    encoder.write_end_arr();
+    encoder.writer.flush()
+}
+
+#[test]
+fn resp_streamed_decode_matrix() {
+    let bytes = streamed();
+    let mut decoder = RespDecoder::new();
+    decoder.try_utf8 = true;
+    assert_eq!(
+        decoder.decode(&bytes).unwrap(),
+        pack!(["hello world", {"k": [1, 2]}, 3])
+    );
+    assert_eq!(decoder.position(), bytes.len());
+
+    assert_eq!(
+        decoder.decode_typed(&bytes).unwrap(),
+        RespValue::Array(vec![
+            RespValue::BulkStr(b"hello world".to_vec()),
+            RespValue::Map(vec![(
+                RespValue::SimpleStr("k".into()),
+                RespValue::Array(vec![RespValue::Int(1), RespValue::Int(2)]),
+            )]),
+            RespValue::Int(3),
+        ])
+    );
+
+    decoder.reset(&bytes);
+    decoder.skip_any().unwrap();
+    assert_eq!(decoder.position(), bytes.len());
+
+    let cases: &[(&[u8], PackValue)] = &[
+        (b"$?\r\n;0\r\n", PackValue::Bytes(vec![])),
+        (b"*?\r\n.\r\n", PackValue::Array(vec![])),
+        (b"~?\r\n:1\r\n*?\r\n.\r\n.\r\n", pack!([1, []])),
+        (b">?\r\n:1\r\n.\r\n", {
+            let mut d = RespDecoder::new();
+            d.decode(b">1\r\n:1\r\n").unwrap()
+        }),
+    ];
+    let mut decoder = RespDecoder::new();
+    for (input, expected) in cases {
+        assert_eq!(decoder.decode(input).unwrap(), *expected);
+    }
+
+    // Cut-off input reports end of input; a bad chunk marker is an error.
+    for cut in 0..bytes.len() {
+        assert!(
+            matches!(
+                decoder.decode(&bytes[..cut]),
+                Err(RespDecodeError::EndOfInput(_))
+            ),
+            "cut {cut}"
+        );
+    }
+    assert_eq!(
+        decoder.decode(b"$?\r\n:3\r\nabc\r\n;0\r\n"),
+        Err(RespDecodeError::InvalidChunk(4))
+    );
+
+    // A huge count inside a streamed aggregate reserves no more room than
+    // the input can fill.
+    for marker in ["*", "~", ">", "%"] {
+        let input = format!("*?\r\n{marker}999999999999999\r\n:1\r\n");
+        assert!(
+            matches!(
+                decoder.decode(input.as_bytes()),
+                Err(RespDecodeError::EndOfInput(_))
+            ),
+            "{marker}"
+        );
+        assert!(
+            matches!(
+                decoder.decode_typed(input.as_bytes()),
+                Err(RespDecodeError::EndOfInput(_))
+            ),
+            "{marker}"
+        );
+    }
+}
+
+fn expected_events() -> Vec<RespStreamEvent> {
+    vec![
+        RespStreamEvent::AggregateStart(Resp::ARR),
+        RespStreamEvent::StrStart,
+        RespStreamEvent::StrChunk(b"hello ".to_vec()),
+        RespStreamEvent::StrChunk(b"world".to_vec()),
+        RespStreamEvent::StrEnd,
+        RespStreamEvent::AggregateStart(Resp::OBJ),
+        RespStreamEvent::Value(pack!("k")),
+        RespStreamEvent::Value(pack!([1, 2])),
+        RespStreamEvent::AggregateEnd,
+        RespStreamEvent::Value(pack!(3)),
+        RespStreamEvent::AggregateEnd,
+        RespStreamEvent::Value(pack!("after")),
+    ]
+}
+
+#[test]
+fn resp_stream_events_matrix() {
+    let mut bytes = streamed();
+    bytes.extend_from_slice(b"+after\r\n");
+
+    for chunk_size in [1, 2, 3, 5, 1000] {
+        let mut decoder = RespStreamingDecoder::new();
+        let mut events = Vec::new();
+        for chunk in bytes.chunks(chunk_size) {
+            decoder.push(chunk);
+            while let Some(event) = decoder.read_event().unwrap() {
+                events.push(event);
+            }
+        }
+        assert_eq!(events, expected_events(), "chunk size {chunk_size}");
+    }
+
+    // Chunks surface before the string ends.
+    let mut decoder = RespStreamingDecoder::new();
+    decoder.push(b"$?\r\n;3\r\nabc\r\n;2\r\nd");
+    assert_eq!(
+        decoder.read_event().unwrap(),
+        Some(RespStreamEvent::StrStart)
+    );
+    assert_eq!(
+        decoder.read_event().unwrap(),
+        Some(RespStreamEvent::StrChunk(b"abc".to_vec()))
+    );
+    assert_eq!(decoder.read_event().unwrap(), None);
+    decoder.push(b"e\r\n;0\r\n");
+    assert_eq!(
+        decoder.read_event().unwrap(),
+        Some(RespStreamEvent::StrChunk(b"de".to_vec()))
+    );
+    assert_eq!(decoder.read_event().unwrap(), Some(RespStreamEvent::StrEnd));
+    assert_eq!(decoder.read_event().unwrap(), None);
+
+    // `read` still returns streamed values whole.
+    let mut decoder = RespStreamingDecoder::new();
+    decoder.set_try_utf8(true);
+    decoder.push(&bytes[..bytes.len() - 1]);
+    assert_eq!(
+        decoder.read().unwrap(),
+        Some(pack!(["hello world", {"k": [1, 2]}, 3]))
+    );
+    assert_eq!(decoder.read().unwrap(), None);
+}
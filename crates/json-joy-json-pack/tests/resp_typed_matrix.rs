@@ -0,0 +1,192 @@
+//! Typed RESP3 decoding that preserves sets, maps, verbatim formats, errors,
+//! pushes, attributes and big numbers.
+//!
+//! Not an upstream port: upstream decodes RESP3 into plain JSON values only.
+
+use json_joy_json_pack::resp::{
+    RespDecodeError, RespDecoder, RespStreamingDecoder, RespValue, RESP_EXTENSION_PUSH,
+};
+use json_joy_json_pack::{JsonPackExtension, PackValue};
+
+fn s(text: &str) -> RespValue {
+    RespValue::SimpleStr(text.into())
+}
+
+fn bulk(text: &str) -> RespValue {
+    RespValue::BulkStr(text.as_bytes().to_vec())
+}
+
+#[test]
+fn resp_typed_scalar_matrix() {
+    let cases: [(&[u8], RespValue); 14] = [
+        (b"_\r\n", RespValue::Null),
+        (b"$-1\r\n", RespValue::Null),
+        (b"*-1\r\n", RespValue::Null),
+        (b"#t\r\n", RespValue::Bool(true)),
+        (b":-42\r\n", RespValue::Int(-42)),
+        (b",1.5\r\n", RespValue::Float(1.5)),
+        (b",-inf\r\n", RespValue::Float(f64::NEG_INFINITY)),
+        (
+            b"(3492890328409238509324850943850943825024385\r\n",
+            RespValue::BigNumber(
+                "3492890328409238509324850943850943825024385"
+                    .parse()
+                    .unwrap(),
+            ),
+        ),
+        (b"+OK\r\n", s("OK")),
+        (b"$5\r\nhe\r\no\r\n", bulk("he\r\no")),
+        (b"$0\r\n\r\n", bulk("")),
+        (
+            b"=15\r\nmkd:Some string\r\n",
+            RespValue::Verbatim {
+                format: "mkd".into(),
+                text: "Some string".into(),
+            },
+        ),
+        (
+            b"-ERR unknown command\r\n",
+            RespValue::SimpleError("ERR unknown command".into()),
+        ),
+        (
+            b"!21\r\nSYNTAX invalid syntax\r\n",
+            RespValue::BulkError("SYNTAX invalid syntax".into()),
+        ),
+    ];
+    let mut decoder = RespDecoder::new();
+    for (bytes, expected) in cases {
+        assert_eq!(
+            decoder.decode_typed(bytes).unwrap(),
+            expected,
+            "{}",
+            String::from_utf8_lossy(bytes)
+        );
+        assert_eq!(decoder.position(), bytes.len());
+    }
+}
+
+#[test]
+fn resp_typed_aggregate_matrix() {
+    let mut decoder = RespDecoder::new();
+
+    // Sets stay sets; map keys keep their types.
+    assert_eq!(
+        decoder
+            .decode_typed(b"%2\r\n:1\r\n~2\r\n+a\r\n#f\r\n$1\r\nk\r\n*0\r\n")
+            .unwrap(),
+        RespValue::Map(vec![
+            (
+                RespValue::Int(1),
+                RespValue::Set(vec![s("a"), RespValue::Bool(false)])
+            ),
+            (bulk("k"), RespValue::Array(vec![])),
+        ])
+    );
+
+    assert_eq!(
+        decoder
+            .decode_typed(b">3\r\n+message\r\n+chan\r\n$2\r\nhi\r\n")
+            .unwrap(),
+        RespValue::Push(vec![s("message"), s("chan"), bulk("hi")])
+    );
+
+    // Attributes wrap the reply that follows them.
+    let attributed = b"|1\r\n+ttl\r\n:3600\r\n*2\r\n:1\r\n:2\r\n";
+    let value = decoder.decode_typed(attributed).unwrap();
+    assert_eq!(
+        value,
+        RespValue::Attr {
+            attributes: vec![(s("ttl"), RespValue::Int(3600))],
+            value: Box::new(RespValue::Array(vec![RespValue::Int(1), RespValue::Int(2)])),
+        }
+    );
+    assert_eq!(decoder.position(), attributed.len());
+}
+
+#[test]
+fn resp_typed_to_pack_value_matrix() {
+    let bytes = b"*6\r\n~1\r\n+a\r\n=7\r\ntxt:abc\r\n=7\r\nmkd:abc\r\n-ERR x\r\n>1\r\n:1\r\n%1\r\n:7\r\n$1\r\nv\r\n";
+    let typed = RespDecoder::new().decode_typed(bytes).unwrap();
+    let expected = PackValue::Array(vec![
+        PackValue::Array(vec![PackValue::Str("a".into())]),
+        PackValue::Str("abc".into()),
+        PackValue::Bytes(b"abc".to_vec()),
+        PackValue::Str("ERR x".into()),
+        PackValue::Extension(Box::new(JsonPackExtension::new(
+            RESP_EXTENSION_PUSH,
+            PackValue::Array(vec![PackValue::Integer(1)]),
+        ))),
+        PackValue::Object(vec![("Integer(7)".into(), PackValue::Bytes(b"v".to_vec()))]),
+    ]);
+    assert_eq!(PackValue::from(typed), expected);
+    // Same as the untyped decoder.
+    assert_eq!(RespDecoder::new().decode(bytes).unwrap(), expected);
+
+    assert_eq!(
+        PackValue::from(RespValue::BigNumber("-12".parse().unwrap())),
+        PackValue::BigInt(-12)
+    );
+    assert!(RespValue::BulkError("E".into()).is_error());
+    assert_eq!(bulk("x").as_str(), Some("x"));
+    assert_eq!(RespValue::Int(1).as_str(), None);
+}
+
+#[test]
+fn resp_typed_streaming_matrix() {
+    let mut decoder = RespStreamingDecoder::new();
+    decoder.push(b"~2\r\n+a\r\n");
+    assert_eq!(decoder.read_typed().unwrap(), None);
+    decoder.push(b"+b\r\n=7\r\ntxt:");
+    assert_eq!(
+        decoder.read_typed().unwrap(),
+        Some(RespValue::Set(vec![s("a"), s("b")]))
+    );
+    assert_eq!(decoder.read_typed().unwrap(), None);
+    decoder.push(b"xyz\r\n");
+    assert_eq!(
+        decoder.read_typed().unwrap(),
+        Some(RespValue::Verbatim {
+            format: "txt".into(),
+            text: "xyz".into(),
+        })
+    );
+    assert_eq!(decoder.read_typed().unwrap(), None);
+}
+
+#[test]
+fn resp_typed_error_matrix() {
+    let cases: [(&[u8], RespDecodeError); 11] = [
+        (b":12a\r\n", RespDecodeError::InvalidNumber(1)),
+        (b",x\r\n", RespDecodeError::InvalidNumber(1)),
+        (b"(1.5\r\n", RespDecodeError::InvalidNumber(1)),
+        (b"=3\r\ntxt\r\n", RespDecodeError::InvalidVerbatim(1)),
+        (b"+\xff\r\n", RespDecodeError::InvalidUtf8(1)),
+        (b"*2\r\n:1\r\n", RespDecodeError::EndOfInput(8)),
+        // Announced counts are not reserved up front.
+        (b"*999999999999999\r\n", RespDecodeError::EndOfInput(18)),
+        (b"%999999999999999\r\n", RespDecodeError::EndOfInput(18)),
+        (
+            b"~999999999999999\r\n_\r\n",
+            RespDecodeError::EndOfInput(21),
+        ),
+        // Lengths past `usize` or with stray bytes are not numbers.
+        (
+            b"*99999999999999999999999\r\n",
+            RespDecodeError::InvalidNumber(1),
+        ),
+        (b"$1x\r\na\r\n", RespDecodeError::InvalidNumber(1)),
+    ];
+    let mut decoder = RespDecoder::new();
+    for (bytes, error) in cases {
+        assert_eq!(
+            decoder.decode_typed(bytes),
+            Err(error),
+            "{}",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+    assert_eq!(
+        decoder.decode(b"*999999999999999\r\n"),
+        Err(RespDecodeError::EndOfInput(18))
+    );
+}
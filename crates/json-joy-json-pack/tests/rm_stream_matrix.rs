@@ -0,0 +1,123 @@
+//! Streaming RM record writing and reassembly.
+//!
+//! Not an upstream port: upstream only frames fully buffered records and
+//! returns nothing for fragments that do not complete a record.
+
+use json_joy_json_pack::rm::{RmDecodeError, RmRecordDecoder, RmRecordEncoder, RmRecordWriter};
+
+/// Splits encoded frames into `(fin, payload)` pairs.
+fn frames(mut bytes: &[u8]) -> Vec<(bool, Vec<u8>)> {
+    let mut out = Vec::new();
+    while !bytes.is_empty() {
+        let header = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let len = (header & 0x7fff_ffff) as usize;
+        out.push((header & 0x8000_0000 != 0, bytes[4..4 + len].to_vec()));
+        bytes = &bytes[4 + len..];
+    }
+    out
+}
+
+#[test]
+fn rm_record_writer_fragment_matrix() {
+    let mut writer = RmRecordWriter::new(4);
+    assert_eq!(writer.max_fragment_size(), 4);
+
+    // Nothing is emitted until a fragment is known not to be the last.
+    assert!(writer.write(b"ab").is_empty());
+    assert!(writer.write(b"cd").is_empty());
+    let out = writer.write(b"efghijk");
+    assert_eq!(
+        frames(&out),
+        [(false, b"abcd".to_vec()), (false, b"efgh".to_vec())]
+    );
+    assert_eq!(frames(&writer.finish()), [(true, b"ijk".to_vec())]);
+
+    // Exactly one full fragment finishes without an empty trailing frame.
+    assert!(writer.write(b"wxyz").is_empty());
+    assert_eq!(frames(&writer.finish()), [(true, b"wxyz".to_vec())]);
+
+    // An empty record is a single empty fin=1 frame, like encode_record.
+    assert_eq!(writer.finish(), RmRecordEncoder::new().encode_record(b""));
+
+    assert_eq!(RmRecordWriter::new(0).max_fragment_size(), 1);
+    assert_eq!(RmRecordWriter::default().max_fragment_size(), 0x7fff_ffff);
+}
+
+#[test]
+fn rm_record_writer_decoder_roundtrip() {
+    let payload: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let mut writer = RmRecordWriter::new(1000);
+    let mut wire = Vec::new();
+    for chunk in payload.chunks(333) {
+        wire.extend(writer.write(chunk));
+    }
+    wire.extend(writer.finish());
+    let parts = frames(&wire);
+    assert_eq!(parts.len(), 10);
+    assert!(parts.iter().all(|(_, p)| p.len() == 1000));
+    assert!(parts[..9].iter().all(|(fin, _)| !fin));
+    assert!(parts[9].0);
+
+    let mut decoder = RmRecordDecoder::new();
+    decoder.push(&wire);
+    let mut record = None;
+    for _ in 0..parts.len() {
+        record = record.or(decoder.read_record());
+    }
+    assert_eq!(record, Some(payload));
+}
+
+#[test]
+fn rm_read_message_reassembly_matrix() {
+    let mut writer = RmRecordWriter::new(3);
+    let mut wire = writer.write(b"abcdefgh");
+    wire.extend(writer.finish());
+    wire.extend(writer.finish());
+    wire.extend(writer.write(b"xyz"));
+    wire.extend(writer.finish());
+
+    let mut decoder = RmRecordDecoder::new();
+    // Fed one byte at a time, each record appears exactly once.
+    let mut records = Vec::new();
+    for byte in &wire {
+        decoder.push(&[*byte]);
+        if let Some(record) = decoder.read_message().unwrap() {
+            records.push(record);
+        }
+    }
+    assert_eq!(records, [b"abcdefgh".to_vec(), vec![], b"xyz".to_vec()]);
+    assert_eq!(decoder.read_message(), Ok(None));
+
+    // All fragments buffered at once reassemble in a single call.
+    let mut decoder = RmRecordDecoder::new();
+    decoder.push(&wire);
+    assert_eq!(decoder.read_message(), Ok(Some(b"abcdefgh".to_vec())));
+}
+
+#[test]
+fn rm_read_message_size_limit_matrix() {
+    let mut writer = RmRecordWriter::new(4);
+    let mut wire = writer.write(b"0123456789");
+    wire.extend(writer.finish());
+
+    let mut decoder = RmRecordDecoder::with_max_record_size(10);
+    decoder.push(&wire);
+    assert_eq!(decoder.read_message(), Ok(Some(b"0123456789".to_vec())));
+
+    let mut decoder = RmRecordDecoder::with_max_record_size(9);
+    decoder.push(&wire);
+    let err = decoder.read_message().unwrap_err();
+    assert_eq!(err, RmDecodeError::RecordTooLarge { size: 10, limit: 9 });
+    assert_eq!(decoder.read_message(), Err(err));
+
+    // The limit applies from the header alone, before the payload arrives.
+    let mut decoder = RmRecordDecoder::with_max_record_size(100);
+    decoder.push(&[0x80, 0x00, 0x10, 0x00]);
+    assert_eq!(
+        decoder.read_message(),
+        Err(RmDecodeError::RecordTooLarge {
+            size: 0x1000,
+            limit: 100,
+        })
+    );
+}
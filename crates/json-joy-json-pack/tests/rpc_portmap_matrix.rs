@@ -0,0 +1,191 @@
+//! Portmapper v2 bodies and calls, checked against hand-assembled wire
+//! bytes and round-tripped through the RPC message codec.
+//!
+//! Not an upstream port: upstream stops at the RPC message layer.
+
+use json_joy_json_pack::rpc::portmap::{IPPROTO_TCP, IPPROTO_UDP, PMAP_PROG, PMAP_VERS};
+use json_joy_json_pack::rpc::{
+    PmapCallArgs, PmapCallResult, PmapDecoder, PmapEncoder, PmapError, PmapMapping, PmapProc,
+    RpcAcceptStat, RpcMessage, RpcMessageDecoder, RpcMessageEncoder, RpcOpaqueAuth,
+};
+use json_joy_json_pack::xdr::XdrDecodeError;
+
+fn words(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_be_bytes()).collect()
+}
+
+fn mappings() -> Vec<PmapMapping> {
+    vec![
+        PmapMapping {
+            prog: PMAP_PROG,
+            vers: 2,
+            prot: IPPROTO_TCP,
+            port: 111,
+        },
+        PmapMapping {
+            prog: 100003,
+            vers: 3,
+            prot: IPPROTO_UDP,
+            port: 2049,
+        },
+        PmapMapping {
+            prog: 100005,
+            vers: 1,
+            prot: IPPROTO_TCP,
+            port: 20048,
+        },
+    ]
+}
+
+#[test]
+fn rpc_portmap_bodies_matrix() {
+    let mut encoder = PmapEncoder::new();
+    let mut decoder = PmapDecoder::new();
+
+    let nfs = mappings()[1];
+    let bytes = encoder.encode_mapping(&nfs);
+    assert_eq!(bytes, words(&[100003, 3, 17, 2049]));
+    assert_eq!(decoder.decode_mapping(&bytes).unwrap(), nfs);
+
+    for ok in [true, false] {
+        let bytes = encoder.encode_bool(ok);
+        assert_eq!(bytes, words(&[ok as u32]));
+        assert_eq!(decoder.decode_bool(&bytes).unwrap(), ok);
+    }
+    assert_eq!(
+        decoder.decode_port(&encoder.encode_port(2049)).unwrap(),
+        2049
+    );
+
+    // pmaplist: a "more follows" flag before each entry and at the end.
+    for n in 0..=3 {
+        let list = &mappings()[..n];
+        let bytes = encoder.encode_pmaplist(list);
+        let mut expected = Vec::new();
+        for m in list {
+            expected.extend(words(&[1, m.prog, m.vers, m.prot, m.port]));
+        }
+        expected.extend(words(&[0]));
+        assert_eq!(bytes, expected);
+        assert_eq!(decoder.decode_pmaplist(&bytes).unwrap(), list);
+        assert_eq!(
+            decoder.decode_pmaplist(&bytes[..bytes.len() - 4]),
+            Err(XdrDecodeError::EndOfInput(bytes.len() - 4))
+        );
+    }
+
+    // CALLIT bodies carry padded opaque arguments and results.
+    let args = PmapCallArgs {
+        prog: 100003,
+        vers: 3,
+        proc_: 0,
+        args: vec![1, 2, 3, 4, 5],
+    };
+    let bytes = encoder.encode_call_args(&args);
+    let mut expected = words(&[100003, 3, 0, 5]);
+    expected.extend([1, 2, 3, 4, 5, 0, 0, 0]);
+    assert_eq!(bytes, expected);
+    assert_eq!(decoder.decode_call_args(&bytes).unwrap(), args);
+    let result = PmapCallResult {
+        port: 2049,
+        res: vec![9; 6],
+    };
+    let bytes = encoder.encode_call_result(&result);
+    assert_eq!(bytes.len(), 4 + 4 + 8);
+    assert_eq!(decoder.decode_call_result(&bytes).unwrap(), result);
+
+    for proc_ in 0..6 {
+        assert_eq!(PmapProc::try_from(proc_).unwrap() as u32, proc_);
+    }
+    assert_eq!(PmapProc::try_from(6), Err(6));
+}
+
+#[test]
+fn rpc_portmap_calls_matrix() {
+    let mut encoder = PmapEncoder::new();
+    let rpc = RpcMessageDecoder::new();
+
+    // GETPORT for NFSv3 over UDP: call header, AUTH_NONE twice, mapping.
+    let bytes = encoder
+        .encode_getport_call(0x1234, 100003, 3, IPPROTO_UDP)
+        .unwrap();
+    assert_eq!(
+        bytes,
+        words(&[0x1234, 0, 2, PMAP_PROG, PMAP_VERS, 3, 0, 0, 0, 0, 100003, 3, 17, 0])
+    );
+    let Some(RpcMessage::Call(call)) = rpc.decode_message(&bytes).unwrap() else {
+        panic!("expected a call");
+    };
+    assert_eq!(call.proc_, PmapProc::GetPort as u32);
+
+    let bytes = encoder.encode_dump_call(7).unwrap();
+    let Some(RpcMessage::Call(call)) = rpc.decode_message(&bytes).unwrap() else {
+        panic!("expected a call");
+    };
+    assert_eq!((call.xid, call.proc_), (7, PmapProc::Dump as u32));
+    assert!(call.params.is_empty());
+
+    let args = PmapCallArgs {
+        prog: 100005,
+        vers: 1,
+        proc_: 0,
+        args: vec![],
+    };
+    let bytes = encoder.encode_callit_call(8, &args).unwrap();
+    let Some(RpcMessage::Call(call)) = rpc.decode_message(&bytes).unwrap() else {
+        panic!("expected a call");
+    };
+    assert_eq!(call.proc_, PmapProc::CallIt as u32);
+    assert_eq!(
+        PmapDecoder::new().decode_call_args(&call.params).unwrap(),
+        args
+    );
+}
+
+#[test]
+fn rpc_portmap_replies_matrix() {
+    let mut pmap = PmapEncoder::new();
+    let mut decoder = PmapDecoder::new();
+    let mut rpc = RpcMessageEncoder::new();
+    let none = RpcOpaqueAuth::none();
+    let reply = |rpc: &mut RpcMessageEncoder, stat: RpcAcceptStat, results: &[u8]| {
+        let bytes = rpc
+            .encode_accepted_reply(1, &none, stat as u32, None, results)
+            .unwrap();
+        RpcMessageDecoder::new()
+            .decode_message(&bytes)
+            .unwrap()
+            .unwrap()
+    };
+
+    let msg = reply(
+        &mut rpc,
+        RpcAcceptStat::Success,
+        &pmap.encode_pmaplist(&mappings()),
+    );
+    let results = PmapDecoder::results(&msg).unwrap();
+    assert_eq!(decoder.decode_pmaplist(results).unwrap(), mappings());
+
+    let msg = reply(&mut rpc, RpcAcceptStat::Success, &pmap.encode_port(0));
+    assert_eq!(
+        decoder
+            .decode_port(PmapDecoder::results(&msg).unwrap())
+            .unwrap(),
+        0
+    );
+
+    let msg = reply(&mut rpc, RpcAcceptStat::ProcUnavail, &[]);
+    assert_eq!(
+        PmapDecoder::results(&msg),
+        Err(PmapError::Failed(RpcAcceptStat::ProcUnavail))
+    );
+    let bytes = rpc.encode_rejected_reply(1, 1, None, Some(1));
+    let msg = RpcMessageDecoder::new()
+        .decode_message(&bytes)
+        .unwrap()
+        .unwrap();
+    assert_eq!(PmapDecoder::results(&msg), Err(PmapError::NotAccepted));
+
+    let err: PmapError = decoder.decode_port(&[0, 0]).unwrap_err().into();
+    assert_eq!(err, PmapError::Xdr(XdrDecodeError::EndOfInput(0)));
+}
@@ -0,0 +1,307 @@
+//! Reactive-RPC compact and binary message codecs.
+//!
+//! Compact shapes follow upstream `reactive-rpc/common/codec/compact`. The
+//! binary frames are worked out by hand from upstream's `encodeHeader` in
+//! `reactive-rpc/common/messages`, covering each of its three header sizes;
+//! `@jsonjoy.com/reactive-rpc` is not an oracle dependency, so they are not
+//! generated.
+
+use json_joy_json_pack::codecs::{
+    CborJsonValueCodec, JsonJsonValueCodec, JsonValueCodec, MsgPackJsonValueCodec,
+};
+use json_joy_json_pack::rpc_rx::{RxBinaryCodec, RxCompactCodec, RxError, RxMessage};
+use json_joy_json_pack::PackValue;
+
+fn all_messages() -> Vec<RxMessage> {
+    let data = PackValue::Object(vec![("x".into(), PackValue::Integer(1))]);
+    let err = PackValue::Object(vec![("message".into(), PackValue::Str("boom".into()))]);
+    vec![
+        RxMessage::Notification {
+            method: "ping".into(),
+            value: None,
+        },
+        RxMessage::Notification {
+            method: "log".into(),
+            value: Some(PackValue::Str("hi".into())),
+        },
+        RxMessage::RequestData {
+            id: 1,
+            method: "sub".into(),
+            value: Some(data.clone()),
+        },
+        RxMessage::RequestComplete {
+            id: 2,
+            method: "get".into(),
+            value: None,
+        },
+        RxMessage::RequestError {
+            id: 3,
+            method: "put".into(),
+            value: err.clone(),
+        },
+        RxMessage::RequestUnsubscribe { id: 4 },
+        RxMessage::ResponseData {
+            id: 5,
+            value: data.clone(),
+        },
+        RxMessage::ResponseComplete {
+            id: 6,
+            value: Some(PackValue::Bool(true)),
+        },
+        RxMessage::ResponseComplete { id: 7, value: None },
+        RxMessage::ResponseError { id: 8, value: err },
+        RxMessage::ResponseUnsubscribe { id: 0xffff },
+    ]
+}
+
+#[test]
+fn rx_compact_shape_matrix() {
+    let mut json = JsonJsonValueCodec::new();
+    let compact = RxCompactCodec::new();
+    let cases: Vec<(RxMessage, &str)> = vec![
+        (
+            RxMessage::Notification {
+                method: "ping".into(),
+                value: None,
+            },
+            r#"[8,"ping"]"#,
+        ),
+        (
+            RxMessage::RequestData {
+                id: 1,
+                method: "sub".into(),
+                value: Some(PackValue::Integer(5)),
+            },
+            r#"[0,1,"sub",5]"#,
+        ),
+        (
+            RxMessage::RequestComplete {
+                id: 2,
+                method: "get".into(),
+                value: None,
+            },
+            r#"[1,2,"get"]"#,
+        ),
+        (
+            RxMessage::RequestError {
+                id: 3,
+                method: "put".into(),
+                value: PackValue::Str("e".into()),
+            },
+            r#"[2,3,"put","e"]"#,
+        ),
+        (RxMessage::RequestUnsubscribe { id: 4 }, "[3,4]"),
+        (
+            RxMessage::ResponseData {
+                id: 5,
+                value: PackValue::Null,
+            },
+            "[4,5,null]",
+        ),
+        (RxMessage::ResponseComplete { id: 6, value: None }, "[5,6]"),
+        (
+            RxMessage::ResponseError {
+                id: 7,
+                value: PackValue::Str("e".into()),
+            },
+            r#"[6,7,"e"]"#,
+        ),
+        (RxMessage::ResponseUnsubscribe { id: 8 }, "[7,8]"),
+    ];
+    for (message, expected) in cases {
+        let bytes = compact
+            .encode(&mut json, std::slice::from_ref(&message))
+            .unwrap();
+        assert_eq!(String::from_utf8(bytes.clone()).unwrap(), expected);
+        assert_eq!(compact.decode(&mut json, &bytes).unwrap(), [message]);
+    }
+
+    let batch = [
+        RxMessage::RequestUnsubscribe { id: 1 },
+        RxMessage::ResponseUnsubscribe { id: 2 },
+    ];
+    let bytes = compact.encode(&mut json, &batch).unwrap();
+    assert_eq!(bytes, b"[[3,1],[7,2]]");
+    assert_eq!(compact.decode(&mut json, &bytes).unwrap(), batch);
+    assert_eq!(compact.encode(&mut json, &[]).unwrap(), b"[]");
+    assert!(compact.decode(&mut json, b"[]").unwrap().is_empty());
+}
+
+#[test]
+fn rx_codec_roundtrip_matrix() {
+    let messages = all_messages();
+    let mut codecs: Vec<Box<dyn JsonValueCodec>> = vec![
+        Box::new(CborJsonValueCodec::new()),
+        Box::new(MsgPackJsonValueCodec::new()),
+        Box::new(JsonJsonValueCodec::new()),
+    ];
+    for codec in codecs.iter_mut() {
+        let codec = codec.as_mut();
+        let compact = RxCompactCodec::new();
+        let bytes = compact.encode(codec, &messages).unwrap();
+        assert_eq!(compact.decode(codec, &bytes).unwrap(), messages);
+
+        let binary = RxBinaryCodec::new();
+        let bytes = binary.encode(codec, &messages).unwrap();
+        assert_eq!(binary.decode(codec, &bytes).unwrap(), messages);
+    }
+}
+
+#[test]
+fn rx_binary_layout_matrix() {
+    let mut cbor = CborJsonValueCodec::new();
+    let binary = RxBinaryCodec::new();
+    // CBOR byte strings whose encoding is exactly `size` bytes long.
+    let blob = |size: usize| {
+        let header = if size < 65_536 { 3 } else { 5 };
+        PackValue::Bytes(vec![0x5a; size - header])
+    };
+    let cases: Vec<(RxMessage, Vec<u8>, Vec<u8>)> = vec![
+        (
+            RxMessage::RequestData {
+                id: 0x0102,
+                method: "ab".into(),
+                value: Some(PackValue::Integer(7)),
+            },
+            vec![0x20, 0x01, 0x01, 0x02, 2, b'a', b'b'],
+            vec![],
+        ),
+        (
+            RxMessage::Notification {
+                method: "n".into(),
+                value: None,
+            },
+            vec![0, 0, 0, 1, b'n'],
+            vec![],
+        ),
+        (
+            RxMessage::Notification {
+                method: "n".into(),
+                value: Some(PackValue::Integer(7)),
+            },
+            vec![0, 0, 1, 1, b'n'],
+            vec![],
+        ),
+        (
+            RxMessage::RequestUnsubscribe { id: 9 },
+            vec![0xe0, 0x00, 0x00, 0x09],
+            vec![],
+        ),
+        (
+            RxMessage::ResponseUnsubscribe { id: 9 },
+            vec![0xe0, 0x01, 0x00, 0x09],
+            vec![],
+        ),
+        (
+            RxMessage::ResponseComplete {
+                id: 0xffff,
+                value: None,
+            },
+            vec![0xa0, 0x00, 0xff, 0xff],
+            vec![],
+        ),
+        (
+            RxMessage::ResponseData {
+                id: 0xabcd,
+                value: blob(4095),
+            },
+            vec![0x8f, 0xff, 0xab, 0xcd],
+            vec![],
+        ),
+        // 13 to 19 bits of size: the id's low byte follows the data.
+        (
+            RxMessage::ResponseData {
+                id: 0xabcd,
+                value: blob(5000),
+            },
+            vec![0x90, 0x27, 0x08, 0xab],
+            vec![0xcd],
+        ),
+        (
+            RxMessage::RequestComplete {
+                id: 0xabcd,
+                method: "m".into(),
+                value: Some(blob(4096)),
+            },
+            vec![0x50, 0x20, 0x00, 0xab, 1, b'm'],
+            vec![0xcd],
+        ),
+        // 20 to 27 bits of size: the whole id follows the data.
+        (
+            RxMessage::ResponseError {
+                id: 0xabcd,
+                value: blob(600_000),
+            },
+            vec![0xd0, 0x12, 0xa7, 0xc0],
+            vec![0xab, 0xcd],
+        ),
+    ];
+    for (message, head, tail) in cases {
+        let bytes = binary
+            .encode(&mut cbor, std::slice::from_ref(&message))
+            .unwrap();
+        assert_eq!(bytes[..head.len()], head, "{message:?}");
+        assert_eq!(bytes[bytes.len() - tail.len()..], tail, "{message:?}");
+        let mut batch = bytes.clone();
+        batch.extend_from_slice(&bytes);
+        assert_eq!(
+            binary.decode(&mut cbor, &batch).unwrap(),
+            [message.clone(), message]
+        );
+    }
+}
+
+#[test]
+fn rx_error_matrix() {
+    let mut cbor = CborJsonValueCodec::new();
+    let binary = RxBinaryCodec::new();
+    let err = binary.decode(&mut cbor, &[0x20, 0, 0, 1]).unwrap_err();
+    assert!(matches!(err, RxError::UnexpectedEof(4)));
+    assert_eq!(err.offset(), Some(4));
+    // The id's low byte is missing after the data.
+    let err = binary
+        .decode(&mut cbor, &[0xe0, 0, 0, 1, 0x90, 0, 0x02, 0, 0x41, 0])
+        .unwrap_err();
+    assert!(matches!(err, RxError::UnexpectedEof(10)));
+    let err = binary.decode(&mut cbor, &[0xe0, 0x05, 0, 1]).unwrap_err();
+    assert!(matches!(err, RxError::InvalidType(5, 0)));
+    let err = binary.decode(&mut cbor, &[0, 0, 0, 1, 0xff]).unwrap_err();
+    assert!(matches!(err, RxError::InvalidMethod(4)));
+    let err = binary
+        .encode(
+            &mut cbor,
+            &[RxMessage::Notification {
+                method: "n".into(),
+                value: Some(PackValue::Bytes(vec![0; 0x20_0000])),
+            }],
+        )
+        .unwrap_err();
+    assert!(matches!(err, RxError::PayloadTooLarge(_)));
+    let err = binary
+        .encode(
+            &mut cbor,
+            &[RxMessage::Notification {
+                method: "m".repeat(256),
+                value: None,
+            }],
+        )
+        .unwrap_err();
+    assert!(matches!(err, RxError::MethodTooLong));
+
+    let mut json = JsonJsonValueCodec::new();
+    let compact = RxCompactCodec::new();
+    for (input, reason) in [
+        (&b"{}"[..], "message is not an array"),
+        (b"[9,1]", "invalid message type"),
+        (b"[4,70000,1]", "invalid id"),
+        (b"[0,1,2]", "invalid method"),
+        (b"[4,1]", "missing value"),
+        (b"[3,1,2]", "too many elements"),
+    ] {
+        let err = compact.decode(&mut json, input).unwrap_err();
+        assert!(
+            matches!(err, RxError::InvalidCompact(r) if r == reason),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! String hot paths shared by the scalar and `simd` builds.
+//!
+//! Not an upstream port. Run with `--features simd` to cover the vectorized
+//! code; the expectations are identical for both builds.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoder};
+use json_joy_json_pack::json::util::find_ending_quote;
+use json_joy_json_pack::json::{JsonDecoder, JsonEncoder};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoderFast};
+use json_joy_json_pack::PackValue;
+
+#[test]
+fn find_ending_quote_matrix() {
+    // Errors carry the same offset in both builds: the end of the input, or
+    // the start when it is already past the end.
+    let cases: [(&[u8], usize, Result<usize, usize>); 10] = [
+        (b"\"abc\"", 1, Ok(4)),
+        (b"\"\"", 1, Ok(1)),
+        (b"\"a\\\"b\"", 1, Ok(5)),
+        (b"\"a\\\\\"", 1, Ok(4)),
+        (b"\"a\\\\\\\"b\"", 1, Ok(7)),
+        (b"\"0123456789abcdef0123456789abcdef\\\"x\"", 1, Ok(36)),
+        (b"\"unterminated", 1, Err(13)),
+        (b"\"a\\\"", 1, Err(4)),
+        (b"\"", 1, Err(1)),
+        (b"\"", 3, Err(3)),
+    ];
+    for (data, x, expected) in cases {
+        assert_eq!(
+            find_ending_quote(data, x).map_err(|e| e.offset().unwrap()),
+            expected,
+            "{data:?}"
+        );
+    }
+}
+
+#[test]
+fn decoder_string_roundtrip_matrix() {
+    let strings = [
+        String::new(),
+        "ascii".to_string(),
+        "x".repeat(100),
+        format!("{}\u{e9}{}", "a".repeat(31), "b".repeat(17)),
+        format!("{}\u{1f600}", "quote \" and backslash \\ ".repeat(3)),
+        "\u{4e2d}\u{6587}".repeat(20),
+    ];
+    for s in strings {
+        let value = PackValue::Str(s);
+        let json = JsonEncoder::new().encode(&value);
+        assert_eq!(JsonDecoder::new().decode(&json).unwrap(), value);
+        let cbor = CborEncoder::new().encode(&value);
+        assert_eq!(CborDecoder::new().decode(&cbor).unwrap(), value);
+        let msgpack = MsgPackEncoderFast::new().encode(&value);
+        assert_eq!(MsgPackDecoderFast::new().decode(&msgpack).unwrap(), value);
+    }
+
+    // Invalid UTF-8 after a long ASCII run is still rejected.
+    let mut bad = vec![0xd9, 40];
+    bad.extend_from_slice(&[b'a'; 39]);
+    bad.push(0xff);
+    assert!(MsgPackDecoderFast::new().decode(&bad).is_err());
+}
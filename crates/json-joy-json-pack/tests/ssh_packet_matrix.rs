@@ -0,0 +1,238 @@
+//! SSH binary packet framing, and zlib / zlib@openssh.com payload
+//! compression switched on by the negotiation events.
+//!
+//! Not an upstream port: upstream stops at RFC 4251 data types.
+
+use json_joy_json_pack::ssh::{SshError, SshPacketDecoder, SshPacketEncoder};
+
+fn payloads() -> Vec<Vec<u8>> {
+    vec![
+        vec![],
+        vec![21],
+        b"\x05ssh-userauth".to_vec(),
+        b"\x5e\x00\x00\x00\x00\x00\x00\x00\x0bhello world".to_vec(),
+        b"ls -la /var/log\n".repeat(200),
+        (0..=255u8).cycle().take(5000).collect(),
+    ]
+}
+
+#[test]
+fn ssh_packet_framing_matrix() {
+    for block_size in [8, 16] {
+        let mut encoder = SshPacketEncoder::new();
+        encoder.set_block_size(block_size).unwrap();
+        let mut decoder = SshPacketDecoder::new();
+        let mut stream = Vec::new();
+        for payload in payloads() {
+            let packet = encoder.encode_packet(&payload).unwrap();
+            // Lengths and padding as RFC 4253 §6 lays them out.
+            assert_eq!(packet.len() % block_size, 0);
+            let packet_len = u32::from_be_bytes(packet[..4].try_into().unwrap()) as usize;
+            let padding = packet[4] as usize;
+            assert_eq!(packet_len, packet.len() - 4);
+            assert!((4..4 + block_size).contains(&padding));
+            assert_eq!(&packet[5..5 + payload.len()], &payload[..]);
+
+            // Incomplete until the last byte arrives.
+            for cut in 0..packet.len() {
+                assert_eq!(decoder.read_packet(&packet[..cut]).unwrap(), None);
+            }
+            assert_eq!(
+                decoder.read_packet(&packet).unwrap(),
+                Some((payload.clone(), packet.len()))
+            );
+            stream.extend_from_slice(&packet);
+        }
+
+        // Back-to-back packets are read one at a time.
+        let mut x = 0;
+        for payload in payloads() {
+            let (read, n) = decoder.read_packet(&stream[x..]).unwrap().unwrap();
+            assert_eq!(read, payload);
+            x += n;
+        }
+        assert_eq!(x, stream.len());
+    }
+}
+
+#[test]
+fn ssh_packet_invalid_matrix() {
+    let mut decoder = SshPacketDecoder::new();
+    let valid = SshPacketEncoder::new().encode_packet(b"payload").unwrap();
+    let packet = |len: u32, padding: u8| {
+        let mut bytes = len.to_be_bytes().to_vec();
+        bytes.push(padding);
+        bytes.resize(4 + len as usize, 0);
+        bytes
+    };
+    for bytes in [
+        packet(4, 4),  // shorter than its padding
+        packet(12, 3), // padding below 4
+        packet(12, 12),
+        packet(13, 4), // not a multiple of 8
+        packet((1 << 20) - 4, 4),
+    ] {
+        assert_eq!(decoder.read_packet(&bytes), Err(SshError::InvalidPacket));
+    }
+    decoder.max_packet_len = valid.len() - 8;
+    assert_eq!(decoder.read_packet(&valid), Err(SshError::InvalidPacket));
+
+    // Block sizes the padding cannot cover are rejected up front.
+    let mut encoder = SshPacketEncoder::new();
+    for block_size in [0, 4, 12, 256, usize::MAX] {
+        assert_eq!(
+            encoder.set_block_size(block_size),
+            Err(SshError::InvalidBlockSize(block_size))
+        );
+    }
+    assert_eq!(encoder.block_size(), 8);
+    encoder.set_block_size(248).unwrap();
+    let packet = encoder.encode_packet(&[0; 251]).unwrap();
+    assert_eq!((packet.len(), packet[4]), (496, 240));
+    assert!(SshPacketDecoder::new().read_packet(&packet).is_ok());
+}
+
+#[cfg(feature = "flate2")]
+mod compression {
+    use json_joy_json_pack::ssh::{
+        SshCompressionAlgorithm, SshError, SshPacketDecoder, SshPacketEncoder,
+    };
+
+    use super::payloads;
+
+    /// Both ends of one direction, driven through the same events.
+    fn pair(algorithm: SshCompressionAlgorithm) -> (SshPacketEncoder, SshPacketDecoder) {
+        let mut encoder = SshPacketEncoder::new();
+        let mut decoder = SshPacketDecoder::new();
+        encoder.compressor.set_negotiated(algorithm);
+        decoder.decompressor.set_negotiated(algorithm);
+        (encoder, decoder)
+    }
+
+    fn round_trip(encoder: &mut SshPacketEncoder, decoder: &mut SshPacketDecoder) -> usize {
+        let mut wire = 0;
+        for payload in payloads() {
+            let packet = encoder.encode_packet(&payload).unwrap();
+            let (read, n) = decoder.read_packet(&packet).unwrap().unwrap();
+            assert_eq!(read, payload);
+            assert_eq!(n, packet.len());
+            wire += packet.len();
+        }
+        wire
+    }
+
+    #[test]
+    fn ssh_compression_negotiation_matrix() {
+        use SshCompressionAlgorithm::*;
+        for algorithm in [None, Zlib, ZlibOpenSsh] {
+            assert_eq!(
+                SshCompressionAlgorithm::from_name(algorithm.name()),
+                Some(algorithm)
+            );
+        }
+        assert_eq!(
+            SshCompressionAlgorithm::from_name("zstd@openssh.com"),
+            Option::None
+        );
+        assert_eq!(
+            SshCompressionAlgorithm::negotiate(
+                &["zlib@openssh.com", "zlib", "none"],
+                &["none", "zlib@openssh.com"]
+            ),
+            Some(ZlibOpenSsh)
+        );
+        assert_eq!(
+            SshCompressionAlgorithm::negotiate(&["zlib"], &["none"]),
+            Option::None
+        );
+
+        // (algorithm, active after NEWKEYS, active after auth)
+        for (algorithm, after_keys, after_auth) in [
+            (None, false, false),
+            (Zlib, true, true),
+            (ZlibOpenSsh, false, true),
+        ] {
+            let (mut encoder, mut decoder) = pair(algorithm);
+            // Nothing changes before NEWKEYS.
+            assert!(!encoder.compressor.is_active());
+            let plain = round_trip(&mut encoder, &mut decoder);
+
+            encoder.compressor.on_new_keys();
+            decoder.decompressor.on_new_keys();
+            assert_eq!(encoder.compressor.algorithm(), algorithm);
+            assert_eq!(encoder.compressor.is_active(), after_keys);
+            assert_eq!(decoder.decompressor.is_active(), after_keys);
+            round_trip(&mut encoder, &mut decoder);
+
+            encoder.compressor.on_auth_success();
+            decoder.decompressor.on_auth_success();
+            assert_eq!(encoder.compressor.is_active(), after_auth);
+            assert_eq!(decoder.decompressor.is_active(), after_auth);
+            // The stream carries over between packets and batches.
+            let wire = round_trip(&mut encoder, &mut decoder);
+            if after_auth {
+                assert!(wire < plain / 2, "{algorithm:?}: {wire} vs {plain}");
+            } else {
+                assert_eq!(wire, plain);
+            }
+        }
+    }
+
+    #[test]
+    fn ssh_compression_rekey_matrix() {
+        use SshCompressionAlgorithm::*;
+        let (mut encoder, mut decoder) = pair(Zlib);
+        encoder.compressor.on_new_keys();
+        decoder.decompressor.on_new_keys();
+        round_trip(&mut encoder, &mut decoder);
+
+        // Renegotiating the same algorithm keeps the stream: a peer that
+        // restarted its inflater would fail here.
+        encoder.compressor.set_negotiated(Zlib);
+        decoder.decompressor.set_negotiated(Zlib);
+        encoder.compressor.on_new_keys();
+        decoder.decompressor.on_new_keys();
+        round_trip(&mut encoder, &mut decoder);
+
+        // Switching to delayed compression after authentication starts a
+        // fresh stream right away.
+        encoder.compressor.on_auth_success();
+        decoder.decompressor.on_auth_success();
+        encoder.compressor.set_negotiated(ZlibOpenSsh);
+        decoder.decompressor.set_negotiated(ZlibOpenSsh);
+        encoder.compressor.on_new_keys();
+        decoder.decompressor.on_new_keys();
+        assert!(encoder.compressor.is_active());
+        round_trip(&mut encoder, &mut decoder);
+
+        // And `none` turns it off.
+        encoder.compressor.set_negotiated(None);
+        decoder.decompressor.set_negotiated(None);
+        encoder.compressor.on_new_keys();
+        decoder.decompressor.on_new_keys();
+        assert!(!encoder.compressor.is_active());
+        let packet = encoder.encode_packet(b"plain").unwrap();
+        assert_eq!(&packet[5..10], b"plain");
+        round_trip(&mut encoder, &mut decoder);
+    }
+
+    #[test]
+    fn ssh_compression_invalid_matrix() {
+        let (_, mut decoder) = pair(SshCompressionAlgorithm::Zlib);
+        decoder.decompressor.on_new_keys();
+
+        // A payload that is not a zlib stream.
+        let mut plain = SshPacketEncoder::new();
+        let garbage = plain.encode_packet(&[0xff; 40]).unwrap();
+        assert_eq!(decoder.read_packet(&garbage), Err(SshError::Compression));
+
+        // A payload that inflates past the limit.
+        let (mut encoder, mut decoder) = pair(SshCompressionAlgorithm::Zlib);
+        encoder.compressor.on_new_keys();
+        decoder.decompressor.on_new_keys();
+        let bomb = encoder.encode_packet(&vec![0; 100_000]).unwrap();
+        assert!(bomb.len() < 1000);
+        decoder.max_packet_len = 50_000;
+        assert_eq!(decoder.read_packet(&bomb), Err(SshError::InvalidPacket));
+    }
+}
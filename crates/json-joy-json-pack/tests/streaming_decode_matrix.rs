@@ -0,0 +1,165 @@
+//! Incremental MessagePack and CBOR decoding from chunked sources.
+//!
+//! Not an upstream port: upstream decoders only read whole buffers.
+
+use json_joy_buffers::{StreamingOctetReader, StreamingReader};
+use json_joy_json_pack::cbor::{CborDecoder, CborEncoder, CborError};
+use json_joy_json_pack::msgpack::{MsgPackDecoder, MsgPackEncoderFast, MsgPackError};
+use json_joy_json_pack::streaming::StreamingSource;
+use json_joy_json_pack::PackValue;
+
+fn values() -> Vec<PackValue> {
+    vec![
+        PackValue::Integer(1),
+        PackValue::Str("héllo".into()),
+        PackValue::Array(vec![
+            PackValue::Null,
+            PackValue::Bool(true),
+            PackValue::Float(1.5),
+            PackValue::Integer(-70000),
+        ]),
+        PackValue::Object(vec![
+            ("key".into(), PackValue::Str("x".repeat(300))),
+            ("bin".into(), PackValue::Bytes(vec![1, 2, 3])),
+        ]),
+        PackValue::Integer(u32::MAX as i64),
+    ]
+}
+
+/// Feeds `bytes` in `chunk`-sized pieces, draining values after each push.
+fn drain_chunks<S: StreamingSource>(
+    mut source: S,
+    push: impl Fn(&mut S, &[u8]),
+    bytes: &[u8],
+    chunk: usize,
+    mut read: impl FnMut(&mut S) -> Option<PackValue>,
+) -> Vec<PackValue> {
+    let mut out = Vec::new();
+    for piece in bytes.chunks(chunk) {
+        push(&mut source, piece);
+        while let Some(value) = read(&mut source) {
+            out.push(value);
+        }
+    }
+    assert!(source.unread().is_empty());
+    out
+}
+
+#[test]
+fn msgpack_streaming_matrix() {
+    let mut encoder = MsgPackEncoderFast::new();
+    let bytes: Vec<u8> = values().iter().flat_map(|v| encoder.encode(v)).collect();
+    for chunk in [1, 2, 3, 7, 64, bytes.len()] {
+        let mut decoder = MsgPackDecoder::new();
+        let got = drain_chunks(
+            StreamingReader::new(),
+            |s, b| s.push(b),
+            &bytes,
+            chunk,
+            |s| decoder.read_streaming(s).unwrap(),
+        );
+        assert_eq!(got, values(), "StreamingReader, chunk {chunk}");
+        let got = drain_chunks(
+            StreamingOctetReader::new(),
+            |s, b| s.push(b.to_vec()),
+            &bytes,
+            chunk,
+            |s| decoder.read_streaming(s).unwrap(),
+        );
+        assert_eq!(got, values(), "StreamingOctetReader, chunk {chunk}");
+        let got = drain_chunks(
+            Vec::new(),
+            |s, b| s.extend_from_slice(b),
+            &bytes,
+            chunk,
+            |s| decoder.read_streaming(s).unwrap(),
+        );
+        assert_eq!(got, values(), "Vec, chunk {chunk}");
+    }
+}
+
+#[test]
+fn cbor_streaming_matrix() {
+    let mut encoder = CborEncoder::new();
+    let bytes: Vec<u8> = values().iter().flat_map(|v| encoder.encode(v)).collect();
+    let decoder = CborDecoder::new();
+    for chunk in [1, 2, 3, 7, 64, bytes.len()] {
+        let got = drain_chunks(
+            StreamingReader::new(),
+            |s, b| s.push(b),
+            &bytes,
+            chunk,
+            |s| decoder.read_streaming(s).unwrap(),
+        );
+        assert_eq!(got, values(), "StreamingReader, chunk {chunk}");
+        let got = drain_chunks(
+            StreamingOctetReader::new(),
+            |s, b| s.push(b.to_vec()),
+            &bytes,
+            chunk,
+            |s| decoder.read_streaming(s).unwrap(),
+        );
+        assert_eq!(got, values(), "StreamingOctetReader, chunk {chunk}");
+    }
+
+    // Indefinite-length containers only complete at their break marker.
+    let indefinite = [0x9f, 0x01, 0x7f, 0x61, b'a', 0x61, b'b', 0xff, 0xff];
+    let mut reader = StreamingReader::new();
+    for (i, byte) in indefinite.iter().enumerate() {
+        reader.push(&[*byte]);
+        let value = decoder.read_streaming(&mut reader).unwrap();
+        if i + 1 < indefinite.len() {
+            assert_eq!(value, None, "after byte {i}");
+        } else {
+            assert_eq!(
+                value,
+                Some(PackValue::Array(vec![
+                    PackValue::Integer(1),
+                    PackValue::Str("ab".into()),
+                ]))
+            );
+        }
+    }
+}
+
+#[test]
+fn streaming_error_matrix() {
+    let mut reader = StreamingReader::new();
+    assert!(matches!(
+        MsgPackDecoder::new().read_streaming(&mut reader),
+        Ok(None)
+    ));
+    assert_eq!(CborDecoder::new().read_streaming(&mut reader), Ok(None));
+
+    // Errors leave the source where it was.
+    let mut reader = StreamingReader::new();
+    reader.push(&[0x01, 0xa1, 0xff]);
+    let mut decoder = MsgPackDecoder::new();
+    assert_eq!(
+        decoder.read_streaming(&mut reader).unwrap(),
+        Some(PackValue::Integer(1))
+    );
+    assert!(matches!(
+        decoder.read_streaming(&mut reader),
+        Err(MsgPackError::InvalidUtf8(1))
+    ));
+    assert_eq!(reader.size(), 2);
+
+    let mut reader = StreamingOctetReader::new();
+    reader.push(vec![0x1c]);
+    assert_eq!(
+        CborDecoder::new().read_streaming(&mut reader),
+        Err(CborError::UnexpectedMinor(0))
+    );
+    assert_eq!(reader.size(), 1);
+
+    // Whole-buffer decoding reports truncation distinctly from bad input.
+    assert_eq!(
+        CborDecoder::new().decode(&[0x82, 0x01]),
+        Err(CborError::UnexpectedEof(2))
+    );
+    assert_eq!(
+        CborDecoder::new().decode(&[]),
+        Err(CborError::UnexpectedEof(0))
+    );
+}
@@ -0,0 +1,398 @@
+//! Token-level event API.
+//!
+//! Not an upstream port: upstream has no visitor over encoded values. Every
+//! case checks the events against the tree codecs: walking a value into an
+//! encoder must match `encode`, and parsing into a `PackValueBuilder` must
+//! match `decode`.
+
+use json_joy_json_pack::cbor::{CborDecoder, CborDecoderOptions, CborEncoder};
+use json_joy_json_pack::json::{JsonDecoder, JsonEncoder};
+use json_joy_json_pack::msgpack::{MsgPackDecoderFast, MsgPackEncoder};
+use json_joy_json_pack::token::{walk, PackValueBuilder, TokenSink};
+use json_joy_json_pack::{pack, JsonPackBigInt, JsonPackExtension, JsonPackValue, PackValue};
+
+fn corpus() -> Vec<PackValue> {
+    vec![
+        pack!(null),
+        pack!(undefined),
+        pack!(true),
+        pack!(-33),
+        pack!(i64::MAX),
+        pack!(u64::MAX),
+        pack!(-1i128 << 70),
+        pack!(JsonPackBigInt::from_u128(u128::MAX)),
+        pack!(1.5),
+        pack!("héllo ☃"),
+        pack!(b"\x00\x01"),
+        pack!([]),
+        pack!({}),
+        pack!([1, [2, [3, []]], { "a": null }]),
+        PackValue::Array((0..20).map(PackValue::from).collect()),
+        PackValue::Object((0..20).map(|i| (format!("k{i}"), pack!(i))).collect()),
+        pack!({ "a": 1, "b": { "c": [true, b"\x01"] }, "": "" }),
+        pack!(JsonPackExtension::new(5, pack!(b"\x01\x02"))),
+        pack!([JsonPackExtension::new(7, pack!({ "x": [1, 2] })), 3]),
+        pack!({
+            "t": JsonPackExtension::new(1, pack!(JsonPackExtension::new(2, pack!(b"z")))),
+            "u": 1
+        }),
+        pack!([JsonPackValue::new(vec![0x01]), false]),
+    ]
+}
+
+fn build(parse: impl FnOnce(&mut PackValueBuilder)) -> PackValue {
+    let mut builder = PackValueBuilder::new();
+    parse(&mut builder);
+    builder.finish().expect("complete value")
+}
+
+#[test]
+fn token_walk_matches_encode_matrix() {
+    for value in corpus() {
+        let mut cbor = CborEncoder::new();
+        walk(&value, &mut cbor);
+        assert_eq!(
+            cbor.writer.flush(),
+            CborEncoder::new().encode(&value),
+            "{value:?}"
+        );
+
+        let mut msgpack = MsgPackEncoder::new();
+        walk(&value, &mut msgpack);
+        assert_eq!(
+            msgpack.inner.writer.flush(),
+            MsgPackEncoder::new().encode(&value),
+            "{value:?}"
+        );
+
+        let mut json = JsonEncoder::new();
+        walk(&value, &mut json);
+        assert_eq!(
+            json.writer.flush(),
+            JsonEncoder::new().encode(&value),
+            "{value:?}"
+        );
+
+        assert_eq!(build(|b| walk(&value, b)), value);
+    }
+}
+
+#[test]
+fn token_walk_map_keys_matrix() {
+    // CBOR and MessagePack write non-text keys as they are; JSON and
+    // `PackValueBuilder` pick them up as `on_value_key`.
+    let values = [
+        PackValue::Map(vec![
+            (pack!(1), pack!("a")),
+            (pack!(b"\x01"), pack!([true])),
+            (pack!([1, 2]), pack!(null)),
+            (pack!("s"), pack!(2)),
+        ]),
+        pack!({ "m": (PackValue::Map(vec![(pack!(false), pack!(1))])) }),
+    ];
+    for value in values {
+        let mut cbor = CborEncoder::new();
+        walk(&value, &mut cbor);
+        assert_eq!(cbor.writer.flush(), CborEncoder::new().encode(&value));
+
+        let mut msgpack = MsgPackEncoder::new();
+        walk(&value, &mut msgpack);
+        assert_eq!(
+            msgpack.inner.writer.flush(),
+            MsgPackEncoder::new().encode(&value)
+        );
+
+        let mut json = JsonEncoder::new();
+        walk(&value, &mut json);
+        assert_eq!(json.writer.flush(), JsonEncoder::new().encode(&value));
+
+        assert_eq!(build(|b| walk(&value, b)), value);
+    }
+}
+
+#[test]
+fn token_parse_matches_decode_matrix() {
+    for value in corpus() {
+        let cbor = CborEncoder::new().encode(&value);
+        let decoder = CborDecoder::new();
+        let mut consumed = 0;
+        let built = build(|b| consumed = decoder.parse_with(&cbor, b).unwrap());
+        assert_eq!(built, decoder.decode(&cbor).unwrap(), "{value:?}");
+        assert_eq!(consumed, cbor.len());
+
+        let msgpack = MsgPackEncoder::new().encode(&value);
+        let mut decoder = MsgPackDecoderFast::new();
+        let built = build(|b| consumed = decoder.parse_with(&msgpack, b).unwrap());
+        assert_eq!(built, decoder.decode(&msgpack).unwrap(), "{value:?}");
+        assert_eq!(consumed, msgpack.len());
+
+        let json = JsonEncoder::new().encode(&value);
+        let mut decoder = JsonDecoder::new();
+        let built = build(|b| consumed = decoder.parse_with(&json, b).unwrap());
+        assert_eq!(built, decoder.decode(&json).unwrap(), "{value:?}");
+        assert_eq!(consumed, json.len());
+    }
+}
+
+#[test]
+fn token_cbor_input_matrix() {
+    let cases: [&[u8]; 5] = [
+        // Indefinite-length array, map, text and byte strings.
+        &[0x9f, 0x01, 0x9f, 0xff, 0xff],
+        &[0xbf, 0x61, b'a', 0x7f, 0x61, b'x', 0x61, b'y', 0xff, 0xff],
+        &[0x5f, 0x41, 0x01, 0x42, 0x02, 0x03, 0xff],
+        // Non-text key, simple value, half float.
+        &[0xa1, 0x01, 0xf0, 0xf9, 0x3c, 0x00],
+        // Bignum tag.
+        &[0xc2, 0x42, 0x01, 0x00],
+    ];
+    for bignum in [false, true] {
+        let decoder = CborDecoder::with_options(CborDecoderOptions {
+            bignum,
+            ..Default::default()
+        });
+        for bytes in cases {
+            let mut consumed = 0;
+            let built = build(|b| consumed = decoder.parse_with(bytes, b).unwrap());
+            let (value, len) = decoder.decode_with_consumed(bytes).unwrap();
+            assert_eq!(built, value, "{bytes:?}");
+            assert_eq!(consumed, len);
+        }
+    }
+
+    let mut builder = PackValueBuilder::new();
+    assert!(CborDecoder::new()
+        .parse_with(
+            &[0xa1, 0x69, b'_', b'_', b'p', b'r', b'o', b't', b'o', b'_', b'_', 0x01],
+            &mut builder
+        )
+        .is_err());
+    assert!(CborDecoder::new()
+        .parse_with(&[0x82, 0x01], &mut builder)
+        .is_err());
+
+    // Headers claiming 2^64 - 1 items fail on the missing items instead of
+    // reserving room for them.
+    let huge = [0xff; 8];
+    for major in [0x9b, 0xbb] {
+        let bytes = [&[major][..], &huge].concat();
+        let mut builder = PackValueBuilder::new();
+        assert!(CborDecoder::new().parse_with(&bytes, &mut builder).is_err());
+    }
+    for header in [
+        &[0xdd, 0xff, 0xff, 0xff, 0xff][..],
+        &[0xdf, 0xff, 0xff, 0xff, 0xff],
+    ] {
+        let mut builder = PackValueBuilder::new();
+        assert!(MsgPackDecoderFast::new()
+            .parse_with(header, &mut builder)
+            .is_err());
+    }
+}
+
+#[test]
+fn token_unsized_containers_matrix() {
+    // JSON reports containers without a length; the MessagePack encoder
+    // patches a 32-bit header and the CBOR encoder writes indefinite length.
+    let text = br#" [1, {"a": [], "b": {"c": "d"}}, [[2], 3], "e"] "#;
+    let value = JsonDecoder::new().decode(text).unwrap();
+
+    let mut msgpack = MsgPackEncoder::new();
+    assert_eq!(
+        JsonDecoder::new().parse_with(text, &mut msgpack).unwrap(),
+        text.len() - 1
+    );
+    let bytes = msgpack.inner.writer.flush();
+    assert_eq!(&bytes[..5], [0xdd, 0, 0, 0, 4]);
+    assert_eq!(MsgPackDecoderFast::new().decode(&bytes).unwrap(), value);
+
+    let mut cbor = CborEncoder::new();
+    JsonDecoder::new().parse_with(text, &mut cbor).unwrap();
+    let bytes = cbor.writer.flush();
+    assert_eq!(bytes[0], 0x9f);
+    assert_eq!(CborDecoder::new().decode(&bytes).unwrap(), value);
+
+    let mut json = JsonEncoder::new();
+    JsonDecoder::new().parse_with(text, &mut json).unwrap();
+    assert_eq!(
+        json.writer.flush(),
+        br#"[1,{"a":[],"b":{"c":"d"}},[[2],3],"e"]"#
+    );
+
+    // Headers are patched correctly when the writer grows mid-container.
+    let big = format!("[{}]", vec!["\"xxxxxxxx\""; 10_000].join(","));
+    let mut msgpack = MsgPackEncoder::new();
+    JsonDecoder::new()
+        .parse_with(big.as_bytes(), &mut msgpack)
+        .unwrap();
+    let bytes = msgpack.inner.writer.flush();
+    assert_eq!(
+        MsgPackDecoderFast::new().decode(&bytes).unwrap(),
+        JsonDecoder::new().decode(big.as_bytes()).unwrap()
+    );
+}
+
+/// A sink that forwards to another, dropping one key and upper-casing
+/// strings: the kind of filter the event API is meant for.
+struct Redact<'a, S> {
+    inner: &'a mut S,
+    depth: usize,
+    skip: Option<usize>,
+}
+
+impl<S: TokenSink> Redact<'_, S> {
+    fn forward(&mut self) -> bool {
+        self.skip.is_none()
+    }
+}
+
+impl<S: TokenSink> TokenSink for Redact<'_, S> {
+    fn on_null(&mut self) {
+        if self.forward() {
+            self.inner.on_null();
+        }
+        self.end_value();
+    }
+    fn on_bool(&mut self, value: bool) {
+        if self.forward() {
+            self.inner.on_bool(value);
+        }
+        self.end_value();
+    }
+    fn on_int(&mut self, value: i64) {
+        if self.forward() {
+            self.inner.on_int(value);
+        }
+        self.end_value();
+    }
+    fn on_uint(&mut self, value: u64) {
+        if self.forward() {
+            self.inner.on_uint(value);
+        }
+        self.end_value();
+    }
+    fn on_big_int(&mut self, value: i128) {
+        if self.forward() {
+            self.inner.on_big_int(value);
+        }
+        self.end_value();
+    }
+    fn on_big_num(&mut self, value: &JsonPackBigInt) {
+        if self.forward() {
+            self.inner.on_big_num(value);
+        }
+        self.end_value();
+    }
+    fn on_float(&mut self, value: f64) {
+        if self.forward() {
+            self.inner.on_float(value);
+        }
+        self.end_value();
+    }
+    fn on_str(&mut self, value: &str) {
+        if self.forward() {
+            self.inner.on_str(&value.to_uppercase());
+        }
+        self.end_value();
+    }
+    fn on_bytes(&mut self, value: &[u8]) {
+        if self.forward() {
+            self.inner.on_bytes(value);
+        }
+        self.end_value();
+    }
+    fn on_array_start(&mut self, len: Option<usize>) {
+        self.depth += 1;
+        if self.forward() {
+            self.inner.on_array_start(len);
+        }
+    }
+    fn on_array_end(&mut self) {
+        self.depth -= 1;
+        if self.forward() {
+            self.inner.on_array_end();
+        }
+        self.end_value();
+    }
+    fn on_map_start(&mut self, _len: Option<usize>) {
+        self.depth += 1;
+        if self.forward() {
+            self.inner.on_map_start(None);
+        }
+    }
+    fn on_key(&mut self, key: &str) {
+        if !self.forward() {
+            return;
+        }
+        if key == "secret" {
+            self.skip = Some(self.depth);
+        } else {
+            self.inner.on_key(key);
+        }
+    }
+    fn on_map_end(&mut self) {
+        self.depth -= 1;
+        if self.forward() {
+            self.inner.on_map_end();
+        }
+        self.end_value();
+    }
+    fn on_tag(&mut self, tag: u64) {
+        if self.forward() {
+            self.inner.on_tag(tag);
+        }
+    }
+    fn on_raw(&mut self, bytes: &[u8]) {
+        if self.forward() {
+            self.inner.on_raw(bytes);
+        }
+        self.end_value();
+    }
+}
+
+impl<S> Redact<'_, S> {
+    fn end_value(&mut self) {
+        if self.skip == Some(self.depth) {
+            self.skip = None;
+        }
+    }
+}
+
+#[test]
+fn token_filter_matrix() {
+    let value = pack!({
+        "user": "ann",
+        "secret": { "pin": [1, 2], "note": "x" },
+        "list": [{ "secret": 1, "k": "v" }, "w"]
+    });
+    let expected = pack!({ "user": "ANN", "list": [{ "k": "V" }, "W"] });
+
+    let cbor = CborEncoder::new().encode(&value);
+    let mut out = MsgPackEncoder::new();
+    CborDecoder::new()
+        .parse_with(
+            &cbor,
+            &mut Redact {
+                inner: &mut out,
+                depth: 0,
+                skip: None,
+            },
+        )
+        .unwrap();
+    let bytes = out.inner.writer.flush();
+    assert_eq!(MsgPackDecoderFast::new().decode(&bytes).unwrap(), expected);
+
+    let mut out = JsonEncoder::new();
+    walk(
+        &value,
+        &mut Redact {
+            inner: &mut out,
+            depth: 0,
+            skip: None,
+        },
+    );
+    assert_eq!(
+        out.writer.flush(),
+        br#"{"user":"ANN","list":[{"k":"V"},"W"]}"#
+    );
+}
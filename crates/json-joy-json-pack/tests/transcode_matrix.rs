@@ -0,0 +1,211 @@
+//! Direct MessagePack <-> CBOR transcoding.
+//!
+//! Not an upstream port. Every case checks the transcoder against decoding
+//! to a `PackValue` and re-encoding with the default encoders.
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::cbor::{decode_cbor_value, CborEncoder, CborError};
+use json_joy_json_pack::msgpack::{
+    MsgPackDecoderFast, MsgPackEncoder, MsgPackEncoderOptions, MsgPackError,
+};
+use json_joy_json_pack::transcode::{cbor_to_msgpack, msgpack_to_cbor};
+use json_joy_json_pack::{pack, JsonPackExtension, PackValue};
+
+fn corpus() -> Vec<PackValue> {
+    vec![
+        pack!(null),
+        pack!(undefined),
+        pack!(false),
+        pack!(true),
+        pack!(0),
+        pack!(127),
+        pack!(-32),
+        pack!(-33),
+        pack!(255),
+        pack!(-129),
+        pack!(65_536),
+        pack!(i64::MIN),
+        pack!(i64::MAX),
+        pack!(u64::MAX),
+        pack!(1.5),
+        pack!(0.1),
+        pack!(f64::INFINITY),
+        pack!(""),
+        pack!("héllo ☃ 😀"),
+        pack!("x".repeat(40)),
+        pack!("y".repeat(70_000)),
+        pack!(b""),
+        pack!(vec![7u8; 300]),
+        pack!([]),
+        pack!([1, [2, [3, []]], { "a": null }]),
+        PackValue::Array((0..20).map(PackValue::from).collect()),
+        pack!({ "a": 1, "b": { "c": [true, b"\x01"] }, "": "" }),
+        PackValue::Object((0..40).map(|i| (format!("k{i}"), pack!(i))).collect()),
+        pack!(JsonPackExtension::new(5, pack!(b"\x01\x02\x03\x04"))),
+        pack!(JsonPackExtension::new(200, pack!(vec![9u8; 17]))),
+    ]
+}
+
+fn via_values_m2c(msgpack: &[u8]) -> Vec<u8> {
+    CborEncoder::new().encode(&MsgPackDecoderFast::new().decode(msgpack).unwrap())
+}
+
+fn via_values_c2m(cbor: &[u8]) -> Vec<u8> {
+    MsgPackEncoder::new().encode(&decode_cbor_value(cbor).unwrap())
+}
+
+fn m2c(msgpack: &[u8]) -> Vec<u8> {
+    let mut writer = Writer::new();
+    assert_eq!(
+        msgpack_to_cbor(msgpack, &mut writer).unwrap(),
+        msgpack.len()
+    );
+    writer.flush()
+}
+
+fn c2m(cbor: &[u8]) -> Vec<u8> {
+    let mut writer = Writer::new();
+    assert_eq!(cbor_to_msgpack(cbor, &mut writer).unwrap(), cbor.len());
+    writer.flush()
+}
+
+#[test]
+fn transcode_msgpack_to_cbor_matrix() {
+    let options = [
+        MsgPackEncoderOptions::default(),
+        MsgPackEncoderOptions {
+            smallest_int: true,
+            float32: true,
+            avoid_str8: true,
+            ..Default::default()
+        },
+    ];
+    for value in corpus() {
+        for options in &options {
+            let msgpack = MsgPackEncoder::with_options(options.clone()).encode(&value);
+            assert_eq!(m2c(&msgpack), via_values_m2c(&msgpack), "{value:?}");
+        }
+    }
+}
+
+#[test]
+fn transcode_cbor_to_msgpack_matrix() {
+    for value in corpus() {
+        let cbor = CborEncoder::new().encode(&value);
+        assert_eq!(c2m(&cbor), via_values_c2m(&cbor), "{value:?}");
+    }
+
+    // Encodings the CBOR encoder never produces.
+    let cases: [&[u8]; 18] = [
+        // Indefinite-length array, map, byte and text strings.
+        &[0x9f, 0x01, 0x9f, 0xff, 0x82, 0x02, 0x03, 0xff],
+        &[
+            0x9f, 0x9f, 0x9f, 0x01, 0xff, 0x81, 0x9f, 0x02, 0x03, 0xff, 0xff, 0xc6, 0x9f, 0xff,
+            0xff,
+        ],
+        // Indefinite-length containers inside a non-text key.
+        &[0xbf, 0x9f, 0x01, 0xff, 0x9f, 0x02, 0x03, 0xff, 0xff],
+        &[0xbf, 0x61, b'a', 0x01, 0x61, b'b', 0x9f, 0xff, 0xff],
+        &[0x5f, 0x42, 1, 2, 0x41, 3, 0xff],
+        &[0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff],
+        // Half, single and double precision floats.
+        &[0xf9, 0x3c, 0x00],
+        &[0xfa, 0x3f, 0xc0, 0x00, 0x00],
+        &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+        // Non-text map keys.
+        &[0xa3, 0x01, 0x02, 0xf5, 0x03, 0xf6, 0x04],
+        // Negative integer below i64::MIN.
+        &[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        // Tags over bytes, over other values, and nested.
+        &[0xc2, 0x42, 0x01, 0x00],
+        &[0xd8, 0x20, 0x63, b'u', b'r', b'i'],
+        &[0xc1, 0xc6, 0x43, 1, 2, 3],
+        &[0xc6, 0x5f, 0x41, 1, 0xff],
+        // Tags past the largest extension type.
+        &[0xd8, 0x80, 0x42, 1, 2],
+        &[0xd9, 0x01, 0x01, 0x41, 1],
+        // Simple values.
+        &[0x82, 0xf0, 0xf8, 0x20],
+    ];
+    for cbor in cases {
+        assert_eq!(c2m(cbor), via_values_c2m(cbor), "{cbor:x?}");
+    }
+
+    // The nested indefinite-length arrays keep their item counts.
+    let cbor = [
+        0x9f, 0x9f, 0x01, 0x9f, 0xff, 0xff, 0x9f, 0x02, 0x03, 0xff, 0xff,
+    ];
+    assert_eq!(c2m(&cbor), [0x92, 0x92, 0x01, 0x90, 0x92, 0x02, 0x03]);
+    // Tag 128 does not fit an extension type: it is dropped, not wrapped
+    // to type -128.
+    assert_eq!(c2m(&[0xd8, 0x80, 0x42, 1, 2]), [0xc4, 0x02, 1, 2]);
+    assert_eq!(c2m(&[0xd8, 0x7f, 0x42, 1, 2]), [0xd5, 0x7f, 1, 2]);
+}
+
+#[test]
+fn transcode_stream_matrix() {
+    // Only the first value is consumed, and output is appended.
+    let mut writer = Writer::new();
+    writer.u8(0xaa);
+    assert_eq!(cbor_to_msgpack(&[0x01, 0x02], &mut writer).unwrap(), 1);
+    assert_eq!(msgpack_to_cbor(&[0xc3, 0xc2], &mut writer).unwrap(), 1);
+    assert_eq!(writer.flush(), [0xaa, 0x01, 0xf5]);
+
+    // Round trip through both directions.
+    let value = pack!({ "id": 1, "tags": ["a", "b"], "blob": b"\x00\xff", "f": 2.5 });
+    let msgpack = MsgPackEncoder::new().encode(&value);
+    let cbor = m2c(&msgpack);
+    assert_eq!(decode_cbor_value(&cbor).unwrap(), value);
+    assert_eq!(c2m(&cbor), msgpack);
+}
+
+#[test]
+fn transcode_error_matrix() {
+    let msgpack: [(&[u8], MsgPackError); 5] = [
+        (&[], MsgPackError::UnexpectedEof(0)),
+        (&[0x92, 0x01], MsgPackError::UnexpectedEof(2)),
+        (&[0xa2, 0xff, 0xfe], MsgPackError::InvalidUtf8(1)),
+        (&[0x81, 0x01, 0x01], MsgPackError::NotStr),
+        (
+            &[
+                0x81, 0xa9, b'_', b'_', b'p', b'r', b'o', b't', b'o', b'_', b'_', 0xc0,
+            ],
+            MsgPackError::InvalidKey,
+        ),
+    ];
+    // `MsgPackError` is not `PartialEq`; compare the messages.
+    for (bytes, error) in msgpack {
+        let mut writer = Writer::new();
+        writer.u8(0xaa);
+        let err = msgpack_to_cbor(bytes, &mut writer).unwrap_err();
+        assert_eq!(err.to_string(), error.to_string(), "{bytes:x?}");
+        assert!(MsgPackDecoderFast::new().decode(bytes).is_err());
+        // Partial output is discarded.
+        assert_eq!(writer.flush(), [0xaa]);
+    }
+
+    let cbor: [(&[u8], CborError); 6] = [
+        (&[], CborError::UnexpectedEof(0)),
+        (&[0x82, 0x01], CborError::UnexpectedEof(2)),
+        (&[0x9f, 0x01], CborError::UnexpectedEof(2)),
+        (&[0xbf, 0x01, 0xff], CborError::UnexpectedObjBreak(2)),
+        (&[0xff], CborError::UnexpectedMinor(0)),
+        (
+            &[
+                0xa1, 0x69, b'_', b'_', b'p', b'r', b'o', b't', b'o', b'_', b'_', 0xf6,
+            ],
+            CborError::UnexpectedObjKey(1),
+        ),
+    ];
+    for (bytes, error) in cbor {
+        let mut writer = Writer::new();
+        writer.u8(0xaa);
+        assert_eq!(
+            cbor_to_msgpack(bytes, &mut writer),
+            Err(error.clone()),
+            "{bytes:x?}"
+        );
+        assert_eq!(decode_cbor_value(bytes), Err(error));
+        assert_eq!(writer.flush(), [0xaa]);
+    }
+}
@@ -0,0 +1,326 @@
+//! `try_encode` refusing values an encoder would silently degrade.
+//!
+//! Not an upstream port: upstream encoders always coerce.
+
+use json_joy_json_pack::avro::AvroEncoder;
+use json_joy_json_pack::cbor::{CborEncoderDag, CborEncoderFast};
+use json_joy_json_pack::checked::{EncodeLossError, LossKind};
+use json_joy_json_pack::ion::IonEncoder;
+use json_joy_json_pack::json::{JsonEncoder, JsonEncoderDag, JsonEncoderStable};
+use json_joy_json_pack::msgpack::{
+    MsgPackEncoder, MsgPackEncoderFast, MsgPackEncoderOptions, MsgPackExtCodec, MsgPackExtRegistry,
+};
+use json_joy_json_pack::ubjson::{UbjsonEncoder, UbjsonEncoderOptions};
+use json_joy_json_pack::{pack, JsonPackBigInt, JsonPackExtension, JsonPackValue, PackValue};
+
+fn nested(value: PackValue) -> PackValue {
+    pack!({"ok": [1, "x"], "a/b": [{"c~d": [true, (value)]}]})
+}
+
+const PATH: &str = "/a~1b/0/c~0d/1";
+
+fn ext(tag: u64, val: PackValue) -> PackValue {
+    PackValue::Extension(Box::new(JsonPackExtension::new(tag, val)))
+}
+
+fn loss(path: &str, kind: LossKind) -> EncodeLossError {
+    EncodeLossError {
+        path: path.into(),
+        kind,
+    }
+}
+
+#[test]
+fn try_encode_json_matrix() {
+    let degraded = [
+        (PackValue::Float(f64::NAN), LossKind::NonFiniteFloat),
+        (
+            PackValue::Float(f64::NEG_INFINITY),
+            LossKind::NonFiniteFloat,
+        ),
+        (ext(1, PackValue::Bytes(vec![1])), LossKind::Extension),
+        (
+            PackValue::Blob(JsonPackValue::new(b"1".to_vec())),
+            LossKind::Blob,
+        ),
+    ];
+    for (value, kind) in degraded {
+        let doc = nested(value.clone());
+        assert_eq!(JsonEncoder::new().try_encode(&doc), Err(loss(PATH, kind)));
+        assert_eq!(
+            JsonEncoderStable::new().try_encode(&doc),
+            Err(loss(PATH, kind))
+        );
+        assert_eq!(
+            JsonEncoderDag::new().try_encode(&doc),
+            Err(loss(PATH, kind))
+        );
+        assert_eq!(JsonEncoder::new().try_encode(&value), Err(loss("", kind)));
+    }
+
+    // Representable values encode exactly as `encode` does.
+    let doc = nested(pack!({
+        "big": (PackValue::BigInt(i128::MAX)),
+        "bytes": (PackValue::Bytes(vec![0, 255])),
+        "undef": (PackValue::Undefined),
+        "f": 1.5
+    }));
+    let mut json = JsonEncoder::new();
+    assert_eq!(json.try_encode(&doc).unwrap(), json.encode(&doc));
+    let mut stable = JsonEncoderStable::new();
+    assert_eq!(stable.try_encode(&doc).unwrap(), stable.encode(&doc));
+    let mut dag = JsonEncoderDag::new();
+    assert_eq!(dag.try_encode(&doc).unwrap(), dag.encode(&doc));
+}
+
+#[test]
+fn try_encode_dag_cbor_matrix() {
+    let big = JsonPackBigInt::new(false, &[1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let degraded = [
+        (PackValue::Undefined, LossKind::Undefined),
+        (PackValue::Float(f64::INFINITY), LossKind::NonFiniteFloat),
+        (PackValue::BigInt(u64::MAX as i128 + 1), LossKind::BigInt),
+        (PackValue::BigInt(-(1i128 << 64) - 1), LossKind::BigInt),
+        (PackValue::BigNum(big), LossKind::BigInt),
+        (ext(0, pack!("2024-01-01")), LossKind::Tag(0)),
+    ];
+    let mut encoder = CborEncoderDag::new();
+    for (value, kind) in degraded {
+        assert_eq!(encoder.try_encode(&nested(value)), Err(loss(PATH, kind)));
+    }
+    // A loss inside a CID tag is found at the tag's path.
+    assert_eq!(
+        encoder.try_encode(&nested(ext(42, PackValue::Float(f64::NAN)))),
+        Err(loss(PATH, LossKind::NonFiniteFloat))
+    );
+
+    let doc = nested(pack!([
+        (ext(42, PackValue::Bytes(vec![0, 1]))),
+        (PackValue::BigInt(-(1i128 << 64))),
+        (PackValue::UInteger(u64::MAX)),
+        (PackValue::Blob(JsonPackValue::new(vec![0xf6])))
+    ]));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+}
+
+#[test]
+fn try_encode_msgpack_matrix() {
+    let degraded = [
+        PackValue::Integer(i64::MIN + 1),
+        PackValue::UInteger(u64::MAX),
+        PackValue::UInteger((1 << 53) + 1),
+        PackValue::BigInt(i128::MAX),
+    ];
+    let mut encoder = MsgPackEncoderFast::new();
+    for value in degraded {
+        assert_eq!(
+            encoder.try_encode(&nested(value)),
+            Err(loss(PATH, LossKind::BigInt))
+        );
+    }
+    // Types are signed bytes: 128..=255 would be truncated.
+    let bad_ext = [
+        ext(256, PackValue::Bytes(vec![1])),
+        ext(255, PackValue::Bytes(vec![1])),
+        ext(-129i64 as u64, PackValue::Bytes(vec![1])),
+        ext(1, pack!("s")),
+    ];
+    for value in bad_ext {
+        assert_eq!(
+            encoder.try_encode(&nested(value)),
+            Err(loss(PATH, LossKind::Extension))
+        );
+    }
+
+    // Integers a float64 holds exactly are fine, as are floats, NaN included.
+    let doc = nested(pack!([
+        (PackValue::UInteger(1 << 53)),
+        (PackValue::Integer(i64::MIN)),
+        (PackValue::BigInt(-(1i128 << 100))),
+        (PackValue::Float(f64::NAN)),
+        (ext(-1i64 as u64, PackValue::Bytes(vec![1]))),
+        (ext(127, PackValue::Bytes(vec![1]))),
+        (PackValue::Undefined)
+    ]));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+
+    assert_eq!(
+        loss(PATH, LossKind::Tag(7)).to_string(),
+        format!("cannot encode tag 7 at `{PATH}` without loss")
+    );
+}
+
+#[test]
+fn try_encode_msgpack_options_matrix() {
+    let big = [
+        PackValue::UInteger(u64::MAX),
+        PackValue::Integer(i64::MIN + 1),
+        PackValue::BigInt(u64::MAX as i128),
+    ];
+    // Upstream's integer fallback rounds these; `smallest_int` keeps them.
+    let mut encoder = MsgPackEncoder::new();
+    for value in big.clone() {
+        assert_eq!(
+            encoder.try_encode(&nested(value)),
+            Err(loss(PATH, LossKind::BigInt))
+        );
+    }
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        smallest_int: true,
+        ..Default::default()
+    });
+    let doc = nested(PackValue::Array(big.to_vec()));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+    assert_eq!(
+        encoder.try_encode(&nested(PackValue::BigInt((1i128 << 64) + 1))),
+        Err(loss(PATH, LossKind::BigInt))
+    );
+
+    // A registered codec carries a non-byte value; an unregistered type or
+    // a value the codec refuses would drop the tag.
+    let codec = MsgPackExtCodec {
+        decode: |data| Some(PackValue::Str(String::from_utf8(data.to_vec()).ok()?)),
+        encode: |value| match value {
+            PackValue::Str(s) => Some(s.as_bytes().to_vec()),
+            _ => None,
+        },
+    };
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        ext_registry: MsgPackExtRegistry::new().with(-3, codec),
+        ..Default::default()
+    });
+    let doc = nested(ext(-3i64 as u64, pack!("s")));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+    for value in [
+        ext(-3i64 as u64, pack!(1)),
+        ext(3, pack!("s")),
+        ext(253, pack!("s")),
+    ] {
+        assert_eq!(
+            encoder.try_encode(&nested(value)),
+            Err(loss(PATH, LossKind::Extension))
+        );
+    }
+}
+
+#[test]
+fn try_encode_cbor_fast_matrix() {
+    let mut encoder = CborEncoderFast::new();
+    assert_eq!(
+        encoder.try_encode(&nested(PackValue::Undefined)),
+        Err(loss(PATH, LossKind::Undefined))
+    );
+    let doc = nested(pack!([(PackValue::Float(f64::NAN)), "x", null]));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+}
+
+#[test]
+fn try_encode_ubjson_matrix() {
+    let degraded = [
+        (PackValue::UInteger(u64::MAX), LossKind::BigInt),
+        (PackValue::BigInt(i64::MIN as i128 - 1), LossKind::BigInt),
+        (ext(1, PackValue::Bytes(vec![1])), LossKind::Extension),
+        (PackValue::Blob(JsonPackValue::new(vec![0])), LossKind::Blob),
+    ];
+    let mut encoder = UbjsonEncoder::new();
+    for (value, kind) in degraded {
+        assert_eq!(encoder.try_encode(&nested(value)), Err(loss(PATH, kind)));
+    }
+    assert_eq!(
+        encoder.try_encode(&PackValue::Map(vec![(pack!([1]), pack!(1))])),
+        Err(loss("/", LossKind::NonTextKey))
+    );
+
+    // `H` holds any integer.
+    let mut encoder = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        high_precision: true,
+        ..Default::default()
+    });
+    let doc = nested(pack!([
+        (PackValue::UInteger(u64::MAX)),
+        (PackValue::BigInt(i128::MIN)),
+        (PackValue::BigNum(JsonPackBigInt::new(true, &[1; 20])))
+    ]));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+}
+
+#[test]
+fn try_encode_ion_matrix() {
+    let degraded = [
+        (PackValue::Undefined, LossKind::Undefined),
+        (ext(1, PackValue::Bytes(vec![1])), LossKind::Extension),
+        (PackValue::Blob(JsonPackValue::new(vec![0])), LossKind::Blob),
+    ];
+    let mut encoder = IonEncoder::new();
+    for (value, kind) in degraded {
+        assert_eq!(encoder.try_encode(&nested(value)), Err(loss(PATH, kind)));
+    }
+    let doc = nested(pack!([
+        (PackValue::UInteger(u64::MAX)),
+        (PackValue::BigInt(i128::MIN)),
+        (PackValue::Bytes(vec![1, 2]))
+    ]));
+    assert_eq!(encoder.try_encode(&doc).unwrap(), encoder.encode(&doc));
+}
+
+#[test]
+fn try_encode_avro_matrix() {
+    let big = JsonPackBigInt::new(true, &[1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let degraded = [
+        (PackValue::UInteger(u64::MAX), LossKind::BigInt),
+        (PackValue::BigInt(i64::MAX as i128 + 1), LossKind::BigInt),
+        (PackValue::BigNum(big), LossKind::BigInt),
+        (PackValue::Undefined, LossKind::Undefined),
+        (ext(1, PackValue::Bytes(vec![1])), LossKind::Extension),
+        (PackValue::Blob(JsonPackValue::new(vec![0])), LossKind::Blob),
+    ];
+    let mut encoder = AvroEncoder::new();
+    for (value, kind) in degraded {
+        assert_eq!(encoder.try_encode(&nested(value)), Err(loss(PATH, kind)));
+    }
+    assert_eq!(
+        encoder.try_encode(&PackValue::Map(vec![(pack!([1]), pack!(1))])),
+        Err(loss("/", LossKind::NonTextKey))
+    );
+
+    let doc = nested(pack!([
+        (PackValue::UInteger(i64::MAX as u64)),
+        (PackValue::BigInt(i64::MIN as i128)),
+        (PackValue::BigNum(JsonPackBigInt::from_i128(-5))),
+        (PackValue::Float(f64::NAN))
+    ]));
+    let bytes = encoder.try_encode(&doc).unwrap();
+    encoder.write_any(&doc);
+    assert_eq!(bytes, encoder.writer.flush());
+}
+
+#[test]
+fn try_encode_map_key_matrix() {
+    let map = |key: PackValue, value: PackValue| PackValue::Map(vec![(key, value)]);
+
+    // Keys without a text form would be written as `""`.
+    for key in [
+        PackValue::Bytes(vec![1]),
+        pack!([1]),
+        map(pack!(1), pack!(2)),
+    ] {
+        let doc = nested(map(key, pack!(1)));
+        let err = Err(loss(&format!("{PATH}/"), LossKind::NonTextKey));
+        assert_eq!(JsonEncoder::new().try_encode(&doc), err);
+        assert_eq!(JsonEncoderStable::new().try_encode(&doc), err);
+        assert_eq!(JsonEncoderDag::new().try_encode(&doc), err);
+        assert_eq!(CborEncoderDag::new().try_encode(&doc), err);
+        let mut msgpack = MsgPackEncoderFast::new();
+        assert_eq!(msgpack.try_encode(&doc).unwrap(), msgpack.encode(&doc));
+    }
+
+    // Scalar keys are written as text, and map values are checked too.
+    let doc = map(pack!(7), map(pack!(true), PackValue::Float(f64::NAN)));
+    assert_eq!(
+        JsonEncoder::new().try_encode(&doc),
+        Err(loss("/7/true", LossKind::NonFiniteFloat))
+    );
+    let doc = map(PackValue::Null, pack!("x"));
+    let mut json = JsonEncoder::new();
+    assert_eq!(json.try_encode(&doc).unwrap(), br#"{"null":"x"}"#);
+}
@@ -0,0 +1,97 @@
+//! BJData byte order and its unsigned and half-precision markers.
+//!
+//! Not an upstream port: upstream only reads and writes big-endian UBJSON.
+
+use json_joy_json_pack::ubjson::{
+    UbjsonDecoder, UbjsonDecoderOptions, UbjsonEncoder, UbjsonEncoderOptions, UbjsonError,
+};
+use json_joy_json_pack::{pack, PackValue};
+
+fn bjdata_encoder() -> UbjsonEncoder {
+    UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        bjdata: true,
+        ..Default::default()
+    })
+}
+
+fn bjdata_decoder() -> UbjsonDecoder {
+    UbjsonDecoder::with_options(UbjsonDecoderOptions {
+        bjdata: true,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn ubjson_bjdata_little_endian_matrix() {
+    let cases: Vec<(PackValue, Vec<u8>)> = vec![
+        (PackValue::Integer(0x1234), vec![b'I', 0x34, 0x12]),
+        (
+            PackValue::Integer(-0x1234_5678),
+            vec![b'l', 0x88, 0xa9, 0xcb, 0xed],
+        ),
+        (
+            PackValue::Integer(0x0102_0304_0506_0708),
+            vec![b'L', 8, 7, 6, 5, 4, 3, 2, 1],
+        ),
+        (
+            PackValue::Float(1.5),
+            vec![b'D', 0, 0, 0, 0, 0, 0, 0xf8, 0x3f],
+        ),
+    ];
+    for (value, bytes) in cases {
+        assert_eq!(bjdata_encoder().encode(&value), bytes, "{value:?}");
+        assert_eq!(bjdata_decoder().decode(&bytes).unwrap(), value);
+        // The same value in UBJSON has the bytes reversed.
+        let ubjson = UbjsonEncoder::new().encode(&value);
+        assert_eq!(ubjson[0], bytes[0]);
+        assert!(ubjson[1..].iter().eq(bytes[1..].iter().rev()));
+    }
+
+    // Long strings take an `l` length, little-endian too.
+    let long = "x".repeat(300);
+    let bytes = bjdata_encoder().encode(&PackValue::Str(long.clone()));
+    assert_eq!(bytes[..6], [b'S', b'l', 0x2c, 0x01, 0, 0]);
+    assert_eq!(
+        bjdata_decoder().decode(&bytes).unwrap(),
+        PackValue::Str(long)
+    );
+
+    let value = pack!({"a": [1, -300, 70000, 2.5, "b"], "c": null});
+    let bytes = bjdata_encoder().encode(&value);
+    assert_eq!(bjdata_decoder().decode(&bytes).unwrap(), value);
+    assert_ne!(UbjsonDecoder::new().decode(&bytes).ok(), Some(value));
+}
+
+#[test]
+fn ubjson_bjdata_markers_matrix() {
+    let decoder = bjdata_decoder();
+    let cases: Vec<(&[u8], PackValue)> = vec![
+        (b"u\x34\x12", PackValue::Integer(0x1234)),
+        (b"m\x78\x56\x34\x12", PackValue::Integer(0x1234_5678)),
+        (b"M\x01\0\0\0\0\0\0\0", PackValue::Integer(1)),
+        (
+            b"M\xff\xff\xff\xff\xff\xff\xff\xff",
+            PackValue::UInteger(u64::MAX),
+        ),
+        (b"h\x00\x3e", PackValue::Float(1.5)),
+        (b"h\x00\xfc", PackValue::Float(f64::NEG_INFINITY)),
+    ];
+    for (bytes, value) in cases {
+        assert_eq!(decoder.decode(bytes).unwrap(), value, "{bytes:02x?}");
+        assert!(
+            matches!(
+                UbjsonDecoder::new().decode(bytes),
+                Err(UbjsonError::UnexpectedByte(_, 0))
+            ),
+            "{bytes:02x?}"
+        );
+    }
+
+    // Typed arrays of the unsigned markers keep their item widths.
+    let PackValue::Extension(ext) = decoder.decode(b"[$m#U\x02\x01\0\0\0\x02\0\0\0").unwrap()
+    else {
+        panic!("not a typed array");
+    };
+    assert_eq!(ext.tag, b'm' as u64);
+    assert_eq!(*ext.val, PackValue::Bytes(vec![1, 0, 0, 0, 2, 0, 0, 0]));
+}
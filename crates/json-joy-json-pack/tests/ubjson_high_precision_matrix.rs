@@ -0,0 +1,173 @@
+//! UBJSON `H` high-precision numbers and `C` chars.
+//!
+//! Not an upstream port: upstream neither emits nor reads `H`, and never
+//! emits `C`.
+
+use json_joy_json_pack::ubjson::{
+    UbjsonDecoder, UbjsonDecoderOptions, UbjsonEncoder, UbjsonEncoderOptions, UbjsonError,
+    UbjsonHighPrecisionPolicy,
+};
+use json_joy_json_pack::{JsonPackBigInt, PackValue};
+
+fn high_precision(digits: &str) -> Vec<u8> {
+    let mut out = vec![b'H', b'U', digits.len() as u8];
+    out.extend_from_slice(digits.as_bytes());
+    out
+}
+
+fn digits_of(value: &PackValue) -> String {
+    match value {
+        PackValue::UInteger(u) => u.to_string(),
+        PackValue::BigInt(i) => i.to_string(),
+        PackValue::BigNum(n) => n.to_string(),
+        other => panic!("not a big integer: {other:?}"),
+    }
+}
+
+#[test]
+fn ubjson_high_precision_encode_matrix() {
+    let big = "-123456789012345678901234567890123456789012";
+    let cases = [
+        (PackValue::UInteger(u64::MAX), "18446744073709551615"),
+        (
+            PackValue::BigInt(i128::MIN),
+            "-170141183460469231731687303715884105728",
+        ),
+        (PackValue::BigNum(big.parse().unwrap()), big),
+    ];
+    let mut encoder = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        high_precision: true,
+        ..UbjsonEncoderOptions::default()
+    });
+    let decoder = UbjsonDecoder::new();
+    for (value, digits) in cases {
+        let bytes = encoder.encode(&value);
+        assert_eq!(bytes, high_precision(digits), "{value:?}");
+        assert_eq!(digits_of(&decoder.decode(&bytes).unwrap()), digits);
+    }
+
+    // Values that fit in i64 keep their native integer markers.
+    assert_eq!(encoder.encode(&PackValue::BigInt(-1)), [b'i', 0xff]);
+    assert_eq!(
+        encoder.encode(&PackValue::BigNum(JsonPackBigInt::from_i128(300))),
+        [b'I', 0x01, 0x2c]
+    );
+
+    // Off by default: out-of-range integers clamp as upstream does.
+    let clamped = UbjsonEncoder::new().encode(&PackValue::BigInt(i128::MAX));
+    assert_eq!(clamped[0], b'L');
+    // Big numbers have no upstream form and always keep their digits.
+    for digits in [big, "170141183460469231731687303715884105727"] {
+        let value = PackValue::BigNum(digits.parse().unwrap());
+        let bytes = UbjsonEncoder::new().encode(&value);
+        assert_eq!(bytes, high_precision(digits));
+        assert_eq!(digits_of(&decoder.decode(&bytes).unwrap()), digits);
+    }
+}
+
+#[test]
+fn ubjson_high_precision_decode_matrix() {
+    let decoder = UbjsonDecoder::new();
+    let cases = [
+        ("42", PackValue::Integer(42)),
+        ("-9223372036854775808", PackValue::Integer(i64::MIN)),
+        ("18446744073709551615", PackValue::UInteger(u64::MAX)),
+        ("-18446744073709551616", PackValue::BigInt(-(1i128 << 64))),
+        (
+            "1234567890123456789012345678901234567890123",
+            PackValue::BigNum(
+                "1234567890123456789012345678901234567890123"
+                    .parse()
+                    .unwrap(),
+            ),
+        ),
+        (
+            "3.141592653589793238462643383279",
+            PackValue::Float(std::f64::consts::PI),
+        ),
+        ("-1.5e300", PackValue::Float(-1.5e300)),
+        ("-0.5", PackValue::Float(-0.5)),
+        ("0.000001", PackValue::Float(1e-6)),
+        ("1E+2", PackValue::Float(100.0)),
+        ("25e-1", PackValue::Float(2.5)),
+        ("0", PackValue::Integer(0)),
+    ];
+    for (digits, expected) in cases {
+        assert_eq!(
+            decoder.decode(&high_precision(digits)).unwrap(),
+            expected,
+            "{digits}"
+        );
+    }
+
+    // Inside containers.
+    let mut bytes = vec![b'['];
+    bytes.extend(high_precision("1.25"));
+    bytes.push(b']');
+    assert_eq!(
+        decoder.decode(&bytes).unwrap(),
+        PackValue::Array(vec![PackValue::Float(1.25)])
+    );
+
+    let raw = UbjsonDecoder::with_options(UbjsonDecoderOptions {
+        high_precision: UbjsonHighPrecisionPolicy::String,
+        ..UbjsonDecoderOptions::default()
+    });
+    let digits = "3.141592653589793238462643383279";
+    assert_eq!(
+        raw.decode(&high_precision(digits)).unwrap(),
+        PackValue::Str(digits.into())
+    );
+    // The raw policy does not validate the digits.
+    assert_eq!(
+        raw.decode(&high_precision("NaN")).unwrap(),
+        PackValue::Str("NaN".into())
+    );
+
+    let bad = [
+        "", "-", "NaN", "inf", "1x", "+1", ".5", "1_000", "1.", "1e", "1e+", "01", "-01.5",
+        "1.5.5", "1e5e5", "--1", "1-",
+    ];
+    for bad in bad {
+        assert!(
+            matches!(
+                decoder.decode(&high_precision(bad)),
+                Err(UbjsonError::InvalidNumber(3))
+            ),
+            "{bad:?}"
+        );
+    }
+    assert!(matches!(
+        decoder.decode(&[b'H', b'U', 5, b'1']),
+        Err(UbjsonError::UnexpectedEof(3))
+    ));
+}
+
+#[test]
+fn ubjson_char_matrix() {
+    let mut encoder = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        chars: true,
+        ..UbjsonEncoderOptions::default()
+    });
+    let decoder = UbjsonDecoder::new();
+    let value = PackValue::Object(vec![
+        ("k".into(), PackValue::Str("a".into())),
+        ("e".into(), PackValue::Str("é".into())),
+        ("s".into(), PackValue::Str("ab".into())),
+    ]);
+    let bytes = encoder.encode(&value);
+    assert_eq!(
+        bytes,
+        [
+            b'{', b'U', 1, b'k', b'C', b'a', b'U', 1, b'e', b'S', b'U', 2, 0xc3, 0xa9, b'U', 1,
+            b's', b'S', b'U', 2, b'a', b'b', b'}',
+        ]
+    );
+    assert_eq!(decoder.decode(&bytes).unwrap(), value);
+
+    // Off by default.
+    assert_eq!(
+        UbjsonEncoder::new().encode(&PackValue::Str("a".into())),
+        [b'S', b'U', 1, b'a']
+    );
+}
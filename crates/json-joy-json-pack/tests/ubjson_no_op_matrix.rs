@@ -0,0 +1,135 @@
+//! UBJSON `N` no-ops and unknown-marker tolerance.
+//!
+//! Not an upstream port: upstream decodes `N` as `undefined` and never
+//! writes no-ops.
+
+use json_joy_json_pack::ubjson::{
+    UbjsonDecoder, UbjsonDecoderOptions, UbjsonEncoder, UbjsonEncoderOptions, UbjsonError,
+    UbjsonNoOpPolicy,
+};
+use json_joy_json_pack::{pack, PackValue};
+
+fn skipping() -> UbjsonDecoder {
+    UbjsonDecoder::with_options(UbjsonDecoderOptions {
+        no_op: UbjsonNoOpPolicy::Skip,
+        ..UbjsonDecoderOptions::default()
+    })
+}
+
+#[test]
+fn ubjson_no_op_default_is_undefined() {
+    let decoder = UbjsonDecoder::new();
+    assert_eq!(decoder.decode(b"N").unwrap(), PackValue::Undefined);
+    assert_eq!(
+        decoder.decode(b"[NN]").unwrap(),
+        PackValue::Array(vec![PackValue::Undefined, PackValue::Undefined])
+    );
+    // A no-op in a key position is not a length.
+    assert!(matches!(
+        decoder.decode(b"{Ni\x01aT}"),
+        Err(UbjsonError::UnexpectedByte(b'N', 1))
+    ));
+}
+
+#[test]
+fn ubjson_no_op_skip_matrix() {
+    let decoder = skipping();
+    for (input, expected) in [
+        (&b"NNT"[..], PackValue::Bool(true)),
+        (b"[N]", pack!([])),
+        (b"[NNi\x01NNi\x02N]", pack!([1, 2])),
+        (b"[N[N]N]", pack!([[]])),
+        (b"{N}", pack!({})),
+        (b"{Ni\x01aNNTN}", pack!({"a": true})),
+        (b"{i\x01a{Ni\x01bNZ}N}", pack!({"a": {"b": null}})),
+    ] {
+        assert_eq!(decoder.decode(input).unwrap(), expected, "{input:?}");
+    }
+
+    // Only no-ops: there is no value.
+    for input in [&b""[..], b"N", b"NNN"] {
+        assert!(
+            matches!(decoder.decode(input), Err(UbjsonError::UnexpectedEof(_))),
+            "{input:?}"
+        );
+    }
+    assert!(matches!(
+        decoder.decode(b"[NN"),
+        Err(UbjsonError::UnexpectedEof(3))
+    ));
+    // Lengths are never no-ops.
+    assert!(matches!(
+        decoder.decode(b"SNi\x01a"),
+        Err(UbjsonError::UnexpectedByte(b'N', 1))
+    ));
+    assert!(matches!(
+        decoder.decode(b"[#Ni\x01i\x05"),
+        Err(UbjsonError::UnexpectedByte(b'N', 2))
+    ));
+}
+
+#[test]
+fn ubjson_skip_unknown_markers_matrix() {
+    let lenient = UbjsonDecoder::with_options(UbjsonDecoderOptions {
+        skip_unknown_markers: true,
+        ..UbjsonDecoderOptions::default()
+    });
+    for (input, expected) in [
+        (&b"\x00\x0aT"[..], PackValue::Bool(true)),
+        (b"[\x00i\x01 i\x02\n]", pack!([1, 2])),
+        (b"{ i\x01a\x00F }", pack!({"a": false})),
+    ] {
+        assert_eq!(lenient.decode(input).unwrap(), expected, "{input:?}");
+        assert!(UbjsonDecoder::new().decode(input).is_err(), "{input:?}");
+    }
+    // `N` is still a value unless the no-op policy skips it.
+    assert_eq!(lenient.decode(b" N").unwrap(), PackValue::Undefined);
+    assert!(matches!(
+        UbjsonDecoder::new().decode(b"[\x00]"),
+        Err(UbjsonError::UnexpectedByte(0x00, 1))
+    ));
+    // Closers are never skipped.
+    assert!(matches!(
+        lenient.decode(b"]"),
+        Err(UbjsonError::UnexpectedByte(b']', 0))
+    ));
+    assert!(matches!(
+        lenient.decode(b"\x00\x01"),
+        Err(UbjsonError::UnexpectedEof(2))
+    ));
+}
+
+#[test]
+fn ubjson_no_op_encoder_matrix() {
+    let value = pack!({"a": [1, 2, 3], "b": "x"});
+    let mut encoder = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        no_op_every: Some(2),
+        ..UbjsonEncoderOptions::default()
+    });
+    let bytes = encoder.encode(&value);
+    assert_eq!(bytes, b"{U\x01a[U\x01U\x02NU\x03]U\x01bSU\x01xN}".to_vec());
+    assert_eq!(skipping().decode(&bytes).unwrap(), value);
+
+    let json = serde_json::json!([[1], 2, 3, 4]);
+    let bytes = encoder.encode_json(&json);
+    assert_eq!(skipping().decode(&bytes).unwrap(), PackValue::from(&json));
+    assert_eq!(bytes.iter().filter(|&&b| b == b'N').count(), 2);
+
+    // Zero disables padding, like `None`.
+    let mut encoder = UbjsonEncoder::with_options(UbjsonEncoderOptions {
+        no_op_every: Some(0),
+        ..UbjsonEncoderOptions::default()
+    });
+    assert_eq!(encoder.encode(&value), UbjsonEncoder::new().encode(&value));
+
+    // Keep-alives between values of a stream.
+    let mut encoder = UbjsonEncoder::new();
+    encoder.write_no_op();
+    encoder.write_start_arr();
+    encoder.write_no_op();
+    encoder.write_end_arr();
+    encoder.write_no_op();
+    let bytes = encoder.writer.flush();
+    assert_eq!(bytes, b"N[N]N");
+    assert_eq!(skipping().decode(&bytes[..4]).unwrap(), pack!([]));
+}
@@ -137,13 +137,13 @@ fn avro_decoder_matrix() {
     decoder.reset(&[0x80, 0x80, 0x80, 0x80, 0x80]);
     assert_eq!(
         decoder.read_int().unwrap_err(),
-        AvroDecodeError::VarIntTooLong
+        AvroDecodeError::VarIntTooLong(0)
     );
 
     decoder.reset(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80]);
     assert_eq!(
         decoder.read_long().unwrap_err(),
-        AvroDecodeError::VarLongTooLong
+        AvroDecodeError::VarLongTooLong(0)
     );
 
     // Union index is zigzag int; -1 is invalid for union index.
@@ -163,11 +163,11 @@ fn bencode_decode_error_matrix() {
 
     assert!(matches!(
         decoder.decode(b""),
-        Err(BencodeError::UnexpectedEof)
+        Err(BencodeError::UnexpectedEof(0))
     ));
     assert!(matches!(
         decoder.decode(b"i"),
-        Err(BencodeError::UnexpectedEof)
+        Err(BencodeError::UnexpectedEof(1))
     ));
     assert!(matches!(
         decoder.decode(b"d9:__proto__1:ae"),
@@ -175,10 +175,10 @@ fn bencode_decode_error_matrix() {
     ));
     assert!(matches!(
         decoder.decode(b"1"),
-        Err(BencodeError::UnexpectedEof)
+        Err(BencodeError::UnexpectedEof(1))
     ));
     assert!(matches!(
         decoder.decode(b"2:a"),
-        Err(BencodeError::UnexpectedEof)
+        Err(BencodeError::UnexpectedEof(2))
     ));
 }
@@ -152,13 +152,16 @@ fn bson_special_value_wire_matrix() {
 fn bson_decoder_error_matrix() {
     let mut decoder = BsonDecoder::new();
 
-    assert!(matches!(decoder.decode(&[]), Err(BsonError::UnexpectedEof)));
+    assert!(matches!(
+        decoder.decode(&[]),
+        Err(BsonError::UnexpectedEof(0))
+    ));
 
     // Valid-sized document with unsupported element type 0x14.
     let unsupported = vec![8, 0, 0, 0, 0x14, b'a', 0x00, 0x00];
     assert!(matches!(
         decoder.decode(&unsupported),
-        Err(BsonError::UnsupportedType(0x14))
+        Err(BsonError::UnsupportedType(0x14, 4))
     ));
 
     // String with invalid UTF-8 payload.
@@ -171,6 +174,6 @@ fn bson_decoder_error_matrix() {
     ];
     assert!(matches!(
         decoder.decode(&invalid_utf8),
-        Err(BsonError::InvalidUtf8)
+        Err(BsonError::InvalidUtf8(11))
     ));
 }
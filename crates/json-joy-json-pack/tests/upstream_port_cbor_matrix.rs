@@ -158,7 +158,7 @@ fn cbor_validate_matrix() {
     longer[..encoded.len()].copy_from_slice(&encoded);
     assert!(matches!(
         decoder.validate(&longer, 0, longer.len()),
-        Err(CborError::InvalidSize)
+        Err(CborError::InvalidSize(0))
     ));
 
     let shorter = encoded[..encoded.len() - 1].to_vec();
@@ -241,3 +241,55 @@ fn cbor_stable_and_dag_matrix() {
         .expect("decode dag tag 43");
     assert_eq!(dag_read_43, obj(&[("b", PackValue::Str("cid".into()))]));
 }
+
+/// Without the `f16` feature the half-float options are accepted and
+/// ignored.
+#[cfg(not(feature = "f16"))]
+#[test]
+fn cbor_float16_option_ignored_matrix() {
+    let mut encoder = CborEncoder::new();
+    encoder.options.float16 = true;
+    encoder.options.nan_payloads = true;
+    assert_eq!(
+        encoder.encode(&PackValue::Float(1.5)),
+        [0xfa, 0x3f, 0xc0, 0, 0]
+    );
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn cbor_float16_option_matrix() {
+    let decoder = CborDecoder::new();
+    let mut default_encoder = CborEncoder::new();
+    assert_eq!(
+        default_encoder.encode(&PackValue::Float(1.5)),
+        [0xfa, 0x3f, 0xc0, 0, 0]
+    );
+
+    let mut encoder = CborEncoder::new();
+    encoder.options.float16 = true;
+    let cases: Vec<(f64, Vec<u8>)> = vec![
+        (0.0, vec![0xf9, 0x00, 0x00]),
+        (-0.0, vec![0xf9, 0x80, 0x00]),
+        (1.5, vec![0xf9, 0x3e, 0x00]),
+        (65504.0, vec![0xf9, 0x7b, 0xff]),
+        (5.960464477539063e-8, vec![0xf9, 0x00, 0x01]),
+        (f64::INFINITY, vec![0xf9, 0x7c, 0x00]),
+        (f64::NEG_INFINITY, vec![0xf9, 0xfc, 0x00]),
+        (f64::NAN, vec![0xf9, 0x7e, 0x00]),
+        (100000.0, vec![0xfa, 0x47, 0xc3, 0x50, 0x00]),
+        (
+            0.1,
+            vec![0xfb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+        ),
+    ];
+    for (value, expected) in cases {
+        let encoded = encoder.encode(&PackValue::Float(value));
+        assert_eq!(encoded, expected, "encoding {value}");
+        let decoded = decoder.decode(&encoded).expect("decode f16");
+        assert_pack_value_eq(&decoded, &PackValue::Float(value));
+        if let PackValue::Float(f) = decoded {
+            assert_eq!(f.is_sign_negative(), value.is_sign_negative());
+        }
+    }
+}
@@ -162,7 +162,7 @@ fn ion_decoder_error_matrix() {
 
     assert!(matches!(
         decoder.decode(&[]),
-        Err(IonDecodeError::EndOfInput)
+        Err(IonDecodeError::EndOfInput(0))
     ));
     assert!(matches!(
         decoder.decode(&[0xe0, 0x01, 0x00, 0xeb]),
@@ -203,7 +203,7 @@ fn ion_decoder_base_edge_matrix() {
     // Unknown type nibble should surface explicit unknown-type error.
     assert!(matches!(
         decoder.decode(&[0xe0, 0x01, 0x00, 0xea, 0x50]),
-        Err(IonDecodeError::UnknownType(5))
+        Err(IonDecodeError::UnknownType(5, 4))
     ));
 
     // Annotation wrappers shorter than 3 bytes are invalid.
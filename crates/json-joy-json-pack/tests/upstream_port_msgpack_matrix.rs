@@ -1,6 +1,6 @@
 use json_joy_json_pack::msgpack::{
-    MsgPackDecoder, MsgPackDecoderFast, MsgPackEncoder, MsgPackEncoderFast, MsgPackEncoderStable,
-    MsgPackError, MsgPackPathSegment, MsgPackToJsonConverter,
+    MsgPackDecoder, MsgPackDecoderFast, MsgPackEncoder, MsgPackEncoderFast, MsgPackEncoderOptions,
+    MsgPackEncoderStable, MsgPackError, MsgPackPathSegment, MsgPackToJsonConverter,
 };
 use json_joy_json_pack::{JsonPackExtension, JsonPackValue, PackValue};
 
@@ -303,10 +303,103 @@ fn msgpack_to_json_converter_matrix() {
 
     for doc in docs {
         let msgpack = encoder.encode(&doc);
-        let json_text = converter.convert(&msgpack);
+        let json_text = converter.convert(&msgpack).unwrap();
         let parsed: serde_json::Value =
             serde_json::from_str(&json_text).unwrap_or_else(|e| panic!("invalid json: {e}"));
         let expected: serde_json::Value = doc.clone().into();
         assert_eq!(parsed, expected);
     }
 }
+
+fn int_of(value: &PackValue) -> i128 {
+    match value {
+        PackValue::Integer(i) => *i as i128,
+        PackValue::UInteger(u) => *u as i128,
+        PackValue::BigInt(i) => *i,
+        other => panic!("expected integer, got {other:?}"),
+    }
+}
+
+#[test]
+fn msgpack_encoder_options_matrix() {
+    let mut decoder = MsgPackDecoderFast::new();
+    let mut default_encoder = MsgPackEncoder::new();
+    let mut fast = MsgPackEncoderFast::new();
+    let doc = obj(&[
+        ("a", PackValue::Integer(200)),
+        ("b", PackValue::Integer(-100)),
+        ("c", PackValue::Float(1.5)),
+        ("d", PackValue::Str("x".repeat(40))),
+    ]);
+    assert_eq!(default_encoder.encode(&doc), fast.encode(&doc));
+
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        smallest_int: true,
+        ..Default::default()
+    });
+    let cases: Vec<(PackValue, Vec<u8>)> = vec![
+        (PackValue::Integer(127), vec![0x7f]),
+        (PackValue::Integer(200), vec![0xcc, 200]),
+        (PackValue::UInteger(300), vec![0xcd, 0x01, 0x2c]),
+        (PackValue::Integer(-32), vec![0xe0]),
+        (PackValue::Integer(-100), vec![0xd0, 0x9c]),
+        (PackValue::Integer(-129), vec![0xd1, 0xff, 0x7f]),
+        (
+            PackValue::Integer(-40000),
+            vec![0xd2, 0xff, 0xff, 0x63, 0xc0],
+        ),
+        (
+            PackValue::Integer(1 << 40),
+            vec![0xcf, 0, 0, 0x01, 0, 0, 0, 0, 0],
+        ),
+        (
+            PackValue::Integer(-(1 << 40)),
+            vec![0xd3, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0],
+        ),
+        (
+            PackValue::BigInt(u64::MAX as i128),
+            vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ),
+    ];
+    for (value, expected) in cases {
+        let encoded = encoder.encode(&value);
+        assert_eq!(encoded, expected, "encoding {value:?}");
+        let decoded = decoder.decode(&encoded).expect("decode int");
+        assert_eq!(int_of(&decoded), int_of(&value), "decoding {value:?}");
+    }
+
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        float32: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        encoder.encode(&PackValue::Float(1.5)),
+        [0xca, 0x3f, 0xc0, 0, 0]
+    );
+    assert_eq!(encoder.encode(&PackValue::Float(0.1))[0], 0xcb);
+    assert_eq!(encoder.encode(&PackValue::Float(f64::NAN))[0], 0xcb);
+    let nested = PackValue::Array(vec![PackValue::Float(-2.25)]);
+    let encoded = encoder.encode(&nested);
+    assert_eq!(encoded, [0x91, 0xca, 0xc0, 0x10, 0, 0]);
+    assert_pack_value_eq(&decoder.decode(&encoded).unwrap(), &nested);
+
+    let mut encoder = MsgPackEncoder::with_options(MsgPackEncoderOptions {
+        avoid_str8: true,
+        ..Default::default()
+    });
+    for (len, header) in [
+        (5, vec![0xa5]),
+        (31, vec![0xbf]),
+        (32, vec![0xda, 0, 32]),
+        (255, vec![0xda, 0, 255]),
+        (70000, vec![0xdb, 0, 1, 0x11, 0x70]),
+    ] {
+        let value = PackValue::Str("x".repeat(len));
+        let encoded = encoder.encode(&value);
+        assert_eq!(&encoded[..header.len()], &header[..], "length {len}");
+        assert_eq!(decoder.decode(&encoded).unwrap(), value);
+    }
+    let encoded = encoder.encode(&doc);
+    assert!(!encoded.contains(&0xd9));
+    assert_pack_value_eq(&decoder.decode(&encoded).unwrap(), &doc);
+}
@@ -156,9 +156,77 @@ fn ssh_error_and_write_any_matrix() {
     decoder.reset(&[0, 0, 0]);
     assert!(matches!(
         decoder.read_uint32(),
-        Err(SshError::UnexpectedEof)
+        Err(SshError::UnexpectedEof(0))
     ));
 
     decoder.reset(&[0, 0, 0, 2, 0xff, 0xff]);
-    assert!(matches!(decoder.read_str(), Err(SshError::InvalidUtf8)));
+    assert!(matches!(decoder.read_str(), Err(SshError::InvalidUtf8(4))));
+}
+
+#[test]
+fn ssh_mpint_conversion_matrix() {
+    // RFC 4251 §5 examples.
+    let cases: [(i128, &[u8], &str); 5] = [
+        (0, &[], "0"),
+        (
+            0x9a378f9b2e332a7,
+            &[0x09, 0xa3, 0x78, 0xf9, 0xb2, 0xe3, 0x32, 0xa7],
+            "9a378f9b2e332a7",
+        ),
+        (0x80, &[0x00, 0x80], "80"),
+        (-0x1234, &[0xed, 0xcc], "-1234"),
+        (-0xdeadbeef, &[0xff, 0x21, 0x52, 0x41, 0x11], "-deadbeef"),
+    ];
+    for (value, data, hex) in cases {
+        let mpint = JsonPackMpint::from_i128(value);
+        assert_eq!(mpint.data, data);
+        assert!(mpint.is_canonical());
+        assert_eq!(mpint.to_hex(), hex);
+        assert_eq!(JsonPackMpint::from_hex(hex).unwrap(), mpint);
+        assert_eq!(mpint.checked_to_i128(), Some(value));
+
+        let wire = mpint.to_ssh_bytes();
+        assert_eq!(&wire[..4], (data.len() as u32).to_be_bytes());
+        assert_eq!(
+            JsonPackMpint::from_ssh_bytes(&wire),
+            Some((mpint.clone(), wire.len()))
+        );
+    }
+
+    assert_eq!(
+        JsonPackMpint::from_hex("0xFF").unwrap().data,
+        vec![0x00, 0xff]
+    );
+    assert!(JsonPackMpint::from_hex("0x").is_err());
+    assert!(JsonPackMpint::from_hex("xyz").is_err());
+    assert_eq!(JsonPackMpint::from_ssh_bytes(&[0, 0, 0, 2, 1]), None);
+
+    // Unsigned key material (e.g. an RSA modulus with the high bit set).
+    let modulus = [0xc5u8; 20];
+    let mpint = JsonPackMpint::from_unsigned_bytes(&modulus);
+    assert_eq!(mpint.data.len(), 21);
+    assert_eq!(mpint.data[0], 0);
+    assert_eq!(mpint.to_unsigned_bytes().unwrap(), modulus);
+    assert_eq!(mpint.checked_to_i128(), None);
+    assert_eq!(JsonPackMpint::from_i128(-1).to_unsigned_bytes(), None);
+
+    assert_eq!(
+        JsonPackMpint::from_u128(u128::MAX).to_u128(),
+        Some(u128::MAX)
+    );
+    assert_eq!(JsonPackMpint::from_i128(i128::MIN).to_i128(), i128::MIN);
+    assert_eq!(
+        JsonPackMpint::from_i128(i128::MIN).to_i64(),
+        Err("Value is outside safe integer range")
+    );
+
+    // Non-canonical input is normalized on the wire.
+    let padded = JsonPackMpint::new(vec![0x00, 0x00, 0x7f]);
+    assert!(!padded.is_canonical());
+    let mut encoder = SshEncoder::new();
+    encoder.write_mpint(&padded);
+    assert_eq!(encoder.writer.flush(), vec![0, 0, 0, 1, 0x7f]);
+
+    let big = PackValue::BigInt(-0x1234);
+    assert_eq!(encoder.encode(&big).unwrap(), vec![0, 0, 0, 2, 0xed, 0xcc]);
 }
@@ -44,6 +44,10 @@ fn ws_error_surface_matrix() {
         WsFrameDecodingError::InvalidFrame.to_string(),
         "invalid WebSocket frame"
     );
+    assert_eq!(
+        WsFrameDecodingError::ControlFrameTooLong(126).to_string(),
+        "control frame payload of 126 bytes exceeds 125"
+    );
     assert_eq!(
         WsFrameEncodingError::InvalidFrame.to_string(),
         "WS_FRAME_ENCODING"
@@ -162,6 +162,6 @@ fn ubjson_streaming_and_error_matrix() {
     ));
     assert!(matches!(
         decoder.decode(&[0x53, 0x55, 0x02, b'a']),
-        Err(UbjsonError::UnexpectedEof)
+        Err(UbjsonError::UnexpectedEof(3))
     ));
 }
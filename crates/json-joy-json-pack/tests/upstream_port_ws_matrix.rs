@@ -231,7 +231,7 @@ fn ws_decoder_invalid_control_and_partial_payload_matrix() {
     let err = decoder.read_frame_header().unwrap_err();
     assert_eq!(
         err,
-        json_joy_json_pack::ws::WsFrameDecodingError::InvalidFrame
+        json_joy_json_pack::ws::WsFrameDecodingError::ControlFrameTooLong(126)
     );
 
     let mut decoder = WsFrameDecoder::new();
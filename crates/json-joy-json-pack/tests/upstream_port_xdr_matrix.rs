@@ -2,6 +2,7 @@ use json_joy_json_pack::xdr::{
     XdrDecodeError, XdrDecoder, XdrDiscriminant, XdrEncodeError, XdrEncoder, XdrSchema,
     XdrSchemaDecoder, XdrSchemaEncoder, XdrUnionValue, XdrValue,
 };
+use json_joy_json_pack::JsonPackMpint;
 
 fn schema_roundtrip(value: &XdrValue, schema: &XdrSchema) -> XdrValue {
     let mut encoder = XdrSchemaEncoder::new();
@@ -361,5 +362,23 @@ fn xdr_decoder_invalid_utf8_matrix() {
     let err = decoder
         .read_string()
         .expect_err("invalid UTF-8 should fail");
-    assert_eq!(err, XdrDecodeError::InvalidUtf8);
+    assert_eq!(err, XdrDecodeError::InvalidUtf8(4));
+}
+
+#[test]
+fn xdr_mpint_roundtrip_matrix() {
+    let mut encoder = XdrEncoder::new();
+    let mut decoder = XdrDecoder::new();
+
+    encoder.write_mpint(&JsonPackMpint::new(vec![0xff, 0xff, 0x80]));
+    encoder.write_mpint(&JsonPackMpint::from_i128(0x80));
+    let bytes = encoder.writer.flush();
+    assert_eq!(
+        bytes,
+        vec![0, 0, 0, 1, 0x80, 0, 0, 0, 0, 0, 0, 2, 0x00, 0x80, 0, 0]
+    );
+
+    decoder.reset(&bytes);
+    assert_eq!(decoder.read_mpint().unwrap().to_i128(), -128);
+    assert_eq!(decoder.read_mpint().unwrap().to_i128(), 128);
 }
@@ -0,0 +1,61 @@
+//! Codec encoders backed by pooled writers.
+//!
+//! Not an upstream port: upstream has no writer pool.
+
+use json_joy_buffers::{WriterPool, WriterPoolOptions};
+use json_joy_json_pack::codecs::{Codecs, JsonValueCodec};
+use json_joy_json_pack::PackValue;
+
+fn large_value() -> PackValue {
+    PackValue::Array(
+        (0..200)
+            .map(|i| PackValue::Str(format!("item-{i}-{}", "x".repeat(20))))
+            .collect(),
+    )
+}
+
+#[test]
+fn writer_pool_codecs_matrix() {
+    let mut pool = WriterPool::with_options(WriterPoolOptions {
+        alloc_size: 64,
+        ..WriterPoolOptions::default()
+    });
+    let value = large_value();
+    let mut expected = Codecs::new();
+    let expected: Vec<Vec<u8>> = vec![
+        expected.cbor.encode(&value).unwrap(),
+        expected.msgpack.encode(&value).unwrap(),
+        expected.json.encode(&value).unwrap(),
+    ];
+
+    let mut grown = Vec::new();
+    for round in 0..3 {
+        let mut codecs = Codecs::from_pool(&mut pool);
+        let codecs_list: [&mut dyn JsonValueCodec; 3] =
+            [&mut codecs.cbor, &mut codecs.msgpack, &mut codecs.json];
+        for (codec, expected) in codecs_list.into_iter().zip(&expected) {
+            let bytes = codec.encode(&value).unwrap();
+            assert_eq!(&bytes, expected, "round {round}");
+            assert_eq!(codec.decode(&bytes).unwrap(), value);
+        }
+        let sizes = [
+            codecs.cbor.encoder.writer.uint8.len(),
+            codecs.msgpack.encoder.inner.writer.uint8.len(),
+            codecs.json.encoder.writer.uint8.len(),
+        ];
+        if round == 0 {
+            assert!(sizes.iter().all(|&s| s > 64));
+            grown = sizes.to_vec();
+        }
+        codecs.release(&mut pool);
+    }
+
+    let stats = pool.stats();
+    assert_eq!(stats.acquired, 9);
+    assert_eq!(stats.reused, 6);
+    assert_eq!(stats.released, 9);
+    assert_eq!(stats.discarded, 0);
+    assert_eq!(stats.pooled, 3);
+    assert!(stats.pooled_capacity >= grown.iter().sum::<usize>());
+    assert!(stats.peak_capacity >= *grown.iter().max().unwrap());
+}
@@ -0,0 +1,271 @@
+//! WebSocket frame decoding against Autobahn-derived fixtures.
+//!
+//! Not an upstream port: upstream has no conformance checks. Case numbers
+//! refer to the Autobahn|Testsuite fuzzing client cases the fixtures are
+//! taken from; cases it does not cover are marked `rfc`. Framing checks
+//! need `with_strict(true)`; by default the decoder accepts what upstream
+//! accepts.
+
+use json_joy_json_pack::ws::{
+    WsFrame, WsFrameDecoder, WsFrameDecodingError, WsFrameEncoder, WsFrameOpcode, WsPeer,
+};
+
+use WsFrameDecodingError::*;
+
+const MASK: [u8; 4] = [0x37, 0xfa, 0x21, 0x3d];
+
+/// "κόσμε" followed by a UTF-16 surrogate and "edited", from case 6.3.1.
+const INVALID_UTF8: &[u8] = b"\xce\xba\xe1\xbd\xb9\xcf\x83\xce\xbc\xce\xb5\xed\xa0\x80edited";
+
+/// A client frame: masked with [`MASK`].
+fn client_frame(b0: u8, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() < 126);
+    let mut out = vec![b0, 0x80 | payload.len() as u8];
+    out.extend_from_slice(&MASK);
+    out.extend(payload.iter().enumerate().map(|(i, b)| b ^ MASK[i % 4]));
+    out
+}
+
+/// A server frame: unmasked.
+fn server_frame(b0: u8, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() < 126);
+    let mut out = vec![b0, payload.len() as u8];
+    out.extend_from_slice(payload);
+    out
+}
+
+fn close_payload(code: u16, reason: &[u8]) -> Vec<u8> {
+    let mut out = code.to_be_bytes().to_vec();
+    out.extend_from_slice(reason);
+    out
+}
+
+/// Decodes one frame, reading a close frame's payload too.
+fn decode(decoder: &mut WsFrameDecoder, bytes: Vec<u8>) -> Result<WsFrame, WsFrameDecodingError> {
+    decoder.push(bytes);
+    let frame = decoder.read_frame_header()?.expect("complete frame");
+    match frame {
+        WsFrame::Close(mut close) => {
+            decoder.read_close_frame_data(&mut close)?;
+            Ok(WsFrame::Close(close))
+        }
+        frame => Ok(frame),
+    }
+}
+
+#[test]
+fn ws_conformance_framing_error_matrix() {
+    let cases: &[(&str, Vec<u8>, WsFrameDecodingError)] = &[
+        ("3.1", client_frame(0x91, b"Hello"), ReservedBits(0b001)),
+        ("3.2", client_frame(0xa1, b"Hello"), ReservedBits(0b010)),
+        ("3.3", client_frame(0xb1, b"Hello"), ReservedBits(0b011)),
+        ("3.4", client_frame(0xc1, b"Hello"), ReservedBits(0b100)),
+        ("3.5", client_frame(0xd2, b"\x00\xff"), ReservedBits(0b101)),
+        ("3.6", client_frame(0xe9, b"ping"), ReservedBits(0b110)),
+        ("3.7", client_frame(0xf8, b""), ReservedBits(0b111)),
+        ("4.1.1", client_frame(0x83, b""), ReservedOpcode(3)),
+        ("4.1.2", client_frame(0x84, b"reserved"), ReservedOpcode(4)),
+        ("4.1.3", client_frame(0x85, b""), ReservedOpcode(5)),
+        ("4.1.4", client_frame(0x86, b""), ReservedOpcode(6)),
+        ("4.1.5", client_frame(0x87, b""), ReservedOpcode(7)),
+        ("4.2.1", client_frame(0x8b, b""), ReservedOpcode(11)),
+        ("4.2.2", client_frame(0x8c, b"reserved"), ReservedOpcode(12)),
+        ("4.2.3", client_frame(0x8d, b""), ReservedOpcode(13)),
+        ("4.2.4", client_frame(0x8e, b""), ReservedOpcode(14)),
+        ("4.2.5", client_frame(0x8f, b""), ReservedOpcode(15)),
+        ("5.1", client_frame(0x09, b"frag"), FragmentedControlFrame),
+        ("5.2", client_frame(0x0a, b"frag"), FragmentedControlFrame),
+        ("rfc", client_frame(0x08, b""), FragmentedControlFrame),
+        (
+            "2.5",
+            [&[0x89, 0xfe, 0x00, 0x7e][..], &MASK, &[0xfe; 126]].concat(),
+            ControlFrameTooLong(126),
+        ),
+        (
+            "7.3.6",
+            [&[0x88, 0xfe, 0x00, 0x7e][..], &MASK, &[0; 126]].concat(),
+            ControlFrameTooLong(126),
+        ),
+        (
+            "rfc",
+            [&[0x82, 0xfe, 0x00, 0x7d][..], &MASK].concat(),
+            NonMinimalLength(125),
+        ),
+        (
+            "rfc",
+            [&[0x82, 0xff, 0, 0, 0, 0, 0, 0, 0xff, 0xff][..], &MASK].concat(),
+            NonMinimalLength(0xffff),
+        ),
+        (
+            "rfc",
+            [&[0x82, 0xff, 0x80, 0, 0, 0, 0, 0, 0, 0][..], &MASK].concat(),
+            LengthTooLarge(1 << 63),
+        ),
+        ("rfc", server_frame(0x81, b"Hello"), UnmaskedFrame),
+        ("7.3.2", client_frame(0x88, b"\x03"), TruncatedCloseCode),
+        (
+            "7.5.1",
+            client_frame(0x88, &close_payload(1000, INVALID_UTF8)),
+            InvalidCloseReason,
+        ),
+    ];
+    for (case, bytes, expected) in cases {
+        let mut decoder = WsFrameDecoder::new()
+            .with_strict(true)
+            .with_peer(WsPeer::Client);
+        let err = decode(&mut decoder, bytes.clone()).unwrap_err();
+        assert_eq!(&err, expected, "case {case}");
+    }
+
+    let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Server);
+    let err = decode(&mut decoder, client_frame(0x81, b"Hello")).unwrap_err();
+    assert_eq!(err, MaskedFrame);
+}
+
+#[test]
+fn ws_conformance_close_code_matrix() {
+    // 7.7.x: codes an endpoint may send.
+    for code in [
+        1000u16, 1001, 1002, 1003, 1007, 1008, 1009, 1010, 1011, 1012, 1013, 1014, 3000, 3999,
+        4000, 4999,
+    ] {
+        let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Client);
+        match decode(
+            &mut decoder,
+            client_frame(0x88, &close_payload(code, b"bye")),
+        ) {
+            Ok(WsFrame::Close(frame)) => {
+                assert_eq!(frame.code, code);
+                assert_eq!(frame.reason, "bye");
+            }
+            other => panic!("code {code}: {other:?}"),
+        }
+    }
+    // 7.9.x: codes that are reserved, unassigned or local-only.
+    for code in [
+        0u16, 999, 1004, 1005, 1006, 1015, 1016, 1100, 2000, 2999, 5000, 65535,
+    ] {
+        let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Server);
+        let err = decode(&mut decoder, server_frame(0x88, &close_payload(code, b""))).unwrap_err();
+        assert_eq!(err, InvalidCloseCode(code), "code {code}");
+    }
+}
+
+#[test]
+fn ws_conformance_lenient_without_peer_matrix() {
+    // Without a peer only framing is checked: either masking direction and
+    // any close code pass, and an invalid reason reads as empty.
+    let mut decoder = WsFrameDecoder::new();
+    for bytes in [client_frame(0x81, b"Hello"), server_frame(0x81, b"Hello")] {
+        match decode(&mut decoder, bytes).unwrap() {
+            WsFrame::Data(header) => {
+                let mut payload = vec![0; header.length];
+                decoder.copy_frame_data(&header, &mut payload, 0);
+                assert_eq!(payload, b"Hello");
+            }
+            other => panic!("expected data frame, got {other:?}"),
+        }
+    }
+    match decode(
+        &mut decoder,
+        server_frame(0x88, &close_payload(1005, b"\xff")),
+    )
+    .unwrap()
+    {
+        WsFrame::Close(frame) => {
+            assert_eq!(frame.code, 1005);
+            assert_eq!(frame.reason, "");
+        }
+        other => panic!("expected close frame, got {other:?}"),
+    }
+    let mut decoder = WsFrameDecoder::new().with_strict(true);
+    let err = decode(&mut decoder, server_frame(0xc1, b"")).unwrap_err();
+    assert_eq!(err, ReservedBits(0b100));
+}
+
+#[test]
+fn ws_conformance_upstream_default_matrix() {
+    // Without strict mode the framing rules upstream skips are not checked.
+    let data = [
+        ("3.4", client_frame(0xc1, b"Hello"), 1, true),
+        ("3.7", client_frame(0xf2, b""), 2, true),
+        ("4.1.2", client_frame(0x84, b"reserved"), 4, true),
+        (
+            "rfc",
+            [&[0x82, 0xfe, 0x00, 0x05][..], &MASK, &[0; 5]].concat(),
+            2,
+            true,
+        ),
+        (
+            "rfc",
+            [&[0x02, 0xff, 0, 0, 0, 0, 0, 0, 0, 0x05][..], &MASK, &[0; 5]].concat(),
+            2,
+            false,
+        ),
+    ];
+    for (case, bytes, opcode, fin) in data {
+        let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Client);
+        match decode(&mut decoder, bytes) {
+            Ok(WsFrame::Data(header)) => {
+                assert_eq!((header.opcode, header.fin), (opcode, fin), "case {case}");
+            }
+            other => panic!("case {case}: {other:?}"),
+        }
+    }
+    let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Client);
+    match decode(&mut decoder, client_frame(0x09, b"frag")) {
+        Ok(WsFrame::Ping(frame)) => {
+            assert_eq!((frame.header.fin, &frame.data[..]), (false, &b"frag"[..]))
+        }
+        other => panic!("case 5.1: {other:?}"),
+    }
+    // Unknown control opcodes fail as upstream's do, without a reason.
+    for b0 in [0x8b, 0x8f] {
+        let mut decoder = WsFrameDecoder::new();
+        let err = decode(&mut decoder, client_frame(b0, b"")).unwrap_err();
+        assert_eq!(err, InvalidFrame);
+    }
+}
+
+#[test]
+fn ws_conformance_negotiated_rsv_matrix() {
+    // permessage-deflate sets RSV1 on the first frame of a message.
+    let mut decoder = WsFrameDecoder::new()
+        .with_strict(true)
+        .with_rsv(0b100)
+        .with_peer(WsPeer::Client);
+    match decode(
+        &mut decoder,
+        client_frame(0xc1, b"\xf2\x48\xcd\xc9\xc9\x07\x00"),
+    )
+    .unwrap()
+    {
+        WsFrame::Data(header) => assert_eq!(header.length, 7),
+        other => panic!("expected data frame, got {other:?}"),
+    }
+    decoder.reader.skip(7);
+    let err = decode(&mut decoder, client_frame(0xa1, b"")).unwrap_err();
+    assert_eq!(err, ReservedBits(0b010));
+}
+
+#[test]
+fn ws_conformance_encoder_round_trip_matrix() {
+    // Everything the encoder emits passes a peer-aware decoder.
+    let mut encoder = WsFrameEncoder::new();
+    let mut decoder = WsFrameDecoder::new().with_peer(WsPeer::Server);
+    for length in [0usize, 125, 126, 65535, 65536] {
+        decoder.push(encoder.encode_hdr(true, WsFrameOpcode::Binary, length, 0));
+        match decoder.read_frame_header().unwrap() {
+            Some(WsFrame::Data(header)) => assert_eq!(header.length, length),
+            other => panic!("length {length}: {other:?}"),
+        }
+    }
+    let frames = [
+        encoder.encode_ping(Some(&[0xfe; 125])),
+        encoder.encode_pong(Some(b"pong")),
+        encoder.encode_close("normal", 1000),
+    ];
+    for bytes in frames {
+        decode(&mut decoder, bytes).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+//! WebSocket opening handshake helpers.
+//!
+//! Not an upstream port: upstream has no handshake support.
+
+use json_joy_json_pack::ws::handshake::{
+    accept_key, client_key, format_extensions, format_protocols, is_valid_client_key,
+    parse_extensions, parse_protocols, select_protocol, server_response,
+};
+use json_joy_json_pack::ws::{WsExtension, WsHandshakeError};
+
+#[test]
+fn ws_accept_key_matrix() {
+    let cases = [
+        // RFC 6455 §1.3.
+        ("dGhlIHNhbXBsZSBub25jZQ==", "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="),
+        (
+            " dGhlIHNhbXBsZSBub25jZQ==\t",
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=",
+        ),
+        ("AQIDBAUGBwgJCgsMDQ4PEA==", "C/0nmHhBztSRGR1CwL6Tf4ZjwpY="),
+        // Exercise the SHA-1 padding boundary and multi-block input.
+        ("", "Kfh9QIsMVZcl6xEPYxPHzW8SZ8w="),
+        ("aaaaaaaaaaaaaaaaaaa", "yzaGyu0mcUukN7CdsSwa30tnCpc="),
+        (&"x".repeat(100), "DJTE+uYDnPxiT+W6VvIG/iPUxv8="),
+    ];
+    for (key, accept) in cases {
+        assert_eq!(accept_key(key), accept, "{key:?}");
+    }
+
+    let nonce: [u8; 16] = core::array::from_fn(|i| i as u8 + 1);
+    assert_eq!(client_key(&nonce), "AQIDBAUGBwgJCgsMDQ4PEA==");
+    assert!(is_valid_client_key("AQIDBAUGBwgJCgsMDQ4PEA=="));
+    assert!(!is_valid_client_key("AQIDBAUGBwgJCgsMDQ4P"));
+    assert!(!is_valid_client_key("AQIDBAUGBwgJCgsMDQ4PEA"));
+    assert!(!is_valid_client_key("not base64 at all!!!!!!!"));
+}
+
+#[test]
+fn ws_extensions_matrix() {
+    let header = "permessage-deflate; client_max_window_bits, permessage-deflate;\
+                  server_max_window_bits=\"10\" ; server_no_context_takeover,, x-foo;a=\"b\\\"c,d\"";
+    let extensions = parse_extensions(header);
+    assert!(
+        extensions.is_err(),
+        "quoted values must be tokens once unquoted"
+    );
+
+    let header = "permessage-deflate; client_max_window_bits, permessage-deflate;\
+                  server_max_window_bits=\"10\" ; server_no_context_takeover,, x-foo";
+    let extensions = parse_extensions(header).unwrap();
+    assert_eq!(
+        extensions,
+        vec![
+            WsExtension::new("permessage-deflate").with_param("client_max_window_bits", None),
+            WsExtension::new("permessage-deflate")
+                .with_param("server_max_window_bits", Some("10"))
+                .with_param("server_no_context_takeover", None),
+            WsExtension::new("x-foo"),
+        ]
+    );
+    assert_eq!(
+        extensions[1].param("SERVER_MAX_WINDOW_BITS"),
+        Some(Some("10"))
+    );
+    assert_eq!(
+        extensions[1].param("server_no_context_takeover"),
+        Some(None)
+    );
+    assert_eq!(extensions[1].param("client_max_window_bits"), None);
+
+    let formatted = format_extensions(&extensions);
+    assert_eq!(
+        formatted,
+        "permessage-deflate; client_max_window_bits, \
+         permessage-deflate; server_max_window_bits=10; server_no_context_takeover, x-foo"
+    );
+    assert_eq!(parse_extensions(&formatted).unwrap(), extensions);
+    assert_eq!(parse_extensions("").unwrap(), vec![]);
+
+    // Non-token values are quoted on output.
+    assert_eq!(
+        format_extensions(&[WsExtension::new("x").with_param("v", Some("a \"b\""))]),
+        "x; v=\"a \\\"b\\\"\""
+    );
+
+    for bad in [
+        "; a",
+        "bad name",
+        "x; =1",
+        "x; a=\"1",
+        "x; a=1 2",
+        "x; a=\"\"",
+    ] {
+        assert!(
+            matches!(
+                parse_extensions(bad),
+                Err(WsHandshakeError::InvalidExtension(_))
+            ),
+            "{bad:?}"
+        );
+    }
+}
+
+#[test]
+fn ws_protocols_matrix() {
+    let offered = parse_protocols("chat.v2, chat.v1 ,, graphql-ws").unwrap();
+    assert_eq!(offered, ["chat.v2", "chat.v1", "graphql-ws"]);
+    assert_eq!(
+        format_protocols(&["chat.v2", "chat.v1"]),
+        "chat.v2, chat.v1"
+    );
+
+    // Client preference order wins; names are case-sensitive.
+    assert_eq!(
+        select_protocol(&offered, &["graphql-ws", "chat.v1"]),
+        Some("chat.v1")
+    );
+    assert_eq!(select_protocol(&offered, &["CHAT.V1"]), None);
+    assert_eq!(select_protocol(&[], &["chat.v1"]), None);
+
+    assert_eq!(
+        parse_protocols("chat, a b"),
+        Err(WsHandshakeError::InvalidProtocol("a b".into()))
+    );
+}
+
+#[test]
+fn ws_server_response_matrix() {
+    assert_eq!(
+        server_response("dGhlIHNhbXBsZSBub25jZQ==", None, &[]),
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+         \r\n"
+    );
+    assert_eq!(
+        server_response(
+            "dGhlIHNhbXBsZSBub25jZQ==",
+            Some("chat"),
+            &[WsExtension::new("permessage-deflate")
+                .with_param("client_max_window_bits", Some("10"))],
+        ),
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+         Sec-WebSocket-Protocol: chat\r\n\
+         Sec-WebSocket-Extensions: permessage-deflate; client_max_window_bits=10\r\n\
+         \r\n"
+    );
+}
@@ -0,0 +1,114 @@
+//! Incremental WebSocket payload reads into caller buffers.
+//!
+//! Not an upstream port: upstream copies a frame's payload in one call.
+
+use json_joy_json_pack::ws::{WsFrame, WsFrameDecoder, WsFrameEncoder, WsFrameOpcode};
+
+fn frame(data: &[u8], mask: u32) -> Vec<u8> {
+    let mut encoder = WsFrameEncoder::new();
+    encoder.write_hdr(true, WsFrameOpcode::Binary, data.len(), mask);
+    if mask == 0 {
+        encoder.writer.buf(data);
+    } else {
+        encoder.write_buf_xor(data, mask);
+    }
+    encoder.writer.flush()
+}
+
+fn read_data_header(decoder: &mut WsFrameDecoder) -> usize {
+    match decoder.read_frame_header().unwrap() {
+        Some(WsFrame::Data(header)) => header.length,
+        other => panic!("expected data frame, got {other:?}"),
+    }
+}
+
+#[test]
+fn ws_payload_stream_chunk_matrix() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    for mask in [0u32, 0x1234_5678] {
+        for (chunk, buf_len) in [(1usize, 1usize), (7, 3), (64, 100), (1000, 1000)] {
+            let bytes = frame(&data, mask);
+            let mut decoder = WsFrameDecoder::new();
+            let header_len = bytes.len() - data.len();
+            decoder.push(bytes[..header_len].to_vec());
+            assert_eq!(read_data_header(&mut decoder), data.len());
+            assert_eq!(decoder.payload_remaining(), data.len());
+
+            // Nothing buffered yet.
+            let mut buf = vec![0u8; buf_len];
+            assert_eq!(decoder.read_payload_into(&mut buf), 0);
+
+            let mut out = Vec::new();
+            for part in bytes[header_len..].chunks(chunk) {
+                decoder.push(part.to_vec());
+                loop {
+                    let n = decoder.read_payload_into(&mut buf);
+                    if n == 0 {
+                        break;
+                    }
+                    assert!(n <= buf_len);
+                    out.extend_from_slice(&buf[..n]);
+                }
+            }
+            assert_eq!(out, data, "mask {mask:x} chunk {chunk} buf {buf_len}");
+            assert_eq!(decoder.payload_remaining(), 0);
+        }
+    }
+}
+
+#[test]
+fn ws_payload_stream_frame_boundary_matrix() {
+    // Two frames in one push: payload reads stop at the first frame's end.
+    let mut bytes = frame(b"hello", 0xdead_beef);
+    bytes.extend(frame(b"xy", 0));
+    let mut decoder = WsFrameDecoder::new();
+    decoder.push(bytes);
+
+    assert_eq!(read_data_header(&mut decoder), 5);
+    let mut buf = [0u8; 16];
+    assert_eq!(decoder.read_payload_into(&mut buf[..2]), 2);
+    assert_eq!(decoder.read_payload_into(&mut buf[2..]), 3);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(decoder.read_payload_into(&mut buf), 0);
+
+    assert_eq!(read_data_header(&mut decoder), 2);
+    assert_eq!(decoder.read_payload_into(&mut buf), 2);
+    assert_eq!(&buf[..2], b"xy");
+
+    // An empty destination reads nothing.
+    assert_eq!(decoder.read_payload_into(&mut []), 0);
+}
+
+#[test]
+fn ws_payload_stream_split_header_matrix() {
+    // Headers cut anywhere are left unread until the rest arrives.
+    for len in [5usize, 300, 70_000] {
+        for mask in [0u32, 0x1234_5678] {
+            let data = vec![7u8; len];
+            let bytes = frame(&data, mask);
+            let header_len = bytes.len() - len;
+            let mut decoder = WsFrameDecoder::new();
+            for (i, &byte) in bytes[..header_len].iter().enumerate() {
+                decoder.push(vec![byte]);
+                if i + 1 < header_len {
+                    assert!(decoder.read_frame_header().unwrap().is_none(), "{len} {i}");
+                }
+            }
+            assert_eq!(read_data_header(&mut decoder), len);
+            assert_eq!(decoder.reader.size(), 0);
+        }
+    }
+
+    // A control frame is returned only once its payload is buffered.
+    let ping = WsFrameEncoder::new().encode_ping(Some(b"hello"));
+    let mut decoder = WsFrameDecoder::new();
+    for &byte in &ping[..ping.len() - 1] {
+        decoder.push(vec![byte]);
+        assert!(decoder.read_frame_header().unwrap().is_none());
+    }
+    decoder.push(ping[ping.len() - 1..].to_vec());
+    match decoder.read_frame_header().unwrap() {
+        Some(WsFrame::Ping(frame)) => assert_eq!(frame.data, b"hello"),
+        other => panic!("expected ping, got {other:?}"),
+    }
+}
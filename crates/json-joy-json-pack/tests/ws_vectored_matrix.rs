@@ -0,0 +1,113 @@
+//! WebSocket frames emitted as a stack header plus a borrowed payload, or
+//! written whole into a caller's `Writer`.
+//!
+//! Not an upstream port: upstream encodes a header and leaves the payload
+//! to the caller.
+
+use std::io::{IoSlice, Write};
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::ws::{
+    WsFrame, WsFrameDecoder, WsFrameEncoder, WsFrameHeaderBytes, WsFrameOpcode,
+};
+
+const LENGTHS: [usize; 8] = [0, 1, 125, 126, 127, 65535, 65536, 70000];
+const OPCODES: [WsFrameOpcode; 3] = [
+    WsFrameOpcode::Binary,
+    WsFrameOpcode::Text,
+    WsFrameOpcode::Continue,
+];
+
+fn payload(len: usize) -> Vec<u8> {
+    (0..=255u8).cycle().take(len).collect()
+}
+
+/// The frame built by copying the payload behind an encoded header.
+fn copied(fin: bool, opcode: WsFrameOpcode, data: &[u8], mask: u32) -> Vec<u8> {
+    let mut encoder = WsFrameEncoder::new();
+    encoder.write_hdr(fin, opcode, data.len(), mask);
+    if mask == 0 {
+        encoder.writer.buf(data);
+    } else {
+        encoder.write_buf_xor(data, mask);
+    }
+    encoder.writer.flush()
+}
+
+#[test]
+fn ws_frame_parts_matrix() {
+    let mut encoder = WsFrameEncoder::new();
+    for len in LENGTHS {
+        let data = payload(len);
+        for opcode in OPCODES {
+            for fin in [true, false] {
+                for mask in [0, 0x1234_5678] {
+                    let header = WsFrameHeaderBytes::new(fin, opcode, len, mask);
+                    assert_eq!(
+                        &header[..],
+                        &encoder.encode_hdr(fin, opcode, len, mask)[..],
+                        "len {len} mask {mask:x}"
+                    );
+                }
+
+                // The payload is handed back as is; a vectored write sends
+                // the same bytes as the copied frame.
+                let (header, body) = WsFrameEncoder::frame_parts(fin, opcode, &data);
+                assert!(std::ptr::eq(body, &data[..]));
+                let mut sent = Vec::new();
+                let n = sent
+                    .write_vectored(&[IoSlice::new(&header), IoSlice::new(body)])
+                    .unwrap();
+                assert_eq!(n, header.len() + len);
+                assert_eq!(sent, copied(fin, opcode, &data, 0), "len {len}");
+            }
+        }
+
+        let (header, body) = WsFrameEncoder::data_msg_parts(&data);
+        assert_eq!(header.as_ref(), encoder.encode_data_msg_hdr_fast(len));
+        assert!(std::ptr::eq(body, &data[..]));
+    }
+}
+
+#[test]
+fn ws_write_frame_into_matrix() {
+    let mut encoder = WsFrameEncoder::new();
+    for len in LENGTHS {
+        let data = payload(len);
+        for opcode in OPCODES {
+            for mask in [0, 0x1234_5678] {
+                // Appends after existing output and leaves the encoder's
+                // own writer untouched.
+                let mut out = Writer::with_alloc_size(16);
+                out.buf(b"prefix");
+                encoder.write_frame_into(&mut out, false, opcode, &data, mask);
+                let mut expected = b"prefix".to_vec();
+                expected.extend(copied(false, opcode, &data, mask));
+                assert_eq!(out.flush(), expected, "len {len} mask {mask:x}");
+                assert!(encoder.writer.flush().is_empty());
+            }
+        }
+
+        let mut out = Writer::new();
+        encoder.write_data_msg_into(&mut out, &data);
+        encoder.write_data_msg_into(&mut out, &data);
+        let bytes = out.flush();
+
+        // Two frames back to back decode to the original payload.
+        let mut decoder = WsFrameDecoder::new();
+        decoder.push(bytes);
+        for _ in 0..2 {
+            match decoder.read_frame_header().unwrap() {
+                Some(WsFrame::Data(header)) => {
+                    assert!(header.fin);
+                    assert_eq!(header.opcode, WsFrameOpcode::Binary as u8);
+                    assert_eq!(header.length, len);
+                }
+                other => panic!("expected data frame, got {other:?}"),
+            }
+            let mut buf = vec![0u8; len];
+            assert_eq!(decoder.read_payload_into(&mut buf), len);
+            assert_eq!(buf, data);
+        }
+    }
+}
@@ -47,6 +47,9 @@ pub use util::{get_accessed_properties, json_path_equals, json_path_to_string};
 mod codegen;
 pub use codegen::{JsonPathCodegen, JsonPathCompiledFn};
 
+mod pointer;
+pub use pointer::{json_path_pointers, json_path_to_pointers, pointer_to_json_path};
+
 #[cfg(test)]
 mod tests {
     use super::*;
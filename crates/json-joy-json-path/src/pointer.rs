@@ -0,0 +1,118 @@
+//! Conversion between JSONPath expressions and JSON Pointers.
+//!
+//! Not an upstream port. A JSONPath made only of name and non-negative
+//! index selectors (unions included) names a fixed set of locations, so
+//! [`json_path_to_pointers`] expands it without a document. Anything else
+//! (wildcards, slices, negative indices, descendants, filters) depends on
+//! the document's shape; [`json_path_pointers`] evaluates the path and
+//! returns the pointer of every match.
+
+use json_joy_json_pointer::{format_json_pointer, is_valid_index, parse_json_pointer};
+use serde_json::Value;
+
+use crate::eval::JsonPathEval;
+use crate::types::{JSONPath, PathComponent, PathSegment, Selector};
+
+/// Expands a simple JSONPath into the JSON Pointers it names, or `None` if
+/// the path uses a selector whose matches depend on the document.
+///
+/// Unions multiply out in selector order. A pointer may address nothing in
+/// a given document; an index step only matches arrays and a name step only
+/// objects, which pointers cannot express.
+///
+/// ```
+/// use json_joy_json_path::{json_path_to_pointers, JsonPathParser};
+///
+/// let path = JsonPathParser::parse("$.users[0,2]['e/mail']").unwrap();
+/// assert_eq!(
+///     json_path_to_pointers(&path).unwrap(),
+///     ["/users/0/e~1mail", "/users/2/e~1mail"]
+/// );
+/// assert!(json_path_to_pointers(&JsonPathParser::parse("$.users[*]").unwrap()).is_none());
+/// ```
+pub fn json_path_to_pointers(path: &JSONPath) -> Option<Vec<String>> {
+    let mut paths: Vec<Vec<String>> = vec![Vec::new()];
+    for segment in &path.segments {
+        if segment.recursive {
+            return None;
+        }
+        let steps = segment
+            .selectors
+            .iter()
+            .map(|selector| match selector {
+                Selector::Name(name) => Some(name.clone()),
+                Selector::Index(index) if *index >= 0 => Some(index.to_string()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        paths = paths
+            .iter()
+            .flat_map(|prefix| {
+                steps.iter().map(move |step| {
+                    let mut path = prefix.clone();
+                    path.push(step.clone());
+                    path
+                })
+            })
+            .collect();
+    }
+    Some(paths.iter().map(|p| format_json_pointer(p)).collect())
+}
+
+/// Evaluates `path` against `doc` and returns the JSON Pointer of each
+/// match, in match order.
+///
+/// Works for any JSONPath. Descendant and overlapping selectors can match a
+/// value more than once; its pointer is then repeated.
+///
+/// ```
+/// use json_joy_json_path::{json_path_pointers, JsonPathParser};
+/// use serde_json::json;
+///
+/// let doc = json!({"users": [{"name": "ann"}, {"name": "bob", "admin": true}]});
+/// let path = JsonPathParser::parse("$.users[?@.admin].name").unwrap();
+/// assert_eq!(json_path_pointers(&path, &doc), ["/users/1/name"]);
+/// ```
+pub fn json_path_pointers(path: &JSONPath, doc: &Value) -> Vec<String> {
+    JsonPathEval::eval_query(path, doc)
+        .paths
+        .iter()
+        .map(|components| {
+            let steps: Vec<String> = components
+                .iter()
+                .map(|component| match component {
+                    PathComponent::Key(key) => key.clone(),
+                    PathComponent::Index(index) => index.to_string(),
+                })
+                .collect();
+            format_json_pointer(&steps)
+        })
+        .collect()
+}
+
+/// Converts a JSON Pointer into a JSONPath with one selector per step.
+///
+/// Pointers do not say whether a step is an array index or an object key.
+/// Steps that are valid RFC 6901 indices (`0`, or digits without a leading
+/// zero) become index selectors and match array elements only; all others
+/// become name selectors.
+///
+/// ```
+/// use json_joy_json_path::{json_path_to_string, pointer_to_json_path};
+///
+/// let path = pointer_to_json_path("/users/0/e~1mail");
+/// assert_eq!(json_path_to_string(&path), "$.users[0]['e/mail']");
+/// ```
+pub fn pointer_to_json_path(pointer: &str) -> JSONPath {
+    let segments = parse_json_pointer(pointer)
+        .into_iter()
+        .map(|step| {
+            let selector = match step.parse::<isize>() {
+                Ok(index) if is_valid_index(&step) => Selector::Index(index),
+                _ => Selector::Name(step),
+            };
+            PathSegment::new(vec![selector], false)
+        })
+        .collect();
+    JSONPath::new(segments)
+}
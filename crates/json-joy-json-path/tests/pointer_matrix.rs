@@ -0,0 +1,146 @@
+//! Conversion between JSONPath expressions and JSON Pointers.
+//!
+//! Not an upstream port. Pointers from a static expansion or an evaluation
+//! must address exactly the values the evaluator returns.
+
+use json_joy_json_path::{
+    json_path_pointers, json_path_to_pointers, json_path_to_string, pointer_to_json_path,
+    JsonPathEval, JsonPathParser,
+};
+use json_joy_json_pointer::{get, parse_json_pointer};
+use serde_json::{json, Value};
+
+fn doc() -> Value {
+    json!({
+        "store": {
+            "book": [
+                {"title": "a", "price": 8},
+                {"title": "b", "price": 12, "isbn": "x"},
+                {"title": "c", "price": 9}
+            ],
+            "bicycle": {"color": "red", "price": 20}
+        },
+        "a/b": {"~": 1},
+        "": [true],
+        "10": "ten"
+    })
+}
+
+fn resolve<'a>(doc: &'a Value, pointer: &str) -> Option<&'a Value> {
+    get(doc, &parse_json_pointer(pointer))
+}
+
+#[test]
+fn json_path_to_pointers_static_matrix() {
+    let cases: [(&str, Option<&[&str]>); 10] = [
+        ("$", Some(&[""])),
+        ("$.store.bicycle.color", Some(&["/store/bicycle/color"])),
+        ("$.store.book[1].isbn", Some(&["/store/book/1/isbn"])),
+        (
+            "$.store.book[0,2]['title','price']",
+            Some(&[
+                "/store/book/0/title",
+                "/store/book/0/price",
+                "/store/book/2/title",
+                "/store/book/2/price",
+            ]),
+        ),
+        ("$['a/b']['~']", Some(&["/a~1b/~0"])),
+        ("$[''][0]", Some(&["//0"])),
+        ("$.store.book[-1]", None),
+        ("$.store.book[*].title", None),
+        ("$.store.book[0:2]", None),
+        ("$..price", None),
+    ];
+    let doc = doc();
+    for (expr, expected) in cases {
+        let path = JsonPathParser::parse(expr).unwrap();
+        let pointers = json_path_to_pointers(&path);
+        assert_eq!(
+            pointers,
+            expected.map(|e| e.iter().map(|s| s.to_string()).collect::<Vec<_>>()),
+            "{expr}"
+        );
+        // Where the document has every location, they agree with evaluation.
+        if let Some(pointers) = pointers {
+            let resolved: Vec<&Value> = pointers.iter().filter_map(|p| resolve(&doc, p)).collect();
+            assert_eq!(resolved, JsonPathEval::eval(&path, &doc), "{expr}");
+        }
+    }
+}
+
+#[test]
+fn json_path_pointers_eval_matrix() {
+    let cases: [(&str, &[&str]); 7] = [
+        ("$", &[""]),
+        (
+            "$.store.book[*].title",
+            &[
+                "/store/book/0/title",
+                "/store/book/1/title",
+                "/store/book/2/title",
+            ],
+        ),
+        ("$.store.book[-1]", &["/store/book/2"]),
+        ("$.store.book[1:]", &["/store/book/1", "/store/book/2"]),
+        (
+            "$.store.book[?(@.price < 10)].title",
+            &["/store/book/0/title", "/store/book/2/title"],
+        ),
+        ("$['a/b'].*", &["/a~1b/~0"]),
+        ("$.nope[0]", &[]),
+    ];
+    let doc = doc();
+    for (expr, expected) in cases {
+        let path = JsonPathParser::parse(expr).unwrap();
+        let pointers = json_path_pointers(&path, &doc);
+        assert_eq!(pointers, expected, "{expr}");
+        let resolved: Vec<&Value> = pointers.iter().filter_map(|p| resolve(&doc, p)).collect();
+        assert_eq!(resolved, JsonPathEval::eval(&path, &doc), "{expr}");
+    }
+
+    let path = JsonPathParser::parse("$..price").unwrap();
+    let mut pointers = json_path_pointers(&path, &doc);
+    pointers.sort();
+    assert_eq!(
+        pointers,
+        [
+            "/store/bicycle/price",
+            "/store/book/0/price",
+            "/store/book/1/price",
+            "/store/book/2/price",
+        ]
+    );
+}
+
+#[test]
+fn pointer_to_json_path_matrix() {
+    let cases = [
+        ("", "$"),
+        ("/store/book/0/title", "$.store.book[0].title"),
+        ("/a~1b/~0", "$['a/b']['~']"),
+        ("//0", "$[''][0]"),
+        ("/store/book/01", "$.store.book['01']"),
+        ("/store/book/-", "$.store.book['-']"),
+    ];
+    for (pointer, expected) in cases {
+        let path = pointer_to_json_path(pointer);
+        assert_eq!(json_path_to_string(&path), expected, "{pointer}");
+        assert_eq!(JsonPathParser::parse(expected).unwrap(), path, "{pointer}");
+    }
+
+    // Round trip through a static expansion.
+    let doc = doc();
+    for pointer in ["", "/store/book/1/isbn", "/a~1b/~0", "//0"] {
+        let path = pointer_to_json_path(pointer);
+        assert_eq!(json_path_to_pointers(&path).unwrap(), [pointer]);
+        assert_eq!(
+            JsonPathEval::eval(&path, &doc),
+            [resolve(&doc, pointer).unwrap()]
+        );
+    }
+
+    // A numeric object key becomes an index selector, which only matches
+    // arrays.
+    assert!(JsonPathEval::eval(&pointer_to_json_path("/10"), &doc).is_empty());
+}
@@ -0,0 +1,151 @@
+//! Pre-parsed JSON Pointers.
+//!
+//! Not an upstream port: upstream re-parses pointer strings (or generates
+//! code per pointer). A [`CompiledPointer`] splits and unescapes once and
+//! parses array indices up front, and [`resolve_many`] resolves a batch
+//! against one document, walking each shared prefix only once.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::util::{format_json_pointer, is_valid_index, parse_json_pointer};
+use crate::JsonPointerError;
+
+/// A JSON Pointer split into unescaped steps.
+///
+/// Array steps must be RFC 6901 indices: `0`, or digits without a leading
+/// zero. `-` (past the end) never resolves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompiledPointer {
+    path: Vec<String>,
+    indices: Vec<Option<usize>>,
+}
+
+impl CompiledPointer {
+    /// Compiles a pointer string. Fails with
+    /// [`JsonPointerError::PointerInvalid`] if a non-empty pointer does not
+    /// start with `/`.
+    pub fn new(pointer: &str) -> Result<Self, JsonPointerError> {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(JsonPointerError::PointerInvalid);
+        }
+        Ok(Self::from_path(parse_json_pointer(pointer)))
+    }
+
+    /// Compiles an already split path.
+    pub fn from_path(path: Vec<String>) -> Self {
+        let indices = path
+            .iter()
+            .map(|step| {
+                if is_valid_index(step) {
+                    step.parse().ok()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Self { path, indices }
+    }
+
+    /// The unescaped steps.
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Whether the pointer addresses the whole document.
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// The value the pointer addresses in `doc`, if any.
+    pub fn resolve<'a>(&self, doc: &'a Value) -> Option<&'a Value> {
+        (0..self.path.len()).try_fold(doc, |val, i| self.step(val, i))
+    }
+
+    /// Mutable variant of [`CompiledPointer::resolve`].
+    pub fn resolve_mut<'a>(&self, doc: &'a mut Value) -> Option<&'a mut Value> {
+        let mut current = doc;
+        for (step, index) in self.path.iter().zip(&self.indices) {
+            current = match current {
+                Value::Array(arr) => arr.get_mut((*index)?)?,
+                Value::Object(map) => map.get_mut(step)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    fn step<'a>(&self, val: &'a Value, i: usize) -> Option<&'a Value> {
+        match val {
+            Value::Array(arr) => arr.get(self.indices[i]?),
+            Value::Object(map) => map.get(&self.path[i]),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CompiledPointer {
+    type Err = JsonPointerError;
+
+    fn from_str(pointer: &str) -> Result<Self, Self::Err> {
+        Self::new(pointer)
+    }
+}
+
+impl fmt::Display for CompiledPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_json_pointer(&self.path))
+    }
+}
+
+/// Resolves every pointer against `doc`; results are in the order of
+/// `pointers`.
+///
+/// Pointers are visited in path order, so a prefix shared by consecutive
+/// pointers (such as `/users/0` in `/users/0/name` and `/users/0/email`) is
+/// walked once.
+///
+/// ```
+/// use json_joy_json_pointer::{resolve_many, CompiledPointer};
+/// use serde_json::json;
+///
+/// let doc = json!({"users": [{"name": "ann", "email": "a@x"}]});
+/// let pointers: Vec<CompiledPointer> = ["/users/0/email", "/users/1", "/users/0/name"]
+///     .iter()
+///     .map(|p| p.parse().unwrap())
+///     .collect();
+/// assert_eq!(
+///     resolve_many(&pointers, &doc),
+///     [Some(&json!("a@x")), None, Some(&json!("ann"))]
+/// );
+/// ```
+pub fn resolve_many<'a>(pointers: &[CompiledPointer], doc: &'a Value) -> Vec<Option<&'a Value>> {
+    let mut order: Vec<usize> = (0..pointers.len()).collect();
+    order.sort_by(|&a, &b| pointers[a].path.cmp(&pointers[b].path));
+    let mut results = vec![None; pointers.len()];
+    // `stack[i]` is the value at the first `i` steps of `prev`.
+    let mut stack: Vec<&Value> = vec![doc];
+    let mut prev: &[String] = &[];
+    for i in order {
+        let pointer = &pointers[i];
+        let shared = prev
+            .iter()
+            .zip(&pointer.path)
+            .take_while(|(a, b)| a == b)
+            .count();
+        stack.truncate(shared + 1);
+        while stack.len() <= pointer.path.len() {
+            match pointer.step(stack[stack.len() - 1], stack.len() - 1) {
+                Some(val) => stack.push(val),
+                None => break,
+            }
+        }
+        if stack.len() == pointer.path.len() + 1 {
+            results[i] = Some(stack[pointer.path.len()]);
+        }
+        prev = &pointer.path;
+    }
+    results
+}
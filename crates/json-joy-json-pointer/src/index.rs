@@ -1,8 +1,13 @@
 //! Mirrors upstream `index.ts`.
 
+pub use crate::compiled::{resolve_many, CompiledPointer};
 pub use crate::find::find;
 pub use crate::find_by_pointer::find_by_pointer;
 pub use crate::get::{get, get_mut};
+pub use crate::typed::{
+    find_typed, format_typed_pointer, get_typed, get_typed_mut, parse_typed_pointer, to_typed_path,
+    PathSegment,
+};
 pub use crate::types::{Path, PathStep, Reference, ReferenceKey};
 pub use crate::util::{
     escape_component, format_json_pointer, is_child, is_integer, is_path_equal, is_root,
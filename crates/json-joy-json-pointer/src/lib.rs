@@ -17,11 +17,13 @@
 use thiserror::Error;
 
 pub mod codegen;
+mod compiled;
 mod find;
 #[path = "findByPointer/mod.rs"]
 pub mod find_by_pointer;
 mod get;
 mod index;
+mod typed;
 pub mod types;
 mod util;
 pub mod validate;
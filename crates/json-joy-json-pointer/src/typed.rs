@@ -0,0 +1,159 @@
+//! Typed path segments.
+//!
+//! Not an upstream port: upstream paths are `(string | number)[]` and every
+//! lookup re-checks whether a step is numeric. [`parse_typed_pointer`]
+//! classifies each step once, so evaluation loops match on the segment
+//! instead of parsing strings.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::types::{Reference, ReferenceKey};
+use crate::util::{escape_component, is_valid_index, parse_json_pointer};
+use crate::JsonPointerError;
+
+/// One step of a JSON Pointer, classified by the RFC 6901 parse rules.
+///
+/// A numeric step is an [`Index`](Self::Index) even though it may address
+/// an object; evaluation then looks up its decimal form as a key, which
+/// round-trips because valid indices have no leading zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// An object key (unescaped).
+    Key(String),
+    /// An array index: `0` or digits without a leading zero.
+    Index(usize),
+    /// `-`, one past the last array element.
+    ArrayEnd,
+}
+
+impl PathSegment {
+    /// Classifies one unescaped step.
+    pub fn parse(step: &str) -> Self {
+        if step == "-" {
+            return Self::ArrayEnd;
+        }
+        if is_valid_index(step) {
+            if let Ok(index) = step.parse() {
+                return Self::Index(index);
+            }
+        }
+        Self::Key(step.to_string())
+    }
+
+    /// The step as an object key.
+    pub fn key(&self) -> Cow<'_, str> {
+        match self {
+            Self::Key(key) => Cow::Borrowed(key),
+            Self::Index(index) => Cow::Owned(index.to_string()),
+            Self::ArrayEnd => Cow::Borrowed("-"),
+        }
+    }
+
+    /// The array index, if the step is one.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Self::Index(index) => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key())
+    }
+}
+
+/// Parses a JSON Pointer into typed segments.
+pub fn parse_typed_pointer(pointer: &str) -> Vec<PathSegment> {
+    to_typed_path(&parse_json_pointer(pointer))
+}
+
+/// Classifies the steps of an already split path.
+pub fn to_typed_path(path: &[String]) -> Vec<PathSegment> {
+    path.iter().map(|step| PathSegment::parse(step)).collect()
+}
+
+/// Formats typed segments back into a JSON Pointer string.
+pub fn format_typed_pointer(path: &[PathSegment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        out.push('/');
+        out.push_str(&escape_component(&segment.key()));
+    }
+    out
+}
+
+/// Typed variant of [`get`](crate::get): the value at `path`, if any.
+pub fn get_typed<'a>(val: &'a Value, path: &[PathSegment]) -> Option<&'a Value> {
+    let mut current = val;
+    for segment in path {
+        current = match current {
+            Value::Array(arr) => arr.get(segment.index()?)?,
+            Value::Object(map) => map.get(segment.key().as_ref())?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Mutable variant of [`get_typed`].
+pub fn get_typed_mut<'a>(val: &'a mut Value, path: &[PathSegment]) -> Option<&'a mut Value> {
+    let mut current = val;
+    for segment in path {
+        current = match current {
+            Value::Array(arr) => arr.get_mut(segment.index()?)?,
+            Value::Object(map) => map.get_mut(segment.key().as_ref())?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Typed variant of [`find`](crate::find), with the same results and errors.
+pub fn find_typed(val: &Value, path: &[PathSegment]) -> Result<Reference, JsonPointerError> {
+    let mut current = val;
+    let mut obj = None;
+    let mut key = None;
+    for (i, segment) in path.iter().enumerate() {
+        let is_last = i == path.len() - 1;
+        obj = Some(current.clone());
+        let next = match current {
+            Value::Array(arr) => {
+                let index = match segment {
+                    PathSegment::Index(index) => *index,
+                    PathSegment::ArrayEnd => arr.len(),
+                    PathSegment::Key(_) => return Err(JsonPointerError::InvalidIndex),
+                };
+                key = Some(ReferenceKey::Index(index));
+                arr.get(index)
+            }
+            Value::Object(map) => {
+                let step = segment.key();
+                let next = map.get(step.as_ref());
+                key = Some(ReferenceKey::String(step.into_owned()));
+                next
+            }
+            _ => return Err(JsonPointerError::NotFound),
+        };
+        match next {
+            Some(next) => current = next,
+            None if is_last => {
+                return Ok(Reference {
+                    val: None,
+                    obj,
+                    key,
+                })
+            }
+            None => return Err(JsonPointerError::NotFound),
+        }
+    }
+    Ok(Reference {
+        val: Some(current.clone()),
+        obj,
+        key,
+    })
+}
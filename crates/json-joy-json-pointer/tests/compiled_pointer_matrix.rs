@@ -0,0 +1,104 @@
+//! Compiled pointers and batch resolution.
+//!
+//! Not an upstream port: upstream has no pre-parsed pointer type. Results
+//! are checked against `get` over the same paths.
+
+use json_joy_json_pointer::{
+    get, parse_json_pointer, resolve_many, CompiledPointer, JsonPointerError,
+};
+use serde_json::{json, Value};
+
+fn doc() -> Value {
+    json!({
+        "users": [
+            {"name": "ann", "tags": ["a", "b"]},
+            {"name": "bob", "tags": []}
+        ],
+        "a/b": {"~c": 1},
+        "": {"": 2},
+        "10": "key",
+        "n": null
+    })
+}
+
+const POINTERS: [&str; 18] = [
+    "",
+    "/users",
+    "/users/0/name",
+    "/users/0/tags/1",
+    "/users/1/name",
+    "/users/1/tags/0",
+    "/users/2",
+    "/users/-",
+    "/users/0/name/x",
+    "/a~1b/~0c",
+    "/",
+    "//",
+    "/10",
+    "/n",
+    "/n/x",
+    "/users/0/tags/1",
+    "/missing/deep/path",
+    "/users/0/name",
+];
+
+#[test]
+fn compiled_pointer_resolve_matrix() {
+    let mut doc = doc();
+    for pointer in POINTERS {
+        let compiled = CompiledPointer::new(pointer).unwrap();
+        let path = parse_json_pointer(pointer);
+        assert_eq!(compiled.path(), path);
+        assert_eq!(compiled.is_root(), pointer.is_empty());
+        assert_eq!(compiled.to_string(), pointer);
+        assert_eq!(compiled.resolve(&doc), get(&doc, &path), "{pointer}");
+        let expected = get(&doc, &path).cloned();
+        assert_eq!(compiled.resolve_mut(&mut doc).cloned(), expected);
+    }
+
+    let compiled: CompiledPointer = "/users/1/name".parse().unwrap();
+    *compiled.resolve_mut(&mut doc).unwrap() = json!("eve");
+    assert_eq!(doc["users"][1]["name"], "eve");
+
+    // Array steps must be canonical indices.
+    for pointer in ["/users/01", "/users/+1", "/users/ 1"] {
+        assert_eq!(CompiledPointer::new(pointer).unwrap().resolve(&doc), None);
+    }
+    assert_eq!(
+        CompiledPointer::from_path(vec!["users".into(), "0".into()]).resolve(&doc),
+        Some(&doc["users"][0])
+    );
+    assert_eq!(
+        CompiledPointer::new("users"),
+        Err(JsonPointerError::PointerInvalid)
+    );
+}
+
+#[test]
+fn compiled_pointer_resolve_many_matrix() {
+    let doc = doc();
+    let pointers: Vec<CompiledPointer> = POINTERS
+        .iter()
+        .map(|p| CompiledPointer::new(p).unwrap())
+        .collect();
+    let expected: Vec<Option<&Value>> = pointers.iter().map(|p| p.resolve(&doc)).collect();
+    assert_eq!(resolve_many(&pointers, &doc), expected);
+
+    let reversed: Vec<CompiledPointer> = pointers.iter().rev().cloned().collect();
+    let mut results = resolve_many(&reversed, &doc);
+    results.reverse();
+    assert_eq!(results, expected);
+
+    assert!(resolve_many(&[], &doc).is_empty());
+
+    // Many pointers sharing long prefixes.
+    let wide = json!({"a": {"b": (0..100).map(|i| json!({"v": i})).collect::<Vec<_>>()}});
+    let pointers: Vec<CompiledPointer> = (0..150)
+        .rev()
+        .map(|i| CompiledPointer::new(&format!("/a/b/{i}/v")).unwrap())
+        .collect();
+    let results = resolve_many(&pointers, &wide);
+    for (pointer, result) in pointers.iter().zip(results) {
+        assert_eq!(result, pointer.resolve(&wide));
+    }
+}
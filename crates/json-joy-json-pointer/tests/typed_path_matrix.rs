@@ -0,0 +1,109 @@
+//! Typed path segments and their evaluation.
+//!
+//! Not an upstream port: upstream paths are untyped. Results are checked
+//! against `find` and `get` over the same pointers.
+
+use json_joy_json_pointer::{
+    find, find_typed, format_typed_pointer, get, get_typed, get_typed_mut, parse_json_pointer,
+    parse_typed_pointer, PathSegment,
+};
+use serde_json::{json, Value};
+
+fn doc() -> Value {
+    json!({
+        "users": [
+            {"name": "ann", "tags": ["a", "b"]},
+            {"name": "bob", "tags": []}
+        ],
+        "a/b": {"~c": 1},
+        "": {"": 2},
+        "10": "key",
+        "-": "dash",
+        "n": null
+    })
+}
+
+const POINTERS: [&str; 17] = [
+    "",
+    "/users",
+    "/users/0/name",
+    "/users/0/tags/1",
+    "/users/1/tags/0",
+    "/users/2",
+    "/users/-",
+    "/users/-/x",
+    "/users/x",
+    "/users/0/name/x",
+    "/a~1b/~0c",
+    "//",
+    "/10",
+    "/-",
+    "/n",
+    "/n/x",
+    "/missing/x",
+];
+
+#[test]
+fn typed_parse_matrix() {
+    let cases: &[(&str, &[PathSegment])] = &[
+        ("", &[]),
+        ("/", &[PathSegment::Key(String::new())]),
+        (
+            "/a~1b/0/-/~0",
+            &[
+                PathSegment::Key("a/b".into()),
+                PathSegment::Index(0),
+                PathSegment::ArrayEnd,
+                PathSegment::Key("~".into()),
+            ],
+        ),
+        (
+            "/01/1e2/-1/--",
+            &[
+                PathSegment::Key("01".into()),
+                PathSegment::Key("1e2".into()),
+                PathSegment::Key("-1".into()),
+                PathSegment::Key("--".into()),
+            ],
+        ),
+        ("/123", &[PathSegment::Index(123)]),
+        (
+            "/99999999999999999999999",
+            &[PathSegment::Key("99999999999999999999999".into())],
+        ),
+    ];
+    for (pointer, expected) in cases {
+        let typed = parse_typed_pointer(pointer);
+        assert_eq!(typed, *expected, "{pointer}");
+        assert_eq!(format_typed_pointer(&typed), *pointer);
+    }
+    assert_eq!(PathSegment::Index(7).key(), "7");
+    assert_eq!(PathSegment::ArrayEnd.to_string(), "-");
+    assert_eq!(PathSegment::Key("k".into()).index(), None);
+}
+
+#[test]
+fn typed_eval_matches_untyped() {
+    let mut doc = doc();
+    for pointer in POINTERS {
+        let path = parse_json_pointer(pointer);
+        let typed = parse_typed_pointer(pointer);
+        assert_eq!(get_typed(&doc, &typed), get(&doc, &path), "{pointer}");
+        assert_eq!(find_typed(&doc, &typed), find(&doc, &path), "{pointer}");
+        let expected = get(&doc, &path).cloned();
+        assert_eq!(get_typed_mut(&mut doc, &typed).cloned(), expected);
+    }
+
+    // Numeric segments still address object keys.
+    assert_eq!(
+        get_typed(&doc, &[PathSegment::Index(10)]),
+        Some(&json!("key"))
+    );
+    assert_eq!(
+        get_typed(&doc, &[PathSegment::ArrayEnd]),
+        Some(&json!("dash"))
+    );
+
+    *get_typed_mut(&mut doc, &parse_typed_pointer("/users/1/name")).unwrap() = json!("eve");
+    assert_eq!(doc["users"][1]["name"], "eve");
+}
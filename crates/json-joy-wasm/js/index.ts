@@ -29,9 +29,29 @@
  */
 
 export { Model } from './src/Model';
-export type { WasmModelClass } from './src/Model';
+export type {
+  LimitError,
+  ModelLimits,
+  ModelStats,
+  WasmModelClass,
+} from './src/Model';
 export { ModelApi } from './src/ModelApi';
-export { Patch } from './src/Patch';
+export {
+  Patch,
+  initPatchCodecs,
+  patchToCompact,
+  patchFromCompact,
+  patchToVerbose,
+  patchFromVerbose,
+} from './src/Patch';
+export type { WasmPatchCodecs } from './src/Patch';
+export { PatchBuilder } from './src/PatchBuilder';
+export type {
+  Id,
+  Span,
+  WasmPatchBuilder,
+  WasmPatchBuilderClass,
+} from './src/PatchBuilder';
 export {
   NodeApi,
   ObjApi,
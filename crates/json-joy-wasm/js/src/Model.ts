@@ -8,6 +8,7 @@
 
 import { ModelApi } from './ModelApi';
 import { Patch } from './Patch';
+import { PatchBuilder } from './PatchBuilder';
 import type { WasmModel } from './nodes';
 
 /**
@@ -26,12 +27,59 @@ export interface WasmModelClass {
   new (sid?: bigint): WasmModel;
   /** Static factory form: `WasmModel.create(sid?)`. */
   create(sid?: bigint): WasmModel;
+  /** Empty document on a server clock starting at `time` (default 1). */
+  withServerClock(time?: bigint): WasmModel;
   /** Decode from binary structural encoding. */
   fromBinary(data: Uint8Array): WasmModel;
   /** Generate a random session ID (static on the WASM class). */
   rndSid(): bigint;
 }
 
+/**
+ * Size limits on remote patches, see {@link Model.setLimits}.
+ */
+export interface ModelLimits {
+  /** Largest accepted patch, in bytes. */
+  maxPatchBytes?: number;
+  /** Largest the encoded document may grow to, in bytes (estimated). */
+  maxModelBytes?: number;
+  /** Most patches accepted in one {@link Model.applyBatch} call. */
+  maxBatchCount?: number;
+}
+
+/**
+ * Thrown by {@link Model.applyPatch} and {@link Model.applyBatch} when a
+ * limit set with {@link Model.setLimits} is exceeded.
+ */
+export interface LimitError {
+  code: 'PATCH_TOO_LARGE' | 'MODEL_TOO_LARGE' | 'BATCH_TOO_LARGE';
+  limit: number;
+  actual: number;
+  message: string;
+}
+
+/**
+ * Size counters returned by {@link Model.stats}.  Timestamps are
+ * `[sid, time]` pairs.
+ */
+export interface ModelStats {
+  sid: number;
+  time: number;
+  /** Latest timestamp seen from each remote session. */
+  peers: Array<[number, number]>;
+  /** Number of patches applied to the document. */
+  tick: number;
+  node_count: number;
+  /** Node counts by kind: `con`, `val`, `obj`, `vec`, `str`, `bin`, `arr`. */
+  node_kinds: Record<string, number>;
+  /** Deleted items still held in `str`, `bin` and `arr` nodes. */
+  tombstones: number;
+  /** Rough WASM heap footprint of the document, in bytes. */
+  memory_estimate: number;
+  /** Remote patches passed to {@link Model.applyPatch} and {@link Model.applyBatch}. */
+  patches: { applied: number; applied_bytes: number; rejected: number };
+}
+
 /**
  * A JSON CRDT document.
  *
@@ -115,7 +163,22 @@ export class Model {
   }
 
   /**
-   * Decode a document from its binary structural encoding.
+   * Create a new empty document on a server clock.  Only patches issued by
+   * the server, in order, can be applied to it; `fork` gives a
+   * logical-clock copy for local editing.
+   *
+   * @param time Starting server time.  Defaults to 1.
+   *
+   * Mirrors `Model.withServerClock(schema?, time?)`.
+   */
+  static withServerClock(time?: number): Model {
+    const bigTime = time !== undefined ? BigInt(time) : undefined;
+    return new Model(Model.requireWasm().withServerClock(bigTime));
+  }
+
+  /**
+   * Decode a document from its binary structural encoding, with either a
+   * logical or a server clock.
    *
    * Mirrors `Model.fromBinary(bytes)`.
    */
@@ -177,6 +240,20 @@ export class Model {
     return this._wasm.view();
   }
 
+  /**
+   * Return the view encoded as CBOR.  Cheaper than `view()` for large
+   * documents that are sent on rather than read, and `bin` nodes stay byte
+   * strings.
+   */
+  viewCbor(): Uint8Array {
+    return this._wasm.viewCbor();
+  }
+
+  /** Return the view encoded as MessagePack, see {@link Model.viewCbor}. */
+  viewMsgpack(): Uint8Array {
+    return this._wasm.viewMsgpack();
+  }
+
   // ── Forking ────────────────────────────────────────────────────────────────
 
   /**
@@ -222,10 +299,58 @@ export class Model {
   }
 
   /**
-   * Apply multiple remote patches in order.
+   * Apply multiple remote patches in order, in one call into WASM.  Nothing
+   * is applied if any patch is invalid or over a limit.
+   *
+   * Also takes an encoded batch, such as one compressed with the WASM
+   * `patchBatchEncode` function.
+   */
+  applyBatch(patches: Array<Patch | Uint8Array> | Uint8Array): void {
+    if (patches instanceof Uint8Array) {
+      this._wasm.applyPatchBatch(patches);
+      return;
+    }
+    const bins = patches.map((p) => (p instanceof Patch ? p.bin : p));
+    const batch = new Uint8Array(
+      bins.reduce((size, bin) => size + 4 + bin.length, 0),
+    );
+    const view = new DataView(batch.buffer);
+    let offset = 0;
+    for (const bin of bins) {
+      view.setUint32(offset, bin.length, true);
+      batch.set(bin, offset + 4);
+      offset += 4 + bin.length;
+    }
+    this._wasm.applyPatchBatch(batch);
+  }
+
+  /**
+   * Limit the remote patches {@link applyPatch} and {@link applyBatch}
+   * accept.  Omitted limits are lifted.  A patch over a limit throws a
+   * {@link LimitError} and is not applied.
+   */
+  setLimits(limits: ModelLimits): void {
+    this._wasm.setLimits(
+      limits.maxPatchBytes,
+      limits.maxModelBytes,
+      limits.maxBatchCount,
+    );
+  }
+
+  /**
+   * Size counters of this document, for monitoring.  Walks every node, so
+   * avoid calling it on each edit.
+   */
+  stats(): ModelStats {
+    return JSON.parse(this._wasm.stats()) as ModelStats;
+  }
+
+  /**
+   * A {@link PatchBuilder} continuing this document's clock.  Pass its patch
+   * to {@link applyPatch} to apply it here.
    */
-  applyBatch(patches: Array<Patch | Uint8Array>): void {
-    for (const p of patches) this.applyPatch(p);
+  patchBuilder(): PatchBuilder {
+    return new PatchBuilder(this._wasm.patchBuilder());
   }
 
   // ── Lifecycle ──────────────────────────────────────────────────────────────
@@ -12,3 +12,82 @@ export class Patch {
     return this.bin.length === 0;
   }
 }
+
+/**
+ * The patch JSON conversion functions exported by the WASM package.
+ *
+ * Pass the package module itself to {@link initPatchCodecs}:
+ *
+ * ```ts
+ * import init, * as wasm from '../pkg/json_joy_wasm';
+ *
+ * await init();
+ * initPatchCodecs(wasm);
+ * ```
+ */
+export interface WasmPatchCodecs {
+  patchToCompact(bin: Uint8Array): string;
+  patchFromCompact(json: string): Uint8Array;
+  patchToVerbose(bin: Uint8Array): string;
+  patchFromVerbose(json: string): Uint8Array;
+}
+
+let codecs: WasmPatchCodecs | null = null;
+
+/** Register the WASM patch codecs used by the functions below. */
+export function initPatchCodecs(wasm: WasmPatchCodecs): void {
+  codecs = wasm;
+}
+
+function requireCodecs(): WasmPatchCodecs {
+  if (!codecs) {
+    throw new Error(
+      'WASM not initialised — call initPatchCodecs(wasm) after await init()',
+    );
+  }
+  return codecs;
+}
+
+const toBin = (patch: Patch | Uint8Array): Uint8Array =>
+  patch instanceof Patch ? patch.bin : patch;
+
+/**
+ * Convert a patch to the compact JSON format.
+ *
+ * Mirrors `encode` from upstream `json-crdt-patch/codec/compact`.
+ */
+export function patchToCompact(patch: Patch | Uint8Array): unknown[] {
+  return JSON.parse(requireCodecs().patchToCompact(toBin(patch))) as unknown[];
+}
+
+/**
+ * Convert a compact JSON patch to a {@link Patch}.  Throws if any operation
+ * is malformed.
+ *
+ * Mirrors `decode` from upstream `json-crdt-patch/codec/compact`.
+ */
+export function patchFromCompact(json: unknown[]): Patch {
+  return new Patch(requireCodecs().patchFromCompact(JSON.stringify(json)));
+}
+
+/**
+ * Convert a patch to the verbose JSON format.
+ *
+ * Mirrors `encode` from upstream `json-crdt-patch/codec/verbose`.
+ */
+export function patchToVerbose(patch: Patch | Uint8Array): Record<string, unknown> {
+  return JSON.parse(requireCodecs().patchToVerbose(toBin(patch))) as Record<
+    string,
+    unknown
+  >;
+}
+
+/**
+ * Convert a verbose JSON patch to a {@link Patch}.  Throws if any operation
+ * is malformed.
+ *
+ * Mirrors `decode` from upstream `json-crdt-patch/codec/verbose`.
+ */
+export function patchFromVerbose(json: Record<string, unknown>): Patch {
+  return new Patch(requireCodecs().patchFromVerbose(JSON.stringify(json)));
+}
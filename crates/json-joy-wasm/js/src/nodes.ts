@@ -13,6 +13,7 @@
  * Mirrors the upstream `nodes.ts` API from `json-joy`.
  */
 
+import type { WasmPatchBuilder } from './PatchBuilder';
 import type { ApiPath, PathKey } from './types';
 import { normalizePath, pathToJson } from './types';
 
@@ -34,12 +35,31 @@ export interface WasmModel {
   toBinary(): Uint8Array;
   /** Current JSON view of the whole document. */
   view(): unknown;
+  /** The view encoded as CBOR. */
+  viewCbor(): Uint8Array;
+  /** The view encoded as MessagePack. */
+  viewMsgpack(): Uint8Array;
   /** The session ID of the local logical clock (as BigInt). */
   sid(): bigint;
   /** Fork this document with an optional new session ID. */
   fork(sid?: bigint): WasmModel;
   /** Apply a remote patch (binary) to this document. */
   applyPatch(bytes: Uint8Array): void;
+  /**
+   * Apply remote patches: each preceded by its length as a u32 LE, or a
+   * batch from `patchBatchEncode`.
+   */
+  applyPatchBatch(batch: Uint8Array): void;
+  /** Limit the remote patches accepted; omitted limits are lifted. */
+  setLimits(
+    maxPatchBytes?: number,
+    maxModelBytes?: number,
+    maxBatchCount?: number,
+  ): void;
+  /** Size counters of this document, as JSON text. */
+  stats(): string;
+  /** A patch builder continuing this document's clock. */
+  patchBuilder(): WasmPatchBuilder;
 
   // ── Editing ────────────────────────────────────────────────────────────────
   apiSet(json_str: string): void;
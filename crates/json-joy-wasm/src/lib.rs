@@ -4,6 +4,18 @@
 //! library API.  The TypeScript layer in `js/` wraps this to reconstruct the
 //! exact chainable API feel (`model.api.str(['key']).ins(0, 'hello')`).
 //!
+//! Stateless `jsonPatchApply`, `jsonPointerGet` and `jsonDiff` functions
+//! expose plain JSON Patch and JSON Pointer operations without a `Model`;
+//! `patchInspect` summarises a binary CRDT patch, `patchToCompact` and
+//! `patchToVerbose` convert one to JSON and back, and `patchBatchEncode`
+//! compresses a batch of them.  `setLimits` caps the size of the remote
+//! patches a `Model` accepts, and `stats` reports its size.  `viewCbor` and
+//! `viewMsgpack` return the view as binary instead of a JS value.
+//! `withServerClock` and `fromBinary` also give documents on a server clock,
+//! which only take the server's own patches.
+//! `PatchBuilder` builds a patch op by op, for callers that know exactly
+//! what changed.
+//!
 //! # Boundary discipline
 //!
 //! Every public `#[wasm_bindgen]` method performs exactly **one** meaningful
@@ -15,16 +27,24 @@ use wasm_bindgen::prelude::*;
 
 use json_joy::json_crdt::codec::structural::binary as structural_binary;
 use json_joy::json_crdt::model::api::find_path;
-use json_joy::json_crdt::model::util::random_session_id;
+use json_joy::json_crdt::model::util::{
+    random_session_id, RandomSessionIds, RangeSessionIds, SeededSessionIds, SessionIdGenerator,
+};
 use json_joy::json_crdt::model::Model as CrdtModel;
 use json_joy::json_crdt::nodes::{BinNode, CrdtNode, IndexExt};
 use json_joy::json_crdt::ORIGIN;
 use json_joy::json_crdt_diff::JsonCrdtDiff;
-use json_joy::json_crdt_patch::clock::{Ts, Tss};
+use json_joy::json_crdt_patch::clock::{ServerClockVector, Ts, Tss};
+use json_joy::json_crdt_patch::codec::batch::{
+    decode_batch, decode_batch_limited, encode_batch_v1, encode_batch_v2, BatchCompression,
+};
+use json_joy::json_crdt_patch::enums::SESSION;
 use json_joy::json_crdt_patch::operations::Op;
 use json_joy::json_crdt_patch::patch::Patch;
-use json_joy::json_crdt_patch::patch_builder::PatchBuilder;
+use json_joy::json_crdt_patch::patch_builder::PatchBuilder as CrdtPatchBuilder;
+use json_joy::{json_patch, json_patch_diff};
 use json_joy_json_pack::PackValue;
+use json_joy_json_pointer::CompiledPointer;
 use serde_json::Value;
 
 // ── Internal helpers ─────────────────────────────────────────────────────────
@@ -37,7 +57,7 @@ use serde_json::Value;
 /// - Strings → StrNode (CRDT-editable, so `api.str([key]).ins()` works after `api.set(...)`)
 /// - Arrays → ArrNode (elements via `build_json` recursively)
 /// - Objects → ObjNode (values via `build_json` recursively)
-fn build_json(builder: &mut PatchBuilder, v: &Value) -> Ts {
+fn build_json(builder: &mut CrdtPatchBuilder, v: &Value) -> Ts {
     match v {
         Value::Null | Value::Bool(_) | Value::Number(_) => {
             builder.con_val(PackValue::from_json_scalar(v))
@@ -74,7 +94,7 @@ fn build_json(builder: &mut PatchBuilder, v: &Value) -> Ts {
 
 /// Like `build_json` but treats scalars as `con` constants and compound types
 /// as structural CRDT nodes.  Mirrors `PatchBuilder.constOrJson()`.
-fn const_or_json(builder: &mut PatchBuilder, v: &Value) -> Ts {
+fn const_or_json(builder: &mut CrdtPatchBuilder, v: &Value) -> Ts {
     match v {
         Value::Array(_) | Value::Object(_) => build_json(builder, v),
         _ => builder.con_val(PackValue::from_json_scalar(v)),
@@ -140,6 +160,24 @@ pub struct Model {
     /// since the last `view()` call we can return the cached `JsValue` in O(1)
     /// (a single reference-count bump) instead of rebuilding the full tree.
     view_cache: Option<(u64, JsValue)>,
+    /// Guards on remote input, set by `setLimits`.
+    limits: Limits,
+    /// Estimated encoded size of the document: exact when last measured,
+    /// plus the sizes of the patches applied since.
+    model_bytes: usize,
+    /// Remote patches applied and rejected, reported by `stats`.
+    counters: PatchCounters,
+}
+
+/// Counts of remote patches, for `Model.stats`.
+#[derive(Debug, Clone, Copy, Default)]
+struct PatchCounters {
+    /// Patches applied by `applyPatch` and `applyPatchBatch`.
+    applied: u64,
+    /// Bytes of the applied patches.
+    applied_bytes: u64,
+    /// `applyPatch` and `applyPatchBatch` calls that failed.
+    rejected: u64,
 }
 
 impl Model {
@@ -148,6 +186,9 @@ impl Model {
             inner,
             local_changes: Vec::new(),
             view_cache: None,
+            limits: Limits::default(),
+            model_bytes: 0,
+            counters: PatchCounters::default(),
         }
     }
 
@@ -156,11 +197,11 @@ impl Model {
     /// `local_changes`.
     fn with_builder<F>(&mut self, f: F) -> Result<(), String>
     where
-        F: FnOnce(&CrdtModel, &mut PatchBuilder) -> Result<(), String>,
+        F: FnOnce(&CrdtModel, &mut CrdtPatchBuilder) -> Result<(), String>,
     {
         let sid = self.inner.clock.sid;
         let time = self.inner.clock.time;
-        let mut builder = PatchBuilder::new(sid, time);
+        let mut builder = CrdtPatchBuilder::new(sid, time);
         f(&self.inner, &mut builder)?;
         let patch = builder.flush();
         if !patch.ops.is_empty() {
@@ -180,6 +221,228 @@ impl Model {
         }
         find_path(&self.inner, root_val, path).map_err(|e| format!("path not found: {e:?}"))
     }
+
+    /// Parse `targets_json` (a JSON array of documents) and move the model
+    /// through each of them, returning the binary patch of every step.
+    fn diff_batch(&mut self, targets_json: &str) -> Result<Vec<Option<Vec<u8>>>, String> {
+        let targets: Vec<Value> =
+            serde_json::from_str(targets_json).map_err(|e| format!("invalid JSON: {e}"))?;
+        let patches = self.inner.diff_many(&targets);
+        if patches.iter().any(Option::is_some) {
+            self.view_cache = None;
+        }
+        Ok(patches)
+    }
+
+    /// Check `patches` against the patch and model size limits.  Near the
+    /// model limit the document is re-measured before giving up, so local
+    /// edits and deletions are accounted for.
+    fn check_limits(&mut self, patches: &[&[u8]]) -> Result<(), LimitError> {
+        if let Some(limit) = self.limits.max_patch_bytes {
+            if let Some(patch) = patches.iter().find(|p| p.len() > limit) {
+                return Err(LimitError::Patch {
+                    limit,
+                    actual: patch.len(),
+                });
+            }
+        }
+        if let Some(limit) = self.limits.max_model_bytes {
+            let added: usize = patches.iter().map(|p| p.len()).sum();
+            if self.model_bytes + added > limit {
+                self.model_bytes = structural_binary::encode(&self.inner).len();
+                if self.model_bytes + added > limit {
+                    return Err(LimitError::Model {
+                        limit,
+                        actual: self.model_bytes + added,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check and decode binary patches.
+    fn admit(&mut self, patches: &[&[u8]]) -> Result<Vec<Patch>, ApplyError> {
+        self.check_limits(patches)?;
+        let decoded = patches
+            .iter()
+            .map(|bytes| {
+                Patch::from_binary(bytes)
+                    .map_err(|e| ApplyError::Invalid(format!("patch decode error: {e:?}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.inner.clock.sid == SESSION::SERVER {
+            self.check_server_clock(&decoded)?;
+        }
+        Ok(decoded)
+    }
+
+    /// A server-clock document only takes patches issued by the server, in
+    /// order: its encoding drops session IDs, so a patch from any other
+    /// session would be written back as the server's.  Mirrors upstream
+    /// `ServerClockVector.observe`, except that a rejected patch leaves the
+    /// document untouched.
+    fn check_server_clock(&self, patches: &[Patch]) -> Result<(), ApplyError> {
+        let mut clock = ServerClockVector::new(self.inner.clock.time);
+        for patch in patches {
+            let Some(id) = patch.get_id() else {
+                continue;
+            };
+            if id.sid != SESSION::SERVER {
+                return Err(ApplyError::Invalid(format!(
+                    "server clock: patch from session {}",
+                    id.sid
+                )));
+            }
+            clock
+                .observe(id, patch.span())
+                .map_err(|e| ApplyError::Invalid(format!("server clock: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// Check, decode and apply binary patches.  Nothing is applied unless
+    /// every patch passes.
+    fn apply_binary(&mut self, patches: &[&[u8]]) -> Result<(), ApplyError> {
+        let decoded = self
+            .admit(patches)
+            .inspect_err(|_| self.counters.rejected += 1)?;
+        for patch in &decoded {
+            self.inner.apply_patch(patch);
+        }
+        let bytes: usize = patches.iter().map(|p| p.len()).sum();
+        self.model_bytes += bytes;
+        self.counters.applied += patches.len() as u64;
+        self.counters.applied_bytes += bytes as u64;
+        self.view_cache = None;
+        Ok(())
+    }
+
+    /// Decode a v1 or v2 patch batch and apply it, checking the batch count
+    /// first.  A compressed body may not inflate past the model limit.
+    fn apply_batch(&mut self, data: &[u8]) -> Result<(), ApplyError> {
+        let max_body = self.limits.max_model_bytes.unwrap_or(usize::MAX);
+        let limit = self.limits.max_batch_count;
+        let patches = decode_batch_limited(data, max_body)
+            .map_err(|e| ApplyError::Invalid(e.to_string()))
+            .and_then(|patches| match limit {
+                Some(limit) if patches.len() > limit => Err(LimitError::Batch {
+                    limit,
+                    actual: patches.len(),
+                }
+                .into()),
+                _ => Ok(patches),
+            });
+        match patches {
+            Ok(patches) => {
+                self.apply_binary(&patches.iter().map(Vec::as_slice).collect::<Vec<_>>())
+            }
+            Err(e) => {
+                self.counters.rejected += 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// The core [`ModelStats`](json_joy::json_crdt::ModelStats) JSON plus
+    /// the remote patch counters.
+    fn stats_json(&self) -> Value {
+        let mut stats = self.inner.stats().to_json();
+        stats["patches"] = serde_json::json!({
+            "applied": self.counters.applied,
+            "applied_bytes": self.counters.applied_bytes,
+            "rejected": self.counters.rejected,
+        });
+        stats
+    }
+}
+
+// ── Limits ───────────────────────────────────────────────────────────────────
+
+/// Size guards for patches received from peers.  `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+struct Limits {
+    max_patch_bytes: Option<usize>,
+    max_model_bytes: Option<usize>,
+    max_batch_count: Option<usize>,
+}
+
+/// Input over one of the configured [`Limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LimitError {
+    Patch { limit: usize, actual: usize },
+    Model { limit: usize, actual: usize },
+    Batch { limit: usize, actual: usize },
+}
+
+impl LimitError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Patch { .. } => "PATCH_TOO_LARGE",
+            Self::Model { .. } => "MODEL_TOO_LARGE",
+            Self::Batch { .. } => "BATCH_TOO_LARGE",
+        }
+    }
+
+    /// The error as a plain JS object: `{ code, limit, actual, message }`.
+    fn to_js(self) -> JsValue {
+        let (Self::Patch { limit, actual }
+        | Self::Model { limit, actual }
+        | Self::Batch { limit, actual }) = self;
+        let ser = serde_wasm_bindgen::Serializer::json_compatible();
+        serde_json::json!({
+            "code": self.code(),
+            "limit": limit,
+            "actual": actual,
+            "message": self.to_string(),
+        })
+        .serialize(&ser)
+        .unwrap_or(JsValue::NULL)
+    }
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Patch { limit, actual } => {
+                write!(f, "patch of {actual} bytes exceeds the {limit}-byte limit")
+            }
+            Self::Model { limit, actual } => {
+                write!(
+                    f,
+                    "document would reach {actual} bytes, over the {limit}-byte limit"
+                )
+            }
+            Self::Batch { limit, actual } => {
+                write!(
+                    f,
+                    "batch of {actual} patches exceeds the {limit}-patch limit"
+                )
+            }
+        }
+    }
+}
+
+/// Why a remote patch was not applied.
+#[derive(Debug)]
+enum ApplyError {
+    Limit(LimitError),
+    Invalid(String),
+}
+
+impl From<LimitError> for ApplyError {
+    fn from(e: LimitError) -> Self {
+        Self::Limit(e)
+    }
+}
+
+impl From<ApplyError> for JsValue {
+    fn from(e: ApplyError) -> Self {
+        match e {
+            ApplyError::Limit(e) => e.to_js(),
+            ApplyError::Invalid(msg) => JsValue::from_str(&msg),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -200,13 +463,33 @@ impl Model {
         Self::from_inner(inner)
     }
 
-    /// Decode a model from its binary representation.
+    /// Create a new empty document with a session ID from `ids`.
+    #[wasm_bindgen(js_name = "createWith")]
+    pub fn create_with(ids: &mut SessionIds) -> Model {
+        Self::from_inner(CrdtModel::create_with(ids.inner.as_mut()))
+    }
+
+    /// Create a new empty document on a server clock starting at `time`
+    /// (default `1`).  Only patches issued by the server, in order, can be
+    /// applied to it.
+    ///
+    /// Mirrors `Model.withServerClock(schema?, time?)`.
+    #[wasm_bindgen(js_name = "withServerClock")]
+    pub fn with_server_clock(time: Option<u64>) -> Model {
+        Self::from_inner(CrdtModel::new_server(time.unwrap_or(1)))
+    }
+
+    /// Decode a model from its binary representation, in either the logical
+    /// or the server clock encoding.
     ///
     /// Mirrors `Model.fromBinary(bytes)`.
     #[wasm_bindgen(js_name = "fromBinary")]
     pub fn from_binary(data: &[u8]) -> Result<Model, JsValue> {
         structural_binary::decode(data)
-            .map(Self::from_inner)
+            .map(|inner| Model {
+                model_bytes: data.len(),
+                ..Self::from_inner(inner)
+            })
             .map_err(|e| JsValue::from_str(&format!("decode error: {e:?}")))
     }
 
@@ -244,21 +527,50 @@ impl Model {
         js
     }
 
-    /// Fork this document with a new session ID.
+    /// Return the current view encoded as CBOR, written straight from the
+    /// document so no JSON crosses the boundary.  `bin` nodes are byte
+    /// strings.
+    #[wasm_bindgen(js_name = "viewCbor")]
+    pub fn view_cbor(&self) -> Vec<u8> {
+        self.inner.view_cbor()
+    }
+
+    /// Return the current view encoded as MessagePack; see `viewCbor`.
+    #[wasm_bindgen(js_name = "viewMsgpack")]
+    pub fn view_msgpack(&self) -> Vec<u8> {
+        self.inner.view_msgpack()
+    }
+
+    /// Fork this document with a new session ID.  The old session is kept
+    /// as a peer, so forking a server-clock document gives a logical-clock
+    /// one that still encodes the server's timestamps.
     ///
     /// Mirrors `model.fork(sid?)`.
     pub fn fork(&self, sid: Option<u64>) -> Model {
         let new_sid = sid.unwrap_or_else(random_session_id);
         let mut cloned = self.inner.clone();
-        cloned.clock.sid = new_sid;
+        cloned.clock = self.inner.clock.fork(new_sid);
         Self::from_inner(cloned)
     }
 
+    /// Fork this document with a session ID from `ids`.
+    #[wasm_bindgen(js_name = "forkWith")]
+    pub fn fork_with(&self, ids: &mut SessionIds) -> Model {
+        self.fork(Some(ids.inner.next_sid()))
+    }
+
     /// Return this document's session ID.
     pub fn sid(&self) -> u64 {
         self.inner.clock.sid
     }
 
+    /// A `PatchBuilder` continuing this document's clock.  Pass its patch to
+    /// `applyPatch` to apply it here.
+    #[wasm_bindgen(js_name = "patchBuilder")]
+    pub fn patch_builder(&self) -> PatchBuilder {
+        PatchBuilder::at(self.inner.clock.sid, self.inner.clock.time)
+    }
+
     /// Generate a fresh random session ID.
     ///
     /// Mirrors `Model.sid()` / `model.rndSid()`.
@@ -272,13 +584,52 @@ impl Model {
     /// Apply a remote patch (received from a peer).
     ///
     /// Mirrors `model.applyPatch(patch)` where `patch` is passed as binary.
+    ///
+    /// Fails with a `{ code, limit, actual, message }` object when the patch
+    /// is over a limit set by `setLimits`.
     #[wasm_bindgen(js_name = "applyPatch")]
     pub fn apply_patch(&mut self, patch_bytes: &[u8]) -> Result<(), JsValue> {
-        let patch = Patch::from_binary(patch_bytes)
-            .map_err(|e| JsValue::from_str(&format!("patch decode error: {e:?}")))?;
-        self.inner.apply_patch(&patch);
-        self.view_cache = None;
-        Ok(())
+        Ok(self.apply_binary(&[patch_bytes])?)
+    }
+
+    /// Apply a batch of remote patches: v1, each preceded by its length as a
+    /// little-endian `u32`, or v2 from `patchBatchEncode`.  The whole batch
+    /// is checked against the limits and decoded before any of it is
+    /// applied.
+    #[wasm_bindgen(js_name = "applyPatchBatch")]
+    pub fn apply_patch_batch(&mut self, batch: &[u8]) -> Result<(), JsValue> {
+        Ok(self.apply_batch(batch)?)
+    }
+
+    /// Return size counters for this document as JSON text: session and
+    /// clock, node counts by kind, tombstones, a memory estimate in bytes,
+    /// and how many remote patches were applied or rejected.
+    pub fn stats(&self) -> String {
+        self.stats_json().to_string()
+    }
+
+    /// Limit the patches `applyPatch` and `applyPatchBatch` accept: the size
+    /// of each patch, the size the document may grow to, and the number of
+    /// patches in a batch.  Omitted limits are lifted.  Local edits are not
+    /// limited, and forks start without limits.
+    ///
+    /// The document size is estimated from its encoded size, re-measured
+    /// here and whenever a patch would take the estimate over the limit.
+    #[wasm_bindgen(js_name = "setLimits")]
+    pub fn set_limits(
+        &mut self,
+        max_patch_bytes: Option<u32>,
+        max_model_bytes: Option<u32>,
+        max_batch_count: Option<u32>,
+    ) {
+        self.limits = Limits {
+            max_patch_bytes: max_patch_bytes.map(|n| n as usize),
+            max_model_bytes: max_model_bytes.map(|n| n as usize),
+            max_batch_count: max_batch_count.map(|n| n as usize),
+        };
+        if self.limits.max_model_bytes.is_some() {
+            self.model_bytes = structural_binary::encode(&self.inner).len();
+        }
     }
 
     // ── Local editing API ─────────────────────────────────────────────────
@@ -689,7 +1040,7 @@ impl Model {
                 Some(node) => differ.diff(node, &next),
                 None => {
                     // Document is empty — treat as setting the root.
-                    let mut builder = PatchBuilder::new(sid, time);
+                    let mut builder = CrdtPatchBuilder::new(sid, time);
                     let id = build_json(&mut builder, &next);
                     builder.root(id);
                     builder.flush()
@@ -707,6 +1058,20 @@ impl Model {
         Ok(bytes)
     }
 
+    /// Batched `diffApply`: move the document through each document in the
+    /// JSON array `targets_json` in turn, and return one patch per target.
+    ///
+    /// Each entry is a `Uint8Array`, or `undefined` where that step changes
+    /// nothing.  One boundary crossing and one patch encoder serve the whole
+    /// batch.
+    #[wasm_bindgen(js_name = "diffApplyBatch")]
+    pub fn diff_apply_batch(&mut self, targets_json: &str) -> Result<Vec<JsValue>, JsValue> {
+        let patches = self
+            .diff_batch(targets_json)
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(patches.into_iter().map(JsValue::from).collect())
+    }
+
     // ── View helpers ─────────────────────────────────────────────────────
 
     /// Return the current length of the `str` node at `path`.
@@ -789,6 +1154,332 @@ impl Model {
     }
 }
 
+// ── Session IDs ──────────────────────────────────────────────────────────────
+
+/// A session ID generator for `Model.createWith` / `model.forkWith`.
+///
+/// Seeded generators give reproducible IDs (e.g. for tests); range
+/// generators keep IDs inside a slice of the ID space assigned to a server.
+#[wasm_bindgen]
+pub struct SessionIds {
+    inner: Box<dyn SessionIdGenerator>,
+}
+
+#[wasm_bindgen]
+impl SessionIds {
+    /// Random IDs, as used by `Model.create()`.
+    pub fn random() -> SessionIds {
+        Self {
+            inner: Box::new(RandomSessionIds),
+        }
+    }
+
+    /// Reproducible IDs: the same `seed` always yields the same sequence.
+    pub fn seeded(seed: u64) -> SessionIds {
+        Self {
+            inner: Box::new(SeededSessionIds::new(seed)),
+        }
+    }
+
+    /// IDs within `start..=end`, reproducible if `seed` is given.
+    ///
+    /// The range must be non-empty and within `0xFFFF..=2^53-1`.
+    pub fn range(start: u64, end: u64, seed: Option<u64>) -> Result<SessionIds, JsValue> {
+        let inner: Option<Box<dyn SessionIdGenerator>> = match seed {
+            Some(seed) => RangeSessionIds::new(start..=end, SeededSessionIds::new(seed))
+                .map(|ids| Box::new(ids) as _),
+            None => {
+                RangeSessionIds::new(start..=end, RandomSessionIds).map(|ids| Box::new(ids) as _)
+            }
+        };
+        inner
+            .map(|inner| Self { inner })
+            .ok_or_else(|| JsValue::from_str(&format!("invalid session ID range {start}..={end}")))
+    }
+
+    /// Return the next session ID.
+    #[wasm_bindgen(js_name = "nextSid")]
+    pub fn next_sid(&mut self) -> u64 {
+        self.inner.next_sid()
+    }
+}
+
+// ── Patch builder ────────────────────────────────────────────────────────────
+
+/// Largest integer a JS number holds exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+fn id_part(n: f64) -> Result<u64, String> {
+    if n.fract() == 0.0 && (0.0..=MAX_SAFE_INTEGER).contains(&n) {
+        Ok(n as u64)
+    } else {
+        Err(format!("invalid ID component {n}"))
+    }
+}
+
+/// Parse a `[sid, time]` pair.
+fn id_from_js(id: &[f64]) -> Result<Ts, String> {
+    match id {
+        [sid, time] => Ok(Ts::new(id_part(*sid)?, id_part(*time)?)),
+        _ => Err(format!(
+            "ID must be a [sid, time] pair, got {} numbers",
+            id.len()
+        )),
+    }
+}
+
+fn id_to_js(id: Ts) -> Vec<f64> {
+    vec![id.sid as f64, id.time as f64]
+}
+
+/// Parse flat `[sid, time, span, …]` triples.
+fn spans_from_js(spans: &[f64]) -> Result<Vec<Tss>, String> {
+    if spans.is_empty() || !spans.len().is_multiple_of(3) {
+        return Err("spans must be non-empty [sid, time, span] triples".to_string());
+    }
+    spans
+        .chunks(3)
+        .map(|s| Ok(Tss::new(id_part(s[0])?, id_part(s[1])?, id_part(s[2])?)))
+        .collect()
+}
+
+/// Builds a CRDT patch operation by operation.
+///
+/// For applications that know exactly what changed: the patch holds just
+/// those operations, where `diffApply` would diff the whole document.
+/// IDs are `[sid, time]` pairs.  An insert returns the ID of its first item,
+/// and item `i` has ID `[sid, time + i]`.  Nothing is checked against a
+/// document, so IDs must name nodes of the right kind.
+///
+/// Mirrors `PatchBuilder` from `json-crdt-patch`, with a subset of its
+/// methods.
+#[wasm_bindgen]
+pub struct PatchBuilder {
+    inner: CrdtPatchBuilder,
+}
+
+impl PatchBuilder {
+    fn at(sid: u64, time: u64) -> PatchBuilder {
+        Self {
+            inner: CrdtPatchBuilder::new(sid, time),
+        }
+    }
+
+    fn set_key_inner(&mut self, obj: &[f64], key: &str, value: &[f64]) -> Result<(), String> {
+        let (obj, value) = (id_from_js(obj)?, id_from_js(value)?);
+        self.inner.ins_obj(obj, vec![(key.to_string(), value)]);
+        Ok(())
+    }
+
+    fn ins_str_inner(&mut self, obj: &[f64], after: &[f64], text: &str) -> Result<Ts, String> {
+        if text.is_empty() {
+            return Err("cannot insert empty text".to_string());
+        }
+        let (obj, after) = (id_from_js(obj)?, id_from_js(after)?);
+        Ok(self.inner.ins_str(obj, after, text.to_string()))
+    }
+
+    fn del_inner(&mut self, obj: &[f64], spans: &[f64]) -> Result<(), String> {
+        let (obj, spans) = (id_from_js(obj)?, spans_from_js(spans)?);
+        self.inner.del(obj, spans);
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl PatchBuilder {
+    /// A builder whose first operation gets ID `[sid, time]`.  Both are
+    /// plain numbers, like the components of every other ID.
+    #[wasm_bindgen(constructor)]
+    pub fn new(sid: f64, time: f64) -> Result<PatchBuilder, JsValue> {
+        let id = id_from_js(&[sid, time]).map_err(|e| JsValue::from_str(&e))?;
+        Ok(Self::at(id.sid, id.time))
+    }
+
+    /// Create a `con` constant holding a JSON value.
+    #[wasm_bindgen(js_name = "newCon")]
+    pub fn new_con(&mut self, value_json: &str) -> Result<Vec<f64>, JsValue> {
+        let v = parse_json(value_json)?;
+        Ok(id_to_js(self.inner.con_val(PackValue::from(&v))))
+    }
+
+    /// Create an empty `obj` node.
+    #[wasm_bindgen(js_name = "newObj")]
+    pub fn new_obj(&mut self) -> Vec<f64> {
+        id_to_js(self.inner.obj())
+    }
+
+    /// Create an empty `str` node.
+    #[wasm_bindgen(js_name = "newStr")]
+    pub fn new_str(&mut self) -> Vec<f64> {
+        id_to_js(self.inner.str_node())
+    }
+
+    /// Create nodes for a JSON value as `apiSet` does: strings become `str`
+    /// nodes, arrays `arr` and objects `obj`.  Returns the top node's ID.
+    pub fn json(&mut self, value_json: &str) -> Result<Vec<f64>, JsValue> {
+        let v = parse_json(value_json)?;
+        Ok(id_to_js(build_json(&mut self.inner, &v)))
+    }
+
+    /// Set `key` of the `obj` node `obj` to the node `value`.
+    #[wasm_bindgen(js_name = "setKey")]
+    pub fn set_key(&mut self, obj: &[f64], key: &str, value: &[f64]) -> Result<(), JsValue> {
+        self.set_key_inner(obj, key, value)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Insert `text` into the `str` node `obj` after the character `after`,
+    /// or at the start when `after` is `obj` itself.
+    #[wasm_bindgen(js_name = "insStr")]
+    pub fn ins_str(&mut self, obj: &[f64], after: &[f64], text: &str) -> Result<Vec<f64>, JsValue> {
+        self.ins_str_inner(obj, after, text)
+            .map(id_to_js)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Delete items of the `str`, `bin` or `arr` node `obj`.  `spans` is
+    /// flat `[sid, time, span, …]` triples, each naming `span` items from
+    /// `[sid, time]` on.
+    pub fn del(&mut self, obj: &[f64], spans: &[f64]) -> Result<(), JsValue> {
+        self.del_inner(obj, spans)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Make the node `value` the document root.
+    pub fn root(&mut self, value: &[f64]) -> Result<(), JsValue> {
+        let value = id_from_js(value).map_err(|e| JsValue::from_str(&e))?;
+        self.inner.root(value);
+        Ok(())
+    }
+
+    /// The ID the next operation will get.
+    #[wasm_bindgen(js_name = "nextId")]
+    pub fn next_id(&self) -> Vec<f64> {
+        id_to_js(Ts::new(self.inner.clock.sid(), self.inner.next_time()))
+    }
+
+    /// Return the operations built so far as a binary patch and start a new
+    /// one.  Returns an empty `Uint8Array` when there are none.
+    pub fn flush(&mut self) -> Vec<u8> {
+        let patch = self.inner.flush();
+        if patch.ops.is_empty() {
+            return Vec::new();
+        }
+        patch.to_binary()
+    }
+}
+
+// ── Stateless JSON helpers ───────────────────────────────────────────────────
+//
+// Plain JSON Patch / JSON Pointer operations for consumers that do not need a
+// CRDT document. Documents and patches cross the boundary as JSON text.
+
+fn parse_json(json_str: &str) -> Result<Value, JsValue> {
+    serde_json::from_str(json_str).map_err(|e| JsValue::from_str(&format!("invalid JSON: {e}")))
+}
+
+/// Apply an RFC 6902 JSON Patch (a JSON array of operations, extended
+/// operations such as `str_ins` included) to `doc_json` and return the
+/// resulting document as JSON text.
+///
+/// Mirrors `applyPatch` from upstream `json-patch`.
+#[wasm_bindgen(js_name = "jsonPatchApply")]
+pub fn json_patch_apply(doc_json: &str, patch_json: &str) -> Result<String, JsValue> {
+    let doc = parse_json(doc_json)?;
+    let ops = json_patch::from_json_patch(&parse_json(patch_json)?)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options = json_patch::ApplyPatchOptions { mutate: true };
+    let result = json_patch::apply_patch(doc, &ops, &options)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(result.doc.to_string())
+}
+
+/// Return the value at the JSON Pointer `pointer` in `doc_json` as JSON
+/// text, or `undefined` if there is none.
+#[wasm_bindgen(js_name = "jsonPointerGet")]
+pub fn json_pointer_get(doc_json: &str, pointer: &str) -> Result<Option<String>, JsValue> {
+    let doc = parse_json(doc_json)?;
+    let pointer = CompiledPointer::new(pointer).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(pointer.resolve(&doc).map(Value::to_string))
+}
+
+/// Return a JSON Patch, as JSON text, that turns `src_json` into
+/// `dst_json`.  String changes are expressed as `str_ins` / `str_del`.
+///
+/// Mirrors `JsonPatchDiff` from upstream `json-patch-diff`.
+#[wasm_bindgen(js_name = "jsonDiff")]
+pub fn json_diff(src_json: &str, dst_json: &str) -> Result<String, JsValue> {
+    let ops = json_patch_diff::diff(&parse_json(src_json)?, &parse_json(dst_json)?);
+    Ok(json_patch::to_json_patch(&ops).to_string())
+}
+
+/// Return a JSON summary of a binary-encoded CRDT patch: its id and span,
+/// op counts by kind, the nodes it writes to and how much it inserts and
+/// deletes.  Timestamps are `[sid, time]` pairs.
+#[wasm_bindgen(js_name = "patchInspect")]
+pub fn patch_inspect(patch_bytes: &[u8]) -> Result<String, JsValue> {
+    Ok(decode_patch(patch_bytes)?.describe().to_json().to_string())
+}
+
+fn decode_patch(patch_bytes: &[u8]) -> Result<Patch, JsValue> {
+    Patch::from_binary(patch_bytes)
+        .map_err(|e| JsValue::from_str(&format!("patch decode error: {e:?}")))
+}
+
+/// Convert a binary CRDT patch to the compact JSON format, as JSON text.
+#[wasm_bindgen(js_name = "patchToCompact")]
+pub fn patch_to_compact(patch_bytes: &[u8]) -> Result<String, JsValue> {
+    let json = decode_patch(patch_bytes)?
+        .to_compact_json()
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(json.to_string())
+}
+
+/// Convert a compact JSON patch, as JSON text, to binary.
+#[wasm_bindgen(js_name = "patchFromCompact")]
+pub fn patch_from_compact(json: &str) -> Result<Vec<u8>, JsValue> {
+    Patch::from_compact_json(&parse_json(json)?)
+        .map(|patch| patch.to_binary())
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Convert a binary CRDT patch to the verbose JSON format, as JSON text.
+#[wasm_bindgen(js_name = "patchToVerbose")]
+pub fn patch_to_verbose(patch_bytes: &[u8]) -> Result<String, JsValue> {
+    let json = decode_patch(patch_bytes)?
+        .to_verbose_json()
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(json.to_string())
+}
+
+/// Convert a verbose JSON patch, as JSON text, to binary.
+#[wasm_bindgen(js_name = "patchFromVerbose")]
+pub fn patch_from_verbose(json: &str) -> Result<Vec<u8>, JsValue> {
+    Patch::from_verbose_json(&parse_json(json)?)
+        .map(|patch| patch.to_binary())
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Re-encode a v1 or v2 patch batch as v2, with the body compressed by
+/// `compression`: `"none"`, `"deflate"` (raw deflate) or `"zstd"`.
+#[wasm_bindgen(js_name = "patchBatchEncode")]
+pub fn patch_batch_encode(batch: &[u8], compression: &str) -> Result<Vec<u8>, JsValue> {
+    let compression = BatchCompression::from_name(compression)
+        .ok_or_else(|| JsValue::from_str(&format!("unknown compression {compression:?}")))?;
+    let patches = decode_batch(batch).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+    encode_batch_v2(&refs, compression).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Re-encode a v1 or v2 patch batch as v1, for splitting in JS.
+#[wasm_bindgen(js_name = "patchBatchDecode")]
+pub fn patch_batch_decode(batch: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let patches = decode_batch(batch).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+    Ok(encode_batch_v1(&refs))
+}
+
 // ── BinNode navigation helpers ────────────────────────────────────────────────
 //
 // Mirrors the private helpers in json_crdt/model/api.rs.
@@ -996,8 +1687,7 @@ mod tests {
     fn diff_apply_noop_when_equal() {
         let mut m = model();
         m.api_set(r#"{"x":1}"#).unwrap();
-        m.diff_apply(r#"{"x":1}"#).unwrap(); // same state — may or may not produce patch
-                                             // The important invariant: view is still correct
+        assert!(m.diff_apply(r#"{"x":1}"#).unwrap().is_empty());
         let v = m.inner.view();
         assert_eq!(v["x"], json!(1));
     }
@@ -1017,4 +1707,431 @@ mod tests {
         assert!(!bytes.is_empty());
         assert_eq!(m.inner.view(), json!({"a": "hello", "b": [1, 2]}));
     }
+
+    #[test]
+    fn json_patch_apply_returns_document() {
+        let doc = json_patch_apply(
+            r#"{"a":[1,2],"s":"hi"}"#,
+            r#"[{"op":"add","path":"/a/-","value":3},{"op":"str_ins","path":"/s","pos":2,"str":"!"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&doc).unwrap(),
+            json!({"a": [1, 2, 3], "s": "hi!"})
+        );
+    }
+
+    #[test]
+    fn json_pointer_get_resolves_or_misses() {
+        let doc = r#"{"a":{"b/c":[10,20]}}"#;
+        assert_eq!(
+            json_pointer_get(doc, "/a/b~1c/1").unwrap().as_deref(),
+            Some("20")
+        );
+        assert_eq!(
+            json_pointer_get(doc, "").unwrap().as_deref(),
+            Some(r#"{"a":{"b/c":[10,20]}}"#)
+        );
+        assert_eq!(json_pointer_get(doc, "/a/x").unwrap(), None);
+        assert_eq!(json_pointer_get(doc, "/a/b~1c/2").unwrap(), None);
+    }
+
+    #[test]
+    fn json_diff_round_trips_through_json_patch_apply() {
+        let src = r#"{"a":1,"b":[1,2,3],"s":"hello"}"#;
+        let dst = r#"{"b":[1,3,4],"s":"help","c":{"d":null}}"#;
+        let patch = json_diff(src, dst).unwrap();
+        let out = json_patch_apply(src, &patch).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&out).unwrap(),
+            serde_json::from_str::<Value>(dst).unwrap()
+        );
+        assert_eq!(json_diff(src, src).unwrap(), "[]");
+    }
+
+    #[test]
+    fn diff_batch_replays_on_a_replica() {
+        let mut m = Model::create(Some(0x10000));
+        let mut replica = Model::create(Some(0x20000));
+        let patches = m
+            .diff_batch(r#"[{"a":1},{"a":1},{"a":2,"s":"x"},[true]]"#)
+            .unwrap();
+        assert_eq!(patches.len(), 4);
+        assert!(patches[1].is_none());
+        for patch in patches.into_iter().flatten() {
+            replica.apply_patch(&patch).unwrap();
+        }
+        assert_eq!(m.inner.view(), json!([true]));
+        assert_eq!(replica.inner.view(), json!([true]));
+        assert!(m.diff_batch(r#"{"a":1}"#).is_err());
+    }
+
+    #[test]
+    fn patch_inspect_summarises_local_changes() {
+        let mut m = Model::create(Some(0x10000));
+        m.api_set(r#"{"s":"abc"}"#).unwrap();
+        let summary: Value = serde_json::from_str(&patch_inspect(&m.api_flush()).unwrap()).unwrap();
+        assert_eq!(summary["id"][0], 0x10000);
+        assert_eq!(summary["op_kinds"]["ins_str"], 1);
+        assert_eq!(summary["inserted_text"], 3);
+        assert_eq!(summary["targets"][0][0], 0x10000);
+    }
+
+    #[test]
+    fn session_ids_seeded_models_are_reproducible() {
+        let mut a = SessionIds::seeded(7);
+        let mut b = SessionIds::seeded(7);
+        let m = Model::create_with(&mut a);
+        assert_eq!(m.sid(), Model::create_with(&mut b).sid());
+        assert_eq!(m.fork_with(&mut a).sid(), m.fork_with(&mut b).sid());
+        assert_ne!(m.sid(), m.fork_with(&mut SessionIds::seeded(8)).sid());
+    }
+
+    #[test]
+    fn session_ids_range_stays_in_range() {
+        let mut ids = SessionIds::range(100_000, 100_009, None).unwrap();
+        for _ in 0..50 {
+            assert!((100_000..=100_009).contains(&Model::create_with(&mut ids).sid()));
+        }
+        let mut ids = SessionIds::range(200_000, 200_000, Some(1)).unwrap();
+        assert_eq!(ids.next_sid(), 200_000);
+    }
+
+    /// Three patches from one peer: set an object, then two key writes.
+    fn peer_patches() -> Vec<Vec<u8>> {
+        let mut peer = model();
+        peer.api_set(r#"{"a":1}"#).unwrap();
+        let first = peer.api_flush();
+        peer.api_obj_set("null", r#"{"b":"x"}"#).unwrap();
+        let second = peer.api_flush();
+        peer.api_obj_set("null", r#"{"c":[1,2,3]}"#).unwrap();
+        vec![first, second, peer.api_flush()]
+    }
+
+    #[test]
+    fn apply_batch_without_limits() {
+        let patches = peer_patches();
+        let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+        let mut m = Model::create(Some(99_999));
+        m.apply_batch(&encode_batch_v1(&refs)).unwrap();
+        assert_eq!(m.inner.view(), json!({"a": 1, "b": "x", "c": [1, 2, 3]}));
+        assert!(matches!(
+            m.apply_batch(&[9, 0, 0, 0]),
+            Err(ApplyError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "deflate", feature = "zstd"))]
+    fn apply_batch_v2_compressed() {
+        let patches = peer_patches();
+        let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+        for compression in ["none", "deflate", "zstd"] {
+            let batch = patch_batch_encode(&encode_batch_v1(&refs), compression).unwrap();
+            assert_eq!(patch_batch_decode(&batch).unwrap(), encode_batch_v1(&refs));
+            let mut m = Model::create(Some(99_999));
+            m.apply_batch(&batch).unwrap();
+            assert_eq!(m.inner.view(), json!({"a": 1, "b": "x", "c": [1, 2, 3]}));
+        }
+
+        // A body that inflates past the model limit is not inflated.
+        let zeros = vec![0u8; 100_000];
+        let bomb = encode_batch_v2(&[&zeros], BatchCompression::Zstd).unwrap();
+        assert!(bomb.len() < 1_000);
+        let mut m = Model::create(Some(99_999));
+        m.set_limits(None, Some(50_000), None);
+        assert!(matches!(
+            m.apply_batch(&bomb),
+            Err(ApplyError::Invalid(msg)) if msg == "patch batch body is over 50000 bytes"
+        ));
+    }
+
+    #[test]
+    fn limits_reject_patch_batch_and_model() {
+        let patches = peer_patches();
+        let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+        let largest = refs.iter().map(|p| p.len()).max().unwrap();
+
+        let mut m = Model::create(Some(99_999));
+        m.set_limits(Some(largest as u32 - 1), None, None);
+        let err = m.apply_binary(&refs).unwrap_err();
+        assert!(matches!(
+            err,
+            ApplyError::Limit(LimitError::Patch { actual, .. }) if actual == largest
+        ));
+
+        m.set_limits(None, None, Some(2));
+        let err = m.apply_batch(&encode_batch_v1(&refs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ApplyError::Limit(LimitError::Batch {
+                limit: 2,
+                actual: 3
+            })
+        ));
+        assert_eq!(m.inner.view(), json!(null), "nothing applied");
+
+        // The model limit counts the document plus the incoming patches.
+        let empty = structural_binary::encode(&m.inner).len();
+        let total: usize = refs.iter().map(|p| p.len()).sum();
+        m.set_limits(None, Some((empty + total - 1) as u32), None);
+        let err = m.apply_batch(&encode_batch_v1(&refs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ApplyError::Limit(LimitError::Model { actual, .. }) if actual == empty + total
+        ));
+        m.set_limits(Some(largest as u32), Some((empty + total) as u32), Some(3));
+        m.apply_batch(&encode_batch_v1(&refs)).unwrap();
+        assert_eq!(m.inner.view(), json!({"a": 1, "b": "x", "c": [1, 2, 3]}));
+    }
+
+    #[test]
+    fn stats_count_applied_and_rejected_patches() {
+        let patches = peer_patches();
+        let refs: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
+        let mut m = Model::create(Some(99_999));
+        m.apply_binary(&refs[..1]).unwrap();
+        m.apply_batch(&encode_batch_v1(&refs[1..])).unwrap();
+        assert!(m.apply_batch(&[1]).is_err());
+        m.set_limits(Some(1), None, None);
+        assert!(m.apply_binary(&refs).is_err());
+
+        let stats = m.stats_json();
+        let bytes: usize = refs.iter().map(|p| p.len()).sum();
+        assert_eq!(
+            stats["patches"],
+            json!({"applied": 3, "applied_bytes": bytes, "rejected": 2})
+        );
+        assert_eq!(stats["sid"], json!(99_999));
+        assert_eq!(stats["tick"], json!(3));
+        assert_eq!(stats["peers"].as_array().unwrap().len(), 1);
+        assert_eq!(stats["node_kinds"]["obj"], json!(1));
+    }
+
+    #[test]
+    fn model_limit_remeasures_before_rejecting() {
+        let mut m = model();
+        m.api_set(r#"{"s":"0123456789012345678901234567890123456789"}"#)
+            .unwrap();
+        let size = m.to_binary().len();
+        m.set_limits(None, Some(size as u32 + 4), None);
+        // Deleting text shrinks the document; the stale estimate would
+        // reject the next patch.
+        m.api_str_del(r#"["s"]"#, 0, 40).unwrap();
+        m.model_bytes = size;
+        let mut peer = Model::create(Some(99_999));
+        peer.api_set("1").unwrap();
+        let patch = peer.api_flush();
+        assert!(size + patch.len() > size + 4);
+        m.apply_binary(&[&patch]).unwrap();
+        assert!(m.model_bytes < size + patch.len());
+
+        let err = LimitError::Model {
+            limit: 10,
+            actual: 12,
+        };
+        assert_eq!(err.code(), "MODEL_TOO_LARGE");
+        assert_eq!(
+            err.to_string(),
+            "document would reach 12 bytes, over the 10-byte limit"
+        );
+    }
+
+    /// Snapshots of server-clock documents from the upstream fixtures.
+    const SERVER_SNAPSHOTS: [&str; 3] = [
+        "model_roundtrip_server_array_v1",
+        "model_roundtrip_server_nested_v1",
+        "model_roundtrip_server_object_v1",
+    ];
+
+    /// Hand-built server-clock trees from the upstream fixtures.  Their node
+    /// IDs may run past the clock, so they are only decoded and re-encoded.
+    const SERVER_TREES: [&str; 4] = [
+        "model_canonical_encode_server_arr_v1",
+        "model_canonical_encode_server_obj_nested_v1",
+        "model_canonical_encode_server_scalar_v1",
+        "model_canonical_encode_server_vec_sparse_v1",
+    ];
+
+    /// The binary and view of each named fixture.
+    fn server_fixtures(names: &[&str]) -> Vec<(Vec<u8>, Value)> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/compat/fixtures");
+        names
+            .iter()
+            .map(|name| {
+                let text = std::fs::read_to_string(format!("{dir}/{name}.json")).unwrap();
+                let fixture: Value = serde_json::from_str(&text).unwrap();
+                let hex = fixture["expected"]["model_binary_hex"].as_str().unwrap();
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                    .collect();
+                (bytes, fixture["expected"]["view_json"].clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn server_clock_fixtures_round_trip() {
+        for (bytes, view) in server_fixtures(&SERVER_SNAPSHOTS)
+            .into_iter()
+            .chain(server_fixtures(&SERVER_TREES))
+        {
+            let m = Model::from_binary(&bytes).unwrap();
+            assert_eq!(m.inner.view(), view);
+            assert_eq!(m.sid(), SESSION::SERVER);
+            assert_eq!(m.to_binary(), bytes);
+        }
+        let mut m = Model::with_server_clock(None);
+        assert_eq!((m.sid(), m.inner.clock.time), (SESSION::SERVER, 1));
+        m.api_set(r#"{"a":[1]}"#).unwrap();
+        let bytes = m.to_binary();
+        assert_eq!(bytes[0], 0x80);
+        assert_eq!(
+            Model::from_binary(&bytes).unwrap().inner.view(),
+            json!({"a": [1]})
+        );
+    }
+
+    #[test]
+    fn server_clock_applies_server_patches_in_order() {
+        for (bytes, view) in server_fixtures(&SERVER_SNAPSHOTS) {
+            let mut server = Model::from_binary(&bytes).unwrap();
+            server.api_set(r#"{"k":"v"}"#).unwrap();
+            let first = server.api_flush();
+            server.api_obj_set("null", r#"{"n":2}"#).unwrap();
+            let second = server.api_flush();
+
+            let mut replica = Model::from_binary(&bytes).unwrap();
+            let err = replica.apply_binary(&[&second]).unwrap_err();
+            assert!(matches!(
+                err,
+                ApplyError::Invalid(msg) if msg == "server clock: TIME_TRAVEL"
+            ));
+            let err = replica
+                .apply_batch(&encode_batch_v1(&[&second, &first]))
+                .unwrap_err();
+            assert!(matches!(err, ApplyError::Invalid(_)));
+            assert_eq!(replica.inner.view(), view, "nothing applied");
+
+            replica
+                .apply_batch(&encode_batch_v1(&[&first, &second]))
+                .unwrap();
+            // Re-applying a seen patch is not time travel.
+            replica.apply_patch(&first).unwrap();
+            assert_eq!(replica.inner.view(), json!({"k": "v", "n": 2}));
+            assert_eq!(replica.to_binary(), server.to_binary());
+            assert_eq!(replica.counters.rejected, 2);
+        }
+    }
+
+    #[test]
+    fn server_clock_rejects_other_sessions() {
+        let (bytes, view) = server_fixtures(&SERVER_SNAPSHOTS).remove(2);
+        let mut m = Model::from_binary(&bytes).unwrap();
+        let patches = peer_patches();
+        let err = m.apply_binary(&[&patches[0]]).unwrap_err();
+        assert!(matches!(
+            err,
+            ApplyError::Invalid(msg) if msg == "server clock: patch from session 65536"
+        ));
+        assert_eq!(m.inner.view(), view);
+        assert_eq!(m.to_binary(), bytes);
+    }
+
+    #[test]
+    fn server_clock_fork_is_logical() {
+        for (bytes, view) in server_fixtures(&SERVER_SNAPSHOTS) {
+            let server = Model::from_binary(&bytes).unwrap();
+            let mut forked = server.fork(Some(77_777));
+            assert_eq!(forked.sid(), 77_777);
+            assert!(forked.inner.clock.peers.contains_key(&SESSION::SERVER));
+            let binary = forked.to_binary();
+            assert_ne!(binary[0], 0x80);
+            assert_eq!(Model::from_binary(&binary).unwrap().inner.view(), view);
+
+            forked.api_set(r#"{"x":1}"#).unwrap();
+            let patch = forked.api_flush();
+            let decoded = Model::from_binary(&forked.to_binary()).unwrap();
+            assert_eq!(decoded.inner.view(), json!({"x": 1}));
+            let mut peer = Model::from_binary(&binary).unwrap();
+            peer.apply_patch(&patch).unwrap();
+            assert_eq!(peer.inner.view(), json!({"x": 1}));
+        }
+    }
+
+    #[test]
+    fn view_cbor_and_msgpack_match_view() {
+        let mut m = model();
+        m.api_set(r#"{"s":"text","n":[1,2.5,null],"o":{"t":true}}"#)
+            .unwrap();
+        m.api_obj_set("null", r#"{"b":null}"#).unwrap();
+        let cbor = json_joy_json_pack::decode_cbor_value(&m.view_cbor()).unwrap();
+        let msgpack = json_joy_json_pack::msgpack::MsgPackDecoder::new()
+            .decode(&m.view_msgpack())
+            .unwrap();
+        for pack in [cbor, msgpack] {
+            assert_eq!(Value::from(pack), m.inner.view());
+        }
+    }
+
+    #[test]
+    fn patch_builder_edits_apply_to_model_and_peer() {
+        let mut m = model();
+        m.api_set("{}").unwrap();
+        let mut peer = Model::create(Some(999_999));
+        peer.apply_patch(&m.api_flush()).unwrap();
+
+        let obj = id_to_js(m.inner.root.val);
+        let mut b = m.patch_builder();
+        let s = b.new_str();
+        let first = b.ins_str(&s, &s, "hello").unwrap();
+        b.set_key(&obj, "t", &s).unwrap();
+        assert_eq!(b.next_id(), [first[0], first[1] + 6.0]);
+        let patch = b.flush();
+        assert!(b.flush().is_empty());
+        m.apply_patch(&patch).unwrap();
+        assert_eq!(m.inner.view(), json!({"t": "hello"}));
+
+        // The next builder continues the clock; delete "ell".
+        let mut b = m.patch_builder();
+        b.del(&s, &[first[0], first[1] + 1.0, 3.0]).unwrap();
+        let del = b.flush();
+        m.apply_patch(&del).unwrap();
+        assert_eq!(m.inner.view(), json!({"t": "ho"}));
+        for patch in [patch, del] {
+            peer.apply_patch(&patch).unwrap();
+        }
+        assert_eq!(peer.inner.view(), m.inner.view());
+    }
+
+    #[test]
+    fn patch_builder_rejects_malformed_ids() {
+        assert!(id_from_js(&[1.0]).is_err());
+        assert!(id_from_js(&[1.5, 2.0]).is_err());
+        assert!(id_from_js(&[-1.0, 2.0]).is_err());
+        assert_eq!(id_from_js(&[7.0, 3.0]), Ok(Ts::new(7, 3)));
+        assert!(spans_from_js(&[]).is_err());
+        assert!(spans_from_js(&[1.0, 2.0]).is_err());
+        let mut b = PatchBuilder::new(100_000.0, 1.0).unwrap();
+        assert_eq!(b.next_id(), [100_000.0, 1.0]);
+        let s = b.new_str();
+        assert!(b.ins_str_inner(&s, &s, "").is_err());
+        assert!(b.set_key_inner(&s, "k", &[1.0]).is_err());
+        assert!(b.del_inner(&s, &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn patch_json_conversions_round_trip() {
+        let mut m = Model::create(Some(0x10000));
+        m.api_set(r#"{"s":"abc","n":[1,null]}"#).unwrap();
+        let bytes = m.api_flush();
+        let compact = patch_to_compact(&bytes).unwrap();
+        assert!(compact.starts_with("[[[65536,"));
+        assert_eq!(patch_from_compact(&compact).unwrap(), bytes);
+        let verbose = patch_to_verbose(&bytes).unwrap();
+        let value: Value = serde_json::from_str(&verbose).unwrap();
+        assert_eq!(value["ops"][0]["op"], "new_obj");
+        assert_eq!(patch_from_verbose(&verbose).unwrap(), bytes);
+    }
 }
@@ -313,6 +313,7 @@ pub fn decode(fields: &IndexedFields) -> Result<Model, DecodeError> {
         index: NodeIndex::default(),
         clock,
         tick: 0,
+        deletes: Default::default(),
     };
 
     // Decode root reference
@@ -597,6 +598,12 @@ fn read_cbor_value(r: &mut CrdtReader) -> Result<PackValue, CborError> {
             21 => Ok(PackValue::Bool(true)),
             22 => Ok(PackValue::Null),
             23 => Ok(PackValue::Undefined),
+            25 => {
+                let b = r.buf(2);
+                Ok(PackValue::Float(json_joy_buffers::decode_f16(
+                    u16::from_be_bytes([b[0], b[1]]),
+                )))
+            }
             26 => {
                 let b = r.buf(4);
                 Ok(PackValue::Float(
@@ -756,6 +756,12 @@ fn read_cbor_value_sidecar(r: &mut CrdtReader) -> Result<PackValue, DecodeError>
             21 => Ok(PackValue::Bool(true)),
             22 => Ok(PackValue::Null),
             23 => Ok(PackValue::Undefined),
+            25 => {
+                let b = r.buf(2);
+                Ok(PackValue::Float(json_joy_buffers::decode_f16(
+                    u16::from_be_bytes([b[0], b[1]]),
+                )))
+            }
             26 => {
                 let b = r.buf(4);
                 Ok(PackValue::Float(
@@ -453,19 +453,112 @@ pub enum DecodeError {
     InvalidClockTable,
     #[error("format error: {0}")]
     Format(String),
+    #[error("session {0} cannot be written with the server clock encoding")]
+    NotServerClock(u64),
 }
 
 /// Decode a structural binary document back into a [`Model`].
 pub fn decode(data: &[u8]) -> Result<Model, DecodeError> {
-    if data.is_empty() {
+    match probe(data)? {
+        ModelFormat::Server => decode_server(data),
+        ModelFormat::Logical => decode_logical(data),
+    }
+}
+
+// ── Format detection and conversion ─────────────────────────────────────────
+
+/// Clock encoding of a structural binary snapshot.
+///
+/// Not an upstream port: upstream branches on the first byte inline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// `[4-byte clock-table offset] [tree] [clock table]`, for documents
+    /// edited by any number of sessions.
+    Logical,
+    /// `[0x80] [vu57 server_time] [tree]`, for documents whose timestamps
+    /// all belong to [`SESSION::SERVER`].
+    Server,
+}
+
+impl ModelFormat {
+    /// The format [`encode`] picks for `model`.
+    pub fn of(model: &Model) -> Self {
+        if model.clock.sid == SESSION::SERVER {
+            ModelFormat::Server
+        } else {
+            ModelFormat::Logical
+        }
+    }
+}
+
+/// Detect the format of a structural binary snapshot and check that its
+/// header (and, for [`ModelFormat::Logical`], its clock table) is intact,
+/// without decoding the tree.
+pub fn probe(data: &[u8]) -> Result<ModelFormat, DecodeError> {
+    let Some(&first) = data.first() else {
         return Err(DecodeError::EndOfInput);
+    };
+    let mut r = CrdtReader::new(data);
+    // Like upstream, any first byte with the high bit set means server
+    // time; the low bits are ignored.
+    if first & 0x80 != 0 {
+        r.u8();
+        r.vu57();
+        if r.x > data.len() {
+            return Err(DecodeError::EndOfInput);
+        }
+        return Ok(ModelFormat::Server);
     }
-    let is_server = data[0] & 0x80 != 0;
-    if is_server {
-        decode_server(data)
-    } else {
-        decode_logical(data)
+    if data.len() < 4 {
+        return Err(DecodeError::EndOfInput);
+    }
+    let offset = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    r.x = 4 + offset;
+    if r.x >= data.len() {
+        return Err(DecodeError::InvalidClockTable);
+    }
+    let n = r.vu57();
+    if n == 0 {
+        return Err(DecodeError::InvalidClockTable);
+    }
+    for _ in 0..n.saturating_mul(2) {
+        r.vu57();
+        if r.x > data.len() {
+            return Err(DecodeError::InvalidClockTable);
+        }
+    }
+    Ok(ModelFormat::Logical)
+}
+
+/// Encode `model` in the given format, regardless of its session ID.
+///
+/// [`ModelFormat::Server`] drops session IDs, so it fails with
+/// [`DecodeError::NotServerClock`] unless every session the model has seen
+/// is [`SESSION::SERVER`].
+pub fn encode_as(model: &Model, format: ModelFormat) -> Result<Vec<u8>, DecodeError> {
+    let mut w = CrdtWriter::new();
+    match format {
+        ModelFormat::Logical => encode_logical(model, &mut w),
+        ModelFormat::Server => {
+            let foreign = std::iter::once(model.clock.sid)
+                .chain(model.clock.peers.keys().copied())
+                .find(|&sid| sid != SESSION::SERVER);
+            if let Some(sid) = foreign {
+                return Err(DecodeError::NotServerClock(sid));
+            }
+            encode_server(model, &mut w);
+        }
+    }
+    Ok(w.flush())
+}
+
+/// Re-encode a structural binary snapshot in `format`.  Returns the input
+/// unchanged if it is already in that format.
+pub fn convert(data: &[u8], format: ModelFormat) -> Result<Vec<u8>, DecodeError> {
+    if probe(data)? == format {
+        return Ok(data.to_vec());
     }
+    encode_as(&decode(data)?, format)
 }
 
 fn decode_server(data: &[u8]) -> Result<Model, DecodeError> {
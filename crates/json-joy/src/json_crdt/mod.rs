@@ -22,5 +22,5 @@ pub mod schema;
 pub use constants::{ORIGIN, UNDEFINED_TS};
 pub use extensions::{AnyExtension, ExtApi, ExtNode, Extensions};
 pub use model::Model;
-pub use model::ModelApi;
+pub use model::{ModelApi, ModelStats};
 pub use nodes::{CrdtNode, NodeIndex};
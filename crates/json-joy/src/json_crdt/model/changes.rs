@@ -0,0 +1,115 @@
+//! Paths changed since a clock.
+//!
+//! Not an upstream port.
+
+use json_joy_json_pointer::format_json_pointer;
+
+use super::Model;
+use crate::json_crdt::nodes::rga::Rga;
+use crate::json_crdt::nodes::{CrdtNode, TsKey};
+use crate::json_crdt_patch::clock::{ClockVector, Ts};
+
+impl Model {
+    /// Return JSON Pointers to the parts of the view changed by operations
+    /// `clock` has not seen, in document order.
+    ///
+    /// An object key or `vec` slot is reported when its value was written
+    /// (or the key deleted); a string, binary or array when anything was
+    /// inserted into or deleted from it.  Paths below a reported path are
+    /// not listed, and a rewritten root is `""`.  A view that only changed
+    /// by losing a write to a concurrent one is not reported.
+    ///
+    /// Deletes are only tracked for patches applied to this model, so pass a
+    /// clock taken from it after it was created or decoded.
+    ///
+    /// Not an upstream port.
+    pub fn changed_paths_since(&self, clock: &ClockVector) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut path = Vec::new();
+        self.visit(self.root.val, clock, &mut path, &mut out);
+        out
+    }
+
+    /// Report `path` if the value `id` is new to `clock`, else look inside it.
+    fn visit(&self, id: Ts, clock: &ClockVector, path: &mut Vec<String>, out: &mut Vec<String>) {
+        if clock.has_seen(id) {
+            self.collect_changes(id, clock, path, out);
+        } else {
+            out.push(format_json_pointer(path));
+        }
+    }
+
+    fn visit_child(
+        &self,
+        key: String,
+        id: Ts,
+        clock: &ClockVector,
+        path: &mut Vec<String>,
+        out: &mut Vec<String>,
+    ) {
+        path.push(key);
+        self.visit(id, clock, path, out);
+        path.pop();
+    }
+
+    /// Report changes inside the node `id`, which `clock` has seen.
+    fn collect_changes(
+        &self,
+        id: Ts,
+        clock: &ClockVector,
+        path: &mut Vec<String>,
+        out: &mut Vec<String>,
+    ) {
+        let Some(node) = self.index.get(&TsKey::from(id)) else {
+            return;
+        };
+        match node {
+            CrdtNode::Con(_) => {}
+            CrdtNode::Val(n) => self.visit(n.val, clock, path, out),
+            CrdtNode::Obj(n) => {
+                for (key, &id) in &n.keys {
+                    self.visit_child(key.clone(), id, clock, path, out);
+                }
+            }
+            CrdtNode::Vec(n) => {
+                for (i, id) in n.elements.iter().enumerate() {
+                    if let Some(id) = id {
+                        self.visit_child(i.to_string(), *id, clock, path, out);
+                    }
+                }
+            }
+            CrdtNode::Str(n) => {
+                if self.rga_edited(id, &n.rga, clock) {
+                    out.push(format_json_pointer(path));
+                }
+            }
+            CrdtNode::Bin(n) => {
+                if self.rga_edited(id, &n.rga, clock) {
+                    out.push(format_json_pointer(path));
+                }
+            }
+            CrdtNode::Arr(n) => {
+                // An insert or delete shifts every later index, so the
+                // array is reported as a whole.
+                if self.rga_edited(id, &n.rga, clock) {
+                    out.push(format_json_pointer(path));
+                    return;
+                }
+                let ids = n.rga.iter_live().filter_map(|c| c.data.as_ref()).flatten();
+                for (i, &id) in ids.enumerate() {
+                    self.visit_child(i.to_string(), id, clock, path, out);
+                }
+            }
+        }
+    }
+
+    /// Whether the RGA of node `id` has inserts or deletes new to `clock`.
+    fn rga_edited<T: Clone>(&self, id: Ts, rga: &Rga<T>, clock: &ClockVector) -> bool {
+        let unseen = |id: &Ts| !clock.has_seen(*id);
+        rga.chunks.iter().map(|c| &c.id).any(unseen)
+            || self
+                .deletes
+                .get(&TsKey::from(id))
+                .is_some_and(|ids| ids.iter().any(unseen))
+    }
+}
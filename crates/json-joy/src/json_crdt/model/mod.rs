@@ -13,16 +13,23 @@
 //! [`Model::view`].
 
 pub mod api;
+mod changes;
 pub mod util;
 
 pub use api::ModelApi;
 
-use serde_json::Value;
+use std::collections::BTreeMap;
+use std::mem::size_of;
+
+use json_joy_json_pack::msgpack::MsgPackEncoder;
+use json_joy_json_pack::{CborEncoder, PackValue};
+use serde_json::{json, Value};
 
 use super::constants::ORIGIN;
+use super::nodes::rga::{Chunk, Rga};
 use super::nodes::{
-    ArrNode, BinNode, ConNode, CrdtNode, IndexExt, NodeIndex, ObjNode, RootNode, StrNode, ValNode,
-    VecNode,
+    ArrNode, BinNode, ConNode, CrdtNode, IndexExt, NodeIndex, ObjNode, RootNode, StrNode, TsKey,
+    ValNode, VecNode,
 };
 use crate::json_crdt_patch::clock::{ClockVector, Ts};
 use crate::json_crdt_patch::enums::SESSION;
@@ -49,6 +56,13 @@ pub struct Model {
     ///
     /// Mirrors `Model.tick` in the upstream TypeScript.
     pub tick: u64,
+    /// The latest delete from each session per `str`, `bin` and `arr` node,
+    /// as tombstones do not record when they were made.  Only holds deletes
+    /// applied since the model was created or decoded; read by
+    /// [`Model::changed_paths_since`].
+    ///
+    /// Not an upstream port.
+    pub(crate) deletes: BTreeMap<TsKey, Vec<Ts>>,
 }
 
 impl Model {
@@ -62,9 +76,16 @@ impl Model {
             index: NodeIndex::default(),
             clock: ClockVector::new(sid, 1),
             tick: 0,
+            deletes: BTreeMap::new(),
         }
     }
 
+    /// The latest delete from each session per `str`, `bin` and `arr` node,
+    /// keyed by node ID. See [`Model::changed_paths_since`].
+    pub fn deletes(&self) -> &BTreeMap<TsKey, Vec<Ts>> {
+        &self.deletes
+    }
+
     /// Create a model with a randomly-generated session ID.
     pub fn create() -> Self {
         // Use a simple pseudo-random SID (same range as upstream: ≥ 65536).
@@ -72,11 +93,37 @@ impl Model {
         Self::new(sid)
     }
 
+    /// Create a model with a session ID from `generator`.
+    pub fn create_with<G: util::SessionIdGenerator + ?Sized>(generator: &mut G) -> Self {
+        Self::new(generator.next_sid())
+    }
+
     /// Return the JSON view of the current document state.
     pub fn view(&self) -> Value {
         self.root.view(&self.index)
     }
 
+    /// Return the view as a [`PackValue`], with `bin` nodes as bytes.
+    ///
+    /// Not an upstream port; see [`CrdtNode::view_pack`].
+    pub fn view_pack(&self) -> PackValue {
+        self.root.view_pack(&self.index)
+    }
+
+    /// Return the view encoded as CBOR.
+    ///
+    /// Not an upstream port. Written straight from the document tree, so a
+    /// large view crosses a process or WASM boundary without a JSON round
+    /// trip, and `bin` nodes stay byte strings.
+    pub fn view_cbor(&self) -> Vec<u8> {
+        CborEncoder::new().encode(&self.view_pack())
+    }
+
+    /// Return the view encoded as MessagePack; see [`Model::view_cbor`].
+    pub fn view_msgpack(&self) -> Vec<u8> {
+        MsgPackEncoder::new().encode(&self.view_pack())
+    }
+
     /// Serialize this model using structural binary encoding.
     ///
     /// Mirrors upstream `Model.toBinary()`.
@@ -103,6 +150,99 @@ impl Model {
         self.tick += 1;
     }
 
+    /// Apply RFC 6902 JSON Patch `ops` to the document and return the CRDT
+    /// patch that made the change, or `None` if the document is unchanged
+    /// (e.g. only `test` operations).
+    ///
+    /// Not an upstream port. The operations run against the current view,
+    /// and the result is diffed into the model, so the returned patch is the
+    /// minimal edit rather than one operation per JSON Patch operation. The
+    /// patch is all-or-nothing: if any operation fails, the model is left
+    /// untouched.
+    pub fn apply_json_patch(
+        &mut self,
+        ops: &[crate::json_patch::Op],
+    ) -> Result<Option<Patch>, crate::json_patch::PatchError> {
+        let options = crate::json_patch::ApplyPatchOptions { mutate: true };
+        let next = crate::json_patch::apply_patch(self.view(), ops, &options)?.doc;
+        Ok(self.diff_apply(&next))
+    }
+
+    /// Diff the document against `next`, apply the resulting patch and
+    /// return it, or `None` if the document already equals `next`.
+    pub fn diff_apply(&mut self, next: &Value) -> Option<Patch> {
+        // Diff from the root register so a change of the root's type is a
+        // register write.
+        let root = CrdtNode::Val(ValNode {
+            id: ORIGIN,
+            val: self.root.val,
+        });
+        let patch = crate::json_crdt_diff::diff_node(
+            &root,
+            &self.index,
+            self.clock.sid,
+            self.clock.time,
+            next,
+        );
+        if let Some(patch) = &patch {
+            self.apply_patch(patch);
+        }
+        patch
+    }
+
+    /// Move the document through each of `targets` in turn, returning the
+    /// binary patch for every step (`None` where the step changes nothing).
+    ///
+    /// Not an upstream port. Equivalent to calling [`Model::diff_apply`] and
+    /// [`Patch::to_binary`] per target, but one patch encoder is shared by
+    /// the whole batch.
+    pub fn diff_many(&mut self, targets: &[Value]) -> Vec<Option<Vec<u8>>> {
+        let mut encoder = crate::json_crdt_patch::codec::binary::Encoder::new();
+        targets
+            .iter()
+            .map(|next| self.diff_apply(next).map(|patch| encoder.encode(&patch)))
+            .collect()
+    }
+
+    /// Returns counters describing the size of the document, for
+    /// monitoring how it grows.
+    ///
+    /// Not an upstream port. Walks every node, so it costs about as much as
+    /// [`Model::view`].
+    pub fn stats(&self) -> ModelStats {
+        let mut peers: Vec<Ts> = self.clock.peers.values().copied().collect();
+        peers.sort_by_key(|ts| ts.sid);
+        let mut stats = ModelStats {
+            sid: self.clock.sid,
+            time: self.clock.time,
+            peers,
+            tick: self.tick,
+            node_count: self.index.len(),
+            memory_estimate: size_of::<Self>(),
+            ..Default::default()
+        };
+        for node in self.index.values() {
+            *stats.node_kinds.entry(node.name()).or_insert(0) += 1;
+            let (tombstones, heap) = match node {
+                CrdtNode::Con(_) | CrdtNode::Val(_) => (0, 0),
+                CrdtNode::Obj(n) => (
+                    0,
+                    n.keys
+                        .keys()
+                        .map(|k| k.len() + size_of::<(String, Ts)>())
+                        .sum(),
+                ),
+                CrdtNode::Vec(n) => (0, n.elements.capacity() * size_of::<Option<Ts>>()),
+                CrdtNode::Str(n) => rga_stats(&n.rga, String::len),
+                CrdtNode::Bin(n) => rga_stats(&n.rga, Vec::len),
+                CrdtNode::Arr(n) => rga_stats(&n.rga, |ids| ids.len() * size_of::<Ts>()),
+            };
+            stats.tombstones += tombstones;
+            stats.memory_estimate += size_of::<(TsKey, CrdtNode)>() + heap;
+        }
+        stats
+    }
+
     /// Recursively remove a node and its entire subtree from the index.
     ///
     /// Mirrors `Model._gcTree(value)` in the upstream TypeScript.
@@ -118,11 +258,21 @@ impl Model {
         let Some(node) = self.index.remove_node(&ts) else {
             return;
         };
+        self.deletes.remove(&TsKey::from(ts));
         for child_id in node.child_ids() {
             self.gc_tree(child_id);
         }
     }
 
+    /// Keep `id` as the latest delete in `obj` from its session.
+    fn record_delete(&mut self, obj: Ts, id: Ts) {
+        let seen = self.deletes.entry(TsKey::from(obj)).or_default();
+        match seen.iter_mut().find(|ts| ts.sid == id.sid) {
+            Some(ts) => ts.time = ts.time.max(id.time),
+            None => seen.push(id),
+        }
+    }
+
     /// Apply a single operation.
     ///
     /// Mirrors `Model.applyOperation` in the upstream TypeScript.
@@ -286,9 +436,15 @@ impl Model {
             }
 
             // Delete ranges in a `str`, `bin`, or `arr`.
-            Op::Del { obj, what, .. } => match self.index.get_mut_ts(obj) {
-                Some(CrdtNode::Str(node)) => node.delete(what),
-                Some(CrdtNode::Bin(node)) => node.delete(what),
+            Op::Del { id, obj, what } => match self.index.get_mut_ts(obj) {
+                Some(CrdtNode::Str(node)) => {
+                    node.delete(what);
+                    self.record_delete(*obj, *id);
+                }
+                Some(CrdtNode::Bin(node)) => {
+                    node.delete(what);
+                    self.record_delete(*obj, *id);
+                }
                 Some(CrdtNode::Arr(node)) => {
                     // GC the data-node IDs before tombstoning the slots.
                     // Mirrors upstream: for each span item, getById → _gcTree.
@@ -302,6 +458,7 @@ impl Model {
                         }
                     }
                     node.delete(what);
+                    self.record_delete(*obj, *id);
                     for old in to_gc {
                         self.gc_tree(old);
                     }
@@ -329,6 +486,7 @@ impl Model {
             index: super::nodes::NodeIndex::default(),
             clock: ClockVector::new(SESSION::SERVER, server_time),
             tick: 0,
+            deletes: BTreeMap::new(),
         }
     }
 
@@ -341,8 +499,60 @@ impl Model {
             index: super::nodes::NodeIndex::default(),
             clock,
             tick: 0,
+            deletes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Deleted items and heap bytes held by an RGA.
+fn rga_stats<T: Clone>(rga: &Rga<T>, data_bytes: impl Fn(&T) -> usize) -> (u64, usize) {
+    let mut tombstones = 0;
+    let mut heap = rga.chunks.capacity() * size_of::<Chunk<T>>();
+    for chunk in &rga.chunks {
+        match &chunk.data {
+            Some(data) => heap += data_bytes(data),
+            None => tombstones += chunk.span,
         }
     }
+    (tombstones, heap)
+}
+
+/// Size counters of a [`Model`], returned by [`Model::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelStats {
+    /// Local session ID.
+    pub sid: u64,
+    /// Local logical time.
+    pub time: u64,
+    /// Latest timestamp seen from each remote session, by session ID.
+    pub peers: Vec<Ts>,
+    /// Number of patches applied, see [`Model::tick`].
+    pub tick: u64,
+    /// Number of nodes in the index.
+    pub node_count: usize,
+    /// Number of nodes of each kind, keyed by [`CrdtNode::name`].
+    pub node_kinds: BTreeMap<&'static str, usize>,
+    /// Deleted items still held in `str`, `bin` and `arr` nodes.
+    pub tombstones: u64,
+    /// Rough heap footprint in bytes: node storage, RGA chunks, and the
+    /// text, bytes and keys they hold.
+    pub memory_estimate: usize,
+}
+
+impl ModelStats {
+    /// Renders the stats as JSON, with timestamps as `[sid, time]`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "sid": self.sid,
+            "time": self.time,
+            "peers": self.peers.iter().map(|ts| json!([ts.sid, ts.time])).collect::<Vec<_>>(),
+            "tick": self.tick,
+            "node_count": self.node_count,
+            "node_kinds": self.node_kinds,
+            "tombstones": self.tombstones,
+            "memory_estimate": self.memory_estimate,
+        })
+    }
 }
 
 /// Very simple pseudo-random session ID generator.
@@ -1,6 +1,16 @@
 //! Utility functions for the JSON CRDT model.
 //!
 //! Mirrors `json-crdt/model/util.ts`.
+//!
+//! [`SessionIdGenerator`] and its implementations are not an upstream port:
+//! upstream has the single global [`random_session_id`] scheme.
+
+use std::ops::RangeInclusive;
+
+use crate::json_crdt_patch::enums::SESSION;
+
+/// Lowest session ID handed out; the values below are reserved.
+const RESERVED: u64 = 0xFFFF;
 
 /// Generates a random session ID up to 53 bits in size.
 ///
@@ -15,9 +25,7 @@
 /// derives entropy from the system clock with additional mixing, matching
 /// the required range without pulling in an external `rand` crate.
 pub fn random_session_id() -> u64 {
-    const SESSION_MAX: u64 = 9007199254740991; // 2^53 - 1
-    const RESERVED: u64 = 0xFFFF; // 65535
-    const DIFF: u64 = SESSION_MAX - RESERVED;
+    const DIFF: u64 = SESSION::MAX - RESERVED;
 
     use std::time::{SystemTime, UNIX_EPOCH};
     let d = SystemTime::now()
@@ -32,18 +40,103 @@ pub fn random_session_id() -> u64 {
     (mixed % DIFF) + RESERVED
 }
 
+/// Source of session IDs for new and forked models.
+pub trait SessionIdGenerator {
+    /// Return the next session ID.
+    fn next_sid(&mut self) -> u64;
+}
+
+/// The default scheme: [`random_session_id`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomSessionIds;
+
+impl SessionIdGenerator for RandomSessionIds {
+    fn next_sid(&mut self) -> u64 {
+        random_session_id()
+    }
+}
+
+/// Reproducible session IDs in the [`random_session_id`] range: the same
+/// seed always yields the same sequence.
+///
+/// ```
+/// use json_joy::json_crdt::model::util::{SeededSessionIds, SessionIdGenerator};
+///
+/// let mut a = SeededSessionIds::new(42);
+/// let mut b = SeededSessionIds::new(42);
+/// assert_eq!(a.next_sid(), b.next_sid());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeededSessionIds {
+    state: u64,
+}
+
+impl SeededSessionIds {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl SessionIdGenerator for SeededSessionIds {
+    fn next_sid(&mut self) -> u64 {
+        // SplitMix64.
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        z % (SESSION::MAX - RESERVED + 1) + RESERVED
+    }
+}
+
+/// Session IDs restricted to a range, drawn from another generator; for
+/// servers that hand each node its own slice of the ID space.
+///
+/// ```
+/// use json_joy::json_crdt::model::util::{RangeSessionIds, RandomSessionIds, SessionIdGenerator};
+///
+/// let mut ids = RangeSessionIds::new(1_000_000..=1_999_999, RandomSessionIds).unwrap();
+/// assert!((1_000_000..=1_999_999).contains(&ids.next_sid()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RangeSessionIds<G> {
+    start: u64,
+    len: u64,
+    inner: G,
+}
+
+impl<G: SessionIdGenerator> RangeSessionIds<G> {
+    /// Returns `None` if `range` is empty or not within
+    /// `0xFFFF..=SESSION::MAX`.
+    pub fn new(range: RangeInclusive<u64>, inner: G) -> Option<Self> {
+        let (start, end) = range.into_inner();
+        if start < RESERVED || start > end || end > SESSION::MAX {
+            return None;
+        }
+        Some(Self {
+            start,
+            len: end - start + 1,
+            inner,
+        })
+    }
+}
+
+impl<G: SessionIdGenerator> SessionIdGenerator for RangeSessionIds<G> {
+    fn next_sid(&mut self) -> u64 {
+        self.start + self.inner.next_sid() % self.len
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn random_session_id_in_range() {
-        const SESSION_MAX: u64 = 9007199254740991;
-        const RESERVED: u64 = 0xFFFF;
         for _ in 0..10 {
             let id = random_session_id();
             assert!(id >= RESERVED, "id {id} should be >= {RESERVED}");
-            assert!(id <= SESSION_MAX, "id {id} should be <= {SESSION_MAX}");
+            assert!(id <= SESSION::MAX, "id {id} should be <= {}", SESSION::MAX);
         }
     }
 
@@ -53,4 +146,32 @@ mod tests {
             assert!(random_session_id() > 0);
         }
     }
+
+    #[test]
+    fn seeded_session_ids_are_reproducible_and_in_range() {
+        let mut a = SeededSessionIds::new(1);
+        let mut b = SeededSessionIds::new(1);
+        let ids: Vec<u64> = (0..100).map(|_| a.next_sid()).collect();
+        assert_eq!(ids, (0..100).map(|_| b.next_sid()).collect::<Vec<_>>());
+        assert!(ids.iter().all(|id| (RESERVED..=SESSION::MAX).contains(id)));
+        assert_ne!(ids[0], SeededSessionIds::new(2).next_sid());
+    }
+
+    #[test]
+    fn range_session_ids_stay_in_range() {
+        let mut ids = RangeSessionIds::new(70_000..=70_003, SeededSessionIds::new(9)).unwrap();
+        let mut seen = [false; 4];
+        for _ in 0..200 {
+            let id = ids.next_sid();
+            assert!((70_000..=70_003).contains(&id));
+            seen[(id - 70_000) as usize] = true;
+        }
+        assert_eq!(seen, [true; 4]);
+
+        assert!(RangeSessionIds::new(RESERVED..=SESSION::MAX, RandomSessionIds).is_some());
+        assert!(RangeSessionIds::new(3..=70_000, RandomSessionIds).is_none());
+        let (start, end) = (70_001, 70_000);
+        assert!(RangeSessionIds::new(start..=end, RandomSessionIds).is_none());
+        assert!(RangeSessionIds::new(70_000..=SESSION::MAX + 1, RandomSessionIds).is_none());
+    }
 }
@@ -521,6 +521,15 @@ impl RootNode {
             None => Value::Null,
         }
     }
+
+    /// Like [`view`](Self::view), as a [`PackValue`]; see
+    /// [`CrdtNode::view_pack`].
+    pub fn view_pack(&self, index: &NodeIndex) -> PackValue {
+        match index.get(&TsKey::from(self.val)) {
+            Some(node) => node.view_pack(index),
+            None => PackValue::Null,
+        }
+    }
 }
 
 impl Default for RootNode {
@@ -568,6 +577,57 @@ impl CrdtNode {
         }
     }
 
+    /// Like [`view`](Self::view), but as a [`PackValue`] that keeps what
+    /// JSON cannot hold: `bin` nodes are bytes rather than arrays of
+    /// numbers, and constants keep their original type.
+    ///
+    /// Not an upstream port: upstream views are JS values, which already
+    /// hold `Uint8Array`s.
+    pub fn view_pack(&self, index: &NodeIndex) -> PackValue {
+        let resolve = |id: &Ts| match index.get(&TsKey::from(*id)) {
+            Some(node) => node.view_pack(index),
+            None => PackValue::Null,
+        };
+        match self {
+            Self::Con(n) => match &n.val {
+                ConValue::Ref(_) => PackValue::Null,
+                ConValue::Val(pv) => pv.clone(),
+            },
+            Self::Val(n) => resolve(&n.val),
+            Self::Obj(n) => PackValue::Object(
+                n.keys
+                    .iter()
+                    .filter_map(|(key, id)| {
+                        let val = match index.get(&TsKey::from(*id))? {
+                            CrdtNode::Con(ConNode {
+                                val: ConValue::Val(PackValue::Undefined),
+                                ..
+                            }) => return None,
+                            node => node.view_pack(index),
+                        };
+                        Some((key.clone(), val))
+                    })
+                    .collect(),
+            ),
+            Self::Vec(n) => PackValue::Array(
+                n.elements
+                    .iter()
+                    .map(|e| e.as_ref().map_or(PackValue::Null, resolve))
+                    .collect(),
+            ),
+            Self::Str(n) => PackValue::Str(n.view_str()),
+            Self::Bin(n) => PackValue::Bytes(n.view()),
+            Self::Arr(n) => PackValue::Array(
+                n.rga
+                    .iter_live()
+                    .filter_map(|chunk| chunk.data.as_ref())
+                    .flatten()
+                    .map(resolve)
+                    .collect(),
+            ),
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Self::Con(_) => "con",
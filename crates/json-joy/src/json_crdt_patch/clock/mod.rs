@@ -215,6 +215,23 @@ impl ClockVector {
         }
     }
 
+    /// Whether this clock has seen `id`: the local session up to its current
+    /// time, peers up to their last observed time.  System timestamps are
+    /// always seen.
+    ///
+    /// Not an upstream port.
+    pub fn has_seen(&self, id: Ts) -> bool {
+        if id.sid == SESSION::SYSTEM {
+            return true;
+        }
+        if id.sid == self.sid {
+            return id.time < self.time;
+        }
+        self.peers
+            .get(&id.sid)
+            .is_some_and(|peer| id.time <= peer.time)
+    }
+
     /// Deep clone with the same session ID.
     pub fn clone_same(&self) -> ClockVector {
         self.fork(self.sid)
@@ -0,0 +1,230 @@
+//! Framing of several binary-encoded patches into one payload.
+//!
+//! Not an upstream port. Two layouts are read:
+//!
+//! - **v1**: each patch preceded by its length as a little-endian `u32`.
+//! - **v2**: the 4-byte magic `"jb2" 0xff`, a compression byte (see
+//!   [`BatchCompression`]), then the body, compressed as that byte says:
+//!   each patch preceded by its length as an unsigned LEB128 varint.
+//!
+//! Read as a v1 length, the v2 magic is over 4 GB, so a v1 batch never
+//! starts with it and [`decode_batch`] tells the two apart by the first
+//! four bytes.
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+use std::io::{Read, Write};
+
+use json_joy_buffers::Writer;
+use json_joy_json_pack::util::varint::{read_varint, write_varint};
+use thiserror::Error;
+
+/// First four bytes of a v2 batch.
+pub const BATCH_V2_MAGIC: [u8; 4] = [b'j', b'b', b'2', 0xff];
+
+/// Compression of a v2 batch body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchCompression {
+    /// Stored as is.
+    #[default]
+    None = 0,
+    /// Raw deflate (RFC 1951), no zlib header; `deflate-raw` in the
+    /// browser's `CompressionStream`. Needs the `deflate` feature.
+    Deflate = 1,
+    /// A Zstandard frame. Needs the `zstd` feature.
+    Zstd = 2,
+}
+
+impl BatchCompression {
+    /// Parses `"none"`, `"deflate"` or `"zstd"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "deflate" => Some(Self::Deflate),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Deflate => "deflate",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::None),
+            1 => Some(Self::Deflate),
+            2 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Whether this build can compress and decompress with it.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Deflate => cfg!(feature = "deflate"),
+            Self::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+}
+
+/// Error reading or writing a patch batch.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BatchError {
+    #[error("truncated patch batch")]
+    Truncated,
+    #[error("unknown patch batch compression {0}")]
+    UnknownCompression(u8),
+    #[error("{} compression is not enabled in this build", .0.name())]
+    Unsupported(BatchCompression),
+    #[error("corrupt compressed patch batch")]
+    Corrupt,
+    #[error("patch batch body is over {0} bytes")]
+    TooLarge(usize),
+}
+
+/// Encodes `patches` as a v1 batch.
+pub fn encode_batch_v1(patches: &[&[u8]]) -> Vec<u8> {
+    let size = patches.iter().map(|p| 4 + p.len()).sum();
+    let mut out = Vec::with_capacity(size);
+    for patch in patches {
+        out.extend_from_slice(&(patch.len() as u32).to_le_bytes());
+        out.extend_from_slice(patch);
+    }
+    out
+}
+
+/// Encodes `patches` as a v2 batch with the body compressed by
+/// `compression`.
+pub fn encode_batch_v2(
+    patches: &[&[u8]],
+    compression: BatchCompression,
+) -> Result<Vec<u8>, BatchError> {
+    let mut writer = Writer::new();
+    for patch in patches {
+        write_varint(&mut writer, patch.len() as u64);
+        writer.buf(patch);
+    }
+    let body = writer.flush();
+    let mut out = Vec::with_capacity(BATCH_V2_MAGIC.len() + 1 + body.len());
+    out.extend_from_slice(&BATCH_V2_MAGIC);
+    out.push(compression as u8);
+    compress(compression, &body, &mut out)?;
+    Ok(out)
+}
+
+/// Decodes a v1 or v2 batch into its patches.
+pub fn decode_batch(data: &[u8]) -> Result<Vec<Vec<u8>>, BatchError> {
+    decode_batch_limited(data, usize::MAX)
+}
+
+/// Like [`decode_batch`], but fails with [`BatchError::TooLarge`] once a
+/// v2 body decompresses to more than `max_body` bytes, before the rest is
+/// inflated. v1 batches are not checked: they are already in memory.
+pub fn decode_batch_limited(data: &[u8], max_body: usize) -> Result<Vec<Vec<u8>>, BatchError> {
+    let Some(rest) = data.strip_prefix(&BATCH_V2_MAGIC) else {
+        return split_v1(data);
+    };
+    let (&byte, compressed) = rest.split_first().ok_or(BatchError::Truncated)?;
+    let compression =
+        BatchCompression::from_byte(byte).ok_or(BatchError::UnknownCompression(byte))?;
+    let body = decompress(compression, compressed, max_body)?;
+    split_v2(&body)
+}
+
+fn split_v1(mut data: &[u8]) -> Result<Vec<Vec<u8>>, BatchError> {
+    let mut patches = Vec::new();
+    while !data.is_empty() {
+        let (len, rest) = data.split_first_chunk::<4>().ok_or(BatchError::Truncated)?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err(BatchError::Truncated);
+        }
+        let (patch, rest) = rest.split_at(len);
+        patches.push(patch.to_vec());
+        data = rest;
+    }
+    Ok(patches)
+}
+
+fn split_v2(body: &[u8]) -> Result<Vec<Vec<u8>>, BatchError> {
+    let mut patches = Vec::new();
+    let mut pos = 0;
+    while pos < body.len() {
+        let (len, read) = read_varint(body, pos, 10).map_err(|_| BatchError::Truncated)?;
+        pos += read;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| pos.checked_add(len))
+            .filter(|&end| end <= body.len())
+            .ok_or(BatchError::Truncated)?;
+        patches.push(body[pos..end].to_vec());
+        pos = end;
+    }
+    Ok(patches)
+}
+
+fn compress(
+    compression: BatchCompression,
+    body: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<(), BatchError> {
+    match compression {
+        BatchCompression::None => out.extend_from_slice(body),
+        #[cfg(feature = "deflate")]
+        BatchCompression::Deflate => {
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(out, flate2::Compression::default());
+            encoder
+                .write_all(body)
+                .and_then(|_| encoder.try_finish())
+                .expect("writing to a Vec");
+        }
+        #[cfg(feature = "zstd")]
+        BatchCompression::Zstd => ruzstd::encoding::compress(
+            body,
+            out.by_ref(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        ),
+        #[allow(unreachable_patterns)]
+        other => return Err(BatchError::Unsupported(other)),
+    }
+    Ok(())
+}
+
+fn decompress(
+    compression: BatchCompression,
+    data: &[u8],
+    max_body: usize,
+) -> Result<Vec<u8>, BatchError> {
+    #[cfg(any(feature = "deflate", feature = "zstd"))]
+    let read_limited = |reader: &mut dyn Read| {
+        let mut body = Vec::new();
+        reader
+            .take((max_body as u64).saturating_add(1))
+            .read_to_end(&mut body)
+            .map_err(|_| BatchError::Corrupt)?;
+        Ok(body)
+    };
+    let body = match compression {
+        BatchCompression::None => data.to_vec(),
+        #[cfg(feature = "deflate")]
+        BatchCompression::Deflate => read_limited(&mut flate2::read::DeflateDecoder::new(data))?,
+        #[cfg(feature = "zstd")]
+        BatchCompression::Zstd => {
+            let mut decoder =
+                ruzstd::decoding::StreamingDecoder::new(data).map_err(|_| BatchError::Corrupt)?;
+            read_limited(&mut decoder)?
+        }
+        #[allow(unreachable_patterns)]
+        other => return Err(BatchError::Unsupported(other)),
+    };
+    if body.len() > max_body {
+        return Err(BatchError::TooLarge(max_body));
+    }
+    Ok(body)
+}
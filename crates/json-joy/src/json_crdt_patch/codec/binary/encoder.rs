@@ -11,7 +11,7 @@ use crate::json_crdt_patch::operations::{ConValue, Op};
 use crate::json_crdt_patch::patch::Patch;
 use crate::json_crdt_patch::util::binary::CrdtWriter;
 use json_joy_buffers::is_float32;
-use json_joy_json_pack::PackValue;
+use json_joy_json_pack::{JsonPackExtension, PackValue};
 
 /// Binary codec encoder.
 pub struct Encoder {
@@ -317,6 +317,15 @@ impl Encoder {
                     w.buf(&(*i as f64).to_be_bytes()); // fallback
                 }
             }
+            PackValue::BigNum(n) => match n.to_i128() {
+                Some(i) => Self::write_pack_value(w, &PackValue::BigInt(i)),
+                None => {
+                    // CBOR bignum (tag 2 / 3)
+                    let (tag, payload) = n.to_cbor_bignum();
+                    let ext = JsonPackExtension::new(tag, PackValue::Bytes(payload));
+                    Self::write_pack_value(w, &PackValue::Extension(Box::new(ext)));
+                }
+            },
             PackValue::Str(s) => Self::write_cbor_str(w, s),
             PackValue::Bytes(b) => {
                 let len = b.len();
@@ -350,21 +359,19 @@ impl Encoder {
                 }
             }
             PackValue::Object(obj) => {
-                let len = obj.len();
-                if len <= 23 {
-                    w.u8(0xA0 | len as u8);
-                } else if len <= 0xFF {
-                    w.u8(0xB8);
-                    w.u8(len as u8);
-                } else {
-                    w.u8(0xB9);
-                    w.buf(&(len as u16).to_be_bytes());
-                }
+                Self::write_map_hdr(w, obj.len());
                 for (k, v) in obj {
                     Self::write_cbor_str(w, k);
                     Self::write_pack_value(w, v);
                 }
             }
+            PackValue::Map(map) => {
+                Self::write_map_hdr(w, map.len());
+                for (k, v) in map {
+                    Self::write_pack_value(w, k);
+                    Self::write_pack_value(w, v);
+                }
+            }
             PackValue::Blob(b) => w.buf(&b.val),
             PackValue::Extension(ext) => {
                 // CBOR tag
@@ -383,6 +390,18 @@ impl Encoder {
         }
     }
 
+    fn write_map_hdr(w: &mut CrdtWriter, len: usize) {
+        if len <= 23 {
+            w.u8(0xA0 | len as u8);
+        } else if len <= 0xFF {
+            w.u8(0xB8);
+            w.u8(len as u8);
+        } else {
+            w.u8(0xB9);
+            w.buf(&(len as u16).to_be_bytes());
+        }
+    }
+
     fn write_uint(w: &mut CrdtWriter, u: u64) {
         if u <= 23 {
             w.u8(u as u8);
@@ -62,6 +62,7 @@ fn pack_to_json_value(v: json_joy_json_pack::PackValue) -> serde_json::Value {
             .map(Value::Number)
             .unwrap_or(Value::Null),
         PackValue::BigInt(i) => serde_json::json!(i),
+        PackValue::BigNum(n) => Value::String(n.to_string()),
         PackValue::Str(s) => Value::String(s),
         PackValue::Bytes(b) => Value::String(json_joy_base64::to_base64(&b)),
         PackValue::Array(arr) => Value::Array(arr.into_iter().map(pack_to_json_value).collect()),
@@ -72,6 +73,11 @@ fn pack_to_json_value(v: json_joy_json_pack::PackValue) -> serde_json::Value {
                 .collect();
             Value::Object(map)
         }
+        PackValue::Map(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k.to_key_string(), pack_to_json_value(v)))
+                .collect(),
+        ),
         PackValue::Extension(_) => Value::Null,
     }
 }
@@ -5,7 +5,10 @@
 //! - `verbose` — human-readable JSON object format
 //! - `compact` — space-efficient JSON array format
 //! - `compact_binary` — CBOR-encoded compact format
+//!
+//! `batch` frames several binary patches into one payload.
 
+pub mod batch;
 pub mod binary;
 pub mod clock;
 pub mod compact;
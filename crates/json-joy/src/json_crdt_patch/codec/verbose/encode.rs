@@ -31,6 +31,7 @@ fn pack_to_json(v: &json_joy_json_pack::PackValue) -> Value {
             .map(Value::Number)
             .unwrap_or(Value::Null),
         PackValue::BigInt(i) => json!(i),
+        PackValue::BigNum(n) => Value::String(n.to_string()),
         PackValue::Str(s) => json!(s),
         PackValue::Bytes(b) => Value::String(json_joy_base64::to_base64(b)),
         PackValue::Array(arr) => Value::Array(arr.iter().map(pack_to_json).collect()),
@@ -41,6 +42,11 @@ fn pack_to_json(v: &json_joy_json_pack::PackValue) -> Value {
                 .collect();
             Value::Object(map)
         }
+        PackValue::Map(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.to_key_string(), pack_to_json(v)))
+                .collect(),
+        ),
         PackValue::Extension(_ext) => json!(null), // not representable in plain JSON
     }
 }
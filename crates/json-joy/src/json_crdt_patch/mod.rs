@@ -33,5 +33,5 @@ pub use enums::{
     JsonCrdtDataType, JsonCrdtPatchOpcode, OpcodeOverlay, SESSION, SYSTEM_SESSION_TIME,
 };
 pub use operations::{ConValue, Op};
-pub use patch::Patch;
+pub use patch::{Patch, PatchJsonError, PatchSummary};
 pub use patch_builder::PatchBuilder;
@@ -5,6 +5,8 @@
 use crate::json_crdt_patch::clock::{ts, Ts};
 use crate::json_crdt_patch::operations::{ConValue, Op};
 use json_joy_json_pack::PackValue;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
 
 /// A JSON CRDT Patch: an ordered list of operations with optional metadata.
 ///
@@ -110,6 +112,152 @@ impl Patch {
     ) -> Result<Patch, crate::json_crdt_patch::codec::binary::DecodeError> {
         crate::json_crdt_patch::codec::binary::decode(data)
     }
+
+    /// Encodes the patch in the compact JSON format: a header `[id, meta?]`
+    /// followed by one array per operation.
+    ///
+    /// Not an upstream port: upstream calls the compact codec directly.
+    pub fn to_compact_json(&self) -> Result<Value, PatchJsonError> {
+        if self.ops.is_empty() {
+            return Err(PatchJsonError::Empty);
+        }
+        Ok(Value::Array(
+            crate::json_crdt_patch::codec::compact::encode(self),
+        ))
+    }
+
+    /// Decodes a patch from the compact JSON format.
+    ///
+    /// Every operation is checked before decoding: one with an unknown
+    /// opcode or malformed operands fails with [`PatchJsonError::InvalidOp`]
+    /// rather than being skipped or read with defaults.
+    pub fn from_compact_json(data: &Value) -> Result<Patch, PatchJsonError> {
+        let ops = data.as_array().ok_or(PatchJsonError::InvalidPatch)?;
+        let header = ops
+            .first()
+            .and_then(Value::as_array)
+            .ok_or(PatchJsonError::InvalidPatch)?;
+        check_json_id(header.first())?;
+        if let Some(i) = ops[1..].iter().position(|op| !is_compact_op(op)) {
+            return Err(PatchJsonError::InvalidOp(i));
+        }
+        Ok(crate::json_crdt_patch::codec::compact::decode(ops))
+    }
+
+    /// Encodes the patch in the verbose JSON format:
+    /// `{"id": …, "ops": [{"op": "new_con", …}, …], "meta"?: …}`.
+    ///
+    /// Not an upstream port: upstream calls the verbose codec directly.
+    pub fn to_verbose_json(&self) -> Result<Value, PatchJsonError> {
+        if self.ops.is_empty() {
+            return Err(PatchJsonError::Empty);
+        }
+        Ok(crate::json_crdt_patch::codec::verbose::encode(self))
+    }
+
+    /// Decodes a patch from the verbose JSON format.
+    ///
+    /// Operations are checked as in [`Patch::from_compact_json`].
+    pub fn from_verbose_json(data: &Value) -> Result<Patch, PatchJsonError> {
+        let obj = data.as_object().ok_or(PatchJsonError::InvalidPatch)?;
+        check_json_id(obj.get("id"))?;
+        let ops = obj
+            .get("ops")
+            .and_then(Value::as_array)
+            .ok_or(PatchJsonError::InvalidPatch)?;
+        if let Some(i) = ops.iter().position(|op| !is_verbose_op(op)) {
+            return Err(PatchJsonError::InvalidOp(i));
+        }
+        Ok(crate::json_crdt_patch::codec::verbose::decode(data))
+    }
+
+    /// Returns a structured summary of what the patch does, for logging
+    /// and debugging.
+    pub fn describe(&self) -> PatchSummary {
+        let mut summary = PatchSummary {
+            id: self.get_id(),
+            span: self.span(),
+            op_count: self.ops.len(),
+            has_meta: self.meta.is_some(),
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        for op in &self.ops {
+            *summary.op_kinds.entry(op.name()).or_insert(0) += 1;
+            let obj = match op {
+                Op::InsVal { obj, .. }
+                | Op::InsObj { obj, .. }
+                | Op::InsVec { obj, .. }
+                | Op::UpdArr { obj, .. } => *obj,
+                Op::InsStr { obj, .. } => {
+                    summary.inserted_text += op.span();
+                    *obj
+                }
+                Op::InsBin { obj, .. } => {
+                    summary.inserted_bytes += op.span();
+                    *obj
+                }
+                Op::InsArr { obj, .. } => {
+                    summary.inserted_items += op.span();
+                    *obj
+                }
+                Op::Del { obj, what, .. } => {
+                    summary.deleted += what.iter().map(|s| s.span).sum::<u64>();
+                    *obj
+                }
+                _ => continue,
+            };
+            if seen.insert(obj) {
+                summary.targets.push(obj);
+            }
+        }
+        summary
+    }
+}
+
+/// Summary of a [`Patch`], returned by [`Patch::describe`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatchSummary {
+    /// ID of the first operation, `None` for an empty patch.
+    pub id: Option<Ts>,
+    /// Total logical clock span of the patch.
+    pub span: u64,
+    /// Number of operations.
+    pub op_count: usize,
+    /// Number of operations of each kind, keyed by [`Op::name`].
+    pub op_kinds: BTreeMap<&'static str, usize>,
+    /// IDs of the nodes written to by `ins_*`, `upd_arr` and `del`
+    /// operations, in order of first appearance.
+    pub targets: Vec<Ts>,
+    /// Text inserted by `ins_str`, in UTF-16 code units.
+    pub inserted_text: u64,
+    /// Bytes inserted by `ins_bin`.
+    pub inserted_bytes: u64,
+    /// Elements inserted by `ins_arr`.
+    pub inserted_items: u64,
+    /// Total span of the ranges removed by `del`.
+    pub deleted: u64,
+    /// Whether the patch carries metadata.
+    pub has_meta: bool,
+}
+
+impl PatchSummary {
+    /// Renders the summary as JSON, with timestamps as `[sid, time]`.
+    pub fn to_json(&self) -> Value {
+        let ts_json = |id: &Ts| json!([id.sid, id.time]);
+        json!({
+            "id": self.id.as_ref().map(ts_json),
+            "span": self.span,
+            "op_count": self.op_count,
+            "op_kinds": self.op_kinds,
+            "targets": self.targets.iter().map(ts_json).collect::<Vec<_>>(),
+            "inserted_text": self.inserted_text,
+            "inserted_bytes": self.inserted_bytes,
+            "inserted_items": self.inserted_items,
+            "deleted": self.deleted,
+            "has_meta": self.has_meta,
+        })
+    }
 }
 
 impl std::fmt::Display for Patch {
@@ -126,6 +274,149 @@ impl std::fmt::Display for Patch {
     }
 }
 
+/// Why a patch could not be converted to or from JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchJsonError {
+    /// The patch has no operations, so no ID to write.
+    Empty,
+    /// Not an array with a header (compact) or an object with an `ops`
+    /// array (verbose).
+    InvalidPatch,
+    /// The patch ID is missing or is neither a time nor `[sid, time]`.
+    InvalidId,
+    /// The operation at this index has an unknown opcode or malformed
+    /// operands.
+    InvalidOp(usize),
+}
+
+impl std::fmt::Display for PatchJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchJsonError::Empty => write!(f, "PATCH_EMPTY"),
+            PatchJsonError::InvalidPatch => write!(f, "INVALID_PATCH"),
+            PatchJsonError::InvalidId => write!(f, "INVALID_ID"),
+            PatchJsonError::InvalidOp(i) => write!(f, "INVALID_OP at {i}"),
+        }
+    }
+}
+
+impl std::error::Error for PatchJsonError {}
+
+/// Checks a JSON patch ID the way the compact and verbose decoders read it.
+fn check_json_id(id: Option<&Value>) -> Result<(), PatchJsonError> {
+    if is_json_id(id) {
+        Ok(())
+    } else {
+        Err(PatchJsonError::InvalidId)
+    }
+}
+
+/// A timestamp in either JSON format: a bare time or `[sid, time]`.
+fn is_json_id(id: Option<&Value>) -> bool {
+    match id {
+        Some(Value::Number(n)) => n.is_u64(),
+        Some(Value::Array(parts)) => parts.len() == 2 && parts.iter().all(Value::is_u64),
+        _ => false,
+    }
+}
+
+/// An array whose items all pass `f`.
+fn is_array_of(v: Option<&Value>, f: impl Fn(&Value) -> bool) -> bool {
+    v.and_then(Value::as_array)
+        .is_some_and(|items| items.iter().all(f))
+}
+
+/// An `ins_obj` / `ins_vec` entry: `[key, id]`.
+fn is_json_tuple(v: &Value, is_key: impl Fn(&Value) -> bool) -> bool {
+    matches!(v.as_array().map(Vec::as_slice), Some([key, id]) if is_key(key) && is_json_id(Some(id)))
+}
+
+fn is_vec_index(v: &Value) -> bool {
+    v.as_u64().is_some_and(|i| i <= u8::MAX as u64)
+}
+
+fn is_base64(v: Option<&Value>) -> bool {
+    v.and_then(Value::as_str)
+        .is_some_and(|s| json_joy_base64::from_base64(s).is_ok())
+}
+
+/// Checks one compact-format operation: `[opcode, ...operands]`.
+fn is_compact_op(op: &Value) -> bool {
+    use crate::json_crdt_patch::enums::JsonCrdtPatchOpcode as Code;
+    let Some(a) = op.as_array() else {
+        return false;
+    };
+    let Some(code) = a
+        .first()
+        .and_then(Value::as_u64)
+        .and_then(|n| u8::try_from(n).ok())
+        .and_then(Code::from_u8)
+    else {
+        return false;
+    };
+    let id = |i: usize| is_json_id(a.get(i));
+    let span = |s: &Value| matches!(s.as_array().map(Vec::as_slice), Some(p) if (p.len() == 2 || p.len() == 3) && p.iter().all(Value::is_u64));
+    match code {
+        Code::NewCon => match a.len() {
+            1 | 2 => true,
+            3 => a[2] == Value::Bool(true) && id(1),
+            _ => false,
+        },
+        Code::NewVal | Code::NewObj | Code::NewVec | Code::NewStr | Code::NewBin | Code::NewArr => {
+            a.len() == 1
+        }
+        Code::InsVal => a.len() == 3 && id(1) && id(2),
+        Code::InsObj => {
+            a.len() == 3 && id(1) && is_array_of(a.get(2), |t| is_json_tuple(t, Value::is_string))
+        }
+        Code::InsVec => {
+            a.len() == 3 && id(1) && is_array_of(a.get(2), |t| is_json_tuple(t, is_vec_index))
+        }
+        Code::InsStr => a.len() == 4 && id(1) && id(2) && a[3].is_string(),
+        Code::InsBin => a.len() == 4 && id(1) && id(2) && is_base64(a.get(3)),
+        Code::InsArr => {
+            a.len() == 4 && id(1) && id(2) && is_array_of(a.get(3), |e| is_json_id(Some(e)))
+        }
+        Code::UpdArr => a.len() == 4 && id(1) && id(2) && id(3),
+        Code::Del => a.len() == 3 && id(1) && is_array_of(a.get(2), span),
+        Code::Nop => a.len() == 1 || (a.len() == 2 && a[1].is_u64()),
+    }
+}
+
+/// Checks one verbose-format operation: `{"op": name, ...operands}`.
+fn is_verbose_op(op: &Value) -> bool {
+    let Some(o) = op.as_object() else {
+        return false;
+    };
+    let id = |key: &str| is_json_id(o.get(key));
+    let after = || o.get("after").is_none() || id("after");
+    let span = |s: &Value| matches!(s.as_array().map(Vec::as_slice), Some(p) if p.len() == 3 && p.iter().all(Value::is_u64));
+    match o.get("op").and_then(Value::as_str) {
+        Some("new_con") => match o.get("timestamp") {
+            None | Some(Value::Bool(false)) => true,
+            Some(Value::Bool(true)) => id("value"),
+            Some(_) => false,
+        },
+        Some("new_val" | "new_obj" | "new_vec" | "new_str" | "new_bin" | "new_arr") => true,
+        Some("ins_val") => id("obj") && id("value"),
+        Some("ins_obj") => {
+            id("obj") && is_array_of(o.get("value"), |t| is_json_tuple(t, Value::is_string))
+        }
+        Some("ins_vec") => {
+            id("obj") && is_array_of(o.get("value"), |t| is_json_tuple(t, is_vec_index))
+        }
+        Some("ins_str") => id("obj") && after() && o.get("value").is_some_and(Value::is_string),
+        Some("ins_bin") => id("obj") && after() && is_base64(o.get("value")),
+        Some("ins_arr") => {
+            id("obj") && after() && is_array_of(o.get("values"), |e| is_json_id(Some(e)))
+        }
+        Some("upd_arr") => id("obj") && id("ref") && id("value"),
+        Some("del") => id("obj") && is_array_of(o.get("what"), span),
+        Some("nop") => o.get("len").is_none_or(Value::is_u64),
+        _ => false,
+    }
+}
+
 /// Applies the timestamp transform function to a single operation.
 fn rewrite_op<F>(op: &Op, f: &F) -> Op
 where
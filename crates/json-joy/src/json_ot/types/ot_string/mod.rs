@@ -10,6 +10,8 @@
 //! - `DeleteStr(s)` — reversible delete storing the deleted text
 //! - `Insert(s)` — insert text
 
+use std::ops::Range;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum StringComponent {
     Retain(usize),
@@ -253,15 +255,15 @@ pub fn transform(op: &StringOp, against: &StringOp, left_wins: bool) -> StringOp
             }
             (Some(o), Some(a)) => {
                 match (&o, &a) {
+                    // Both insert at the same position: `left_wins` puts ours first
+                    (StringComponent::Insert(s), StringComponent::Insert(_)) if left_wins => {
+                        append(&mut result, StringComponent::Insert(s.clone()));
+                        rem_ag = Some(a);
+                    }
                     // Against inserts: add retain to account for inserted chars
                     (_, StringComponent::Insert(s)) => {
-                        if left_wins {
-                            rem_op = Some(o);
-                            append(&mut result, StringComponent::Retain(s.chars().count()));
-                        } else {
-                            append(&mut result, StringComponent::Retain(s.chars().count()));
-                            rem_op = Some(o);
-                        }
+                        append(&mut result, StringComponent::Retain(s.chars().count()));
+                        rem_op = Some(o);
                     }
                     // Op inserts: pass through
                     (StringComponent::Insert(s), _) => {
@@ -362,6 +364,65 @@ pub fn transform(op: &StringOp, against: &StringOp, left_wins: bool) -> StringOp
     normalize(result)
 }
 
+/// Which side of text inserted exactly at a position the position ends up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bias {
+    /// Stay before the inserted text, as a remote caret usually should.
+    #[default]
+    Before,
+    /// Move past the inserted text, as the caret of whoever typed it does.
+    After,
+}
+
+/// Map character offset `pos` in the source of `op` to its offset in the
+/// result. A position inside deleted text moves to where the text was.
+///
+/// Not an upstream port.
+pub fn transform_position(pos: usize, op: &StringOp, bias: Bias) -> usize {
+    let mut pos = pos;
+    let mut src = 0;
+    let mut dst = 0;
+    for comp in op {
+        match comp {
+            StringComponent::Retain(n) => {
+                if pos < src + n {
+                    return dst + pos - src;
+                }
+                src += n;
+                dst += n;
+            }
+            StringComponent::Delete(_) | StringComponent::DeleteStr(_) => {
+                let n = comp.src_len();
+                // Deleted text has no width, so its end is the same place.
+                if pos < src + n {
+                    pos = src + n;
+                }
+                src += n;
+            }
+            StringComponent::Insert(s) => {
+                if pos == src && bias == Bias::Before {
+                    return dst;
+                }
+                dst += s.chars().count();
+            }
+        }
+    }
+    dst + pos - src
+}
+
+/// Map a selection of characters `range` through `op`.
+///
+/// Text inserted at either edge stays outside the selection, and a caret
+/// (an empty range) stays before it. A selection whose text was all
+/// replaced collapses to a caret before the new text.
+///
+/// Not an upstream port.
+pub fn transform_range(range: Range<usize>, op: &StringOp) -> Range<usize> {
+    let end = transform_position(range.end, op, Bias::Before);
+    let start = transform_position(range.start, op, Bias::After).min(end);
+    start..end
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,6 +494,9 @@ mod tests {
         let result = apply("hello", &transformed);
         // After "B" is inserted first, our op should still insert "A" in the right place
         assert!(result.contains('A'));
+        assert_eq!(apply(&apply("hello", &against), &transformed), "ABhello");
+        let transformed = transform(&op, &against, false);
+        assert_eq!(apply(&apply("hello", &against), &transformed), "BAhello");
     }
 
     // ── StringComponent src_len / dst_len ───────────────────────────────
@@ -913,4 +977,55 @@ mod tests {
         let t = transform(&op, &against, true);
         assert_eq!(t, vec![StringComponent::DeleteStr("de".to_string())]);
     }
+
+    #[test]
+    fn transform_position_bias_at_insert() {
+        // "hello" -> "heXXllo"
+        let op = vec![
+            StringComponent::Retain(2),
+            StringComponent::Insert("XX".to_string()),
+        ];
+        assert_eq!(transform_position(1, &op, Bias::Before), 1);
+        assert_eq!(transform_position(2, &op, Bias::Before), 2);
+        assert_eq!(transform_position(2, &op, Bias::After), 4);
+        assert_eq!(transform_position(3, &op, Bias::Before), 5);
+        assert_eq!(transform_position(5, &op, Bias::Before), 7);
+    }
+
+    #[test]
+    fn transform_position_inside_delete() {
+        // "hello" -> "hYo"
+        let op = vec![
+            StringComponent::Retain(1),
+            StringComponent::DeleteStr("ell".to_string()),
+            StringComponent::Insert("Y".to_string()),
+        ];
+        assert_eq!(transform_position(1, &op, Bias::After), 2);
+        assert_eq!(transform_position(3, &op, Bias::Before), 1);
+        assert_eq!(transform_position(3, &op, Bias::After), 2);
+        assert_eq!(transform_position(4, &op, Bias::Before), 1);
+        assert_eq!(transform_position(5, &op, Bias::Before), 3);
+    }
+
+    #[test]
+    fn transform_range_edges() {
+        // "hello" -> "hXelYlo"
+        let op = vec![
+            StringComponent::Retain(1),
+            StringComponent::Insert("X".to_string()),
+            StringComponent::Retain(2),
+            StringComponent::Insert("Y".to_string()),
+        ];
+        assert_eq!(transform_range(1..3, &op), 2..4);
+        assert_eq!(transform_range(0..4, &op), 0..6);
+        assert_eq!(transform_range(1..1, &op), 1..1);
+        // The selected "ell" is replaced by "Y".
+        let op = vec![
+            StringComponent::Retain(1),
+            StringComponent::Delete(3),
+            StringComponent::Insert("Y".to_string()),
+        ];
+        assert_eq!(transform_range(1..4, &op), 1..1);
+        assert_eq!(transform_range(0..5, &op), 0..3);
+    }
 }
@@ -472,9 +472,17 @@ fn pack_to_json_value(v: &PackValue) -> Value {
             }
             Value::Object(m)
         }
+        PackValue::Map(entries) => {
+            let mut m = Map::new();
+            for (k, v) in entries {
+                m.insert(k.to_key_string(), pack_to_json_value(v));
+            }
+            Value::Object(m)
+        }
         PackValue::Bytes(_) => Value::Null,
         PackValue::Undefined => Value::Null,
         PackValue::BigInt(i) => Value::Number((*i as i64).into()),
+        PackValue::BigNum(n) => Value::String(n.to_string()),
         PackValue::Extension(_) => Value::Null,
         PackValue::Blob(_) => Value::Null,
     }
@@ -16,6 +16,7 @@ use json_joy_json_pack::PackValue;
 /// - object → 2 + sum of (2 + key bytes + value size) per entry
 /// - pre-encoded blob → raw byte length as-is
 /// - extension with byte payload → 6 + payload length
+/// - arbitrary-precision integer → 6 + two's complement byte length (ext)
 pub fn msgpack_size_fast(value: &PackValue) -> usize {
     match value {
         PackValue::Null | PackValue::Undefined => 1,
@@ -24,6 +25,7 @@ pub fn msgpack_size_fast(value: &PackValue) -> usize {
         | PackValue::UInteger(_)
         | PackValue::Float(_)
         | PackValue::BigInt(_) => 9,
+        PackValue::BigNum(n) => 6 + n.to_twos_complement().len(),
         PackValue::Str(s) => 4 + s.len(),
         PackValue::Bytes(b) => 5 + b.len(),
         PackValue::Array(arr) => {
@@ -40,6 +42,13 @@ pub fn msgpack_size_fast(value: &PackValue) -> usize {
             }
             size
         }
+        PackValue::Map(map) => {
+            let mut size: usize = 2;
+            for (key, val) in map {
+                size += msgpack_size_fast(key) + msgpack_size_fast(val);
+            }
+            size
+        }
         PackValue::Blob(blob) => blob.val.len(),
         // Upstream extensions always wrap raw bytes and use `6 + payload.length`.
         // Rust allows non-byte extension payloads; for those we mirror the local
@@ -6,6 +6,7 @@
 //! using insertion sort before serializing, ensuring a deterministic output
 //! regardless of the order keys were inserted into the map.
 
+use json_joy_json_pack::util::format_f64_js;
 use json_joy_util::insertion_sort_by;
 use json_joy_util::strings::escape;
 use serde_json::Value;
@@ -20,7 +21,10 @@ pub fn stringify(val: &Value) -> String {
         Value::String(s) => format!("\"{}\"", escape(s)),
         Value::Null => "null".to_owned(),
         Value::Bool(b) => b.to_string(),
-        Value::Number(n) => n.to_string(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() => format_f64_js(f),
+            _ => n.to_string(),
+        },
         Value::Array(arr) => {
             if arr.is_empty() {
                 return "[]".to_owned();
@@ -85,6 +89,18 @@ mod tests {
         assert_eq!(stringify(&json!(decimal)), "3.14");
     }
 
+    #[test]
+    fn float_values_match_javascript() {
+        assert_eq!(stringify(&json!(1.0)), "1");
+        assert_eq!(stringify(&json!(-0.0)), "0");
+        assert_eq!(stringify(&json!(1e21)), "1e+21");
+        assert_eq!(stringify(&json!(1.5e-7)), "1.5e-7");
+        assert_eq!(
+            stringify(&json!([0.1, 2.5e20])),
+            "[0.1,250000000000000000000]"
+        );
+    }
+
     #[test]
     fn string_value() {
         assert_eq!(stringify(&json!("hello")), r#""hello""#);
@@ -0,0 +1,115 @@
+//! `Model::changed_paths_since`.
+//!
+//! Not an upstream port. Each case edits a copy of one document with JSON
+//! Patch and checks the paths reported against the clock from before.
+
+use json_joy::json_crdt::model::Model;
+use json_joy::json_patch::from_json_patch;
+use serde_json::{json, Value};
+
+fn base() -> Value {
+    json!({
+        "title": "draft",
+        "tags": ["a", "b", {"k": 1}],
+        "meta": {"n": 1, "s": "x"},
+        "a/b": 0
+    })
+}
+
+fn edit(model: &mut Model, ops: Value) {
+    model
+        .apply_json_patch(&from_json_patch(&ops).unwrap())
+        .unwrap();
+}
+
+fn model() -> Model {
+    let mut model = Model::new(0x10000);
+    edit(
+        &mut model,
+        json!([{"op": "replace", "path": "", "value": base()}]),
+    );
+    model
+}
+
+#[test]
+fn model_changed_paths_matrix() {
+    let cases: Vec<(Value, &[&str])> = vec![
+        (json!([]), &[]),
+        (
+            json!([{"op": "replace", "path": "/title", "value": "drafts"}]),
+            &["/title"],
+        ),
+        // A delete alone leaves no new timestamp in the string.
+        (
+            json!([{"op": "replace", "path": "/title", "value": "dra"}]),
+            &["/title"],
+        ),
+        (
+            json!([{"op": "replace", "path": "/meta/n", "value": 2}]),
+            &["/meta/n"],
+        ),
+        (json!([{"op": "remove", "path": "/meta/s"}]), &["/meta/s"]),
+        (
+            json!([{"op": "add", "path": "/new", "value": [1]}]),
+            &["/new"],
+        ),
+        (
+            json!([{"op": "replace", "path": "/tags/2/k", "value": 2}]),
+            &["/tags/2/k"],
+        ),
+        (json!([{"op": "remove", "path": "/tags/0"}]), &["/tags"]),
+        (
+            json!([{"op": "add", "path": "/tags/-", "value": "c"}]),
+            &["/tags"],
+        ),
+        (
+            json!([{"op": "replace", "path": "/a~1b", "value": 1}]),
+            &["/a~1b"],
+        ),
+        (
+            json!([
+                {"op": "replace", "path": "/meta/n", "value": 2},
+                {"op": "replace", "path": "/title", "value": "final"}
+            ]),
+            &["/title", "/meta/n"],
+        ),
+        (json!([{"op": "replace", "path": "", "value": 7}]), &[""]),
+    ];
+    for (ops, expected) in cases {
+        let mut model = model();
+        let clock = model.clock.clone();
+        edit(&mut model, ops.clone());
+        assert_eq!(model.changed_paths_since(&clock), expected, "{ops}");
+        assert!(model.changed_paths_since(&model.clock).is_empty());
+    }
+}
+
+#[test]
+fn model_changed_paths_remote() {
+    let mut local = model();
+    let mut remote = local.clone();
+    remote.clock = local.clock.fork(0x20000);
+    let before = local.clock.clone();
+
+    // The peer deletes text it did not insert and appends to the array.
+    let ops = json!([
+        {"op": "replace", "path": "/title", "value": "dr"},
+        {"op": "add", "path": "/tags/-", "value": "z"}
+    ]);
+    let patch = remote
+        .apply_json_patch(&from_json_patch(&ops).unwrap())
+        .unwrap()
+        .unwrap();
+    local.apply_patch(&patch);
+    assert_eq!(local.view(), remote.view());
+    assert_eq!(local.changed_paths_since(&before), ["/title", "/tags"]);
+    // The peer's own clock has seen its edits.
+    assert!(local.changed_paths_since(&remote.clock).is_empty());
+
+    // A local edit after the peer's is new to the peer.
+    edit(
+        &mut local,
+        json!([{"op": "replace", "path": "/meta/n", "value": 5}]),
+    );
+    assert_eq!(local.changed_paths_since(&remote.clock), ["/meta/n"]);
+}
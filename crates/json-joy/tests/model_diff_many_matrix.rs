@@ -0,0 +1,65 @@
+//! `Model::diff_apply` and `Model::diff_many`.
+//!
+//! Not an upstream port. A batch must produce the same patches as diffing
+//! the targets one at a time, and replaying them on a replica must reach
+//! the same state.
+
+use json_joy::json_crdt::model::{Model, ModelApi};
+use json_joy::json_crdt_patch::patch::Patch;
+use serde_json::{json, Value};
+
+fn model() -> Model {
+    let mut model = Model::new(0x10000);
+    ModelApi::new(&mut model)
+        .set(&json!({"title": "draft", "tags": ["a"]}))
+        .unwrap();
+    model
+}
+
+fn targets() -> Vec<Value> {
+    vec![
+        json!({"title": "draft", "tags": ["a"]}),
+        json!({"title": "drafts", "tags": ["a", "b"]}),
+        json!({"title": "drafts", "tags": ["b"], "n": 1}),
+        json!({"title": "drafts", "tags": ["b"], "n": 1}),
+        json!([1, 2]),
+        json!("flat"),
+        json!(null),
+        json!({"title": "again"}),
+    ]
+}
+
+#[test]
+fn model_diff_many_matches_sequential_diff_apply() {
+    let mut batch = model();
+    let mut sequential = model();
+    let mut replica = model();
+
+    let patches = batch.diff_many(&targets());
+    assert_eq!(patches.len(), targets().len());
+    for (i, (target, bytes)) in targets().iter().zip(&patches).enumerate() {
+        let expected = sequential.diff_apply(target).map(|p| p.to_binary());
+        assert_eq!(bytes, &expected, "step {i}");
+        assert_eq!(bytes.is_none(), i == 0 || i == 3, "step {i}");
+        if let Some(bytes) = bytes {
+            replica.apply_patch(&Patch::from_binary(bytes).unwrap());
+        }
+        assert_eq!(replica.view(), *target, "step {i}");
+    }
+    assert_eq!(batch.view(), json!({"title": "again"}));
+    assert_eq!(batch.to_binary(), sequential.to_binary());
+    assert_eq!(replica.to_binary(), batch.to_binary());
+}
+
+#[test]
+fn model_diff_many_empty_and_fresh() {
+    let mut local = model();
+    let before = local.to_binary();
+    assert!(local.diff_many(&[]).is_empty());
+    assert_eq!(local.to_binary(), before);
+
+    let mut fresh = Model::new(0x10000);
+    let patches = fresh.diff_many(&[json!({"a": [1]}), json!({"a": [1, 2]})]);
+    assert!(patches.iter().all(Option::is_some));
+    assert_eq!(fresh.view(), json!({"a": [1, 2]}));
+}
@@ -0,0 +1,125 @@
+//! Structural binary format detection and conversion.
+//!
+//! Not an upstream port. Covers `probe` on well-formed and damaged
+//! snapshots, and conversion between the logical and server clock
+//! encodings.
+
+use json_joy::json_crdt::codec::structural::binary::{
+    convert, decode, encode, encode_as, probe, DecodeError, ModelFormat,
+};
+use json_joy::json_crdt::model::{Model, ModelApi};
+use json_joy::json_crdt_patch::enums::SESSION;
+use serde_json::{json, Value};
+use std::mem::discriminant;
+
+fn doc() -> Value {
+    json!({"title": "draft", "tags": ["a", "b"], "n": 1.5, "bin": null})
+}
+
+fn logical() -> Model {
+    let mut model = Model::new(0x10000);
+    ModelApi::new(&mut model).set(&doc()).unwrap();
+    model
+}
+
+fn server() -> Model {
+    let mut model = Model::new_server(1);
+    ModelApi::new(&mut model).set(&doc()).unwrap();
+    ModelApi::new(&mut model)
+        .set(&json!({"title": "final"}))
+        .unwrap();
+    model
+}
+
+#[test]
+fn model_format_probe_matrix() {
+    let cases = [
+        (Model::new(0x10000), ModelFormat::Logical),
+        (logical(), ModelFormat::Logical),
+        (Model::new_server(5), ModelFormat::Server),
+        (server(), ModelFormat::Server),
+    ];
+    for (model, format) in cases {
+        assert_eq!(ModelFormat::of(&model), format);
+        let bytes = encode(&model);
+        assert_eq!(probe(&bytes).unwrap(), format);
+        assert_eq!(decode(&bytes).unwrap().view(), model.view());
+    }
+}
+
+#[test]
+fn model_format_probe_errors() {
+    let bytes = encode(&logical());
+    let cases: [(&[u8], DecodeError); 6] = [
+        (&[], DecodeError::EndOfInput),
+        (&[0x80, 0xff], DecodeError::EndOfInput),
+        (&[0xff, 0xff], DecodeError::EndOfInput),
+        (&[0x00, 0x00], DecodeError::EndOfInput),
+        (
+            &[0x00, 0x00, 0x00, 0x09, 0x00],
+            DecodeError::InvalidClockTable,
+        ),
+        (&bytes[..bytes.len() - 1], DecodeError::InvalidClockTable),
+    ];
+    for (data, expected) in cases {
+        for err in [probe(data).unwrap_err(), decode(data).unwrap_err()] {
+            assert_eq!(
+                discriminant(&err),
+                discriminant(&expected),
+                "{data:?}: {err:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn model_format_probe_high_bit() {
+    // Upstream reads any first byte with the high bit set as server time.
+    for first in [0x80, 0x81, 0xc0, 0xff] {
+        assert_eq!(probe(&[first, 0x01]).unwrap(), ModelFormat::Server);
+    }
+    let mut bytes = encode(&server());
+    bytes[0] = 0xff;
+    assert_eq!(decode(&bytes).unwrap().view(), server().view());
+}
+
+#[test]
+fn model_format_convert_matrix() {
+    let model = server();
+    let server_bytes = encode(&model);
+
+    let logical_bytes = convert(&server_bytes, ModelFormat::Logical).unwrap();
+    assert_eq!(probe(&logical_bytes).unwrap(), ModelFormat::Logical);
+    let decoded = decode(&logical_bytes).unwrap();
+    assert_eq!(decoded.view(), model.view());
+    assert_eq!(decoded.clock.sid, SESSION::SERVER);
+    assert_eq!(decoded.clock.time, model.clock.time);
+
+    // Back to the original bytes, and through `encode_as` directly.
+    assert_eq!(
+        convert(&logical_bytes, ModelFormat::Server).unwrap(),
+        server_bytes
+    );
+    assert_eq!(
+        encode_as(&decoded, ModelFormat::Server).unwrap(),
+        server_bytes
+    );
+    assert_eq!(
+        encode_as(&model, ModelFormat::Logical).unwrap(),
+        logical_bytes
+    );
+
+    // Same format is a no-op.
+    assert_eq!(
+        convert(&server_bytes, ModelFormat::Server).unwrap(),
+        server_bytes
+    );
+    let bytes = encode(&logical());
+    assert_eq!(convert(&bytes, ModelFormat::Logical).unwrap(), bytes);
+
+    // Other sessions cannot be written with the server clock.
+    assert!(matches!(
+        convert(&bytes, ModelFormat::Server),
+        Err(DecodeError::NotServerClock(0x10000))
+    ));
+}
@@ -0,0 +1,122 @@
+//! `Model::apply_json_patch`.
+//!
+//! Not an upstream port. Every case checks the model view against plain
+//! JSON Patch application, and that the returned CRDT patch, sent over the
+//! wire, brings a replica of the original model to the same state.
+
+use json_joy::json_crdt::model::{Model, ModelApi};
+use json_joy::json_crdt_patch::patch::Patch;
+use json_joy::json_patch::{apply_ops, from_json_patch, PatchError};
+use serde_json::{json, Value};
+
+fn model(doc: &Value) -> Model {
+    let mut model = Model::new(0x10000);
+    ModelApi::new(&mut model).set(doc).unwrap();
+    model
+}
+
+fn doc() -> Value {
+    json!({
+        "title": "draft",
+        "tags": ["a", "b"],
+        "meta": {"rev": 1, "owner": {"id": 7}},
+        "flag": false
+    })
+}
+
+#[test]
+fn model_apply_json_patch_matrix() {
+    let cases = [
+        json!([{"op": "replace", "path": "/title", "value": "final"}]),
+        json!([{"op": "add", "path": "/tags/-", "value": "c"}]),
+        json!([{"op": "add", "path": "/tags/0", "value": "z"}, {"op": "remove", "path": "/tags/2"}]),
+        json!([{"op": "remove", "path": "/meta/owner"}]),
+        json!([{"op": "move", "from": "/meta/rev", "path": "/rev"}]),
+        json!([{"op": "copy", "from": "/meta", "path": "/meta2"}]),
+        json!([
+            {"op": "test", "path": "/flag", "value": false},
+            {"op": "replace", "path": "/flag", "value": true},
+            {"op": "add", "path": "/meta/owner/name", "value": {"first": "ann"}}
+        ]),
+        json!([{"op": "replace", "path": "", "value": [1, 2, 3]}]),
+        json!([{"op": "replace", "path": "/meta", "value": "flat"}]),
+        json!([{"op": "str_ins", "path": "/title", "pos": 5, "str": "s"}]),
+    ];
+    for ops in cases {
+        let ops = from_json_patch(&ops).unwrap();
+        let expected = apply_ops(doc(), &ops).unwrap().doc;
+
+        let mut local = model(&doc());
+        let mut replica = local.clone();
+        let patch = local
+            .apply_json_patch(&ops)
+            .unwrap()
+            .expect("document changed");
+        assert_eq!(local.view(), expected, "{ops:?}");
+
+        let patch = Patch::from_binary(&patch.to_binary()).unwrap();
+        replica.apply_patch(&patch);
+        assert_eq!(replica.view(), expected, "{ops:?}");
+        assert_eq!(replica.to_binary(), local.to_binary(), "{ops:?}");
+    }
+}
+
+#[test]
+fn model_apply_json_patch_sequential() {
+    // Later patches build on earlier ones, and the clock keeps advancing.
+    let mut local = model(&doc());
+    let mut replica = local.clone();
+    let batches = [
+        json!([{"op": "add", "path": "/tags/-", "value": "c"}]),
+        json!([{"op": "remove", "path": "/tags/0"}]),
+        json!([{"op": "replace", "path": "/meta/rev", "value": 2}]),
+    ];
+    let mut expected = doc();
+    for ops in batches {
+        let ops = from_json_patch(&ops).unwrap();
+        expected = apply_ops(expected, &ops).unwrap().doc;
+        let patch = local.apply_json_patch(&ops).unwrap().unwrap();
+        replica.apply_patch(&patch);
+    }
+    assert_eq!(local.view(), expected);
+    assert_eq!(replica.view(), expected);
+}
+
+#[test]
+fn model_apply_json_patch_no_change_and_errors() {
+    let mut local = model(&doc());
+    let before = local.to_binary();
+
+    let cases = [
+        json!([]),
+        json!([{"op": "test", "path": "/title", "value": "draft"}]),
+        json!([{"op": "replace", "path": "/title", "value": "draft"}]),
+    ];
+    for ops in cases {
+        let ops = from_json_patch(&ops).unwrap();
+        assert!(local.apply_json_patch(&ops).unwrap().is_none(), "{ops:?}");
+    }
+
+    // A failing operation leaves earlier ones unapplied.
+    let failing = [
+        (
+            json!([
+                {"op": "replace", "path": "/title", "value": "x"},
+                {"op": "test", "path": "/flag", "value": true}
+            ]),
+            PatchError::Test,
+        ),
+        (
+            json!([
+                {"op": "add", "path": "/tags/-", "value": "c"},
+                {"op": "remove", "path": "/nope"}
+            ]),
+            PatchError::NotFound,
+        ),
+    ];
+    for (ops, err) in failing {
+        let ops = from_json_patch(&ops).unwrap();
+        assert_eq!(local.apply_json_patch(&ops).unwrap_err(), err, "{ops:?}");
+    }
+    assert_eq!(local.to_binary(), before);
+}
@@ -0,0 +1,213 @@
+//! Differential replay of recorded patch sequences.
+//!
+//! Not an upstream port. Replays the pinned `model_apply_replay` fixtures
+//! and the randomised `model_replay_fuzz` fixtures written by
+//! `tools/oracle-node/generate-replay-fixtures.cjs` (to
+//! `tests/compat/replay_fixtures`, or the directory named by
+//! `JSON_JOY_REPLAY_FIXTURES`; a missing directory fails unless that is
+//! `skip`), and compares the final view and model binary
+//! with upstream's. Each sequence is also replayed with every patch
+//! delivered twice and through a binary round trip, which must not change
+//! the result.
+
+use std::path::{Path, PathBuf};
+
+use json_joy::json_crdt::codec::structural::binary as structural_binary;
+use json_joy::json_crdt::model::Model;
+use json_joy::json_crdt::nodes::{CrdtNode, TsKey};
+use json_joy::json_crdt_patch::patch::Patch;
+use serde_json::{Map, Value};
+
+const PINNED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/compat/fixtures");
+const GENERATED: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/compat/replay_fixtures"
+);
+
+/// One recorded replay and what upstream ended up with.
+struct Replay {
+    name: String,
+    label: String,
+    base: Vec<u8>,
+    patches: Vec<Patch>,
+    pattern: Vec<usize>,
+    view: Value,
+    binary: Vec<u8>,
+    effective: usize,
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+impl Replay {
+    fn from_json(doc: &Value) -> Self {
+        let (input, expected) = (&doc["input"], &doc["expected"]);
+        let hex = |v: &Value| decode_hex(v.as_str().unwrap());
+        let patches = input["patches_binary_hex"].as_array().unwrap();
+        let pattern = input["replay_pattern"].as_array().unwrap();
+        Self {
+            name: doc["name"].as_str().unwrap().to_string(),
+            label: input["label"].as_str().unwrap_or_default().to_string(),
+            base: hex(&input["base_model_binary_hex"]),
+            patches: patches
+                .iter()
+                .map(|p| Patch::from_binary(&hex(p)).unwrap())
+                .collect(),
+            pattern: pattern
+                .iter()
+                .map(|i| i.as_u64().unwrap() as usize)
+                .collect(),
+            view: expected["view_json"].clone(),
+            binary: hex(&expected["model_binary_hex"]),
+            effective: expected["applied_patch_count_effective"].as_u64().unwrap() as usize,
+        }
+    }
+
+    /// Applies `pattern` to the base model, counting the patches that
+    /// changed it.
+    fn run(&self, pattern: &[usize]) -> (Model, usize) {
+        let mut model = structural_binary::decode(&self.base).unwrap();
+        let mut effective = 0;
+        for &i in pattern {
+            let before = structural_binary::encode(&model);
+            model.apply_patch(&self.patches[i]);
+            if structural_binary::encode(&model) != before {
+                effective += 1;
+            }
+        }
+        (model, effective)
+    }
+
+    /// Describes every way the replay differs from upstream or from itself.
+    fn check(&self) -> Result<(), String> {
+        let mut diffs = Vec::new();
+        let (model, effective) = self.run(&self.pattern);
+        let binary = structural_binary::encode(&model);
+        if view(&model) != self.view {
+            diffs.push(format!("view {} != upstream {}", view(&model), self.view));
+        }
+        if binary != self.binary {
+            diffs.push("model binary differs from upstream".to_string());
+        }
+        if effective != self.effective {
+            diffs.push(format!(
+                "{effective} patches took effect, upstream {}",
+                self.effective
+            ));
+        }
+        let doubled: Vec<usize> = self.pattern.iter().flat_map(|&i| [i, i]).collect();
+        if structural_binary::encode(&self.run(&doubled).0) != binary {
+            diffs.push("delivering each patch twice changed the result".to_string());
+        }
+        match structural_binary::decode(&binary) {
+            Ok(decoded) if view(&decoded) == view(&model) => {}
+            _ => diffs.push("final binary does not decode to the same view".to_string()),
+        }
+        if diffs.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} ({}, pattern {:?}): {}",
+                self.name,
+                self.label,
+                self.pattern,
+                diffs.join("; ")
+            ))
+        }
+    }
+}
+
+/// The view as upstream's fixtures record it: a `bin` root is a JS
+/// `Uint8Array`, which JSON writes as an object keyed by index.
+fn view(model: &Model) -> Value {
+    let view = model.view();
+    match (model.index.get(&TsKey::from(model.root.val)), view) {
+        (Some(CrdtNode::Bin(_)), Value::Array(items)) => Value::Object(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect::<Map<_, _>>(),
+        ),
+        (_, view) => view,
+    }
+}
+
+/// The replays of `scenario` in `dir`, by name.
+fn load(dir: &Path, scenario: &str) -> Vec<Replay> {
+    let mut out: Vec<Replay> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| serde_json::from_str::<Value>(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .filter(|doc| doc["scenario"] == scenario)
+        .map(|doc| Replay::from_json(&doc))
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+fn assert_replays(replays: &[Replay]) {
+    let failures: Vec<String> = replays.iter().filter_map(|r| r.check().err()).collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} replays diverged:\n{}",
+        failures.len(),
+        replays.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn model_replay_pinned_corpus() {
+    let replays = load(Path::new(PINNED), "model_apply_replay");
+    assert_eq!(replays.len(), 140);
+    assert_replays(&replays);
+}
+
+// Ignored until the pinned-seed corpus is committed to
+// `tests/compat/replay_fixtures`; run it with `--include-ignored`.
+#[test]
+#[ignore = "needs the generated corpus in tests/compat/replay_fixtures"]
+fn model_replay_generated_corpus() {
+    let dir = match std::env::var_os("JSON_JOY_REPLAY_FIXTURES") {
+        Some(dir) if dir == "skip" => {
+            eprintln!("JSON_JOY_REPLAY_FIXTURES=skip; not replaying generated fixtures");
+            return;
+        }
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(GENERATED),
+    };
+    assert!(
+        dir.is_dir(),
+        "no replay fixture directory {}; generate it with \
+         tools/oracle-node/generate-replay-fixtures.cjs --count 200 --seed 7, \
+         or set JSON_JOY_REPLAY_FIXTURES=skip",
+        dir.display()
+    );
+    let replays = load(&dir, "model_replay_fuzz");
+    assert!(
+        !replays.is_empty(),
+        "no replay fixtures in {}",
+        dir.display()
+    );
+    assert_replays(&replays);
+}
+
+#[test]
+fn model_replay_reports_divergence() {
+    let mut replays = load(Path::new(PINNED), "model_apply_replay");
+    let mut replay = replays.swap_remove(0);
+    let name = replay.name.clone();
+    replay.view = Value::Null;
+    replay.effective += 1;
+    let err = replay.check().unwrap_err();
+    assert!(err.starts_with(&name), "{err}");
+    assert!(err.contains("view "), "{err}");
+    assert!(err.contains("patches took effect"), "{err}");
+    assert!(!err.contains("model binary"), "{err}");
+}
@@ -0,0 +1,89 @@
+//! `Model::stats`.
+//!
+//! Not an upstream port. Counts are checked against documents small enough
+//! to tally by hand.
+
+use json_joy::json_crdt::model::{Model, ModelApi, ModelStats};
+use json_joy::json_crdt_patch::clock::ts;
+use json_joy::json_patch::from_json_patch;
+use serde_json::json;
+
+#[test]
+fn model_stats_empty() {
+    let model = Model::new(0x10000);
+    let stats = model.stats();
+    assert_eq!(
+        stats,
+        ModelStats {
+            sid: 0x10000,
+            time: model.clock.time,
+            memory_estimate: stats.memory_estimate,
+            ..Default::default()
+        }
+    );
+    assert!(stats.memory_estimate > 0);
+    assert_eq!(
+        stats.to_json(),
+        json!({
+            "sid": 0x10000,
+            "time": model.clock.time,
+            "peers": [],
+            "tick": 0,
+            "node_count": 0,
+            "node_kinds": {},
+            "tombstones": 0,
+            "memory_estimate": stats.memory_estimate
+        })
+    );
+}
+
+#[test]
+fn model_stats_matrix() {
+    let mut model = Model::new(0x10000);
+    let ops = from_json_patch(&json!([
+        {"op": "replace", "path": "", "value": {"s": "hello world", "n": 1, "a": [1, 2]}}
+    ]))
+    .unwrap();
+    let patch = model.apply_json_patch(&ops).unwrap().unwrap();
+    let root = model.root.val;
+    let mut api = ModelApi::new(&mut model);
+    let text = api.find(root, &[json!("s")]).unwrap();
+    let arr = api.find(root, &[json!("a")]).unwrap();
+    api.str_del(text, 0, 6).unwrap();
+    api.arr_del(arr, 1, 1).unwrap();
+    assert_eq!(model.view(), json!({"s": "world", "n": 1, "a": [1]}));
+
+    let stats = model.stats();
+    assert_eq!(stats.node_count, 6);
+    assert_eq!(
+        stats.node_kinds.into_iter().collect::<Vec<_>>(),
+        [("arr", 1), ("con", 2), ("obj", 1), ("str", 1), ("val", 1)]
+    );
+    assert_eq!(stats.tombstones, 7);
+    assert_eq!(stats.tick, 3);
+    assert!(stats.peers.is_empty());
+
+    // A replica counts the author among its peers.
+    let mut replica = Model::new(0x20000);
+    replica.apply_patch(&patch);
+    let last = patch.get_id().unwrap().time + patch.span() - 1;
+    let stats = replica.stats();
+    assert_eq!(stats.peers, [ts(0x10000, last)]);
+    assert_eq!(stats.tick, 1);
+    // Without the deletes, the removed element's nodes are still indexed.
+    assert_eq!(stats.tombstones, 0);
+    assert_eq!(stats.node_count, 8);
+    assert_eq!(stats.to_json()["peers"], json!([[0x10000, last]]));
+}
+
+#[test]
+fn model_stats_memory_estimate_grows() {
+    let mut model = Model::new(0x10000);
+    ModelApi::new(&mut model).set(&json!({"s": ""})).unwrap();
+    let before = model.stats().memory_estimate;
+    let root = model.root.val;
+    let mut api = ModelApi::new(&mut model);
+    let text = api.find(root, &[json!("s")]).unwrap();
+    api.str_ins(text, 0, &"x".repeat(10_000)).unwrap();
+    assert!(model.stats().memory_estimate >= before + 10_000);
+}
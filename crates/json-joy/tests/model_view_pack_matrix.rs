@@ -0,0 +1,104 @@
+//! `Model::view_pack`, `view_cbor` and `view_msgpack`.
+//!
+//! Not an upstream port. The binary views are decoded back and compared
+//! with the JSON view.
+
+use json_joy::json_crdt::model::Model;
+use json_joy::json_crdt_patch::clock::tss;
+use json_joy::json_crdt_patch::patch_builder::PatchBuilder;
+use json_joy_json_pack::msgpack::MsgPackDecoder;
+use json_joy_json_pack::{decode_cbor_value, PackValue};
+use serde_json::{json, Value};
+
+/// An object holding every node type, with a deleted array element and
+/// an `undefined` key.
+fn doc() -> Model {
+    let mut model = Model::new(0x10000);
+    let mut b = PatchBuilder::new(0x10000, model.clock.time);
+    let obj = b.obj();
+    let text = b.str_node();
+    b.ins_str(text, text, "hello".into());
+    let bin = b.bin();
+    b.ins_bin(bin, bin, vec![0, 1, 255]);
+    let vec = b.vec();
+    let one = b.con_val(PackValue::Integer(1));
+    let yes = b.con_val(PackValue::Bool(true));
+    b.ins_vec(vec, vec![(0, one), (2, yes)]);
+    let arr = b.arr();
+    let null = b.con_val(PackValue::Null);
+    let gone = b.con_val(PackValue::Integer(2));
+    let half = b.con_val(PackValue::Float(0.5));
+    let first = b.ins_arr(arr, arr, vec![null, gone, half]);
+    b.del(arr, vec![tss(0x10000, first.time + 1, 1)]);
+    let undef = b.con_val(PackValue::Undefined);
+    let neg = b.con_val(PackValue::Integer(-7));
+    b.ins_obj(
+        obj,
+        vec![
+            ("s".into(), text),
+            ("b".into(), bin),
+            ("v".into(), vec),
+            ("a".into(), arr),
+            ("u".into(), undef),
+            ("n".into(), neg),
+        ],
+    );
+    b.root(obj);
+    model.apply_patch(&b.flush());
+    model
+}
+
+#[test]
+fn model_view_pack_matrix() {
+    let model = doc();
+    let expected = PackValue::Object(vec![
+        ("s".into(), PackValue::Str("hello".into())),
+        ("b".into(), PackValue::Bytes(vec![0, 1, 255])),
+        (
+            "v".into(),
+            PackValue::Array(vec![
+                PackValue::Integer(1),
+                PackValue::Null,
+                PackValue::Bool(true),
+            ]),
+        ),
+        (
+            "a".into(),
+            PackValue::Array(vec![PackValue::Null, PackValue::Float(0.5)]),
+        ),
+        ("n".into(), PackValue::Integer(-7)),
+    ]);
+    assert_eq!(model.view_pack(), expected);
+    assert_eq!(
+        model.view(),
+        json!({"s": "hello", "b": [0, 1, 255], "v": [1, null, true], "a": [null, 0.5], "n": -7})
+    );
+
+    assert_eq!(decode_cbor_value(&model.view_cbor()).unwrap(), expected);
+    assert_eq!(
+        MsgPackDecoder::new().decode(&model.view_msgpack()).unwrap(),
+        expected
+    );
+
+    // Without `bin` nodes the binary views carry the JSON view.
+    let mut model = Model::new(0x10000);
+    let ops = json_joy::json_patch::from_json_patch(&json!([
+        {"op": "replace", "path": "", "value": {"x": [1, "two", {"y": null}], "z": 2.5}}
+    ]))
+    .unwrap();
+    model.apply_json_patch(&ops).unwrap();
+    for pack in [
+        decode_cbor_value(&model.view_cbor()).unwrap(),
+        MsgPackDecoder::new().decode(&model.view_msgpack()).unwrap(),
+    ] {
+        assert_eq!(Value::from(pack), model.view());
+    }
+}
+
+#[test]
+fn model_view_pack_empty() {
+    let model = Model::new(0x10000);
+    assert_eq!(model.view_pack(), PackValue::Null);
+    assert_eq!(model.view_cbor(), [0xf6]);
+    assert_eq!(model.view_msgpack(), [0xc0]);
+}
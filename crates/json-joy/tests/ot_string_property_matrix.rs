@@ -0,0 +1,160 @@
+//! `ot_string` transform, compose and cursor-mapping properties over random
+//! documents.
+//!
+//! Not an upstream port: upstream tests `ot-string` with fixed cases only.
+//! Documents mix one-, two- and four-byte characters so that offsets are
+//! counted in characters, and deletes come in both counted and reversible
+//! forms.
+
+use json_joy::json_ot::ot_string::{
+    apply, compose, transform, transform_position, transform_range, Bias, StringComponent, StringOp,
+};
+use proptest::prelude::*;
+
+/// One step of an op before it is fitted to a document: a kind, a length
+/// and the text to insert.
+type Step = (u8, usize, String);
+
+fn doc() -> impl Strategy<Value = String> {
+    "[abé😀]{0,12}"
+}
+
+fn steps() -> impl Strategy<Value = Vec<Step>> {
+    prop::collection::vec((0u8..4, 1usize..5, "[xyé😀]{1,3}"), 0..6)
+}
+
+/// Fits `steps` to `doc`: retains and deletes are cut to the characters
+/// left, so any steps give a valid op.
+fn op(doc: &str, steps: &[Step]) -> StringOp {
+    let chars: Vec<char> = doc.chars().collect();
+    let mut at = 0;
+    let mut op = Vec::new();
+    for (kind, len, text) in steps {
+        let n = (*len).min(chars.len() - at);
+        match kind {
+            0 if n > 0 => op.push(StringComponent::Retain(n)),
+            1 if n > 0 => op.push(StringComponent::Delete(n)),
+            2 if n > 0 => op.push(StringComponent::DeleteStr(
+                chars[at..at + n].iter().collect(),
+            )),
+            3 => {
+                op.push(StringComponent::Insert(text.clone()));
+                continue;
+            }
+            _ => continue,
+        }
+        at += n;
+    }
+    op
+}
+
+fn config() -> ProptestConfig {
+    ProptestConfig {
+        cases: 4000,
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    }
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    /// TP1: both orders of two concurrent ops reach the same document.
+    #[test]
+    fn ot_string_transform_tp1(d in doc(), a in steps(), b in steps()) {
+        let (a, b) = (op(&d, &a), op(&d, &b));
+        let left = apply(&apply(&d, &a), &transform(&b, &a, false));
+        let right = apply(&apply(&d, &b), &transform(&a, &b, true));
+        prop_assert_eq!(left, right);
+    }
+
+    /// Transforming against a no-op changes nothing.
+    #[test]
+    fn ot_string_transform_identity(d in doc(), a in steps()) {
+        let a = op(&d, &a);
+        for left_wins in [true, false] {
+            let t = transform(&a, &Vec::new(), left_wins);
+            prop_assert_eq!(apply(&d, &t), apply(&d, &a));
+        }
+    }
+
+    /// A composed op does what its parts do in turn.
+    #[test]
+    fn ot_string_compose_apply(d in doc(), a in steps(), b in steps()) {
+        let a = op(&d, &a);
+        let mid = apply(&d, &a);
+        let b = op(&mid, &b);
+        prop_assert_eq!(apply(&d, &compose(&a, &b)), apply(&mid, &b));
+    }
+
+    /// `compose` is associative.
+    #[test]
+    fn ot_string_compose_associative(
+        d in doc(),
+        a in steps(),
+        b in steps(),
+        c in steps(),
+    ) {
+        let a = op(&d, &a);
+        let ad = apply(&d, &a);
+        let b = op(&ad, &b);
+        let bd = apply(&ad, &b);
+        let c = op(&bd, &c);
+        let left = compose(&compose(&a, &b), &c);
+        let right = compose(&a, &compose(&b, &c));
+        prop_assert_eq!(apply(&d, &left), apply(&bd, &c));
+        prop_assert_eq!(left, right);
+    }
+
+    /// A position before a kept character still points at it, and
+    /// selections stay ordered and inside the result.
+    #[test]
+    fn ot_string_transform_position(d in doc(), a in steps(), pos in 0usize..13) {
+        let a = op(&d, &a);
+        let out = apply(&d, &a);
+        let len = out.chars().count();
+        let pos = pos.min(d.chars().count());
+        let kept = kept_chars(&d, &a);
+        for bias in [Bias::Before, Bias::After] {
+            let at = transform_position(pos, &a, bias);
+            prop_assert!(at <= len);
+            if let Some(Some(c)) = kept.get(pos) {
+                if bias == Bias::After || !inserted_at(&a, pos) {
+                    prop_assert_eq!(out.chars().nth(at), Some(*c));
+                }
+            }
+        }
+        let r = transform_range(0..pos, &a);
+        prop_assert!(r.start <= r.end && r.end <= len);
+        prop_assert_eq!(transform_range(pos..pos, &a), {
+            let at = transform_position(pos, &a, Bias::Before);
+            at..at
+        });
+    }
+}
+
+/// The characters of `doc`, with `None` for those `op` deletes.
+fn kept_chars(doc: &str, op: &StringOp) -> Vec<Option<char>> {
+    let mut chars: Vec<Option<char>> = doc.chars().map(Some).collect();
+    let mut at = 0;
+    for comp in op {
+        if matches!(
+            comp,
+            StringComponent::Delete(_) | StringComponent::DeleteStr(_)
+        ) {
+            chars[at..at + comp.src_len()].fill(None);
+        }
+        at += comp.src_len();
+    }
+    chars
+}
+
+/// Whether `op` inserts text at source offset `pos`.
+fn inserted_at(op: &StringOp, pos: usize) -> bool {
+    let mut at = 0;
+    op.iter().any(|comp| {
+        let hit = at == pos && matches!(comp, StringComponent::Insert(_));
+        at += comp.src_len();
+        hit
+    })
+}
@@ -0,0 +1,143 @@
+//! v1 and v2 patch batch framing in `json_crdt_patch::codec::batch`.
+//!
+//! Not an upstream port: upstream has no batch wire format. Compressed
+//! cases run when the `deflate` and `zstd` features are enabled.
+
+use json_joy::json_crdt::model::Model;
+use json_joy::json_crdt_patch::codec::batch::*;
+use json_joy::json_crdt_patch::patch::Patch;
+use json_joy::json_patch::from_json_patch;
+use serde_json::json;
+
+fn patches() -> Vec<Vec<u8>> {
+    let mut model = Model::new(0x10000);
+    let mut out = Vec::new();
+    for ops in [
+        json!([{"op": "add", "path": "", "value": {"text": "hello", "list": []}}]),
+        json!([{"op": "add", "path": "/list/-", "value": "x".repeat(200)}]),
+        json!([{"op": "add", "path": "/list/-", "value": "x".repeat(200)}]),
+    ] {
+        let ops = from_json_patch(&ops).unwrap();
+        let patch = model.apply_json_patch(&ops).unwrap().unwrap();
+        out.push(patch.to_binary());
+    }
+    out
+}
+
+fn refs(patches: &[Vec<u8>]) -> Vec<&[u8]> {
+    patches.iter().map(Vec::as_slice).collect()
+}
+
+#[test]
+fn patch_batch_v1_matrix() {
+    let bytes = encode_batch_v1(&[b"ab", b"", b"c"]);
+    assert_eq!(
+        bytes,
+        [2, 0, 0, 0, b'a', b'b', 0, 0, 0, 0, 1, 0, 0, 0, b'c']
+    );
+    assert_eq!(decode_batch(&bytes).unwrap(), [&b"ab"[..], b"", b"c"]);
+    assert!(decode_batch(&[]).unwrap().is_empty());
+    for bad in [&bytes[..bytes.len() - 1], &bytes[..3]] {
+        assert_eq!(decode_batch(bad), Err(BatchError::Truncated));
+    }
+}
+
+#[test]
+fn patch_batch_v2_matrix() {
+    let bytes = encode_batch_v2(&[b"ab", b"", &[7; 200]], BatchCompression::None).unwrap();
+    let mut expected = vec![b'j', b'b', b'2', 0xff, 0, 2, b'a', b'b', 0, 0xc8, 0x01];
+    expected.extend([7; 200]);
+    assert_eq!(bytes, expected);
+    assert_eq!(
+        decode_batch(&bytes).unwrap(),
+        [b"ab".to_vec(), vec![], vec![7; 200]]
+    );
+    assert_eq!(
+        encode_batch_v2(&[], BatchCompression::None).unwrap(),
+        BATCH_V2_MAGIC
+            .iter()
+            .copied()
+            .chain([0])
+            .collect::<Vec<_>>()
+    );
+
+    for (bytes, err) in [
+        (&b"jb2\xff"[..], BatchError::Truncated),
+        (b"jb2\xff\x00\x03ab", BatchError::Truncated),
+        (b"jb2\xff\x00\x80", BatchError::Truncated),
+        (b"jb2\xff\x07", BatchError::UnknownCompression(7)),
+    ] {
+        assert_eq!(decode_batch(bytes), Err(err), "{bytes:?}");
+    }
+    assert_eq!(
+        decode_batch_limited(&bytes, 10),
+        Err(BatchError::TooLarge(10))
+    );
+    assert_eq!(decode_batch_limited(&bytes, 206).unwrap().len(), 3);
+
+    for (name, compression) in [
+        ("none", BatchCompression::None),
+        ("deflate", BatchCompression::Deflate),
+        ("zstd", BatchCompression::Zstd),
+    ] {
+        assert_eq!(BatchCompression::from_name(name), Some(compression));
+        assert_eq!(compression.name(), name);
+    }
+    assert_eq!(BatchCompression::from_name("gzip"), None);
+}
+
+#[test]
+fn patch_batch_compression_matrix() {
+    let patches = patches();
+    let plain = encode_batch_v2(&refs(&patches), BatchCompression::None).unwrap();
+    for compression in [BatchCompression::Deflate, BatchCompression::Zstd] {
+        let encoded = encode_batch_v2(&refs(&patches), compression);
+        if !compression.is_supported() {
+            assert_eq!(encoded, Err(BatchError::Unsupported(compression)));
+            let mut bytes = plain.clone();
+            bytes[4] = compression as u8;
+            assert_eq!(
+                decode_batch(&bytes),
+                Err(BatchError::Unsupported(compression))
+            );
+            continue;
+        }
+        let bytes = encoded.unwrap();
+        assert_eq!(
+            (&bytes[..4], bytes[4]),
+            (&BATCH_V2_MAGIC[..], compression as u8)
+        );
+        assert!(bytes.len() < plain.len(), "{compression:?}");
+        assert_eq!(decode_batch(&bytes).unwrap(), patches);
+
+        // The limit stops decompression early.
+        let body = plain.len() - 5;
+        assert_eq!(decode_batch_limited(&bytes, body).unwrap(), patches);
+        assert_eq!(
+            decode_batch_limited(&bytes, body - 1),
+            Err(BatchError::TooLarge(body - 1))
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt.truncate(bytes.len() / 2);
+        assert!(decode_batch(&corrupt).is_err(), "{compression:?}");
+    }
+}
+
+#[test]
+fn patch_batch_replays_on_a_replica() {
+    let patches = patches();
+    let mut replica = Model::new(0x20000);
+    for bytes in [
+        encode_batch_v1(&refs(&patches)),
+        encode_batch_v2(&refs(&patches), BatchCompression::None).unwrap(),
+    ] {
+        for patch in decode_batch(&bytes).unwrap() {
+            replica.apply_patch(&Patch::from_binary(&patch).unwrap());
+        }
+    }
+    assert_eq!(
+        replica.view(),
+        json!({"text": "hello", "list": ["x".repeat(200), "x".repeat(200)]})
+    );
+}
@@ -0,0 +1,102 @@
+//! `Patch::describe`.
+//!
+//! Not an upstream port. Summaries must survive a binary round trip and
+//! agree with the patch's own id and span.
+
+use json_joy::json_crdt_patch::clock::{ts, tss};
+use json_joy::json_crdt_patch::constants::ORIGIN;
+use json_joy::json_crdt_patch::patch::Patch;
+use json_joy::json_crdt_patch::patch_builder::PatchBuilder;
+use json_joy_json_pack::PackValue;
+use serde_json::json;
+
+#[test]
+fn patch_describe_empty() {
+    let summary = Patch::new().describe();
+    assert_eq!(summary.id, None);
+    assert_eq!(summary.op_count, 0);
+    assert!(summary.op_kinds.is_empty());
+    assert!(summary.targets.is_empty());
+    assert_eq!(
+        summary.to_json(),
+        json!({
+            "id": null,
+            "span": 0,
+            "op_count": 0,
+            "op_kinds": {},
+            "targets": [],
+            "inserted_text": 0,
+            "inserted_bytes": 0,
+            "inserted_items": 0,
+            "deleted": 0,
+            "has_meta": false
+        })
+    );
+}
+
+#[test]
+fn patch_describe_matrix() {
+    let mut b = PatchBuilder::new(0x10000, 5);
+    let obj = b.obj();
+    let text = b.str_node();
+    b.ins_str(text, text, "héllo 😀".into());
+    let bin = b.bin();
+    b.ins_bin(bin, bin, vec![1, 2, 3]);
+    let arr = b.arr();
+    let one = b.con_val(PackValue::Integer(1));
+    let two = b.con_val(PackValue::Integer(2));
+    let last = b.ins_arr(arr, arr, vec![one, two]);
+    b.upd_arr(arr, last, one);
+    b.ins_obj(
+        obj,
+        vec![
+            ("text".into(), text),
+            ("bin".into(), bin),
+            ("arr".into(), arr),
+        ],
+    );
+    b.del(text, vec![tss(0x10000, 7, 2), tss(0x10000, 12, 1)]);
+    b.del(ts(2, 9), vec![tss(2, 10, 4)]);
+    b.root(obj);
+    let mut patch = b.flush();
+    patch.meta = Some(PackValue::Str("m".into()));
+
+    let patch = Patch::from_binary(&patch.to_binary()).unwrap();
+    let summary = patch.describe();
+    assert_eq!(summary.id, Some(ts(0x10000, 5)));
+    assert_eq!(summary.id, patch.get_id());
+    assert_eq!(summary.span, patch.span());
+    assert_eq!(summary.op_count, patch.ops.len());
+    assert_eq!(summary.targets, [text, bin, arr, obj, ts(2, 9), ORIGIN]);
+    assert_eq!(summary.inserted_text, 8);
+    assert_eq!(summary.inserted_bytes, 3);
+    assert_eq!(summary.inserted_items, 2);
+    assert_eq!(summary.deleted, 7);
+    assert!(summary.has_meta);
+    assert_eq!(
+        summary.to_json(),
+        json!({
+            "id": [0x10000, 5],
+            "span": patch.span(),
+            "op_count": 14,
+            "op_kinds": {
+                "new_obj": 1, "new_str": 1, "new_bin": 1, "new_arr": 1,
+                "new_con": 2, "ins_str": 1, "ins_bin": 1, "ins_arr": 1,
+                "upd_arr": 1, "ins_obj": 1, "del": 2, "ins_val": 1
+            },
+            "targets": [
+                [text.sid, text.time],
+                [bin.sid, bin.time],
+                [arr.sid, arr.time],
+                [obj.sid, obj.time],
+                [2, 9],
+                [0, 0]
+            ],
+            "inserted_text": 8,
+            "inserted_bytes": 3,
+            "inserted_items": 2,
+            "deleted": 7,
+            "has_meta": true
+        })
+    );
+}
@@ -0,0 +1,167 @@
+//! `Patch` compact and verbose JSON conversions.
+//!
+//! Not an upstream port: upstream calls the codecs directly. Checked against
+//! the `patch_alt_codecs` fixtures in both directions.
+
+use json_joy::json_crdt_patch::patch::{Patch, PatchJsonError};
+use json_joy::json_crdt_patch::patch_builder::PatchBuilder;
+use json_joy_json_pack::PackValue;
+use serde_json::{json, Value};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/compat/fixtures");
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn alt_codec_fixtures() -> Vec<(String, Value)> {
+    let mut out: Vec<(String, Value)> = std::fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with("patch_alt_codecs_")
+        })
+        .map(|path| {
+            let text = std::fs::read_to_string(&path).unwrap();
+            (
+                path.display().to_string(),
+                serde_json::from_str(&text).unwrap(),
+            )
+        })
+        .collect();
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+#[test]
+fn patch_json_fixture_matrix() {
+    let fixtures = alt_codec_fixtures();
+    assert_eq!(fixtures.len(), 44);
+    for (name, fixture) in fixtures {
+        let binary = decode_hex(fixture["input"]["patch_binary_hex"].as_str().unwrap());
+        let patch = Patch::from_binary(&binary).unwrap();
+        let compact = &fixture["expected"]["compact_json"];
+        let verbose = &fixture["expected"]["verbose_json"];
+        assert_eq!(&patch.to_compact_json().unwrap(), compact, "{name}");
+        assert_eq!(&patch.to_verbose_json().unwrap(), verbose, "{name}");
+        let from_compact = Patch::from_compact_json(compact).unwrap();
+        let from_verbose = Patch::from_verbose_json(verbose).unwrap();
+        assert_eq!(from_compact.to_binary(), binary, "{name}");
+        assert_eq!(from_verbose.to_binary(), binary, "{name}");
+    }
+}
+
+#[test]
+fn patch_json_meta_and_server_ids() {
+    // Session 1 is the server: compact writes its ID as a bare time.
+    let mut b = PatchBuilder::new(1, 3);
+    let s = b.str_node();
+    b.ins_str(s, s, "hi".into());
+    b.root(s);
+    let mut patch = b.flush();
+    patch.meta = Some(PackValue::Str("note".into()));
+    let compact = patch.to_compact_json().unwrap();
+    assert_eq!(compact[0], json!([3, "note"]));
+    let verbose = patch.to_verbose_json().unwrap();
+    assert_eq!(verbose["id"], json!([1, 3]));
+    assert_eq!(verbose["meta"], json!("note"));
+    assert_eq!(Patch::from_compact_json(&compact).unwrap(), patch);
+    assert_eq!(Patch::from_verbose_json(&verbose).unwrap(), patch);
+}
+
+#[test]
+fn patch_json_error_matrix() {
+    assert_eq!(Patch::new().to_compact_json(), Err(PatchJsonError::Empty));
+    assert_eq!(Patch::new().to_verbose_json(), Err(PatchJsonError::Empty));
+    let compact: &[(Value, PatchJsonError)] = &[
+        (json!({}), PatchJsonError::InvalidPatch),
+        (json!([]), PatchJsonError::InvalidPatch),
+        (json!([1]), PatchJsonError::InvalidPatch),
+        (json!([[]]), PatchJsonError::InvalidId),
+        (json!([["x"]]), PatchJsonError::InvalidId),
+        (json!([[[1]]]), PatchJsonError::InvalidId),
+    ];
+    for (data, err) in compact {
+        assert_eq!(Patch::from_compact_json(data), Err(*err), "{data}");
+    }
+    let verbose: &[(Value, PatchJsonError)] = &[
+        (json!([]), PatchJsonError::InvalidPatch),
+        (json!({"ops": []}), PatchJsonError::InvalidId),
+        (json!({"id": "x", "ops": []}), PatchJsonError::InvalidId),
+    ];
+    for (data, err) in verbose {
+        assert_eq!(Patch::from_verbose_json(data), Err(*err), "{data}");
+    }
+    // Malformed operations fail with their index instead of being skipped
+    // or read with defaults.
+    let compact_ops: &[Value] = &[
+        json!(5),
+        json!([]),
+        json!([99]),
+        json!([300]),
+        json!([2, 1]),
+        json!([0, 1, false]),
+        json!([9, 1]),
+        json!([9, "x", 2]),
+        json!([9, [1, 2, 3], 2]),
+        json!([10, 1, [["k"]]]),
+        json!([10, 1, [[1, 2]]]),
+        json!([11, 1, [[256, 2]]]),
+        json!([12, 1, 1, 5]),
+        json!([13, 1, 1, "*"]),
+        json!([14, 1, 1, [-1]]),
+        json!([15, 1, 1]),
+        json!([16, 1, [[1]]]),
+        json!([17, "x"]),
+    ];
+    for op in compact_ops {
+        let data = json!([[[1, 2]], [2], op]);
+        assert_eq!(
+            Patch::from_compact_json(&data),
+            Err(PatchJsonError::InvalidOp(1)),
+            "{data}"
+        );
+    }
+    let verbose_ops: &[Value] = &[
+        json!([]),
+        json!({}),
+        json!({"op": "bogus"}),
+        json!({"op": "new_con", "timestamp": 1}),
+        json!({"op": "new_con", "timestamp": true, "value": "x"}),
+        json!({"op": "ins_val", "obj": [1, 2]}),
+        json!({"op": "ins_obj", "obj": [1, 2], "value": [["k", "v"]]}),
+        json!({"op": "ins_vec", "obj": [1, 2], "value": [["0", [1, 2]]]}),
+        json!({"op": "ins_str", "obj": [1, 2], "after": 1.5, "value": "a"}),
+        json!({"op": "ins_bin", "obj": [1, 2], "value": "*"}),
+        json!({"op": "ins_arr", "obj": [1, 2], "values": [[1]]}),
+        json!({"op": "upd_arr", "obj": [1, 2], "value": [1, 3]}),
+        json!({"op": "del", "obj": [1, 2], "what": [[1, 2]]}),
+        json!({"op": "nop", "len": -1}),
+    ];
+    for op in verbose_ops {
+        let data = json!({"id": [1, 2], "ops": [{"op": "new_obj"}, op]});
+        assert_eq!(
+            Patch::from_verbose_json(&data),
+            Err(PatchJsonError::InvalidOp(1)),
+            "{data}"
+        );
+    }
+    assert_eq!(
+        Patch::from_verbose_json(&json!({"id": [1, 2]})),
+        Err(PatchJsonError::InvalidPatch)
+    );
+    assert_eq!(
+        Patch::from_compact_json(&json!([[[1, -2]]])),
+        Err(PatchJsonError::InvalidId)
+    );
+
+    // A header alone is an empty patch.
+    assert!(Patch::from_compact_json(&json!([[[1, 2]]]))
+        .unwrap()
+        .ops
+        .is_empty());
+}
@@ -10,7 +10,7 @@
 mod deep_equal;
 
 // Re-export from the standalone json-equal crate for backward compatibility.
-pub use json_joy_json_equal::deep_equal;
+pub use json_joy_json_equal::{deep_equal, deep_equal_with, DeepEqualOptions};
 
 pub use deep_equal::deep_equal_binary;
 
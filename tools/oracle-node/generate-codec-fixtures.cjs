@@ -0,0 +1,130 @@
+const fs = require('node:fs');
+const path = require('node:path');
+
+const ROOT = path.resolve(__dirname, '..', '..');
+const OUT_DIR = path.join(ROOT, 'tests', 'compat', 'codec_fixtures');
+const FIXTURE_VERSION = 1;
+const PACK = '@jsonjoy.com/json-pack/lib';
+const PACK_VERSION = require('@jsonjoy.com/json-pack/package.json').version;
+
+const {CborEncoder} = require(`${PACK}/cbor/CborEncoder.js`);
+const {CborEncoderFast} = require(`${PACK}/cbor/CborEncoderFast.js`);
+const {CborEncoderStable} = require(`${PACK}/cbor/CborEncoderStable.js`);
+const {CborEncoderDag} = require(`${PACK}/cbor/CborEncoderDag.js`);
+const {MsgPackEncoder} = require(`${PACK}/msgpack/MsgPackEncoder.js`);
+const {MsgPackEncoderFast} = require(`${PACK}/msgpack/MsgPackEncoderFast.js`);
+const {MsgPackEncoderStable} = require(`${PACK}/msgpack/MsgPackEncoderStable.js`);
+const {JsonEncoder} = require(`${PACK}/json/JsonEncoder.js`);
+const {JsonEncoderStable} = require(`${PACK}/json/JsonEncoderStable.js`);
+const {JsonEncoderDag} = require(`${PACK}/json/JsonEncoderDag.js`);
+
+// The JSON encoders take a writer; borrow the class the others construct.
+const Writer = new CborEncoder().writer.constructor;
+
+// Upstream class name -> [format, factory], matching the Rust ports listed
+// in `json_joy_json_pack::conformance::CodecFormat::encoders`.
+const ENCODERS = {
+  CborEncoder: ['cbor', () => new CborEncoder()],
+  CborEncoderFast: ['cbor', () => new CborEncoderFast()],
+  CborEncoderStable: ['cbor', () => new CborEncoderStable()],
+  CborEncoderDag: ['cbor', () => new CborEncoderDag()],
+  MsgPackEncoder: ['msgpack', () => new MsgPackEncoder()],
+  MsgPackEncoderFast: ['msgpack', () => new MsgPackEncoderFast()],
+  MsgPackEncoderStable: ['msgpack', () => new MsgPackEncoderStable()],
+  JsonEncoder: ['json', () => new JsonEncoder(new Writer())],
+  JsonEncoderStable: ['json', () => new JsonEncoderStable(new Writer())],
+  JsonEncoderDag: ['json', () => new JsonEncoderDag(new Writer())],
+};
+
+function str(len, ch) {
+  return ch.repeat(len);
+}
+
+function obj(n) {
+  const out = {};
+  for (let i = n - 1; i >= 0; i--) out[`k${i}`] = i;
+  return out;
+}
+
+// Values around every length and width boundary of the three formats.
+const VALUES = {
+  null: null,
+  true: true,
+  false: false,
+  uint_0: 0,
+  uint_23: 23,
+  uint_24: 24,
+  uint_127: 127,
+  uint_128: 128,
+  uint_255: 255,
+  uint_256: 256,
+  uint_65535: 65535,
+  uint_65536: 65536,
+  uint_4294967296: 4294967296,
+  uint_max_safe: Number.MAX_SAFE_INTEGER,
+  neg_1: -1,
+  neg_24: -24,
+  neg_25: -25,
+  neg_32: -32,
+  neg_33: -33,
+  neg_129: -129,
+  neg_32769: -32769,
+  neg_min_safe: Number.MIN_SAFE_INTEGER,
+  float_half: 1.5,
+  float_tenth: 0.1,
+  float_neg: -123.456,
+  float_big: 1e21,
+  float_tiny: 5e-324,
+  str_empty: '',
+  str_23: str(23, 'a'),
+  str_24: str(24, 'a'),
+  str_31: str(31, 'a'),
+  str_32: str(32, 'a'),
+  str_255: str(255, 'a'),
+  str_256: str(256, 'a'),
+  str_utf8: 'Zoë ✓ 😀',
+  str_escape: 'line\n"quote"\\\t\u0001',
+  array_empty: [],
+  array_15: Array.from({length: 15}, (_, i) => i),
+  array_16: Array.from({length: 16}, (_, i) => i),
+  array_24: Array.from({length: 24}, (_, i) => i),
+  object_empty: {},
+  object_15: obj(15),
+  object_16: obj(16),
+  object_nested: {b: [1, {c: null, a: 'x'}], a: {z: true, y: [0.5]}},
+};
+
+function hex(bytes) {
+  return Buffer.from(bytes).toString('hex');
+}
+
+function main() {
+  fs.mkdirSync(OUT_DIR, {recursive: true});
+  for (const file of fs.readdirSync(OUT_DIR)) {
+    if (file.endsWith('.json')) fs.rmSync(path.join(OUT_DIR, file));
+  }
+  let count = 0;
+  for (const [encoderName, [format, factory]] of Object.entries(ENCODERS)) {
+    const suffix = encoderName.split('Encoder')[1].toLowerCase();
+    for (const [label, json] of Object.entries(VALUES)) {
+      const name = `codec_${format}_${suffix ? suffix + '_' : ''}${label}_v1`;
+      const fixture = {
+        fixture_version: FIXTURE_VERSION,
+        name,
+        scenario: `codec_${format}`,
+        input: {encoder: encoderName, json},
+        expected: {hex: hex(factory().encode(json))},
+        meta: {
+          upstream_package: '@jsonjoy.com/json-pack',
+          upstream_version: PACK_VERSION,
+          generator: 'tools/oracle-node/generate-codec-fixtures.cjs',
+        },
+      };
+      fs.writeFileSync(path.join(OUT_DIR, `${name}.json`), JSON.stringify(fixture, null, 2) + '\n', 'utf8');
+      count++;
+    }
+  }
+  console.log(`wrote ${count} fixtures to ${OUT_DIR}`);
+}
+
+main();
@@ -0,0 +1,187 @@
+const fs = require('node:fs');
+const path = require('node:path');
+const {Model} = require('json-joy/lib/json-crdt/index.js');
+
+// Randomised patch replay fixtures for
+// `crates/json-joy/tests/model_replay_fuzz_matrix.rs`.
+//
+// Each fixture forks a random document to a few peers, lets every peer make
+// random edits, and replays all their patches into the base document in a
+// random order with duplicates, recording the view and binary upstream ends
+// up with. Fixtures use the `model_apply_replay` layout under the
+// `model_replay_fuzz` scenario.
+//
+// Usage: node generate-replay-fixtures.cjs [--count N] [--seed S] [--out DIR]
+
+const ROOT = path.resolve(__dirname, '..', '..');
+const FIXTURE_VERSION = 1;
+const UPSTREAM_VERSION = require('json-joy/package.json').version;
+
+function parseArgs(argv) {
+  const args = {count: 60, seed: 0x5eed, out: path.join(ROOT, 'tests', 'compat', 'replay_fixtures')};
+  for (let i = 0; i < argv.length; i += 2) {
+    const [flag, value] = [argv[i], argv[i + 1]];
+    if (value === undefined) throw new Error(`missing value for ${flag}`);
+    if (flag === '--count') args.count = Number(value);
+    else if (flag === '--seed') args.seed = Number(value);
+    else if (flag === '--out') args.out = path.resolve(value);
+    else throw new Error(`unknown flag ${flag}`);
+  }
+  return args;
+}
+
+function hex(bytes) {
+  return Buffer.from(bytes).toString('hex');
+}
+
+function mulberry32(seed) {
+  let t = seed >>> 0;
+  return function rng() {
+    t += 0x6D2B79F5;
+    let r = Math.imul(t ^ (t >>> 15), 1 | t);
+    r ^= r + Math.imul(r ^ (r >>> 7), 61 | r);
+    return ((r ^ (r >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+function randInt(rng, maxExclusive) {
+  return Math.floor(rng() * maxExclusive);
+}
+
+function randString(rng, minLen, maxLen) {
+  const alphabet = 'abcdefghijklmnopqrstuvwxyzé😀';
+  const chars = Array.from(alphabet);
+  const len = minLen + randInt(rng, maxLen - minLen + 1);
+  let out = '';
+  for (let i = 0; i < len; i++) out += chars[randInt(rng, chars.length)];
+  return out;
+}
+
+function randJson(rng, depth) {
+  const t = randInt(rng, depth > 0 ? 7 : 4);
+  if (t === 0) return null;
+  if (t === 1) return rng() < 0.5;
+  if (t === 2) return randInt(rng, 2000) - 1000;
+  if (t === 3 || t === 4) return randString(rng, 0, 8);
+  if (t === 5) return Array.from({length: randInt(rng, 4)}, () => randJson(rng, depth - 1));
+  const obj = {};
+  for (let i = randInt(rng, 4); i > 0; i--) obj[randString(rng, 1, 3)] = randJson(rng, depth - 1);
+  return obj;
+}
+
+// A random edit of a top-level object: set, delete, or splice a string key.
+function mutate(rng, view) {
+  const next = JSON.parse(JSON.stringify(view));
+  const keys = Object.keys(next);
+  const key = keys.length && rng() < 0.7 ? keys[randInt(rng, keys.length)] : randString(rng, 1, 3);
+  const old = next[key];
+  const t = randInt(rng, 4);
+  if (typeof old === 'string' && t < 2) {
+    const chars = Array.from(old);
+    const at = randInt(rng, chars.length + 1);
+    chars.splice(at, randInt(rng, 3), ...Array.from(randString(rng, 0, 3)));
+    next[key] = chars.join('');
+  } else if (t === 2 && keys.length) {
+    delete next[key];
+  } else {
+    next[key] = randJson(rng, 2);
+  }
+  return next;
+}
+
+// Every patch at least once, in random order, plus a few duplicates. Half of
+// the patterns keep each peer's own patches in order.
+function replayPattern(rng, peerPatches) {
+  const queues = peerPatches.map((ids) => ids.slice());
+  const causal = rng() < 0.5;
+  const pattern = [];
+  while (queues.some((q) => q.length)) {
+    const live = queues.filter((q) => q.length);
+    const q = live[randInt(rng, live.length)];
+    pattern.push(causal ? q.shift() : q.splice(randInt(rng, q.length), 1)[0]);
+  }
+  for (let i = randInt(rng, 3); i > 0; i--) {
+    pattern.splice(randInt(rng, pattern.length + 1), 0, pattern[randInt(rng, pattern.length)]);
+  }
+  return {pattern, causal};
+}
+
+function buildFixture(index, seed) {
+  const rng = mulberry32(seed);
+  const sid = 90000 + index * 16;
+  const base = Model.create(undefined, sid);
+  const data = {};
+  for (let i = 1 + randInt(rng, 4); i > 0; i--) data[randString(rng, 1, 3)] = randJson(rng, 2);
+  base.api.set(data);
+  base.api.flush();
+  const baseBin = base.toBinary();
+
+  const patches = [];
+  const peerPatches = [];
+  const peers = 2 + randInt(rng, 3);
+  for (let p = 1; p <= peers; p++) {
+    const peer = base.fork(sid + p);
+    const ids = [];
+    for (let e = 1 + randInt(rng, 3); e > 0; e--) {
+      const patch = peer.api.diff(mutate(rng, peer.view()));
+      if (!patch) continue;
+      peer.applyPatch(patch);
+      ids.push(patches.length);
+      patches.push(patch);
+    }
+    peerPatches.push(ids);
+  }
+  const {pattern, causal} = replayPattern(rng, peerPatches);
+
+  const model = Model.fromBinary(baseBin);
+  let effective = 0;
+  for (const idx of pattern) {
+    const before = hex(model.toBinary());
+    model.applyPatch(patches[idx]);
+    if (hex(model.toBinary()) !== before) effective++;
+  }
+  const name = `model_replay_fuzz_${String(index + 1).padStart(3, '0')}_v1`;
+  return {
+    fixture_version: FIXTURE_VERSION,
+    name,
+    scenario: 'model_replay_fuzz',
+    input: {
+      base_model_binary_hex: hex(baseBin),
+      patches_binary_hex: patches.map((p) => hex(p.toBinary())),
+      replay_pattern: pattern,
+      label: `${peers}_peers_${causal ? 'causal' : 'shuffled'}`,
+      seed,
+    },
+    expected: {
+      view_json: model.view() === undefined ? null : model.view(),
+      model_binary_hex: hex(model.toBinary()),
+      applied_patch_count_effective: effective,
+      clock_observed: {
+        patch_ids: patches.map((p) => {
+          const id = p.getId();
+          return id ? [id.sid, id.time] : null;
+        }),
+      },
+    },
+    meta: {
+      upstream_package: 'json-joy',
+      upstream_version: UPSTREAM_VERSION,
+      generator: 'tools/oracle-node/generate-replay-fixtures.cjs',
+    },
+  };
+}
+
+function main() {
+  const args = parseArgs(process.argv.slice(2));
+  fs.mkdirSync(args.out, {recursive: true});
+  for (const file of fs.readdirSync(args.out)) {
+    if (file.endsWith('.json')) fs.rmSync(path.join(args.out, file));
+  }
+  for (let i = 0; i < args.count; i++) {
+    const fixture = buildFixture(i, (args.seed + i) >>> 0);
+    fs.writeFileSync(path.join(args.out, `${fixture.name}.json`), JSON.stringify(fixture, null, 2) + '\n', 'utf8');
+  }
+  console.log(`wrote ${args.count} fixtures to ${args.out}`);
+}
+
+main();